@@ -0,0 +1,85 @@
+#[macro_use]
+extern crate criterion;
+
+extern crate halo2;
+use halo2::arithmetic::BatchInvert;
+use halo2::pasta::Fp;
+use halo2::plonk::Assigned;
+use halo2::poly::ColumnMatrix;
+
+use criterion::{BenchmarkId, Criterion};
+
+const COLUMNS: usize = 16;
+
+/// Returns the value assigned to a cell, with a denominator in every other row.
+fn value(column: usize, row: usize) -> Assigned<Fp> {
+    let numerator = Fp::from((column * row) as u64 + 1);
+    if row % 2 == 0 {
+        Assigned::Trivial(numerator)
+    } else {
+        Assigned::Rational(numerator, Fp::from(row as u64))
+    }
+}
+
+/// Assigns every cell row by row (as circuits do), and evaluates the columns, storing
+/// each column in its own allocation.
+fn separate_columns(rows: usize) -> Vec<Vec<Fp>> {
+    let mut columns = vec![vec![Assigned::Zero; rows]; COLUMNS];
+    for row in 0..rows {
+        for (column, values) in columns.iter_mut().enumerate() {
+            values[row] = value(column, row);
+        }
+    }
+
+    let mut denominators: Vec<Vec<_>> = columns
+        .iter()
+        .map(|values| values.iter().map(|v| v.denominator()).collect())
+        .collect();
+    denominators
+        .iter_mut()
+        .flat_map(|d| d.iter_mut().filter_map(|d| d.as_mut()))
+        .batch_invert();
+
+    columns
+        .iter()
+        .zip(denominators.into_iter())
+        .map(|(values, inv_denoms)| {
+            values
+                .iter()
+                .zip(inv_denoms.into_iter())
+                .map(|(v, inv_den)| v.numerator() * inv_den.unwrap_or_else(Fp::one))
+                .collect()
+        })
+        .collect()
+}
+
+/// Assigns every cell row by row (as circuits do), and evaluates the columns, storing
+/// the columns in a [`ColumnMatrix`].
+fn column_matrix(rows: usize) -> ColumnMatrix<Fp> {
+    let mut matrix = ColumnMatrix::new(COLUMNS, rows, Assigned::Zero);
+    for row in 0..rows {
+        for column in 0..COLUMNS {
+            matrix.column_mut(column).unwrap()[row] = value(column, row);
+        }
+    }
+    matrix.batch_invert()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("advice-storage");
+    for k in [10, 14].iter() {
+        let rows = 1 << k;
+        group.bench_with_input(
+            BenchmarkId::new("separate-columns", k),
+            &rows,
+            |b, &rows| b.iter(|| separate_columns(rows)),
+        );
+        group.bench_with_input(BenchmarkId::new("column-matrix", k), &rows, |b, &rows| {
+            b.iter(|| column_matrix(rows))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
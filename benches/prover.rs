@@ -0,0 +1,284 @@
+#[macro_use]
+extern crate criterion;
+
+extern crate halo2;
+use halo2::circuit::{Layouter, SimpleFloorPlanner};
+use halo2::pasta::{EqAffine, Fp};
+use halo2::plonk::*;
+use halo2::poly::{commitment::Params, Rotation};
+use halo2::transcript::{Blake2bWrite, Challenge255};
+
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion};
+
+/// The sizes of the circuits that are benchmarked.
+const K: [u32; 3] = [8, 10, 12];
+
+/// The number of gates in a gate-heavy circuit.
+const GATES: usize = 8;
+
+/// The kind of constraint that dominates a benchmarked circuit.
+#[derive(Clone, Copy, Debug)]
+enum Shape {
+    /// Many gates of degree 4 on every row.
+    Gates,
+    /// A lookup of every advice cell into a fixed table.
+    Lookups,
+    /// Two copy constraints on every row.
+    Copies,
+}
+
+impl Shape {
+    const ALL: [Shape; 3] = [Shape::Gates, Shape::Lookups, Shape::Copies];
+
+    fn name(self) -> &'static str {
+        match self {
+            Shape::Gates => "gate-heavy",
+            Shape::Lookups => "lookup-heavy",
+            Shape::Copies => "copy-heavy",
+        }
+    }
+}
+
+/// The parts of the prover that are timed separately, each made up of one or more
+/// phases reported to a [`ProverObserver`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Subsystem {
+    Synthesis,
+    Commitments,
+    Vanishing,
+    Multiopen,
+}
+
+impl Subsystem {
+    const ALL: [Subsystem; 4] = [
+        Subsystem::Synthesis,
+        Subsystem::Commitments,
+        Subsystem::Vanishing,
+        Subsystem::Multiopen,
+    ];
+
+    fn of(phase: ProverPhase) -> Self {
+        match phase {
+            ProverPhase::WitnessSynthesis(_) => Subsystem::Synthesis,
+            ProverPhase::InstanceCommitment
+            | ProverPhase::AdviceCommitment(_)
+            | ProverPhase::LookupPermutation
+            | ProverPhase::PermutationProduct
+            | ProverPhase::LookupProduct => Subsystem::Commitments,
+            ProverPhase::Vanishing => Subsystem::Vanishing,
+            ProverPhase::Evaluation | ProverPhase::Multiopen => Subsystem::Multiopen,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Subsystem::Synthesis => "synthesis",
+            Subsystem::Commitments => "commitments",
+            Subsystem::Vanishing => "vanishing",
+            Subsystem::Multiopen => "multiopen",
+        }
+    }
+}
+
+/// Accumulates the time that the prover spends in the phases of one subsystem.
+struct Timer {
+    subsystem: Subsystem,
+    elapsed: Duration,
+}
+
+impl ProverObserver for Timer {
+    fn phase_finished(&mut self, phase: ProverPhase, report: &PhaseReport) {
+        if Subsystem::of(phase) == self.subsystem {
+            self.elapsed += report.elapsed;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ShapeConfig {
+    a: Column<Advice>,
+    b: Column<Advice>,
+    c: Column<Advice>,
+    q: Selector,
+    table: Option<Column<Fixed>>,
+    perm: Option<Permutation>,
+}
+
+/// A circuit that enforces `a * b = c` on each of its rows, with `b = 1`, along with
+/// the constraints of its shape.
+#[derive(Clone, Copy, Debug)]
+struct ShapeCircuit {
+    shape: Shape,
+    rows: usize,
+    witness: bool,
+}
+
+impl Circuit<Fp> for ShapeCircuit {
+    type Config = ShapeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        ShapeCircuit {
+            witness: false,
+            ..*self
+        }
+    }
+
+    fn configure(_: &mut ConstraintSystem<Fp>) -> ShapeConfig {
+        panic!("a ShapeCircuit can only be configured with Circuit::configure_from");
+    }
+
+    fn configure_from(&self, meta: &mut ConstraintSystem<Fp>) -> ShapeConfig {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let c = meta.advice_column();
+        let q = meta.selector();
+
+        let gates = match self.shape {
+            Shape::Gates => GATES,
+            _ => 1,
+        };
+        for i in 0..gates {
+            meta.create_gate("mul", |meta| {
+                let q = meta.query_selector(q);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let c = meta.query_advice(c, Rotation::cur());
+                vec![q * (a.clone() * b - c) * (a + Expression::Constant(Fp::from(i as u64)))]
+            });
+        }
+
+        let table = match self.shape {
+            Shape::Lookups => {
+                let table = meta.fixed_column();
+                for column in [a, b, c].iter().copied() {
+                    meta.lookup(|meta| {
+                        let q = meta.query_selector(q);
+                        let value = meta.query_advice(column, Rotation::cur());
+                        let table = meta.query_fixed(table, Rotation::cur());
+                        vec![(q * value, table)]
+                    });
+                }
+                Some(table)
+            }
+            _ => None,
+        };
+
+        let perm = match self.shape {
+            Shape::Copies => Some(meta.permutation(&[a.into(), b.into(), c.into()])),
+            _ => None,
+        };
+
+        ShapeConfig {
+            a,
+            b,
+            c,
+            q,
+            table,
+            perm,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: ShapeConfig,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        if let Some(table) = config.table {
+            let values: Vec<_> = (0..self.rows).map(|row| Fp::from(row as u64)).collect();
+            layouter.assign_region(
+                || "table",
+                |mut region| {
+                    region.assign_fixed_from_slice(|| "table", table, 0, &values)?;
+                    Ok(())
+                },
+            )?;
+        }
+
+        let known = |value: Fp| if self.witness { Some(value) } else { None };
+        let a: Vec<_> = (0..self.rows)
+            .map(|row| known(Fp::from(row as u64)))
+            .collect();
+        let b = vec![known(Fp::one()); self.rows];
+
+        layouter.assign_region(
+            || "rows",
+            |mut region| {
+                for row in 0..self.rows {
+                    config.q.enable(&mut region, row)?;
+                }
+                let a_cells = region.assign_advice_from_slice(|| "a", config.a, 0, &a)?;
+                let b_cells = region.assign_advice_from_slice(|| "b", config.b, 0, &b)?;
+                let c_cells = region.assign_advice_from_slice(|| "c", config.c, 0, &a)?;
+
+                if let Some(perm) = &config.perm {
+                    for (a, c) in a_cells.iter().zip(c_cells.iter()) {
+                        region.constrain_equal(perm, *a, *c)?;
+                    }
+                    for pair in b_cells.windows(2) {
+                        region.constrain_equal(perm, pair[0], pair[1])?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+fn bench_shape(shape: Shape, c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("prover-{}", shape.name()));
+    group.sample_size(10);
+
+    for k in K.iter().copied() {
+        let params: Params<EqAffine> = Params::new(k);
+        let circuit = ShapeCircuit {
+            shape,
+            rows: 1 << (k - 1),
+            witness: true,
+        };
+
+        let vk =
+            keygen_vk(&params, &circuit.without_witnesses()).expect("keygen_vk should not fail");
+        let pk = keygen_pk(&params, vk, &circuit.without_witnesses())
+            .expect("keygen_pk should not fail");
+
+        // Each subsystem is timed by the observer across whole proofs, so that the
+        // phases of the other subsystems are excluded from its measurements.
+        for subsystem in Subsystem::ALL.iter().copied() {
+            group.bench_with_input(BenchmarkId::new(subsystem.name(), k), &k, |b, _| {
+                b.iter_custom(|iters| {
+                    let mut timer = Timer {
+                        subsystem,
+                        elapsed: Duration::default(),
+                    };
+                    for _ in 0..iters {
+                        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+                        create_proof_with_observer(
+                            &params,
+                            &pk,
+                            &[circuit],
+                            &[&[]],
+                            &mut transcript,
+                            &mut timer,
+                        )
+                        .expect("proof generation should not fail");
+                    }
+                    timer.elapsed
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    for shape in Shape::ALL.iter().copied() {
+        bench_shape(shape, c);
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,389 @@
+//! Membership of a leaf in a Merkle tree with a public root.
+//!
+//! The prover knows a leaf, and the path from the leaf to the root: the sibling at
+//! each level, and whether the current node is the left or the right child. The
+//! circuit hashes its way up the path, and constrains the result to equal the root in
+//! the instance column.
+//!
+//! This crate does not ship a Poseidon chip, so this example uses a toy hash in its
+//! place, with the same `x^5` S-box that Poseidon uses over the Pasta fields:
+//!
+//! ```text
+//! H(l, r) = s_R, where s_0 = l and s_{i+1} = (s_i + r + c_i)^5.
+//! ```
+//!
+//! The toy hash has far too few rounds to be secure. A real circuit would replace
+//! `HashChip` with a Poseidon chip, and keep the rest of the circuit as it is.
+
+extern crate halo2;
+
+use std::marker::PhantomData;
+
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Chip, Layouter, SimpleFloorPlanner},
+    dev::{prove_and_verify, MockProver},
+    pasta::{EqAffine, Fp},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Permutation, Selector,
+    },
+    poly::Rotation,
+};
+
+/// The number of levels of the tree, which has `2^DEPTH` leaves.
+const DEPTH: usize = 4;
+
+/// The number of rounds of the toy hash.
+const ROUNDS: usize = 8;
+
+/// Returns the round constants of the toy hash.
+fn round_constants<F: FieldExt>() -> Vec<F> {
+    (0..ROUNDS as u64).map(|i| F::from_u64(i * i + 7)).collect()
+}
+
+/// Computes the toy hash of two nodes outside the circuit.
+fn hash<F: FieldExt>(left: F, right: F) -> F {
+    round_constants::<F>()
+        .into_iter()
+        .fold(left, |state, c| (state + right + c).pow_vartime(&[5]))
+}
+
+/// A node of the tree that has been assigned in the circuit.
+#[derive(Clone, Copy, Debug)]
+struct Node<F: FieldExt> {
+    cell: Cell,
+    value: Option<F>,
+}
+
+#[derive(Clone, Debug)]
+struct MerkleConfig {
+    /// The columns in which each level is laid out.
+    advice: [Column<Advice>; 5],
+    /// The column through which the root is exposed.
+    public: Column<Advice>,
+    round_constant: Column<Fixed>,
+    s_swap: Selector,
+    s_round: Selector,
+    s_root: Selector,
+    perm: Permutation,
+}
+
+/// A chip that hashes a node together with its sibling.
+struct HashChip<F: FieldExt> {
+    config: MerkleConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for HashChip<F> {
+    type Config = MerkleConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> HashChip<F> {
+    fn construct(config: MerkleConfig) -> Self {
+        HashChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> MerkleConfig {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let public = meta.advice_column();
+        let round_constant = meta.fixed_column();
+        let root = meta.instance_column();
+        let s_swap = meta.selector();
+        let s_round = meta.selector();
+        let s_root = meta.selector();
+        let perm = meta.permutation(&[
+            advice[0].into(),
+            advice[1].into(),
+            advice[3].into(),
+            advice[4].into(),
+            public.into(),
+        ]);
+
+        // Each level is laid out in a region of `ROUNDS + 2` rows:
+        //
+        // | row        | a0      | a1      | a2  | a3   | a4    |
+        // |------------|---------|---------|-----|------|-------|
+        // | 0          | node    | sibling | bit | left | right |
+        // | 1          | s_0     | right   |     |      |       |
+        // | ...        | ...     | right   |     |      |       |
+        // | ROUNDS + 1 | s_R     | right   |     |      |       |
+        //
+        // where `left` and `right` are copied into the first row of the hash.
+        meta.create_gate("swap", |meta| {
+            let s_swap = meta.query_selector(s_swap);
+            let node = meta.query_advice(advice[0], Rotation::cur());
+            let sibling = meta.query_advice(advice[1], Rotation::cur());
+            let bit = meta.query_advice(advice[2], Rotation::cur());
+            let left = meta.query_advice(advice[3], Rotation::cur());
+            let right = meta.query_advice(advice[4], Rotation::cur());
+
+            // If `bit` is 1, the node is the right child.
+            let one = Expression::Constant(F::one());
+            vec![
+                s_swap.clone() * bit.clone() * (one - bit.clone()),
+                s_swap.clone()
+                    * (left.clone()
+                        - node.clone()
+                        - bit.clone() * (sibling.clone() - node.clone())),
+                s_swap * (left + right - node - sibling),
+            ]
+        });
+
+        meta.create_gate("round", |meta| {
+            let s_round = meta.query_selector(s_round);
+            let state = meta.query_advice(advice[0], Rotation::cur());
+            let key = meta.query_advice(advice[1], Rotation::cur());
+            let next_state = meta.query_advice(advice[0], Rotation::next());
+            let next_key = meta.query_advice(advice[1], Rotation::next());
+            let c = meta.query_fixed(round_constant, Rotation::cur());
+
+            let x = state + key.clone() + c;
+            let x2 = x.clone() * x.clone();
+            vec![
+                s_round.clone() * (x2.clone() * x2 * x - next_state),
+                s_round * (next_key - key),
+            ]
+        });
+
+        meta.create_gate("root", |meta| {
+            let s_root = meta.query_selector(s_root);
+            let public = meta.query_advice(public, Rotation::cur());
+            let root = meta.query_instance(root, Rotation::cur());
+            vec![s_root * (public - root)]
+        });
+
+        MerkleConfig {
+            advice,
+            public,
+            round_constant,
+            s_swap,
+            s_round,
+            s_root,
+            perm,
+        }
+    }
+
+    /// Loads the leaf into the circuit.
+    fn load_leaf(&self, mut layouter: impl Layouter<F>, leaf: Option<F>) -> Result<Node<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load leaf",
+            |mut region| {
+                let cell = region.assign_advice_value(|| "leaf", config.advice[0], 0, leaf)?;
+                Ok(Node { cell, value: leaf })
+            },
+        )
+    }
+
+    /// Hashes `node` together with `sibling`, in the order given by `bit`, and returns
+    /// the parent node.
+    fn hash_level(
+        &self,
+        mut layouter: impl Layouter<F>,
+        node: Node<F>,
+        sibling: Option<F>,
+        bit: Option<bool>,
+    ) -> Result<Node<F>, Error> {
+        let config = self.config();
+        let constants = round_constants::<F>();
+
+        let (left, right) = match (node.value, sibling, bit) {
+            (Some(node), Some(sibling), Some(false)) => (Some(node), Some(sibling)),
+            (Some(node), Some(sibling), Some(true)) => (Some(sibling), Some(node)),
+            _ => (None, None),
+        };
+        let bit = bit.map(|bit| if bit { F::one() } else { F::zero() });
+
+        layouter.assign_region(
+            || "hash level",
+            |mut region| {
+                config.s_swap.enable(&mut region, 0)?;
+                region.assign_advice_then_copy(
+                    || "node",
+                    config.advice[0],
+                    0,
+                    &config.perm,
+                    node.cell,
+                    node.value,
+                )?;
+                region.assign_advice_value(|| "sibling", config.advice[1], 0, sibling)?;
+                region.assign_advice_value(|| "bit", config.advice[2], 0, bit)?;
+                let left_cell = region.assign_advice_value(|| "left", config.advice[3], 0, left)?;
+                let right_cell =
+                    region.assign_advice_value(|| "right", config.advice[4], 0, right)?;
+
+                // The hash starts from `left`, and is keyed by `right` in every round.
+                let mut state = left;
+                let state_cell = region.assign_advice_then_copy(
+                    || "s_0",
+                    config.advice[0],
+                    1,
+                    &config.perm,
+                    left_cell,
+                    state,
+                )?;
+                region.assign_advice_then_copy(
+                    || "key",
+                    config.advice[1],
+                    1,
+                    &config.perm,
+                    right_cell,
+                    right,
+                )?;
+
+                let mut output = state_cell;
+                for (round, c) in constants.iter().enumerate() {
+                    let row = round + 1;
+                    config.s_round.enable(&mut region, row)?;
+                    region.assign_fixed(
+                        || "round constant",
+                        config.round_constant,
+                        row,
+                        || Ok(*c),
+                    )?;
+
+                    state = state
+                        .and_then(|state| right.map(|right| (state + right + c).pow_vartime(&[5])));
+                    output =
+                        region.assign_advice_value(|| "state", config.advice[0], row + 1, state)?;
+                    region.assign_advice_value(|| "key", config.advice[1], row + 1, right)?;
+                }
+
+                Ok(Node {
+                    cell: output,
+                    value: state,
+                })
+            },
+        )
+    }
+
+    /// Constrains `node` to equal the root in the first row of the instance column.
+    fn expose_root(&self, mut layouter: impl Layouter<F>, node: Node<F>) -> Result<(), Error> {
+        let config = self.config();
+
+        // `public` and `s_root` are only used by this region, so it is placed in the
+        // first row.
+        layouter.assign_region(
+            || "expose root",
+            |mut region| {
+                config.s_root.enable(&mut region, 0)?;
+                region.assign_advice_then_copy(
+                    || "root",
+                    config.public,
+                    0,
+                    &config.perm,
+                    node.cell,
+                    node.value,
+                )?;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[derive(Clone, Default)]
+struct MerkleCircuit<F: FieldExt> {
+    leaf: Option<F>,
+    /// The sibling at each level, starting from the leaf, and whether the node at that
+    /// level is the right child.
+    path: [(Option<F>, Option<bool>); DEPTH],
+}
+
+impl<F: FieldExt> Circuit<F> for MerkleCircuit<F> {
+    type Config = MerkleConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> MerkleConfig {
+        HashChip::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: MerkleConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = HashChip::construct(config);
+
+        let mut node = chip.load_leaf(layouter.namespace(|| "leaf"), self.leaf)?;
+        for (level, (sibling, bit)) in self.path.iter().enumerate() {
+            node = chip.hash_level(
+                layouter.namespace(|| format!("level {}", level)),
+                node,
+                *sibling,
+                *bit,
+            )?;
+        }
+        chip.expose_root(layouter.namespace(|| "root"), node)
+    }
+}
+
+fn main() {
+    // Each level uses `ROUNDS + 2` rows.
+    let k = 7;
+
+    // Build a tree over the leaves 0, 1, 2, ..., and prove the membership of one of
+    // them.
+    let mut levels = vec![(0..1u64 << DEPTH).map(Fp::from).collect::<Vec<_>>()];
+    for _ in 0..DEPTH {
+        let nodes = levels.last().unwrap();
+        let parents = nodes.chunks(2).map(|pair| hash(pair[0], pair[1])).collect();
+        levels.push(parents);
+    }
+    let root = levels[DEPTH][0];
+
+    let index = 11;
+    let mut path = [(None, None); DEPTH];
+    for (level, step) in path.iter_mut().enumerate() {
+        let position = index >> level;
+        *step = (Some(levels[level][position ^ 1]), Some(position & 1 == 1));
+    }
+    let circuit = MerkleCircuit {
+        leaf: Some(levels[0][index]),
+        path,
+    };
+
+    let mut public_inputs = vec![Fp::zero(); 1 << k];
+    public_inputs[0] = root;
+
+    // The leaf is in the tree with this root.
+    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // A different leaf is not.
+    let wrong_leaf = MerkleCircuit {
+        leaf: Some(levels[0][index] + Fp::one()),
+        ..circuit.clone()
+    };
+    let prover = MockProver::run(k, &wrong_leaf, vec![public_inputs.clone()]).unwrap();
+    assert!(prover.verify().is_err());
+
+    // Create and verify a real proof, checking that it agrees with `MockProver`.
+    prove_and_verify::<EqAffine, _>(k, &circuit, vec![public_inputs]).unwrap();
+    println!(
+        "Verified membership of leaf {} in a tree of depth {}",
+        index, DEPTH
+    );
+}
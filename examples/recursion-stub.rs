@@ -0,0 +1,227 @@
+//! A stub of recursive proof composition over the Pasta cycle.
+//!
+//! Verifying a proof ends with an expensive check of the commitment `G`, which the
+//! inner product argument computes from the verifier's challenges. Recursion defers
+//! that check: the verifier instead keeps `G` and the challenges as an *accumulator*,
+//! and a proof over the other curve of the cycle shows that the accumulator was
+//! handled correctly. Because the scalar field of Pallas is the base field of Vesta, a
+//! circuit proven over Pallas can operate on the coordinates of Vesta points natively.
+//!
+//! This example takes the first steps of that construction:
+//!
+//! 1. It creates a proof over Vesta of a small inner circuit.
+//! 2. It verifies the proof up to the accumulator, and then checks the accumulator
+//!    directly, which is the step that recursion would defer.
+//! 3. It proves over Pallas an outer circuit that takes the coordinates of `G` as
+//!    public inputs, and checks that `G` is on the Vesta curve.
+//!
+//! A full recursive verifier would check the rest of the inner verifier inside the
+//! outer circuit as well, and would pass the accumulator on to the next proof.
+
+extern crate halo2;
+
+use group::Curve;
+use halo2::{
+    arithmetic::{CurveAffine, FieldExt},
+    circuit::{Layouter, SimpleFloorPlanner},
+    dev::prove_and_verify,
+    pasta::{EpAffine, EqAffine, Fp, Fq},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+        ConstraintSystem, Error, Expression, Selector,
+    },
+    poly::{
+        commitment::{Blind, Params},
+        Rotation,
+    },
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+
+/// The `b` coefficient of the Vesta curve, `y^2 = x^3 + b`.
+const VESTA_B: u64 = 5;
+
+#[derive(Clone, Debug)]
+struct CubeConfig {
+    x: Column<Advice>,
+    s_cube: Selector,
+}
+
+/// The inner circuit, which proves knowledge of a cube root of its public input.
+#[derive(Default)]
+struct CubeCircuit<F: FieldExt> {
+    x: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for CubeCircuit<F> {
+    type Config = CubeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> CubeConfig {
+        let x = meta.advice_column();
+        let y = meta.instance_column();
+        let s_cube = meta.selector();
+
+        meta.create_gate("cube", |meta| {
+            let s_cube = meta.query_selector(s_cube);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_instance(y, Rotation::cur());
+            vec![s_cube * (x.clone() * x.clone() * x - y)]
+        });
+
+        CubeConfig { x, s_cube }
+    }
+
+    fn synthesize(&self, config: CubeConfig, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_region(
+            || "cube",
+            |mut region| {
+                config.s_cube.enable(&mut region, 0)?;
+                region.assign_advice_value(|| "x", config.x, 0, self.x)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct OnCurveConfig {
+    x: Column<Advice>,
+    y: Column<Advice>,
+    s_on_curve: Selector,
+}
+
+/// The outer circuit, which checks that the point in its public inputs is on the
+/// Vesta curve.
+#[derive(Default)]
+struct OnCurveCircuit<F: FieldExt> {
+    point: Option<(F, F)>,
+}
+
+impl<F: FieldExt> Circuit<F> for OnCurveCircuit<F> {
+    type Config = OnCurveConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> OnCurveConfig {
+        let x = meta.advice_column();
+        let y = meta.advice_column();
+        let public_x = meta.instance_column();
+        let public_y = meta.instance_column();
+        let s_on_curve = meta.selector();
+
+        meta.create_gate("on curve", |meta| {
+            let s_on_curve = meta.query_selector(s_on_curve);
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+            let public_x = meta.query_instance(public_x, Rotation::cur());
+            let public_y = meta.query_instance(public_y, Rotation::cur());
+
+            let b = Expression::Constant(F::from_u64(VESTA_B));
+            vec![
+                s_on_curve.clone()
+                    * (y.clone() * y.clone() - x.clone() * x.clone() * x.clone() - b),
+                s_on_curve.clone() * (x - public_x),
+                s_on_curve * (y - public_y),
+            ]
+        });
+
+        OnCurveConfig { x, y, s_on_curve }
+    }
+
+    fn synthesize(
+        &self,
+        config: OnCurveConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "on curve",
+            |mut region| {
+                config.s_on_curve.enable(&mut region, 0)?;
+                let x = self.point.map(|(x, _)| x);
+                let y = self.point.map(|(_, y)| y);
+                region.assign_advice_value(|| "x", config.x, 0, x)?;
+                region.assign_advice_value(|| "y", config.y, 0, y)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Returns an instance column holding `value` in its first row, padded to `2^k` rows.
+fn instance_column<F: FieldExt>(k: u32, value: F) -> Vec<F> {
+    let mut column = vec![F::zero(); 1 << k];
+    column[0] = value;
+    column
+}
+
+fn main() {
+    let k = 4;
+
+    // Create the inner proof over Vesta.
+    let x = Fp::from(3);
+    let inner = CubeCircuit { x: Some(x) };
+    let params: Params<EqAffine> = Params::new(k);
+    let empty_circuit = CubeCircuit::default();
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let instance = pk
+        .get_vk()
+        .get_domain()
+        .lagrange_from_vec(instance_column(k, x * x * x));
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        &[inner],
+        &[&[instance.clone()]],
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    // Verify the inner proof up to the accumulator.
+    let commitment = params
+        .commit_lagrange(&instance, Blind::default())
+        .to_affine();
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let guard = verify_proof(
+        &params,
+        pk.get_vk(),
+        params.empty_msm(),
+        &[&[commitment]],
+        &mut transcript,
+    )
+    .expect("the proof should be well-formed");
+
+    // Computing `G` takes time linear in the size of the circuit. A recursive verifier
+    // would not compute it here, but would keep the accumulator to be checked later.
+    let g = guard.compute_g();
+    let (msm, accumulator) = guard.use_g(g);
+    assert!(msm.eval());
+
+    // Prove over Pallas that the accumulated point is on the Vesta curve.
+    let coordinates = accumulator.g.coordinates().unwrap();
+    let (gx, gy): (Fq, Fq) = (*coordinates.x(), *coordinates.y());
+    let outer = OnCurveCircuit {
+        point: Some((gx, gy)),
+    };
+    prove_and_verify::<EpAffine, _>(
+        k,
+        &outer,
+        vec![instance_column(k, gx), instance_column(k, gy)],
+    )
+    .unwrap();
+
+    println!(
+        "Verified a Vesta proof, and a Pallas proof over its accumulator with {} challenges",
+        accumulator.challenges_packed.len()
+    );
+}
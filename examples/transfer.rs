@@ -0,0 +1,214 @@
+//! A transfer between two balances, with range checks on the results.
+//!
+//! The prover knows the balances of a sender and a receiver, and an amount to transfer
+//! from one to the other. The circuit exposes the new balances as public inputs, and
+//! looks up the amount and both new balances in a table of the values `0..2^8`, so that
+//! the sender cannot overdraw their balance and the receiver's balance cannot wrap
+//! around the field.
+//!
+//! This example checks the circuit with `MockProver`, and then creates and verifies a
+//! real proof.
+
+extern crate halo2;
+
+use group::Curve;
+use halo2::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, SimpleFloorPlanner},
+    dev::MockProver,
+    gadget::table::{TableBuilder, TableColumn},
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_single, Advice, Circuit, Column,
+        ConstraintSystem, Error, Selector,
+    },
+    poly::{
+        commitment::{Blind, Params},
+        Rotation,
+    },
+    transcript::{Blake2bWrite, Challenge255},
+};
+
+/// The number of bits in a balance.
+const BALANCE_BITS: u32 = 8;
+
+#[derive(Clone, Debug)]
+struct TransferConfig {
+    sender: Column<Advice>,
+    amount: Column<Advice>,
+    receiver: Column<Advice>,
+    s_transfer: Selector,
+    range: TableColumn,
+}
+
+#[derive(Clone, Default)]
+struct TransferCircuit<F: FieldExt> {
+    sender: Option<F>,
+    receiver: Option<F>,
+    amount: Option<F>,
+}
+
+impl<F: FieldExt> Circuit<F> for TransferCircuit<F> {
+    type Config = TransferConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> TransferConfig {
+        let sender = meta.advice_column();
+        let amount = meta.advice_column();
+        let receiver = meta.advice_column();
+        let new_sender = meta.instance_column();
+        let new_receiver = meta.instance_column();
+        let s_transfer = meta.selector();
+        let range = TableColumn::new(meta);
+
+        // The transfer is laid out in a single row, next to the new balances in the
+        // instance columns:
+        //
+        // | sender | amount | receiver | new_sender | new_receiver | s_transfer |
+        // |--------|--------|----------|------------|--------------|------------|
+        // |   s    |   a    |    r     |   s - a    |    r + a     |     1      |
+        meta.create_gate("transfer", |meta| {
+            let s_transfer = meta.query_selector(s_transfer);
+            let sender = meta.query_advice(sender, Rotation::cur());
+            let amount = meta.query_advice(amount, Rotation::cur());
+            let receiver = meta.query_advice(receiver, Rotation::cur());
+            let new_sender = meta.query_instance(new_sender, Rotation::cur());
+            let new_receiver = meta.query_instance(new_receiver, Rotation::cur());
+
+            vec![
+                s_transfer.clone() * (sender - amount.clone() - new_sender),
+                s_transfer * (receiver + amount - new_receiver),
+            ]
+        });
+
+        // The amount and the new balances must be in the range table. In the rows where
+        // the selector is disabled, the inputs of the lookups are zero, which is also
+        // in the table.
+        meta.lookup(|meta| {
+            let s_transfer = meta.query_selector(s_transfer);
+            let amount = meta.query_advice(amount, Rotation::cur());
+            let range = meta.query_fixed(range.column(), Rotation::cur());
+            vec![(s_transfer * amount, range)]
+        });
+        for balance in [new_sender, new_receiver].iter().copied() {
+            meta.lookup(|meta| {
+                let s_transfer = meta.query_selector(s_transfer);
+                let balance = meta.query_instance(balance, Rotation::cur());
+                let range = meta.query_fixed(range.column(), Rotation::cur());
+                vec![(s_transfer * balance, range)]
+            });
+        }
+
+        TransferConfig {
+            sender,
+            amount,
+            receiver,
+            s_transfer,
+            range,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: TransferConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        TableBuilder::new(&[config.range])
+            .rows((0..(1 << BALANCE_BITS)).map(|value| (F::from_u64(value),)))
+            .assign(layouter.namespace(|| "range table"))?;
+
+        // This is the only region in the advice columns, so it is placed in the first
+        // row, where the new balances are in the instance columns.
+        layouter.assign_region(
+            || "transfer",
+            |mut region| {
+                config.s_transfer.enable(&mut region, 0)?;
+                region.assign_advice_value(|| "sender", config.sender, 0, self.sender)?;
+                region.assign_advice_value(|| "amount", config.amount, 0, self.amount)?;
+                region.assign_advice_value(|| "receiver", config.receiver, 0, self.receiver)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Returns the instance columns holding the given new balances, padded to `2^k` rows.
+fn new_balances(k: u32, sender: Fp, receiver: Fp) -> Vec<Vec<Fp>> {
+    [sender, receiver]
+        .iter()
+        .map(|balance| {
+            let mut column = vec![Fp::zero(); 1 << k];
+            column[0] = *balance;
+            column
+        })
+        .collect()
+}
+
+fn main() {
+    // The range table has 2^8 rows, so the circuit needs 2^9 rows.
+    let k = 9;
+    let value = |value: u64| Fp::from(value);
+
+    let circuit = TransferCircuit {
+        sender: Some(value(100)),
+        receiver: Some(value(20)),
+        amount: Some(value(30)),
+    };
+    let public_inputs = new_balances(k, value(70), value(50));
+
+    // Given the correct new balances, the circuit is satisfied.
+    let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Overdrawing the sender's balance satisfies the transfer gate, but the new balance
+    // of the sender wraps around the field, and so is not in the range table.
+    let overdraft = TransferCircuit {
+        amount: Some(value(120)),
+        ..circuit.clone()
+    };
+    let prover = MockProver::run(
+        k,
+        &overdraft,
+        new_balances(k, value(100) - value(120), value(140)),
+    )
+    .unwrap();
+    assert!(prover.verify().is_err());
+
+    // Generate the keys, which only depend on the shape of the circuit.
+    let params: Params<EqAffine> = Params::new(k);
+    let empty_circuit = TransferCircuit::default();
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    // Create a proof of the transfer.
+    let instance: Vec<_> = public_inputs
+        .iter()
+        .map(|column| pk.get_vk().get_domain().lagrange_from_vec(column.clone()))
+        .collect();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&instance], &mut transcript)
+        .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    // The verifier only needs the commitments to the public inputs.
+    let commit = |columns: &[Vec<Fp>]| -> Vec<EqAffine> {
+        columns
+            .iter()
+            .map(|column| {
+                let poly = pk.get_vk().get_domain().lagrange_from_vec(column.clone());
+                params.commit_lagrange(&poly, Blind::default()).to_affine()
+            })
+            .collect()
+    };
+    assert!(verify_single(&params, pk.get_vk(), &proof, &commit(&public_inputs)).is_ok());
+
+    // The proof does not verify against any other new balances.
+    let wrong_inputs = new_balances(k, value(60), value(60));
+    assert!(verify_single(&params, pk.get_vk(), &proof, &commit(&wrong_inputs)).is_err());
+
+    println!("Verified a transfer proof of {} bytes", proof.len());
+}
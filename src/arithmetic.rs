@@ -1,11 +1,19 @@
 //! This module provides common utilities, traits and structures for group,
 //! field and polynomial arithmetic.
 
+use std::error;
+use std::fmt;
+
 use crossbeam_utils::thread;
 pub use ff::Field;
 use group::Group as _;
+use rand::rngs::OsRng;
+use subtle::Choice;
+
+mod curves;
+mod pasta;
 
-pub use pasta_curves::arithmetic::*;
+pub use curves::*;
 
 /// Extension trait for iterators over mutable field elements which allows those
 /// field elements to be inverted in a batch.
@@ -18,23 +26,25 @@ pub trait BatchInvert<F: Field> {
 
 impl<'a, F, I> BatchInvert<F> for I
 where
-    F: FieldExt,
+    F: Field,
     I: IntoIterator<Item = &'a mut F>,
 {
     fn batch_invert(self) -> F {
+        let is_zero = |p: &F| Choice::from(p.is_zero() as u8);
+
         let mut acc = F::one();
         let iter = self.into_iter();
         let mut tmp = Vec::with_capacity(iter.size_hint().0);
         for p in iter {
             let q = *p;
             tmp.push((acc, p));
-            acc = F::conditional_select(&(acc * q), &acc, q.ct_is_zero());
+            acc = F::conditional_select(&(acc * q), &acc, is_zero(&q));
         }
         acc = acc.invert().unwrap();
         let allinv = acc;
 
         for (tmp, p) in tmp.into_iter().rev() {
-            let skip = p.ct_is_zero();
+            let skip = is_zero(p);
 
             let tmp = tmp * acc;
             acc = F::conditional_select(&(acc * *p), &acc, skip);
@@ -45,6 +55,131 @@ where
     }
 }
 
+/// A prime field with a multiplicative subgroup of order `2^S`, over which evaluation
+/// domains are defined.
+///
+/// `S` and the element of order `2^S` are those of [`ff::PrimeField`], so circuits
+/// over the field have at most `2^S` rows, less the extension for the degree of their
+/// constraints.
+pub trait TwoAdicField: ff::PrimeField {
+    /// Returns the inverse of [`ff::PrimeField::root_of_unity`].
+    fn root_of_unity_inv() -> Self;
+
+    /// Returns an element of odd order greater than one, whose powers lie in distinct
+    /// cosets of the subgroup of order `2^S`. The powers of this element separate the
+    /// columns of permutation arguments.
+    fn delta() -> Self;
+
+    /// Returns the inverse of two.
+    fn two_inv() -> Self;
+}
+
+/// A field with a primitive cube root of unity, which shifts extended evaluation
+/// domains into a coset and scales challenges derived from the transcript.
+pub trait CubeRootOfUnity: Field {
+    /// Returns a primitive cube root of unity.
+    fn zeta() -> Self;
+}
+
+/// A field with a canonical 32-byte little-endian encoding, which is used to write
+/// field elements to transcripts, keys and proofs.
+pub trait CanonicalEncoding: Field {
+    /// Returns the modulus of the field as a hexadecimal string, which identifies the
+    /// field in the headers of serialized data.
+    fn modulus() -> &'static str;
+
+    /// Returns the canonical little-endian encoding of this element.
+    fn to_bytes(&self) -> [u8; 32];
+
+    /// Decodes an element, failing if `bytes` is not a canonical encoding.
+    fn from_bytes(bytes: &[u8; 32]) -> subtle::CtOption<Self>;
+
+    /// Reduces a 64-byte little-endian integer into the field.
+    fn from_bytes_wide(bytes: &[u8; 64]) -> Self;
+}
+
+/// The field that keygen, the prover and the verifier work over.
+///
+/// This is implemented for every field that implements [`TwoAdicField`],
+/// [`CubeRootOfUnity`] and [`CanonicalEncoding`] (and [`Ord`], by which lookup
+/// arguments sort their values), together with [`BatchInvert`], which is available
+/// for every [`Field`]. Those are the only traits that a third-party field needs to
+/// implement; [`check_field_constants`] checks that the implementation is consistent.
+pub trait FieldExt: TwoAdicField + CubeRootOfUnity + CanonicalEncoding + Ord {
+    /// Returns whether or not this element is zero, in constant time.
+    fn ct_is_zero(&self) -> Choice {
+        Choice::from(self.is_zero() as u8)
+    }
+
+    /// Obtains a field element congruent to the integer `v`.
+    fn from_u64(v: u64) -> Self {
+        Self::from(v)
+    }
+
+    /// Obtains a uniformly random field element from the operating system.
+    fn rand() -> Self {
+        Self::random(OsRng)
+    }
+
+    /// Exponentiates `self` by `by`, where `by` is a little-endian order integer
+    /// exponent, in constant time.
+    fn pow(&self, by: &[u64; 4]) -> Self {
+        let mut res = Self::one();
+        for e in by.iter().rev() {
+            for i in (0..64).rev() {
+                res = res.square();
+                let mut tmp = res;
+                tmp *= self;
+                res.conditional_assign(&tmp, (((*e >> i) & 0x1) as u8).into());
+            }
+        }
+        res
+    }
+}
+
+impl<F: TwoAdicField + CubeRootOfUnity + CanonicalEncoding + Ord> FieldExt for F {}
+
+/// This represents an element of a group with basic operations that can be
+/// performed. This allows an FFT implementation (for example) to operate
+/// generically over either a field or elliptic curve group.
+pub trait Group: Copy + Clone + Send + Sync + 'static {
+    /// The group is assumed to be of prime order $p$. `Scalar` is the
+    /// associated scalar field of size $p$.
+    type Scalar: FieldExt;
+
+    /// Returns the additive identity of the group.
+    fn group_zero() -> Self;
+
+    /// Adds `rhs` to this group element.
+    fn group_add(&mut self, rhs: &Self);
+
+    /// Subtracts `rhs` from this group element.
+    fn group_sub(&mut self, rhs: &Self);
+
+    /// Scales this group element by a scalar.
+    fn group_scale(&mut self, by: &Self::Scalar);
+}
+
+impl<F: FieldExt> Group for F {
+    type Scalar = F;
+
+    fn group_zero() -> Self {
+        F::zero()
+    }
+
+    fn group_add(&mut self, rhs: &Self) {
+        *self += *rhs;
+    }
+
+    fn group_sub(&mut self, rhs: &Self) {
+        *self -= *rhs;
+    }
+
+    fn group_scale(&mut self, by: &Self::Scalar) {
+        *self *= *by;
+    }
+}
+
 fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut C::Curve) {
     let coeffs: Vec<[u8; 32]> = coeffs.iter().map(|a| a.to_bytes()).collect();
 
@@ -460,3 +595,116 @@ fn test_lagrange_interpolate() {
         }
     }
 }
+
+/// The parts of a [`FieldExt`] implementation that [`check_field_constants`] can
+/// reject.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldConstantError {
+    /// The root of unity of [`ff::PrimeField`] does not have order `2^S`.
+    RootOfUnity,
+    /// [`TwoAdicField::root_of_unity_inv`] is not the inverse of the root of unity.
+    RootOfUnityInv,
+    /// [`TwoAdicField::delta`] does not have odd order greater than one.
+    Delta,
+    /// [`CubeRootOfUnity::zeta`] is not a primitive cube root of unity.
+    Zeta,
+    /// [`TwoAdicField::two_inv`] is not the inverse of two.
+    TwoInv,
+    /// The methods of [`CanonicalEncoding`] do not agree on the encoding of field
+    /// elements.
+    Encoding,
+}
+
+impl fmt::Display for FieldConstantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RootOfUnity => write!(f, "the root of unity does not have order 2^S"),
+            Self::RootOfUnityInv => {
+                write!(
+                    f,
+                    "root_of_unity_inv is not the inverse of the root of unity"
+                )
+            }
+            Self::Delta => write!(f, "delta does not have odd order greater than one"),
+            Self::Zeta => write!(f, "zeta is not a primitive cube root of unity"),
+            Self::TwoInv => write!(f, "two_inv is not the inverse of two"),
+            Self::Encoding => write!(f, "the byte encodings of field elements are inconsistent"),
+        }
+    }
+}
+
+impl error::Error for FieldConstantError {}
+
+/// Checks the capability traits of a [`FieldExt`] implementation against each other.
+///
+/// Fields that are not defined by this crate should be checked with this function in
+/// their tests before they are used with keygen, the prover and the verifier. It
+/// checks that:
+///
+/// - the root of unity of [`ff::PrimeField`] has order exactly `2^S`, and
+///   [`TwoAdicField::root_of_unity_inv`] is its inverse;
+/// - [`TwoAdicField::delta`] has odd order greater than one, so that its powers lie in
+///   distinct cosets of the subgroup of order `2^S`;
+/// - [`CubeRootOfUnity::zeta`] is a primitive cube root of unity;
+/// - [`TwoAdicField::two_inv`] is the inverse of two;
+/// - [`CanonicalEncoding::to_bytes`], [`CanonicalEncoding::from_bytes`] and
+///   [`CanonicalEncoding::from_bytes_wide`] agree on the encoding of `p - 1`.
+pub fn check_field_constants<F: FieldExt>() -> Result<(), FieldConstantError> {
+    let one = F::one();
+    let check = |ok: bool, error| if ok { Ok(()) } else { Err(error) };
+
+    let mut half_order = F::root_of_unity();
+    for _ in 1..F::S {
+        half_order = half_order.square();
+    }
+    check(
+        F::S > 0 && half_order == -one,
+        FieldConstantError::RootOfUnity,
+    )?;
+    check(
+        F::root_of_unity() * F::root_of_unity_inv() == one,
+        FieldConstantError::RootOfUnityInv,
+    )?;
+
+    let minus_one = (-one).to_bytes();
+
+    // delta^t = 1, where p - 1 = t * 2^S with t odd.
+    let mut t = [0u64; 4];
+    for (limb, bytes) in t.iter_mut().zip(minus_one.chunks(8)) {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(bytes);
+        *limb = u64::from_le_bytes(buf);
+    }
+    for _ in 0..F::S {
+        let mut carry = 0;
+        for limb in t.iter_mut().rev() {
+            let next = *limb << 63;
+            *limb = (*limb >> 1) | carry;
+            carry = next;
+        }
+    }
+    check(
+        F::delta() != one && F::delta().pow_vartime(t) == one,
+        FieldConstantError::Delta,
+    )?;
+
+    check(
+        F::zeta() != one && F::zeta().square() * F::zeta() == one,
+        FieldConstantError::Zeta,
+    )?;
+    check(F::two_inv().double() == one, FieldConstantError::TwoInv)?;
+
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(&minus_one);
+    check(
+        Option::<F>::from(F::from_bytes(&minus_one)) == Some(-one)
+            && F::from_bytes_wide(&wide) == -one,
+        FieldConstantError::Encoding,
+    )
+}
+
+#[test]
+fn test_field_constants() {
+    assert_eq!(check_field_constants::<Fp>(), Ok(()));
+    assert_eq!(check_field_constants::<crate::pasta::Fq>(), Ok(()));
+}
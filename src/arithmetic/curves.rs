@@ -0,0 +1,125 @@
+//! The curve traits that keygen, the prover and the verifier work over.
+
+use std::io;
+use std::ops::{Add, Sub};
+
+use group::prime::{PrimeCurve, PrimeCurveAffine};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use super::{FieldExt, Group};
+
+/// This trait is a common interface for dealing with elements of an elliptic
+/// curve group in a "projective" form, where that arithmetic is usually more
+/// efficient.
+pub trait CurveExt:
+    PrimeCurve<Affine = <Self as CurveExt>::AffineExt>
+    + group::Group<Scalar = <Self as CurveExt>::ScalarExt>
+    + Default
+    + ConditionallySelectable
+    + ConstantTimeEq
+    + From<<Self as PrimeCurve>::Affine>
+    + Group<Scalar = <Self as group::Group>::Scalar>
+{
+    /// The scalar field of this elliptic curve.
+    type ScalarExt: FieldExt;
+    /// The base field over which this elliptic curve is constructed.
+    type Base: FieldExt;
+    /// The affine version of the curve
+    type AffineExt: CurveAffine<CurveExt = Self, ScalarExt = <Self as CurveExt>::ScalarExt>;
+
+    /// CURVE_ID used for hash-to-curve.
+    const CURVE_ID: &'static str;
+
+    /// Requests a hasher that accepts messages and returns near-uniformly
+    /// distributed elements in the group, given domain prefix `domain_prefix`.
+    ///
+    /// The elements are used as the generators of [`Params`], so their discrete
+    /// logarithms with respect to each other must be unknown.
+    ///
+    /// [`Params`]: crate::poly::commitment::Params
+    #[allow(clippy::type_complexity)]
+    fn hash_to_curve<'a>(domain_prefix: &'a str) -> Box<dyn Fn(&[u8]) -> Self + 'a>;
+}
+
+/// This trait is the affine counterpart to [`CurveExt`] and is used for
+/// serialization, storage in memory, and inspection of $x$ and $y$ coordinates.
+pub trait CurveAffine:
+    PrimeCurveAffine<
+        Scalar = <Self as CurveAffine>::ScalarExt,
+        Curve = <Self as CurveAffine>::CurveExt,
+    > + Default
+    + Add<Output = <Self as PrimeCurveAffine>::Curve>
+    + Sub<Output = <Self as PrimeCurveAffine>::Curve>
+    + ConditionallySelectable
+    + ConstantTimeEq
+    + From<<Self as PrimeCurveAffine>::Curve>
+{
+    /// The scalar field of this elliptic curve.
+    type ScalarExt: FieldExt;
+    /// The base field over which this elliptic curve is constructed.
+    type Base: FieldExt;
+    /// The projective form of the curve
+    type CurveExt: CurveExt<AffineExt = Self, ScalarExt = <Self as CurveAffine>::ScalarExt>;
+
+    /// Gets the coordinates of this point.
+    ///
+    /// Returns None if this is the identity.
+    fn coordinates(&self) -> CtOption<Coordinates<Self>>;
+
+    /// Obtains a point given $(x, y)$, failing if it is not on the
+    /// curve.
+    fn from_xy(x: Self::Base, y: Self::Base) -> CtOption<Self>;
+
+    /// Returns whether or not this element is on the curve; should
+    /// always be true unless an "unchecked" API was used.
+    fn is_on_curve(&self) -> Choice;
+
+    /// Reads a compressed element from the buffer and attempts to parse it
+    /// using `from_bytes`.
+    fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let mut compressed = Self::Repr::default();
+        reader.read_exact(compressed.as_mut())?;
+        Option::from(Self::from_bytes(&compressed))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof"))
+    }
+
+    /// Writes an element in compressed form to the buffer.
+    fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let compressed = self.to_bytes();
+        writer.write_all(compressed.as_ref())
+    }
+}
+
+/// The affine coordinates of a point on an elliptic curve.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Coordinates<C: CurveAffine> {
+    pub(crate) x: C::Base,
+    pub(crate) y: C::Base,
+}
+
+impl<C: CurveAffine> Coordinates<C> {
+    /// Obtains a `Coordinates` value given $(x, y)$, failing if it is not on the curve.
+    pub fn from_xy(x: C::Base, y: C::Base) -> CtOption<Self> {
+        // We use CurveAffine::from_xy to validate the coordinates.
+        C::from_xy(x, y).map(|_| Coordinates { x, y })
+    }
+
+    /// Returns the x-coordinate.
+    pub fn x(&self) -> &C::Base {
+        &self.x
+    }
+
+    /// Returns the y-coordinate.
+    pub fn y(&self) -> &C::Base {
+        &self.y
+    }
+}
+
+impl<C: CurveAffine> ConditionallySelectable for Coordinates<C> {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Coordinates {
+            x: C::Base::conditional_select(&a.x, &b.x, choice),
+            y: C::Base::conditional_select(&a.y, &b.y, choice),
+        }
+    }
+}
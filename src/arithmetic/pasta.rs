@@ -0,0 +1,113 @@
+//! Implementations of the arithmetic traits for the Pasta curves.
+
+use pasta_curves::{arithmetic as pasta, pallas, vesta};
+use subtle::{Choice, CtOption};
+
+use super::{
+    CanonicalEncoding, Coordinates, CubeRootOfUnity, CurveAffine, CurveExt, Group, TwoAdicField,
+};
+
+macro_rules! impl_field {
+    ($field:ty) => {
+        impl TwoAdicField for $field {
+            fn root_of_unity_inv() -> Self {
+                <$field as pasta::FieldExt>::ROOT_OF_UNITY_INV
+            }
+
+            fn delta() -> Self {
+                <$field as pasta::FieldExt>::DELTA
+            }
+
+            fn two_inv() -> Self {
+                <$field as pasta::FieldExt>::TWO_INV
+            }
+        }
+
+        impl CubeRootOfUnity for $field {
+            fn zeta() -> Self {
+                <$field as pasta::FieldExt>::ZETA
+            }
+        }
+
+        impl CanonicalEncoding for $field {
+            fn modulus() -> &'static str {
+                <$field as pasta::FieldExt>::MODULUS
+            }
+
+            fn to_bytes(&self) -> [u8; 32] {
+                <$field as pasta::FieldExt>::to_bytes(self)
+            }
+
+            fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+                <$field as pasta::FieldExt>::from_bytes(bytes)
+            }
+
+            fn from_bytes_wide(bytes: &[u8; 64]) -> Self {
+                <$field as pasta::FieldExt>::from_bytes_wide(bytes)
+            }
+        }
+    };
+}
+
+macro_rules! impl_curve {
+    ($curve:ty, $affine:ty, $scalar:ty, $base:ty) => {
+        impl Group for $curve {
+            type Scalar = $scalar;
+
+            fn group_zero() -> Self {
+                <$curve as group::Group>::identity()
+            }
+
+            fn group_add(&mut self, rhs: &Self) {
+                *self += *rhs;
+            }
+
+            fn group_sub(&mut self, rhs: &Self) {
+                *self -= *rhs;
+            }
+
+            fn group_scale(&mut self, by: &Self::Scalar) {
+                *self *= *by;
+            }
+        }
+
+        impl CurveExt for $curve {
+            type ScalarExt = $scalar;
+            type Base = $base;
+            type AffineExt = $affine;
+
+            const CURVE_ID: &'static str = <$curve as pasta::CurveExt>::CURVE_ID;
+
+            fn hash_to_curve<'a>(domain_prefix: &'a str) -> Box<dyn Fn(&[u8]) -> Self + 'a> {
+                <$curve as pasta::CurveExt>::hash_to_curve(domain_prefix)
+            }
+        }
+
+        impl CurveAffine for $affine {
+            type ScalarExt = $scalar;
+            type Base = $base;
+            type CurveExt = $curve;
+
+            fn coordinates(&self) -> CtOption<Coordinates<Self>> {
+                <$affine as pasta::CurveAffine>::coordinates(self).map(|coordinates| Coordinates {
+                    x: *coordinates.x(),
+                    y: *coordinates.y(),
+                })
+            }
+
+            fn from_xy(x: Self::Base, y: Self::Base) -> CtOption<Self> {
+                <$affine as pasta::CurveAffine>::from_xy(x, y)
+            }
+
+            fn is_on_curve(&self) -> Choice {
+                <$affine as pasta::CurveAffine>::is_on_curve(self)
+            }
+        }
+    };
+}
+
+impl_field!(pallas::Base);
+impl_field!(pallas::Scalar);
+
+impl_curve!(pallas::Point, pallas::Affine, pallas::Scalar, pallas::Base);
+impl_curve!(vesta::Point, vesta::Affine, vesta::Scalar, vesta::Base);
@@ -1,12 +1,15 @@
 //! Traits and structs for implementing circuit components.
 
-use std::{fmt, marker::PhantomData};
+use std::{convert::TryFrom, fmt, marker::PhantomData};
 
 use ff::Field;
 
 use crate::{
     arithmetic::FieldExt,
-    plonk::{Advice, Any, Assigned, Column, Error, Fixed, Permutation, Selector},
+    plonk::{
+        Advice, Any, Assigned, Column, ColumnType, Error, Fixed, Instance, Lane, Permutation,
+        Selector,
+    },
 };
 
 pub mod floor_planner;
@@ -46,7 +49,7 @@ pub trait Chip<F: FieldExt>: Sized {
 }
 
 /// Index of a region in a layouter
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct RegionIndex(usize);
 
 impl From<usize> for RegionIndex {
@@ -82,14 +85,64 @@ impl std::ops::Deref for RegionStart {
 }
 
 /// A pointer to a cell within a circuit.
-#[derive(Clone, Copy, Debug)]
+///
+/// Cells are created by [`layouter::RegionLayouter`] implementations, and refer to rows
+/// relative to the start of their region. The absolute row of a cell is only known to
+/// the floor planner that positioned the region.
+///
+/// Cells can be compared and hashed, so gadgets can use them as keys (for example, to
+/// record which cells they have already copied). Two cells are equal if they are in the
+/// same column at the same offset of the same region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Cell {
     /// Identifies the region in which this cell resides.
-    region_index: RegionIndex,
+    pub region_index: RegionIndex,
     /// The relative offset of this cell within its region.
-    row_offset: usize,
+    pub row_offset: usize,
     /// The column of this cell.
-    column: Column<Any>,
+    pub column: Column<Any>,
+}
+
+impl Cell {
+    /// Returns the index of the region in which this cell resides.
+    pub fn region_index(&self) -> RegionIndex {
+        self.region_index
+    }
+
+    /// Returns the offset of this cell within its region.
+    pub fn row_offset(&self) -> usize {
+        self.row_offset
+    }
+
+    /// Returns the column of this cell.
+    pub fn column(&self) -> Column<Any> {
+        self.column
+    }
+
+    /// Describes this cell, including the name of its region if it is given.
+    pub(crate) fn describe(&self, region_name: Option<&str>) -> String {
+        let region = match region_name {
+            Some(name) => format!("region {} ('{}')", *self.region_index, name),
+            None => format!("region {}", *self.region_index),
+        };
+        format!(
+            "Cell({}, offset {}, {:?} column {})",
+            region,
+            self.row_offset,
+            self.column.column_type(),
+            self.column.index()
+        )
+    }
+}
+
+/// Cells are displayed without the names of their regions, which are only known to the
+/// layouter; [`MockProver::describe_cell`] includes them.
+///
+/// [`MockProver::describe_cell`]: crate::dev::MockProver::describe_cell
+impl fmt::Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe(None))
+    }
 }
 
 /// A region of the circuit in which a [`Chip`] can assign cells.
@@ -174,9 +227,107 @@ impl<'r, F: Field> Region<'r, F> {
             })
     }
 
+    /// Assigns an advice value (witness) that is given directly, rather than computed
+    /// by a closure.
+    ///
+    /// This is equivalent to calling [`Region::assign_advice`] with a closure that
+    /// returns `value`. A `None` value is unknown (as witnesses are during key
+    /// generation), and is treated in the same way as a closure that returns
+    /// [`Error::SynthesisError`].
+    pub fn assign_advice_value<VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        offset: usize,
+        value: Option<VR>,
+    ) -> Result<Cell, Error>
+    where
+        VR: Into<Assigned<F>>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let value = value.map(|v| v.into());
+        self.region
+            .assign_advice(&|| annotation().into(), column, offset, &mut || {
+                value.ok_or(Error::SynthesisError)
+            })
+    }
+
+    /// Assigns an advice value in the same way as [`Region::assign_advice_value`], and
+    /// constrains the new cell to equal `from`.
+    ///
+    /// This is how a variable assigned in another region is copied into this one;
+    /// `value` should be the value of `from`.
+    pub fn assign_advice_then_copy<VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        offset: usize,
+        permutation: &Permutation,
+        from: Cell,
+        value: Option<VR>,
+    ) -> Result<Cell, Error>
+    where
+        VR: Into<Assigned<F>>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let cell = self.assign_advice_value(annotation, column, offset, value)?;
+        self.constrain_equal(permutation, from, cell)?;
+        Ok(cell)
+    }
+
+    /// Assigns a contiguous run of advice values (witnesses) to a column, starting at
+    /// `offset`, and returns the assigned cells.
+    ///
+    /// This is equivalent to calling [`Region::assign_advice`] for each value with the
+    /// same annotation, but avoids the per-cell overhead of doing so when filling large
+    /// regions. `None` values are unknown, and are treated in the same way as a value
+    /// closure that returns [`Error::SynthesisError`].
+    pub fn assign_advice_from_slice<VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        offset: usize,
+        values: &[Option<VR>],
+    ) -> Result<Vec<Cell>, Error>
+    where
+        VR: Copy + Into<Assigned<F>>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let values: Vec<_> = values.iter().map(|value| value.map(|v| v.into())).collect();
+        self.region
+            .assign_advice_slice(&|| annotation().into(), column, offset, &values)
+    }
+
+    /// Assigns a contiguous run of fixed values to a column, starting at `offset`, and
+    /// returns the assigned cells.
+    ///
+    /// This is equivalent to calling [`Region::assign_fixed`] for each value with the
+    /// same annotation, but avoids the per-cell overhead of doing so when filling large
+    /// regions such as lookup tables.
+    pub fn assign_fixed_from_slice<VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        offset: usize,
+        values: &[VR],
+    ) -> Result<Vec<Cell>, Error>
+    where
+        VR: Copy + Into<Assigned<F>>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let values: Vec<_> = values.iter().map(|&v| v.into()).collect();
+        self.region
+            .assign_fixed_slice(&|| annotation().into(), column, offset, &values)
+    }
+
     /// Constraint two cells to have the same value.
     ///
-    /// Returns an error if either of the cells is not within the given permutation.
+    /// Returns [`Error::InvalidCopy`] if either of the cells is not within the given
+    /// permutation.
     pub fn constrain_equal(
         &mut self,
         permutation: &Permutation,
@@ -185,6 +336,213 @@ impl<'r, F: Field> Region<'r, F> {
     ) -> Result<(), Error> {
         self.region.constrain_equal(permutation, left, right)
     }
+
+    /// Constrains a cell to have a constant value.
+    ///
+    /// The constant is stored by the floor planner in a fixed column that has been
+    /// enabled with [`ConstraintSystem::enable_constant`]. That column and the column of
+    /// `cell` must both be within the given permutation.
+    ///
+    /// [`ConstraintSystem::enable_constant`]: crate::plonk::ConstraintSystem::enable_constant
+    pub fn constrain_constant<VR>(
+        &mut self,
+        permutation: &Permutation,
+        cell: Cell,
+        constant: VR,
+    ) -> Result<(), Error>
+    where
+        VR: Into<Assigned<F>>,
+    {
+        self.region
+            .constrain_constant(permutation, cell, constant.into())
+    }
+
+    /// Assigns a constant value to an advice column, and constrains it to equal that
+    /// constant using [`Region::constrain_constant`].
+    pub fn assign_advice_from_constant<VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        offset: usize,
+        permutation: &Permutation,
+        constant: VR,
+    ) -> Result<Cell, Error>
+    where
+        VR: Into<Assigned<F>>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let constant = constant.into();
+        let cell = self.assign_advice(annotation, column, offset, || Ok(constant))?;
+        self.constrain_constant(permutation, cell, constant)?;
+        Ok(cell)
+    }
+
+    /// Returns the value assigned to `cell`, which must be in a fixed column of this
+    /// region or of a region assigned before it.
+    ///
+    /// This allows chips to compute witnesses from tables that the circuit has already
+    /// assigned, without keeping a copy of the table themselves. The value is `None`
+    /// when it is not known, which is always the case while the floor planner is
+    /// measuring regions, and within regions assigned with [`Layouter::assign_regions`]
+    /// for cells outside of the region itself. It is also `None` when creating proofs if the circuit assigned the cell an
+    /// unknown value.
+    pub fn fixed_value(&self, cell: Cell) -> Result<Option<F>, Error> {
+        self.region.fixed_value(cell)
+    }
+
+    /// Returns the value of the instance cell at absolute `row` of `column`.
+    ///
+    /// This allows witnesses to be computed from public inputs, without the circuit
+    /// having to be given a private copy of them. The value is `None` when it is not
+    /// known, which is always the case during key generation and while the floor
+    /// planner is measuring regions.
+    pub fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.region.query_instance(column, row)
+    }
+
+    /// Marks this region as instance-dependent, meaning that its advice values can
+    /// differ between proofs of the same circuit.
+    ///
+    /// When a proof is created with a [`CachedWitness`] recorded from an earlier proof,
+    /// only instance-dependent regions compute their advice values; the advice values of
+    /// every other region are taken from the cache, without calling the closures passed
+    /// to [`Region::assign_advice`]. A region must therefore be marked if any of its
+    /// advice values depend on the instance, on the values of another instance-dependent
+    /// region, or on anything else that may change between proofs.
+    ///
+    /// This can be called at any point in the region, and has no effect when the
+    /// witness is not being cached.
+    ///
+    /// [`CachedWitness`]: crate::plonk::CachedWitness
+    pub fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        self.region.mark_instance_dependent()
+    }
+
+    /// Declares that this region uses at least `rows` rows.
+    ///
+    /// Floor planners that measure regions will reserve this many rows for the region,
+    /// even if it assigns fewer. This can be called at any point in the region.
+    pub fn set_min_rows(&mut self, rows: usize) {
+        self.region.set_min_rows(rows)
+    }
+
+    /// Returns a view of the row at `offset` of this region, in which the columns of
+    /// `lane` are addressed by their position within the lane.
+    ///
+    /// See [`WideRegion`].
+    pub fn wide<'w>(&'w mut self, lane: &'w Lane, offset: usize) -> WideRegion<'w, 'r, F> {
+        WideRegion {
+            region: self,
+            lane,
+            offset,
+        }
+    }
+}
+
+/// A single row of a [`Region`], spanning the columns of a [`Lane`].
+///
+/// Chips with wide gates (which use many columns of a single row) can store a [`Lane`]
+/// in their config instead of a fixed set of columns, and assign each row by the
+/// position of its columns within the lane. If the lane is declared with
+/// [`ConstraintSystem::interchangeable_lanes`], floor planners that support it (such
+/// as [`V1`]) can then relocate the chip's regions horizontally, into any of the
+/// interchangeable lanes.
+///
+/// ```ignore
+/// layouter.assign_wide_region(|| "sum", &config.lane, |mut row| {
+///     row.enable_selector(|| "sum", 4)?;
+///     for (index, value) in values.iter().enumerate() {
+///         row.assign_advice(|| "term", index, || Ok(*value))?;
+///     }
+///     row.assign_advice(|| "sum", 3, || Ok(sum))
+/// })?;
+/// ```
+///
+/// [`ConstraintSystem::interchangeable_lanes`]: crate::plonk::ConstraintSystem::interchangeable_lanes
+/// [`V1`]: floor_planner::V1
+#[derive(Debug)]
+pub struct WideRegion<'w, 'r, F: Field> {
+    region: &'w mut Region<'r, F>,
+    lane: &'w Lane,
+    offset: usize,
+}
+
+impl<'w, 'r, F: Field> WideRegion<'w, 'r, F> {
+    /// Returns the number of columns in this row.
+    pub fn width(&self) -> usize {
+        self.lane.columns().len()
+    }
+
+    /// Returns the offset of this row within its region.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the column at the given position within the lane, converted to the
+    /// requested column type.
+    ///
+    /// Returns [`Error::BoundsFailure`] if the lane has no column at `index`, and
+    /// [`Error::SynthesisError`] if the column has a different type.
+    pub fn column<C: ColumnType>(&self, index: usize) -> Result<Column<C>, Error>
+    where
+        Column<C>: TryFrom<Column<Any>>,
+    {
+        let column = *self.lane.columns().get(index).ok_or(Error::BoundsFailure)?;
+        Column::<C>::try_from(column).map_err(|_| Error::SynthesisError)
+    }
+
+    /// Assigns an advice value (witness) to the column at `index` within the lane.
+    pub fn assign_advice<'v, V, VR, A, AR>(
+        &'v mut self,
+        annotation: A,
+        index: usize,
+        to: V,
+    ) -> Result<Cell, Error>
+    where
+        V: FnMut() -> Result<VR, Error> + 'v,
+        VR: Into<Assigned<F>>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let column = self.column::<Advice>(index)?;
+        self.region
+            .assign_advice(annotation, column, self.offset, to)
+    }
+
+    /// Assigns a fixed value to the column at `index` within the lane.
+    pub fn assign_fixed<'v, V, VR, A, AR>(
+        &'v mut self,
+        annotation: A,
+        index: usize,
+        to: V,
+    ) -> Result<Cell, Error>
+    where
+        V: FnMut() -> Result<VR, Error> + 'v,
+        VR: Into<Assigned<F>>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let column = self.column::<Fixed>(index)?;
+        self.region
+            .assign_fixed(annotation, column, self.offset, to)
+    }
+
+    /// Enables the selector whose fixed column is at `index` within the lane.
+    pub fn enable_selector<A, AR>(&mut self, annotation: A, index: usize) -> Result<(), Error>
+    where
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let selector = Selector(self.column::<Fixed>(index)?);
+        self.region
+            .enable_selector(annotation, &selector, self.offset)
+    }
+
+    /// Returns the underlying region, to copy cells into this row or constrain them.
+    pub fn region(&mut self) -> &mut Region<'r, F> {
+        self.region
+    }
 }
 
 /// A layout strategy within a circuit. The layouter is chip-agnostic and applies its
@@ -215,6 +573,99 @@ pub trait Layouter<F: Field> {
         N: Fn() -> NR,
         NR: Into<String>;
 
+    /// Assigns a batch of independent regions, named `name(0)`, `name(1)`, and so on.
+    ///
+    /// Each element of `assignments` assigns one region, exactly as it would with
+    /// [`Layouter::assign_region`], and the results are returned in the same order. The
+    /// regions may copy-constrain to cells from regions assigned before this call, but
+    /// not to each other.
+    ///
+    /// The default implementation assigns the regions one at a time. Floor planners that
+    /// support parallel synthesis (such as [`SimpleFloorPlanner`] and [`V1`]) instead
+    /// compute each region's values on a separate [`RegionBuffer`], spread across
+    /// threads, and then merge the buffers into the circuit in order. This makes it much
+    /// faster to synthesize circuits containing many similar regions.
+    ///
+    /// ```ignore
+    /// let cells = layouter.assign_regions(
+    ///     |i| format!("row {}", i),
+    ///     rows.iter()
+    ///         .map(|row| move |mut region: Region<'_, F>| chip.assign_row(&mut region, row))
+    ///         .collect(),
+    /// )?;
+    /// ```
+    ///
+    /// [`V1`]: floor_planner::V1
+    /// [`RegionBuffer`]: layouter::RegionBuffer
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        assignments
+            .into_iter()
+            .enumerate()
+            .map(|(i, assignment)| self.assign_region(|| name(i), assignment))
+            .collect()
+    }
+
+    /// Assign a region of gates whose shape is declared upfront by `hint`.
+    ///
+    /// This behaves like [`Layouter::assign_region`], except that floor planners may
+    /// lay out the region using the hint, without running `assignment` to measure it.
+    /// This avoids the cost of an extra pass over very tall regions, and lets the floor
+    /// planner reserve contiguous space for them. The hint must cover every column and
+    /// row that `assignment` uses; otherwise the region may overlap others.
+    ///
+    /// The default implementation calls [`Layouter::assign_region`], declaring the
+    /// hinted rows with [`Region::set_min_rows`].
+    ///
+    /// ```ignore
+    /// layouter.assign_region_with_hint(
+    ///     || "table",
+    ///     RegionHint::new(1 << 10, &[config.table.into()], &[]),
+    ///     |mut region| chip.assign_table(&mut region),
+    /// )?;
+    /// ```
+    fn assign_region_with_hint<A, N, NR>(
+        &mut self,
+        name: N,
+        hint: layouter::RegionHint,
+        mut assignment: A,
+    ) -> Result<(), Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.assign_region(name, |mut region| {
+            region.set_min_rows(hint.rows());
+            assignment(region)
+        })
+    }
+
+    /// Assigns a region of a single row, whose columns are addressed by their position
+    /// within `lane`; see [`WideRegion`].
+    fn assign_wide_region<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        lane: &Lane,
+        mut assignment: A,
+    ) -> Result<AR, Error>
+    where
+        A: FnMut(WideRegion<'_, '_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.assign_region(name, |mut region| assignment(region.wide(lane, 0)))
+    }
+
     /// Gets the "root" of this assignment, bypassing the namespacing.
     ///
     /// Not intended for downstream consumption; use [`Layouter::namespace`] instead.
@@ -262,20 +713,48 @@ impl<'a, F: Field, L: Layouter<F> + 'a> Layouter<F> for NamespacedLayouter<'a, F
         self.0.assign_region(name, assignment)
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_regions(name, assignments)
+    }
+
+    fn assign_region_with_hint<A, N, NR>(
+        &mut self,
+        name: N,
+        hint: layouter::RegionHint,
+        assignment: A,
+    ) -> Result<(), Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_region_with_hint(name, hint, assignment)
+    }
+
     fn get_root(&mut self) -> &mut Self::Root {
         self.0.get_root()
     }
 
-    fn push_namespace<NR, N>(&mut self, _name_fn: N)
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        panic!("Only the root's push_namespace should be called");
+        self.get_root().push_namespace(name_fn)
     }
 
-    fn pop_namespace(&mut self, _gadget_name: Option<String>) {
-        panic!("Only the root's pop_namespace should be called");
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.get_root().pop_namespace(gadget_name)
     }
 }
 
@@ -311,3 +790,34 @@ impl<'a, F: Field, L: Layouter<F> + 'a> Drop for NamespacedLayouter<'a, F, L> {
         self.get_root().pop_namespace(gadget_name);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::Cell;
+    use crate::plonk::{Advice, Any, Column, Fixed};
+
+    #[test]
+    fn cell() {
+        let cell = |region: usize, row_offset: usize, column: Column<Any>| Cell {
+            region_index: region.into(),
+            row_offset,
+            column,
+        };
+        let advice: Column<Any> = Column::new(1, Advice).into();
+        let fixed: Column<Any> = Column::new(1, Fixed).into();
+
+        let a = cell(2, 3, advice);
+        assert_eq!(a.to_string(), "Cell(region 2, offset 3, Advice column 1)");
+        assert_eq!(
+            a.describe(Some("mul")),
+            "Cell(region 2 ('mul'), offset 3, Advice column 1)"
+        );
+
+        let cells: HashSet<_> = vec![a, cell(2, 3, advice), cell(2, 3, fixed), cell(1, 3, advice)]
+            .into_iter()
+            .collect();
+        assert_eq!(cells.len(), 3);
+    }
+}
@@ -2,5 +2,5 @@
 
 pub(super) mod single_pass;
 
-mod v1;
-pub use v1::{V1Pass, V1};
+pub mod v1;
+pub use v1::{V1Pass, V1SinglePass, V1};
@@ -1,5 +1,6 @@
 use std::cmp;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt;
 use std::marker::PhantomData;
 
@@ -7,12 +8,12 @@ use ff::Field;
 
 use crate::{
     circuit::{
-        layouter::{RegionLayouter, RegionShape},
+        layouter::{buffer_regions, RegionHint, RegionLayouter, RegionShape},
         Cell, Layouter, Region, RegionIndex, RegionStart,
     },
     plonk::{
-        Advice, Any, Assigned, Assignment, Circuit, Column, Error, Fixed, FloorPlanner,
-        Permutation, Selector,
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Permutation, Selector,
     },
 };
 
@@ -21,6 +22,9 @@ use crate::{
 /// This floor planner is suitable for debugging circuits. It aims to reflect the circuit
 /// "business logic" in the circuit layout as closely as possible. It uses a single-pass
 /// layouter that does not reorder regions for optimal packing.
+///
+/// Constants requested by a region are assigned immediately after that region, in
+/// order, to the first fixed column enabled with [`ConstraintSystem::enable_constant`].
 #[derive(Debug)]
 pub struct SimpleFloorPlanner;
 
@@ -30,7 +34,11 @@ impl FloorPlanner for SimpleFloorPlanner {
         circuit: &C,
         config: C::Config,
     ) -> Result<(), Error> {
-        let layouter = SingleChipLayouter::new(cs)?;
+        let mut meta = ConstraintSystem::default();
+        circuit.configure_from(&mut meta);
+
+        let mut layouter = SingleChipLayouter::new(cs)?;
+        layouter.constants = meta.constants;
         circuit.synthesize(config, layouter)
     }
 }
@@ -40,8 +48,12 @@ pub struct SingleChipLayouter<'a, F: Field, CS: Assignment<F> + 'a> {
     cs: &'a mut CS,
     /// Stores the starting row for each region.
     regions: Vec<RegionStart>,
+    /// Stores the number of rows reserved for each region.
+    region_rows: Vec<usize>,
     /// Stores the first empty row for each column.
     columns: HashMap<Column<Any>, usize>,
+    /// The fixed columns that constants may be assigned to.
+    constants: Vec<Column<Fixed>>,
     _marker: PhantomData<F>,
 }
 
@@ -49,7 +61,9 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for SingleChipLayouter<'a,
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("SingleChipLayouter")
             .field("regions", &self.regions)
+            .field("region_rows", &self.region_rows)
             .field("columns", &self.columns)
+            .field("constants", &self.constants)
             .finish()
     }
 }
@@ -60,11 +74,66 @@ impl<'a, F: Field, CS: Assignment<F>> SingleChipLayouter<'a, F, CS> {
         let ret = SingleChipLayouter {
             cs,
             regions: vec![],
+            region_rows: vec![],
             columns: HashMap::default(),
+            constants: vec![],
             _marker: PhantomData,
         };
         Ok(ret)
     }
+
+    /// Positions a region after the previous regions that use any of its columns.
+    fn place_region(&mut self, shape: RegionShape) {
+        // We implement the simplest approach here: position the region starting at the
+        // earliest row for which none of the columns are in use.
+        let mut region_start = 0;
+        for column in &shape.columns {
+            region_start = cmp::max(region_start, self.columns.get(column).cloned().unwrap_or(0));
+        }
+        self.regions.push(region_start.into());
+        self.region_rows.push(shape.row_count);
+
+        // Update column usage information.
+        for column in shape.columns {
+            self.columns.insert(column, region_start + shape.row_count);
+        }
+    }
+
+    /// Assigns constants in order, to the first constants column.
+    fn assign_constants(
+        &mut self,
+        constants_to_assign: Vec<(Permutation, Assigned<F>, Cell)>,
+    ) -> Result<(), Error> {
+        if constants_to_assign.is_empty() {
+            return Ok(());
+        }
+
+        let constants_column = *self
+            .constants
+            .get(0)
+            .ok_or(Error::NotEnoughColumnsForConstants)?;
+        for (permutation, constant, cell) in constants_to_assign {
+            let row = self.columns.entry(constants_column.into()).or_default();
+            let constant_row = *row;
+            *row += 1;
+
+            self.cs.assign_fixed(
+                || format!("Constant({:?})", constant.evaluate()),
+                constants_column,
+                constant_row,
+                || Ok(constant),
+            )?;
+            self.cs.copy(
+                &permutation,
+                constants_column.into(),
+                constant_row,
+                cell.column,
+                *self.regions[*cell.region_index] + cell.row_offset,
+            )?;
+        }
+
+        Ok(())
+    }
 }
 
 impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a, F, CS> {
@@ -84,31 +153,100 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
             let region: &mut dyn RegionLayouter<F> = &mut shape;
             assignment(region.into())?;
         }
-
-        // Lay out this region. We implement the simplest approach here: position the
-        // region starting at the earliest row for which none of the columns are in use.
-        let mut region_start = 0;
-        for column in &shape.columns {
-            region_start = cmp::max(region_start, self.columns.get(column).cloned().unwrap_or(0));
-        }
-        self.regions.push(region_start.into());
-
-        // Update column usage information.
-        for column in shape.columns {
-            self.columns.insert(column, region_start + shape.row_count);
-        }
+        self.place_region(shape);
 
         self.cs.enter_region(name);
+        self.cs
+            .place_region(*self.regions[region_index], self.region_rows[region_index]);
         let mut region = SingleChipLayouterRegion::new(self, region_index.into());
         let result = {
             let region: &mut dyn RegionLayouter<F> = &mut region;
             assignment(region.into())
         }?;
+        let constants_to_assign = region.constants;
         self.cs.exit_region();
+        self.assign_constants(constants_to_assign)?;
 
         Ok(result)
     }
 
+    fn assign_region_with_hint<A, N, NR>(
+        &mut self,
+        name: N,
+        hint: RegionHint,
+        mut assignment: A,
+    ) -> Result<(), Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let region_index = self.regions.len();
+
+        // Take the shape of the region from the hint.
+        self.place_region(RegionShape::from_hint(region_index.into(), &hint));
+
+        self.cs.enter_region(name);
+        self.cs
+            .place_region(*self.regions[region_index], self.region_rows[region_index]);
+        let mut region = SingleChipLayouterRegion::new(self, region_index.into());
+        {
+            let region: &mut dyn RegionLayouter<F> = &mut region;
+            assignment(region.into())
+        }?;
+        let constants_to_assign = region.constants;
+        self.cs.exit_region();
+        self.assign_constants(constants_to_assign)
+    }
+
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        mut assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        let first_region = self.regions.len();
+
+        // Lay out the regions in order, exactly as `assign_region` would.
+        for (i, assignment) in assignments.iter_mut().enumerate() {
+            let mut shape = RegionShape::new((first_region + i).into());
+            {
+                let region: &mut dyn RegionLayouter<F> = &mut shape;
+                assignment(region.into())?;
+            }
+            self.place_region(shape);
+        }
+
+        // Compute the regions' values in parallel, and then merge them in order.
+        let mut results = Vec::with_capacity(assignments.len());
+        for (i, buffered) in buffer_regions(first_region, assignments)
+            .into_iter()
+            .enumerate()
+        {
+            let (result, buffer) = buffered?;
+
+            self.cs.enter_region(|| name(i));
+            self.cs.place_region(
+                *self.regions[first_region + i],
+                self.region_rows[first_region + i],
+            );
+            let mut region = SingleChipLayouterRegion::new(self, (first_region + i).into());
+            buffer.replay(&mut region)?;
+            let constants_to_assign = region.constants;
+            self.cs.exit_region();
+            self.assign_constants(constants_to_assign)?;
+
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+
     fn get_root(&mut self) -> &mut Self::Root {
         self
     }
@@ -129,6 +267,8 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
 struct SingleChipLayouterRegion<'r, 'a, F: Field, CS: Assignment<F> + 'a> {
     layouter: &'r mut SingleChipLayouter<'a, F, CS>,
     region_index: RegionIndex,
+    /// Stores the constants to be assigned, and the cells to which they are copied.
+    constants: Vec<(Permutation, Assigned<F>, Cell)>,
 }
 
 impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug
@@ -147,8 +287,20 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> SingleChipLayouterRegion<'r, 'a,
         SingleChipLayouterRegion {
             layouter,
             region_index,
+            constants: vec![],
         }
     }
+
+    /// Returns the cells for `len` rows of `column`, starting at `offset`.
+    fn cells(&self, column: Column<Any>, offset: usize, len: usize) -> Vec<Cell> {
+        (offset..offset + len)
+            .map(|row_offset| Cell {
+                region_index: self.region_index,
+                row_offset,
+                column,
+            })
+            .collect()
+    }
 }
 
 impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
@@ -209,6 +361,40 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
         })
     }
 
+    fn assign_advice_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<Vec<Cell>, Error> {
+        self.layouter.cs.assign_advice_slice(
+            annotation,
+            column,
+            *self.layouter.regions[*self.region_index] + offset,
+            values,
+        )?;
+
+        Ok(self.cells(column.into(), offset, values.len()))
+    }
+
+    fn assign_fixed_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        values: &[Assigned<F>],
+    ) -> Result<Vec<Cell>, Error> {
+        self.layouter.cs.assign_fixed_slice(
+            annotation,
+            column,
+            *self.layouter.regions[*self.region_index] + offset,
+            values,
+        )?;
+
+        Ok(self.cells(column.into(), offset, values.len()))
+    }
+
     fn constrain_equal(
         &mut self,
         permutation: &Permutation,
@@ -225,4 +411,34 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F>
 
         Ok(())
     }
+
+    fn constrain_constant(
+        &mut self,
+        permutation: &Permutation,
+        cell: Cell,
+        constant: Assigned<F>,
+    ) -> Result<(), Error> {
+        self.constants.push((permutation.clone(), constant, cell));
+        Ok(())
+    }
+
+    fn fixed_value(&self, cell: Cell) -> Result<Option<F>, Error> {
+        let column = Column::<Fixed>::try_from(cell.column).map_err(|_| Error::SynthesisError)?;
+        let region_start = self
+            .layouter
+            .regions
+            .get(*cell.region_index)
+            .ok_or(Error::BoundsFailure)?;
+        self.layouter
+            .cs
+            .fixed_value(column, **region_start + cell.row_offset)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.layouter.cs.query_instance(column, row)
+    }
+
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        self.layouter.cs.mark_instance_dependent()
+    }
 }
@@ -1,20 +1,26 @@
+//! The version 1 floor planner, and the strategies it uses.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt;
-use std::marker::PhantomData;
 
 use ff::Field;
 
 use crate::{
     circuit::{
-        layouter::{RegionLayouter, RegionShape},
+        layouter::{buffer_regions, RegionHint, RegionLayouter, RegionShape},
         Cell, Layouter, Region, RegionIndex, RegionStart,
     },
     plonk::{
-        Advice, Assigned, Assignment, Circuit, Column, Error, Fixed, FloorPlanner, Permutation,
-        Selector,
+        Advice, Any, Assigned, Assignment, Circuit, Column, ColumnType, ConstraintSystem, Error,
+        Fixed, FloorPlanner, Instance, Permutation, Selector,
     },
 };
 
-mod strategy;
+mod single_pass;
+pub use single_pass::V1SinglePass;
+
+pub mod strategy;
 
 /// The version 1 [`FloorPlanner`] provided by `halo2`.
 ///
@@ -24,6 +30,19 @@ mod strategy;
 /// - Regions are measured as rectangles, bounded on the cells they assign.
 /// - Regions are layed out using a greedy first-fit strategy, after sorting regions by
 ///   their "advice area" (number of advice columns * rows).
+/// - If the circuit declares [interchangeable lanes], a region that lies within one lane
+///   may instead be placed in another lane of the same group, if it can start at an
+///   earlier row there.
+/// - Constants requested by regions are collected while the regions are assigned, and
+///   then assigned to the unused rows of the [constants columns], extending the circuit
+///   beyond the regions only if those rows run out.
+/// - Regions assigned with [`Layouter::assign_region_with_hint`] are measured from their
+///   hint, without running their assignment closure.
+///
+/// [`Layouter::assign_region_with_hint`]: crate::circuit::Layouter::assign_region_with_hint
+///
+/// [interchangeable lanes]: crate::plonk::ConstraintSystem::interchangeable_lanes
+/// [constants columns]: crate::plonk::ConstraintSystem::enable_constant
 #[derive(Debug)]
 pub struct V1;
 
@@ -31,7 +50,12 @@ struct V1Plan<'a, F: Field, CS: Assignment<F> + 'a> {
     cs: &'a mut CS,
     /// Stores the starting row for each region.
     regions: Vec<RegionStart>,
-    _marker: PhantomData<F>,
+    /// Stores the number of rows reserved for each region.
+    region_rows: Vec<usize>,
+    /// Stores the columns each region has been moved to, if any.
+    column_maps: Vec<strategy::ColumnMap>,
+    /// Stores the constants to be assigned, and the cells to which they are copied.
+    constants: Vec<(Permutation, Assigned<F>, Cell)>,
 }
 
 impl<'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for V1Plan<'a, F, CS> {
@@ -46,10 +70,86 @@ impl<'a, F: Field, CS: Assignment<F>> V1Plan<'a, F, CS> {
         let ret = V1Plan {
             cs,
             regions: vec![],
-            _marker: PhantomData,
+            region_rows: vec![],
+            column_maps: vec![],
+            constants: vec![],
         };
         Ok(ret)
     }
+
+    /// Positions the measured regions, returning the resulting column allocations.
+    fn place_regions(
+        &mut self,
+        shapes: Vec<RegionShape>,
+        meta: &ConstraintSystem<F>,
+    ) -> HashMap<Column<Any>, strategy::Allocations> {
+        self.region_rows = shapes.iter().map(|shape| shape.row_count).collect();
+        let (placements, column_allocations) =
+            strategy::slot_in_biggest_advice_first_with_lanes(shapes, &meta.lanes);
+        let (regions, column_maps) = placements.into_iter().unzip();
+        self.regions = regions;
+        self.column_maps = column_maps;
+        column_allocations
+    }
+
+    /// Positions the collected constants around the regions, and assigns them.
+    fn assign_constants(
+        &mut self,
+        column_allocations: &HashMap<Column<Any>, strategy::Allocations>,
+        meta: &ConstraintSystem<F>,
+    ) -> Result<(), Error> {
+        let constants = std::mem::take(&mut self.constants);
+        if !constants.is_empty() && meta.constants.is_empty() {
+            return Err(Error::NotEnoughColumnsForConstants);
+        }
+        let constant_positions =
+            strategy::place_constants(column_allocations, &meta.constants, constants.len());
+        for ((column, row), (permutation, constant, cell)) in
+            constant_positions.into_iter().zip(constants)
+        {
+            self.cs.assign_fixed(
+                || format!("Constant({:?})", constant.evaluate()),
+                column,
+                row,
+                || Ok(constant),
+            )?;
+            self.cs.copy(
+                &permutation,
+                column.into(),
+                row,
+                cell.column,
+                *self.regions[*cell.region_index] + cell.row_offset,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the column that `column` has been moved to within the given region.
+    fn column<C>(&self, region_index: RegionIndex, column: Column<C>) -> Column<C>
+    where
+        C: ColumnType,
+        Column<C>: Into<Column<Any>> + TryFrom<Column<Any>>,
+    {
+        let key: Column<Any> = column.into();
+        match self.column_maps[*region_index].get(&key) {
+            Some(mapped) => Column::<C>::try_from(*mapped)
+                .ok()
+                .expect("lanes have matching column types"),
+            None => column,
+        }
+    }
+
+    /// Returns `cell`, with its column replaced by the column its region was moved to.
+    ///
+    /// Regions are only moved into columns outside of their original lane, so this is a
+    /// no-op for cells that already refer to the moved columns.
+    fn cell(&self, cell: Cell) -> Cell {
+        Cell {
+            column: self.column(cell.region_index, cell.column),
+            ..cell
+        }
+    }
 }
 
 impl FloorPlanner for V1 {
@@ -69,16 +169,21 @@ impl FloorPlanner for V1 {
                 .synthesize(config.clone(), V1Pass::<_, CS>::measure(pass))?;
         }
 
-        plan.regions = strategy::slot_in_biggest_advice_first(measure.regions);
+        let mut meta = ConstraintSystem::default();
+        circuit.configure_from(&mut meta);
+
+        // Planning: position the regions.
+        let column_allocations = plan.place_regions(measure.regions, &meta);
 
-        // Second pass: assign the regions.
+        // Second pass: assign the regions, collecting their constants.
         let mut assign = AssignmentPass::new(&mut plan);
         {
             let pass = &mut assign;
             circuit.synthesize(config, V1Pass::assign(pass))?;
         }
 
-        Ok(())
+        // Third pass: position the constants around the regions, and assign them.
+        plan.assign_constants(&column_allocations, &meta)
     }
 }
 
@@ -117,6 +222,46 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for V1Pass<'p, 'a, F,
         }
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        match &mut self.0 {
+            Pass::Measurement(pass) => assignments
+                .into_iter()
+                .map(|assignment| pass.assign_region(assignment))
+                .collect(),
+            Pass::Assignment(pass) => pass.assign_regions(name, assignments),
+        }
+    }
+
+    fn assign_region_with_hint<A, N, NR>(
+        &mut self,
+        name: N,
+        hint: RegionHint,
+        assignment: A,
+    ) -> Result<(), Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        match &mut self.0 {
+            Pass::Measurement(pass) => {
+                pass.assign_region_with_hint(&hint);
+                Ok(())
+            }
+            Pass::Assignment(pass) => pass.assign_region(name, assignment),
+        }
+    }
+
     fn get_root(&mut self) -> &mut Self::Root {
         self
     }
@@ -139,17 +284,42 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for V1Pass<'p, 'a, F,
 }
 
 /// Measures the circuit.
-#[derive(Debug)]
+///
+/// This is the first pass of the [`V1`] floor planner, which records the shape of each
+/// region without assigning it. It can also be used on its own, through
+/// [`MeasurementPass::measure`], by tools that need the shapes of a circuit's regions
+/// (such as layout visualizers, or estimators of the required `k`), and by custom
+/// [`Layouter`]s that measure their regions before positioning them.
+#[derive(Debug, Default)]
 pub struct MeasurementPass {
     regions: Vec<RegionShape>,
 }
 
 impl MeasurementPass {
-    fn new() -> Self {
+    /// Creates a pass that has not measured any regions.
+    pub fn new() -> Self {
         MeasurementPass { regions: vec![] }
     }
 
-    fn assign_region<F: Field, A, AR>(&mut self, mut assignment: A) -> Result<AR, Error>
+    /// Synthesizes `circuit` without witnesses, and returns the shape of each of its
+    /// regions, in the order in which they were assigned.
+    ///
+    /// Regions assigned with [`Layouter::assign_region_with_hint`] are measured from
+    /// their hints, as [`V1`] measures them.
+    pub fn measure<F: Field, C: Circuit<F>>(circuit: &C) -> Result<Vec<RegionShape>, Error> {
+        let mut meta = ConstraintSystem::default();
+        let config = circuit.configure_from(&mut meta);
+
+        let mut pass = MeasurementPass::new();
+        circuit
+            .without_witnesses()
+            .synthesize(config, MeasurementLayouter(&mut pass))?;
+        Ok(pass.regions)
+    }
+
+    /// Measures the next region by running `assignment` against a [`RegionShape`], and
+    /// returns the result of the assignment.
+    pub fn assign_region<F: Field, A, AR>(&mut self, mut assignment: A) -> Result<AR, Error>
     where
         A: FnMut(Region<'_, F>) -> Result<AR, Error>,
     {
@@ -165,6 +335,71 @@ impl MeasurementPass {
 
         Ok(result)
     }
+
+    /// Records the next region with the shape declared by `hint`.
+    pub fn assign_region_with_hint(&mut self, hint: &RegionHint) {
+        let region_index = self.regions.len();
+        self.regions
+            .push(RegionShape::from_hint(region_index.into(), hint));
+    }
+
+    /// Returns the shapes of the regions measured so far.
+    pub fn regions(&self) -> &[RegionShape] {
+        &self.regions
+    }
+
+    /// Returns the shapes of the regions that were measured.
+    pub fn into_regions(self) -> Vec<RegionShape> {
+        self.regions
+    }
+}
+
+/// A [`Layouter`] that measures every region with a [`MeasurementPass`].
+#[derive(Debug)]
+struct MeasurementLayouter<'p>(&'p mut MeasurementPass);
+
+impl<'p, F: Field> Layouter<F> for MeasurementLayouter<'p> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, _: N, assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_region(assignment)
+    }
+
+    fn assign_region_with_hint<A, N, NR>(
+        &mut self,
+        _: N,
+        hint: RegionHint,
+        _: A,
+    ) -> Result<(), Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_region_with_hint(&hint);
+        Ok(())
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; namespaces don't affect the shapes of regions.
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; namespaces don't affect the shapes of regions.
+    }
 }
 
 /// Assigns the circuit.
@@ -194,6 +429,10 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> AssignmentPass<'p, 'a, F, CS> {
         self.region_index += 1;
 
         self.plan.cs.enter_region(name);
+        self.plan.cs.place_region(
+            *self.plan.regions[region_index],
+            self.plan.region_rows[region_index],
+        );
         let mut region = V1Region::new(self.plan, region_index.into());
         let result = {
             let region: &mut dyn RegionLayouter<F> = &mut region;
@@ -203,6 +442,44 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> AssignmentPass<'p, 'a, F, CS> {
 
         Ok(result)
     }
+
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        // The regions have already been positioned, so we can compute their values in
+        // parallel, and then merge them in order.
+        let first_region = self.region_index;
+        self.region_index += assignments.len();
+
+        let mut results = Vec::with_capacity(assignments.len());
+        for (i, buffered) in buffer_regions(first_region, assignments)
+            .into_iter()
+            .enumerate()
+        {
+            let (result, buffer) = buffered?;
+
+            self.plan.cs.enter_region(|| name(i));
+            self.plan.cs.place_region(
+                *self.plan.regions[first_region + i],
+                self.plan.region_rows[first_region + i],
+            );
+            let mut region = V1Region::new(self.plan, (first_region + i).into());
+            buffer.replay(&mut region)?;
+            self.plan.cs.exit_region();
+
+            results.push(result);
+        }
+
+        Ok(results)
+    }
 }
 
 struct V1Region<'r, 'a, F: Field, CS: Assignment<F> + 'a> {
@@ -223,6 +500,17 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> V1Region<'r, 'a, F, CS> {
     fn new(plan: &'r mut V1Plan<'a, F, CS>, region_index: RegionIndex) -> Self {
         V1Region { plan, region_index }
     }
+
+    /// Returns the cells for `len` rows of `column`, starting at `offset`.
+    fn cells(&self, column: Column<Any>, offset: usize, len: usize) -> Vec<Cell> {
+        (offset..offset + len)
+            .map(|row_offset| Cell {
+                region_index: self.region_index,
+                row_offset,
+                column,
+            })
+            .collect()
+    }
 }
 
 impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r, 'a, F, CS> {
@@ -232,9 +520,10 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         selector: &Selector,
         offset: usize,
     ) -> Result<(), Error> {
+        let selector = Selector(self.plan.column(self.region_index, selector.0));
         self.plan.cs.enable_selector(
             annotation,
-            selector,
+            &selector,
             *self.plan.regions[*self.region_index] + offset,
         )
     }
@@ -246,6 +535,7 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         offset: usize,
         to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error> {
+        let column = self.plan.column(self.region_index, column);
         self.plan.cs.assign_advice(
             annotation,
             column,
@@ -267,6 +557,7 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         offset: usize,
         to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error> {
+        let column = self.plan.column(self.region_index, column);
         self.plan.cs.assign_fixed(
             annotation,
             column,
@@ -281,12 +572,52 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         })
     }
 
+    fn assign_advice_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<Vec<Cell>, Error> {
+        let column = self.plan.column(self.region_index, column);
+        self.plan.cs.assign_advice_slice(
+            annotation,
+            column,
+            *self.plan.regions[*self.region_index] + offset,
+            values,
+        )?;
+
+        Ok(self.cells(column.into(), offset, values.len()))
+    }
+
+    fn assign_fixed_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        values: &[Assigned<F>],
+    ) -> Result<Vec<Cell>, Error> {
+        let column = self.plan.column(self.region_index, column);
+        self.plan.cs.assign_fixed_slice(
+            annotation,
+            column,
+            *self.plan.regions[*self.region_index] + offset,
+            values,
+        )?;
+
+        Ok(self.cells(column.into(), offset, values.len()))
+    }
+
     fn constrain_equal(
         &mut self,
         permutation: &Permutation,
         left: Cell,
         right: Cell,
     ) -> Result<(), Error> {
+        // Cells created by a `RegionBuffer` refer to the columns requested by the
+        // circuit, rather than the columns their region was moved to.
+        let left = self.plan.cell(left);
+        let right = self.plan.cell(right);
         self.plan.cs.copy(
             permutation,
             left.column,
@@ -297,4 +628,38 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
 
         Ok(())
     }
+
+    fn constrain_constant(
+        &mut self,
+        permutation: &Permutation,
+        cell: Cell,
+        constant: Assigned<F>,
+    ) -> Result<(), Error> {
+        let cell = self.plan.cell(cell);
+        self.plan
+            .constants
+            .push((permutation.clone(), constant, cell));
+        Ok(())
+    }
+
+    fn fixed_value(&self, cell: Cell) -> Result<Option<F>, Error> {
+        let cell = self.plan.cell(cell);
+        let column = Column::<Fixed>::try_from(cell.column).map_err(|_| Error::SynthesisError)?;
+        let region_start = self
+            .plan
+            .regions
+            .get(*cell.region_index)
+            .ok_or(Error::BoundsFailure)?;
+        self.plan
+            .cs
+            .fixed_value(column, **region_start + cell.row_offset)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.plan.cs.query_instance(column, row)
+    }
+
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        self.plan.cs.mark_instance_dependent()
+    }
 }
@@ -0,0 +1,334 @@
+//! A variant of the [`V1`] floor planner that synthesizes the circuit only once.
+//!
+//! [`V1`]: super::V1
+
+use std::fmt;
+
+use ff::Field;
+
+use super::{V1Plan, V1Region};
+use crate::{
+    circuit::{
+        layouter::{buffer_regions, RegionBuffer, RegionHint, RegionLayouter, RegionShape},
+        Cell, Layouter, Region,
+    },
+    plonk::{
+        Advice, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Permutation, Selector,
+    },
+};
+
+/// A [`FloorPlanner`] that produces the same layout as [`V1`], while synthesizing the
+/// circuit only once.
+///
+/// [`V1`] synthesizes a circuit twice: once without witnesses to measure its regions,
+/// and once to assign them. `V1SinglePass` instead records the assignments of each
+/// region (computing their values) in a single synthesis, measures the regions from
+/// those records, and then positions the regions and replays their assignments. Copy
+/// constraints and constants are resolved as the regions are replayed.
+///
+/// This halves the time spent in the circuit's own synthesis code, which makes it
+/// suited to proving, at the cost of holding every assigned value in memory until all
+/// regions have been synthesized. Key generation can use either floor planner, since
+/// the layouts are identical.
+///
+/// [`V1`]: super::V1
+#[derive(Debug)]
+pub struct V1SinglePass;
+
+impl FloorPlanner for V1SinglePass {
+    fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+    ) -> Result<(), Error> {
+        let mut meta = ConstraintSystem::default();
+        circuit.configure_from(&mut meta);
+
+        // Synthesize the circuit, recording the assignments of each region.
+        let mut recording = Recording {
+            shapes: vec![],
+            buffers: vec![],
+            steps: vec![],
+        };
+        circuit.synthesize(
+            config,
+            RecordingLayouter {
+                cs: &*cs,
+                recording: &mut recording,
+            },
+        )?;
+
+        // Planning: position the regions.
+        let mut plan = V1Plan::new(cs)?;
+        let column_allocations = plan.place_regions(recording.shapes, &meta);
+
+        // Replay the regions at their positions, collecting their constants.
+        let mut buffers = recording.buffers.into_iter();
+        let mut region_index = 0;
+        for step in recording.steps {
+            match step {
+                Step::Region(name) => {
+                    let buffer = buffers.next().expect("each region step has a buffer");
+                    plan.cs.enter_region(|| name);
+                    plan.cs
+                        .place_region(*plan.regions[region_index], plan.region_rows[region_index]);
+                    let mut region = V1Region::new(&mut plan, region_index.into());
+                    buffer.replay(&mut region)?;
+                    plan.cs.exit_region();
+                    region_index += 1;
+                }
+                Step::PushNamespace(name) => plan.cs.push_namespace(|| name),
+                Step::PopNamespace(gadget_name) => plan.cs.pop_namespace(gadget_name),
+            }
+        }
+
+        // Position the constants around the regions, and assign them.
+        plan.assign_constants(&column_allocations, &meta)
+    }
+}
+
+/// A step of synthesis that is replayed once the regions have been positioned.
+#[derive(Debug)]
+enum Step {
+    /// A region with the given name, whose assignments are in the next buffer.
+    Region(String),
+    PushNamespace(String),
+    PopNamespace(Option<String>),
+}
+
+/// The regions recorded by a [`RecordingLayouter`].
+#[derive(Debug)]
+struct Recording<F: Field> {
+    /// The shape of each region, measured from its assignments or hint.
+    shapes: Vec<RegionShape>,
+    /// The assignments of each region.
+    buffers: Vec<RegionBuffer<F>>,
+    steps: Vec<Step>,
+}
+
+impl<F: Field> Recording<F> {
+    fn push_region(&mut self, name: String, shape: RegionShape, buffer: RegionBuffer<F>) {
+        self.shapes.push(shape);
+        self.buffers.push(buffer);
+        self.steps.push(Step::Region(name));
+    }
+}
+
+/// Records the regions of a circuit for [`V1SinglePass`].
+struct RecordingLayouter<'r, F: Field, CS: Assignment<F>> {
+    /// The backend, which is only used to query instance values.
+    cs: &'r CS,
+    recording: &'r mut Recording<F>,
+}
+
+impl<'r, F: Field, CS: Assignment<F>> fmt::Debug for RecordingLayouter<'r, F, CS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingLayouter")
+            .field("recording", &self.recording)
+            .finish()
+    }
+}
+
+impl<'r, F: Field, CS: Assignment<F>> RecordingLayouter<'r, F, CS> {
+    /// Runs `assignment` against a new buffer for the next region.
+    fn record<A, AR>(&mut self, mut assignment: A) -> Result<(AR, RegionBuffer<F>), Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+    {
+        let mut buffer = RegionBuffer::new(self.recording.buffers.len().into());
+        let result = {
+            let mut region = RecordingRegion {
+                buffer: &mut buffer,
+                previous: &self.recording.buffers,
+                cs: self.cs,
+            };
+            let region: &mut dyn RegionLayouter<F> = &mut region;
+            assignment(region.into())
+        }?;
+        Ok((result, buffer))
+    }
+}
+
+impl<'r, F: Field, CS: Assignment<F>> Layouter<F> for RecordingLayouter<'r, F, CS> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let (result, buffer) = self.record(assignment)?;
+        self.recording
+            .push_region(name().into(), buffer.shape(), buffer);
+        Ok(result)
+    }
+
+    fn assign_region_with_hint<A, N, NR>(
+        &mut self,
+        name: N,
+        hint: RegionHint,
+        assignment: A,
+    ) -> Result<(), Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<(), Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        // Measure the region from its hint, as `V1` does.
+        let ((), buffer) = self.record(assignment)?;
+        let shape = RegionShape::from_hint(self.recording.buffers.len().into(), &hint);
+        self.recording.push_region(name().into(), shape, buffer);
+        Ok(())
+    }
+
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn(usize) -> NR,
+        NR: Into<String>,
+    {
+        let first_region = self.recording.buffers.len();
+        let mut results = Vec::with_capacity(assignments.len());
+        for (i, buffered) in buffer_regions(first_region, assignments)
+            .into_iter()
+            .enumerate()
+        {
+            let (result, buffer) = buffered?;
+            self.recording
+                .push_region(name(i).into(), buffer.shape(), buffer);
+            results.push(result);
+        }
+        Ok(results)
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.recording
+            .steps
+            .push(Step::PushNamespace(name_fn().into()));
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.recording.steps.push(Step::PopNamespace(gadget_name));
+    }
+}
+
+/// A region that records its assignments into a buffer, while answering queries for
+/// instance values and for fixed values assigned by itself or by earlier regions.
+struct RecordingRegion<'r, F: Field, CS: Assignment<F>> {
+    buffer: &'r mut RegionBuffer<F>,
+    previous: &'r [RegionBuffer<F>],
+    cs: &'r CS,
+}
+
+impl<'r, F: Field, CS: Assignment<F>> fmt::Debug for RecordingRegion<'r, F, CS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordingRegion")
+            .field("buffer", &self.buffer)
+            .finish()
+    }
+}
+
+impl<'r, F: Field, CS: Assignment<F>> RegionLayouter<F> for RecordingRegion<'r, F, CS> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.buffer.enable_selector(annotation, selector, offset)
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<Cell, Error> {
+        self.buffer.assign_advice(annotation, column, offset, to)
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<Cell, Error> {
+        self.buffer.assign_fixed(annotation, column, offset, to)
+    }
+
+    fn assign_advice_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<Vec<Cell>, Error> {
+        self.buffer
+            .assign_advice_slice(annotation, column, offset, values)
+    }
+
+    fn assign_fixed_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        values: &[Assigned<F>],
+    ) -> Result<Vec<Cell>, Error> {
+        self.buffer
+            .assign_fixed_slice(annotation, column, offset, values)
+    }
+
+    fn constrain_equal(
+        &mut self,
+        permutation: &Permutation,
+        left: Cell,
+        right: Cell,
+    ) -> Result<(), Error> {
+        self.buffer.constrain_equal(permutation, left, right)
+    }
+
+    fn constrain_constant(
+        &mut self,
+        permutation: &Permutation,
+        cell: Cell,
+        constant: Assigned<F>,
+    ) -> Result<(), Error> {
+        self.buffer.constrain_constant(permutation, cell, constant)
+    }
+
+    fn fixed_value(&self, cell: Cell) -> Result<Option<F>, Error> {
+        match self.previous.get(*cell.region_index) {
+            Some(buffer) => buffer.fixed_value(cell),
+            None => self.buffer.fixed_value(cell),
+        }
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.cs.query_instance(column, row)
+    }
+
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        self.buffer.mark_instance_dependent()
+    }
+
+    fn set_min_rows(&mut self, rows: usize) {
+        self.buffer.set_min_rows(rows)
+    }
+}
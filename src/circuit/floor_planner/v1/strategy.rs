@@ -1,14 +1,25 @@
+//! Strategies for positioning regions and constants within a circuit.
+//!
+//! These are the building blocks of the [`V1`] floor planner, and can be reused by
+//! custom floor planners that measure their regions with [`RegionShape`].
+//!
+//! [`V1`]: super::V1
+
 use std::{
     cmp,
     collections::{BTreeSet, HashMap},
+    ops::Range,
 };
 
 use super::RegionShape;
 use crate::{
     circuit::RegionStart,
-    plonk::{Any, Column},
+    plonk::{Any, Column, Fixed, Lane},
 };
 
+/// A substitution of columns, used to move a region from one lane to another.
+pub type ColumnMap = HashMap<Column<Any>, Column<Any>>;
+
 /// A region allocated within a column.
 #[derive(Clone, Default, Debug, PartialEq, Eq)]
 struct AllocatedRegion {
@@ -31,24 +42,52 @@ impl PartialOrd for AllocatedRegion {
 }
 
 /// An area of empty space within a column.
-struct EmptySpace {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmptySpace {
     // The starting position of the empty space.
     start: usize,
-    // The number of rows of empty space, or `None` if unbounded.
+    // The row at which the empty space ends, or `None` if unbounded.
     end: Option<usize>,
 }
 
+impl EmptySpace {
+    /// Returns the first row of the empty space.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the row after the last row of the empty space, or `None` if it is
+    /// unbounded.
+    pub fn end(&self) -> Option<usize> {
+        self.end
+    }
+
+    /// Returns the rows of the empty space, or `None` if it is unbounded.
+    pub fn range(&self) -> Option<Range<usize>> {
+        self.end.map(|end| self.start..end)
+    }
+}
+
 /// Allocated rows within a column.
 ///
 /// This is a set of [a_start, a_end) pairs representing disjoint allocated intervals.
 #[derive(Clone, Default, Debug)]
-struct Allocations(BTreeSet<AllocatedRegion>);
+pub struct Allocations(BTreeSet<AllocatedRegion>);
 
 impl Allocations {
+    /// Returns the row that starts the unbounded unallocated interval.
+    pub fn unbounded_interval_start(&self) -> usize {
+        self.0
+            .iter()
+            .last()
+            .map(|r| r.start + r.length)
+            .unwrap_or(0)
+    }
+
     /// Return all the *unallocated* nonempty intervals intersecting [start, end).
     ///
     /// `end = None` represents an unbounded end.
-    fn free_intervals(
+    pub fn free_intervals(
         &self,
         start: usize,
         end: Option<usize>,
@@ -146,7 +185,10 @@ fn first_fit_region(
 
 /// Positions the regions starting at the earliest row for which none of the columns are
 /// in use, taking into account gaps between earlier regions.
-fn slot_in(region_shapes: Vec<RegionShape>) -> Vec<(RegionStart, RegionShape)> {
+///
+/// The regions are positioned in the order given; see [`sort_by_advice_area`] for a
+/// useful ordering.
+pub fn slot_in(region_shapes: Vec<RegionShape>) -> Vec<(RegionStart, RegionShape)> {
     // Tracks the empty regions for each column.
     let mut column_allocations: HashMap<Column<Any>, Allocations> = Default::default();
 
@@ -173,8 +215,105 @@ fn slot_in(region_shapes: Vec<RegionShape>) -> Vec<(RegionStart, RegionShape)> {
         .collect()
 }
 
-/// Sorts the regions by advice area and then lays them out with the [`slot_in`] strategy.
-pub fn slot_in_biggest_advice_first(region_shapes: Vec<RegionShape>) -> Vec<RegionStart> {
+/// Returns the column maps that would move the region into each lane that it could
+/// occupy, starting with the identity map.
+fn lane_candidates(region: &RegionShape, lane_groups: &[Vec<Lane>]) -> Vec<ColumnMap> {
+    let mut candidates = vec![ColumnMap::default()];
+    if region.columns().is_empty() {
+        return candidates;
+    }
+
+    for lanes in lane_groups {
+        let home = lanes.iter().find(|lane| {
+            region
+                .columns()
+                .iter()
+                .all(|column| lane.columns().contains(column))
+        });
+        if let Some(home) = home {
+            candidates.extend(lanes.iter().filter(|lane| *lane != home).map(|lane| {
+                home.columns()
+                    .iter()
+                    .cloned()
+                    .zip(lane.columns().iter().cloned())
+                    .collect()
+            }));
+        }
+    }
+
+    candidates
+}
+
+/// Positions the regions like [`slot_in`], but additionally considers moving each
+/// region into the other lanes of any lane group that contains all of its columns.
+///
+/// Each region is placed in the lane where it can start earliest; ties are broken in
+/// favour of the region's own columns, and then in lane order.
+fn slot_in_lanes(
+    region_shapes: Vec<RegionShape>,
+    lane_groups: &[Vec<Lane>],
+) -> (
+    Vec<(RegionStart, ColumnMap, RegionShape)>,
+    HashMap<Column<Any>, Allocations>,
+) {
+    // Tracks the empty regions for each column.
+    let mut column_allocations: HashMap<Column<Any>, Allocations> = Default::default();
+
+    let regions = region_shapes
+        .into_iter()
+        .map(|region| {
+            let mapped_columns = |map: &ColumnMap| {
+                // Sort the region's columns to ensure determinism (see `slot_in`).
+                let mut columns: Vec<_> = region
+                    .columns()
+                    .iter()
+                    .map(|column| *map.get(column).unwrap_or(column))
+                    .collect();
+                columns.sort_unstable();
+                columns
+            };
+
+            let mut candidates = lane_candidates(&region, lane_groups);
+            let map = if candidates.len() == 1 {
+                candidates.pop().unwrap()
+            } else {
+                // Find where the region would start in each lane, without allocating.
+                candidates
+                    .into_iter()
+                    .map(|map| {
+                        let start = first_fit_region(
+                            &mut column_allocations.clone(),
+                            &mapped_columns(&map),
+                            region.row_count(),
+                            0,
+                            None,
+                        )
+                        .expect("We can always fit a region somewhere");
+                        (start, map)
+                    })
+                    .min_by_key(|(start, _)| *start)
+                    .map(|(_, map)| map)
+                    .unwrap()
+            };
+
+            let region_start = first_fit_region(
+                &mut column_allocations,
+                &mapped_columns(&map),
+                region.row_count(),
+                0,
+                None,
+            )
+            .expect("We can always fit a region somewhere");
+
+            (region_start.into(), map, region)
+        })
+        .collect();
+
+    (regions, column_allocations)
+}
+
+/// Sorts the regions by advice area (largest first).
+pub fn sort_by_advice_area(region_shapes: Vec<RegionShape>) -> Vec<RegionShape> {
     let mut sorted_regions: Vec<_> = region_shapes.into_iter().collect();
     sorted_regions.sort_unstable_by_key(|shape| {
         // Count the number of advice columns
@@ -187,15 +326,93 @@ pub fn slot_in_biggest_advice_first(region_shapes: Vec<RegionShape>) -> Vec<Regi
         advice_cols * shape.row_count()
     });
     sorted_regions.reverse();
+    sorted_regions
+}
 
+/// Sorts the regions by advice area and then lays them out with the [`slot_in`] strategy.
+pub fn slot_in_biggest_advice_first(region_shapes: Vec<RegionShape>) -> Vec<RegionStart> {
     // Lay out the sorted regions.
-    let mut regions = slot_in(sorted_regions);
+    let mut regions = slot_in(sort_by_advice_area(region_shapes));
 
     // Un-sort the regions so they match the original indexing.
     regions.sort_unstable_by_key(|(_, region)| region.region_index().0);
     regions.into_iter().map(|(start, _)| start).collect()
 }
 
+/// Sorts the regions by advice area and then lays them out with the `slot_in_lanes`
+/// strategy, returning the start row and column map of each region, along with the
+/// resulting column allocations.
+///
+/// With no lane groups, this produces the same layout as
+/// [`slot_in_biggest_advice_first`].
+pub fn slot_in_biggest_advice_first_with_lanes(
+    region_shapes: Vec<RegionShape>,
+    lane_groups: &[Vec<Lane>],
+) -> (
+    Vec<(RegionStart, ColumnMap)>,
+    HashMap<Column<Any>, Allocations>,
+) {
+    // Lay out the sorted regions.
+    let (mut regions, column_allocations) =
+        slot_in_lanes(sort_by_advice_area(region_shapes), lane_groups);
+
+    // Un-sort the regions so they match the original indexing.
+    regions.sort_unstable_by_key(|(_, _, region)| region.region_index().0);
+    let regions = regions
+        .into_iter()
+        .map(|(start, map, _)| (start, map))
+        .collect();
+
+    (regions, column_allocations)
+}
+
+/// Chooses positions for `count` constants within the given constants columns.
+///
+/// Constants are first placed in the rows of each column (in order) that are not used by
+/// any region, up to the last row used by any column. Any remaining constants are then
+/// placed in the rows after that, filling each row across the constants columns before
+/// moving on to the next.
+///
+/// Panics if `count` is non-zero and no constants columns are given.
+pub fn place_constants(
+    column_allocations: &HashMap<Column<Any>, Allocations>,
+    constants: &[Column<Fixed>],
+    count: usize,
+) -> Vec<(Column<Fixed>, usize)> {
+    assert!(count == 0 || !constants.is_empty());
+
+    // Determine how many rows the regions require.
+    let first_unassigned_row = column_allocations
+        .values()
+        .map(|a| a.unbounded_interval_start())
+        .max()
+        .unwrap_or(0);
+
+    let mut positions: Vec<_> = constants
+        .iter()
+        .flat_map(|&column| {
+            column_allocations
+                .get(&Column::<Any>::from(column))
+                .cloned()
+                .unwrap_or_default()
+                .free_intervals(0, Some(first_unassigned_row))
+                .flat_map(|space| space.range().unwrap())
+                .map(|row| (column, row))
+                .collect::<Vec<_>>()
+        })
+        .take(count)
+        .collect();
+
+    let remaining = count - positions.len();
+    positions.extend(
+        (first_unassigned_row..)
+            .flat_map(|row| constants.iter().map(move |&column| (column, row)))
+            .take(remaining),
+    );
+
+    positions
+}
+
 #[test]
 fn test_slot_in() {
     let regions = vec![
@@ -205,11 +422,15 @@ fn test_slot_in() {
                 .into_iter()
                 .collect(),
             row_count: 15,
+            column_rows: HashMap::default(),
+            constants: 0,
         },
         RegionShape {
             region_index: 1.into(),
             columns: vec![Column::new(2, Any::Advice)].into_iter().collect(),
             row_count: 10,
+            column_rows: HashMap::default(),
+            constants: 0,
         },
         RegionShape {
             region_index: 2.into(),
@@ -217,6 +438,8 @@ fn test_slot_in() {
                 .into_iter()
                 .collect(),
             row_count: 10,
+            column_rows: HashMap::default(),
+            constants: 0,
         },
     ];
     assert_eq!(
@@ -227,3 +450,82 @@ fn test_slot_in() {
         vec![0.into(), 0.into(), 15.into()]
     );
 }
+
+#[test]
+fn test_slot_in_lanes() {
+    let a = |index| Column::new(index, Any::Advice);
+    let lanes = vec![Lane::new(&[a(0), a(1)], &[]), Lane::new(&[a(2), a(3)], &[])];
+    let regions = vec![
+        RegionShape {
+            region_index: 0.into(),
+            columns: vec![a(0), a(1)].into_iter().collect(),
+            row_count: 10,
+            column_rows: HashMap::default(),
+            constants: 0,
+        },
+        RegionShape {
+            region_index: 1.into(),
+            columns: vec![a(0)].into_iter().collect(),
+            row_count: 5,
+            column_rows: HashMap::default(),
+            constants: 0,
+        },
+    ];
+
+    // Without lanes, the second region is stacked below the first.
+    assert_eq!(
+        slot_in_biggest_advice_first_with_lanes(regions.clone(), &[])
+            .0
+            .into_iter()
+            .map(|(start, _)| start)
+            .collect::<Vec<_>>(),
+        slot_in_biggest_advice_first(regions.clone()),
+    );
+
+    // With lanes, it is moved alongside the first region.
+    let (placements, _) = slot_in_biggest_advice_first_with_lanes(regions, &[lanes]);
+    assert_eq!(placements[0], (0.into(), ColumnMap::default()));
+    assert_eq!(placements[1].0, 0.into());
+    assert_eq!(placements[1].1.get(&a(0)), Some(&a(2)));
+}
+
+#[test]
+fn test_place_constants() {
+    let a = Column::new(0, Any::Advice);
+    let f = |index| Column::<Fixed>::new(index, Fixed);
+    let regions = vec![
+        RegionShape {
+            region_index: 0.into(),
+            columns: vec![a, f(0).into()].into_iter().collect(),
+            row_count: 2,
+            column_rows: HashMap::default(),
+            constants: 0,
+        },
+        RegionShape {
+            region_index: 1.into(),
+            columns: vec![a].into_iter().collect(),
+            row_count: 3,
+            column_rows: HashMap::default(),
+            constants: 0,
+        },
+    ];
+    let (_, column_allocations) = slot_in_biggest_advice_first_with_lanes(regions, &[]);
+
+    // The larger region is placed first, so the other region (and its use of f(0)) is
+    // placed in rows 3 and 4. The constants fill the unused rows of f(0), then those of
+    // f(1), and then spill over into new rows.
+    assert_eq!(
+        place_constants(&column_allocations, &[f(0), f(1)], 9),
+        vec![
+            (f(0), 0),
+            (f(0), 1),
+            (f(0), 2),
+            (f(1), 0),
+            (f(1), 1),
+            (f(1), 2),
+            (f(1), 3),
+            (f(1), 4),
+            (f(0), 5),
+        ]
+    );
+}
@@ -1,45 +1,81 @@
 //! Implementations of common circuit layouters.
 
 use std::cmp;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
+use crossbeam_utils::thread;
 use ff::Field;
 
-use super::{Cell, RegionIndex};
+use super::{Cell, Region, RegionIndex};
 use crate::plonk::Assigned;
-use crate::plonk::{Advice, Any, Column, Error, Fixed, Permutation, Selector};
+use crate::plonk::{Advice, Any, Column, Error, Fixed, Instance, Permutation, Selector};
 
 /// Helper trait for implementing a custom [`Layouter`].
 ///
-/// This trait is used for implementing region assignments:
+/// A [`FloorPlanner`] hands the circuit a [`Layouter`], which in turn hands each region
+/// a [`Region`] wrapping some implementation of this trait. Implementations receive
+/// assignments at offsets relative to the start of the region, and are responsible for:
+///
+/// - translating them into absolute rows (and possibly different columns) before
+///   forwarding them to the underlying [`Assignment`];
+/// - returning a [`Cell`] from each assignment that records the region's index, the
+///   relative offset, and the column that was actually assigned, so that later
+///   [`RegionLayouter::constrain_equal`] calls can locate the cell;
+/// - storing the constants requested with [`RegionLayouter::constrain_constant`], and
+///   assigning them once space for them has been chosen.
+///
+/// Floor planners that need to know the size of a region before positioning it can
+/// first run the region's assignment closure against a [`RegionShape`], which implements
+/// this trait by recording the columns, rows and constants that the region uses without
+/// computing any values. For example, a layouter that places each region at the next
+/// row aligned to a power of two might look like:
 ///
 /// ```ignore
-/// impl<'a, F: FieldExt, C: Chip<F>, CS: Assignment<F> + 'a> Layouter<C> for MyLayouter<'a, C, CS> {
-///     fn assign_region(
-///         &mut self,
-///         assignment: impl FnOnce(Region<'_, F, C>) -> Result<(), Error>,
-///     ) -> Result<(), Error> {
-///         let region_index = self.regions.len();
-///         self.regions.push(self.current_gate);
-///
-///         let mut region = MyRegion::new(self, region_index);
-///         {
-///             let region: &mut dyn RegionLayouter<F> = &mut region;
-///             assignment(region.into())?;
-///         }
-///         self.current_gate += region.row_count;
-///
-///         Ok(())
+/// fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+/// where
+///     A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+///     N: Fn() -> NR,
+///     NR: Into<String>,
+/// {
+///     let region_index = self.regions.len();
+///
+///     // Measure the region.
+///     let mut shape = RegionShape::new(region_index.into());
+///     {
+///         let region: &mut dyn RegionLayouter<F> = &mut shape;
+///         assignment(region.into())?;
 ///     }
+///
+///     // Position it, and then assign it.
+///     self.regions.push(self.next_row.into());
+///     self.next_row = (self.next_row + shape.row_count()).next_power_of_two();
+///
+///     self.cs.enter_region(name);
+///     let mut region = MyRegion::new(self, region_index.into());
+///     let result = {
+///         let region: &mut dyn RegionLayouter<F> = &mut region;
+///         assignment(region.into())
+///     }?;
+///     self.cs.exit_region();
+///
+///     Ok(result)
 /// }
 /// ```
 ///
+/// The reusable placement strategies of the [`V1`] floor planner are available in
+/// [`floor_planner::v1::strategy`].
+///
 /// TODO: It would be great if we could constrain the columns in these types to be
 /// "logical" columns that are guaranteed to correspond to the chip (and have come from
 /// `Chip::Config`).
 ///
 /// [`Layouter`]: super::Layouter
+/// [`Region`]: super::Region
+/// [`FloorPlanner`]: crate::plonk::FloorPlanner
+/// [`Assignment`]: crate::plonk::Assignment
+/// [`V1`]: super::floor_planner::V1
+/// [`floor_planner::v1::strategy`]: super::floor_planner::v1::strategy
 pub trait RegionLayouter<F: Field>: fmt::Debug {
     /// Enables a selector at the given offset.
     fn enable_selector<'v>(
@@ -67,6 +103,45 @@ pub trait RegionLayouter<F: Field>: fmt::Debug {
         to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error>;
 
+    /// Assign a contiguous run of advice values (witnesses), starting at the given offset.
+    ///
+    /// `None` values are unknown. The default implementation calls
+    /// [`RegionLayouter::assign_advice`] for each value.
+    fn assign_advice_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<Vec<Cell>, Error> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                self.assign_advice(annotation, column, offset + i, &mut || {
+                    value.ok_or(Error::SynthesisError)
+                })
+            })
+            .collect()
+    }
+
+    /// Assign a contiguous run of fixed values, starting at the given offset.
+    ///
+    /// The default implementation calls [`RegionLayouter::assign_fixed`] for each value.
+    fn assign_fixed_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        values: &[Assigned<F>],
+    ) -> Result<Vec<Cell>, Error> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| self.assign_fixed(annotation, column, offset + i, &mut || Ok(*value)))
+            .collect()
+    }
+
     /// Constraint two cells to have the same value.
     ///
     /// Returns an error if either of the cells is not within the given permutation.
@@ -76,6 +151,86 @@ pub trait RegionLayouter<F: Field>: fmt::Debug {
         left: Cell,
         right: Cell,
     ) -> Result<(), Error>;
+
+    /// Constrains a cell to have a constant value.
+    ///
+    /// The layouter is responsible for assigning the constant to a fixed column that
+    /// has been enabled with [`ConstraintSystem::enable_constant`], and for copying it
+    /// into `cell` using the given permutation.
+    ///
+    /// [`ConstraintSystem::enable_constant`]: crate::plonk::ConstraintSystem::enable_constant
+    fn constrain_constant(
+        &mut self,
+        permutation: &Permutation,
+        cell: Cell,
+        constant: Assigned<F>,
+    ) -> Result<(), Error>;
+
+    /// Returns the value assigned to `cell`, which must be in a fixed column of this
+    /// region or of a region assigned before it.
+    ///
+    /// Returns `Ok(None)` if the value is not known, such as while regions are being
+    /// measured. The default implementation always does so.
+    fn fixed_value(&self, _cell: Cell) -> Result<Option<F>, Error> {
+        Ok(None)
+    }
+
+    /// Returns the value of the instance cell at absolute `row` of `column`.
+    ///
+    /// Returns `Ok(None)` if the value is not known, such as while regions are being
+    /// measured or keys are being generated. The default implementation always does so.
+    fn query_instance(&self, _column: Column<Instance>, _row: usize) -> Result<Option<F>, Error> {
+        Ok(None)
+    }
+
+    /// Marks the region as instance-dependent; see [`Region::mark_instance_dependent`].
+    ///
+    /// The default implementation does nothing.
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Declares that the region uses at least `rows` rows, even if it assigns fewer.
+    ///
+    /// The default implementation does nothing; only layouters that measure regions
+    /// (such as [`RegionShape`]) need to take this into account.
+    fn set_min_rows(&mut self, _rows: usize) {}
+}
+
+/// A declaration of the space that a region will use, provided to
+/// [`Layouter::assign_region_with_hint`] so that floor planners can lay out the region
+/// without measuring it.
+///
+/// [`Layouter::assign_region_with_hint`]: super::Layouter::assign_region_with_hint
+#[derive(Clone, Debug)]
+pub struct RegionHint {
+    rows: usize,
+    columns: Vec<Column<Any>>,
+}
+
+impl RegionHint {
+    /// Creates a hint for a region that uses `rows` rows of the given columns, and of
+    /// the fixed columns of the given selectors.
+    pub fn new(rows: usize, columns: &[Column<Any>], selectors: &[Selector]) -> Self {
+        RegionHint {
+            rows,
+            columns: columns
+                .iter()
+                .cloned()
+                .chain(selectors.iter().map(|selector| selector.0.into()))
+                .collect(),
+        }
+    }
+
+    /// Returns the number of rows declared by this hint.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the columns declared by this hint.
+    pub fn columns(&self) -> &[Column<Any>] {
+        &self.columns
+    }
 }
 
 /// The shape of a region. For a region at a certain index, we track
@@ -84,7 +239,9 @@ pub trait RegionLayouter<F: Field>: fmt::Debug {
 pub struct RegionShape {
     pub(super) region_index: RegionIndex,
     pub(super) columns: HashSet<Column<Any>>,
+    pub(super) column_rows: HashMap<Column<Any>, usize>,
     pub(super) row_count: usize,
+    pub(super) constants: usize,
 }
 
 impl RegionShape {
@@ -93,10 +250,25 @@ impl RegionShape {
         RegionShape {
             region_index,
             columns: HashSet::default(),
+            column_rows: HashMap::default(),
             row_count: 0,
+            constants: 0,
         }
     }
 
+    /// Create the `RegionShape` declared by `hint`, for a region at `region_index`.
+    ///
+    /// Every column of the hint is treated as being used for all of its rows.
+    pub fn from_hint(region_index: RegionIndex, hint: &RegionHint) -> Self {
+        let mut shape = RegionShape::new(region_index);
+        for column in hint.columns() {
+            shape.columns.insert(*column);
+            shape.column_rows.insert(*column, hint.rows());
+        }
+        shape.row_count = hint.rows();
+        shape
+    }
+
     /// Get the `region_index` of a `RegionShape`.
     pub fn region_index(&self) -> RegionIndex {
         self.region_index
@@ -111,6 +283,42 @@ impl RegionShape {
     pub fn row_count(&self) -> usize {
         self.row_count
     }
+
+    /// Get the number of rows of the given column that are used by a `RegionShape`,
+    /// counting from the start of the region, or `None` if the column is unused.
+    ///
+    /// This is at most [`RegionShape::row_count`], and can be smaller for columns that
+    /// the region only uses near its start.
+    pub fn column_row_count(&self, column: Column<Any>) -> Option<usize> {
+        self.column_rows.get(&column).cloned()
+    }
+
+    /// Records that `column` is used at `offset`.
+    fn track(&mut self, column: Column<Any>, offset: usize) {
+        self.columns.insert(column);
+        let rows = self.column_rows.entry(column).or_default();
+        *rows = cmp::max(*rows, offset + 1);
+        self.row_count = cmp::max(self.row_count, offset + 1);
+    }
+
+    /// Records that `len` rows of `column` are used from `offset`, and returns the cells.
+    fn track_slice(&mut self, column: Column<Any>, offset: usize, len: usize) -> Vec<Cell> {
+        if len > 0 {
+            self.track(column, offset + len - 1);
+        }
+        (offset..offset + len)
+            .map(|row_offset| Cell {
+                region_index: self.region_index,
+                row_offset,
+                column,
+            })
+            .collect()
+    }
+
+    /// Get the number of constants requested by a `RegionShape`.
+    pub fn constant_count(&self) -> usize {
+        self.constants
+    }
 }
 
 impl<F: Field> RegionLayouter<F> for RegionShape {
@@ -122,8 +330,7 @@ impl<F: Field> RegionLayouter<F> for RegionShape {
     ) -> Result<(), Error> {
         // Track the selector's fixed column as part of the region's shape.
         // TODO: Avoid exposing selector internals?
-        self.columns.insert(selector.0.into());
-        self.row_count = cmp::max(self.row_count, offset + 1);
+        self.track(selector.0.into(), offset);
         Ok(())
     }
 
@@ -134,8 +341,7 @@ impl<F: Field> RegionLayouter<F> for RegionShape {
         offset: usize,
         _to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error> {
-        self.columns.insert(column.into());
-        self.row_count = cmp::max(self.row_count, offset + 1);
+        self.track(column.into(), offset);
 
         Ok(Cell {
             region_index: self.region_index,
@@ -151,8 +357,7 @@ impl<F: Field> RegionLayouter<F> for RegionShape {
         offset: usize,
         _to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
     ) -> Result<Cell, Error> {
-        self.columns.insert(column.into());
-        self.row_count = cmp::max(self.row_count, offset + 1);
+        self.track(column.into(), offset);
 
         Ok(Cell {
             region_index: self.region_index,
@@ -161,6 +366,26 @@ impl<F: Field> RegionLayouter<F> for RegionShape {
         })
     }
 
+    fn assign_advice_slice<'v>(
+        &'v mut self,
+        _: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<Vec<Cell>, Error> {
+        Ok(self.track_slice(column.into(), offset, values.len()))
+    }
+
+    fn assign_fixed_slice<'v>(
+        &'v mut self,
+        _: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        values: &[Assigned<F>],
+    ) -> Result<Vec<Cell>, Error> {
+        Ok(self.track_slice(column.into(), offset, values.len()))
+    }
+
     fn constrain_equal(
         &mut self,
         _permutation: &Permutation,
@@ -170,4 +395,934 @@ impl<F: Field> RegionLayouter<F> for RegionShape {
         // Equality constraints don't affect the region shape.
         Ok(())
     }
+
+    fn constrain_constant(
+        &mut self,
+        _permutation: &Permutation,
+        _cell: Cell,
+        _constant: Assigned<F>,
+    ) -> Result<(), Error> {
+        // Constants are assigned outside of the region, but we count them so that the
+        // floor planner can reserve space for them.
+        self.constants += 1;
+        Ok(())
+    }
+
+    fn set_min_rows(&mut self, rows: usize) {
+        self.row_count = cmp::max(self.row_count, rows);
+    }
+}
+
+/// An operation recorded by a [`RegionBuffer`].
+#[derive(Debug)]
+enum BufferedOp<F: Field> {
+    EnableSelector {
+        annotation: String,
+        selector: Selector,
+        offset: usize,
+    },
+    AssignAdvice {
+        annotation: String,
+        column: Column<Advice>,
+        offset: usize,
+        value: Result<Assigned<F>, Error>,
+    },
+    AssignFixed {
+        annotation: String,
+        column: Column<Fixed>,
+        offset: usize,
+        value: Result<Assigned<F>, Error>,
+    },
+    AssignAdviceSlice {
+        annotation: String,
+        column: Column<Advice>,
+        offset: usize,
+        values: Vec<Option<Assigned<F>>>,
+    },
+    AssignFixedSlice {
+        annotation: String,
+        column: Column<Fixed>,
+        offset: usize,
+        values: Vec<Assigned<F>>,
+    },
+    ConstrainEqual {
+        permutation: Permutation,
+        left: Cell,
+        right: Cell,
+    },
+    ConstrainConstant {
+        permutation: Permutation,
+        cell: Cell,
+        constant: Assigned<F>,
+    },
+    MarkInstanceDependent,
+    SetMinRows {
+        rows: usize,
+    },
+}
+
+/// A region that records its assignments, so that they can be computed on a different
+/// thread to the one that owns the circuit's [`Assignment`].
+///
+/// Values are computed as soon as they are assigned, and the recorded operations are
+/// later applied in order to the floor planner's own [`RegionLayouter`] with
+/// [`RegionBuffer::replay`]. Cells returned by the buffer can be used with
+/// [`RegionLayouter::constrain_equal`] both within the buffered region, and (once the
+/// buffer has been replayed) in later regions.
+///
+/// See [`Layouter::assign_regions`] for how floor planners use this to synthesize
+/// regions in parallel.
+///
+/// [`Assignment`]: crate::plonk::Assignment
+/// [`Layouter::assign_regions`]: super::Layouter::assign_regions
+#[derive(Debug)]
+pub struct RegionBuffer<F: Field> {
+    region_index: RegionIndex,
+    ops: Vec<BufferedOp<F>>,
+}
+
+impl<F: Field> RegionBuffer<F> {
+    /// Creates an empty buffer for the region at `region_index`.
+    pub fn new(region_index: RegionIndex) -> Self {
+        RegionBuffer {
+            region_index,
+            ops: vec![],
+        }
+    }
+
+    /// Applies the recorded operations, in order, to the given region.
+    ///
+    /// Values are passed through unchanged, so any error that occurred while computing
+    /// an assigned value is only returned if `region` asks for that value. A value that
+    /// `region` asks for more than once is [`Error::SynthesisError`] after the first time.
+    pub fn replay(self, region: &mut dyn RegionLayouter<F>) -> Result<(), Error> {
+        for op in self.ops {
+            match op {
+                BufferedOp::EnableSelector {
+                    annotation,
+                    selector,
+                    offset,
+                } => region.enable_selector(&|| annotation.clone(), &selector, offset)?,
+                BufferedOp::AssignAdvice {
+                    annotation,
+                    column,
+                    offset,
+                    value,
+                } => {
+                    let mut value = Some(value);
+                    region.assign_advice(&|| annotation.clone(), column, offset, &mut || {
+                        value.take().unwrap_or(Err(Error::SynthesisError))
+                    })?;
+                }
+                BufferedOp::AssignFixed {
+                    annotation,
+                    column,
+                    offset,
+                    value,
+                } => {
+                    let mut value = Some(value);
+                    region.assign_fixed(&|| annotation.clone(), column, offset, &mut || {
+                        value.take().unwrap_or(Err(Error::SynthesisError))
+                    })?;
+                }
+                BufferedOp::AssignAdviceSlice {
+                    annotation,
+                    column,
+                    offset,
+                    values,
+                } => {
+                    region.assign_advice_slice(&|| annotation.clone(), column, offset, &values)?;
+                }
+                BufferedOp::AssignFixedSlice {
+                    annotation,
+                    column,
+                    offset,
+                    values,
+                } => {
+                    region.assign_fixed_slice(&|| annotation.clone(), column, offset, &values)?;
+                }
+                BufferedOp::ConstrainEqual {
+                    permutation,
+                    left,
+                    right,
+                } => region.constrain_equal(&permutation, left, right)?,
+                BufferedOp::ConstrainConstant {
+                    permutation,
+                    cell,
+                    constant,
+                } => region.constrain_constant(&permutation, cell, constant)?,
+                BufferedOp::MarkInstanceDependent => region.mark_instance_dependent()?,
+                BufferedOp::SetMinRows { rows } => region.set_min_rows(rows),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the shape of the buffered region, as a [`RegionShape`] would have
+    /// measured it from the same assignments.
+    pub fn shape(&self) -> RegionShape {
+        let mut shape = RegionShape::new(self.region_index);
+        for op in &self.ops {
+            match op {
+                BufferedOp::EnableSelector {
+                    selector, offset, ..
+                } => shape.track(selector.0.into(), *offset),
+                BufferedOp::AssignAdvice { column, offset, .. } => {
+                    shape.track((*column).into(), *offset)
+                }
+                BufferedOp::AssignFixed { column, offset, .. } => {
+                    shape.track((*column).into(), *offset)
+                }
+                BufferedOp::AssignAdviceSlice {
+                    column,
+                    offset,
+                    values,
+                    ..
+                } => {
+                    shape.track_slice((*column).into(), *offset, values.len());
+                }
+                BufferedOp::AssignFixedSlice {
+                    column,
+                    offset,
+                    values,
+                    ..
+                } => {
+                    shape.track_slice((*column).into(), *offset, values.len());
+                }
+                BufferedOp::ConstrainEqual { .. } | BufferedOp::MarkInstanceDependent => (),
+                BufferedOp::ConstrainConstant { .. } => shape.constants += 1,
+                BufferedOp::SetMinRows { rows } => {
+                    shape.row_count = cmp::max(shape.row_count, *rows)
+                }
+            }
+        }
+        shape
+    }
+
+    fn cell(&self, column: Column<Any>, offset: usize) -> Cell {
+        Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column,
+        }
+    }
+}
+
+impl<F: Field> RegionLayouter<F> for RegionBuffer<F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.ops.push(BufferedOp::EnableSelector {
+            annotation: annotation(),
+            selector: *selector,
+            offset,
+        });
+        Ok(())
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<Cell, Error> {
+        self.ops.push(BufferedOp::AssignAdvice {
+            annotation: annotation(),
+            column,
+            offset,
+            value: to(),
+        });
+        Ok(self.cell(column.into(), offset))
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<Cell, Error> {
+        self.ops.push(BufferedOp::AssignFixed {
+            annotation: annotation(),
+            column,
+            offset,
+            value: to(),
+        });
+        Ok(self.cell(column.into(), offset))
+    }
+
+    fn assign_advice_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<Vec<Cell>, Error> {
+        self.ops.push(BufferedOp::AssignAdviceSlice {
+            annotation: annotation(),
+            column,
+            offset,
+            values: values.to_vec(),
+        });
+        Ok((offset..offset + values.len())
+            .map(|offset| self.cell(column.into(), offset))
+            .collect())
+    }
+
+    fn assign_fixed_slice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        values: &[Assigned<F>],
+    ) -> Result<Vec<Cell>, Error> {
+        self.ops.push(BufferedOp::AssignFixedSlice {
+            annotation: annotation(),
+            column,
+            offset,
+            values: values.to_vec(),
+        });
+        Ok((offset..offset + values.len())
+            .map(|offset| self.cell(column.into(), offset))
+            .collect())
+    }
+
+    fn constrain_equal(
+        &mut self,
+        permutation: &Permutation,
+        left: Cell,
+        right: Cell,
+    ) -> Result<(), Error> {
+        self.ops.push(BufferedOp::ConstrainEqual {
+            permutation: permutation.clone(),
+            left,
+            right,
+        });
+        Ok(())
+    }
+
+    fn constrain_constant(
+        &mut self,
+        permutation: &Permutation,
+        cell: Cell,
+        constant: Assigned<F>,
+    ) -> Result<(), Error> {
+        self.ops.push(BufferedOp::ConstrainConstant {
+            permutation: permutation.clone(),
+            cell,
+            constant,
+        });
+        Ok(())
+    }
+
+    fn fixed_value(&self, cell: Cell) -> Result<Option<F>, Error> {
+        if *cell.region_index != *self.region_index {
+            return Ok(None);
+        }
+
+        // Later assignments to the same cell take precedence.
+        for op in self.ops.iter().rev() {
+            match op {
+                BufferedOp::AssignFixed {
+                    column,
+                    offset,
+                    value,
+                    ..
+                } if Column::<Any>::from(*column) == cell.column && *offset == cell.row_offset => {
+                    return Ok(value.as_ref().ok().map(|value| value.evaluate()));
+                }
+                BufferedOp::AssignFixedSlice {
+                    column,
+                    offset,
+                    values,
+                    ..
+                } if Column::<Any>::from(*column) == cell.column
+                    && (*offset..*offset + values.len()).contains(&cell.row_offset) =>
+                {
+                    return Ok(Some(values[cell.row_offset - *offset].evaluate()));
+                }
+                _ => (),
+            }
+        }
+        Ok(None)
+    }
+
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        self.ops.push(BufferedOp::MarkInstanceDependent);
+        Ok(())
+    }
+
+    fn set_min_rows(&mut self, rows: usize) {
+        self.ops.push(BufferedOp::SetMinRows { rows });
+    }
+}
+
+/// Runs each of the given region assignments against its own [`RegionBuffer`], spreading
+/// the assignments across threads.
+///
+/// The regions are given consecutive indices starting from `first_region`. The results
+/// are returned in the same order as `assignments`.
+pub fn buffer_regions<F, A, AR>(
+    first_region: usize,
+    mut assignments: Vec<A>,
+) -> Vec<Result<(AR, RegionBuffer<F>), Error>>
+where
+    F: Field,
+    A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+    AR: Send,
+{
+    if assignments.is_empty() {
+        return vec![];
+    }
+    let mut results: Vec<_> = assignments.iter().map(|_| None).collect();
+
+    let num_threads = num_cpus::get();
+    let chunk = (assignments.len() + num_threads - 1) / num_threads;
+    thread::scope(|scope| {
+        for (i, (assignments, results)) in assignments
+            .chunks_mut(chunk)
+            .zip(results.chunks_mut(chunk))
+            .enumerate()
+        {
+            scope.spawn(move |_| {
+                for (j, (assignment, result)) in
+                    assignments.iter_mut().zip(results.iter_mut()).enumerate()
+                {
+                    let mut buffer = RegionBuffer::new((first_region + i * chunk + j).into());
+                    let region: &mut dyn RegionLayouter<F> = &mut buffer;
+                    let ar = assignment(region.into());
+                    *result = Some(ar.map(|ar| (ar, buffer)));
+                }
+            });
+        }
+    })
+    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every region was assigned"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use ff::Field;
+    use pasta_curves::Fp;
+
+    use super::{RegionHint, RegionLayouter, RegionShape};
+    use crate::{
+        circuit::{
+            floor_planner::{v1::MeasurementPass, V1SinglePass, V1},
+            Layouter, Region, SimpleFloorPlanner,
+        },
+        dev::{FloorPlan, MockProver, RegionPlacement},
+        plonk::{
+            Advice, Any, Circuit, Column, ConstraintSystem, Error, Fixed, FloorPlanner, Instance,
+            Lane, Selector,
+        },
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct SquareConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        s: Selector,
+    }
+
+    /// Assigns each `(a, a^2)` pair in its own region, in parallel.
+    struct SquaresCircuit<P> {
+        squares: Vec<(u64, u64)>,
+        _marker: PhantomData<P>,
+    }
+
+    impl<P: FloorPlanner> Circuit<Fp> for SquaresCircuit<P> {
+        type Config = SquareConfig;
+        type FloorPlanner = P;
+
+        fn without_witnesses(&self) -> Self {
+            SquaresCircuit {
+                squares: self.squares.clone(),
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let s = meta.selector();
+
+            meta.create_gate("square", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a.clone() * a - b)]
+            });
+
+            SquareConfig { a, b, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: SquareConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let config = &config;
+            layouter.assign_regions(
+                |i| format!("square {}", i),
+                self.squares
+                    .iter()
+                    .map(|&(a, b)| {
+                        move |mut region: Region<'_, Fp>| -> Result<(), Error> {
+                            config.s.enable(&mut region, 0)?;
+                            region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(a)))?;
+                            region.assign_advice(|| "b", config.b, 0, || Ok(Fp::from(b)))?;
+                            Ok(())
+                        }
+                    })
+                    .collect(),
+            )?;
+            Ok(())
+        }
+    }
+
+    fn verify<P: FloorPlanner>(squares: Vec<(u64, u64)>) -> bool {
+        let circuit = SquaresCircuit::<P> {
+            squares,
+            _marker: PhantomData,
+        };
+        MockProver::run(6, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn parallel_regions() {
+        let squares: Vec<_> = (0..20).map(|a| (a, a * a)).collect();
+        let mut bad_squares = squares.clone();
+        bad_squares[13].1 += 1;
+
+        assert!(verify::<SimpleFloorPlanner>(squares.clone()));
+        assert!(!verify::<SimpleFloorPlanner>(bad_squares.clone()));
+        assert!(verify::<V1>(squares.clone()));
+        assert!(!verify::<V1>(bad_squares.clone()));
+        assert!(verify::<V1SinglePass>(squares.clone()));
+        assert!(!verify::<V1SinglePass>(bad_squares));
+
+        // Both V1 floor planners choose the same layout.
+        fn placements<P: FloorPlanner>(squares: Vec<(u64, u64)>) -> Vec<RegionPlacement> {
+            let circuit = SquaresCircuit::<P> {
+                squares,
+                _marker: PhantomData,
+            };
+            FloorPlan::new(&circuit).unwrap().regions().to_vec()
+        }
+        assert_eq!(
+            placements::<V1>(squares.clone()),
+            placements::<V1SinglePass>(squares)
+        );
+    }
+
+    #[derive(Clone)]
+    struct TableConfig {
+        a: Column<Advice>,
+        b: Column<Fixed>,
+        s: Selector,
+    }
+
+    /// Copies a fixed table into an advice column, one slice at a time.
+    struct TableCircuit<P> {
+        table: Vec<u64>,
+        witness: Vec<u64>,
+        _marker: PhantomData<P>,
+    }
+
+    impl<P: FloorPlanner> Circuit<Fp> for TableCircuit<P> {
+        type Config = TableConfig;
+        type FloorPlanner = P;
+
+        fn without_witnesses(&self) -> Self {
+            TableCircuit {
+                table: self.table.clone(),
+                witness: vec![],
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> TableConfig {
+            let a = meta.advice_column();
+            let b = meta.fixed_column();
+            let s = meta.selector();
+
+            meta.create_gate("copy", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_fixed(b, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a - b)]
+            });
+
+            TableConfig { a, b, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: TableConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "table",
+                |mut region| {
+                    for offset in 0..self.table.len() {
+                        config.s.enable(&mut region, offset)?;
+                    }
+                    let table: Vec<_> = self.table.iter().map(|&b| Fp::from(b)).collect();
+                    let witness: Vec<_> = if self.witness.is_empty() {
+                        vec![None; self.table.len()]
+                    } else {
+                        self.witness.iter().map(|&a| Some(Fp::from(a))).collect()
+                    };
+
+                    let cells = region.assign_fixed_from_slice(|| "b", config.b, 0, &table)?;
+                    assert_eq!(cells.len(), table.len());
+                    region.assign_advice_from_slice(|| "a", config.a, 0, &witness)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify_table<P: FloorPlanner>(witness: Vec<u64>) -> bool {
+        let circuit = TableCircuit::<P> {
+            table: (0..10).map(|b| b * 3).collect(),
+            witness,
+            _marker: PhantomData,
+        };
+        MockProver::run(5, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn slice_assignments() {
+        let witness: Vec<_> = (0..10).map(|a| a * 3).collect();
+        let mut bad_witness = witness.clone();
+        bad_witness[4] += 1;
+
+        assert!(verify_table::<SimpleFloorPlanner>(witness.clone()));
+        assert!(!verify_table::<SimpleFloorPlanner>(bad_witness.clone()));
+        assert!(verify_table::<V1>(witness.clone()));
+        assert!(!verify_table::<V1>(bad_witness.clone()));
+        assert!(verify_table::<V1SinglePass>(witness));
+        assert!(!verify_table::<V1SinglePass>(bad_witness));
+    }
+
+    #[test]
+    fn region_hints() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a: Column<Any> = meta.advice_column().into();
+        let s = meta.selector();
+
+        let shape = RegionShape::from_hint(0.into(), &RegionHint::new(10, &[a], &[s]));
+        assert_eq!(shape.row_count(), 10);
+        assert_eq!(shape.column_row_count(a), Some(10));
+        assert_eq!(shape.column_row_count(s.0.into()), Some(10));
+
+        let mut shape = RegionShape::new(0.into());
+        {
+            let mut region: Region<'_, Fp> = (&mut shape as &mut dyn RegionLayouter<Fp>).into();
+            region
+                .assign_advice(|| "a", meta.advice_column(), 2, || Ok(Fp::from(0)))
+                .unwrap();
+            region.set_min_rows(7);
+        }
+        assert_eq!(shape.row_count(), 7);
+    }
+
+    /// Reads a fixed table back from an earlier region to compute its witness.
+    struct ReadbackCircuit<P> {
+        table: Vec<u64>,
+        _marker: PhantomData<P>,
+    }
+
+    impl<P: FloorPlanner> Circuit<Fp> for ReadbackCircuit<P> {
+        type Config = TableConfig;
+        type FloorPlanner = P;
+
+        fn without_witnesses(&self) -> Self {
+            ReadbackCircuit {
+                table: self.table.clone(),
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> TableConfig {
+            TableCircuit::<P>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: TableConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let table: Vec<_> = self.table.iter().map(|&b| Fp::from(b)).collect();
+            let cells = layouter.assign_region(
+                || "table",
+                |mut region| region.assign_fixed_from_slice(|| "b", config.b, 0, &table),
+            )?;
+
+            layouter.assign_region(
+                || "readback",
+                |mut region| {
+                    for (offset, cell) in cells.iter().enumerate() {
+                        let value = region.fixed_value(*cell)?;
+                        if let Some(value) = value {
+                            assert_eq!(value, table[offset]);
+                        }
+                        region.assign_advice(
+                            || "a",
+                            config.a,
+                            offset,
+                            || value.ok_or(Error::SynthesisError),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn fixed_values() {
+        fn verify_readback<P: FloorPlanner>() -> bool {
+            let circuit = ReadbackCircuit::<P> {
+                table: (0..10).map(|b| b * 5).collect(),
+                _marker: PhantomData,
+            };
+            MockProver::run(5, &circuit, vec![])
+                .unwrap()
+                .verify()
+                .is_ok()
+        }
+
+        assert!(verify_readback::<SimpleFloorPlanner>());
+        assert!(verify_readback::<V1>());
+        assert!(verify_readback::<V1SinglePass>());
+    }
+
+    #[derive(Clone)]
+    struct HalvingConfig {
+        a: Column<Advice>,
+        i: Column<Instance>,
+        s: Selector,
+    }
+
+    /// Witnesses the halves of its public inputs.
+    struct HalvingCircuit<P> {
+        rows: usize,
+        _marker: PhantomData<P>,
+    }
+
+    impl<P: FloorPlanner> Circuit<Fp> for HalvingCircuit<P> {
+        type Config = HalvingConfig;
+        type FloorPlanner = P;
+
+        fn without_witnesses(&self) -> Self {
+            HalvingCircuit {
+                rows: self.rows,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> HalvingConfig {
+            let a = meta.advice_column();
+            let i = meta.instance_column();
+            let s = meta.selector();
+
+            meta.create_gate("halve", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let i = meta.query_instance(i, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a.clone() + a - i)]
+            });
+
+            HalvingConfig { a, i, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: HalvingConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "halves",
+                |mut region| {
+                    let half = Fp::from(2).invert().unwrap();
+                    for row in 0..self.rows {
+                        config.s.enable(&mut region, row)?;
+                        let value = region.query_instance(config.i, row)?;
+                        region.assign_advice(
+                            || "a",
+                            config.a,
+                            row,
+                            || value.map(|v| v * half).ok_or(Error::SynthesisError),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn instance_values() {
+        fn verify_halving<P: FloorPlanner>() -> bool {
+            let circuit = HalvingCircuit::<P> {
+                rows: 10,
+                _marker: PhantomData,
+            };
+            let instance = (0..32).map(|i| Fp::from(i * 6)).collect();
+            MockProver::run(5, &circuit, vec![instance])
+                .unwrap()
+                .verify()
+                .is_ok()
+        }
+
+        assert!(verify_halving::<SimpleFloorPlanner>());
+        assert!(verify_halving::<V1>());
+        assert!(verify_halving::<V1SinglePass>());
+    }
+
+    #[derive(Clone)]
+    struct SumConfig {
+        lane: Lane,
+    }
+
+    /// Assigns `a + b = c` in each of a list of wide regions, in a lane that can be
+    /// swapped for another.
+    struct SumCircuit<P> {
+        sums: Vec<(u64, u64, u64)>,
+        _marker: PhantomData<P>,
+    }
+
+    impl<P: FloorPlanner> Circuit<Fp> for SumCircuit<P> {
+        type Config = SumConfig;
+        type FloorPlanner = P;
+
+        fn without_witnesses(&self) -> Self {
+            SumCircuit {
+                sums: self.sums.clone(),
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SumConfig {
+            let mut lane = || {
+                let columns: Vec<Column<Any>> =
+                    (0..3).map(|_| meta.advice_column().into()).collect();
+                let s = meta.selector();
+
+                meta.create_gate("sum", |meta| {
+                    let a = meta.query_any(columns[0], Rotation::cur());
+                    let b = meta.query_any(columns[1], Rotation::cur());
+                    let c = meta.query_any(columns[2], Rotation::cur());
+                    let s = meta.query_selector(s);
+                    vec![s * (a + b - c)]
+                });
+
+                Lane::new(&columns, &[s])
+            };
+            let lanes = vec![lane(), lane()];
+            meta.interchangeable_lanes(lanes.clone());
+
+            SumConfig {
+                lane: lanes[0].clone(),
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: SumConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            for &(a, b, c) in &self.sums {
+                layouter.assign_wide_region(
+                    || "sum",
+                    &config.lane,
+                    |mut row| {
+                        assert_eq!(row.width(), 4);
+                        row.enable_selector(|| "sum", 3)?;
+                        for (index, value) in [a, b, c].iter().enumerate() {
+                            row.assign_advice(|| "value", index, || Ok(Fp::from(*value)))?;
+                        }
+
+                        // Positions are checked against the lane.
+                        assert!(matches!(
+                            row.assign_fixed(|| "advice", 0, || Ok(Fp::zero())),
+                            Err(Error::SynthesisError)
+                        ));
+                        assert!(matches!(
+                            row.assign_advice(|| "missing", 4, || Ok(Fp::zero())),
+                            Err(Error::BoundsFailure)
+                        ));
+                        Ok(())
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn wide_regions() {
+        fn starts<P: FloorPlanner>(sums: Vec<(u64, u64, u64)>) -> Vec<usize> {
+            let circuit = SumCircuit::<P> {
+                sums: sums.clone(),
+                _marker: PhantomData,
+            };
+            assert_eq!(
+                MockProver::run(4, &circuit, vec![])
+                    .unwrap()
+                    .verify()
+                    .is_ok(),
+                sums.iter().all(|(a, b, c)| a + b == *c)
+            );
+            FloorPlan::new(&circuit)
+                .unwrap()
+                .regions()
+                .iter()
+                .map(|region| region.start)
+                .collect()
+        }
+
+        let sums = vec![(1, 2, 3), (4, 5, 9)];
+        assert_eq!(starts::<SimpleFloorPlanner>(sums.clone()), vec![0, 1]);
+        // V1 moves the second region into the other lane.
+        assert_eq!(starts::<V1>(sums), vec![0, 0]);
+        assert_eq!(starts::<V1>(vec![(1, 2, 3), (4, 5, 10)]), vec![0, 0]);
+    }
+
+    #[test]
+    fn measurement() {
+        let circuit = SumCircuit::<SimpleFloorPlanner> {
+            sums: vec![(1, 2, 3), (4, 5, 9)],
+            _marker: PhantomData,
+        };
+        let shapes = MeasurementPass::measure(&circuit).unwrap();
+        assert_eq!(shapes.len(), 2);
+        for (index, shape) in shapes.iter().enumerate() {
+            assert_eq!(*shape.region_index(), index);
+            assert_eq!(shape.row_count(), 1);
+            assert_eq!(shape.columns().len(), 4);
+            assert_eq!(shape.constant_count(), 0);
+        }
+    }
 }
@@ -0,0 +1,115 @@
+//! Elliptic curves, other than the Pasta curves, that implement the curve traits in
+//! [`arithmetic`](crate::arithmetic).
+//!
+//! The proving system needs the scalar field of the curve to have a multiplicative
+//! subgroup of order $2^k$ for each $k$ up to the size of the extended evaluation
+//! domain of a circuit (see [`TwoAdicField`]). Keygen returns
+//! [`Error::IncompatibleParams`] for circuits that are too large for the scalar field
+//! of their curve. In particular, the scalar fields of grumpkin and secq256k1 only
+//! have a subgroup of order $2$, so those curves can be used with the polynomial
+//! commitment scheme, but not to prove circuits.
+//!
+//! [`TwoAdicField`]: crate::arithmetic::TwoAdicField
+//! [`Error::IncompatibleParams`]: crate::plonk::Error::IncompatibleParams
+
+use ff::PrimeField;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+#[macro_use]
+mod macros;
+
+pub mod bn256;
+pub mod grumpkin;
+#[cfg(feature = "secp256k1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "secp256k1")))]
+pub mod secp256k1;
+#[cfg(feature = "secp256k1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "secp256k1")))]
+pub mod secq256k1;
+
+/// The compressed SEC1 encoding of a point on [`secp256k1`] or [`secq256k1`].
+#[cfg(feature = "secp256k1")]
+#[cfg_attr(docsrs, doc(cfg(feature = "secp256k1")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sec1Compressed(pub [u8; 33]);
+
+#[cfg(feature = "secp256k1")]
+impl Default for Sec1Compressed {
+    fn default() -> Self {
+        Sec1Compressed([0; 33])
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl AsRef<[u8]> for Sec1Compressed {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl AsMut<[u8]> for Sec1Compressed {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+/// Computes `a + b + carry`, returning the result and the new carry over.
+#[inline(always)]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let ret = (a as u128) + (b as u128) + (carry as u128);
+    (ret as u64, (ret >> 64) as u64)
+}
+
+/// Computes `a - (b + borrow)`, returning the result and the new borrow.
+#[inline(always)]
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let ret = (a as u128).wrapping_sub((b as u128) + ((borrow >> 63) as u128));
+    (ret as u64, (ret >> 64) as u64)
+}
+
+/// Computes `a + (b * c) + carry`, returning the result and the new carry over.
+#[inline(always)]
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let ret = (a as u128) + ((b as u128) * (c as u128)) + (carry as u128);
+    (ret as u64, (ret >> 64) as u64)
+}
+
+/// Computes the square root of `f` in constant time with the Tonelli-Shanks algorithm,
+/// given `(t - 1) / 2`, where `p - 1 = t * 2^S` with `t` odd.
+fn sqrt_tonelli_shanks<F: PrimeField + ConstantTimeEq>(f: &F, tm1d2: &[u64; 4]) -> CtOption<F> {
+    // w = self^((t - 1) // 2)
+    let w = f.pow_vartime(tm1d2);
+
+    let mut v = F::S;
+    let mut x = w * f;
+    let mut b = x * w;
+
+    // Initialize z as the 2^S root of unity.
+    let mut z = F::root_of_unity();
+
+    for max_v in (1..=F::S).rev() {
+        let mut k = 1;
+        let mut tmp = b.square();
+        let mut j_less_than_v: Choice = 1.into();
+
+        for j in 2..max_v {
+            let tmp_is_one = tmp.ct_eq(&F::one());
+            let squared = F::conditional_select(&tmp, &z, tmp_is_one).square();
+            tmp = F::conditional_select(&squared, &tmp, tmp_is_one);
+            let new_z = F::conditional_select(&z, &squared, tmp_is_one);
+            j_less_than_v &= !j.ct_eq(&v);
+            k = u32::conditional_select(&j, &k, tmp_is_one);
+            z = F::conditional_select(&z, &new_z, j_less_than_v);
+        }
+
+        let result = x * z;
+        x = F::conditional_select(&result, &x, b.ct_eq(&F::one()));
+        z = z.square();
+        b *= z;
+        v = k;
+    }
+
+    // Only return Some if it's the square root.
+    CtOption::new(x, (x * x).ct_eq(f))
+}
@@ -0,0 +1,198 @@
+//! The BN254 curve, also known as alt_bn128, which is the curve of the precompiles
+//! for pairings on Ethereum.
+//!
+//! Only the group $\mathbb{G}_1$ is implemented: the curve $y^2 = x^3 + 3$ over
+//! [`Fq`], whose group of points has the prime order of [`Fr`]. Its scalar field has a
+//! multiplicative subgroup of order $2^{28}$, so it can prove circuits of up to
+//! $2^{28}$ rows, less the extension for the degree of their constraints.
+
+new_field!(
+    /// The base field of BN254, whose modulus is
+    /// `0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47`.
+    Fq,
+    modulus: [
+        0x3c208c16d87cfd47,
+        0x97816a916871ca8d,
+        0xb85045b68181585d,
+        0x30644e72e131a029,
+    ],
+    modulus_str: "0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+    inv: 0x87d20782e4866389,
+    r: [
+        0xd35d438dc58f0d9d,
+        0x0a78eb28f5c70b3d,
+        0x666ea36f7879462c,
+        0x0e0a77c19a07df2f,
+    ],
+    r2: [
+        0xf32cfc5b538afa89,
+        0xb5e71911d44501fb,
+        0x47ab1eff0a417ff6,
+        0x06d89f71cab8351f,
+    ],
+    r3: [
+        0xb1cd6dafda1530df,
+        0x62f210e6a7283db6,
+        0xef7f0b0c0ada0afb,
+        0x20fd6e902d592544,
+    ],
+    num_bits: 254,
+    s: 1,
+    // 3, in Montgomery form
+    generator: [
+        0x7a17caa950ad28d7,
+        0x1f6ac17ae15521b9,
+        0x334bea4e696bd284,
+        0x2a1f6744ce179d8e,
+    ],
+    root_of_unity: [
+        0x68c3488912edefaa,
+        0x8d087f6872aabf4f,
+        0x51e1a24709081231,
+        0x2259d6b14729c0fa,
+    ],
+    root_of_unity_inv: [
+        0x68c3488912edefaa,
+        0x8d087f6872aabf4f,
+        0x51e1a24709081231,
+        0x2259d6b14729c0fa,
+    ],
+    delta: [
+        0xf60647ce410d7ff7,
+        0x2f3d6f4dd31bd011,
+        0x2943337e3940c6d1,
+        0x1d9598e8a7e39857,
+    ],
+    zeta: [
+        0x3350c88e13e80b9c,
+        0x7dce557cdb5e56b9,
+        0x6001b4b8b615564a,
+        0x2682e617020217e0,
+    ],
+    two_inv: [
+        0x87bee7d24f060572,
+        0xd0fd2add2f1c6ae5,
+        0x8f5f7492fcfd4f44,
+        0x1f37631a3d9cbfac,
+    ],
+    t_minus1_over2: [
+        0x4f082305b61f3f51,
+        0x65e05aa45a1c72a3,
+        0x6e14116da0605617,
+        0x0c19139cb84c680a,
+    ],
+);
+
+new_field!(
+    /// The scalar field of BN254, whose modulus is
+    /// `0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001`.
+    Fr,
+    modulus: [
+        0x43e1f593f0000001,
+        0x2833e84879b97091,
+        0xb85045b68181585d,
+        0x30644e72e131a029,
+    ],
+    modulus_str: "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+    inv: 0xc2e1f593efffffff,
+    r: [
+        0xac96341c4ffffffb,
+        0x36fc76959f60cd29,
+        0x666ea36f7879462e,
+        0x0e0a77c19a07df2f,
+    ],
+    r2: [
+        0x1bb8e645ae216da7,
+        0x53fe3ab1e35c59e3,
+        0x8c49833d53bb8085,
+        0x0216d0b17f4e44a5,
+    ],
+    r3: [
+        0x5e94d8e1b4bf0040,
+        0x2a489cbe1cfbb6b8,
+        0x893cc664a19fcfed,
+        0x0cf8594b7fcc657c,
+    ],
+    num_bits: 254,
+    s: 28,
+    // 5, in Montgomery form
+    generator: [
+        0x1b0d0ef99fffffe6,
+        0xeaba68a3a32a913f,
+        0x47d8eb76d8dd0689,
+        0x15d0085520f5bbc3,
+    ],
+    root_of_unity: [
+        0x636e735580d13d9c,
+        0xa22bf3742445ffd6,
+        0x56452ac01eb203d8,
+        0x1860ef942963f9e7,
+    ],
+    root_of_unity_inv: [
+        0x89bcc016584bb683,
+        0xe8d9887f0164a50c,
+        0x755e95cb795eda3d,
+        0x0f572b871323b130,
+    ],
+    delta: [
+        0xb1132acfdd0ede60,
+        0xbb4b2bd501254442,
+        0xb3559919ba247a31,
+        0x1a7adfe2c8b1068c,
+    ],
+    zeta: [
+        0x93e7cede4a0329b3,
+        0x7d4fdca77a96c167,
+        0x8be4ba08b19a750a,
+        0x1cbd5653a5661c25,
+    ],
+    two_inv: [
+        0x783c14d81ffffffe,
+        0xaf982f6f0c8d1edd,
+        0x8f5f7492fcfd4f45,
+        0x1f37631a3d9cbfac,
+    ],
+    t_minus1_over2: [
+        0xcdcb848a1f0fac9f,
+        0x0c0ac2e9419f4243,
+        0x098d014dc2822db4,
+        0x0000000183227397,
+    ],
+);
+
+new_curve!(
+    /// A point of $\mathbb{G}_1$ of BN254, in Jacobian coordinates.
+    G1,
+    /// A point of $\mathbb{G}_1$ of BN254, in affine coordinates.
+    G1Affine,
+    base: Fq,
+    scalar: Fr,
+    curve_id: "bn256_g1",
+    // 3, in Montgomery form
+    b: [
+        0x7a17caa950ad28d7,
+        0x1f6ac17ae15521b9,
+        0x334bea4e696bd284,
+        0x2a1f6744ce179d8e,
+    ],
+    generator: ([
+        0xd35d438dc58f0d9d,
+        0x0a78eb28f5c70b3d,
+        0x666ea36f7879462c,
+        0x0e0a77c19a07df2f,
+    ], [
+        0xa6ba871b8b1e1b3a,
+        0x14f1d651eb8e167b,
+        0xccdd46def0f28c58,
+        0x1c14ef83340fbe5e,
+    ]),
+    encoding: spare_bits,
+);
+
+#[test]
+fn test_field_constants() {
+    use crate::arithmetic::check_field_constants;
+
+    assert_eq!(check_field_constants::<Fq>(), Ok(()));
+    assert_eq!(check_field_constants::<Fr>(), Ok(()));
+}
@@ -0,0 +1,36 @@
+//! The grumpkin curve, $y^2 = x^3 - 17$ over the scalar field of BN254, which forms a
+//! cycle with BN254: the scalar field of each curve is the base field of the other.
+//!
+//! The scalar field of grumpkin is the base field of BN254, which only has a
+//! multiplicative subgroup of order $2$, so grumpkin cannot prove circuits.
+
+pub use super::bn256::{Fq as Fr, Fr as Fq};
+
+new_curve!(
+    /// A point of grumpkin, in Jacobian coordinates.
+    G1,
+    /// A point of grumpkin, in affine coordinates.
+    G1Affine,
+    base: Fq,
+    scalar: Fr,
+    curve_id: "grumpkin",
+    // -17, in Montgomery form
+    b: [
+        0xdd7056026000005a,
+        0x223fa97acb319311,
+        0xcc388229877910c0,
+        0x034394632b724eaa,
+    ],
+    generator: ([
+        0xac96341c4ffffffb,
+        0x36fc76959f60cd29,
+        0x666ea36f7879462e,
+        0x0e0a77c19a07df2f,
+    ], [
+        0x11b2dff1448c41d8,
+        0x23d3446f21c77dc3,
+        0xaa7b8cf435dfafbb,
+        0x14b34cf69dc25d68,
+    ]),
+    encoding: spare_bits,
+);
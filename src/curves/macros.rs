@@ -0,0 +1,1517 @@
+//! Macros that implement prime fields in Montgomery form, and short Weierstrass curves
+//! with `a = 0` in Jacobian coordinates, over four 64-bit limbs.
+//!
+//! The arithmetic handles moduli up to `2^256`, so the results of additions and
+//! Montgomery reductions may carry into a fifth limb before they are reduced.
+
+macro_rules! impl_binops_additive {
+    ($lhs:ident, $rhs:ident, $output:ident) => {
+        impl<'b> ::core::ops::Add<&'b $rhs> for $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn add(self, rhs: &'b $rhs) -> $output {
+                &self + rhs
+            }
+        }
+
+        impl<'a> ::core::ops::Add<$rhs> for &'a $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn add(self, rhs: $rhs) -> $output {
+                self + &rhs
+            }
+        }
+
+        impl ::core::ops::Add<$rhs> for $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn add(self, rhs: $rhs) -> $output {
+                &self + &rhs
+            }
+        }
+
+        impl<'b> ::core::ops::Sub<&'b $rhs> for $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn sub(self, rhs: &'b $rhs) -> $output {
+                &self - rhs
+            }
+        }
+
+        impl<'a> ::core::ops::Sub<$rhs> for &'a $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn sub(self, rhs: $rhs) -> $output {
+                self - &rhs
+            }
+        }
+
+        impl ::core::ops::Sub<$rhs> for $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn sub(self, rhs: $rhs) -> $output {
+                &self - &rhs
+            }
+        }
+    };
+}
+
+macro_rules! impl_binops_additive_assign {
+    ($lhs:ident, $rhs:ident) => {
+        impl_binops_additive!($lhs, $rhs, $lhs);
+
+        impl ::core::ops::AddAssign<$rhs> for $lhs {
+            #[inline]
+            fn add_assign(&mut self, rhs: $rhs) {
+                *self = &*self + &rhs;
+            }
+        }
+
+        impl<'b> ::core::ops::AddAssign<&'b $rhs> for $lhs {
+            #[inline]
+            fn add_assign(&mut self, rhs: &'b $rhs) {
+                *self = &*self + rhs;
+            }
+        }
+
+        impl ::core::ops::SubAssign<$rhs> for $lhs {
+            #[inline]
+            fn sub_assign(&mut self, rhs: $rhs) {
+                *self = &*self - &rhs;
+            }
+        }
+
+        impl<'b> ::core::ops::SubAssign<&'b $rhs> for $lhs {
+            #[inline]
+            fn sub_assign(&mut self, rhs: &'b $rhs) {
+                *self = &*self - rhs;
+            }
+        }
+    };
+}
+
+macro_rules! impl_binops_multiplicative {
+    ($lhs:ident, $rhs:ident, $output:ident) => {
+        impl<'b> ::core::ops::Mul<&'b $rhs> for $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn mul(self, rhs: &'b $rhs) -> $output {
+                &self * rhs
+            }
+        }
+
+        impl<'a> ::core::ops::Mul<$rhs> for &'a $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn mul(self, rhs: $rhs) -> $output {
+                self * &rhs
+            }
+        }
+
+        impl ::core::ops::Mul<$rhs> for $lhs {
+            type Output = $output;
+
+            #[inline]
+            fn mul(self, rhs: $rhs) -> $output {
+                &self * &rhs
+            }
+        }
+    };
+}
+
+macro_rules! impl_binops_multiplicative_assign {
+    ($lhs:ident, $rhs:ident) => {
+        impl_binops_multiplicative!($lhs, $rhs, $lhs);
+
+        impl ::core::ops::MulAssign<$rhs> for $lhs {
+            #[inline]
+            fn mul_assign(&mut self, rhs: $rhs) {
+                *self = &*self * &rhs;
+            }
+        }
+
+        impl<'b> ::core::ops::MulAssign<&'b $rhs> for $lhs {
+            #[inline]
+            fn mul_assign(&mut self, rhs: &'b $rhs) {
+                *self = &*self * rhs;
+            }
+        }
+    };
+}
+
+/// Implements a prime field of at most 256 bits. All of the constants except for the
+/// modulus, `inv` and `t_minus1_over2` are the limbs of elements in Montgomery form.
+macro_rules! new_field {
+    (
+        $(#[$attr:meta])*
+        $field:ident,
+        modulus: $modulus:expr,
+        modulus_str: $modulus_str:expr,
+        inv: $inv:expr,
+        r: $r:expr,
+        r2: $r2:expr,
+        r3: $r3:expr,
+        num_bits: $num_bits:expr,
+        s: $s:expr,
+        generator: $generator:expr,
+        root_of_unity: $root_of_unity:expr,
+        root_of_unity_inv: $root_of_unity_inv:expr,
+        delta: $delta:expr,
+        zeta: $zeta:expr,
+        two_inv: $two_inv:expr,
+        t_minus1_over2: $t_minus1_over2:expr,
+    ) => {
+        $(#[$attr])*
+        // The internal representation is four 64-bit limbs in little-endian order, in
+        // Montgomery form; i.e. a is represented by aR mod p, with R = 2^256.
+        #[derive(Clone, Copy, Eq)]
+        pub struct $field(pub(crate) [u64; 4]);
+
+        impl $field {
+            const MODULUS: [u64; 4] = $modulus;
+            /// `-(p^{-1} mod 2^64) mod 2^64`
+            const INV: u64 = $inv;
+            /// `(t - 1) / 2`, where `p - 1 = t * 2^S` with `t` odd.
+            const T_MINUS1_OVER2: [u64; 4] = $t_minus1_over2;
+
+            /// Returns zero, the additive identity.
+            #[inline]
+            pub fn zero() -> Self {
+                $field([0, 0, 0, 0])
+            }
+
+            /// Returns one, the multiplicative identity.
+            #[inline]
+            pub fn one() -> Self {
+                // R = 2^256 mod p
+                $field($r)
+            }
+
+            /// Converts from an integer represented by little-endian limbs, which must
+            /// be smaller than the modulus.
+            fn from_raw(limbs: [u64; 4]) -> Self {
+                // R^2 = 2^512 mod p
+                $field(limbs).mul(&$field($r2))
+            }
+
+            fn from_u512(limbs: [u64; 8]) -> Self {
+                // The lower and upper halves are each smaller than R, so they can be
+                // converted by multiplying them by R^2 and R^3 = 2^768 mod p
+                // respectively, which are in the field.
+                let lo = $field([limbs[0], limbs[1], limbs[2], limbs[3]]);
+                let hi = $field([limbs[4], limbs[5], limbs[6], limbs[7]]);
+                lo.mul(&$field($r2)).add(&hi.mul(&$field($r3)))
+            }
+
+            /// Subtracts the modulus from `2^256 carry + limbs` if it is not smaller
+            /// than the modulus. The value must be smaller than twice the modulus.
+            #[inline(always)]
+            fn subtract_modulus(carry: u64, limbs: [u64; 4]) -> Self {
+                use $crate::curves::sbb;
+
+                let (d0, borrow) = sbb(limbs[0], Self::MODULUS[0], 0);
+                let (d1, borrow) = sbb(limbs[1], Self::MODULUS[1], borrow);
+                let (d2, borrow) = sbb(limbs[2], Self::MODULUS[2], borrow);
+                let (d3, borrow) = sbb(limbs[3], Self::MODULUS[3], borrow);
+                let (_, borrow) = sbb(carry, 0, borrow);
+
+                // If the value was smaller than the modulus, the subtraction underflowed
+                // and borrow = 0xfff...fff, so we keep the original value.
+                $field([
+                    (limbs[0] & borrow) | (d0 & !borrow),
+                    (limbs[1] & borrow) | (d1 & !borrow),
+                    (limbs[2] & borrow) | (d2 & !borrow),
+                    (limbs[3] & borrow) | (d3 & !borrow),
+                ])
+            }
+
+            #[inline(always)]
+            fn montgomery_reduce(r: [u64; 8]) -> Self {
+                use $crate::curves::{adc, mac};
+
+                // The Montgomery reduction here is based on Algorithm 14.32 in
+                // Handbook of Applied Cryptography
+                // <http://cacr.uwaterloo.ca/hac/about/chap14.pdf>.
+                let m = Self::MODULUS;
+
+                let k = r[0].wrapping_mul(Self::INV);
+                let (_, carry) = mac(r[0], k, m[0], 0);
+                let (r1, carry) = mac(r[1], k, m[1], carry);
+                let (r2, carry) = mac(r[2], k, m[2], carry);
+                let (r3, carry) = mac(r[3], k, m[3], carry);
+                let (r4, carry2) = adc(r[4], 0, carry);
+
+                let k = r1.wrapping_mul(Self::INV);
+                let (_, carry) = mac(r1, k, m[0], 0);
+                let (r2, carry) = mac(r2, k, m[1], carry);
+                let (r3, carry) = mac(r3, k, m[2], carry);
+                let (r4, carry) = mac(r4, k, m[3], carry);
+                let (r5, carry2) = adc(r[5], carry2, carry);
+
+                let k = r2.wrapping_mul(Self::INV);
+                let (_, carry) = mac(r2, k, m[0], 0);
+                let (r3, carry) = mac(r3, k, m[1], carry);
+                let (r4, carry) = mac(r4, k, m[2], carry);
+                let (r5, carry) = mac(r5, k, m[3], carry);
+                let (r6, carry2) = adc(r[6], carry2, carry);
+
+                let k = r3.wrapping_mul(Self::INV);
+                let (_, carry) = mac(r3, k, m[0], 0);
+                let (r4, carry) = mac(r4, k, m[1], carry);
+                let (r5, carry) = mac(r5, k, m[2], carry);
+                let (r6, carry) = mac(r6, k, m[3], carry);
+                let (r7, carry2) = adc(r[7], carry2, carry);
+
+                // The result is smaller than twice the modulus, but may not fit in four
+                // limbs if the modulus is close to 2^256.
+                Self::subtract_modulus(carry2, [r4, r5, r6, r7])
+            }
+
+            /// Multiplies `rhs` by `self`, returning the result.
+            #[inline]
+            pub fn mul(&self, rhs: &Self) -> Self {
+                use $crate::curves::mac;
+
+                // Schoolbook multiplication
+                let (a, b) = (self.0, rhs.0);
+
+                let (r0, carry) = mac(0, a[0], b[0], 0);
+                let (r1, carry) = mac(0, a[0], b[1], carry);
+                let (r2, carry) = mac(0, a[0], b[2], carry);
+                let (r3, r4) = mac(0, a[0], b[3], carry);
+
+                let (r1, carry) = mac(r1, a[1], b[0], 0);
+                let (r2, carry) = mac(r2, a[1], b[1], carry);
+                let (r3, carry) = mac(r3, a[1], b[2], carry);
+                let (r4, r5) = mac(r4, a[1], b[3], carry);
+
+                let (r2, carry) = mac(r2, a[2], b[0], 0);
+                let (r3, carry) = mac(r3, a[2], b[1], carry);
+                let (r4, carry) = mac(r4, a[2], b[2], carry);
+                let (r5, r6) = mac(r5, a[2], b[3], carry);
+
+                let (r3, carry) = mac(r3, a[3], b[0], 0);
+                let (r4, carry) = mac(r4, a[3], b[1], carry);
+                let (r5, carry) = mac(r5, a[3], b[2], carry);
+                let (r6, r7) = mac(r6, a[3], b[3], carry);
+
+                Self::montgomery_reduce([r0, r1, r2, r3, r4, r5, r6, r7])
+            }
+
+            /// Squares this element.
+            #[inline]
+            pub fn square(&self) -> Self {
+                self.mul(self)
+            }
+
+            /// Adds `rhs` to `self`, returning the result.
+            #[inline]
+            pub fn add(&self, rhs: &Self) -> Self {
+                use $crate::curves::adc;
+
+                let (d0, carry) = adc(self.0[0], rhs.0[0], 0);
+                let (d1, carry) = adc(self.0[1], rhs.0[1], carry);
+                let (d2, carry) = adc(self.0[2], rhs.0[2], carry);
+                let (d3, carry) = adc(self.0[3], rhs.0[3], carry);
+
+                Self::subtract_modulus(carry, [d0, d1, d2, d3])
+            }
+
+            /// Doubles this element.
+            #[inline]
+            pub fn double(&self) -> Self {
+                self.add(self)
+            }
+
+            /// Subtracts `rhs` from `self`, returning the result.
+            #[inline]
+            pub fn sub(&self, rhs: &Self) -> Self {
+                use $crate::curves::{adc, sbb};
+
+                let m = Self::MODULUS;
+                let (d0, borrow) = sbb(self.0[0], rhs.0[0], 0);
+                let (d1, borrow) = sbb(self.0[1], rhs.0[1], borrow);
+                let (d2, borrow) = sbb(self.0[2], rhs.0[2], borrow);
+                let (d3, borrow) = sbb(self.0[3], rhs.0[3], borrow);
+
+                // If underflow occurred on the final limb, borrow = 0xfff...fff, otherwise
+                // borrow = 0x000...000. Thus, we use it as a mask to conditionally add the
+                // modulus.
+                let (d0, carry) = adc(d0, m[0] & borrow, 0);
+                let (d1, carry) = adc(d1, m[1] & borrow, carry);
+                let (d2, carry) = adc(d2, m[2] & borrow, carry);
+                let (d3, _) = adc(d3, m[3] & borrow, carry);
+
+                $field([d0, d1, d2, d3])
+            }
+
+            /// Negates `self`.
+            #[inline]
+            pub fn neg(&self) -> Self {
+                $field::zero().sub(self)
+            }
+
+            fn canonical_limbs(&self) -> [u64; 4] {
+                let limbs = self.0;
+                Self::montgomery_reduce([limbs[0], limbs[1], limbs[2], limbs[3], 0, 0, 0, 0]).0
+            }
+
+            pub(crate) fn from_canonical_bytes(bytes: &[u8; 32]) -> ::subtle::CtOption<Self> {
+                use $crate::curves::sbb;
+
+                let mut limbs = [0u64; 4];
+                for (limb, bytes) in limbs.iter_mut().zip(bytes.chunks(8)) {
+                    let mut buf = [0u8; 8];
+                    buf.copy_from_slice(bytes);
+                    *limb = u64::from_le_bytes(buf);
+                }
+
+                // Try to subtract the modulus; this underflows, producing a borrow of
+                // 0xfff...fff, only if the value is smaller than the modulus.
+                let m = Self::MODULUS;
+                let (_, borrow) = sbb(limbs[0], m[0], 0);
+                let (_, borrow) = sbb(limbs[1], m[1], borrow);
+                let (_, borrow) = sbb(limbs[2], m[2], borrow);
+                let (_, borrow) = sbb(limbs[3], m[3], borrow);
+
+                ::subtle::CtOption::new(
+                    $field::from_raw(limbs),
+                    ::subtle::Choice::from((borrow as u8) & 1),
+                )
+            }
+
+            pub(crate) fn canonical_bytes(&self) -> [u8; 32] {
+                let mut bytes = [0u8; 32];
+                for (bytes, limb) in bytes.chunks_mut(8).zip(self.canonical_limbs().iter()) {
+                    bytes.copy_from_slice(&limb.to_le_bytes());
+                }
+                bytes
+            }
+        }
+
+        impl ::core::fmt::Debug for $field {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                write!(f, "0x")?;
+                for b in self.canonical_bytes().iter().rev() {
+                    write!(f, "{:02x}", b)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl Default for $field {
+            #[inline]
+            fn default() -> Self {
+                $field::zero()
+            }
+        }
+
+        impl From<u64> for $field {
+            fn from(value: u64) -> Self {
+                $field::from_raw([value, 0, 0, 0])
+            }
+        }
+
+        impl ::subtle::ConstantTimeEq for $field {
+            fn ct_eq(&self, other: &Self) -> ::subtle::Choice {
+                self.0[0].ct_eq(&other.0[0])
+                    & self.0[1].ct_eq(&other.0[1])
+                    & self.0[2].ct_eq(&other.0[2])
+                    & self.0[3].ct_eq(&other.0[3])
+            }
+        }
+
+        impl PartialEq for $field {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                ::subtle::ConstantTimeEq::ct_eq(self, other).into()
+            }
+        }
+
+        impl Ord for $field {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                let left = self.canonical_limbs();
+                let right = other.canonical_limbs();
+                left.iter().rev().cmp(right.iter().rev())
+            }
+        }
+
+        impl PartialOrd for $field {
+            fn partial_cmp(&self, other: &Self) -> Option<::core::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl ::subtle::ConditionallySelectable for $field {
+            fn conditional_select(a: &Self, b: &Self, choice: ::subtle::Choice) -> Self {
+                $field([
+                    u64::conditional_select(&a.0[0], &b.0[0], choice),
+                    u64::conditional_select(&a.0[1], &b.0[1], choice),
+                    u64::conditional_select(&a.0[2], &b.0[2], choice),
+                    u64::conditional_select(&a.0[3], &b.0[3], choice),
+                ])
+            }
+        }
+
+        impl<'a> ::core::ops::Neg for &'a $field {
+            type Output = $field;
+
+            #[inline]
+            fn neg(self) -> $field {
+                $field::neg(self)
+            }
+        }
+
+        impl ::core::ops::Neg for $field {
+            type Output = $field;
+
+            #[inline]
+            fn neg(self) -> $field {
+                $field::neg(&self)
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Add<&'b $field> for &'a $field {
+            type Output = $field;
+
+            #[inline]
+            fn add(self, rhs: &'b $field) -> $field {
+                $field::add(self, rhs)
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Sub<&'b $field> for &'a $field {
+            type Output = $field;
+
+            #[inline]
+            fn sub(self, rhs: &'b $field) -> $field {
+                $field::sub(self, rhs)
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Mul<&'b $field> for &'a $field {
+            type Output = $field;
+
+            #[inline]
+            fn mul(self, rhs: &'b $field) -> $field {
+                $field::mul(self, rhs)
+            }
+        }
+
+        impl_binops_additive_assign!($field, $field);
+        impl_binops_multiplicative_assign!($field, $field);
+
+        impl ::ff::Field for $field {
+            fn random(mut rng: impl ::rand::RngCore) -> Self {
+                let mut limbs = [0u64; 8];
+                for limb in limbs.iter_mut() {
+                    *limb = rng.next_u64();
+                }
+                $field::from_u512(limbs)
+            }
+
+            fn zero() -> Self {
+                $field::zero()
+            }
+
+            fn one() -> Self {
+                $field::one()
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == $field::zero()
+            }
+
+            fn square(&self) -> Self {
+                $field::square(self)
+            }
+
+            fn double(&self) -> Self {
+                $field::double(self)
+            }
+
+            fn invert(&self) -> ::subtle::CtOption<Self> {
+                use $crate::curves::sbb;
+
+                // By Fermat's little theorem, self^(p - 2) is the inverse of self.
+                let m = Self::MODULUS;
+                let (e0, borrow) = sbb(m[0], 2, 0);
+                let (e1, borrow) = sbb(m[1], 0, borrow);
+                let (e2, borrow) = sbb(m[2], 0, borrow);
+                let (e3, _) = sbb(m[3], 0, borrow);
+
+                ::subtle::CtOption::new(
+                    self.pow_vartime(&[e0, e1, e2, e3]),
+                    !::subtle::ConstantTimeEq::ct_eq(self, &$field::zero()),
+                )
+            }
+
+            fn sqrt(&self) -> ::subtle::CtOption<Self> {
+                $crate::curves::sqrt_tonelli_shanks(self, &Self::T_MINUS1_OVER2)
+            }
+        }
+
+        impl ::ff::PrimeField for $field {
+            type Repr = [u8; 32];
+
+            const NUM_BITS: u32 = $num_bits;
+            const CAPACITY: u32 = $num_bits - 1;
+            const S: u32 = $s;
+
+            fn from_repr(repr: Self::Repr) -> Option<Self> {
+                $field::from_canonical_bytes(&repr).into()
+            }
+
+            fn to_repr(&self) -> Self::Repr {
+                self.canonical_bytes()
+            }
+
+            fn is_odd(&self) -> bool {
+                self.canonical_limbs()[0] & 1 == 1
+            }
+
+            fn multiplicative_generator() -> Self {
+                $field($generator)
+            }
+
+            fn root_of_unity() -> Self {
+                $field($root_of_unity)
+            }
+        }
+
+        impl $crate::arithmetic::TwoAdicField for $field {
+            fn root_of_unity_inv() -> Self {
+                $field($root_of_unity_inv)
+            }
+
+            fn delta() -> Self {
+                $field($delta)
+            }
+
+            fn two_inv() -> Self {
+                $field($two_inv)
+            }
+        }
+
+        impl $crate::arithmetic::CubeRootOfUnity for $field {
+            fn zeta() -> Self {
+                $field($zeta)
+            }
+        }
+
+        impl $crate::arithmetic::CanonicalEncoding for $field {
+            fn modulus() -> &'static str {
+                $modulus_str
+            }
+
+            fn to_bytes(&self) -> [u8; 32] {
+                self.canonical_bytes()
+            }
+
+            fn from_bytes(bytes: &[u8; 32]) -> ::subtle::CtOption<Self> {
+                $field::from_canonical_bytes(bytes)
+            }
+
+            fn from_bytes_wide(bytes: &[u8; 64]) -> Self {
+                let mut limbs = [0u64; 8];
+                for (limb, bytes) in limbs.iter_mut().zip(bytes.chunks(8)) {
+                    let mut buf = [0u8; 8];
+                    buf.copy_from_slice(bytes);
+                    *limb = u64::from_le_bytes(buf);
+                }
+                $field::from_u512(limbs)
+            }
+        }
+    };
+}
+
+/// Implements the curve `y^2 = x^3 + b`, which must have prime order, in Jacobian
+/// coordinates, with the points in affine coordinates as its affine representation.
+/// The constants are the limbs of base field elements in Montgomery form.
+macro_rules! new_curve {
+    (
+        $(#[$attr:meta])*
+        $name:ident,
+        $(#[$affine_attr:meta])*
+        $affine:ident,
+        base: $base:ident,
+        scalar: $scalar:ident,
+        curve_id: $curve_id:expr,
+        b: $b:expr,
+        generator: ($gen_x:expr, $gen_y:expr),
+        encoding: $encoding:ident,
+    ) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Debug)]
+        pub struct $name {
+            x: $base,
+            y: $base,
+            z: $base,
+        }
+
+        $(#[$affine_attr])*
+        #[derive(Copy, Clone)]
+        pub struct $affine {
+            x: $base,
+            y: $base,
+        }
+
+        impl $name {
+            fn curve_constant_b() -> $base {
+                $base($b)
+            }
+
+            /// Returns the identity of the group: the point at infinity.
+            pub fn identity() -> Self {
+                $name {
+                    x: $base::zero(),
+                    y: $base::zero(),
+                    z: $base::zero(),
+                }
+            }
+
+            /// Returns whether or not this point is the identity.
+            pub fn is_identity(&self) -> ::subtle::Choice {
+                ::subtle::ConstantTimeEq::ct_eq(&self.z, &$base::zero())
+            }
+
+            /// Returns a fixed generator of the group.
+            pub fn generator() -> Self {
+                $affine::generator().to_curve()
+            }
+
+            /// Doubles this point.
+            pub fn double(&self) -> Self {
+                // http://www.hyperelliptic.org/EFD/g1p/auto-shortw-jacobian-0.html#doubling-dbl-2009-l
+                //
+                // There are no points of order 2.
+
+                let a = self.x.square();
+                let b = self.y.square();
+                let c = b.square();
+                let d = self.x + b;
+                let d = d.square();
+                let d = d - a - c;
+                let d = d + d;
+                let e = a + a + a;
+                let f = e.square();
+                let z3 = self.z * self.y;
+                let z3 = z3 + z3;
+                let x3 = f - (d + d);
+                let c = c + c;
+                let c = c + c;
+                let c = c + c;
+                let y3 = e * (d - x3) - c;
+
+                let tmp = $name {
+                    x: x3,
+                    y: y3,
+                    z: z3,
+                };
+
+                ::subtle::ConditionallySelectable::conditional_select(
+                    &tmp,
+                    &$name::identity(),
+                    self.is_identity(),
+                )
+            }
+
+            /// Converts this point to affine coordinates.
+            pub fn to_affine(&self) -> $affine {
+                let zinv = ::ff::Field::invert(&self.z).unwrap_or_else($base::zero);
+                let zinv2 = zinv.square();
+                let x = self.x * zinv2;
+                let zinv3 = zinv2 * zinv;
+                let y = self.y * zinv3;
+
+                let tmp = $affine { x, y };
+
+                ::subtle::ConditionallySelectable::conditional_select(
+                    &tmp,
+                    &$affine::identity(),
+                    self.is_identity(),
+                )
+            }
+
+            /// Multiplies this point by a scalar, with double-and-add over the bits of
+            /// the scalar from the most significant.
+            fn multiply(&self, scalar: &[u8; 32]) -> Self {
+                let mut acc = $name::identity();
+                for bit in scalar.iter().rev().flat_map(|byte| {
+                    (0..8)
+                        .rev()
+                        .map(move |i| ::subtle::Choice::from((byte >> i) & 1u8))
+                }) {
+                    acc = acc.double();
+                    acc = ::subtle::ConditionallySelectable::conditional_select(
+                        &acc,
+                        &(acc + self),
+                        bit,
+                    );
+                }
+                acc
+            }
+        }
+
+        impl $affine {
+            /// Returns the identity of the group: the point at infinity.
+            pub fn identity() -> Self {
+                $affine {
+                    x: $base::zero(),
+                    y: $base::zero(),
+                }
+            }
+
+            /// Returns whether or not this point is the identity.
+            pub fn is_identity(&self) -> ::subtle::Choice {
+                ::subtle::ConstantTimeEq::ct_eq(&self.x, &$base::zero())
+                    & ::subtle::ConstantTimeEq::ct_eq(&self.y, &$base::zero())
+            }
+
+            /// Returns a fixed generator of the group.
+            pub fn generator() -> Self {
+                $affine {
+                    x: $base($gen_x),
+                    y: $base($gen_y),
+                }
+            }
+
+            /// Converts this point to Jacobian coordinates.
+            pub fn to_curve(&self) -> $name {
+                $name {
+                    x: self.x,
+                    y: self.y,
+                    z: ::subtle::ConditionallySelectable::conditional_select(
+                        &$base::one(),
+                        &$base::zero(),
+                        self.is_identity(),
+                    ),
+                }
+            }
+
+            fn is_on_curve(&self) -> ::subtle::Choice {
+                // y^2 - x^3 ?= b
+                ::subtle::ConstantTimeEq::ct_eq(
+                    &(self.y.square() - self.x.square() * self.x),
+                    &$name::curve_constant_b(),
+                ) | self.is_identity()
+            }
+
+            /// Returns the point with the given x-coordinate whose y-coordinate has the
+            /// given sign, if there is one.
+            fn from_x(x: $base, y_is_odd: ::subtle::Choice) -> ::subtle::CtOption<Self> {
+                let y2 = x.square() * x + $name::curve_constant_b();
+                ::ff::Field::sqrt(&y2).map(|y| {
+                    let sign = ::subtle::Choice::from(::ff::PrimeField::is_odd(&y) as u8);
+                    $affine {
+                        x,
+                        y: ::subtle::ConditionallySelectable::conditional_select(
+                            &y,
+                            &-y,
+                            y_is_odd ^ sign,
+                        ),
+                    }
+                })
+            }
+        }
+
+        impl ::core::fmt::Debug for $affine {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                if bool::from(self.is_identity()) {
+                    write!(f, "Infinity")
+                } else {
+                    write!(f, "({:?}, {:?})", self.x, self.y)
+                }
+            }
+        }
+
+        impl ::group::Group for $name {
+            type Scalar = $scalar;
+
+            fn random(mut rng: impl ::rand::RngCore) -> Self {
+                loop {
+                    let x = <$base as ::ff::Field>::random(&mut rng);
+                    let y_is_odd = ::subtle::Choice::from((rng.next_u32() % 2) as u8);
+                    let p = $affine::from_x(x, y_is_odd);
+                    if bool::from(p.is_some()) {
+                        break p.unwrap().to_curve();
+                    }
+                }
+            }
+
+            fn identity() -> Self {
+                $name::identity()
+            }
+
+            fn generator() -> Self {
+                $name::generator()
+            }
+
+            fn is_identity(&self) -> ::subtle::Choice {
+                $name::is_identity(self)
+            }
+
+            fn double(&self) -> Self {
+                $name::double(self)
+            }
+        }
+
+        impl ::group::Curve for $name {
+            type AffineRepr = $affine;
+
+            fn batch_normalize(p: &[Self], q: &mut [Self::AffineRepr]) {
+                assert_eq!(p.len(), q.len());
+
+                let mut acc = $base::one();
+                for (p, q) in p.iter().zip(q.iter_mut()) {
+                    // We use the `x` field of the affine point to store the product of
+                    // previous z-coordinates seen.
+                    q.x = acc;
+
+                    // We will end up skipping all identities in p
+                    acc = ::subtle::ConditionallySelectable::conditional_select(
+                        &(acc * p.z),
+                        &acc,
+                        p.is_identity(),
+                    );
+                }
+
+                // This is the inverse, as all z-coordinates are nonzero and the ones
+                // that are not are skipped.
+                acc = ::ff::Field::invert(&acc).unwrap();
+
+                for (p, q) in p.iter().rev().zip(q.iter_mut().rev()) {
+                    let skip = p.is_identity();
+
+                    // Compute tmp = 1/z
+                    let tmp = q.x * acc;
+
+                    // Cancel out z-coordinate in denominator of `acc`
+                    acc = ::subtle::ConditionallySelectable::conditional_select(
+                        &(acc * p.z),
+                        &acc,
+                        skip,
+                    );
+
+                    // Set the coordinates to the correct value
+                    let tmp2 = tmp.square();
+                    let tmp3 = tmp2 * tmp;
+
+                    q.x = p.x * tmp2;
+                    q.y = p.y * tmp3;
+
+                    *q = ::subtle::ConditionallySelectable::conditional_select(
+                        &*q,
+                        &$affine::identity(),
+                        skip,
+                    );
+                }
+            }
+
+            fn to_affine(&self) -> Self::AffineRepr {
+                $name::to_affine(self)
+            }
+        }
+
+        impl ::group::prime::PrimeGroup for $name {}
+
+        impl ::group::prime::PrimeCurve for $name {
+            type Affine = $affine;
+        }
+
+        impl ::group::prime::PrimeCurveAffine for $affine {
+            type Scalar = $scalar;
+            type Curve = $name;
+
+            fn identity() -> Self {
+                $affine::identity()
+            }
+
+            fn generator() -> Self {
+                $affine::generator()
+            }
+
+            fn is_identity(&self) -> ::subtle::Choice {
+                $affine::is_identity(self)
+            }
+
+            fn to_curve(&self) -> $name {
+                $affine::to_curve(self)
+            }
+        }
+
+        impl ::group::GroupEncoding for $name {
+            type Repr = <$affine as ::group::GroupEncoding>::Repr;
+
+            fn from_bytes(bytes: &Self::Repr) -> ::subtle::CtOption<Self> {
+                <$affine as ::group::GroupEncoding>::from_bytes(bytes).map(|p| p.to_curve())
+            }
+
+            fn from_bytes_unchecked(bytes: &Self::Repr) -> ::subtle::CtOption<Self> {
+                // We can't avoid curve checks when parsing a compressed encoding.
+                <Self as ::group::GroupEncoding>::from_bytes(bytes)
+            }
+
+            fn to_bytes(&self) -> Self::Repr {
+                ::group::GroupEncoding::to_bytes(&self.to_affine())
+            }
+        }
+
+        impl_encoding!($name, $affine, $base, $encoding);
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name::identity()
+            }
+        }
+
+        impl Default for $affine {
+            fn default() -> Self {
+                $affine::identity()
+            }
+        }
+
+        impl From<$affine> for $name {
+            fn from(p: $affine) -> $name {
+                p.to_curve()
+            }
+        }
+
+        impl<'a> From<&'a $affine> for $name {
+            fn from(p: &'a $affine) -> $name {
+                p.to_curve()
+            }
+        }
+
+        impl From<$name> for $affine {
+            fn from(p: $name) -> $affine {
+                p.to_affine()
+            }
+        }
+
+        impl<'a> From<&'a $name> for $affine {
+            fn from(p: &'a $name) -> $affine {
+                p.to_affine()
+            }
+        }
+
+        impl ::subtle::ConstantTimeEq for $name {
+            fn ct_eq(&self, other: &Self) -> ::subtle::Choice {
+                // Is (xz^2, yz^3, z) equal to (x'z'^2, yz'^3, z') when converted to affine?
+
+                let z = other.z.square();
+                let x1 = self.x * z;
+                let z = z * other.z;
+                let y1 = self.y * z;
+                let z = self.z.square();
+                let x2 = other.x * z;
+                let z = z * self.z;
+                let y2 = other.y * z;
+
+                let self_is_zero = self.is_identity();
+                let other_is_zero = other.is_identity();
+
+                // Both points are at infinity, or neither is and their coordinates are
+                // the same.
+                (self_is_zero & other_is_zero)
+                    | ((!self_is_zero)
+                        & (!other_is_zero)
+                        & ::subtle::ConstantTimeEq::ct_eq(&x1, &x2)
+                        & ::subtle::ConstantTimeEq::ct_eq(&y1, &y2))
+            }
+        }
+
+        impl ::subtle::ConstantTimeEq for $affine {
+            fn ct_eq(&self, other: &Self) -> ::subtle::Choice {
+                ::subtle::ConstantTimeEq::ct_eq(&self.x, &other.x)
+                    & ::subtle::ConstantTimeEq::ct_eq(&self.y, &other.y)
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                ::subtle::ConstantTimeEq::ct_eq(self, other).into()
+            }
+        }
+
+        impl PartialEq for $affine {
+            fn eq(&self, other: &Self) -> bool {
+                ::subtle::ConstantTimeEq::ct_eq(self, other).into()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl Eq for $affine {}
+
+        impl ::subtle::ConditionallySelectable for $name {
+            fn conditional_select(a: &Self, b: &Self, choice: ::subtle::Choice) -> Self {
+                $name {
+                    x: $base::conditional_select(&a.x, &b.x, choice),
+                    y: $base::conditional_select(&a.y, &b.y, choice),
+                    z: $base::conditional_select(&a.z, &b.z, choice),
+                }
+            }
+        }
+
+        impl ::subtle::ConditionallySelectable for $affine {
+            fn conditional_select(a: &Self, b: &Self, choice: ::subtle::Choice) -> Self {
+                $affine {
+                    x: $base::conditional_select(&a.x, &b.x, choice),
+                    y: $base::conditional_select(&a.y, &b.y, choice),
+                }
+            }
+        }
+
+        impl<'a> ::core::ops::Neg for &'a $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name {
+                    x: self.x,
+                    y: -self.y,
+                    z: self.z,
+                }
+            }
+        }
+
+        impl ::core::ops::Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                -&self
+            }
+        }
+
+        impl<'a> ::core::ops::Neg for &'a $affine {
+            type Output = $affine;
+
+            fn neg(self) -> $affine {
+                $affine {
+                    x: self.x,
+                    y: -self.y,
+                }
+            }
+        }
+
+        impl ::core::ops::Neg for $affine {
+            type Output = $affine;
+
+            fn neg(self) -> $affine {
+                -&self
+            }
+        }
+
+        impl<T> ::core::iter::Sum<T> for $name
+        where
+            T: ::core::borrow::Borrow<$name>,
+        {
+            fn sum<I>(iter: I) -> Self
+            where
+                I: Iterator<Item = T>,
+            {
+                iter.fold($name::identity(), |acc, item| acc + item.borrow())
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Add<&'a $name> for &'b $name {
+            type Output = $name;
+
+            fn add(self, rhs: &'a $name) -> $name {
+                if bool::from(self.is_identity()) {
+                    *rhs
+                } else if bool::from(rhs.is_identity()) {
+                    *self
+                } else {
+                    let z1z1 = self.z.square();
+                    let z2z2 = rhs.z.square();
+                    let u1 = self.x * z2z2;
+                    let u2 = rhs.x * z1z1;
+                    let s1 = self.y * z2z2 * rhs.z;
+                    let s2 = rhs.y * z1z1 * self.z;
+
+                    if u1 == u2 {
+                        if s1 == s2 {
+                            self.double()
+                        } else {
+                            $name::identity()
+                        }
+                    } else {
+                        let h = u2 - u1;
+                        let i = (h + h).square();
+                        let j = h * i;
+                        let r = s2 - s1;
+                        let r = r + r;
+                        let v = u1 * i;
+                        let x3 = r.square() - j - v - v;
+                        let s1 = s1 * j;
+                        let s1 = s1 + s1;
+                        let y3 = r * (v - x3) - s1;
+                        let z3 = (self.z + rhs.z).square() - z1z1 - z2z2;
+                        let z3 = z3 * h;
+
+                        $name {
+                            x: x3,
+                            y: y3,
+                            z: z3,
+                        }
+                    }
+                }
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Add<&'a $affine> for &'b $name {
+            type Output = $name;
+
+            fn add(self, rhs: &'a $affine) -> $name {
+                if bool::from(self.is_identity()) {
+                    rhs.to_curve()
+                } else if bool::from(rhs.is_identity()) {
+                    *self
+                } else {
+                    let z1z1 = self.z.square();
+                    let u2 = rhs.x * z1z1;
+                    let s2 = rhs.y * z1z1 * self.z;
+
+                    if self.x == u2 {
+                        if self.y == s2 {
+                            self.double()
+                        } else {
+                            $name::identity()
+                        }
+                    } else {
+                        let h = u2 - self.x;
+                        let hh = h.square();
+                        let i = hh + hh;
+                        let i = i + i;
+                        let j = h * i;
+                        let r = s2 - self.y;
+                        let r = r + r;
+                        let v = self.x * i;
+                        let x3 = r.square() - j - v - v;
+                        let j = self.y * j;
+                        let j = j + j;
+                        let y3 = r * (v - x3) - j;
+                        let z3 = (self.z + h).square() - z1z1 - hh;
+
+                        $name {
+                            x: x3,
+                            y: y3,
+                            z: z3,
+                        }
+                    }
+                }
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Add<&'a $name> for &'b $affine {
+            type Output = $name;
+
+            fn add(self, rhs: &'a $name) -> $name {
+                rhs + self
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Add<&'a $affine> for &'b $affine {
+            type Output = $name;
+
+            fn add(self, rhs: &'a $affine) -> $name {
+                self.to_curve() + rhs
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Sub<&'a $name> for &'b $name {
+            type Output = $name;
+
+            fn sub(self, other: &'a $name) -> $name {
+                self + (-other)
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Sub<&'a $affine> for &'b $name {
+            type Output = $name;
+
+            fn sub(self, other: &'a $affine) -> $name {
+                self + (-other)
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Sub<&'a $name> for &'b $affine {
+            type Output = $name;
+
+            fn sub(self, other: &'a $name) -> $name {
+                self + (-other)
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Sub<&'a $affine> for &'b $affine {
+            type Output = $name;
+
+            fn sub(self, other: &'a $affine) -> $name {
+                self + (-other)
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Mul<&'b $scalar> for &'a $name {
+            type Output = $name;
+
+            fn mul(self, other: &'b $scalar) -> $name {
+                self.multiply(&::ff::PrimeField::to_repr(other))
+            }
+        }
+
+        impl<'a, 'b> ::core::ops::Mul<&'b $scalar> for &'a $affine {
+            type Output = $name;
+
+            fn mul(self, other: &'b $scalar) -> $name {
+                self.to_curve().multiply(&::ff::PrimeField::to_repr(other))
+            }
+        }
+
+        impl_binops_additive_assign!($name, $name);
+        impl_binops_additive_assign!($name, $affine);
+        impl_binops_additive!($affine, $affine, $name);
+        impl_binops_additive!($affine, $name, $name);
+        impl_binops_multiplicative_assign!($name, $scalar);
+        impl_binops_multiplicative!($affine, $scalar, $name);
+
+        impl $crate::arithmetic::Group for $name {
+            type Scalar = $scalar;
+
+            fn group_zero() -> Self {
+                $name::identity()
+            }
+
+            fn group_add(&mut self, rhs: &Self) {
+                *self += *rhs;
+            }
+
+            fn group_sub(&mut self, rhs: &Self) {
+                *self -= *rhs;
+            }
+
+            fn group_scale(&mut self, by: &Self::Scalar) {
+                *self *= *by;
+            }
+        }
+
+        impl $crate::arithmetic::CurveExt for $name {
+            type ScalarExt = $scalar;
+            type Base = $base;
+            type AffineExt = $affine;
+
+            const CURVE_ID: &'static str = $curve_id;
+
+            fn hash_to_curve<'a>(domain_prefix: &'a str) -> Box<dyn Fn(&[u8]) -> Self + 'a> {
+                Box::new(move |message| {
+                    // Try-and-increment: each attempt hashes the message with a counter
+                    // to a candidate x-coordinate, until one of them is on the curve.
+                    // This is not constant time, but the messages that are hashed are
+                    // public. The curve has prime order, so every point is in the group.
+                    let mut counter = 0u32;
+                    loop {
+                        let hash = ::blake2b_simd::Params::new()
+                            .hash_length(64)
+                            .personal(b"halo2_tryinc_h2c")
+                            .to_state()
+                            .update(&($curve_id.len() as u64).to_le_bytes())
+                            .update($curve_id.as_bytes())
+                            .update(&(domain_prefix.len() as u64).to_le_bytes())
+                            .update(domain_prefix.as_bytes())
+                            .update(&counter.to_le_bytes())
+                            .update(message)
+                            .finalize();
+                        let mut bytes = [0u8; 64];
+                        bytes.copy_from_slice(hash.as_bytes());
+
+                        let x = <$base as $crate::arithmetic::CanonicalEncoding>::from_bytes_wide(
+                            &bytes,
+                        );
+                        let y_is_odd = ::subtle::Choice::from(bytes[0] & 1);
+                        let p = $affine::from_x(x, y_is_odd);
+                        if bool::from(p.is_some()) {
+                            return p.unwrap().to_curve();
+                        }
+                        counter += 1;
+                    }
+                })
+            }
+        }
+
+        impl $crate::arithmetic::CurveAffine for $affine {
+            type ScalarExt = $scalar;
+            type Base = $base;
+            type CurveExt = $name;
+
+            fn coordinates(&self) -> ::subtle::CtOption<$crate::arithmetic::Coordinates<Self>> {
+                ::subtle::CtOption::new(
+                    $crate::arithmetic::Coordinates {
+                        x: self.x,
+                        y: self.y,
+                    },
+                    !self.is_identity(),
+                )
+            }
+
+            fn from_xy(x: $base, y: $base) -> ::subtle::CtOption<Self> {
+                let p = $affine { x, y };
+                ::subtle::CtOption::new(p, p.is_on_curve())
+            }
+
+            fn is_on_curve(&self) -> ::subtle::Choice {
+                $affine::is_on_curve(self)
+            }
+        }
+
+        #[test]
+        fn test_curve() {
+            use ::group::{Curve, Group};
+            use $crate::arithmetic::{CurveAffine, CurveExt, FieldExt};
+
+            let g = $name::generator();
+            assert!(bool::from(g.to_affine().is_on_curve()));
+
+            // Scalar multiplication agrees with addition, and the generator has the
+            // order of the scalar field.
+            let a = $scalar::rand();
+            let b = $scalar::rand();
+            assert_eq!(g * a + g * b, g * (a + b));
+            assert_eq!(g * -$scalar::one() + g, $name::identity());
+            assert_eq!(g.double(), g + g.to_affine());
+
+            let p = $name::random(::rand::rngs::OsRng);
+            let q = $name::hash_to_curve("test")(b"message");
+            assert!(bool::from(q.to_affine().is_on_curve()));
+            assert_eq!(q, $name::hash_to_curve("test")(b"message"));
+
+            let mut affine = [$affine::identity(); 3];
+            $name::batch_normalize(&[p, $name::identity(), q], &mut affine);
+            assert_eq!(affine, [p.to_affine(), $affine::identity(), q.to_affine()]);
+
+            // Points round-trip through their encoding, including the identity.
+            for p in affine.iter() {
+                let mut bytes = vec![];
+                p.write(&mut bytes).unwrap();
+                assert_eq!(&$affine::read(&mut &bytes[..]).unwrap(), p);
+            }
+        }
+    };
+}
+
+/// Implements the compressed encoding of the affine points of a curve.
+///
+/// With `spare_bits`, the base field must leave the top two bits of the last byte of
+/// its encoding unused. The top bit holds the sign of the y-coordinate, and the next
+/// bit is set for the identity.
+///
+/// With `sec1`, the encoding is the compressed SEC1 encoding: a byte `0x02` or `0x03`
+/// for the sign of the y-coordinate, followed by the big-endian x-coordinate. The
+/// identity is encoded as zeros.
+macro_rules! impl_encoding {
+    ($name:ident, $affine:ident, $base:ident, spare_bits) => {
+        impl ::group::GroupEncoding for $affine {
+            type Repr = [u8; 32];
+
+            fn from_bytes(bytes: &[u8; 32]) -> ::subtle::CtOption<Self> {
+                let mut tmp = *bytes;
+                let y_is_odd = ::subtle::Choice::from(tmp[31] >> 7);
+                let is_identity = ::subtle::Choice::from((tmp[31] >> 6) & 1);
+                tmp[31] &= 0b0011_1111;
+
+                $base::from_canonical_bytes(&tmp).and_then(|x| {
+                    let identity = ::subtle::CtOption::new(
+                        $affine::identity(),
+                        is_identity
+                            & !y_is_odd
+                            & ::subtle::ConstantTimeEq::ct_eq(&x, &$base::zero()),
+                    );
+                    let point = $affine::from_x(x, y_is_odd);
+                    ::subtle::CtOption::new(
+                        ::subtle::ConditionallySelectable::conditional_select(
+                            &point.unwrap_or_else($affine::identity),
+                            &identity.unwrap_or_else($affine::identity),
+                            is_identity,
+                        ),
+                        ::subtle::ConditionallySelectable::conditional_select(
+                            &point.is_some(),
+                            &identity.is_some(),
+                            is_identity,
+                        ),
+                    )
+                })
+            }
+
+            fn from_bytes_unchecked(bytes: &[u8; 32]) -> ::subtle::CtOption<Self> {
+                // We can't avoid curve checks when parsing a compressed encoding.
+                <Self as ::group::GroupEncoding>::from_bytes(bytes)
+            }
+
+            fn to_bytes(&self) -> [u8; 32] {
+                let mut bytes = self.x.canonical_bytes();
+                let y_is_odd = ::ff::PrimeField::is_odd(&self.y) as u8;
+                let is_identity = self.is_identity().unwrap_u8();
+                bytes[31] |= (y_is_odd << 7) | (is_identity << 6);
+                bytes
+            }
+        }
+    };
+
+    ($name:ident, $affine:ident, $base:ident, sec1) => {
+        impl ::group::GroupEncoding for $affine {
+            type Repr = $crate::curves::Sec1Compressed;
+
+            fn from_bytes(bytes: &Self::Repr) -> ::subtle::CtOption<Self> {
+                let prefix = bytes.0[0];
+                let y_is_odd = ::subtle::Choice::from(prefix & 1);
+                let is_point = ::subtle::ConstantTimeEq::ct_eq(&(prefix | 1), &0x03);
+                let is_identity = bytes.0.iter().fold(::subtle::Choice::from(1), |acc, b| {
+                    acc & ::subtle::ConstantTimeEq::ct_eq(b, &0)
+                });
+
+                let mut x = [0u8; 32];
+                x.copy_from_slice(&bytes.0[1..]);
+                x.reverse();
+
+                $base::from_canonical_bytes(&x).and_then(|x| {
+                    let point = $affine::from_x(x, y_is_odd);
+                    ::subtle::CtOption::new(
+                        ::subtle::ConditionallySelectable::conditional_select(
+                            &point.unwrap_or_else($affine::identity),
+                            &$affine::identity(),
+                            is_identity,
+                        ),
+                        (is_point & point.is_some()) | is_identity,
+                    )
+                })
+            }
+
+            fn from_bytes_unchecked(bytes: &Self::Repr) -> ::subtle::CtOption<Self> {
+                // We can't avoid curve checks when parsing a compressed encoding.
+                <Self as ::group::GroupEncoding>::from_bytes(bytes)
+            }
+
+            fn to_bytes(&self) -> Self::Repr {
+                let mut x = self.x.canonical_bytes();
+                x.reverse();
+
+                let mut bytes = [0u8; 33];
+                bytes[0] = 0x02 | ::ff::PrimeField::is_odd(&self.y) as u8;
+                bytes[1..].copy_from_slice(&x);
+
+                let is_identity = self.is_identity();
+                for b in bytes.iter_mut() {
+                    ::subtle::ConditionallySelectable::conditional_assign(b, &0, is_identity);
+                }
+                $crate::curves::Sec1Compressed(bytes)
+            }
+        }
+    };
+}
@@ -0,0 +1,197 @@
+//! The secp256k1 curve, $y^2 = x^3 + 7$ over [`Fp`], which is the curve of the
+//! signatures of Bitcoin.
+//!
+//! Its scalar field [`Fq`] only has a multiplicative subgroup of order $2^6$, so it
+//! can prove circuits of up to $2^6$ rows, less the extension for the degree of their
+//! constraints. It forms a cycle with [`secq256k1`](super::secq256k1).
+
+new_field!(
+    /// The base field of secp256k1, whose modulus is
+    /// `0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f`.
+    Fp,
+    modulus: [
+        0xfffffffefffffc2f,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ],
+    modulus_str: "0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+    inv: 0xd838091dd2253531,
+    r: [
+        0x00000001000003d1,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+    ],
+    r2: [
+        0x000007a2000e90a1,
+        0x0000000000000001,
+        0x0000000000000000,
+        0x0000000000000000,
+    ],
+    r3: [
+        0x002bb1e33795f671,
+        0x0000000100000b73,
+        0x0000000000000000,
+        0x0000000000000000,
+    ],
+    num_bits: 256,
+    s: 1,
+    // 3, in Montgomery form
+    generator: [
+        0x0000000300000b73,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+    ],
+    root_of_unity: [
+        0xfffffffdfffff85e,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ],
+    root_of_unity_inv: [
+        0xfffffffdfffff85e,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+    ],
+    delta: [
+        0x0000000900002259,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+    ],
+    zeta: [
+        0xa75bc9e1717e6f10,
+        0xfc021e9ce3b47f50,
+        0x071676872fd1c6fa,
+        0x85b5c9514344c2ac,
+    ],
+    two_inv: [
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x8000000000000000,
+    ],
+    t_minus1_over2: [
+        0xffffffffbfffff0b,
+        0xffffffffffffffff,
+        0xffffffffffffffff,
+        0x3fffffffffffffff,
+    ],
+);
+
+new_field!(
+    /// The scalar field of secp256k1, whose modulus is
+    /// `0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141`.
+    Fq,
+    modulus: [
+        0xbfd25e8cd0364141,
+        0xbaaedce6af48a03b,
+        0xfffffffffffffffe,
+        0xffffffffffffffff,
+    ],
+    modulus_str: "0xfffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+    inv: 0x4b0dff665588b13f,
+    r: [
+        0x402da1732fc9bebf,
+        0x4551231950b75fc4,
+        0x0000000000000001,
+        0x0000000000000000,
+    ],
+    r2: [
+        0x896cf21467d7d140,
+        0x741496c20e7cf878,
+        0xe697f5e45bcd07c6,
+        0x9d671cd581c69bc5,
+    ],
+    r3: [
+        0x7bc0cfe0e9ff41ed,
+        0x0017648444d4322c,
+        0xb1b31347f1d0b2da,
+        0x555d800c18ef116d,
+    ],
+    num_bits: 256,
+    s: 6,
+    // 7, in Montgomery form
+    generator: [
+        0xc13f6a264e843739,
+        0xe537f5b135039e5d,
+        0x0000000000000008,
+        0x0000000000000000,
+    ],
+    root_of_unity: [
+        0x944cf2a220910e04,
+        0x815c829c780589f4,
+        0x55980b07bc222113,
+        0xc702b0d248825b36,
+    ],
+    root_of_unity_inv: [
+        0xb2dcd52aaf4dd71f,
+        0x428e55dc1672be1d,
+        0xe44b48d2d795a1b6,
+        0xc14ec3314e1097c2,
+    ],
+    delta: [
+        0xd91b33d24319d9e8,
+        0xb81c6596ff5d6740,
+        0xa463969ca14c51c1,
+        0x1900960de4b7929c,
+    ],
+    zeta: [
+        0x8f26d1dbd6da15e4,
+        0x48c9d21fdacaac2a,
+        0x8c5699f9ad96826c,
+        0xacd7bfe87aa729c6,
+    ],
+    two_inv: [
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x8000000000000000,
+    ],
+    t_minus1_over2: [
+        0x777fa4bd19a06c82,
+        0xfd755db9cd5e9140,
+        0xffffffffffffffff,
+        0x01ffffffffffffff,
+    ],
+);
+
+new_curve!(
+    /// A point of secp256k1, in Jacobian coordinates.
+    Secp256k1,
+    /// A point of secp256k1, in affine coordinates.
+    Secp256k1Affine,
+    base: Fp,
+    scalar: Fq,
+    curve_id: "secp256k1",
+    // 7, in Montgomery form
+    b: [
+        0x0000000700001ab7,
+        0x0000000000000000,
+        0x0000000000000000,
+        0x0000000000000000,
+    ],
+    generator: ([
+        0xd7362e5a487e2097,
+        0x231e295329bc66db,
+        0x979f48c033fd129c,
+        0x9981e643e9089f48,
+    ], [
+        0xb15ea6d2d3dbabe2,
+        0x8dfc5d5d1f1dc64d,
+        0x70b6b59aac19c136,
+        0xcf3f851fd4a582d6,
+    ]),
+    encoding: sec1,
+);
+
+#[test]
+fn test_field_constants() {
+    use crate::arithmetic::check_field_constants;
+
+    assert_eq!(check_field_constants::<Fp>(), Ok(()));
+    assert_eq!(check_field_constants::<Fq>(), Ok(()));
+}
@@ -0,0 +1,37 @@
+//! The secq256k1 curve, $y^2 = x^3 + 7$ over the scalar field of secp256k1, which
+//! forms a cycle with [`secp256k1`](super::secp256k1): the scalar field of each curve
+//! is the base field of the other.
+//!
+//! The scalar field of secq256k1 is the base field of secp256k1, which only has a
+//! multiplicative subgroup of order $2$, so secq256k1 cannot prove circuits.
+
+pub use super::secp256k1::{Fp as Fq, Fq as Fp};
+
+new_curve!(
+    /// A point of secq256k1, in Jacobian coordinates.
+    Secq256k1,
+    /// A point of secq256k1, in affine coordinates.
+    Secq256k1Affine,
+    base: Fp,
+    scalar: Fq,
+    curve_id: "secq256k1",
+    // 7, in Montgomery form
+    b: [
+        0xc13f6a264e843739,
+        0xe537f5b135039e5d,
+        0x0000000000000008,
+        0x0000000000000000,
+    ],
+    generator: ([
+        0x402da1732fc9bebf,
+        0x4551231950b75fc4,
+        0x0000000000000001,
+        0x0000000000000000,
+    ], [
+        0x1d0b32115eddea3a,
+        0x72a0cb5565b03cc1,
+        0xa5ed718420a78807,
+        0xafe1004357110e91,
+    ]),
+    encoding: sec1,
+);
@@ -9,15 +9,42 @@ use ff::Field;
 use crate::plonk::Assigned;
 use crate::{
     arithmetic::{FieldExt, Group},
+    circuit::Cell,
     plonk::{
         permutation, Advice, Any, Assignment, Circuit, Column, ColumnType, ConstraintSystem, Error,
-        Expression, Fixed, FloorPlanner, Permutation, Selector,
+        Expression, Fixed, FloorPlanner, Instance, Permutation, Selector,
     },
-    poly::Rotation,
+    poly::{ColumnMatrix, Rotation},
 };
 
 pub mod metadata;
 
+mod corrupt;
+pub mod fuzz;
+
+mod consistency;
+pub use consistency::{prove_and_verify, ConsistencyFailure};
+
+mod determinism;
+pub use determinism::assert_deterministic_keygen;
+
+mod equivalence;
+pub use equivalence::assert_equivalent;
+
+mod floor_plan;
+pub use floor_plan::{FloorPlan, FloorPlannerComparison, PlannerMeasurement, RegionPlacement};
+
+mod reference;
+pub use reference::reference_verifier;
+
+mod json;
+
+mod snapshot;
+pub use snapshot::{compare_vk_snapshot, vk_snapshot, UPDATE_SNAPSHOTS_ENV};
+
+mod trace;
+pub use trace::{replay, RecordingAssignment, Trace, TraceOp};
+
 #[cfg(feature = "dev-graph")]
 mod graph;
 
@@ -25,11 +52,48 @@ mod graph;
 #[cfg_attr(docsrs, doc(cfg(feature = "dev-graph")))]
 pub use graph::{circuit_dot_graph, layout::CircuitLayout};
 
+#[cfg(feature = "dev-tracing")]
+mod tracing;
+
+#[cfg(feature = "dev-tracing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "dev-tracing")))]
+pub use self::tracing::TracingFloorPlanner;
+
 /// Cells that haven't been explicitly assigned to, default to zero.
 fn cell_value<F: Field>(cell: Option<F>) -> F {
     cell.unwrap_or_else(F::zero)
 }
 
+/// Evaluates the assigned cells of each column, inverting all of their denominators in
+/// a single batch as the prover does. Cells that were not assigned remain `None`.
+fn batch_invert_cells<F: FieldExt>(
+    rows: usize,
+    cells: &[Vec<Option<Assigned<F>>>],
+) -> Vec<Vec<Option<F>>> {
+    let columns: Vec<Vec<_>> = cells
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|cell| cell.unwrap_or(Assigned::Zero))
+                .collect()
+        })
+        .collect();
+    let values = ColumnMatrix::from_columns(rows, &columns).batch_invert();
+
+    cells
+        .iter()
+        .zip(values.columns())
+        .map(|(column, values)| {
+            column
+                .iter()
+                .zip(values.iter())
+                .map(|(cell, value)| cell.map(|_| *value))
+                .collect()
+        })
+        .collect()
+}
+
 /// The reasons why a particular circuit is not satisfied.
 #[derive(Debug, PartialEq)]
 pub enum VerifyFailure {
@@ -53,6 +117,20 @@ pub enum VerifyFailure {
         /// The row on which this constraint is not satisfied.
         row: usize,
     },
+    /// A cell used by the inputs of a lookup, on a row where one of the selectors it
+    /// queries is enabled, was not assigned to.
+    LookupCell {
+        /// The index of the lookup. These indices are assigned in the order in which
+        /// `ConstraintSystem::lookup` is called during `Circuit::configure`.
+        lookup_index: usize,
+        /// The region in which this cell should be assigned.
+        region: metadata::Region,
+        /// The column in which this cell should be assigned.
+        column: Column<Any>,
+        /// The offset (relative to the start of the region) at which this cell should be
+        /// assigned.
+        offset: isize,
+    },
     /// A lookup input did not exist in its corresponding table.
     Lookup {
         /// The index of the lookup that is not satisfied. These indices are assigned in
@@ -73,6 +151,20 @@ pub enum VerifyFailure {
         /// The row on which this permutation is not satisfied.
         row: usize,
     },
+    /// A cell was assigned a fraction whose denominator is zero.
+    ///
+    /// The prover evaluates such a fraction to zero. That convention can mask a bug in
+    /// the circuit (such as inverting a witness that should never be zero), so these
+    /// cells are reported even if every constraint is satisfied.
+    ZeroDenominator {
+        /// The region in which the cell was assigned, or `None` if it was assigned
+        /// outside of any region (as constants are by the floor planner).
+        region: Option<metadata::Region>,
+        /// The column of the cell.
+        column: Column<Any>,
+        /// The row of the cell.
+        row: usize,
+    },
 }
 
 impl fmt::Display for VerifyFailure {
@@ -90,6 +182,18 @@ impl fmt::Display for VerifyFailure {
                     region, gate, column, offset
                 )
             }
+            Self::LookupCell {
+                lookup_index,
+                region,
+                column,
+                offset,
+            } => {
+                write!(
+                    f,
+                    "{} uses lookup {}, which requires cell in column {:?} at offset {} to be assigned.",
+                    region, lookup_index, column, offset
+                )
+            }
             Self::Constraint { constraint, row } => {
                 write!(f, "{} is not satisfied on row {}", constraint, row)
             }
@@ -107,6 +211,28 @@ impl fmt::Display for VerifyFailure {
                     perm_index, column, row
                 )
             }
+            Self::ZeroDenominator {
+                region: Some(region),
+                column,
+                row,
+            } => {
+                write!(
+                    f,
+                    "{} assigns a fraction with a zero denominator to cell in column {:?} at row {}",
+                    region, column, row
+                )
+            }
+            Self::ZeroDenominator {
+                region: None,
+                column,
+                row,
+            } => {
+                write!(
+                    f,
+                    "A fraction with a zero denominator is assigned to cell in column {:?} at row {}",
+                    column, row
+                )
+            }
         }
     }
 }
@@ -115,6 +241,8 @@ impl fmt::Display for VerifyFailure {
 struct Region {
     /// The name of the region. Not required to be unique.
     name: String,
+    /// The namespace path in which the region was entered, outermost first.
+    namespace: Vec<String>,
     /// The row that this region starts on, if known.
     start: Option<usize>,
     /// The selectors that have been enabled in this region. All other selectors are by
@@ -145,6 +273,12 @@ impl Region {
 /// are invalid: it stores all the private inputs along with the circuit internals, and
 /// then checks every constraint manually.
 ///
+/// Like the prover, `MockProver` makes all `2^k` rows of each column available to the
+/// circuit: the prover does not reserve any rows for blinding values, so there is no
+/// boundary of usable rows for `MockProver` to enforce. A cell that the circuit leaves
+/// unassigned is reported as a [`VerifyFailure::Cell`] if an enabled gate uses it, and
+/// is otherwise treated as zero, as it is by the prover.
+///
 /// # Examples
 ///
 /// ```
@@ -242,6 +376,16 @@ pub struct MockProver<F: Group + Field> {
     /// The current region being assigned to. Will be `None` after the circuit has been
     /// synthesized.
     current_region: Option<Region>,
+    /// The names of the namespaces that are currently entered, outermost first.
+    namespace: Vec<String>,
+
+    // The values assigned to the fixed and advice cells while the circuit is being
+    // synthesized, arranged as [column][row]. As in the prover, fractions are only
+    // evaluated once synthesis is complete, with a single batch inversion.
+    assigned_fixed: Vec<Vec<Option<Assigned<F>>>>,
+    assigned_advice: Vec<Vec<Option<Assigned<F>>>>,
+    // The cells that were assigned a fraction with a zero denominator.
+    zero_denominators: Vec<(Column<Any>, usize)>,
 
     // The fixed cells in the circuit, arranged as [column][row].
     fixed: Vec<Vec<Option<F>>>,
@@ -262,6 +406,7 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         assert!(self.current_region.is_none());
         self.current_region = Some(Region {
             name: name().into(),
+            namespace: self.namespace.clone(),
             start: None,
             enabled_selectors: HashMap::default(),
             cells: vec![],
@@ -315,10 +460,10 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         }
 
         *self
-            .advice
+            .assigned_advice
             .get_mut(column.index())
             .and_then(|v| v.get_mut(row))
-            .ok_or(Error::BoundsFailure)? = Some(to()?.into().evaluate());
+            .ok_or(Error::BoundsFailure)? = Some(to()?.into());
 
         Ok(())
     }
@@ -342,14 +487,30 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         }
 
         *self
-            .fixed
+            .assigned_fixed
             .get_mut(column.index())
             .and_then(|v| v.get_mut(row))
-            .ok_or(Error::BoundsFailure)? = Some(to()?.into().evaluate());
+            .ok_or(Error::BoundsFailure)? = Some(to()?.into());
 
         Ok(())
     }
 
+    fn fixed_value(&self, column: Column<Fixed>, row: usize) -> Result<Option<F>, Error> {
+        self.assigned_fixed
+            .get(column.index())
+            .and_then(|v| v.get(row))
+            .map(|cell| cell.map(Assigned::evaluate))
+            .ok_or(Error::BoundsFailure)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.instance
+            .get(column.index())
+            .and_then(|v| v.get(row))
+            .map(|v| Some(*v))
+            .ok_or(Error::BoundsFailure)
+    }
+
     fn copy(
         &mut self,
         permutation: &Permutation,
@@ -363,16 +524,15 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
             return Err(Error::BoundsFailure);
         }
 
-        let left_column_index = permutation
-            .mapping()
-            .iter()
-            .position(|c| c == &left_column)
-            .ok_or(Error::SynthesisError)?;
-        let right_column_index = permutation
-            .mapping()
-            .iter()
-            .position(|c| c == &right_column)
-            .ok_or(Error::SynthesisError)?;
+        let (left_column_index, right_column_index) = permutation.copy_indices(
+            self.n as usize,
+            self.current_region
+                .as_ref()
+                .map(|region| region.name.as_str()),
+            &self.namespace,
+            (left_column, left_row),
+            (right_column, right_row),
+        )?;
 
         self.permutations[permutation.index()].copy(
             left_column_index,
@@ -382,22 +542,30 @@ impl<F: Field + Group> Assignment<F> for MockProver<F> {
         )
     }
 
-    fn push_namespace<NR, N>(&mut self, _: N)
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        // TODO: Do something with namespaces :)
+        self.namespace.push(name_fn().into());
     }
 
     fn pop_namespace(&mut self, _: Option<String>) {
-        // TODO: Do something with namespaces :)
+        self.namespace.pop();
     }
 }
 
 impl<F: FieldExt> MockProver<F> {
     /// Runs a synthetic keygen-and-prove operation on the given circuit, collecting data
     /// about the constraints and their assignments.
+    ///
+    /// `instance` contains the values of each instance column; columns shorter than
+    /// `2^k` rows are padded with zeroes, as they are when creating proofs.
+    ///
+    /// As when creating proofs, the values assigned to fractions are evaluated after
+    /// the circuit has been synthesized, by inverting all of their denominators in a
+    /// single batch. Fractions with a zero denominator evaluate to zero, and are
+    /// reported by [`MockProver::verify`] as [`VerifyFailure::ZeroDenominator`].
     pub fn run<ConcreteCircuit: Circuit<F>>(
         k: u32,
         circuit: &ConcreteCircuit,
@@ -406,10 +574,47 @@ impl<F: FieldExt> MockProver<F> {
         let n = 1 << k;
 
         let mut cs = ConstraintSystem::default();
-        let config = ConcreteCircuit::configure(&mut cs);
+        let config = circuit.configure_from(&mut cs);
+        cs.check_degree(k)?;
+
+        // Queries must not wrap all the way around the domain.
+        let (backward, forward) = cs.max_rotation();
+        if std::cmp::max(backward, forward) >= n as usize {
+            return Err(Error::IncompatibleParams);
+        }
+
+        // Instance columns are padded with zeroes, as they are when creating proofs, so
+        // that lookup tables over instance columns have the same rows.
+        if instance.len() != cs.num_instance_columns {
+            return Err(Error::IncompatibleParams);
+        }
+        let instance = instance
+            .into_iter()
+            .map(|mut column| {
+                if column.len() > n as usize {
+                    return Err(Error::BoundsFailure);
+                }
+                column.resize(n as usize, F::zero());
+                Ok(column)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Constant instance columns must hold their configured values, as when creating
+        // proofs.
+        for (column, values) in &cs.constant_instances {
+            let padded = values.iter().cloned().chain(iter::repeat(F::zero()));
+            if values.len() > n as usize
+                || instance[column.index()]
+                    .iter()
+                    .zip(padded)
+                    .any(|(a, b)| *a != b)
+            {
+                return Err(Error::IncompatibleParams);
+            }
+        }
 
-        let fixed = vec![vec![None; n as usize]; cs.num_fixed_columns];
-        let advice = vec![vec![None; n as usize]; cs.num_advice_columns];
+        let assigned_fixed = vec![vec![None; n as usize]; cs.num_fixed_columns];
+        let assigned_advice = vec![vec![None; n as usize]; cs.num_advice_columns];
         let permutations = cs
             .permutations
             .iter()
@@ -421,17 +626,100 @@ impl<F: FieldExt> MockProver<F> {
             cs,
             regions: vec![],
             current_region: None,
-            fixed,
-            advice,
+            namespace: vec![],
+            assigned_fixed,
+            assigned_advice,
+            zero_denominators: vec![],
+            fixed: vec![],
+            advice: vec![],
             instance,
             permutations,
         };
 
         ConcreteCircuit::FloorPlanner::synthesize(&mut prover, circuit, config)?;
 
+        // Evaluate the assigned values as the prover does, noting any fractions whose
+        // denominators are zero and which therefore evaluate to zero.
+        let assigned_fixed = std::mem::take(&mut prover.assigned_fixed);
+        let assigned_advice = std::mem::take(&mut prover.assigned_advice);
+        let assigned_cells = assigned_fixed
+            .iter()
+            .enumerate()
+            .map(|(index, column)| (Column::new(index, Any::Fixed), column))
+            .chain(
+                assigned_advice
+                    .iter()
+                    .enumerate()
+                    .map(|(index, column)| (Column::new(index, Any::Advice), column)),
+            );
+        for (column, cells) in assigned_cells {
+            for (row, cell) in cells.iter().enumerate() {
+                if let Some(Assigned::Rational(_, denominator)) = cell {
+                    if *denominator == F::zero() {
+                        prover.zero_denominators.push((column, row));
+                    }
+                }
+            }
+        }
+        prover.fixed = batch_invert_cells(n as usize, &assigned_fixed);
+        prover.advice = batch_invert_cells(n as usize, &assigned_advice);
+
+        // The circuit does not assign the columns holding split lookup inputs.
+        let intermediates = prover.cs.lookup_intermediate_values(
+            n as usize,
+            |column, row| cell_value(prover.fixed[column][row]),
+            |column, row| cell_value(prover.advice[column][row]),
+            |column, row| prover.instance[column][row],
+        );
+        for (column, values) in intermediates {
+            prover.advice[column.index()] = values.into_iter().map(Some).collect();
+        }
+
         Ok(prover)
     }
 
+    /// Returns the copy constraints of each permutation argument, in the order in which
+    /// `ConstraintSystem::permutation` is called during `Circuit::configure`.
+    ///
+    /// The copy constraints of a permutation argument are given as its cycles: each
+    /// cycle is a list of the cells, as `(column, row)` pairs, that are constrained to be
+    /// equal to each other. Cells that are not constrained to be equal to any other cell
+    /// are omitted.
+    pub fn copy_cycles(&self) -> Vec<Vec<Vec<(Column<Any>, usize)>>> {
+        self.permutations
+            .iter()
+            .zip(self.cs.permutations.iter())
+            .map(|(assembly, argument)| {
+                let columns = argument.get_columns();
+                assembly
+                    .cycles()
+                    .into_iter()
+                    .map(|cycle| {
+                        cycle
+                            .into_iter()
+                            .map(|(column, row)| (columns[column], row))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Describes `cell` in the same way as its [`Display`] implementation, along with
+    /// the name of its region.
+    ///
+    /// Regions are numbered in the order in which the circuit entered them, which is the
+    /// order in which the floor planner numbers them.
+    ///
+    /// [`Display`]: std::fmt::Display
+    pub fn describe_cell(&self, cell: Cell) -> String {
+        let name = self
+            .regions
+            .get(*cell.region_index())
+            .map(|region| region.name.as_str());
+        cell.describe(name)
+    }
+
     /// Returns `Ok(())` if this `MockProver` is satisfied, or a list of errors indicating
     /// the reasons that the circuit is not satisfied.
     pub fn verify(&self) -> Result<(), Vec<VerifyFailure>> {
@@ -463,13 +751,56 @@ impl<F: FieldExt> MockProver<F> {
                                 // Determine where this cell should have been assigned.
                                 let cell_row = ((gate_row + n + cell.rotation.0) % n) as usize;
 
-                                // Check that it was assigned!
-                                if r.cells.contains(&(cell.column, cell_row)) {
+                                // Check that it was assigned! Instance cells are given by
+                                // the verifier rather than assigned within regions.
+                                if cell.column.column_type() == &Any::Instance
+                                    || r.cells.contains(&(cell.column, cell_row))
+                                {
                                     None
                                 } else {
                                     Some(VerifyFailure::Cell {
                                         gate: (gate_index, gate.name()).into(),
-                                        region: (r_i, r.name.clone()).into(),
+                                        region: (r_i, r.name.clone(), r.namespace.clone()).into(),
+                                        column: cell.column,
+                                        offset: cell_row as isize - r.start.unwrap() as isize,
+                                    })
+                                }
+                            })
+                        })
+                    })
+            })
+        });
+
+        // Likewise, check that the cells used by the inputs of each lookup have been
+        // assigned on the rows where the lookup is enabled by a selector.
+        let lookup_cell_errors = self.regions.iter().enumerate().flat_map(|(r_i, r)| {
+            r.enabled_selectors.iter().flat_map(move |(selector, at)| {
+                self.cs
+                    .lookups
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(lookup_index, lookup)| {
+                        lookup
+                            .selector_cells
+                            .iter()
+                            .find(|(s, _)| s == selector)
+                            .map(|(_, cells)| (lookup_index, cells))
+                    })
+                    .flat_map(move |(lookup_index, cells)| {
+                        at.iter().flat_map(move |selector_row| {
+                            let lookup_row = *selector_row as i32;
+
+                            cells.iter().filter_map(move |cell| {
+                                let cell_row = ((lookup_row + n + cell.rotation.0) % n) as usize;
+
+                                if cell.column.column_type() == &Any::Instance
+                                    || r.cells.contains(&(cell.column, cell_row))
+                                {
+                                    None
+                                } else {
+                                    Some(VerifyFailure::LookupCell {
+                                        lookup_index,
+                                        region: (r_i, r.name.clone(), r.namespace.clone()).into(),
                                         column: cell.column,
                                         offset: cell_row as isize - r.start.unwrap() as isize,
                                     })
@@ -653,11 +984,30 @@ impl<F: FieldExt> MockProver<F> {
                         })
                 });
 
+        // Check that no cell was assigned a fraction with a zero denominator. Such a cell
+        // is attributed to the last region that assigned it.
+        let zero_denominator_errors =
+            self.zero_denominators
+                .iter()
+                .map(|&(column, row)| VerifyFailure::ZeroDenominator {
+                    region: self
+                        .regions
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .find(|(_, r)| r.cells.contains(&(column, row)))
+                        .map(|(r_i, r)| (r_i, r.name.clone(), r.namespace.clone()).into()),
+                    column,
+                    row,
+                });
+
         let errors: Vec<_> = iter::empty()
             .chain(selector_errors)
+            .chain(lookup_cell_errors)
             .chain(gate_errors)
             .chain(lookup_errors)
             .chain(perm_errors)
+            .chain(zero_denominator_errors)
             .collect();
         if errors.is_empty() {
             Ok(())
@@ -669,12 +1019,16 @@ impl<F: FieldExt> MockProver<F> {
 
 #[cfg(test)]
 mod tests {
+    use ff::Field;
     use pasta_curves::Fp;
 
     use super::{MockProver, VerifyFailure};
     use crate::{
         circuit::{Layouter, SimpleFloorPlanner},
-        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Selector},
+        plonk::{
+            Advice, Any, Assigned, Circuit, Column, ConstraintSystem, Error, Expression, Fixed,
+            Permutation, Selector, TaggedInput,
+        },
         poly::Rotation,
     };
 
@@ -749,4 +1103,514 @@ mod tests {
             }])
         );
     }
+
+    #[test]
+    fn zero_denominator() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct InverseConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            q: Selector,
+        }
+
+        struct InverseCircuit {}
+
+        impl Circuit<Fp> for InverseCircuit {
+            type Config = InverseConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let q = meta.selector();
+
+                meta.create_gate("Inverse", |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    vec![q * (a * b - Expression::Constant(Fp::one()))]
+                });
+
+                InverseConfig { a, b, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Inverses",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        for (offset, a) in [Fp::from(2u64), Fp::zero()].iter().enumerate() {
+                            region.assign_advice(|| "a", config.a, offset, || Ok(*a))?;
+                            region.assign_advice(
+                                || "b",
+                                config.b,
+                                offset,
+                                || Ok(Assigned::from(*a).invert()),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &InverseCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.advice[1][0], Some(Fp::from(2u64).invert().unwrap()));
+        assert_eq!(prover.advice[1][1], Some(Fp::zero()));
+
+        // The gate is satisfied, but the inverse of zero is reported.
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::ZeroDenominator {
+                region: Some((0, "Inverses".to_owned()).into()),
+                column: Column::new(1, Any::Advice),
+                row: 1,
+            }])
+        );
+    }
+
+    #[test]
+    fn unassigned_lookup_cell() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            q: Selector,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let table = meta.fixed_column();
+                let q = meta.selector();
+
+                meta.lookup(|cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let table = cells.query_fixed(table, Rotation::cur());
+                    let q = cells.query_selector(q);
+
+                    // If q is enabled, a must be assigned to.
+                    vec![(q * a, table)]
+                });
+
+                FaultyCircuitConfig { q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty lookup",
+                    |mut region| {
+                        // Enable the lookup, but forget to assign a.
+                        config.q.enable(&mut region, 0)
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &FaultyCircuit {}, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::LookupCell {
+                lookup_index: 0,
+                region: (0, "Faulty lookup".to_owned()).into(),
+                column: Column::new(0, Any::Advice),
+                offset: 0,
+            }])
+        );
+    }
+
+    #[test]
+    fn split_lookup_inputs() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct CubeConfig {
+            a: Column<Advice>,
+            table: Column<Fixed>,
+            q: Selector,
+        }
+
+        struct CubeCircuit {
+            split: bool,
+        }
+
+        impl Circuit<Fp> for CubeCircuit {
+            type Config = CubeConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(_: &mut ConstraintSystem<Fp>) -> Self::Config {
+                unreachable!("configured with configure_from")
+            }
+
+            fn configure_from(&self, meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let table = meta.fixed_column();
+                let q = meta.selector();
+
+                meta.set_max_lookup_degree(4);
+                if self.split {
+                    meta.enable_lookup_splitting();
+                }
+
+                // The input has degree 4, so the lookup has degree 6 unless it is split.
+                meta.lookup(|cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let table = cells.query_fixed(table, Rotation::cur());
+                    let q = cells.query_selector(q);
+                    vec![(q * a.clone() * a.clone() * a, table)]
+                });
+
+                CubeConfig { a, table, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { split: self.split }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "cubes",
+                    |mut region| {
+                        for (row, cube) in [0u64, 1, 8, 27].iter().copied().enumerate() {
+                            region.assign_fixed(
+                                || "cube",
+                                config.table,
+                                row,
+                                || Ok(Fp::from(cube)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+                layouter.assign_region(
+                    || "cube root",
+                    |mut region| {
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(2u64)))?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        match MockProver::run(K, &CubeCircuit { split: false }, vec![]) {
+            Err(Error::LookupDegreeTooHigh {
+                lookup,
+                degree,
+                max_degree,
+            }) => {
+                assert_eq!((lookup, degree, max_degree), (0, 6, 4));
+            }
+            _ => panic!("expected a lookup degree error"),
+        }
+
+        let prover = MockProver::run(K, &CubeCircuit { split: true }, vec![]).unwrap();
+        assert_eq!(prover.cs.lookup_degrees(), vec![3]);
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn tagged_lookup() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct TaggedConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            tag: Column<Fixed>,
+            table: [Column<Fixed>; 2],
+            q_range: Selector,
+            q_square: Selector,
+        }
+
+        struct TaggedCircuit {
+            value: u64,
+            square: (u64, u64),
+        }
+
+        impl Circuit<Fp> for TaggedCircuit {
+            type Config = TaggedConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let tag = meta.fixed_column();
+                let table = [meta.fixed_column(), meta.fixed_column()];
+                let q_range = meta.selector();
+                let q_square = meta.selector();
+
+                // Tag 1 is a 2-bit range table, and tag 2 is a table of their squares.
+                meta.lookup_tagged(tag, &table, |cells| {
+                    let a = cells.query_advice(a, Rotation::cur());
+                    let b = cells.query_advice(b, Rotation::cur());
+                    vec![
+                        TaggedInput {
+                            selector: q_range,
+                            tag: Fp::from(1u64),
+                            inputs: vec![a.clone()],
+                        },
+                        TaggedInput {
+                            selector: q_square,
+                            tag: Fp::from(2u64),
+                            inputs: vec![a, b],
+                        },
+                    ]
+                });
+
+                TaggedConfig {
+                    a,
+                    b,
+                    tag,
+                    table,
+                    q_range,
+                    q_square,
+                }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    value: 0,
+                    square: (0, 0),
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "tables",
+                    |mut region| {
+                        for v in 0..4u64 {
+                            for (tag, square) in [(1, 0), (2, v * v)].iter() {
+                                let offset = (*tag as usize - 1) * 4 + v as usize;
+                                region.assign_fixed(
+                                    || "tag",
+                                    config.tag,
+                                    offset,
+                                    || Ok(Fp::from(*tag as u64)),
+                                )?;
+                                region.assign_fixed(
+                                    || "value",
+                                    config.table[0],
+                                    offset,
+                                    || Ok(Fp::from(v)),
+                                )?;
+                                region.assign_fixed(
+                                    || "square",
+                                    config.table[1],
+                                    offset,
+                                    || Ok(Fp::from(*square)),
+                                )?;
+                            }
+                        }
+                        Ok(())
+                    },
+                )?;
+
+                layouter.assign_region(
+                    || "uses",
+                    |mut region| {
+                        // Only a is required on the row where the range table is used.
+                        config.q_range.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(self.value)))?;
+
+                        config.q_square.enable(&mut region, 1)?;
+                        region.assign_advice(
+                            || "a",
+                            config.a,
+                            1,
+                            || Ok(Fp::from(self.square.0)),
+                        )?;
+                        region.assign_advice(
+                            || "b",
+                            config.b,
+                            1,
+                            || Ok(Fp::from(self.square.1)),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let circuit = TaggedCircuit {
+            value: 3,
+            square: (3, 9),
+        };
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // 5 is outside the 2-bit range.
+        let circuit = TaggedCircuit {
+            value: 5,
+            square: (3, 9),
+        };
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // (2, 9) is not a row of the squares table.
+        let circuit = TaggedCircuit {
+            value: 3,
+            square: (2, 9),
+        };
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn copy_cycles() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct CopyConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            perm: Permutation,
+        }
+
+        struct CopyCircuit {}
+
+        impl Circuit<Fp> for CopyCircuit {
+            type Config = CopyConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let perm = meta.permutation(&[a.into(), b.into()]);
+
+                CopyConfig { a, b, perm }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "copies",
+                    |mut region| {
+                        let value = || Ok(Fp::from(7u64));
+                        let a_0 = region.assign_advice(|| "a_0", config.a, 0, value)?;
+                        let b_1 = region.assign_advice(|| "b_1", config.b, 1, value)?;
+                        let a_2 = region.assign_advice(|| "a_2", config.a, 2, value)?;
+                        region.assign_advice(|| "b_3", config.b, 3, value)?;
+
+                        region.constrain_equal(&config.perm, b_1, a_0)?;
+                        region.constrain_equal(&config.perm, a_2, b_1)
+                    },
+                )
+            }
+        }
+
+        let prover = MockProver::run(K, &CopyCircuit {}, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let a = Column::new(0, Any::Advice);
+        let b = Column::new(1, Any::Advice);
+        assert_eq!(
+            prover.copy_cycles(),
+            vec![vec![vec![(a, 0), (a, 2), (b, 1)]]]
+        );
+    }
+
+    #[test]
+    fn invalid_copy() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            perm: Permutation,
+        }
+
+        struct FaultyCircuit {}
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let perm = meta.permutation(&[a.into()]);
+
+                FaultyCircuitConfig { a, b, perm }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {}
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.namespace(|| "gadget").assign_region(
+                    || "Faulty copy",
+                    |mut region| {
+                        let a = region.assign_advice(|| "a", config.a, 0, || Ok(Fp::from(1u64)))?;
+                        let b = region.assign_advice(|| "b", config.b, 0, || Ok(Fp::from(1u64)))?;
+
+                        // b is not part of the permutation.
+                        region.constrain_equal(&config.perm, a, b)
+                    },
+                )
+            }
+        }
+
+        match MockProver::run(K, &FaultyCircuit {}, vec![]) {
+            Err(Error::InvalidCopy {
+                region,
+                namespace,
+                permutation,
+                left,
+                right,
+            }) => {
+                assert_eq!(region.as_deref(), Some("Faulty copy"));
+                assert_eq!(namespace, vec!["gadget".to_owned()]);
+                assert_eq!(permutation, 0);
+                assert_eq!(left, (Column::new(0, Any::Advice), 0));
+                assert_eq!(right, (Column::new(1, Any::Advice), 0));
+            }
+            _ => panic!("expected an invalid copy error"),
+        }
+    }
 }
@@ -0,0 +1,419 @@
+//! End-to-end consistency checks between [`MockProver`] and the real prover.
+
+use std::slice;
+
+use ff::Field;
+use group::Curve;
+
+use super::{
+    corrupt::{CorruptedCircuit, Mutation, Perturbation},
+    MockProver, VerifyFailure,
+};
+use crate::{
+    arithmetic::CurveAffine,
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_single, Circuit, Error, ProvingKey},
+    poly::{
+        commitment::{Blind, Params},
+        LagrangeCoeff, Polynomial,
+    },
+    transcript::{Blake2bWrite, Challenge255},
+};
+
+/// The reasons why [`prove_and_verify`] can fail.
+#[derive(Debug)]
+pub enum ConsistencyFailure {
+    /// An error occurred while synthesizing the circuit, generating keys, or proving.
+    Error(Error),
+    /// `MockProver` rejected the honest witness, and so did the real verifier.
+    MockProver(Vec<VerifyFailure>),
+    /// `MockProver` and the real verifier disagreed about a witness.
+    Mismatch {
+        /// Whether `MockProver` accepted the witness (the real verifier did not).
+        mock_prover_accepted: bool,
+        /// The index (in synthesis order) of the advice assignment that was corrupted,
+        /// or `None` for the honest witness.
+        corrupted_advice: Option<usize>,
+    },
+    /// Corrupting any single advice assignment was not detected by `MockProver`, which
+    /// means that none of the circuit's advice cells are constrained.
+    UnconstrainedAdvice,
+}
+
+impl From<Error> for ConsistencyFailure {
+    fn from(error: Error) -> Self {
+        ConsistencyFailure::Error(error)
+    }
+}
+
+/// Runs [`MockProver`], key generation, [`create_proof`] and [`verify_single`] on the
+/// given circuit, and cross-checks that `MockProver` accepts the witness exactly when
+/// the real verifier accepts the proof.
+///
+/// Once the honest witness has been checked, advice assignments are corrupted one at a
+/// time (in synthesis order) until `MockProver` rejects the witness, and the real
+/// verifier is then required to reject the corresponding proof as well.
+///
+/// `instances` contains the values of each instance column; columns shorter than
+/// `2^k` rows are padded with zeroes.
+///
+/// This is intended as a drop-in integration test for circuits, for example over the
+/// Pasta curves:
+///
+/// ```ignore
+/// halo2::dev::prove_and_verify::<EqAffine, _>(K, &circuit, vec![public_inputs]).unwrap();
+/// ```
+pub fn prove_and_verify<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+    instances: Vec<Vec<C::Scalar>>,
+) -> Result<(), ConsistencyFailure> {
+    let params: Params<C> = Params::new(k);
+    let vk = keygen_vk(&params, &circuit.without_witnesses())?;
+    let pk = keygen_pk(&params, vk, &circuit.without_witnesses())?;
+
+    let n = params.n as usize;
+    let instances: Vec<_> = instances
+        .into_iter()
+        .map(|mut column| {
+            column.resize(n, C::Scalar::zero());
+            column
+        })
+        .collect();
+    let instance_polys: Vec<_> = instances
+        .iter()
+        .map(|column| pk.get_vk().get_domain().lagrange_from_vec(column.clone()))
+        .collect();
+
+    // Check the honest witness, while counting the advice assignments.
+    let honest = CorruptedCircuit::new(circuit, None);
+    let mock_result = MockProver::run(k, &honest, instances.clone())?.verify();
+    let advice_assignments = honest.corruption.seen();
+    let proof_verified = prove_and_verify_real(&params, &pk, circuit, &instance_polys)?;
+    match (mock_result, proof_verified) {
+        (Ok(()), true) => (),
+        (Err(failures), false) => return Err(ConsistencyFailure::MockProver(failures)),
+        (mock_result, _) => {
+            return Err(ConsistencyFailure::Mismatch {
+                mock_prover_accepted: mock_result.is_ok(),
+                corrupted_advice: None,
+            })
+        }
+    }
+
+    if advice_assignments == 0 {
+        return Ok(());
+    }
+
+    for index in 0..advice_assignments {
+        let mutation = Mutation {
+            index,
+            perturbation: Perturbation::Add(C::Scalar::one()),
+        };
+        let corrupted = CorruptedCircuit::new(circuit, Some(mutation));
+        if MockProver::run(k, &corrupted, instances.clone())?
+            .verify()
+            .is_ok()
+        {
+            // This cell is unconstrained; try the next one.
+            continue;
+        }
+
+        let corrupted = CorruptedCircuit::new(circuit, Some(mutation));
+        return if prove_and_verify_real(&params, &pk, &corrupted, &instance_polys)? {
+            Err(ConsistencyFailure::Mismatch {
+                mock_prover_accepted: false,
+                corrupted_advice: Some(index),
+            })
+        } else {
+            Ok(())
+        };
+    }
+
+    Err(ConsistencyFailure::UnconstrainedAdvice)
+}
+
+/// Creates a real proof for a single circuit instance and returns whether it verifies.
+///
+/// A prover that refuses to build a proof because a lookup input is missing from its
+/// table counts as a rejection.
+fn prove_and_verify_real<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuit: &ConcreteCircuit,
+    instances: &[Polynomial<C::Scalar, LagrangeCoeff>],
+) -> Result<bool, Error> {
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    match create_proof(
+        params,
+        pk,
+        slice::from_ref(circuit),
+        &[instances],
+        &mut transcript,
+    ) {
+        Ok(()) => (),
+        Err(Error::ConstraintSystemFailure) => return Ok(false),
+        Err(e) => return Err(e),
+    }
+    let proof = transcript.finalize();
+
+    let instance_commitments: Vec<C> = instances
+        .iter()
+        .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
+        .collect();
+
+    Ok(verify_single(params, pk.get_vk(), &proof, &instance_commitments).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::{EqAffine, Fp};
+
+    use super::{prove_and_verify, ConsistencyFailure};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Permutation, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct SquareConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        s: Selector,
+    }
+
+    #[derive(Default)]
+    struct SquareCircuit {
+        a: Option<Fp>,
+        constrained: bool,
+    }
+
+    impl Circuit<Fp> for SquareCircuit {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            SquareCircuit {
+                a: None,
+                constrained: self.constrained,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let s = meta.selector();
+
+            meta.create_gate("square", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a.clone() * a - b)]
+            });
+
+            SquareConfig { a, b, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: SquareConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    if self.constrained {
+                        config.s.enable(&mut region, 0)?;
+                    }
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.a.ok_or(Error::SynthesisError),
+                    )?;
+                    region.assign_advice(
+                        || "b",
+                        config.b,
+                        0,
+                        || self.a.map(|a| a.square()).ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn consistent() {
+        let circuit = SquareCircuit {
+            a: Some(Fp::from(3u64)),
+            constrained: true,
+        };
+        assert!(prove_and_verify::<EqAffine, _>(4, &circuit, vec![]).is_ok());
+    }
+
+    #[test]
+    fn unconstrained() {
+        let circuit = SquareCircuit {
+            a: Some(Fp::from(3u64)),
+            constrained: false,
+        };
+        assert!(matches!(
+            prove_and_verify::<EqAffine, _>(4, &circuit, vec![]),
+            Err(ConsistencyFailure::UnconstrainedAdvice)
+        ));
+    }
+
+    #[derive(Clone)]
+    struct WhitelistConfig {
+        a: Column<Advice>,
+        s: Selector,
+    }
+
+    /// Looks up each of its values in a whitelist provided as an instance column.
+    #[derive(Default)]
+    struct WhitelistCircuit {
+        values: Vec<Option<Fp>>,
+    }
+
+    impl Circuit<Fp> for WhitelistCircuit {
+        type Config = WhitelistConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            WhitelistCircuit {
+                values: vec![None; self.values.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> WhitelistConfig {
+            let a = meta.advice_column();
+            let whitelist = meta.instance_column();
+            let s = meta.selector();
+
+            meta.lookup(|meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let whitelist = meta.query_instance(whitelist, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![(s * a, whitelist)]
+            });
+
+            WhitelistConfig { a, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: WhitelistConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "values",
+                |mut region| {
+                    for (offset, value) in self.values.iter().enumerate() {
+                        config.s.enable(&mut region, offset)?;
+                        region.assign_advice(
+                            || "a",
+                            config.a,
+                            offset,
+                            || value.ok_or(Error::SynthesisError),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn instance_lookup_table() {
+        let whitelist: Vec<_> = [7u64, 11, 13].iter().map(|&v| Fp::from(v)).collect();
+        let circuit = |values: &[u64]| WhitelistCircuit {
+            values: values.iter().map(|&v| Some(Fp::from(v))).collect(),
+        };
+
+        assert!(
+            prove_and_verify::<EqAffine, _>(4, &circuit(&[13, 7, 7]), vec![whitelist.clone()])
+                .is_ok()
+        );
+
+        // A value that is not in the whitelist is rejected.
+        let prover = MockProver::run(4, &circuit(&[13, 8]), vec![whitelist]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[derive(Clone)]
+    struct ChainConfig {
+        columns: Vec<Column<Advice>>,
+        perm: Permutation,
+    }
+
+    /// Copies a value diagonally across a permutation argument over many columns.
+    #[derive(Default)]
+    struct ChainCircuit {
+        value: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for ChainCircuit {
+        type Config = ChainConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> ChainConfig {
+            let columns: Vec<_> = (0..5).map(|_| meta.advice_column()).collect();
+            let perm = meta.permutation(
+                &columns
+                    .iter()
+                    .map(|column| (*column).into())
+                    .collect::<Vec<Column<Any>>>(),
+            );
+
+            ChainConfig { columns, perm }
+        }
+
+        fn synthesize(
+            &self,
+            config: ChainConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "chain",
+                |mut region| {
+                    let mut prev = None;
+                    for (offset, column) in config.columns.iter().enumerate() {
+                        let cell = region.assign_advice(
+                            || "value",
+                            *column,
+                            offset,
+                            || self.value.ok_or(Error::SynthesisError),
+                        )?;
+                        if let Some(prev) = prev {
+                            region.constrain_equal(&config.perm, prev, cell)?;
+                        }
+                        prev = Some(cell);
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn chunked_permutation() {
+        // Without any gates, the permutation is split into single-column chunks so
+        // that the circuit keeps the smallest extended domain.
+        let mut cs = ConstraintSystem::<Fp>::default();
+        ChainCircuit::configure(&mut cs);
+        assert_eq!(cs.permutation_degree_bound(), 3);
+        assert_eq!(cs.permutations[0].num_chunks(3), 5);
+        assert_eq!(cs.degree(), 3);
+
+        let circuit = ChainCircuit {
+            value: Some(Fp::from(5u64)),
+        };
+        assert!(prove_and_verify::<EqAffine, _>(4, &circuit, vec![]).is_ok());
+    }
+}
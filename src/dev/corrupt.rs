@@ -0,0 +1,287 @@
+//! Circuit wrappers that perturb advice assignments during synthesis.
+
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+
+use ff::Field;
+
+use crate::{
+    circuit::{layouter::RegionLayouter, Cell as CircuitCell, Layouter, Region},
+    plonk::{
+        Advice, Assigned, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Permutation,
+        Selector,
+    },
+};
+
+/// A change made to an assigned advice value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Perturbation<F> {
+    /// Adds the given field element to the assigned value.
+    Add(F),
+    /// Replaces the assigned value with the given field element.
+    Replace(F),
+}
+
+impl<F: Field> Perturbation<F> {
+    fn apply(&self, value: Assigned<F>) -> Assigned<F> {
+        match self {
+            Perturbation::Add(delta) => value + *delta,
+            Perturbation::Replace(replacement) => (*replacement).into(),
+        }
+    }
+}
+
+/// A perturbation of a single advice assignment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mutation<F> {
+    /// The index (in synthesis order) of the advice assignment to perturb.
+    pub index: usize,
+    /// The change made to the assigned value.
+    pub perturbation: Perturbation<F>,
+}
+
+/// The location of an advice assignment within a circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdviceLocation {
+    /// The name of the region containing the assignment.
+    pub region: String,
+    /// The annotation of the assignment.
+    pub annotation: String,
+    /// The column that was assigned.
+    pub column: Column<Advice>,
+    /// The offset of the assigned cell within its region.
+    pub offset: usize,
+}
+
+/// Tracks advice assignments, and optionally mutates one of them.
+#[derive(Debug)]
+pub(super) struct Corruption<F> {
+    /// The mutation to apply, if any.
+    target: Option<Mutation<F>>,
+    /// The number of advice values that have been computed so far.
+    seen: Cell<usize>,
+    /// The location of the mutated assignment, once it has been reached.
+    location: RefCell<Option<AdviceLocation>>,
+}
+
+impl<F: Field> Corruption<F> {
+    fn new(target: Option<Mutation<F>>) -> Self {
+        Corruption {
+            target,
+            seen: Cell::new(0),
+            location: RefCell::new(None),
+        }
+    }
+
+    /// Returns the number of advice values that have been computed so far.
+    pub(super) fn seen(&self) -> usize {
+        self.seen.get()
+    }
+
+    /// Returns the location of the mutated assignment, if it was reached.
+    pub(super) fn location(&self) -> Option<AdviceLocation> {
+        self.location.borrow().clone()
+    }
+
+    fn apply(
+        &self,
+        value: Result<Assigned<F>, Error>,
+        location: impl FnOnce() -> AdviceLocation,
+    ) -> Result<Assigned<F>, Error> {
+        let index = self.seen.get();
+        self.seen.set(index + 1);
+        match self.target {
+            Some(mutation) if mutation.index == index => {
+                *self.location.borrow_mut() = Some(location());
+                value.map(|value| mutation.perturbation.apply(value))
+            }
+            _ => value,
+        }
+    }
+}
+
+enum MaybeOwned<'a, T> {
+    Borrowed(&'a T),
+    Owned(T),
+}
+
+/// A wrapper around a circuit that mutates one of its advice assignments.
+pub(super) struct CorruptedCircuit<'a, F, ConcreteCircuit> {
+    circuit: MaybeOwned<'a, ConcreteCircuit>,
+    pub(super) corruption: Corruption<F>,
+}
+
+impl<'a, F: Field, ConcreteCircuit> CorruptedCircuit<'a, F, ConcreteCircuit> {
+    pub(super) fn new(circuit: &'a ConcreteCircuit, target: Option<Mutation<F>>) -> Self {
+        CorruptedCircuit {
+            circuit: MaybeOwned::Borrowed(circuit),
+            corruption: Corruption::new(target),
+        }
+    }
+
+    fn inner(&self) -> &ConcreteCircuit {
+        match &self.circuit {
+            MaybeOwned::Borrowed(circuit) => *circuit,
+            MaybeOwned::Owned(circuit) => circuit,
+        }
+    }
+}
+
+impl<'a, F: Field, ConcreteCircuit: Circuit<F>> Circuit<F>
+    for CorruptedCircuit<'a, F, ConcreteCircuit>
+{
+    type Config = ConcreteCircuit::Config;
+    type FloorPlanner = ConcreteCircuit::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        CorruptedCircuit {
+            circuit: MaybeOwned::Owned(self.inner().without_witnesses()),
+            corruption: Corruption::new(self.corruption.target),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        ConcreteCircuit::configure(meta)
+    }
+
+    fn configure_from(&self, meta: &mut ConstraintSystem<F>) -> Self::Config {
+        self.inner().configure_from(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.inner().synthesize(
+            config,
+            CorruptingLayouter {
+                layouter,
+                corruption: &self.corruption,
+                _marker: PhantomData,
+            },
+        )
+    }
+}
+
+/// A [`Layouter`] that passes every advice value through a [`Corruption`].
+struct CorruptingLayouter<'c, F: Field, L: Layouter<F>> {
+    layouter: L,
+    corruption: &'c Corruption<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<'c, F: Field, L: Layouter<F>> Layouter<F> for CorruptingLayouter<'c, F, L> {
+    type Root = Self;
+
+    fn assign_region<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let corruption = self.corruption;
+        let region_name: String = name().into();
+        self.layouter.assign_region(name, |region| {
+            let mut region = CorruptingRegion {
+                region,
+                name: &region_name,
+                corruption,
+            };
+            let region: &mut dyn RegionLayouter<F> = &mut region;
+            assignment(region.into())
+        })
+    }
+
+    fn get_root(&mut self) -> &mut Self::Root {
+        self
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.layouter.get_root().push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.layouter.get_root().pop_namespace(gadget_name)
+    }
+}
+
+#[derive(Debug)]
+struct CorruptingRegion<'r, 'c, F: Field> {
+    region: Region<'r, F>,
+    name: &'c str,
+    corruption: &'c Corruption<F>,
+}
+
+impl<'r, 'c, F: Field> RegionLayouter<F> for CorruptingRegion<'r, 'c, F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.region.enable_selector(annotation, selector, offset)
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<CircuitCell, Error> {
+        let (name, corruption) = (self.name, self.corruption);
+        self.region.assign_advice(annotation, column, offset, || {
+            corruption.apply(to(), || AdviceLocation {
+                region: name.to_owned(),
+                annotation: annotation(),
+                column,
+                offset,
+            })
+        })
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Result<Assigned<F>, Error> + 'v),
+    ) -> Result<CircuitCell, Error> {
+        self.region.assign_fixed(annotation, column, offset, to)
+    }
+
+    fn constrain_equal(
+        &mut self,
+        permutation: &Permutation,
+        left: CircuitCell,
+        right: CircuitCell,
+    ) -> Result<(), Error> {
+        self.region.constrain_equal(permutation, left, right)
+    }
+
+    fn constrain_constant(
+        &mut self,
+        permutation: &Permutation,
+        cell: CircuitCell,
+        constant: Assigned<F>,
+    ) -> Result<(), Error> {
+        self.region.constrain_constant(permutation, cell, constant)
+    }
+
+    fn fixed_value(&self, cell: CircuitCell) -> Result<Option<F>, Error> {
+        self.region.fixed_value(cell)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.region.query_instance(column, row)
+    }
+
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        self.region.mark_instance_dependent()
+    }
+
+    fn set_min_rows(&mut self, rows: usize) {
+        self.region.set_min_rows(rows)
+    }
+}
@@ -0,0 +1,129 @@
+//! Checks that key generation does not depend on anything but the circuit.
+
+use super::snapshot::{diff_lines, vk_snapshot};
+use crate::{
+    arithmetic::CurveAffine,
+    plonk::{keygen_vk, Circuit},
+    poly::commitment::Params,
+};
+
+/// Checks that generating the verifying key of a circuit is deterministic, panicking if
+/// it is not.
+///
+/// The verifying key is generated twice, and the two keys must have identical
+/// [`PinnedVerificationKey`]s and identical encodings. A key that changes from one run
+/// of key generation to the next breaks compatibility with verifiers that hold an
+/// earlier key, so this catches circuits (and floor planners) whose layout depends on
+/// the iteration order of a `HashMap`, on the thread that ran them, or on other state
+/// that is not part of the circuit. Each run uses freshly seeded hash maps, so
+/// differences in iteration order show up as differences between the keys.
+///
+/// ```ignore
+/// halo2::dev::assert_deterministic_keygen::<EqAffine, _>(K, &circuit);
+/// ```
+///
+/// [`PinnedVerificationKey`]: crate::plonk::PinnedVerificationKey
+pub fn assert_deterministic_keygen<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+) {
+    let params: Params<C> = Params::new(k);
+    let keygen = || {
+        let vk = keygen_vk(&params, &circuit.without_witnesses())
+            .unwrap_or_else(|e| panic!("Key generation failed: {:?}", e));
+        let mut encoding = vec![];
+        vk.write(&mut encoding)
+            .expect("writing to a Vec does not fail");
+        (vk_snapshot(&vk), encoding)
+    };
+
+    let (first_snapshot, first_encoding) = keygen();
+    let (second_snapshot, second_encoding) = keygen();
+
+    if first_snapshot != second_snapshot {
+        panic!(
+            "Key generation is not deterministic; the pinned verifying keys differ:\n{}",
+            diff_lines(&first_snapshot, &second_snapshot)
+        );
+    }
+    if first_encoding != second_encoding {
+        panic!("Key generation is not deterministic; the encoded verifying keys differ");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use pasta_curves::{EqAffine, Fp};
+
+    use super::assert_deterministic_keygen;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{Circuit, Column, ConstraintSystem, Error, Fixed},
+    };
+
+    thread_local! {
+        static COUNTER: Cell<u64> = Cell::new(0);
+    }
+
+    /// Assigns a fixed cell, either with a constant value or with a value that changes
+    /// every time the circuit is synthesized.
+    struct CounterCircuit {
+        deterministic: bool,
+    }
+
+    impl Circuit<Fp> for CounterCircuit {
+        type Config = Column<Fixed>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            CounterCircuit {
+                deterministic: self.deterministic,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Column<Fixed> {
+            meta.fixed_column()
+        }
+
+        fn synthesize(
+            &self,
+            column: Column<Fixed>,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let value = if self.deterministic {
+                1
+            } else {
+                COUNTER.with(|counter| {
+                    counter.set(counter.get() + 1);
+                    counter.get()
+                })
+            };
+            layouter.assign_region(
+                || "counter",
+                |mut region| {
+                    region.assign_fixed(|| "value", column, 0, || Ok(Fp::from(value)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let circuit = CounterCircuit {
+            deterministic: true,
+        };
+        assert_deterministic_keygen::<EqAffine, _>(4, &circuit);
+    }
+
+    #[test]
+    #[should_panic(expected = "Key generation is not deterministic")]
+    fn nondeterministic() {
+        let circuit = CounterCircuit {
+            deterministic: false,
+        };
+        assert_deterministic_keygen::<EqAffine, _>(4, &circuit);
+    }
+}
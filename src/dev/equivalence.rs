@@ -0,0 +1,229 @@
+//! Equivalence checks between two implementations of a circuit.
+
+use super::{MockProver, VerifyFailure};
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, ConstraintSystem},
+};
+
+/// Checks that two circuit implementations are equivalent, panicking if they are not.
+///
+/// For each input (a pair of circuits carrying the same witness, along with the values
+/// of the instance columns), the circuits must configure identical pinned constraint
+/// systems (see [`PinnedConstraintSystem::diff`]), so that they have the same gates,
+/// queries, permutations and lookups, and [`MockProver`] must accept either both
+/// circuits or neither of them. The circuits are configured from their values, with
+/// [`Circuit::configure_from`].
+///
+/// This supports refactors in which a hand-optimized circuit must match a reference
+/// implementation. The inputs should include witnesses that the reference rejects, so
+/// that the check covers the constraints as well as the assignments.
+///
+/// ```ignore
+/// halo2::dev::assert_equivalent(
+///     K,
+///     &[
+///         (Reference { a: Some(x) }, Optimized { a: Some(x) }, vec![vec![y]]),
+///         (Reference { a: Some(z) }, Optimized { a: Some(z) }, vec![vec![y]]),
+///     ],
+/// );
+/// ```
+///
+/// [`PinnedConstraintSystem::diff`]: crate::plonk::PinnedConstraintSystem::diff
+pub fn assert_equivalent<F: FieldExt, A: Circuit<F>, B: Circuit<F>>(
+    k: u32,
+    inputs: &[(A, B, Vec<Vec<F>>)],
+) {
+    for (index, (a, b, instance)) in inputs.iter().enumerate() {
+        let mut cs_a = ConstraintSystem::default();
+        a.configure_from(&mut cs_a);
+        let mut cs_b = ConstraintSystem::default();
+        b.configure_from(&mut cs_b);
+
+        let diff = cs_a.pinned().diff(&cs_b.pinned());
+        if !diff.is_empty() {
+            panic!(
+                "Input {}: the circuits have different constraint systems:\n{}",
+                index, diff
+            );
+        }
+
+        let a = check(k, a, instance);
+        let b = check(k, b, instance);
+        match (a, b) {
+            (Ok(()), Ok(())) | (Err(_), Err(_)) => (),
+            (Ok(()), Err(reason)) => panic!(
+                "Input {}: the first circuit accepted it, but the second rejected it:\n{}",
+                index, reason
+            ),
+            (Err(reason), Ok(())) => panic!(
+                "Input {}: the second circuit accepted it, but the first rejected it:\n{}",
+                index, reason
+            ),
+        }
+    }
+}
+
+/// Runs [`MockProver`] on a circuit, describing why it was rejected if it was.
+fn check<F: FieldExt, C: Circuit<F>>(
+    k: u32,
+    circuit: &C,
+    instance: &[Vec<F>],
+) -> Result<(), String> {
+    let prover = MockProver::run(k, circuit, instance.to_vec())
+        .map_err(|e| format!("synthesis failed: {:?}", e))?;
+    prover.verify().map_err(|failures| {
+        failures
+            .iter()
+            .map(VerifyFailure::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::assert_equivalent;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct SquareConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        s: Selector,
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let s = meta.selector();
+        meta.create_gate("square", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let s = meta.query_selector(s);
+            vec![s * (a.clone() * a - b)]
+        });
+        SquareConfig { a, b, s }
+    }
+
+    /// Checks that `b = a^2`, computing `b` from `a`.
+    #[derive(Default)]
+    struct Reference {
+        a: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for Reference {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: SquareConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    let a = || self.a.ok_or(Error::SynthesisError);
+                    region.assign_advice(|| "a", config.a, 0, a)?;
+                    region.assign_advice(|| "b", config.b, 0, || a().map(|a| a * a))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Checks that `b = a^2`, with `b` given directly.
+    #[derive(Default)]
+    struct Given {
+        a: Option<Fp>,
+        b: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for Given {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: SquareConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.a.ok_or(Error::SynthesisError),
+                    )?;
+                    region.assign_advice(
+                        || "b",
+                        config.b,
+                        0,
+                        || self.b.ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn equivalent() {
+        let a = Fp::from(3u64);
+        assert_equivalent(
+            3,
+            &[(
+                Reference { a: Some(a) },
+                Given {
+                    a: Some(a),
+                    b: Some(a * a),
+                },
+                vec![],
+            )],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the first circuit accepted it, but the second rejected it")]
+    fn not_equivalent() {
+        let a = Fp::from(3u64);
+        assert_equivalent(
+            3,
+            &[(
+                Reference { a: Some(a) },
+                Given {
+                    a: Some(a),
+                    b: Some(a),
+                },
+                vec![],
+            )],
+        );
+    }
+}
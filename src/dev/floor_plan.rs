@@ -0,0 +1,476 @@
+//! Reports of where a floor planner placed each region of a circuit.
+
+use std::cmp;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use ff::Field;
+
+use crate::circuit::{floor_planner::V1, SimpleFloorPlanner};
+use crate::plonk::{
+    Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+    FloorPlanner, Permutation, Selector,
+};
+
+/// The rows and columns that a floor planner chose for a region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionPlacement {
+    /// The name of the region. Not required to be unique.
+    pub name: String,
+    /// The first row of the region.
+    pub start: usize,
+    /// The number of rows reserved for the region.
+    pub rows: usize,
+    /// The columns that the region assigned cells in, in column order.
+    pub columns: Vec<Column<Any>>,
+}
+
+impl RegionPlacement {
+    /// Returns the row after the last row of the region.
+    pub fn end(&self) -> usize {
+        self.start + self.rows
+    }
+}
+
+/// The placement of every region of a circuit, as chosen by its floor planner.
+///
+/// This lists exactly which rows each region occupies, without needing to render the
+/// layout with `CircuitLayout`. Floor planners report the rows that they reserve for
+/// each region with [`Assignment::place_region`]; for floor planners that do not, the
+/// placement of a region is taken from the cells that it assigns.
+///
+/// # Examples
+///
+/// ```ignore
+/// use halo2::dev::FloorPlan;
+///
+/// let plan = FloorPlan::new(&MyCircuit::default())?;
+/// println!("{}", plan);
+/// assert!(plan.total_rows() <= 1 << k);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FloorPlan {
+    regions: Vec<RegionPlacement>,
+    total_rows: usize,
+}
+
+impl FloorPlan {
+    /// Synthesizes the given circuit with its floor planner, and records where each
+    /// region was placed.
+    pub fn new<F: Field, ConcreteCircuit: Circuit<F>>(
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, Error> {
+        Self::with_floor_planner::<F, ConcreteCircuit::FloorPlanner, ConcreteCircuit>(circuit)
+    }
+
+    /// Synthesizes the given circuit with the floor planner `P` instead of its own, and
+    /// records where each region was placed.
+    pub fn with_floor_planner<F: Field, P: FloorPlanner, ConcreteCircuit: Circuit<F>>(
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = circuit.configure_from(&mut cs);
+        let mut recorder = Recorder::default();
+        P::synthesize(&mut recorder, circuit, config)?;
+        Ok(Self::from_recorder(recorder))
+    }
+
+    fn from_recorder(recorder: Recorder) -> Self {
+        FloorPlan {
+            regions: recorder
+                .regions
+                .into_iter()
+                .map(|region| {
+                    let (start, rows) = region.placement.unwrap_or_else(|| {
+                        region
+                            .assigned_rows
+                            .map(|(first, last)| (first, last - first + 1))
+                            .unwrap_or((0, 0))
+                    });
+                    RegionPlacement {
+                        name: region.name,
+                        start,
+                        rows,
+                        columns: region.columns.into_iter().collect(),
+                    }
+                })
+                .collect(),
+            total_rows: recorder.total_rows,
+        }
+    }
+
+    /// Returns the placements of the regions, in the order in which they were
+    /// assigned.
+    pub fn regions(&self) -> &[RegionPlacement] {
+        &self.regions
+    }
+
+    /// Returns the number of rows used by the circuit: the row after the last row that
+    /// a region reserves, or that a cell (such as a constant) is assigned in.
+    pub fn total_rows(&self) -> usize {
+        self.total_rows
+    }
+}
+
+impl fmt::Display for FloorPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} rows used", self.total_rows)?;
+        for region in &self.regions {
+            writeln!(
+                f,
+                "  rows {:>6}..{:<6} {:>3} columns  {}",
+                region.start,
+                region.end(),
+                region.columns.len(),
+                region.name
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The measurements of a circuit under one floor planner, as reported by
+/// [`FloorPlannerComparison`].
+#[derive(Clone, Debug)]
+pub struct PlannerMeasurement {
+    /// The name under which the floor planner was measured.
+    pub name: &'static str,
+    /// The number of rows used by the circuit; see [`FloorPlan::total_rows`].
+    pub rows: usize,
+    /// The smallest `k` for which the circuit fits, accounting for the rotations at
+    /// which columns are queried.
+    pub k: u32,
+    /// The time taken to synthesize the circuit and compute its witness.
+    pub synthesis_time: Duration,
+}
+
+/// Measurements of a circuit under several floor planners, for choosing
+/// [`Circuit::FloorPlanner`].
+///
+/// The circuit is synthesized with each floor planner in turn, computing every
+/// assigned value, so it must be given with its witnesses.
+///
+/// # Examples
+///
+/// ```ignore
+/// use halo2::dev::FloorPlannerComparison;
+///
+/// let mut comparison = FloorPlannerComparison::new(&circuit)?;
+/// comparison.measure::<_, MyFloorPlanner, _>("MyFloorPlanner", &circuit)?;
+/// println!("{}", comparison);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FloorPlannerComparison {
+    measurements: Vec<PlannerMeasurement>,
+}
+
+impl FloorPlannerComparison {
+    /// Measures the given circuit under [`SimpleFloorPlanner`] and [`V1`].
+    pub fn new<F: Field, ConcreteCircuit: Circuit<F>>(
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, Error> {
+        let mut comparison = FloorPlannerComparison::default();
+        comparison.measure::<F, SimpleFloorPlanner, _>("SimpleFloorPlanner", circuit)?;
+        comparison.measure::<F, V1, _>("V1", circuit)?;
+        Ok(comparison)
+    }
+
+    /// Measures the given circuit under the floor planner `P`, reporting it as `name`.
+    pub fn measure<F: Field, P: FloorPlanner, ConcreteCircuit: Circuit<F>>(
+        &mut self,
+        name: &'static str,
+        circuit: &ConcreteCircuit,
+    ) -> Result<(), Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = circuit.configure_from(&mut cs);
+        let mut recorder = Recorder {
+            evaluate: true,
+            ..Recorder::default()
+        };
+
+        let start = Instant::now();
+        P::synthesize(&mut recorder, circuit, config)?;
+        let synthesis_time = start.elapsed();
+
+        let (backward, forward) = cs.max_rotation();
+        let rows = recorder.total_rows;
+        let mut k = 0;
+        while (1 << k) < rows + cmp::max(backward, forward) {
+            k += 1;
+        }
+
+        self.measurements.push(PlannerMeasurement {
+            name,
+            rows,
+            k,
+            synthesis_time,
+        });
+        Ok(())
+    }
+
+    /// Returns the measurements, in the order in which they were made.
+    pub fn measurements(&self) -> &[PlannerMeasurement] {
+        &self.measurements
+    }
+
+    /// Returns the measurement with the smallest `k`, breaking ties by the number of
+    /// rows and then by the synthesis time.
+    pub fn best(&self) -> Option<&PlannerMeasurement> {
+        self.measurements
+            .iter()
+            .min_by_key(|m| (m.k, m.rows, m.synthesis_time))
+    }
+}
+
+impl fmt::Display for FloorPlannerComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>10} {:>4} {:>16}",
+            "floor planner", "rows", "k", "synthesis time"
+        )?;
+        for m in &self.measurements {
+            writeln!(
+                f,
+                "{:<24} {:>10} {:>4} {:>16}",
+                m.name,
+                m.rows,
+                m.k,
+                format!("{:?}", m.synthesis_time)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct RecordedRegion {
+    name: String,
+    /// The rows reported by the floor planner, if any.
+    placement: Option<(usize, usize)>,
+    /// The first and last rows assigned in this region.
+    assigned_rows: Option<(usize, usize)>,
+    columns: BTreeSet<Column<Any>>,
+}
+
+#[derive(Debug, Default)]
+struct Recorder {
+    regions: Vec<RecordedRegion>,
+    current_region: Option<usize>,
+    total_rows: usize,
+    /// Whether assigned values are computed, so that synthesis can be timed.
+    evaluate: bool,
+}
+
+impl Recorder {
+    fn update(&mut self, column: Column<Any>, row: usize) {
+        self.total_rows = cmp::max(self.total_rows, row + 1);
+
+        if let Some(region) = self.current_region {
+            let region = &mut self.regions[region];
+            region.columns.insert(column);
+            region.assigned_rows = Some(match region.assigned_rows {
+                Some((first, last)) => (cmp::min(first, row), cmp::max(last, row)),
+                None => (row, row),
+            });
+        }
+    }
+}
+
+impl<F: Field> Assignment<F> for Recorder {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        assert!(self.current_region.is_none());
+        self.current_region = Some(self.regions.len());
+        self.regions.push(RecordedRegion {
+            name: name_fn().into(),
+            placement: None,
+            assigned_rows: None,
+            columns: BTreeSet::new(),
+        });
+    }
+
+    fn exit_region(&mut self) {
+        assert!(self.current_region.is_some());
+        self.current_region = None;
+    }
+
+    fn place_region(&mut self, start: usize, rows: usize) {
+        let region = self
+            .current_region
+            .expect("regions are placed after they are entered");
+        self.regions[region].placement = Some((start, rows));
+        self.total_rows = cmp::max(self.total_rows, start + rows);
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update(selector.0.into(), row);
+        Ok(())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if self.evaluate {
+            let value: Assigned<F> = to()?.into();
+            value.evaluate();
+        }
+        self.update(column.into(), row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if self.evaluate {
+            let value: Assigned<F> = to()?.into();
+            value.evaluate();
+        }
+        self.update(column.into(), row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: &Permutation,
+        _: Column<Any>,
+        _: usize,
+        _: Column<Any>,
+        _: usize,
+    ) -> Result<(), Error> {
+        // Do nothing; we don't care about permutations in this context.
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        // Do nothing; we don't care about namespaces in this context.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{FloorPlan, FloorPlannerComparison};
+    use crate::{
+        circuit::{floor_planner::V1, Layouter, SimpleFloorPlanner},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, FloorPlanner},
+    };
+
+    #[derive(Clone)]
+    struct PlanConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+    }
+
+    /// Assigns three rows in `a`, two rows in `b`, and then one row in both.
+    struct PlanCircuit<P: FloorPlanner>(std::marker::PhantomData<P>);
+
+    impl<P: FloorPlanner> Circuit<Fp> for PlanCircuit<P> {
+        type Config = PlanConfig;
+        type FloorPlanner = P;
+
+        fn without_witnesses(&self) -> Self {
+            PlanCircuit(std::marker::PhantomData)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> PlanConfig {
+            PlanConfig {
+                a: meta.advice_column(),
+                b: meta.advice_column(),
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: PlanConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let mut assign = |name: &'static str, columns: &[Column<Advice>], rows: usize| {
+                layouter.assign_region(
+                    || name,
+                    |mut region| {
+                        for column in columns {
+                            for row in 0..rows {
+                                region.assign_advice(|| name, *column, row, || Ok(Fp::zero()))?;
+                            }
+                        }
+                        Ok(())
+                    },
+                )
+            };
+            assign("a", &[config.a], 3)?;
+            assign("b", &[config.b], 2)?;
+            assign("both", &[config.a, config.b], 1)
+        }
+    }
+
+    #[test]
+    fn floor_plan() {
+        fn placements(plan: &FloorPlan) -> Vec<(&str, usize, usize)> {
+            plan.regions()
+                .iter()
+                .map(|region| (region.name.as_str(), region.start, region.rows))
+                .collect()
+        }
+
+        let plan =
+            FloorPlan::new(&PlanCircuit::<SimpleFloorPlanner>(std::marker::PhantomData)).unwrap();
+        assert_eq!(
+            placements(&plan),
+            vec![("a", 0, 3), ("b", 0, 2), ("both", 3, 1)]
+        );
+        assert_eq!(plan.regions()[2].columns.len(), 2);
+        assert_eq!(plan.total_rows(), 4);
+
+        let plan = FloorPlan::new(&PlanCircuit::<V1>(std::marker::PhantomData)).unwrap();
+        assert_eq!(plan.regions().len(), 3);
+        assert_eq!(plan.total_rows(), 4);
+        assert!(format!("{}", plan).starts_with("4 rows used\n"));
+
+        let comparison =
+            FloorPlannerComparison::new(&PlanCircuit::<V1>(std::marker::PhantomData)).unwrap();
+        let measurements = comparison.measurements();
+        assert_eq!(measurements.len(), 2);
+        assert_eq!(measurements[0].name, "SimpleFloorPlanner");
+        assert_eq!(measurements[0].rows, 4);
+        assert_eq!(measurements[0].k, 2);
+        assert_eq!(measurements[1].rows, 4);
+    }
+}
@@ -0,0 +1,294 @@
+//! Soundness fuzzing for circuits.
+//!
+//! [`fuzz`] synthesizes a circuit with a valid witness, then repeatedly perturbs one of
+//! its advice assignments and checks that [`MockProver`] rejects the result. Any
+//! perturbation that is accepted points to an under-constrained cell.
+//!
+//! [`MockProver`]: super::MockProver
+
+use std::fmt;
+
+use ff::Field;
+use rand::{Rng, RngCore};
+
+use super::{corrupt::CorruptedCircuit, MockProver, VerifyFailure};
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, Error},
+};
+
+pub use super::corrupt::{AdviceLocation, Mutation, Perturbation};
+
+/// A source of mutations to apply to a circuit's advice assignments.
+pub trait MutationStrategy<F> {
+    /// Returns the mutation to try next, given the total number of advice assignments
+    /// made while synthesizing the circuit, or `None` once fuzzing should stop.
+    fn next_mutation(&mut self, advice_assignments: usize) -> Option<Mutation<F>>;
+}
+
+/// Adds one to each advice assignment in turn, stopping after the last assignment.
+#[derive(Debug, Default)]
+pub struct Exhaustive {
+    next: usize,
+}
+
+impl<F: Field> MutationStrategy<F> for Exhaustive {
+    fn next_mutation(&mut self, advice_assignments: usize) -> Option<Mutation<F>> {
+        if self.next >= advice_assignments {
+            return None;
+        }
+        let index = self.next;
+        self.next += 1;
+        Some(Mutation {
+            index,
+            perturbation: Perturbation::Add(F::one()),
+        })
+    }
+}
+
+/// Adds a random non-zero value to a randomly-chosen advice assignment, for a fixed
+/// number of trials.
+#[derive(Debug)]
+pub struct Random<R: RngCore> {
+    rng: R,
+    trials: usize,
+}
+
+impl<R: RngCore> Random<R> {
+    /// Returns a strategy that produces `trials` mutations using the given RNG.
+    pub fn new(rng: R, trials: usize) -> Self {
+        Random { rng, trials }
+    }
+}
+
+impl<F: Field, R: RngCore> MutationStrategy<F> for Random<R> {
+    fn next_mutation(&mut self, advice_assignments: usize) -> Option<Mutation<F>> {
+        if self.trials == 0 || advice_assignments == 0 {
+            return None;
+        }
+        self.trials -= 1;
+
+        let index = self.rng.gen_range(0..advice_assignments);
+        let delta = loop {
+            let delta = F::random(&mut self.rng);
+            if delta != F::zero() {
+                break delta;
+            }
+        };
+        Some(Mutation {
+            index,
+            perturbation: Perturbation::Add(delta),
+        })
+    }
+}
+
+/// The reasons why [`fuzz`] can fail to run.
+#[derive(Debug)]
+pub enum FuzzError {
+    /// An error occurred while synthesizing the circuit.
+    Error(Error),
+    /// `MockProver` rejected the unmodified witness, so there is nothing to fuzz.
+    HonestWitnessRejected(Vec<VerifyFailure>),
+}
+
+impl From<Error> for FuzzError {
+    fn from(error: Error) -> Self {
+        FuzzError::Error(error)
+    }
+}
+
+/// A mutation that `MockProver` incorrectly accepted.
+#[derive(Debug, PartialEq)]
+pub struct AcceptedMutation<F> {
+    /// The mutation that was applied.
+    pub mutation: Mutation<F>,
+    /// Where the mutated advice value was assigned, if the mutation was applied.
+    pub location: Option<AdviceLocation>,
+}
+
+/// The outcome of a fuzzing run.
+#[derive(Debug)]
+pub struct FuzzReport<F> {
+    /// The number of mutations that were tried.
+    pub trials: usize,
+    /// The mutations that `MockProver` accepted.
+    pub accepted: Vec<AcceptedMutation<F>>,
+}
+
+impl<F> FuzzReport<F> {
+    /// Returns `true` if every mutation was rejected.
+    pub fn is_sound(&self) -> bool {
+        self.accepted.is_empty()
+    }
+}
+
+impl<F: fmt::Debug> FuzzReport<F> {
+    /// Panics with a description of every accepted mutation, if there are any.
+    pub fn assert_sound(&self) {
+        if !self.is_sound() {
+            panic!("{}", self);
+        }
+    }
+}
+
+impl<F: fmt::Debug> fmt::Display for FuzzReport<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} mutations were accepted by MockProver",
+            self.accepted.len(),
+            self.trials
+        )?;
+        for accepted in &self.accepted {
+            write!(
+                f,
+                "- advice assignment {} ({:?})",
+                accepted.mutation.index, accepted.mutation.perturbation
+            )?;
+            if let Some(location) = &accepted.location {
+                write!(
+                    f,
+                    ": '{}' in region '{}', column {:?}, offset {}",
+                    location.annotation, location.region, location.column, location.offset
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fuzzes the advice assignments of a circuit.
+///
+/// The circuit must be satisfied by its unmodified witness. Mutations are then drawn from
+/// `strategy` until it is exhausted, and each one is applied to a fresh synthesis of the
+/// circuit. Mutations that [`MockProver`] accepts are returned in the report.
+///
+/// Strategies that replace values should avoid replacing a value with itself, as the
+/// unchanged witness will always be accepted.
+///
+/// ```ignore
+/// use halo2::dev::fuzz::{fuzz, Exhaustive};
+///
+/// fuzz(K, &circuit, vec![public_inputs], Exhaustive::default())
+///     .unwrap()
+///     .assert_sound();
+/// ```
+///
+/// [`MockProver`]: super::MockProver
+pub fn fuzz<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+    k: u32,
+    circuit: &ConcreteCircuit,
+    instance: Vec<Vec<F>>,
+    mut strategy: impl MutationStrategy<F>,
+) -> Result<FuzzReport<F>, FuzzError> {
+    let honest = CorruptedCircuit::new(circuit, None);
+    MockProver::run(k, &honest, instance.clone())?
+        .verify()
+        .map_err(FuzzError::HonestWitnessRejected)?;
+    let advice_assignments = honest.corruption.seen();
+
+    let mut report = FuzzReport {
+        trials: 0,
+        accepted: vec![],
+    };
+    while let Some(mutation) = strategy.next_mutation(advice_assignments) {
+        report.trials += 1;
+        let corrupted = CorruptedCircuit::new(circuit, Some(mutation));
+        if MockProver::run(k, &corrupted, instance.clone())?
+            .verify()
+            .is_ok()
+        {
+            report.accepted.push(AcceptedMutation {
+                mutation,
+                location: corrupted.corruption.location(),
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{fuzz, Exhaustive};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct DoubleConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        s: Selector,
+    }
+
+    /// Constrains `b = 2a`, but leaves `c` unconstrained.
+    #[derive(Default)]
+    struct DoubleCircuit {
+        a: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for DoubleCircuit {
+        type Config = DoubleConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> DoubleConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.advice_column();
+            let s = meta.selector();
+
+            meta.create_gate("double", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a.clone() + a - b)]
+            });
+
+            DoubleConfig { a, b, c, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: DoubleConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "double",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    let a = || self.a.ok_or(Error::SynthesisError);
+                    region.assign_advice(|| "a", config.a, 0, a)?;
+                    region.assign_advice(|| "b", config.b, 0, || a().map(|a| a + a))?;
+                    region.assign_advice(|| "c", config.c, 0, a)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn finds_unconstrained_cell() {
+        let circuit = DoubleCircuit {
+            a: Some(Fp::from(5u64)),
+        };
+        let report = fuzz(3, &circuit, vec![], Exhaustive::default()).unwrap();
+
+        assert_eq!(report.trials, 3);
+        assert_eq!(report.accepted.len(), 1);
+        let location = report.accepted[0].location.as_ref().unwrap();
+        assert_eq!(location.region, "double");
+        assert_eq!(location.annotation, "c");
+        assert_eq!(location.offset, 0);
+    }
+}
@@ -73,7 +73,7 @@ impl CircuitLayout {
 
         // Collect the layout details.
         let mut cs = ConstraintSystem::default();
-        let config = ConcreteCircuit::configure(&mut cs);
+        let config = circuit.configure_from(&mut cs);
         let mut layout = Layout::default();
         ConcreteCircuit::FloorPlanner::synthesize(&mut layout, circuit, config).unwrap();
 
@@ -0,0 +1,477 @@
+//! Machine-readable descriptions of verification failures and constraint systems.
+
+use std::fmt::{self, Write};
+
+use super::{metadata, MockProver, VerifyFailure};
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{Any, Column, ConstraintSystem, Expression},
+};
+
+/// The version of the schema written by [`ConstraintSystem::export_ir`].
+const IR_SCHEMA_VERSION: u32 = 1;
+
+/// Writes the fields of a JSON object.
+struct JsonObject {
+    out: String,
+    empty: bool,
+}
+
+impl JsonObject {
+    fn new() -> Self {
+        JsonObject {
+            out: String::from("{"),
+            empty: true,
+        }
+    }
+
+    /// Adds a field whose value is already encoded as JSON.
+    fn raw(mut self, key: &str, value: &str) -> Self {
+        if !self.empty {
+            self.out.push(',');
+        }
+        self.empty = false;
+        write_string(&mut self.out, key);
+        self.out.push(':');
+        self.out.push_str(value);
+        self
+    }
+
+    fn string(self, key: &str, value: &str) -> Self {
+        let mut encoded = String::new();
+        write_string(&mut encoded, value);
+        self.raw(key, &encoded)
+    }
+
+    fn number<N: fmt::Display>(self, key: &str, value: N) -> Self {
+        self.raw(key, &value.to_string())
+    }
+
+    fn finish(mut self) -> String {
+        self.out.push('}');
+        self.out
+    }
+}
+
+/// Writes `value` as a JSON string.
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn array<I: IntoIterator<Item = String>>(items: I) -> String {
+    let items: Vec<_> = items.into_iter().collect();
+    format!("[{}]", items.join(","))
+}
+
+fn gate(gate: &metadata::Gate) -> String {
+    JsonObject::new()
+        .number("index", gate.index())
+        .string("name", gate.name())
+        .finish()
+}
+
+fn region(region: &metadata::Region) -> String {
+    JsonObject::new()
+        .number("index", region.index())
+        .string("name", region.name())
+        .raw(
+            "namespace",
+            &array(region.namespace().iter().map(|name| {
+                let mut encoded = String::new();
+                write_string(&mut encoded, name);
+                encoded
+            })),
+        )
+        .finish()
+}
+
+fn column(column: &Column<Any>) -> String {
+    let column = metadata::Column::from(*column);
+    JsonObject::new()
+        .string("type", &format!("{:?}", column.column_type()))
+        .number("index", column.index())
+        .finish()
+}
+
+impl VerifyFailure {
+    /// Returns a JSON description of this failure.
+    ///
+    /// The description is an object whose `kind` field names the variant (`"cell"`,
+    /// `"constraint"`, `"lookup_cell"`, `"lookup"`, `"permutation"` or
+    /// `"zero_denominator"`), followed by the fields of the variant. Gates, constraints
+    /// and regions are objects with their `index` and `name` (and for regions, the
+    /// array of names in their `namespace` path), a missing region is `null`, and
+    /// columns are objects with their `type` and `index`. The
+    /// `message` field holds the same description as the [`Display`] implementation.
+    ///
+    /// Use [`MockProver::failure_to_json`] to also include the values of the cells
+    /// involved in a failed constraint.
+    ///
+    /// [`Display`]: std::fmt::Display
+    pub fn to_json(&self) -> String {
+        self.json_object().finish()
+    }
+
+    fn json_object(&self) -> JsonObject {
+        let object = match self {
+            Self::Cell {
+                gate: g,
+                region: r,
+                column: c,
+                offset,
+            } => JsonObject::new()
+                .string("kind", "cell")
+                .raw("gate", &gate(g))
+                .raw("region", &region(r))
+                .raw("column", &column(c))
+                .number("offset", offset),
+            Self::Constraint { constraint, row } => JsonObject::new()
+                .string("kind", "constraint")
+                .raw(
+                    "constraint",
+                    &JsonObject::new()
+                        .raw("gate", &gate(constraint.gate()))
+                        .number("index", constraint.index())
+                        .string("name", constraint.name())
+                        .finish(),
+                )
+                .number("row", row),
+            Self::LookupCell {
+                lookup_index,
+                region: r,
+                column: c,
+                offset,
+            } => JsonObject::new()
+                .string("kind", "lookup_cell")
+                .number("lookup_index", lookup_index)
+                .raw("region", &region(r))
+                .raw("column", &column(c))
+                .number("offset", offset),
+            Self::Lookup { lookup_index, row } => JsonObject::new()
+                .string("kind", "lookup")
+                .number("lookup_index", lookup_index)
+                .number("row", row),
+            Self::Permutation {
+                perm_index,
+                column,
+                row,
+            } => JsonObject::new()
+                .string("kind", "permutation")
+                .number("perm_index", perm_index)
+                .number("column", column)
+                .number("row", row),
+            Self::ZeroDenominator {
+                region: r,
+                column: c,
+                row,
+            } => JsonObject::new()
+                .string("kind", "zero_denominator")
+                .raw(
+                    "region",
+                    &r.as_ref().map_or_else(|| "null".to_string(), region),
+                )
+                .raw("column", &column(c))
+                .number("row", row),
+        };
+        object.string("message", &self.to_string())
+    }
+}
+
+impl<F: FieldExt> MockProver<F> {
+    /// Returns a JSON description of a failure returned by [`MockProver::verify`].
+    ///
+    /// This is the description returned by [`VerifyFailure::to_json`], with an
+    /// additional `cell_values` field for constraint failures. It lists each cell
+    /// queried by the gate, as its `column`, `rotation`, and `value` on the failing row
+    /// (or `null` if the cell was not assigned).
+    pub fn failure_to_json(&self, failure: &VerifyFailure) -> String {
+        let object = failure.json_object();
+        match failure {
+            VerifyFailure::Constraint { constraint, row } => {
+                let n = self.n as i32;
+                let cells = self.cs.gates[constraint.gate().index()]
+                    .queried_cells()
+                    .iter()
+                    .map(|cell| {
+                        let row = (((*row as i32 + cell.rotation.0) % n + n) % n) as usize;
+                        let index = cell.column.index();
+                        let value = match cell.column.column_type() {
+                            Any::Advice => self.advice[index][row],
+                            Any::Fixed => self.fixed[index][row],
+                            Any::Instance => Some(self.instance[index][row]),
+                        };
+                        let value = value.map_or_else(
+                            || String::from("null"),
+                            |value| {
+                                let mut encoded = String::new();
+                                write_string(&mut encoded, &format!("{:?}", value));
+                                encoded
+                            },
+                        );
+                        JsonObject::new()
+                            .raw("column", &column(&cell.column))
+                            .number("rotation", cell.rotation.0)
+                            .raw("value", &value)
+                            .finish()
+                    });
+                object.raw("cell_values", &array(cells)).finish()
+            }
+            _ => object.finish(),
+        }
+    }
+
+    /// Returns a JSON array describing each of the given failures, as returned by
+    /// [`MockProver::failure_to_json`].
+    pub fn failures_to_json(&self, failures: &[VerifyFailure]) -> String {
+        array(failures.iter().map(|failure| self.failure_to_json(failure)))
+    }
+}
+
+/// Returns the name of a column in an exported constraint system, such as `advice[0]`.
+fn column_name(column: Column<Any>) -> String {
+    let column_type = match column.column_type() {
+        Any::Advice => "advice",
+        Any::Fixed => "fixed",
+        Any::Instance => "instance",
+    };
+    format!("{}[{}]", column_type, column.index())
+}
+
+fn string(value: &str) -> String {
+    let mut encoded = String::new();
+    write_string(&mut encoded, value);
+    encoded
+}
+
+impl<F: FieldExt> ConstraintSystem<F> {
+    /// Returns a JSON description of this constraint system, for use by tools outside
+    /// Rust such as formal verifiers, or for comparing two circuits.
+    ///
+    /// The description is an object with the following fields:
+    ///
+    /// - `version`: the version of this schema, currently 1.
+    /// - `columns`: an array of the columns, each an object with its `name`, `type`
+    ///   (`"Advice"`, `"Fixed"` or `"Instance"`) and `index`. Columns are named by their
+    ///   type and index, as in `"advice[0]"`; selectors are fixed columns.
+    /// - `gates`: an array of the gates, each an object with its `name` and an array of
+    ///   `constraints`, each with its `name` and its polynomial `poly`.
+    /// - `lookups`: an array of the lookup arguments, each an object with arrays of
+    ///   `inputs` and `tables` expressions of the same length.
+    /// - `permutations`: an array of the permutation arguments, each an array of the
+    ///   names of the columns that it contains.
+    ///
+    /// Expressions are objects whose `kind` field is one of:
+    ///
+    /// - `"constant"`, with the `value` of the constant as a string;
+    /// - `"query"`, with the `column` that is queried (by name) and the `rotation` of
+    ///   the query relative to the current row;
+    /// - `"sum"` or `"product"`, with the `left` and `right` operands;
+    /// - `"scaled"`, with the expression `poly` and the constant `scalar` it is
+    ///   multiplied by, as a string.
+    ///
+    /// Constants are written in the same form as their [`Debug`] implementation.
+    ///
+    /// [`Debug`]: std::fmt::Debug
+    pub fn export_ir(&self) -> String {
+        let columns = (0..self.num_advice_columns)
+            .map(|index| Column::new(index, Any::Advice))
+            .chain((0..self.num_fixed_columns).map(|index| Column::new(index, Any::Fixed)))
+            .chain((0..self.num_instance_columns).map(|index| Column::new(index, Any::Instance)))
+            .map(|c| {
+                JsonObject::new()
+                    .string("name", &column_name(c))
+                    .string("type", &format!("{:?}", c.column_type()))
+                    .number("index", c.index())
+                    .finish()
+            });
+
+        let gates = self.gates.iter().map(|gate| {
+            let constraints = gate.polynomials().iter().enumerate().map(|(index, poly)| {
+                JsonObject::new()
+                    .string("name", gate.constraint_name(index))
+                    .raw("poly", &self.expression_json(poly))
+                    .finish()
+            });
+            JsonObject::new()
+                .string("name", gate.name())
+                .raw("constraints", &array(constraints))
+                .finish()
+        });
+
+        let lookups = self.lookups.iter().map(|lookup| {
+            let expressions = |expressions: &[Expression<F>]| {
+                array(expressions.iter().map(|e| self.expression_json(e)))
+            };
+            JsonObject::new()
+                .raw("inputs", &expressions(&lookup.input_expressions))
+                .raw("tables", &expressions(&lookup.table_expressions))
+                .finish()
+        });
+
+        let permutations = self.permutations.iter().map(|argument| {
+            array(
+                argument
+                    .get_columns()
+                    .into_iter()
+                    .map(|c| string(&column_name(c))),
+            )
+        });
+
+        JsonObject::new()
+            .number("version", IR_SCHEMA_VERSION)
+            .raw("columns", &array(columns))
+            .raw("gates", &array(gates))
+            .raw("lookups", &array(lookups))
+            .raw("permutations", &array(permutations))
+            .finish()
+    }
+
+    fn expression_json(&self, expression: &Expression<F>) -> String {
+        let query = |column: Column<Any>, rotation: i32| {
+            JsonObject::new()
+                .string("kind", "query")
+                .string("column", &column_name(column))
+                .number("rotation", rotation)
+                .finish()
+        };
+        let binary = |kind: &str, left: String, right: String| {
+            JsonObject::new()
+                .string("kind", kind)
+                .raw("left", &left)
+                .raw("right", &right)
+                .finish()
+        };
+        expression.evaluate(
+            &|value| {
+                JsonObject::new()
+                    .string("kind", "constant")
+                    .string("value", &format!("{:?}", value))
+                    .finish()
+            },
+            &|index| {
+                let (column, rotation) = self.fixed_queries[index];
+                query(column.into(), rotation.0)
+            },
+            &|index| {
+                let (column, rotation) = self.advice_queries[index];
+                query(column.into(), rotation.0)
+            },
+            &|index| {
+                let (column, rotation) = self.instance_queries[index];
+                query(column.into(), rotation.0)
+            },
+            &|left, right| binary("sum", left, right),
+            &|left, right| binary("product", left, right),
+            &|poly, scalar| {
+                JsonObject::new()
+                    .string("kind", "scaled")
+                    .raw("poly", &poly)
+                    .string("scalar", &format!("{:?}", scalar))
+                    .finish()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::write_string;
+    use crate::{
+        dev::VerifyFailure,
+        plonk::{Any, Column, ConstraintSystem},
+        poly::Rotation,
+    };
+
+    #[test]
+    fn escaped_strings() {
+        let mut out = String::new();
+        write_string(&mut out, "a \"gate\"\\\n\u{1}");
+        assert_eq!(out, "\"a \\\"gate\\\"\\\\\\n\\u0001\"");
+    }
+
+    #[test]
+    fn cell_failure() {
+        let failure = VerifyFailure::Cell {
+            gate: (0, "Equality check").into(),
+            region: (2, "Faulty synthesis".to_owned()).into(),
+            column: Column::new(1, Any::Advice),
+            offset: -1,
+        };
+        assert_eq!(
+            failure.to_json(),
+            format!(
+                "{{\"kind\":\"cell\",\
+                \"gate\":{{\"index\":0,\"name\":\"Equality check\"}},\
+                \"region\":{{\"index\":2,\"name\":\"Faulty synthesis\",\"namespace\":[]}},\
+                \"column\":{{\"type\":\"Advice\",\"index\":1}},\
+                \"offset\":-1,\"message\":\"{}\"}}",
+                failure
+            )
+        );
+    }
+
+    #[test]
+    fn export_ir() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        let s = meta.fixed_column();
+        let instance = meta.instance_column();
+        meta.create_gate("square", |meta| {
+            let next = meta.query_advice(a, Rotation::next());
+            let a = meta.query_advice(a, Rotation::cur());
+            let s = meta.query_fixed(s, Rotation::cur());
+            vec![("next", s * (a.clone() * a - next))]
+        });
+        meta.lookup(|meta| {
+            vec![(
+                meta.query_advice(a, Rotation::cur()),
+                meta.query_fixed(s, Rotation::cur()),
+            )]
+        });
+        meta.permutation(&[a.into(), instance.into()]);
+
+        let query = |column: &str, rotation: i32| {
+            format!(
+                "{{\"kind\":\"query\",\"column\":\"{}\",\"rotation\":{}}}",
+                column, rotation
+            )
+        };
+        let a = query("advice[0]", 0);
+        let s = query("fixed[0]", 0);
+        let next = query("advice[0]", 1);
+        let minus_one = format!("{:?}", -Fp::one());
+        assert_eq!(
+            meta.export_ir(),
+            format!(
+                "{{\"version\":1,\"columns\":[\
+                {{\"name\":\"advice[0]\",\"type\":\"Advice\",\"index\":0}},\
+                {{\"name\":\"fixed[0]\",\"type\":\"Fixed\",\"index\":0}},\
+                {{\"name\":\"instance[0]\",\"type\":\"Instance\",\"index\":0}}],\
+                \"gates\":[{{\"name\":\"square\",\"constraints\":[{{\"name\":\"next\",\"poly\":\
+                {{\"kind\":\"product\",\"left\":{s},\"right\":\
+                {{\"kind\":\"sum\",\"left\":{{\"kind\":\"product\",\"left\":{a},\"right\":{a}}},\
+                \"right\":{{\"kind\":\"scaled\",\"poly\":{next},\"scalar\":\"{m}\"}}}}}}}}]}}],\
+                \"lookups\":[{{\"inputs\":[{a}],\"tables\":[{s}]}}],\
+                \"permutations\":[[\"advice[0]\",\"instance[0]\"]]}}",
+                s = s,
+                a = a,
+                next = next,
+                m = minus_one,
+            )
+        );
+    }
+}
@@ -2,8 +2,67 @@
 
 use std::fmt;
 
+use crate::plonk::{self, Any};
+
+/// Metadata about a column within a circuit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Column {
+    /// The type of the column.
+    column_type: Any,
+    /// The index of the column within the columns of its type.
+    index: usize,
+    /// An optional annotation describing the column, such as its name within a chip.
+    annotation: Option<String>,
+}
+
+impl Column {
+    /// Returns the type of the column.
+    pub fn column_type(&self) -> Any {
+        self.column_type
+    }
+
+    /// Returns the index of the column within the columns of its type.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the annotation of the column, if it has one.
+    pub fn annotation(&self) -> Option<&str> {
+        self.annotation.as_deref()
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Column('{:?}', {}", self.column_type, self.index)?;
+        if let Some(annotation) = &self.annotation {
+            write!(f, " - {}", annotation)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl From<plonk::Column<Any>> for Column {
+    fn from(column: plonk::Column<Any>) -> Self {
+        Column {
+            column_type: *column.column_type(),
+            index: column.index(),
+            annotation: None,
+        }
+    }
+}
+
+impl From<(plonk::Column<Any>, String)> for Column {
+    fn from((column, annotation): (plonk::Column<Any>, String)) -> Self {
+        Column {
+            annotation: Some(annotation),
+            ..column.into()
+        }
+    }
+}
+
 /// Metadata about a configured gate within a circuit.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Gate {
     /// The index of the active gate. These indices are assigned in the order in which
     /// `ConstraintSystem::create_gate` is called during `Circuit::configure`.
@@ -13,6 +72,18 @@ pub struct Gate {
     name: &'static str,
 }
 
+impl Gate {
+    /// Returns the index of the gate.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the name of the gate.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
 impl fmt::Display for Gate {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Gate {} ('{}')", self.index, self.name)
@@ -26,7 +97,7 @@ impl From<(usize, &'static str)> for Gate {
 }
 
 /// Metadata about a configured constraint within a circuit.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Constraint {
     /// The gate containing the constraint.
     gate: Gate,
@@ -39,6 +110,23 @@ pub struct Constraint {
     name: &'static str,
 }
 
+impl Constraint {
+    /// Returns the gate containing the constraint.
+    pub fn gate(&self) -> &Gate {
+        &self.gate
+    }
+
+    /// Returns the index of the constraint within its gate.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the name of the constraint, which is empty if it was not named.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
 impl fmt::Display for Constraint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -63,7 +151,7 @@ impl From<(Gate, usize, &'static str)> for Constraint {
 }
 
 /// Metadata about an assigned region within a circuit.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Region {
     /// The index of the region. These indices are assigned in the order in which
     /// `Layouter::assign_region` is called during `Circuit::synthesize`.
@@ -71,16 +159,49 @@ pub struct Region {
     /// The name of the region. This is specified by the region creator (such as a chip
     /// implementation), and is not enforced to be unique.
     name: String,
+    /// The path of namespaces in which the region was assigned, outermost first.
+    namespace: Vec<String>,
+}
+
+impl Region {
+    /// Returns the index of the region.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the name of the region.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the path of namespaces in which the region was assigned, outermost
+    /// first. This is empty if the region was assigned outside of any namespace.
+    pub fn namespace(&self) -> &[String] {
+        &self.namespace
+    }
 }
 
 impl fmt::Display for Region {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.namespace.is_empty() {
+            write!(f, "{} / ", self.namespace.join("::"))?;
+        }
         write!(f, "Region {} ('{}')", self.index, self.name)
     }
 }
 
 impl From<(usize, String)> for Region {
     fn from((index, name): (usize, String)) -> Self {
-        Region { index, name }
+        (index, name, vec![]).into()
+    }
+}
+
+impl From<(usize, String, Vec<String>)> for Region {
+    fn from((index, name, namespace): (usize, String, Vec<String>)) -> Self {
+        Region {
+            index,
+            name,
+            namespace,
+        }
     }
 }
@@ -0,0 +1,901 @@
+//! A slow reference implementation of the verifier.
+
+use ff::Field;
+use group::Curve;
+
+use crate::{
+    arithmetic::{CurveAffine, FieldExt, TwoAdicField},
+    plonk::{Any, Column, Expression, ProofSection, VerifyError, VerifyingKey},
+    poly::{commitment::Params, Rotation},
+    transcript::{EncodedChallenge, TranscriptRead},
+};
+
+/// A polynomial that the verifier checks an opening of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Opened {
+    Instance {
+        proof: usize,
+        column: usize,
+    },
+    Advice {
+        proof: usize,
+        column: usize,
+    },
+    PermutationProduct {
+        proof: usize,
+        argument: usize,
+        set: usize,
+    },
+    /// Permutation polynomials are committed to in the verifying key, and so are
+    /// shared by all proofs.
+    Permutation {
+        argument: usize,
+        column: usize,
+    },
+    LookupProduct {
+        proof: usize,
+        lookup: usize,
+    },
+    LookupInput {
+        proof: usize,
+        lookup: usize,
+    },
+    LookupTable {
+        proof: usize,
+        lookup: usize,
+    },
+    Fixed {
+        column: usize,
+    },
+    Vanishing {
+        piece: usize,
+    },
+}
+
+/// The claimed evaluations of a polynomial, as indices into [`Openings::points`].
+struct Claims<C: CurveAffine> {
+    polynomial: Opened,
+    commitment: C,
+    evals: Vec<(usize, C::Scalar)>,
+}
+
+/// Every opening claimed by a proof, in the order that the verifier makes them.
+struct Openings<C: CurveAffine> {
+    points: Vec<C::Scalar>,
+    claims: Vec<Claims<C>>,
+}
+
+impl<C: CurveAffine> Openings<C> {
+    fn open(
+        &mut self,
+        polynomial: Opened,
+        commitment: C,
+        point: C::Scalar,
+        eval: C::Scalar,
+    ) -> Result<(), VerifyError> {
+        let point = match self.points.iter().position(|p| *p == point) {
+            Some(index) => index,
+            None => {
+                self.points.push(point);
+                self.points.len() - 1
+            }
+        };
+        let claims = match self.claims.iter().position(|c| c.polynomial == polynomial) {
+            Some(index) => &mut self.claims[index],
+            None => {
+                self.claims.push(Claims {
+                    polynomial,
+                    commitment,
+                    evals: vec![],
+                });
+                self.claims.last_mut().unwrap()
+            }
+        };
+        let claimed = claims
+            .evals
+            .iter()
+            .find(|(p, _)| *p == point)
+            .map(|(_, claimed)| *claimed);
+        match claimed {
+            // A polynomial cannot evaluate to two different values at the same point.
+            Some(claimed) if claimed != eval => Err(VerifyError::Multiopen),
+            Some(_) => Ok(()),
+            None => {
+                claims.evals.push((point, eval));
+                Ok(())
+            }
+        }
+    }
+}
+
+struct LookupCommitments<C> {
+    input: C,
+    table: C,
+    product: C,
+}
+
+struct LookupEvaluations<F> {
+    product: F,
+    product_inv: F,
+    input: F,
+    input_inv: F,
+    table: F,
+}
+
+struct PermutationEvaluations<F> {
+    /// The evaluations of each product polynomial at $x$ and $\omega^{-1} x$.
+    products: Vec<(F, F)>,
+    permutations: Vec<F>,
+}
+
+fn read_point<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
+    transcript: &mut T,
+    section: ProofSection,
+    proof: Option<usize>,
+) -> Result<C, VerifyError> {
+    transcript
+        .read_point()
+        .map_err(|_| VerifyError::Transcript { section, proof })
+}
+
+fn read_scalar<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
+    transcript: &mut T,
+    section: ProofSection,
+    proof: Option<usize>,
+) -> Result<C::Scalar, VerifyError> {
+    transcript
+        .read_scalar()
+        .map_err(|_| VerifyError::Transcript { section, proof })
+}
+
+fn invert<F: Field>(value: F, error: VerifyError) -> Result<F, VerifyError> {
+    let inverse = value.invert();
+    if bool::from(inverse.is_some()) {
+        Ok(inverse.unwrap())
+    } else {
+        Err(error)
+    }
+}
+
+/// Evaluates at `x` the polynomial of least degree through the given points.
+fn interpolate<F: FieldExt>(points: &[F], evals: &[F], x: F) -> F {
+    let mut result = F::zero();
+    for (j, (point_j, eval_j)) in points.iter().zip(evals).enumerate() {
+        let mut term = *eval_j;
+        for (k, point_k) in points.iter().enumerate() {
+            if k != j {
+                // The points are distinct, so this is always invertible.
+                term *= (x - point_k) * (*point_j - point_k).invert().unwrap();
+            }
+        }
+        result += term;
+    }
+    result
+}
+
+/// Verifies proofs by evaluating the equations of the protocol directly.
+///
+/// This accepts exactly the proofs that [`verify_proof`] accepts once its guard is
+/// evaluated, and rejects the others with the same [`VerifyError`]s. It exists to
+/// audit that verifier: rather than batching everything into a single
+/// multiexponentiation, it recomputes each polynomial identity and each commitment
+/// from its definition, checks the multiopen argument against explicitly interpolated
+/// evaluations, and checks the inner product argument with one scalar multiplication
+/// per generator. It is correspondingly slow, and is only intended for tests.
+///
+/// [`verify_proof`]: crate::plonk::verify_proof
+pub fn reference_verifier<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    instance_commitments: &[&[C]],
+    transcript: &mut T,
+) -> Result<(), VerifyError> {
+    use ProofSection::*;
+
+    let cs = vk.cs();
+    if cs.binds_instance_values {
+        return Err(VerifyError::InstanceValuesRequired);
+    }
+    for (proof, commitments) in instance_commitments.iter().enumerate() {
+        if commitments.len() != cs.num_instance_columns {
+            return Err(VerifyError::InstanceColumns {
+                proof,
+                expected: cs.num_instance_columns,
+                actual: commitments.len(),
+            });
+        }
+    }
+    let num_proofs = instance_commitments.len();
+    let proofs = || 0..num_proofs;
+
+    // The verifying key and the instance commitments, except those of constant
+    // instance columns, which the verifying key supplies instead.
+    vk.hash_into(transcript)
+        .map_err(|_| VerifyError::Transcript {
+            section: CommonInputs,
+            proof: None,
+        })?;
+    let mut instance = vec![];
+    for commitments in instance_commitments {
+        let mut substituted = vec![];
+        for (column, commitment) in commitments.iter().enumerate() {
+            match vk.constant_instance_commitment(column) {
+                Some(constant) => substituted.push(constant),
+                None => {
+                    transcript
+                        .common_point("instance", *commitment)
+                        .map_err(|_| VerifyError::Transcript {
+                            section: CommonInputs,
+                            proof: None,
+                        })?;
+                    substituted.push(*commitment);
+                }
+            }
+        }
+        instance.push(substituted);
+    }
+
+    // The commitments, interleaved with the challenges that depend on them.
+    let mut advice = vec![];
+    for proof in proofs() {
+        let mut commitments = vec![];
+        for _ in 0..cs.num_advice_columns {
+            commitments.push(read_point(transcript, AdviceCommitments, Some(proof))?);
+        }
+        advice.push(commitments);
+    }
+
+    let theta = *transcript.squeeze_challenge_scalar::<()>("theta");
+
+    let mut lookups = vec![];
+    for proof in proofs() {
+        let mut permuted = vec![];
+        for _ in &cs.lookups {
+            let input = read_point(transcript, LookupPermutedCommitments, Some(proof))?;
+            let table = read_point(transcript, LookupPermutedCommitments, Some(proof))?;
+            permuted.push((input, table));
+        }
+        lookups.push(permuted);
+    }
+
+    let beta = *transcript.squeeze_challenge_scalar::<()>("beta");
+    let gamma = *transcript.squeeze_challenge_scalar::<()>("gamma");
+
+    let mut permutation_products = vec![];
+    for proof in proofs() {
+        let mut arguments = vec![];
+        for argument in &cs.permutations {
+            let mut products = vec![];
+            for _ in 0..argument.num_chunks(cs.permutation_degree_bound()) {
+                products.push(read_point(
+                    transcript,
+                    PermutationProductCommitments,
+                    Some(proof),
+                )?);
+            }
+            arguments.push(products);
+        }
+        permutation_products.push(arguments);
+    }
+
+    let mut lookup_commitments = vec![];
+    for (proof, permuted) in lookups.into_iter().enumerate() {
+        let mut committed = vec![];
+        for (input, table) in permuted {
+            let product = read_point(transcript, LookupProductCommitments, Some(proof))?;
+            committed.push(LookupCommitments {
+                input,
+                table,
+                product,
+            });
+        }
+        lookup_commitments.push(committed);
+    }
+
+    let y = *transcript.squeeze_challenge_scalar::<()>("y");
+
+    let mut h_commitments = vec![];
+    for _ in 0..vk.get_domain().get_quotient_poly_degree() {
+        h_commitments.push(read_point(transcript, VanishingCommitments, None)?);
+    }
+
+    let x = *transcript.squeeze_challenge_scalar::<()>("x");
+
+    // The evaluations at x and its rotations.
+    let mut instance_evals = vec![];
+    for proof in proofs() {
+        let mut evals = vec![];
+        for _ in &cs.instance_queries {
+            evals.push(read_scalar(transcript, InstanceEvals, Some(proof))?);
+        }
+        instance_evals.push(evals);
+    }
+    let mut advice_evals = vec![];
+    for proof in proofs() {
+        let mut evals = vec![];
+        for _ in &cs.advice_queries {
+            evals.push(read_scalar(transcript, AdviceEvals, Some(proof))?);
+        }
+        advice_evals.push(evals);
+    }
+    let mut fixed_evals = vec![];
+    for _ in &cs.fixed_queries {
+        fixed_evals.push(read_scalar(transcript, FixedEvals, None)?);
+    }
+    let mut h_evals = vec![];
+    for _ in &h_commitments {
+        h_evals.push(read_scalar(transcript, VanishingEvals, None)?);
+    }
+    let mut permutation_evals = vec![];
+    for (proof, arguments) in permutation_products.iter().enumerate() {
+        let mut evaluated = vec![];
+        for (index, products) in arguments.iter().enumerate() {
+            let mut evals = PermutationEvaluations {
+                products: vec![],
+                permutations: vec![],
+            };
+            for _ in products {
+                let eval = read_scalar(transcript, PermutationEvals, Some(proof))?;
+                let inv_eval = read_scalar(transcript, PermutationEvals, Some(proof))?;
+                evals.products.push((eval, inv_eval));
+            }
+            for _ in vk.permutation_commitments(index) {
+                evals
+                    .permutations
+                    .push(read_scalar(transcript, PermutationEvals, Some(proof))?);
+            }
+            evaluated.push(evals);
+        }
+        permutation_evals.push(evaluated);
+    }
+    let mut lookup_evals = vec![];
+    for proof in proofs() {
+        let mut evaluated = vec![];
+        for _ in &cs.lookups {
+            evaluated.push(LookupEvaluations {
+                product: read_scalar(transcript, LookupEvals, Some(proof))?,
+                product_inv: read_scalar(transcript, LookupEvals, Some(proof))?,
+                input: read_scalar(transcript, LookupEvals, Some(proof))?,
+                input_inv: read_scalar(transcript, LookupEvals, Some(proof))?,
+                table: read_scalar(transcript, LookupEvals, Some(proof))?,
+            });
+        }
+        lookup_evals.push(evaluated);
+    }
+
+    // omega^i x
+    let omega = vk.get_domain().get_omega();
+    let omega_inv = vk.get_domain().get_omega_inv();
+    let rotate = |rotation: Rotation| {
+        if rotation.0 >= 0 {
+            x * omega.pow_vartime(&[rotation.0 as u64, 0, 0, 0])
+        } else {
+            x * omega_inv.pow_vartime(&[(-rotation.0) as u64, 0, 0, 0])
+        }
+    };
+    let x_inv = rotate(Rotation::prev());
+
+    // The vanishing argument: every constraint, combined with powers of y, must equal
+    // h(x) (x^n - 1).
+    {
+        let one = C::Scalar::one();
+        let xn = x.pow_vartime(&[params.n, 0, 0, 0]);
+        // l_0(x) = (x^n - 1) / (n (x - 1))
+        let l_0 = (xn - one)
+            * invert(
+                C::Scalar::from_u64(params.n) * (x - one),
+                VerifyError::VanishingIdentity,
+            )?;
+
+        let mut constraints = vec![];
+        for proof in proofs() {
+            let advice_evals = &advice_evals[proof];
+            let instance_evals = &instance_evals[proof];
+            let evaluate = |expression: &Expression<C::Scalar>| {
+                expression.evaluate(
+                    &|scalar| scalar,
+                    &|index| fixed_evals[index],
+                    &|index| advice_evals[index],
+                    &|index| instance_evals[index],
+                    &|a, b| a + b,
+                    &|a, b| a * b,
+                    &|a, scalar| a * scalar,
+                )
+            };
+            let column_eval = |column: &Column<Any>| {
+                let index = cs.permutation_query_index(*column);
+                match column.column_type() {
+                    Any::Advice => advice_evals[index],
+                    Any::Fixed => fixed_evals[index],
+                    Any::Instance => instance_evals[index],
+                }
+            };
+
+            for gate in &cs.gates {
+                constraints.extend(gate.polynomials().iter().map(evaluate));
+            }
+
+            for (argument, evals) in cs.permutations.iter().zip(&permutation_evals[proof]) {
+                let sets = evals.products.len();
+                if let Some(&(first, _)) = evals.products.first() {
+                    // l_0(x) (1 - z_0(x))
+                    constraints.push(l_0 * (one - first));
+                }
+
+                // z_j(x) \prod (p(x) + beta s(x) + gamma)
+                //   - ((1 - l_0(x)) z_j(omega^{-1} x) + l_0(x) z_{j-1}(omega^{-1} x))
+                //     \prod (p(x) + beta delta^i x + gamma)
+                let chunk_len = argument.chunk_len(cs.permutation_degree_bound());
+                let mut delta_i = one;
+                for (j, (columns, permutations)) in argument
+                    .get_columns()
+                    .chunks(chunk_len)
+                    .zip(evals.permutations.chunks(chunk_len))
+                    .enumerate()
+                {
+                    let (z, z_inv) = evals.products[j];
+                    let (_, z_prev_inv) = evals.products[(j + sets - 1) % sets];
+
+                    let mut left = z;
+                    for (column, s) in columns.iter().zip(permutations) {
+                        left *= column_eval(column) + beta * s + gamma;
+                    }
+                    let mut right = (one - l_0) * z_inv + l_0 * z_prev_inv;
+                    for column in columns {
+                        right *= column_eval(column) + beta * delta_i * x + gamma;
+                        delta_i *= C::Scalar::delta();
+                    }
+                    constraints.push(left - right);
+                }
+            }
+
+            for (argument, evals) in cs.lookups.iter().zip(&lookup_evals[proof]) {
+                let compress = |expressions: &[Expression<C::Scalar>]| {
+                    expressions
+                        .iter()
+                        .fold(C::Scalar::zero(), |acc, e| acc * theta + evaluate(e))
+                };
+                // l_0(x) (1 - z(x))
+                constraints.push(l_0 * (one - evals.product));
+                // z(x) (a'(x) + beta) (s'(x) + gamma)
+                //   - z(omega^{-1} x) (compressed inputs + beta) (compressed table + gamma)
+                constraints.push(
+                    evals.product * (evals.input + beta) * (evals.table + gamma)
+                        - evals.product_inv
+                            * (compress(&argument.input_expressions) + beta)
+                            * (compress(&argument.table_expressions) + gamma),
+                );
+                // l_0(x) (a'(x) - s'(x))
+                constraints.push(l_0 * (evals.input - evals.table));
+                // (a'(x) - s'(x)) (a'(x) - a'(omega^{-1} x))
+                constraints.push((evals.input - evals.table) * (evals.input - evals.input_inv));
+            }
+        }
+
+        let expected = constraints
+            .into_iter()
+            .fold(C::Scalar::zero(), |acc, constraint| acc * y + constraint);
+        let mut h_eval = C::Scalar::zero();
+        let mut xn_i = one;
+        for eval in &h_evals {
+            h_eval += *eval * xn_i;
+            xn_i *= xn;
+        }
+        if expected != h_eval * (xn - one) {
+            return Err(VerifyError::VanishingIdentity);
+        }
+    }
+
+    // Every opening, in the order that the production verifier queries them.
+    let mut openings = Openings {
+        points: vec![],
+        claims: vec![],
+    };
+    for proof in proofs() {
+        for (index, &(column, at)) in cs.instance_queries.iter().enumerate() {
+            let column = column.index();
+            openings.open(
+                Opened::Instance { proof, column },
+                instance[proof][column],
+                rotate(at),
+                instance_evals[proof][index],
+            )?;
+        }
+        for (index, &(column, at)) in cs.advice_queries.iter().enumerate() {
+            let column = column.index();
+            openings.open(
+                Opened::Advice { proof, column },
+                advice[proof][column],
+                rotate(at),
+                advice_evals[proof][index],
+            )?;
+        }
+        for (argument, evals) in permutation_evals[proof].iter().enumerate() {
+            let products = &permutation_products[proof][argument];
+            for (set, (commitment, (eval, inv_eval))) in
+                products.iter().zip(&evals.products).enumerate()
+            {
+                let polynomial = Opened::PermutationProduct {
+                    proof,
+                    argument,
+                    set,
+                };
+                openings.open(polynomial, *commitment, x, *eval)?;
+                openings.open(polynomial, *commitment, x_inv, *inv_eval)?;
+            }
+            let commitments = vk.permutation_commitments(argument);
+            for (column, (commitment, eval)) in
+                commitments.iter().zip(&evals.permutations).enumerate()
+            {
+                openings.open(
+                    Opened::Permutation { argument, column },
+                    *commitment,
+                    x,
+                    *eval,
+                )?;
+            }
+        }
+        for (lookup, (commitments, evals)) in lookup_commitments[proof]
+            .iter()
+            .zip(&lookup_evals[proof])
+            .enumerate()
+        {
+            let product = Opened::LookupProduct { proof, lookup };
+            let input = Opened::LookupInput { proof, lookup };
+            let table = Opened::LookupTable { proof, lookup };
+            openings.open(product, commitments.product, x, evals.product)?;
+            openings.open(input, commitments.input, x, evals.input)?;
+            openings.open(table, commitments.table, x, evals.table)?;
+            openings.open(input, commitments.input, x_inv, evals.input_inv)?;
+            openings.open(product, commitments.product, x_inv, evals.product_inv)?;
+        }
+    }
+    for (index, &(column, at)) in cs.fixed_queries.iter().enumerate() {
+        let column = column.index();
+        openings.open(
+            Opened::Fixed { column },
+            vk.fixed_commitments()[column],
+            rotate(at),
+            fixed_evals[index],
+        )?;
+    }
+    for (piece, (commitment, eval)) in h_commitments.iter().zip(&h_evals).enumerate() {
+        openings.open(Opened::Vanishing { piece }, *commitment, x, *eval)?;
+    }
+
+    // The multiopen argument: the polynomials opened at the same set of points are
+    // combined with powers of x_1, and the quotients of the combinations are combined
+    // with powers of x_2 into f.
+    let x_1 = *transcript.squeeze_challenge_scalar::<()>("x_1");
+    let x_2 = *transcript.squeeze_challenge_scalar::<()>("x_2");
+
+    // Point sets (of sorted point indices) are numbered in the order that they are
+    // first opened.
+    let mut sets: Vec<Vec<usize>> = vec![];
+    for claims in &openings.claims {
+        let mut set: Vec<usize> = claims.evals.iter().map(|(point, _)| *point).collect();
+        set.sort_unstable();
+        if !sets.contains(&set) {
+            sets.push(set);
+        }
+    }
+
+    let mut q_commitments = vec![C::identity().to_curve(); sets.len()];
+    let mut q_eval_sets: Vec<_> = sets
+        .iter()
+        .map(|set| vec![C::Scalar::zero(); set.len()])
+        .collect();
+    for claims in &openings.claims {
+        let mut evals = claims.evals.clone();
+        evals.sort_unstable_by_key(|(point, _)| *point);
+        let set: Vec<usize> = evals.iter().map(|(point, _)| *point).collect();
+        let index = sets.iter().position(|s| *s == set).unwrap();
+
+        q_commitments[index] = q_commitments[index] * x_1 + claims.commitment.to_curve();
+        for (q_eval, (_, eval)) in q_eval_sets[index].iter_mut().zip(evals) {
+            *q_eval = *q_eval * x_1 + eval;
+        }
+    }
+
+    let f_commitment = transcript
+        .read_point()
+        .map_err(|_| VerifyError::Multiopen)?;
+    let x_3 = *transcript.squeeze_challenge_scalar::<()>("x_3");
+    let mut q_evals = vec![];
+    for _ in 0..sets.len() {
+        q_evals.push(
+            transcript
+                .read_scalar()
+                .map_err(|_| VerifyError::Multiopen)?,
+        );
+    }
+
+    // f(x_3) = \sum x_2^i (q_i(x_3) - r_i(x_3)) / \prod (x_3 - point)
+    let mut f_eval = C::Scalar::zero();
+    for (index, set) in sets.iter().enumerate() {
+        let points: Vec<_> = set.iter().map(|&point| openings.points[point]).collect();
+        let mut quotient = q_evals[index] - interpolate(&points, &q_eval_sets[index], x_3);
+        for point in &points {
+            quotient *= invert(x_3 - point, VerifyError::Multiopen)?;
+        }
+        f_eval = f_eval * x_2 + quotient;
+    }
+
+    let x_4 = *transcript.squeeze_challenge_scalar::<()>("x_4");
+
+    // The polynomial f + \sum x_4^i q_i must open to f(x_3) + \sum x_4^i q_i(x_3).
+    let mut p = f_commitment.to_curve();
+    let mut v = f_eval;
+    for index in 0..sets.len() {
+        p = p * x_4 + q_commitments[index];
+        v = v * x_4 + q_evals[index];
+    }
+
+    // The inner product argument, which opens p at x_3 to v.
+    let s_commitment = transcript
+        .read_point()
+        .map_err(|_| VerifyError::Multiopen)?;
+    let iota = *transcript.squeeze_challenge_scalar::<()>("iota");
+    p = p - params.g[0] * v + s_commitment * iota;
+    let z = *transcript.squeeze_challenge_scalar::<()>("z");
+
+    let k = params.k as usize;
+    let mut u = vec![];
+    for _ in 0..k {
+        let l = transcript
+            .read_point()
+            .map_err(|_| VerifyError::Multiopen)?;
+        let r = transcript
+            .read_point()
+            .map_err(|_| VerifyError::Multiopen)?;
+        let u_j = *transcript.squeeze_challenge_scalar::<()>("u");
+        p = p + l * invert(u_j, VerifyError::Opening)? + r * u_j;
+        u.push(u_j);
+    }
+    let a = transcript
+        .read_scalar()
+        .map_err(|_| VerifyError::Multiopen)?;
+    let xi = transcript
+        .read_scalar()
+        .map_err(|_| VerifyError::Multiopen)?;
+
+    // The final generator is G = \sum s_i G_i and the final evaluation point is
+    // b = \sum s_i x_3^i, where s_i is the product of the u_j for each bit of i.
+    let mut g = C::identity().to_curve();
+    let mut b = C::Scalar::zero();
+    let mut x_3_i = C::Scalar::one();
+    for (i, g_i) in params.g.iter().enumerate() {
+        let mut s_i = C::Scalar::one();
+        for (bit, u_j) in u.iter().rev().enumerate() {
+            if (i >> bit) & 1 == 1 {
+                s_i *= u_j;
+            }
+        }
+        g = g + *g_i * s_i;
+        b += s_i * x_3_i;
+        x_3_i *= x_3;
+    }
+
+    // p = [a] G + [a b z] U + [xi] H
+    let expected = g * a + params.u * (a * b * z) + params.h * xi;
+    if (p - expected).to_affine() == C::identity() {
+        Ok(())
+    } else {
+        Err(VerifyError::Opening)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::Curve;
+    use pasta_curves::{EqAffine, Fp};
+
+    use super::reference_verifier;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+            ConstraintSystem, Error, Fixed, Permutation, Selector, VerifyError,
+        },
+        poly::{
+            commitment::{Blind, Params},
+            Rotation,
+        },
+        transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+    };
+
+    #[derive(Clone)]
+    struct SquaresConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        table: Column<Fixed>,
+        square: Selector,
+        expose: Selector,
+        permutation: Permutation,
+    }
+
+    /// Squares a value twice, exposing the result and looking up each square in a
+    /// table.
+    #[derive(Default)]
+    struct SquaresCircuit {
+        a: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for SquaresCircuit {
+        type Config = SquaresConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquaresConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let table = meta.fixed_column();
+            let output = meta.instance_column();
+            let square = meta.selector();
+            let expose = meta.selector();
+            let permutation = Permutation::new(meta, &[a.into(), b.into()]);
+
+            meta.create_gate("square", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let square = meta.query_selector(square);
+                vec![square * (a.clone() * a - b)]
+            });
+
+            meta.create_gate("expose", |meta| {
+                let b = meta.query_advice(b, Rotation::cur());
+                let output = meta.query_instance(output, Rotation::cur());
+                let expose = meta.query_selector(expose);
+                vec![expose * (b - output)]
+            });
+
+            meta.lookup(|meta| {
+                let b = meta.query_advice(b, Rotation::cur());
+                let table = meta.query_fixed(table, Rotation::cur());
+                let square = meta.query_selector(square);
+                vec![(square * b, table)]
+            });
+
+            SquaresConfig {
+                a,
+                b,
+                table,
+                square,
+                expose,
+                permutation,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: SquaresConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "squares",
+                |mut region| {
+                    config.square.enable(&mut region, 0)?;
+                    config.square.enable(&mut region, 1)?;
+                    config.expose.enable(&mut region, 1)?;
+
+                    let square = self.a.map(|a| a.square());
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.a.ok_or(Error::SynthesisError),
+                    )?;
+                    let b = region.assign_advice(
+                        || "a^2",
+                        config.b,
+                        0,
+                        || square.ok_or(Error::SynthesisError),
+                    )?;
+                    let a = region.assign_advice(
+                        || "a^2",
+                        config.a,
+                        1,
+                        || square.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(&config.permutation, b, a)?;
+                    region.assign_advice(
+                        || "a^4",
+                        config.b,
+                        1,
+                        || square.map(|s| s.square()).ok_or(Error::SynthesisError),
+                    )?;
+
+                    for (offset, value) in [0u64, 4, 9, 16, 81].iter().enumerate() {
+                        region.assign_fixed(
+                            || "table",
+                            config.table,
+                            offset,
+                            || Ok(Fp::from(*value)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn agrees_with_verify_proof() {
+        const K: u32 = 5;
+        let params: Params<EqAffine> = Params::new(K);
+        let empty = SquaresCircuit::default();
+        let vk = keygen_vk(&params, &empty).unwrap();
+        let pk = keygen_pk(&params, vk, &empty).unwrap();
+
+        let circuits = [
+            SquaresCircuit {
+                a: Some(Fp::from(2u64)),
+            },
+            SquaresCircuit {
+                a: Some(Fp::from(3u64)),
+            },
+        ];
+        let instances: Vec<_> = [16u64, 81]
+            .iter()
+            .map(|&output| {
+                let mut values = vec![Fp::zero(); params.n as usize];
+                values[1] = Fp::from(output);
+                pk.get_vk().get_domain().lagrange_from_vec(values)
+            })
+            .collect();
+        let instance_commitments: Vec<_> = instances
+            .iter()
+            .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
+            .collect();
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &circuits,
+            &[&instances[0..1], &instances[1..2]],
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        let verify = |proof: &[u8], instance_commitments: &[&[EqAffine]]| {
+            let production = verify_proof(
+                &params,
+                pk.get_vk(),
+                params.empty_msm(),
+                instance_commitments,
+                &mut Blake2bRead::<_, _, Challenge255<_>>::init(proof),
+            )
+            .and_then(|guard| {
+                if guard.use_challenges().eval() {
+                    Ok(())
+                } else {
+                    Err(VerifyError::Opening)
+                }
+            });
+            let reference = reference_verifier(
+                &params,
+                pk.get_vk(),
+                instance_commitments,
+                &mut Blake2bRead::<_, _, Challenge255<_>>::init(proof),
+            );
+            assert_eq!(reference, production);
+            reference
+        };
+
+        let both = [&instance_commitments[0..1], &instance_commitments[1..2]];
+        assert_eq!(verify(&proof, &both), Ok(()));
+        assert!(verify(&proof, &[both[1], both[0]]).is_err());
+
+        // Corrupt a bit in each of a sample of the proof's bytes.
+        for index in (0..proof.len()).step_by(17) {
+            let mut corrupted = proof.clone();
+            corrupted[index] ^= 1;
+            assert!(verify(&corrupted, &both).is_err());
+        }
+    }
+}
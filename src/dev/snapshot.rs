@@ -0,0 +1,115 @@
+//! Golden-file snapshots of verification keys.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{arithmetic::CurveAffine, plonk::VerifyingKey};
+
+/// The environment variable that, when set, causes [`compare_vk_snapshot`] to write
+/// snapshot files (creating any that are missing) instead of comparing against them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "HALO2_UPDATE_SNAPSHOTS";
+
+/// Returns a canonical snapshot of a verifying key (or of the verifying key underlying a
+/// proving key).
+///
+/// The snapshot is the pretty-printed [`PinnedVerificationKey`], which is exactly the
+/// data that is hashed into the transcript. Any change to the snapshot therefore implies
+/// that previously-generated proofs will no longer verify.
+///
+/// [`PinnedVerificationKey`]: crate::plonk::PinnedVerificationKey
+pub fn vk_snapshot<C: CurveAffine, K: AsRef<VerifyingKey<C>>>(key: &K) -> String {
+    format!("{:#?}\n", key.as_ref().pinned())
+}
+
+/// Compares `snapshot` against the contents of the file at `path`.
+///
+/// If the [`UPDATE_SNAPSHOTS_ENV`] environment variable is set, the file is (re-)written
+/// with `snapshot` and the comparison succeeds. Otherwise, a missing file returns an
+/// error (so that a deleted or renamed snapshot is not silently recreated), and a
+/// mismatch returns an error describing the differing lines.
+///
+/// This is usually called via [`assert_vk_unchanged!`].
+///
+/// [`assert_vk_unchanged!`]: crate::assert_vk_unchanged
+pub fn compare_vk_snapshot(path: &Path, snapshot: &str) -> Result<(), String> {
+    if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+        return write_snapshot(path, snapshot);
+    }
+
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(format!(
+                "Snapshot {} does not exist (set {}=1 to create it)",
+                path.display(),
+                UPDATE_SNAPSHOTS_ENV,
+            ))
+        }
+        Err(e) => return Err(format!("Failed to read snapshot {}: {}", path.display(), e)),
+    };
+
+    if expected == snapshot {
+        return Ok(());
+    }
+
+    Err(format!(
+        "Verification key does not match snapshot {} (set {}=1 to update it):\n{}",
+        path.display(),
+        UPDATE_SNAPSHOTS_ENV,
+        diff_lines(&expected, snapshot),
+    ))
+}
+
+/// Describes the lines that differ between two snapshots.
+pub(super) fn diff_lines(expected: &str, actual: &str) -> String {
+    let mut message = String::new();
+    let expected_lines: Vec<_> = expected.lines().collect();
+    let actual_lines: Vec<_> = actual.lines().collect();
+    for i in 0..std::cmp::max(expected_lines.len(), actual_lines.len()) {
+        let (expected, actual) = (expected_lines.get(i), actual_lines.get(i));
+        if expected != actual {
+            message.push_str(&format!("line {}:\n", i + 1));
+            if let Some(expected) = expected {
+                message.push_str(&format!("  - {}\n", expected));
+            }
+            if let Some(actual) = actual {
+                message.push_str(&format!("  + {}\n", actual));
+            }
+        }
+    }
+    message
+}
+
+fn write_snapshot(path: &Path, snapshot: &str) -> Result<(), String> {
+    path.parent()
+        .map(fs::create_dir_all)
+        .transpose()
+        .and_then(|_| fs::write(path, snapshot))
+        .map_err(|e| format!("Failed to write snapshot {}: {}", path.display(), e))
+}
+
+/// Asserts that a verifying key (or the verifying key underlying a proving key) matches
+/// the snapshot stored at the given path.
+///
+/// Fails if the snapshot file does not exist. Set the `HALO2_UPDATE_SNAPSHOTS`
+/// environment variable to create a snapshot for a new circuit, or to overwrite
+/// snapshots after an intentional circuit change.
+///
+/// ```ignore
+/// halo2::assert_vk_unchanged!(
+///     concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/my_circuit.vk.txt"),
+///     &vk
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_vk_unchanged {
+    ($path:expr, $key:expr) => {
+        if let Err(message) = $crate::dev::compare_vk_snapshot(
+            ::std::path::Path::new($path),
+            &$crate::dev::vk_snapshot($key),
+        ) {
+            panic!("{}", message);
+        }
+    };
+}
@@ -0,0 +1,746 @@
+//! Recording circuit synthesis into a trace that can be replayed into other backends.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{self, Read};
+
+use crate::{
+    arithmetic::FieldExt,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Permutation, Selector,
+    },
+};
+
+/// The version of the encoding written by [`Trace::write`].
+const TRACE_VERSION: u8 = 1;
+
+/// The length of an encoded field element.
+const SCALAR_LEN: usize = 32;
+
+/// A single operation that a floor planner performed on an [`Assignment`] backend.
+///
+/// Each variant corresponds to a method of [`Assignment`], with the values of any
+/// closures already evaluated. Values that were not known when the trace was recorded
+/// (because their closure returned an error) are `None`.
+#[derive(Clone, Debug)]
+pub enum TraceOp<F> {
+    /// [`Assignment::enter_region`], with the name of the region.
+    EnterRegion(String),
+    /// [`Assignment::exit_region`].
+    ExitRegion,
+    /// [`Assignment::place_region`].
+    PlaceRegion {
+        /// The first row of the region.
+        start: usize,
+        /// The number of rows reserved for the region.
+        rows: usize,
+    },
+    /// [`Assignment::enable_selector`].
+    EnableSelector {
+        /// The annotation of the selector.
+        annotation: String,
+        /// The selector that was enabled.
+        selector: Selector,
+        /// The row at which it was enabled.
+        row: usize,
+    },
+    /// [`Assignment::assign_advice`].
+    AssignAdvice {
+        /// The annotation of the cell.
+        annotation: String,
+        /// The column of the cell.
+        column: Column<Advice>,
+        /// The row of the cell.
+        row: usize,
+        /// The assigned value, if it was known.
+        value: Option<Assigned<F>>,
+    },
+    /// [`Assignment::assign_fixed`].
+    AssignFixed {
+        /// The annotation of the cell.
+        annotation: String,
+        /// The column of the cell.
+        column: Column<Fixed>,
+        /// The row of the cell.
+        row: usize,
+        /// The assigned value, if it was known.
+        value: Option<Assigned<F>>,
+    },
+    /// [`Assignment::mark_instance_dependent`].
+    MarkInstanceDependent,
+    /// [`Assignment::copy`].
+    Copy {
+        /// The permutation argument that constrains the cells.
+        permutation: Permutation,
+        /// The left cell, as `(column, row)`.
+        left: (Column<Any>, usize),
+        /// The right cell, as `(column, row)`.
+        right: (Column<Any>, usize),
+    },
+    /// [`Assignment::push_namespace`], with the name of the namespace.
+    PushNamespace(String),
+    /// [`Assignment::pop_namespace`], with the gadget name that was given.
+    PopNamespace(Option<String>),
+}
+
+/// A recording of every operation performed while synthesizing a circuit.
+///
+/// A trace captures the region and namespace structure of a circuit along with its
+/// assigned cells, enabled selectors and copy constraints, in the order in which the
+/// floor planner performed them. It can be written to and read from a compact binary
+/// encoding, and fed into any other [`Assignment`] backend with [`replay`]. This
+/// allows a witness to be generated once and proven elsewhere, or compared against a
+/// snapshot taken with an earlier version of a circuit.
+///
+/// # Examples
+///
+/// ```ignore
+/// use halo2::dev::{replay, Trace};
+///
+/// let trace = Trace::record(&circuit, instance)?;
+/// let mut bytes = vec![];
+/// trace.write(&mut bytes)?;
+///
+/// let trace = Trace::<Fp>::read(&mut &bytes[..])?;
+/// replay(&trace, &mut backend)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Trace<F> {
+    ops: Vec<TraceOp<F>>,
+}
+
+impl<F: FieldExt> Trace<F> {
+    /// Synthesizes `circuit` with its floor planner, and records the result.
+    ///
+    /// `instance` holds the values of the instance columns that the circuit may query;
+    /// it may be empty if the circuit does not query any.
+    pub fn record<ConcreteCircuit: Circuit<F>>(
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<F>>,
+    ) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = circuit.configure_from(&mut cs);
+        let mut recorder = RecordingAssignment::with_instance(instance);
+        ConcreteCircuit::FloorPlanner::synthesize(&mut recorder, circuit, config)?;
+        Ok(recorder.into_trace())
+    }
+
+    /// Returns the recorded operations, in order.
+    pub fn ops(&self) -> &[TraceOp<F>] {
+        &self.ops
+    }
+
+    /// Writes this trace to a buffer.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&[TRACE_VERSION])?;
+        write_u64(writer, self.ops.len())?;
+        for op in &self.ops {
+            match op {
+                TraceOp::EnterRegion(name) => {
+                    writer.write_all(&[0])?;
+                    write_string(writer, name)?;
+                }
+                TraceOp::ExitRegion => writer.write_all(&[1])?,
+                TraceOp::PlaceRegion { start, rows } => {
+                    writer.write_all(&[2])?;
+                    write_u64(writer, *start)?;
+                    write_u64(writer, *rows)?;
+                }
+                TraceOp::EnableSelector {
+                    annotation,
+                    selector,
+                    row,
+                } => {
+                    writer.write_all(&[3])?;
+                    write_string(writer, annotation)?;
+                    write_u64(writer, selector.0.index())?;
+                    write_u64(writer, *row)?;
+                }
+                TraceOp::AssignAdvice {
+                    annotation,
+                    column,
+                    row,
+                    value,
+                } => {
+                    writer.write_all(&[4])?;
+                    write_string(writer, annotation)?;
+                    write_u64(writer, column.index())?;
+                    write_u64(writer, *row)?;
+                    write_value(writer, value)?;
+                }
+                TraceOp::AssignFixed {
+                    annotation,
+                    column,
+                    row,
+                    value,
+                } => {
+                    writer.write_all(&[5])?;
+                    write_string(writer, annotation)?;
+                    write_u64(writer, column.index())?;
+                    write_u64(writer, *row)?;
+                    write_value(writer, value)?;
+                }
+                TraceOp::MarkInstanceDependent => writer.write_all(&[6])?,
+                TraceOp::Copy {
+                    permutation,
+                    left,
+                    right,
+                } => {
+                    writer.write_all(&[7])?;
+                    write_u64(writer, permutation.index())?;
+                    write_u64(writer, permutation.mapping().len())?;
+                    for column in permutation.mapping() {
+                        write_column(writer, *column)?;
+                    }
+                    write_column(writer, left.0)?;
+                    write_u64(writer, left.1)?;
+                    write_column(writer, right.0)?;
+                    write_u64(writer, right.1)?;
+                }
+                TraceOp::PushNamespace(name) => {
+                    writer.write_all(&[8])?;
+                    write_string(writer, name)?;
+                }
+                TraceOp::PopNamespace(gadget_name) => {
+                    writer.write_all(&[9])?;
+                    match gadget_name {
+                        Some(name) => {
+                            writer.write_all(&[1])?;
+                            write_string(writer, name)?;
+                        }
+                        None => writer.write_all(&[0])?,
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a trace from a buffer.
+    pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        if read_u8(reader)? != TRACE_VERSION {
+            return Err(invalid_data("unsupported trace version"));
+        }
+
+        let len = read_u64(reader)?;
+        let mut ops = vec![];
+        for _ in 0..len {
+            let op = match read_u8(reader)? {
+                0 => TraceOp::EnterRegion(read_string(reader)?),
+                1 => TraceOp::ExitRegion,
+                2 => TraceOp::PlaceRegion {
+                    start: read_u64(reader)?,
+                    rows: read_u64(reader)?,
+                },
+                3 => TraceOp::EnableSelector {
+                    annotation: read_string(reader)?,
+                    selector: Selector(Column::new(read_u64(reader)?, Fixed)),
+                    row: read_u64(reader)?,
+                },
+                4 => TraceOp::AssignAdvice {
+                    annotation: read_string(reader)?,
+                    column: Column::new(read_u64(reader)?, Advice),
+                    row: read_u64(reader)?,
+                    value: read_value(reader)?,
+                },
+                5 => TraceOp::AssignFixed {
+                    annotation: read_string(reader)?,
+                    column: Column::new(read_u64(reader)?, Fixed),
+                    row: read_u64(reader)?,
+                    value: read_value(reader)?,
+                },
+                6 => TraceOp::MarkInstanceDependent,
+                7 => {
+                    let index = read_u64(reader)?;
+                    let mapping = (0..read_u64(reader)?)
+                        .map(|_| read_column(reader))
+                        .collect::<Result<_, _>>()?;
+                    TraceOp::Copy {
+                        permutation: Permutation::from_parts(index, mapping),
+                        left: (read_column(reader)?, read_u64(reader)?),
+                        right: (read_column(reader)?, read_u64(reader)?),
+                    }
+                }
+                8 => TraceOp::PushNamespace(read_string(reader)?),
+                9 => TraceOp::PopNamespace(match read_u8(reader)? {
+                    0 => None,
+                    1 => Some(read_string(reader)?),
+                    _ => return Err(invalid_data("invalid gadget name in trace")),
+                }),
+                _ => return Err(invalid_data("invalid operation in trace")),
+            };
+            ops.push(op);
+        }
+
+        Ok(Trace { ops })
+    }
+}
+
+/// Feeds every operation of `trace` into the backend `cs`, in order.
+///
+/// Values that were unknown when the trace was recorded are assigned with closures that
+/// return [`Error::SynthesisError`], exactly as during the original synthesis.
+pub fn replay<F: FieldExt, CS: Assignment<F>>(trace: &Trace<F>, cs: &mut CS) -> Result<(), Error> {
+    for op in &trace.ops {
+        match op {
+            TraceOp::EnterRegion(name) => cs.enter_region(|| name.clone()),
+            TraceOp::ExitRegion => cs.exit_region(),
+            TraceOp::PlaceRegion { start, rows } => cs.place_region(*start, *rows),
+            TraceOp::EnableSelector {
+                annotation,
+                selector,
+                row,
+            } => cs.enable_selector(|| annotation.clone(), selector, *row)?,
+            TraceOp::AssignAdvice {
+                annotation,
+                column,
+                row,
+                value,
+            } => cs.assign_advice(
+                || annotation.clone(),
+                *column,
+                *row,
+                || value.ok_or(Error::SynthesisError),
+            )?,
+            TraceOp::AssignFixed {
+                annotation,
+                column,
+                row,
+                value,
+            } => cs.assign_fixed(
+                || annotation.clone(),
+                *column,
+                *row,
+                || value.ok_or(Error::SynthesisError),
+            )?,
+            TraceOp::MarkInstanceDependent => cs.mark_instance_dependent()?,
+            TraceOp::Copy {
+                permutation,
+                left,
+                right,
+            } => cs.copy(permutation, left.0, left.1, right.0, right.1)?,
+            TraceOp::PushNamespace(name) => cs.push_namespace(|| name.clone()),
+            TraceOp::PopNamespace(gadget_name) => cs.pop_namespace(gadget_name.clone()),
+        }
+    }
+
+    Ok(())
+}
+
+/// An [`Assignment`] backend that records every operation performed on it into a
+/// [`Trace`].
+///
+/// Fixed values are answered from the cells recorded so far, and instance values from
+/// the instance columns that the recorder was created with (if any).
+#[derive(Debug)]
+pub struct RecordingAssignment<F> {
+    ops: Vec<TraceOp<F>>,
+    instance: Vec<Vec<F>>,
+    fixed: HashMap<(Column<Fixed>, usize), Option<Assigned<F>>>,
+}
+
+impl<F: FieldExt> Default for RecordingAssignment<F> {
+    fn default() -> Self {
+        Self::with_instance(vec![])
+    }
+}
+
+impl<F: FieldExt> RecordingAssignment<F> {
+    /// Creates a recorder that does not know the values of any instance columns.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a recorder that answers queries for instance values from `instance`,
+    /// which holds the values of each instance column.
+    pub fn with_instance(instance: Vec<Vec<F>>) -> Self {
+        RecordingAssignment {
+            ops: vec![],
+            instance,
+            fixed: HashMap::new(),
+        }
+    }
+
+    /// Returns the operations recorded so far.
+    pub fn into_trace(self) -> Trace<F> {
+        Trace { ops: self.ops }
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for RecordingAssignment<F> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.ops.push(TraceOp::EnterRegion(name_fn().into()));
+    }
+
+    fn exit_region(&mut self) {
+        self.ops.push(TraceOp::ExitRegion);
+    }
+
+    fn place_region(&mut self, start: usize, rows: usize) {
+        self.ops.push(TraceOp::PlaceRegion { start, rows });
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.ops.push(TraceOp::EnableSelector {
+            annotation: annotation().into(),
+            selector: *selector,
+            row,
+        });
+        Ok(())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.ops.push(TraceOp::AssignAdvice {
+            annotation: annotation().into(),
+            column,
+            row,
+            value: to().ok().map(|v| v.into()),
+        });
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let value = to().ok().map(|v| v.into());
+        self.fixed.insert((column, row), value);
+        self.ops.push(TraceOp::AssignFixed {
+            annotation: annotation().into(),
+            column,
+            row,
+            value,
+        });
+        Ok(())
+    }
+
+    fn fixed_value(&self, column: Column<Fixed>, row: usize) -> Result<Option<F>, Error> {
+        Ok(self
+            .fixed
+            .get(&(column, row))
+            .and_then(|value| value.map(|v| v.evaluate())))
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        match self.instance.get(column.index()) {
+            Some(values) => values
+                .get(row)
+                .map(|value| Some(*value))
+                .ok_or(Error::BoundsFailure),
+            None => Ok(None),
+        }
+    }
+
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        self.ops.push(TraceOp::MarkInstanceDependent);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        permutation: &Permutation,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.ops.push(TraceOp::Copy {
+            permutation: permutation.clone(),
+            left: (left_column, left_row),
+            right: (right_column, right_row),
+        });
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.ops.push(TraceOp::PushNamespace(name_fn().into()));
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.ops.push(TraceOp::PopNamespace(gadget_name));
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u8<R: io::Read>(reader: &mut R) -> io::Result<u8> {
+    let mut data = [0u8; 1];
+    reader.read_exact(&mut data)?;
+    Ok(data[0])
+}
+
+fn write_u64<W: io::Write>(writer: &mut W, value: usize) -> io::Result<()> {
+    writer.write_all(&(value as u64).to_le_bytes())
+}
+
+fn read_u64<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    let mut data = [0u8; 8];
+    reader.read_exact(&mut data)?;
+    usize::try_from(u64::from_le_bytes(data))
+        .map_err(|_| invalid_data("integer in trace is too large"))
+}
+
+fn write_string<W: io::Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    write_u64(writer, value.len())?;
+    writer.write_all(value.as_bytes())
+}
+
+fn read_string<R: io::Read>(reader: &mut R) -> io::Result<String> {
+    let len = read_u64(reader)?;
+    let mut data = vec![];
+    reader.by_ref().take(len as u64).read_to_end(&mut data)?;
+    if data.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(data).map_err(|_| invalid_data("invalid string in trace"))
+}
+
+fn write_column<W: io::Write>(writer: &mut W, column: Column<Any>) -> io::Result<()> {
+    let tag = match column.column_type() {
+        Any::Advice => 0,
+        Any::Fixed => 1,
+        Any::Instance => 2,
+    };
+    writer.write_all(&[tag])?;
+    write_u64(writer, column.index())
+}
+
+fn read_column<R: io::Read>(reader: &mut R) -> io::Result<Column<Any>> {
+    let column_type = match read_u8(reader)? {
+        0 => Any::Advice,
+        1 => Any::Fixed,
+        2 => Any::Instance,
+        _ => return Err(invalid_data("invalid column type in trace")),
+    };
+    Ok(Column::new(read_u64(reader)?, column_type))
+}
+
+fn write_value<F: FieldExt, W: io::Write>(
+    writer: &mut W,
+    value: &Option<Assigned<F>>,
+) -> io::Result<()> {
+    match value {
+        None => writer.write_all(&[0]),
+        Some(Assigned::Zero) => writer.write_all(&[1]),
+        Some(Assigned::Trivial(value)) => {
+            writer.write_all(&[2])?;
+            writer.write_all(&value.to_bytes())
+        }
+        Some(Assigned::Rational(numerator, denominator)) => {
+            writer.write_all(&[3])?;
+            writer.write_all(&numerator.to_bytes())?;
+            writer.write_all(&denominator.to_bytes())
+        }
+    }
+}
+
+fn read_value<F: FieldExt, R: io::Read>(reader: &mut R) -> io::Result<Option<Assigned<F>>> {
+    Ok(match read_u8(reader)? {
+        0 => None,
+        1 => Some(Assigned::Zero),
+        2 => Some(Assigned::Trivial(read_scalar(reader)?)),
+        3 => Some(Assigned::Rational(
+            read_scalar(reader)?,
+            read_scalar(reader)?,
+        )),
+        _ => return Err(invalid_data("invalid value in trace")),
+    })
+}
+
+fn read_scalar<F: FieldExt, R: io::Read>(reader: &mut R) -> io::Result<F> {
+    let mut data = [0u8; SCALAR_LEN];
+    reader.read_exact(&mut data)?;
+    Option::from(F::from_bytes(&data))
+        .ok_or_else(|| invalid_data("invalid field element encoding in trace"))
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{replay, RecordingAssignment, Trace, TraceOp};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{
+            Advice, Any, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Permutation,
+            Selector,
+        },
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct TraceConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Fixed>,
+        i: Column<Instance>,
+        s: Selector,
+        perm: Permutation,
+    }
+
+    /// Copies `a` into a second region, checks `a * c = b`, and exposes `b`.
+    struct TraceCircuit(Option<Fp>);
+
+    impl Circuit<Fp> for TraceCircuit {
+        type Config = TraceConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            TraceCircuit(None)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> TraceConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.fixed_column();
+            let i = meta.instance_column();
+            let s = meta.selector();
+            let perm = meta.permutation(&[a.into(), b.into(), i.into()]);
+
+            meta.create_gate("a * c = b", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let c = meta.query_fixed(c, Rotation::cur());
+                vec![s * (a * c - b)]
+            });
+
+            TraceConfig {
+                a,
+                b,
+                c,
+                i,
+                s,
+                perm,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: TraceConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let a = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.0.ok_or(Error::SynthesisError),
+                    )
+                },
+            )?;
+
+            let mut layouter = layouter.namespace(|| "multiply");
+            layouter.assign_region(
+                || "a * c",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    let copied = region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.0.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(&config.perm, a, copied)?;
+                    region.assign_fixed(|| "c", config.c, 0, || Ok(Fp::from(3u64)))?;
+                    let b = region.query_instance(config.i, 0)?;
+                    region.assign_advice(|| "b", config.b, 0, || b.ok_or(Error::SynthesisError))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn encode(trace: &Trace<Fp>) -> Vec<u8> {
+        let mut bytes = vec![];
+        trace.write(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let trace = Trace::record(
+            &TraceCircuit(Some(Fp::from(2u64))),
+            vec![vec![Fp::from(6u64)]],
+        )
+        .unwrap();
+        let bytes = encode(&trace);
+
+        // The trace contains the structure, cells and copies of the circuit.
+        let ops = trace.ops();
+        assert!(matches!(&ops[0], TraceOp::EnterRegion(name) if name == "load"));
+        assert!(ops
+            .iter()
+            .any(|op| matches!(op, TraceOp::PushNamespace(name) if name == "multiply")));
+        assert!(ops.iter().any(|op| matches!(
+            op,
+            TraceOp::Copy { left, .. } if left.0 == Column::new(0, Any::Advice)
+        )));
+        assert!(ops.iter().any(|op| matches!(
+            op,
+            TraceOp::AssignAdvice { annotation, value: Some(value), .. }
+                if annotation == "b" && value.evaluate() == Fp::from(6u64)
+        )));
+
+        // The encoding round-trips.
+        let read = Trace::<Fp>::read(&mut &bytes[..]).unwrap();
+        assert_eq!(encode(&read), bytes);
+        assert!(Trace::<Fp>::read(&mut &bytes[..bytes.len() - 1]).is_err());
+
+        // Replaying the trace into another backend performs the same operations.
+        let mut recorder = RecordingAssignment::new();
+        replay(&read, &mut recorder).unwrap();
+        assert_eq!(encode(&recorder.into_trace()), bytes);
+
+        // Unknown values are recorded as such.
+        let trace = Trace::record(&TraceCircuit(None), vec![]).unwrap();
+        assert!(trace
+            .ops()
+            .iter()
+            .any(|op| matches!(op, TraceOp::AssignAdvice { value: None, .. })));
+    }
+}
@@ -0,0 +1,233 @@
+//! Instrumented circuit synthesis, for profiling witness generation.
+
+use std::marker::PhantomData;
+
+use ff::Field;
+use tracing::{debug_span, span::EnteredSpan, trace_span};
+
+use crate::plonk::{
+    Advice, Any, Assigned, Assignment, Circuit, Column, Error, Fixed, FloorPlanner, Instance,
+    Permutation, Selector,
+};
+
+/// A [`FloorPlanner`] that lays out circuits with the floor planner `P`, while emitting
+/// [`tracing`] spans for everything the circuit assigns.
+///
+/// - Each region is covered by a `DEBUG`-level span named `region`, and each namespace
+///   (including those created by [`Layouter::namespace`]) by a `DEBUG`-level span named
+///   `namespace`. Both record the name they were given, and region spans also record
+///   the `path` of the namespaces they were entered in (such as `"poseidon::absorb"`).
+/// - Each cell assignment, selector enable and copy constraint is covered by a
+///   `TRACE`-level span recording its column and absolute row. For assignments, this
+///   includes the time spent computing the assigned value.
+///
+/// Spans are only emitted while the circuit is being synthesized, so they nest inside
+/// whichever spans are active when key generation or proving is started. Combined with
+/// a subscriber such as `tracing-flame`, this shows where witness generation time is
+/// spent per gadget.
+///
+/// To use it, wrap a circuit's floor planner:
+///
+/// ```ignore
+/// impl Circuit<Fp> for MyCircuit {
+///     type Config = MyConfig;
+///     type FloorPlanner = TracingFloorPlanner<SimpleFloorPlanner>;
+///
+///     // ...
+/// }
+/// ```
+///
+/// [`Layouter::namespace`]: crate::circuit::Layouter::namespace
+#[derive(Debug)]
+pub struct TracingFloorPlanner<P: FloorPlanner> {
+    _marker: PhantomData<P>,
+}
+
+impl<P: FloorPlanner> FloorPlanner for TracingFloorPlanner<P> {
+    fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+    ) -> Result<(), Error> {
+        let mut cs = TracingAssignment {
+            cs,
+            spans: vec![],
+            namespace: vec![],
+            _marker: PhantomData,
+        };
+        P::synthesize(&mut cs, circuit, config)
+    }
+}
+
+/// An [`Assignment`] that forwards everything to `cs`, inside tracing spans.
+struct TracingAssignment<'cs, F: Field, CS: Assignment<F>> {
+    cs: &'cs mut CS,
+    /// The spans for the currently-entered namespaces and region, innermost last.
+    spans: Vec<EnteredSpan>,
+    /// The names of the currently-entered namespaces, outermost first.
+    namespace: Vec<String>,
+    _marker: PhantomData<F>,
+}
+
+impl<'cs, F: Field, CS: Assignment<F>> Assignment<F> for TracingAssignment<'cs, F, CS> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name: String = name_fn().into();
+        let path = self.namespace.join("::");
+        self.spans
+            .push(debug_span!("region", name = name.as_str(), path = path.as_str()).entered());
+        self.cs.enter_region(|| name);
+    }
+
+    fn exit_region(&mut self) {
+        self.cs.exit_region();
+        self.spans.pop();
+    }
+
+    fn place_region(&mut self, start: usize, rows: usize) {
+        self.cs.place_region(start, rows);
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _span = trace_span!("enable_selector", selector = ?selector, row).entered();
+        self.cs.enable_selector(annotation, selector, row)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _span = trace_span!("assign_advice", column = ?column, row).entered();
+        self.cs.assign_advice(annotation, column, row, to)
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _span = trace_span!("assign_fixed", column = ?column, row).entered();
+        self.cs.assign_fixed(annotation, column, row, to)
+    }
+
+    fn assign_advice_slice<A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _span = trace_span!(
+            "assign_advice_slice",
+            column = ?column,
+            row,
+            len = values.len()
+        )
+        .entered();
+        self.cs.assign_advice_slice(annotation, column, row, values)
+    }
+
+    fn assign_fixed_slice<A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        values: &[Assigned<F>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _span = trace_span!(
+            "assign_fixed_slice",
+            column = ?column,
+            row,
+            len = values.len()
+        )
+        .entered();
+        self.cs.assign_fixed_slice(annotation, column, row, values)
+    }
+
+    fn fixed_value(&self, column: Column<Fixed>, row: usize) -> Result<Option<F>, Error> {
+        self.cs.fixed_value(column, row)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.cs.query_instance(column, row)
+    }
+
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        self.cs.mark_instance_dependent()
+    }
+
+    fn copy(
+        &mut self,
+        permutation: &Permutation,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        let _span = trace_span!(
+            "copy",
+            left_column = ?left_column,
+            left_row,
+            right_column = ?right_column,
+            right_row
+        )
+        .entered();
+        self.cs
+            .copy(permutation, left_column, left_row, right_column, right_row)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name: String = name_fn().into();
+        self.spans
+            .push(debug_span!("namespace", name = name.as_str()).entered());
+        self.namespace.push(name.clone());
+        self.cs.push_namespace(|| name);
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.cs.pop_namespace(gadget_name);
+        self.namespace.pop();
+        self.spans.pop();
+    }
+}
@@ -0,0 +1,234 @@
+//! Versioned headers for serialized parameters and keys.
+//!
+//! [`Params::write`], [`VerifyingKey::write`], [`CircuitShape::write`] and
+//! [`CircuitIr::write`] start their encodings with a header recording the version of
+//! the encoding, the version of `halo2` that wrote it, the curve it is over and (for
+//! keys) the digest of the circuit it is for. The corresponding `read` functions check the header before
+//! decoding anything else, so that a mismatched file is rejected with a
+//! [`FormatError`] instead of being decoded into meaningless points.
+//!
+//! [`Params::write`]: crate::poly::commitment::Params::write
+//! [`VerifyingKey::write`]: crate::plonk::VerifyingKey::write
+//! [`CircuitShape::write`]: crate::plonk::CircuitShape::write
+//! [`CircuitIr::write`]: crate::interop::ir::CircuitIr::write
+
+use std::error;
+use std::fmt;
+use std::io;
+
+/// The magic bytes at the start of every header.
+const MAGIC: [u8; 8] = *b"halo2fmt";
+
+/// The version of the encodings. This is incremented whenever an encoding changes in
+/// a way that older versions of `halo2` cannot read.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The version of `halo2` that is recorded in headers.
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The kinds of object that can be preceded by a header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Params = 1,
+    VerifyingKey = 2,
+    CircuitShape = 3,
+    CircuitIr = 4,
+}
+
+/// The reasons why an encoding can be rejected by its header.
+///
+/// The `read` functions return these wrapped in an [`io::Error`] of kind
+/// [`io::ErrorKind::InvalidData`]; use [`FormatError::from_io`] to recover them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatError {
+    /// The data does not start with a header for the expected kind of object. It may
+    /// have been written by a version of `halo2` that predates headers, or may be a
+    /// different kind of object (for example, parameters instead of a verifying key).
+    UnknownFormat,
+    /// The data was written with a version of the encoding that this version of
+    /// `halo2` cannot read.
+    IncompatibleVersion {
+        /// The version of the encoding.
+        format_version: u32,
+        /// The version of `halo2` that wrote the data.
+        crate_version: String,
+    },
+    /// The data is over a different curve (or field) than the one being read.
+    CurveMismatch {
+        /// The curve that the data was expected to be over.
+        expected: String,
+        /// The curve that the data is over.
+        found: String,
+    },
+    /// The data was written for a different circuit than the one being read; its
+    /// constraint system differs from the one that the circuit configures.
+    CircuitMismatch,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "data does not start with the expected header"),
+            Self::IncompatibleVersion {
+                format_version,
+                crate_version,
+            } => write!(
+                f,
+                "data was written by halo2 {} with format version {}, but only version {} is supported",
+                crate_version, format_version, FORMAT_VERSION
+            ),
+            Self::CurveMismatch { expected, found } => write!(
+                f,
+                "data is over the curve {}, but {} was expected",
+                found, expected
+            ),
+            Self::CircuitMismatch => write!(f, "data was written for a different circuit"),
+        }
+    }
+}
+
+impl error::Error for FormatError {}
+
+impl From<FormatError> for io::Error {
+    fn from(error: FormatError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+impl FormatError {
+    /// Returns the `FormatError` wrapped in an error returned by a `read` function, if
+    /// the data was rejected because of its header.
+    pub fn from_io(error: &io::Error) -> Option<&Self> {
+        error.get_ref().and_then(|inner| inner.downcast_ref())
+    }
+}
+
+/// Writes a string of at most 255 bytes, truncating longer strings.
+fn write_str<W: io::Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    let bytes = &value.as_bytes()[..value.len().min(u8::MAX as usize)];
+    writer.write_all(&[bytes.len() as u8])?;
+    writer.write_all(bytes)
+}
+
+fn read_str<R: io::Read>(reader: &mut R) -> io::Result<String> {
+    let mut len = [0u8; 1];
+    reader.read_exact(&mut len)?;
+    let mut bytes = vec![0u8; len[0] as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Writes a header for an object of the given kind, over the given curve and (if it
+/// belongs to a circuit) for the circuit with the given digest.
+pub(crate) fn write_header<W: io::Write>(
+    writer: &mut W,
+    kind: Kind,
+    curve: &str,
+    circuit: Option<&[u8; 64]>,
+) -> io::Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    write_str(writer, CRATE_VERSION)?;
+    writer.write_all(&[kind as u8])?;
+    write_str(writer, curve)?;
+    if let Some(digest) = circuit {
+        writer.write_all(digest)?;
+    }
+    Ok(())
+}
+
+/// Reads a header written by [`write_header`], checking that it matches the given
+/// kind, curve and circuit digest.
+pub(crate) fn read_header<R: io::Read>(
+    reader: &mut R,
+    kind: Kind,
+    curve: &str,
+    circuit: Option<&[u8; 64]>,
+) -> io::Result<()> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(FormatError::UnknownFormat.into());
+    }
+
+    let mut format_version = [0u8; 4];
+    reader.read_exact(&mut format_version)?;
+    let format_version = u32::from_le_bytes(format_version);
+    let crate_version = read_str(reader)?;
+    if format_version != FORMAT_VERSION {
+        return Err(FormatError::IncompatibleVersion {
+            format_version,
+            crate_version,
+        }
+        .into());
+    }
+
+    let mut found_kind = [0u8; 1];
+    reader.read_exact(&mut found_kind)?;
+    if found_kind[0] != kind as u8 {
+        return Err(FormatError::UnknownFormat.into());
+    }
+
+    let found = read_str(reader)?;
+    if found != curve {
+        return Err(FormatError::CurveMismatch {
+            expected: curve.to_owned(),
+            found,
+        }
+        .into());
+    }
+
+    if let Some(expected) = circuit {
+        let mut digest = [0u8; 64];
+        reader.read_exact(&mut digest)?;
+        if digest[..] != expected[..] {
+            return Err(FormatError::CircuitMismatch.into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_header, write_header, FormatError, Kind};
+
+    #[test]
+    fn header_mismatches() {
+        let digest = [7u8; 64];
+        let mut header = vec![];
+        write_header(&mut header, Kind::VerifyingKey, "pallas", Some(&digest)).unwrap();
+
+        let read_from = |bytes: &[u8], kind, curve, circuit: &[u8; 64]| {
+            read_header(&mut &bytes[..], kind, curve, Some(circuit))
+                .map_err(|e| FormatError::from_io(&e).cloned())
+        };
+        let read = |kind, curve, circuit: &[u8; 64]| read_from(&header, kind, curve, circuit);
+        assert!(read(Kind::VerifyingKey, "pallas", &digest).is_ok());
+        assert_eq!(
+            read(Kind::Params, "pallas", &digest),
+            Err(Some(FormatError::UnknownFormat))
+        );
+        assert_eq!(
+            read(Kind::VerifyingKey, "vesta", &digest),
+            Err(Some(FormatError::CurveMismatch {
+                expected: "vesta".to_owned(),
+                found: "pallas".to_owned(),
+            }))
+        );
+        assert_eq!(
+            read(Kind::VerifyingKey, "pallas", &[0; 64]),
+            Err(Some(FormatError::CircuitMismatch))
+        );
+
+        // Bump the format version.
+        let mut bumped = header.clone();
+        bumped[8] += 1;
+        assert_eq!(
+            read_from(&bumped, Kind::VerifyingKey, "pallas", &digest),
+            Err(Some(FormatError::IncompatibleVersion {
+                format_version: super::FORMAT_VERSION + 1,
+                crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+            }))
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Chips and gadgets that are shipped with the crate, and the conventions that they
+//! follow.
+//!
+//! The chips in this module are intended as building blocks for examples, benchmarks
+//! and simple circuits, so that each of them does not need to re-implement the same
+//! basic instructions.
+//!
+//! # Conventions
+//!
+//! A chip implements [`Chip`], and exposes its operations through one or more
+//! instruction traits that extend [`Instructions`]. Every instruction takes a
+//! [`Layouter`] (usually namespaced by the caller), and returns the variables that it
+//! assigned as [`Instructions::Var`]. Because all chips use variables that implement
+//! [`Var`], a variable returned by one chip can be passed to another chip, which copies
+//! it into its own region with [`Var::copy_advice`].
+//!
+//! Chips that need to load fixed state (such as lookup tables or constants) before
+//! they are used implement [`Load`], and store the result as their [`Chip::Loaded`]
+//! state.
+
+use std::fmt;
+
+use crate::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Chip, Layouter, Region},
+    plonk::{Advice, Column, Error, Fixed, Permutation},
+};
+
+pub mod linear_combination;
+pub mod select;
+pub mod standard;
+pub mod table;
+
+/// A variable assigned by a chip.
+pub trait Var<F: FieldExt>: Clone + fmt::Debug {
+    /// Returns the cell in which this variable was assigned.
+    fn cell(&self) -> Cell;
+
+    /// Returns the value of this variable, if known.
+    fn value(&self) -> Option<F>;
+
+    /// Assigns this variable to the given advice column of a region, and constrains
+    /// the new cell to equal this variable.
+    fn copy_advice<A, AR>(
+        &self,
+        annotation: A,
+        region: &mut Region<'_, F>,
+        permutation: &Permutation,
+        column: Column<Advice>,
+        offset: usize,
+    ) -> Result<AssignedCell<F>, Error>
+    where
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        let value = self.value();
+        let cell = region.assign_advice_then_copy(
+            annotation,
+            column,
+            offset,
+            permutation,
+            self.cell(),
+            value,
+        )?;
+        Ok(AssignedCell::new(cell, value))
+    }
+}
+
+/// A cell that has been assigned a value, which is the [`Var`] used by the chips in
+/// this module.
+#[derive(Clone, Copy, Debug)]
+pub struct AssignedCell<F: FieldExt> {
+    cell: Cell,
+    value: Option<F>,
+}
+
+impl<F: FieldExt> AssignedCell<F> {
+    /// Constructs an assigned cell from the cell and the value assigned to it.
+    pub fn new(cell: Cell, value: Option<F>) -> Self {
+        AssignedCell { cell, value }
+    }
+}
+
+impl<F: FieldExt> Var<F> for AssignedCell<F> {
+    fn cell(&self) -> Cell {
+        self.cell
+    }
+
+    fn value(&self) -> Option<F> {
+        self.value
+    }
+}
+
+/// The supertrait of instruction traits, which fixes the type of variable that the
+/// instructions of a chip operate on.
+pub trait Instructions<F: FieldExt>: Chip<F> {
+    /// A variable assigned by this chip.
+    type Var: Var<F>;
+}
+
+/// A chip that loads fixed state before it is used.
+pub trait Load<F: FieldExt>: Chip<F> {
+    /// Loads the fixed state of the chip, which is then stored by the chip as its
+    /// [`Chip::Loaded`] state.
+    ///
+    /// This should be called once per circuit, at the start of
+    /// [`Circuit::synthesize`].
+    ///
+    /// [`Circuit::synthesize`]: crate::plonk::Circuit::synthesize
+    fn load(config: &Self::Config, layouter: impl Layouter<F>) -> Result<Self::Loaded, Error>;
+}
+
+/// Returns a witness value, or [`Error::SynthesisError`] if it is not known.
+///
+/// This is the conversion that assignment closures use for values that are missing
+/// during key generation:
+///
+/// ```ignore
+/// region.assign_advice(|| "a", config.a, 0, || witness(a))?;
+/// ```
+pub fn witness<T>(value: Option<T>) -> Result<T, Error> {
+    value.ok_or(Error::SynthesisError)
+}
+
+/// Constants that have been loaded into a fixed column, which chips can use as their
+/// [`Chip::Loaded`] state and copy into their regions.
+#[derive(Clone, Debug)]
+pub struct LoadedConstants<F: FieldExt> {
+    constants: Vec<AssignedCell<F>>,
+}
+
+impl<F: FieldExt> LoadedConstants<F> {
+    /// Loads the given constants into consecutive rows of `column`, in a single region.
+    ///
+    /// `column` must be within the permutation that the constants are later copied
+    /// with.
+    pub fn load(
+        mut layouter: impl Layouter<F>,
+        column: Column<Fixed>,
+        constants: &[F],
+    ) -> Result<Self, Error> {
+        layouter.assign_region(
+            || "load constants",
+            |mut region| {
+                let constants = constants
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, &constant)| {
+                        region
+                            .assign_fixed(|| "constant", column, offset, || Ok(constant))
+                            .map(|cell| AssignedCell::new(cell, Some(constant)))
+                    })
+                    .collect::<Result<_, _>>()?;
+                Ok(LoadedConstants { constants })
+            },
+        )
+    }
+
+    /// Returns the constant at the given index, in the order in which the constants
+    /// were loaded.
+    pub fn get(&self, index: usize) -> Option<&AssignedCell<F>> {
+        self.constants.get(index)
+    }
+
+    /// Returns the number of loaded constants.
+    pub fn len(&self) -> usize {
+        self.constants.len()
+    }
+
+    /// Returns `true` if no constants were loaded.
+    pub fn is_empty(&self) -> bool {
+        self.constants.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{LoadedConstants, Var};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Fixed, Permutation},
+    };
+
+    #[derive(Clone)]
+    struct ConstantsConfig {
+        a: Column<Advice>,
+        constants: Column<Fixed>,
+        perm: Permutation,
+    }
+
+    /// Copies a loaded constant into an advice cell that is assigned a witness.
+    #[derive(Default)]
+    struct ConstantsCircuit {
+        a: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for ConstantsCircuit {
+        type Config = ConstantsConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> ConstantsConfig {
+            let a = meta.advice_column();
+            let constants = meta.fixed_column();
+            let perm = meta.permutation(&[a.into(), constants.into()]);
+
+            ConstantsConfig { a, constants, perm }
+        }
+
+        fn synthesize(
+            &self,
+            config: ConstantsConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let loaded = LoadedConstants::load(
+                layouter.namespace(|| "constants"),
+                config.constants,
+                &[Fp::from(3u64), Fp::from(5u64)],
+            )?;
+            assert_eq!(loaded.len(), 2);
+
+            let five = loaded.get(1).unwrap();
+            layouter.assign_region(
+                || "use constant",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", config.a, 0, || super::witness(self.a))?;
+                    region.constrain_equal(&config.perm, five.cell(), a)?;
+
+                    // Copying the constant assigns its value.
+                    let copied =
+                        five.copy_advice(|| "copy", &mut region, &config.perm, config.a, 1)?;
+                    assert_eq!(copied.value(), Some(Fp::from(5u64)));
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn loaded_constants() {
+        let circuit = |a: u64| ConstantsCircuit {
+            a: Some(Fp::from(a)),
+        };
+
+        let prover = MockProver::run(3, &circuit(5), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(3, &circuit(3), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//! A gadget computing linear combinations of assigned values.
+//!
+//! [`LinearCombinationChip`] computes `c_0 * x_0 + c_1 * x_1 + ...` for constant
+//! coefficients `c_i` and assigned values `x_i`. The values are copied into rows of
+//! `width` advice columns, with the coefficients in `width` fixed columns, and an
+//! accumulator column holds the running sum:
+//!
+//! ```text
+//! | x_0 | x_1 | c_0 | c_1 | acc                         |
+//! |-----|-----|-----|-----|-----------------------------|
+//! | x_0 | x_1 | c_0 | c_1 | c_0 x_0 + c_1 x_1           |
+//! | x_2 | 0   | c_2 | 0   | c_0 x_0 + c_1 x_1 + c_2 x_2 |
+//! ```
+//!
+//! The number of rows is chosen from the number of terms and the width of the chip.
+
+use std::marker::PhantomData;
+
+use super::{witness, AssignedCell, Instructions, Var};
+use crate::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter},
+    plonk::{
+        Advice, Column, ConstraintSystem, Error, Expression, Fixed, Permutation, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+
+/// The instructions implemented by [`LinearCombinationChip`].
+pub trait LinearCombinationInstructions<F: FieldExt>: Instructions<F> {
+    /// Returns the sum of `coefficients[i] * values[i]`.
+    ///
+    /// Returns [`Error::SynthesisError`] if the slices have different lengths.
+    fn linear_combination(
+        &self,
+        layouter: impl Layouter<F>,
+        coefficients: &[F],
+        values: &[Self::Var],
+    ) -> Result<Self::Var, Error>;
+}
+
+/// The configuration of a [`LinearCombinationChip`].
+#[derive(Clone, Debug)]
+pub struct LinearCombinationConfig {
+    /// The advice columns holding the values in each row.
+    pub values: Vec<Column<Advice>>,
+    /// The fixed columns holding the coefficients of the values in each row.
+    pub coefficients: Vec<Column<Fixed>>,
+    /// The advice column holding the accumulated sum.
+    pub acc: Column<Advice>,
+    /// Enables the gate for the first row of a linear combination.
+    pub s_first: Selector,
+    /// Enables the gate for the later rows of a linear combination.
+    pub s_next: Selector,
+    /// The permutation used to copy values into the chip's regions.
+    pub perm: Permutation,
+}
+
+/// A chip that computes linear combinations with constant coefficients.
+#[derive(Debug)]
+pub struct LinearCombinationChip<F: FieldExt> {
+    config: LinearCombinationConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for LinearCombinationChip<F> {
+    type Config = LinearCombinationConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> Instructions<F> for LinearCombinationChip<F> {
+    type Var = AssignedCell<F>;
+}
+
+impl<F: FieldExt> LinearCombinationChip<F> {
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: LinearCombinationConfig) -> Self {
+        LinearCombinationChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configures the chip to combine `values.len()` values per row, allocating a fixed
+    /// column for the coefficient of each.
+    ///
+    /// `perm` must contain the value and accumulator columns, along with the columns of
+    /// any cells that are passed to the chip.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `values` is empty.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        values: &[Column<Advice>],
+        acc: Column<Advice>,
+        perm: Permutation,
+    ) -> LinearCombinationConfig {
+        assert!(
+            !values.is_empty(),
+            "A linear combination needs a value column."
+        );
+
+        let coefficients: Vec<_> = values.iter().map(|_| meta.fixed_column()).collect();
+        let s_first = meta.selector();
+        let s_next = meta.selector();
+
+        // The gates are separate so that the first row does not query the row above it.
+        let row_sum = |meta: &mut VirtualCells<'_, F>| {
+            values.iter().zip(coefficients.iter()).fold(
+                Expression::Constant(F::zero()),
+                |sum, (value, coefficient)| {
+                    sum + meta.query_fixed(*coefficient, Rotation::cur())
+                        * meta.query_advice(*value, Rotation::cur())
+                },
+            )
+        };
+
+        meta.create_gate("linear combination first row", |meta| {
+            let s_first = meta.query_selector(s_first);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            vec![s_first * (acc_cur - row_sum(meta))]
+        });
+
+        meta.create_gate("linear combination next row", |meta| {
+            let s_next = meta.query_selector(s_next);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            vec![s_next * (acc_cur - acc_prev - row_sum(meta))]
+        });
+
+        LinearCombinationConfig {
+            values: values.to_vec(),
+            coefficients,
+            acc,
+            s_first,
+            s_next,
+            perm,
+        }
+    }
+
+    /// Returns the number of rows used by a linear combination of `terms` terms.
+    pub fn rows(&self, terms: usize) -> usize {
+        let width = self.config.values.len();
+        std::cmp::max((terms + width - 1) / width, 1)
+    }
+}
+
+impl<F: FieldExt> LinearCombinationInstructions<F> for LinearCombinationChip<F> {
+    fn linear_combination(
+        &self,
+        mut layouter: impl Layouter<F>,
+        coefficients: &[F],
+        values: &[Self::Var],
+    ) -> Result<Self::Var, Error> {
+        if coefficients.len() != values.len() {
+            return Err(Error::SynthesisError);
+        }
+
+        let config = self.config();
+        let width = config.values.len();
+        let rows = self.rows(values.len());
+
+        layouter.assign_region(
+            || "linear combination",
+            |mut region| {
+                let mut acc = Some(F::zero());
+                let mut acc_cell = None;
+                for row in 0..rows {
+                    if row == 0 {
+                        config.s_first.enable(&mut region, row)?;
+                    } else {
+                        config.s_next.enable(&mut region, row)?;
+                    }
+
+                    for (column, advice) in config.values.iter().enumerate() {
+                        let index = row * width + column;
+                        let (coefficient, value) = match values.get(index) {
+                            Some(value) => {
+                                value.copy_advice(
+                                    || "value",
+                                    &mut region,
+                                    &config.perm,
+                                    *advice,
+                                    row,
+                                )?;
+                                (coefficients[index], value.value())
+                            }
+                            None => {
+                                // Pad the last row with zero terms.
+                                region.assign_advice(
+                                    || "padding",
+                                    *advice,
+                                    row,
+                                    || Ok(F::zero()),
+                                )?;
+                                (F::zero(), Some(F::zero()))
+                            }
+                        };
+                        region.assign_fixed(
+                            || "coefficient",
+                            config.coefficients[column],
+                            row,
+                            || Ok(coefficient),
+                        )?;
+                        acc = acc.and_then(|acc| value.map(|value| acc + coefficient * value));
+                    }
+
+                    acc_cell =
+                        Some(region.assign_advice(|| "acc", config.acc, row, || witness(acc))?);
+                }
+
+                Ok(AssignedCell::new(
+                    acc_cell.expect("a linear combination has at least one row"),
+                    acc,
+                ))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{LinearCombinationChip, LinearCombinationConfig, LinearCombinationInstructions};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        gadget::{witness, AssignedCell, Var},
+        plonk::{Any, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    /// Computes `1 * x_0 + 2 * x_1 + ... + 5 * x_4`, two terms per row.
+    #[derive(Default)]
+    struct DotCircuit {
+        values: Vec<Option<Fp>>,
+        expected: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for DotCircuit {
+        type Config = LinearCombinationConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            DotCircuit {
+                values: vec![None; self.values.len()],
+                expected: None,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> LinearCombinationConfig {
+            let values = [meta.advice_column(), meta.advice_column()];
+            let acc = meta.advice_column();
+            let perm = meta.permutation(
+                &[values[0], values[1], acc]
+                    .iter()
+                    .map(|column| (*column).into())
+                    .collect::<Vec<Column<Any>>>(),
+            );
+            LinearCombinationChip::configure(meta, &values, acc, perm)
+        }
+
+        fn synthesize(
+            &self,
+            config: LinearCombinationConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = LinearCombinationChip::construct(config.clone());
+            assert_eq!(chip.rows(self.values.len()), 3);
+
+            let (values, expected) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let values = self
+                        .values
+                        .iter()
+                        .enumerate()
+                        .map(|(row, value)| {
+                            region
+                                .assign_advice(|| "x", config.values[0], row, || witness(*value))
+                                .map(|cell| AssignedCell::new(cell, *value))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let expected = region.assign_advice(
+                        || "expected",
+                        config.acc,
+                        0,
+                        || witness(self.expected),
+                    )?;
+                    Ok((values, expected))
+                },
+            )?;
+
+            let coefficients: Vec<_> = (1..=5).map(|c| Fp::from(c as u64)).collect();
+            let result = chip.linear_combination(
+                layouter.namespace(|| "dot product"),
+                &coefficients,
+                &values,
+            )?;
+
+            // The slices must have the same length.
+            let mismatched = chip.linear_combination(
+                layouter.namespace(|| "mismatched"),
+                &coefficients[..1],
+                &values,
+            );
+            assert!(matches!(mismatched, Err(Error::SynthesisError)));
+
+            layouter.assign_region(
+                || "check result",
+                |mut region| region.constrain_equal(&config.perm, result.cell(), expected),
+            )
+        }
+    }
+
+    #[test]
+    fn linear_combination() {
+        let circuit = |expected: u64| DotCircuit {
+            values: (1..=5).map(|x| Some(Fp::from(x as u64))).collect(),
+            expected: Some(Fp::from(expected)),
+        };
+
+        // 1 + 4 + 9 + 16 + 25
+        let prover = MockProver::run(4, &circuit(55), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(4, &circuit(54), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
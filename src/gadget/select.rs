@@ -0,0 +1,359 @@
+//! Conditional selection between assigned values.
+//!
+//! A circuit cannot skip the assignment of a branch depending on a witness value,
+//! because the layout of the circuit must be the same for every witness. Instead,
+//! [`SelectChip::branch`] assigns both branches of a computation, and constrains its
+//! output to equal the output of the branch chosen by a boolean condition cell.
+
+use std::marker::PhantomData;
+
+use super::{witness, AssignedCell, Instructions, Var};
+use crate::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, NamespacedLayouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Permutation, Selector},
+    poly::Rotation,
+};
+
+/// One of two values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Either<L, R> {
+    /// The value chosen when the condition is true.
+    Left(L),
+    /// The value chosen when the condition is false.
+    Right(R),
+}
+
+impl<T> Either<T, T> {
+    /// Returns the value, whichever side it is on.
+    pub fn into_inner(self) -> T {
+        match self {
+            Either::Left(value) | Either::Right(value) => value,
+        }
+    }
+}
+
+/// The instructions implemented by [`SelectChip`].
+pub trait SelectInstructions<F: FieldExt>: Instructions<F> {
+    /// Returns `when_true` if `condition` is 1, and `when_false` if it is 0.
+    ///
+    /// `condition` is constrained to be boolean.
+    fn select(
+        &self,
+        layouter: impl Layouter<F>,
+        condition: &Self::Var,
+        when_true: &Self::Var,
+        when_false: &Self::Var,
+    ) -> Result<Self::Var, Error>;
+}
+
+/// The outputs of both branches of a computation, and the output chosen between them.
+#[derive(Clone, Copy, Debug)]
+pub struct Branches<F: FieldExt> {
+    /// The output chosen by the condition.
+    pub output: AssignedCell<F>,
+    /// The output of the branch taken when the condition is true.
+    pub when_true: AssignedCell<F>,
+    /// The output of the branch taken when the condition is false.
+    pub when_false: AssignedCell<F>,
+    condition: Option<F>,
+}
+
+impl<F: FieldExt> Branches<F> {
+    /// Returns the output of the branch that was taken, or `None` if the condition is
+    /// not known.
+    pub fn taken(&self) -> Option<Either<&AssignedCell<F>, &AssignedCell<F>>> {
+        self.condition.map(|condition| {
+            if condition == F::one() {
+                Either::Left(&self.when_true)
+            } else {
+                Either::Right(&self.when_false)
+            }
+        })
+    }
+}
+
+/// The configuration of a [`SelectChip`].
+#[derive(Clone, Debug)]
+pub struct SelectConfig {
+    /// The advice columns holding `(condition, when_true, when_false, output)`.
+    pub advice: [Column<Advice>; 4],
+    /// Enables the selection gate.
+    pub s_select: Selector,
+    /// The permutation used to copy values into the chip's regions.
+    pub perm: Permutation,
+}
+
+/// A chip that selects between two values using a boolean condition.
+#[derive(Debug)]
+pub struct SelectChip<F: FieldExt> {
+    config: SelectConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for SelectChip<F> {
+    type Config = SelectConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: FieldExt> Instructions<F> for SelectChip<F> {
+    type Var = AssignedCell<F>;
+}
+
+impl<F: FieldExt> SelectChip<F> {
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: SelectConfig) -> Self {
+        SelectChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configures the chip over the given advice columns.
+    ///
+    /// `perm` must contain the advice columns, along with the columns of any cells that
+    /// are passed to the chip.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        perm: Permutation,
+    ) -> SelectConfig {
+        let s_select = meta.selector();
+
+        meta.create_gate("select", |meta| {
+            let s = meta.query_selector(s_select);
+            let condition = meta.query_advice(advice[0], Rotation::cur());
+            let when_true = meta.query_advice(advice[1], Rotation::cur());
+            let when_false = meta.query_advice(advice[2], Rotation::cur());
+            let output = meta.query_advice(advice[3], Rotation::cur());
+
+            let one = Expression::Constant(F::one());
+            vec![
+                s.clone() * condition.clone() * (one - condition.clone()),
+                s * (output - when_false.clone() - condition * (when_true - when_false)),
+            ]
+        });
+
+        SelectConfig {
+            advice,
+            s_select,
+            perm,
+        }
+    }
+
+    /// Assigns both branches of a computation, and returns their outputs along with the
+    /// output of the branch chosen by `condition`.
+    ///
+    /// Both `when_true` and `when_false` are always called, each with its own
+    /// namespace, regardless of the value of `condition`.
+    pub fn branch<L, T, E>(
+        &self,
+        mut layouter: L,
+        condition: &AssignedCell<F>,
+        when_true: T,
+        when_false: E,
+    ) -> Result<Branches<F>, Error>
+    where
+        L: Layouter<F>,
+        T: FnOnce(NamespacedLayouter<'_, F, L::Root>) -> Result<AssignedCell<F>, Error>,
+        E: FnOnce(NamespacedLayouter<'_, F, L::Root>) -> Result<AssignedCell<F>, Error>,
+    {
+        let true_output = when_true(layouter.namespace(|| "when true"))?;
+        let false_output = when_false(layouter.namespace(|| "when false"))?;
+        let output = self.select(
+            layouter.namespace(|| "select"),
+            condition,
+            &true_output,
+            &false_output,
+        )?;
+
+        Ok(Branches {
+            output,
+            when_true: true_output,
+            when_false: false_output,
+            condition: condition.value(),
+        })
+    }
+}
+
+impl<F: FieldExt> SelectInstructions<F> for SelectChip<F> {
+    fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        condition: &Self::Var,
+        when_true: &Self::Var,
+        when_false: &Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                config.s_select.enable(&mut region, 0)?;
+
+                let inputs = [condition, when_true, when_false];
+                for (input, column) in inputs.iter().zip(config.advice.iter()) {
+                    input.copy_advice(|| "input", &mut region, &config.perm, *column, 0)?;
+                }
+
+                let value = condition.value().and_then(|condition| {
+                    if condition == F::one() {
+                        when_true.value()
+                    } else {
+                        when_false.value()
+                    }
+                });
+                let cell =
+                    region.assign_advice(|| "output", config.advice[3], 0, || witness(value))?;
+                Ok(AssignedCell::new(cell, value))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{Either, SelectChip, SelectConfig};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        gadget::{witness, AssignedCell, Var},
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error},
+    };
+
+    /// Computes `x + 1` or `x * 2`, depending on a condition.
+    #[derive(Default)]
+    struct BranchCircuit {
+        condition: Option<Fp>,
+        x: Option<Fp>,
+        expected: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for BranchCircuit {
+        type Config = SelectConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SelectConfig {
+            let advice = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let perm = meta.permutation(
+                &advice
+                    .iter()
+                    .map(|column| (*column).into())
+                    .collect::<Vec<Column<Any>>>(),
+            );
+            SelectChip::configure(meta, advice, perm)
+        }
+
+        fn synthesize(
+            &self,
+            config: SelectConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = SelectChip::construct(config.clone());
+
+            let mut assign = |name: &'static str, column: Column<Advice>, value: Option<Fp>| {
+                layouter.assign_region(
+                    || name,
+                    |mut region| {
+                        let cell = region.assign_advice(|| name, column, 0, || witness(value))?;
+                        Ok(AssignedCell::new(cell, value))
+                    },
+                )
+            };
+            let condition = assign("condition", config.advice[0], self.condition)?;
+            let x = assign("x", config.advice[1], self.x)?;
+            let expected = assign("expected", config.advice[3], self.expected)?;
+
+            let branches = chip.branch(
+                layouter.namespace(|| "branch"),
+                &condition,
+                |mut layouter| {
+                    let value = x.value().map(|x| x + Fp::from(1u64));
+                    layouter.assign_region(
+                        || "x + 1",
+                        |mut region| {
+                            let cell = region.assign_advice(
+                                || "x + 1",
+                                config.advice[1],
+                                0,
+                                || witness(value),
+                            )?;
+                            Ok(AssignedCell::new(cell, value))
+                        },
+                    )
+                },
+                |mut layouter| {
+                    let value = x.value().map(|x| x + x);
+                    layouter.assign_region(
+                        || "x * 2",
+                        |mut region| {
+                            let cell = region.assign_advice(
+                                || "x * 2",
+                                config.advice[2],
+                                0,
+                                || witness(value),
+                            )?;
+                            Ok(AssignedCell::new(cell, value))
+                        },
+                    )
+                },
+            )?;
+
+            if let Some(taken) = branches.taken() {
+                assert_eq!(taken.into_inner().value(), branches.output.value());
+                assert_eq!(
+                    matches!(taken, Either::Left(_)),
+                    self.condition == Some(Fp::from(1u64))
+                );
+            }
+
+            layouter.assign_region(
+                || "check output",
+                |mut region| {
+                    region.constrain_equal(&config.perm, branches.output.cell(), expected.cell())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn select() {
+        let circuit = |condition: u64, expected: u64| BranchCircuit {
+            condition: Some(Fp::from(condition)),
+            x: Some(Fp::from(5u64)),
+            expected: Some(Fp::from(expected)),
+        };
+
+        for (condition, expected) in [(1, 6), (0, 10)].iter() {
+            let prover = MockProver::run(4, &circuit(*condition, *expected), vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        // The output must be the chosen branch.
+        let prover = MockProver::run(4, &circuit(1, 10), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The condition must be boolean.
+        let prover = MockProver::run(4, &circuit(2, 6), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
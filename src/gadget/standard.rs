@@ -0,0 +1,424 @@
+//! A chip implementing the standard PLONK gate.
+//!
+//! [`StandardPlonkChip`] lays out each instruction in a single row of three advice
+//! columns `a`, `b` and `c`, constrained by the gate
+//!
+//! ```text
+//! sa * a + sb * b + sm * a * b + sconst - sc * c = 0
+//! ```
+//!
+//! where `sa`, `sb`, `sm`, `sconst` and `sc` are fixed columns. Variables are passed
+//! between instructions with copy constraints over the advice columns.
+
+use std::marker::PhantomData;
+
+use super::{witness, AssignedCell, Instructions, Var};
+use crate::{
+    arithmetic::FieldExt,
+    circuit::{Chip, Layouter, Region},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Instance, Permutation, Selector},
+    poly::Rotation,
+};
+
+/// The instructions implemented by [`StandardPlonkChip`].
+pub trait StandardInstructions<F: FieldExt>: Instructions<F> {
+    /// Loads a private value into the circuit.
+    fn load_private(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Option<F>,
+    ) -> Result<Self::Var, Error>;
+
+    /// Loads a constant value into the circuit. The constant is fixed by the circuit,
+    /// and so is part of the verifying key.
+    fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Var, Error>;
+
+    /// Returns `a + b`.
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error>;
+
+    /// Returns `a * b`.
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error>;
+
+    /// Constrains `a` and `b` to be equal.
+    fn copy(&self, layouter: impl Layouter<F>, a: &Self::Var, b: &Self::Var) -> Result<(), Error>;
+
+    /// Exposes `var` as a public input.
+    fn expose_public(&self, layouter: impl Layouter<F>, var: &Self::Var) -> Result<(), Error>;
+}
+
+/// The configuration of a [`StandardPlonkChip`].
+#[derive(Clone, Debug)]
+pub struct StandardPlonkConfig {
+    /// The advice columns of the standard gate.
+    pub advice: [Column<Advice>; 3],
+    /// The coefficient of `a`.
+    pub sa: Column<Fixed>,
+    /// The coefficient of `b`.
+    pub sb: Column<Fixed>,
+    /// The coefficient of `c`, which is subtracted.
+    pub sc: Column<Fixed>,
+    /// The coefficient of `a * b`.
+    pub sm: Column<Fixed>,
+    /// The constant term.
+    pub sconst: Column<Fixed>,
+    /// Enables the public-input gate, which constrains `a` to equal the instance
+    /// column in the same row.
+    pub s_pub: Selector,
+    /// The instance column holding the public inputs.
+    pub instance: Column<Instance>,
+    /// The permutation over the advice columns.
+    pub perm: Permutation,
+}
+
+/// A chip implementing the standard PLONK gate, over three advice columns.
+///
+/// Public inputs are exposed in the row of the instance column in which the floor
+/// planner places the corresponding region, as in the rest of this crate's examples.
+#[derive(Debug)]
+pub struct StandardPlonkChip<F: FieldExt> {
+    config: StandardPlonkConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Chip<F> for StandardPlonkChip<F> {
+    type Config = StandardPlonkConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// The coefficients of the standard gate in a single row.
+#[derive(Clone, Copy)]
+struct Coefficients<F> {
+    sa: F,
+    sb: F,
+    sc: F,
+    sm: F,
+    sconst: F,
+}
+
+impl<F: FieldExt> Default for Coefficients<F> {
+    fn default() -> Self {
+        Coefficients {
+            sa: F::zero(),
+            sb: F::zero(),
+            sc: F::zero(),
+            sm: F::zero(),
+            sconst: F::zero(),
+        }
+    }
+}
+
+impl<F: FieldExt> StandardPlonkChip<F> {
+    /// Constructs a chip from its configuration.
+    pub fn construct(config: StandardPlonkConfig) -> Self {
+        StandardPlonkChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Configures the chip over the given advice and instance columns, allocating its
+    /// fixed columns and permutation.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        instance: Column<Instance>,
+    ) -> StandardPlonkConfig {
+        let perm = meta.permutation(&[advice[0].into(), advice[1].into(), advice[2].into()]);
+
+        let sa = meta.fixed_column();
+        let sb = meta.fixed_column();
+        let sc = meta.fixed_column();
+        let sm = meta.fixed_column();
+        let sconst = meta.fixed_column();
+        let s_pub = meta.selector();
+
+        meta.create_gate("standard", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let b = meta.query_advice(advice[1], Rotation::cur());
+            let c = meta.query_advice(advice[2], Rotation::cur());
+
+            let sa = meta.query_fixed(sa, Rotation::cur());
+            let sb = meta.query_fixed(sb, Rotation::cur());
+            let sc = meta.query_fixed(sc, Rotation::cur());
+            let sm = meta.query_fixed(sm, Rotation::cur());
+            let sconst = meta.query_fixed(sconst, Rotation::cur());
+
+            vec![a.clone() * sa + b.clone() * sb + a * b * sm + sconst - c * sc]
+        });
+
+        meta.create_gate("public input", |meta| {
+            let a = meta.query_advice(advice[0], Rotation::cur());
+            let p = meta.query_instance(instance, Rotation::cur());
+            let s_pub = meta.query_selector(s_pub);
+
+            vec![s_pub * (a - p)]
+        });
+
+        StandardPlonkConfig {
+            advice,
+            sa,
+            sb,
+            sc,
+            sm,
+            sconst,
+            s_pub,
+            instance,
+            perm,
+        }
+    }
+
+    /// Assigns the coefficients of the standard gate at the given offset.
+    fn assign_coefficients(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        coeffs: Coefficients<F>,
+    ) -> Result<(), Error> {
+        let config = self.config();
+        region.assign_fixed(|| "sa", config.sa, offset, || Ok(coeffs.sa))?;
+        region.assign_fixed(|| "sb", config.sb, offset, || Ok(coeffs.sb))?;
+        region.assign_fixed(|| "sc", config.sc, offset, || Ok(coeffs.sc))?;
+        region.assign_fixed(|| "sm", config.sm, offset, || Ok(coeffs.sm))?;
+        region.assign_fixed(|| "sconst", config.sconst, offset, || Ok(coeffs.sconst))?;
+        Ok(())
+    }
+
+    /// Assigns `a op b` to `c`, given the coefficients of `op`.
+    fn binary_op(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &'static str,
+        a: &AssignedCell<F>,
+        b: &AssignedCell<F>,
+        value: Option<F>,
+        coeffs: Coefficients<F>,
+    ) -> Result<AssignedCell<F>, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                a.copy_advice(|| "lhs", &mut region, &config.perm, config.advice[0], 0)?;
+                b.copy_advice(|| "rhs", &mut region, &config.perm, config.advice[1], 0)?;
+                let cell = region.assign_advice(|| name, config.advice[2], 0, || witness(value))?;
+                self.assign_coefficients(&mut region, 0, coeffs)?;
+                Ok(AssignedCell::new(cell, value))
+            },
+        )
+    }
+}
+
+impl<F: FieldExt> Instructions<F> for StandardPlonkChip<F> {
+    type Var = AssignedCell<F>;
+}
+
+impl<F: FieldExt> StandardInstructions<F> for StandardPlonkChip<F> {
+    fn load_private(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Option<F>,
+    ) -> Result<Self::Var, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load private",
+            |mut region| {
+                let cell = region.assign_advice(
+                    || "private input",
+                    config.advice[0],
+                    0,
+                    || witness(value),
+                )?;
+                Ok(AssignedCell::new(cell, value))
+            },
+        )
+    }
+
+    fn load_constant(
+        &self,
+        mut layouter: impl Layouter<F>,
+        constant: F,
+    ) -> Result<Self::Var, Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "load constant",
+            |mut region| {
+                let cell =
+                    region.assign_advice(|| "constant", config.advice[0], 0, || Ok(constant))?;
+                // a - constant = 0
+                self.assign_coefficients(
+                    &mut region,
+                    0,
+                    Coefficients {
+                        sa: F::one(),
+                        sconst: -constant,
+                        ..Default::default()
+                    },
+                )?;
+                Ok(AssignedCell::new(cell, Some(constant)))
+            },
+        )
+    }
+
+    fn add(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let value = a.value().and_then(|a| b.value().map(|b| a + b));
+        self.binary_op(
+            layouter,
+            "add",
+            a,
+            b,
+            value,
+            Coefficients {
+                sa: F::one(),
+                sb: F::one(),
+                sc: F::one(),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn mul(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let value = a.value().and_then(|a| b.value().map(|b| a * b));
+        self.binary_op(
+            layouter,
+            "mul",
+            a,
+            b,
+            value,
+            Coefficients {
+                sm: F::one(),
+                sc: F::one(),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn copy(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Self::Var,
+        b: &Self::Var,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "copy",
+            |mut region| region.constrain_equal(&config.perm, a.cell(), b.cell()),
+        )
+    }
+
+    fn expose_public(&self, mut layouter: impl Layouter<F>, var: &Self::Var) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "expose public",
+            |mut region| {
+                config.s_pub.enable(&mut region, 0)?;
+                var.copy_advice(|| "public", &mut region, &config.perm, config.advice[0], 0)?;
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{StandardInstructions, StandardPlonkChip, StandardPlonkConfig};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+
+    /// Proves knowledge of `x` such that `x^2 + x + 5` equals the public input.
+    #[derive(Default)]
+    struct PolyCircuit {
+        x: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for PolyCircuit {
+        type Config = StandardPlonkConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> StandardPlonkConfig {
+            let advice = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let instance = meta.instance_column();
+            StandardPlonkChip::configure(meta, advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: StandardPlonkConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = StandardPlonkChip::construct(config);
+
+            let x = chip.load_private(layouter.namespace(|| "x"), self.x)?;
+            let x2 = chip.mul(layouter.namespace(|| "x^2"), &x, &x)?;
+            let sum = chip.add(layouter.namespace(|| "x^2 + x"), &x2, &x)?;
+            let five = chip.load_constant(layouter.namespace(|| "5"), Fp::from(5u64))?;
+            let result = chip.add(layouter.namespace(|| "x^2 + x + 5"), &sum, &five)?;
+            chip.expose_public(layouter.namespace(|| "expose result"), &result)?;
+
+            let x_again = chip.load_private(layouter.namespace(|| "x again"), self.x)?;
+            chip.copy(layouter.namespace(|| "x == x again"), &x, &x_again)
+        }
+    }
+
+    #[test]
+    fn standard_plonk_chip() {
+        let k = 4;
+        let circuit = PolyCircuit {
+            x: Some(Fp::from(3u64)),
+        };
+
+        // Each instruction uses one row, so the result is exposed in row 5.
+        let mut instance = vec![Fp::from(0u64); 1 << k];
+        instance[5] = Fp::from(17u64);
+        let prover = MockProver::run(k, &circuit, vec![instance.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        instance[5] = Fp::from(18u64);
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
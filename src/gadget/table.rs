@@ -0,0 +1,200 @@
+//! Utilities for loading lookup tables into fixed columns.
+
+use std::collections::HashSet;
+
+use crate::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{Column, ConstraintSystem, Error, Fixed},
+};
+
+pub mod bytes;
+
+/// A fixed column that holds (one column of) a lookup table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TableColumn(Column<Fixed>);
+
+impl TableColumn {
+    /// Allocates a new fixed column for a lookup table.
+    pub fn new<F: FieldExt>(meta: &mut ConstraintSystem<F>) -> Self {
+        TableColumn(meta.fixed_column())
+    }
+
+    /// Returns the fixed column holding this table column, to be queried in lookups.
+    pub fn column(&self) -> Column<Fixed> {
+        self.0
+    }
+}
+
+impl From<Column<Fixed>> for TableColumn {
+    fn from(column: Column<Fixed>) -> Self {
+        TableColumn(column)
+    }
+}
+
+/// A row of a lookup table.
+///
+/// This is implemented for tuples of up to four field elements (so the rows of a
+/// single-column table are `(value,)`), and for vectors of field elements.
+pub trait TableRow<F: FieldExt> {
+    /// Returns the values of this row, one per table column.
+    fn into_row(self) -> Vec<F>;
+}
+
+impl<F: FieldExt> TableRow<F> for (F,) {
+    fn into_row(self) -> Vec<F> {
+        vec![self.0]
+    }
+}
+
+impl<F: FieldExt> TableRow<F> for (F, F) {
+    fn into_row(self) -> Vec<F> {
+        vec![self.0, self.1]
+    }
+}
+
+impl<F: FieldExt> TableRow<F> for (F, F, F) {
+    fn into_row(self) -> Vec<F> {
+        vec![self.0, self.1, self.2]
+    }
+}
+
+impl<F: FieldExt> TableRow<F> for (F, F, F, F) {
+    fn into_row(self) -> Vec<F> {
+        vec![self.0, self.1, self.2, self.3]
+    }
+}
+
+impl<F: FieldExt> TableRow<F> for Vec<F> {
+    fn into_row(self) -> Vec<F> {
+        self
+    }
+}
+
+/// Builds a lookup table out of rows of field elements, and assigns it to a set of
+/// [`TableColumn`]s in a single region.
+///
+/// ```ignore
+/// TableBuilder::new(&[config.value, config.square])
+///     .rows((0..16).map(|i| (F::from(i), F::from(i * i))))
+///     .assign(layouter.namespace(|| "squares"))?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct TableBuilder<F: FieldExt> {
+    columns: Vec<TableColumn>,
+    rows: Vec<Vec<F>>,
+    padding: Option<(usize, Vec<F>)>,
+}
+
+impl<F: FieldExt> TableBuilder<F> {
+    /// Creates an empty table over the given columns.
+    pub fn new(columns: &[TableColumn]) -> Self {
+        TableBuilder {
+            columns: columns.to_vec(),
+            rows: vec![],
+            padding: None,
+        }
+    }
+
+    /// Appends the given rows to the table.
+    pub fn rows<R: TableRow<F>>(mut self, rows: impl IntoIterator<Item = R>) -> Self {
+        self.rows.extend(rows.into_iter().map(|row| row.into_row()));
+        self
+    }
+
+    /// Pads the table with copies of `row` until it has `len` rows.
+    ///
+    /// The padding row must be a valid row of the table, since lookups will accept it
+    /// like any other row. It is often the all-zeroes row, which is the value of the
+    /// lookup inputs in rows where the lookup is not enabled.
+    pub fn pad_to<R: TableRow<F>>(mut self, len: usize, row: R) -> Self {
+        self.padding = Some((len, row.into_row()));
+        self
+    }
+
+    /// Returns the rows of the table: the given rows in order with duplicates removed,
+    /// followed by any padding.
+    ///
+    /// Returns [`Error::SynthesisError`] if a row does not have one value per table
+    /// column, or if the deduplicated table already has more rows than it is padded to.
+    pub fn build(&self) -> Result<Vec<Vec<F>>, Error> {
+        let width = self.columns.len();
+        let mut seen = HashSet::new();
+        let mut rows = vec![];
+        for row in &self.rows {
+            if row.len() != width {
+                return Err(Error::SynthesisError);
+            }
+            if seen.insert(row.iter().map(|value| value.to_bytes()).collect::<Vec<_>>()) {
+                rows.push(row.clone());
+            }
+        }
+
+        if let Some((len, padding)) = &self.padding {
+            if padding.len() != width || rows.len() > *len {
+                return Err(Error::SynthesisError);
+            }
+            rows.resize(*len, padding.clone());
+        }
+
+        Ok(rows)
+    }
+
+    /// Assigns the table to its columns, starting at the first row of a new region, and
+    /// returns the number of rows that were assigned.
+    pub fn assign(&self, mut layouter: impl Layouter<F>) -> Result<usize, Error> {
+        let rows = self.build()?;
+
+        layouter.assign_region(
+            || "table",
+            |mut region| {
+                for (index, column) in self.columns.iter().enumerate() {
+                    let values: Vec<_> = rows.iter().map(|row| row[index]).collect();
+                    region.assign_fixed_from_slice(|| "table", column.0, 0, &values)?;
+                }
+                Ok(())
+            },
+        )?;
+
+        Ok(rows.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{TableBuilder, TableColumn};
+    use crate::plonk::{ConstraintSystem, Error};
+
+    #[test]
+    fn build() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let columns = [TableColumn::new(&mut cs), TableColumn::new(&mut cs)];
+        let row = |a: u64, b: u64| (Fp::from(a), Fp::from(b));
+
+        // Duplicate rows are removed, keeping the first occurrence.
+        let table = TableBuilder::new(&columns)
+            .rows(vec![row(1, 2), row(3, 4), row(1, 2)])
+            .pad_to(4, row(0, 0));
+        assert_eq!(
+            table.build().unwrap(),
+            vec![
+                vec![Fp::from(1u64), Fp::from(2u64)],
+                vec![Fp::from(3u64), Fp::from(4u64)],
+                vec![Fp::from(0u64), Fp::from(0u64)],
+                vec![Fp::from(0u64), Fp::from(0u64)],
+            ]
+        );
+
+        // Rows must match the number of columns.
+        let table = TableBuilder::new(&columns).rows(vec![(Fp::from(1u64),)]);
+        assert!(matches!(table.build(), Err(Error::SynthesisError)));
+
+        // The table must fit within its padded length.
+        let table = TableBuilder::new(&columns)
+            .rows(vec![row(1, 2), row(3, 4)])
+            .pad_to(1, row(0, 0));
+        assert!(matches!(table.build(), Err(Error::SynthesisError)));
+    }
+}
@@ -0,0 +1,297 @@
+//! Lookup tables of common operations on bytes.
+//!
+//! These tables are used by most hash-function and VM circuits. The rows of each table
+//! are generated once per thread and shared between circuits, and [`ByteTableLoader`]
+//! assigns each table at most once per synthesis, on its first use.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::{TableBuilder, TableColumn};
+use crate::{
+    arithmetic::FieldExt,
+    circuit::Layouter,
+    plonk::{ConstraintSystem, Error},
+};
+
+/// An operation for which a lookup table can be generated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByteOp {
+    /// Rows `(a, b, a ^ b)` for all bytes `a` and `b`.
+    Xor,
+    /// Rows `(a, b, a & b)` for all bytes `a` and `b`.
+    And,
+    /// Rows `(a, b, a | b)` for all bytes `a` and `b`.
+    Or,
+    /// Rows `(a, s, (a << s) & 0xff)` for all bytes `a` and shifts `s` in `0..8`.
+    Shl,
+    /// Rows `(a, s, a >> s)` for all bytes `a` and shifts `s` in `0..8`.
+    Shr,
+    /// Rows `(a,)` for all `a` in `0..2^8`.
+    Range8,
+    /// Rows `(a,)` for all `a` in `0..2^16`.
+    Range16,
+}
+
+thread_local! {
+    static ROWS: RefCell<HashMap<ByteOp, Rc<Vec<[u64; 3]>>>> = RefCell::new(HashMap::new());
+}
+
+impl ByteOp {
+    /// Returns the number of columns of the table.
+    pub fn width(&self) -> usize {
+        match self {
+            ByteOp::Range8 | ByteOp::Range16 => 1,
+            _ => 3,
+        }
+    }
+
+    /// Returns the number of rows of the table.
+    pub fn num_rows(&self) -> usize {
+        match self {
+            ByteOp::Xor | ByteOp::And | ByteOp::Or | ByteOp::Range16 => 1 << 16,
+            ByteOp::Shl | ByteOp::Shr => 1 << 11,
+            ByteOp::Range8 => 1 << 8,
+        }
+    }
+
+    /// Returns the rows of the table, padded with zeroes to three values each.
+    ///
+    /// The rows are generated on the first call for each operation, and shared with
+    /// later calls on the same thread.
+    pub fn rows(&self) -> Rc<Vec<[u64; 3]>> {
+        ROWS.with(|rows| {
+            rows.borrow_mut()
+                .entry(*self)
+                .or_insert_with(|| Rc::new(self.generate()))
+                .clone()
+        })
+    }
+
+    fn generate(&self) -> Vec<[u64; 3]> {
+        let bytes = || (0..256u64).flat_map(|a| (0..256u64).map(move |b| (a, b)));
+        let shifts = || (0..256u64).flat_map(|a| (0..8u64).map(move |s| (a, s)));
+        match self {
+            ByteOp::Xor => bytes().map(|(a, b)| [a, b, a ^ b]).collect(),
+            ByteOp::And => bytes().map(|(a, b)| [a, b, a & b]).collect(),
+            ByteOp::Or => bytes().map(|(a, b)| [a, b, a | b]).collect(),
+            ByteOp::Shl => shifts().map(|(a, s)| [a, s, (a << s) & 0xff]).collect(),
+            ByteOp::Shr => shifts().map(|(a, s)| [a, s, a >> s]).collect(),
+            ByteOp::Range8 => (0..1 << 8).map(|a| [a, 0, 0]).collect(),
+            ByteOp::Range16 => (0..1 << 16).map(|a| [a, 0, 0]).collect(),
+        }
+    }
+
+    /// Returns a [`TableBuilder`] that assigns the table to the given columns.
+    pub fn builder<F: FieldExt>(&self, columns: &[TableColumn]) -> TableBuilder<F> {
+        let width = self.width();
+        TableBuilder::new(columns).rows(self.rows().iter().map(|row| {
+            row[..width]
+                .iter()
+                .map(|&v| F::from_u64(v))
+                .collect::<Vec<_>>()
+        }))
+    }
+}
+
+/// The configuration of a lookup table for a [`ByteOp`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ByteTableConfig {
+    op: ByteOp,
+    columns: Vec<TableColumn>,
+}
+
+impl ByteTableConfig {
+    /// Allocates the table columns for the given operation.
+    pub fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, op: ByteOp) -> Self {
+        ByteTableConfig {
+            op,
+            columns: (0..op.width()).map(|_| TableColumn::new(meta)).collect(),
+        }
+    }
+
+    /// Returns the operation of this table.
+    pub fn op(&self) -> ByteOp {
+        self.op
+    }
+
+    /// Returns the table columns, to be queried in lookups.
+    pub fn columns(&self) -> &[TableColumn] {
+        &self.columns
+    }
+}
+
+/// Assigns byte tables on their first use within a synthesis.
+///
+/// Chips that share a [`ByteTableConfig`] can each call
+/// [`ByteTableLoader::ensure_loaded`] before using the table, and the table will be
+/// assigned only once. A new loader should be created for each call to
+/// [`Circuit::synthesize`].
+///
+/// [`Circuit::synthesize`]: crate::plonk::Circuit::synthesize
+#[derive(Debug, Default)]
+pub struct ByteTableLoader {
+    loaded: Vec<ByteTableConfig>,
+}
+
+impl ByteTableLoader {
+    /// Creates a loader for which no tables have been assigned.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns the table if it has not been assigned by this loader, and returns
+    /// whether it was assigned by this call.
+    pub fn ensure_loaded<F: FieldExt>(
+        &mut self,
+        table: &ByteTableConfig,
+        layouter: impl Layouter<F>,
+    ) -> Result<bool, Error> {
+        if self.loaded.contains(table) {
+            return Ok(false);
+        }
+
+        table.op.builder(&table.columns).assign(layouter)?;
+        self.loaded.push(table.clone());
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{ByteOp, ByteTableConfig, ByteTableLoader};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    #[test]
+    fn rows() {
+        for op in [
+            ByteOp::Xor,
+            ByteOp::And,
+            ByteOp::Or,
+            ByteOp::Shl,
+            ByteOp::Shr,
+            ByteOp::Range8,
+            ByteOp::Range16,
+        ]
+        .iter()
+        {
+            assert_eq!(op.rows().len(), op.num_rows());
+        }
+
+        assert_eq!(ByteOp::Xor.rows()[0x0f0a], [0x0f, 0x0a, 0x05]);
+        assert_eq!(ByteOp::And.rows()[0x0f0a], [0x0f, 0x0a, 0x0a]);
+        assert_eq!(ByteOp::Or.rows()[0x0f0a], [0x0f, 0x0a, 0x0f]);
+        assert_eq!(ByteOp::Shl.rows()[0x81 * 8 + 1], [0x81, 1, 0x02]);
+        assert_eq!(ByteOp::Shr.rows()[0x81 * 8 + 1], [0x81, 1, 0x40]);
+
+        // The rows are only generated once.
+        assert!(std::rc::Rc::ptr_eq(
+            &ByteOp::Xor.rows(),
+            &ByteOp::Xor.rows()
+        ));
+    }
+
+    #[derive(Clone)]
+    struct ShrConfig {
+        a: Column<Advice>,
+        s: Column<Advice>,
+        out: Column<Advice>,
+        q: Selector,
+        table: ByteTableConfig,
+    }
+
+    /// Looks up a right shift, loading the table from two chips.
+    #[derive(Default)]
+    struct ShrCircuit {
+        values: Option<(u64, u64, u64)>,
+    }
+
+    impl Circuit<Fp> for ShrCircuit {
+        type Config = ShrConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> ShrConfig {
+            let a = meta.advice_column();
+            let s = meta.advice_column();
+            let out = meta.advice_column();
+            let q = meta.selector();
+            let table = ByteTableConfig::configure(meta, ByteOp::Shr);
+
+            meta.lookup(|meta| {
+                let q = meta.query_selector(q);
+                [a, s, out]
+                    .iter()
+                    .zip(table.columns())
+                    .map(|(advice, column)| {
+                        (
+                            q.clone() * meta.query_advice(*advice, Rotation::cur()),
+                            meta.query_fixed(column.column(), Rotation::cur()),
+                        )
+                    })
+                    .collect()
+            });
+
+            ShrConfig {
+                a,
+                s,
+                out,
+                q,
+                table,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: ShrConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let mut loader = ByteTableLoader::new();
+            assert!(loader.ensure_loaded(&config.table, layouter.namespace(|| "first"))?);
+            assert!(!loader.ensure_loaded(&config.table, layouter.namespace(|| "second"))?);
+
+            layouter.assign_region(
+                || "shr",
+                |mut region| {
+                    config.q.enable(&mut region, 0)?;
+                    let values = self.values;
+                    let columns = [config.a, config.s, config.out];
+                    for (i, column) in columns.iter().enumerate() {
+                        region.assign_advice(
+                            || "value",
+                            *column,
+                            0,
+                            || {
+                                let (a, s, out) = values.ok_or(Error::SynthesisError)?;
+                                Ok(Fp::from([a, s, out][i]))
+                            },
+                        )?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        let circuit = |a, s, out| ShrCircuit {
+            values: Some((a, s, out)),
+        };
+
+        let prover = MockProver::run(11, &circuit(0x81, 1, 0x40), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(11, &circuit(0x81, 1, 0x41), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
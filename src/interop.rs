@@ -0,0 +1,7 @@
+//! Adapters for circuits written for other proving systems.
+
+#[cfg(feature = "arkworks")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arkworks")))]
+pub mod arkworks;
+pub mod ir;
+pub mod r1cs;
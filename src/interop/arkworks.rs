@@ -0,0 +1,163 @@
+//! Conversions between this crate's fields and curves and those of arkworks.
+//!
+//! Gadgets and witness generators written against arkworks types can use these to
+//! pass their values to halo2 circuits, and to read values back out of them, without
+//! converting through bytes by hand. Fields are converted through their canonical
+//! little-endian encodings, and points through their affine coordinates; every
+//! conversion checks that the two types are over the same field, so that values are
+//! never silently reduced into a different field.
+
+use std::error;
+use std::fmt;
+
+use ark_ec::models::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use group::prime::PrimeCurveAffine;
+
+use crate::arithmetic::{Coordinates, CurveAffine, FieldExt};
+
+/// The length of the encoding of a [`FieldExt`] element.
+const SCALAR_LEN: usize = 32;
+
+/// The reasons why a value cannot be converted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArkError {
+    /// The two fields have different moduli.
+    ModulusMismatch,
+    /// The point is not on the curve that it is being converted to, or not in its
+    /// prime-order subgroup.
+    NotOnCurve,
+}
+
+impl fmt::Display for ArkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ModulusMismatch => write!(f, "the fields have different moduli"),
+            Self::NotOnCurve => write!(f, "the point is not on the target curve"),
+        }
+    }
+}
+
+impl error::Error for ArkError {}
+
+/// Strips the trailing zero bytes of a little-endian encoding.
+fn trim(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+/// Checks that `F` and `A` have the same modulus, by comparing the encodings of -1.
+fn check_modulus<F: FieldExt, A: PrimeField>() -> Result<(), ArkError> {
+    let ours = (-F::one()).to_bytes();
+    let theirs = (-A::one()).into_repr().to_bytes_le();
+    if trim(&ours) == trim(&theirs) {
+        Ok(())
+    } else {
+        Err(ArkError::ModulusMismatch)
+    }
+}
+
+/// Converts a field element into the arkworks field with the same modulus.
+pub fn field_to_ark<F: FieldExt, A: PrimeField>(value: F) -> Result<A, ArkError> {
+    check_modulus::<F, A>()?;
+    Ok(A::from_le_bytes_mod_order(&value.to_bytes()))
+}
+
+/// Converts an element of an arkworks field into the field with the same modulus.
+pub fn field_from_ark<F: FieldExt, A: PrimeField>(value: A) -> Result<F, ArkError> {
+    check_modulus::<F, A>()?;
+    let mut bytes = [0u8; SCALAR_LEN];
+    let repr = value.into_repr().to_bytes_le();
+    let repr = trim(&repr);
+    bytes[..repr.len()].copy_from_slice(repr);
+    Option::from(F::from_bytes(&bytes)).ok_or(ArkError::ModulusMismatch)
+}
+
+/// Converts a point into the arkworks short Weierstrass curve with the same base field.
+///
+/// Returns [`ArkError::NotOnCurve`] if the point does not lie in the prime-order
+/// subgroup of the arkworks curve, which is the case when the two curves have
+/// different equations.
+pub fn point_to_ark<C: CurveAffine, P: SWModelParameters>(
+    point: C,
+) -> Result<GroupAffine<P>, ArkError> {
+    let coordinates: Option<Coordinates<C>> = point.coordinates().into();
+    let point = match coordinates {
+        Some(coordinates) => GroupAffine::new(
+            field_to_ark(*coordinates.x())?,
+            field_to_ark(*coordinates.y())?,
+            false,
+        ),
+        None => {
+            check_modulus::<C::Base, P::BaseField>()?;
+            GroupAffine::zero()
+        }
+    };
+
+    if point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() {
+        Ok(point)
+    } else {
+        Err(ArkError::NotOnCurve)
+    }
+}
+
+/// Converts a point on an arkworks short Weierstrass curve into the curve with the
+/// same base field.
+///
+/// Returns [`ArkError::NotOnCurve`] if the point does not lie on the target curve.
+pub fn point_from_ark<C: CurveAffine, P: SWModelParameters>(
+    point: GroupAffine<P>,
+) -> Result<C, ArkError> {
+    if point.infinity {
+        check_modulus::<C::Base, P::BaseField>()?;
+        return Ok(C::identity());
+    }
+
+    let x = field_from_ark(point.x)?;
+    let y = field_from_ark(point.y)?;
+    Option::from(C::from_xy(x, y)).ok_or(ArkError::NotOnCurve)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ff::Zero;
+    use ff::Field;
+    use group::{prime::PrimeCurveAffine, Curve, Group};
+    use pasta_curves::{pallas, vesta, Fp};
+
+    use super::{field_from_ark, field_to_ark, point_from_ark, point_to_ark, ArkError};
+
+    #[test]
+    fn fields() {
+        let value = Fp::from(0x1234_5678u64).invert().unwrap();
+        let converted: ark_pallas::Fq = field_to_ark(value).unwrap();
+        assert_eq!(field_from_ark::<Fp, _>(converted), Ok(value));
+
+        // The scalar field of Pallas is the base field of Vesta.
+        assert_eq!(
+            field_to_ark::<Fp, ark_pallas::Fr>(value),
+            Err(ArkError::ModulusMismatch)
+        );
+    }
+
+    #[test]
+    fn points() {
+        let point = (pallas::Point::generator() * pallas::Scalar::from(5u64)).to_affine();
+        let converted: ark_pallas::Affine = point_to_ark(point).unwrap();
+        assert!(converted.is_on_curve());
+        assert_eq!(point_from_ark::<pallas::Affine, _>(converted), Ok(point));
+
+        let identity: ark_pallas::Affine = point_to_ark(pallas::Affine::identity()).unwrap();
+        assert!(identity.is_zero());
+        assert_eq!(
+            point_from_ark::<pallas::Affine, _>(identity),
+            Ok(pallas::Affine::identity())
+        );
+
+        // Vesta points are over a different base field.
+        assert_eq!(
+            point_to_ark::<_, ark_pallas::PallasParameters>(vesta::Affine::generator()),
+            Err(ArkError::ModulusMismatch)
+        );
+    }
+}
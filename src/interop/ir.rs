@@ -0,0 +1,770 @@
+//! Circuits described by data rather than by code.
+//!
+//! A [`CircuitIr`] lists the columns, gates, lookups and permutations of a circuit,
+//! along with the values of its fixed columns and the copy constraints between its
+//! cells. It can be written to and read from a buffer, so that a circuit produced by
+//! another tool (or by a different program) can be proven without writing a
+//! [`Circuit`] implementation for it. [`IrCircuit`] is that implementation: it
+//! configures and synthesizes the circuit described by an IR at runtime.
+//!
+//! The circuit is laid out as a single region starting at the first row, with every
+//! advice column assigned in each of the IR's rows.
+//!
+//! Because its configuration is data, an [`IrCircuit`] must be configured through
+//! [`Circuit::configure_from`]. Functions that configure a circuit from its type alone,
+//! such as [`CircuitShape::new`] and [`VerifyingKey::read`], see an empty circuit; use
+//! their counterparts that take the circuit, such as [`CircuitShape::from_circuit`]
+//! and [`VerifyingKey::read_with_circuit`], instead.
+//!
+//! [`CircuitShape::new`]: crate::plonk::CircuitShape::new
+//! [`CircuitShape::from_circuit`]: crate::plonk::CircuitShape::from_circuit
+//! [`VerifyingKey::read`]: crate::plonk::VerifyingKey::read
+//! [`VerifyingKey::read_with_circuit`]: crate::plonk::VerifyingKey::read_with_circuit
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::io;
+
+use crate::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Layouter, SimpleFloorPlanner},
+    format,
+    plonk::{
+        Advice, Any, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance,
+        Permutation,
+    },
+    poly::Rotation,
+};
+
+/// The length of an encoded field element.
+const SCALAR_LEN: usize = 32;
+
+/// The deepest nesting of expressions that [`CircuitIr::read`] accepts.
+const MAX_EXPRESSION_DEPTH: usize = 256;
+
+/// A column of a [`CircuitIr`], identified by its type and its index among the columns
+/// of that type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IrColumn {
+    /// The advice column with the given index.
+    Advice(usize),
+    /// The fixed column with the given index.
+    Fixed(usize),
+    /// The instance column with the given index.
+    Instance(usize),
+}
+
+/// A polynomial expression over the columns of a [`CircuitIr`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IrExpression<F> {
+    /// A constant.
+    Constant(F),
+    /// A column, queried at the given rotation from the current row.
+    Query {
+        /// The queried column.
+        column: IrColumn,
+        /// The rotation of the query.
+        rotation: i32,
+    },
+    /// The sum of two expressions.
+    Sum(Box<IrExpression<F>>, Box<IrExpression<F>>),
+    /// The product of two expressions.
+    Product(Box<IrExpression<F>>, Box<IrExpression<F>>),
+    /// An expression multiplied by a constant.
+    Scaled(Box<IrExpression<F>>, F),
+}
+
+/// A copy constraint between two cells of a [`CircuitIr`], each given as a column and
+/// a row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IrCopy {
+    /// The index of the permutation that contains both columns.
+    pub permutation: usize,
+    /// The first cell.
+    pub left: (IrColumn, usize),
+    /// The second cell.
+    pub right: (IrColumn, usize),
+}
+
+/// The reasons why a [`CircuitIr`] or a witness for it can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IrError {
+    /// An expression, permutation or copy constraint refers to a column that the
+    /// circuit does not have.
+    UnknownColumn(IrColumn),
+    /// The gate with the given index has no polynomials.
+    EmptyGate {
+        /// The index of the gate.
+        gate: usize,
+    },
+    /// The copy constraint with the given index is invalid: it refers to a
+    /// permutation that the circuit does not have, or to a row beyond the circuit's
+    /// rows.
+    InvalidCopy {
+        /// The index of the copy constraint.
+        copy: usize,
+    },
+    /// The values of the fixed columns do not match the circuit: there is not one
+    /// list of values per fixed column, or a list is longer than the circuit's rows.
+    FixedValues,
+    /// The witness does not have one value per row in each advice column.
+    WitnessShape,
+}
+
+impl fmt::Display for IrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownColumn(column) => write!(f, "unknown column {:?}", column),
+            Self::EmptyGate { gate } => write!(f, "gate {} has no polynomials", gate),
+            Self::InvalidCopy { copy } => write!(f, "copy constraint {} is invalid", copy),
+            Self::FixedValues => write!(f, "fixed values do not match the fixed columns"),
+            Self::WitnessShape => write!(f, "witness does not match the advice columns"),
+        }
+    }
+}
+
+impl error::Error for IrError {}
+
+/// A serializable description of a circuit.
+///
+/// See the [module documentation](self) for how it is laid out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitIr<F> {
+    /// The number of advice columns.
+    pub num_advice_columns: usize,
+    /// The number of fixed columns.
+    pub num_fixed_columns: usize,
+    /// The number of instance columns.
+    pub num_instance_columns: usize,
+    /// The number of rows that the circuit assigns.
+    pub rows: usize,
+    /// The polynomials of each gate, which must be zero on every row.
+    pub gates: Vec<Vec<IrExpression<F>>>,
+    /// The `(input, table)` expression pairs of each lookup argument.
+    pub lookups: Vec<Vec<(IrExpression<F>, IrExpression<F>)>>,
+    /// The columns of each permutation argument.
+    pub permutations: Vec<Vec<IrColumn>>,
+    /// The values of each fixed column, from the first row. Rows beyond the given
+    /// values are zero.
+    pub fixed: Vec<Vec<F>>,
+    /// The copy constraints between cells.
+    pub copies: Vec<IrCopy>,
+}
+
+impl<F: FieldExt> CircuitIr<F> {
+    /// Checks that this IR describes a well-formed circuit.
+    pub fn validate(&self) -> Result<(), IrError> {
+        let expressions = self.gates.iter().flatten().chain(
+            self.lookups
+                .iter()
+                .flatten()
+                .flat_map(|(input, table)| std::iter::once(input).chain(std::iter::once(table))),
+        );
+        for expression in expressions {
+            self.validate_expression(expression)?;
+        }
+        if let Some(gate) = self.gates.iter().position(|polys| polys.is_empty()) {
+            return Err(IrError::EmptyGate { gate });
+        }
+        for column in self.permutations.iter().flatten() {
+            self.validate_column(*column)?;
+        }
+
+        if self.fixed.len() != self.num_fixed_columns
+            || self.fixed.iter().any(|values| values.len() > self.rows)
+        {
+            return Err(IrError::FixedValues);
+        }
+
+        for (index, copy) in self.copies.iter().enumerate() {
+            if copy.permutation >= self.permutations.len()
+                || copy.left.1 >= self.rows
+                || copy.right.1 >= self.rows
+            {
+                return Err(IrError::InvalidCopy { copy: index });
+            }
+            self.validate_column(copy.left.0)?;
+            self.validate_column(copy.right.0)?;
+        }
+
+        Ok(())
+    }
+
+    fn validate_column(&self, column: IrColumn) -> Result<(), IrError> {
+        let (index, count) = match column {
+            IrColumn::Advice(index) => (index, self.num_advice_columns),
+            IrColumn::Fixed(index) => (index, self.num_fixed_columns),
+            IrColumn::Instance(index) => (index, self.num_instance_columns),
+        };
+        if index < count {
+            Ok(())
+        } else {
+            Err(IrError::UnknownColumn(column))
+        }
+    }
+
+    fn validate_expression(&self, expression: &IrExpression<F>) -> Result<(), IrError> {
+        match expression {
+            IrExpression::Constant(_) => Ok(()),
+            IrExpression::Query { column, .. } => self.validate_column(*column),
+            IrExpression::Sum(a, b) | IrExpression::Product(a, b) => {
+                self.validate_expression(a)?;
+                self.validate_expression(b)
+            }
+            IrExpression::Scaled(a, _) => self.validate_expression(a),
+        }
+    }
+
+    /// Writes this IR to a buffer.
+    ///
+    /// The encoding starts with a header (see the [`format`](mod@crate::format) module),
+    /// followed by the column counts, the number of rows, and each list of the IR
+    /// prefixed by its length. Lengths and indices are encoded as little-endian `u64`s,
+    /// rotations as little-endian `i32`s, and field elements in their canonical
+    /// encoding.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        format::write_header(writer, format::Kind::CircuitIr, F::modulus(), None)?;
+        write_len(writer, self.num_advice_columns)?;
+        write_len(writer, self.num_fixed_columns)?;
+        write_len(writer, self.num_instance_columns)?;
+        write_len(writer, self.rows)?;
+
+        write_list(writer, &self.gates, |writer, polys| {
+            write_list(writer, polys, write_expression)
+        })?;
+        write_list(writer, &self.lookups, |writer, lookup| {
+            write_list(writer, lookup, |writer, (input, table)| {
+                write_expression(writer, input)?;
+                write_expression(writer, table)
+            })
+        })?;
+        write_list(writer, &self.permutations, |writer, columns| {
+            write_list(writer, columns, |writer, column| {
+                write_column(writer, *column)
+            })
+        })?;
+        write_list(writer, &self.fixed, |writer, values| {
+            write_list(writer, values, |writer, value| {
+                writer.write_all(&value.to_bytes())
+            })
+        })?;
+        write_list(writer, &self.copies, |writer, copy| {
+            write_len(writer, copy.permutation)?;
+            for (column, row) in [copy.left, copy.right].iter().copied() {
+                write_column(writer, column)?;
+                write_len(writer, row)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Reads an IR from a buffer, and checks that it is
+    /// [well-formed](CircuitIr::validate).
+    pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        format::read_header(reader, format::Kind::CircuitIr, F::modulus(), None)?;
+        let num_advice_columns = read_len(reader)?;
+        let num_fixed_columns = read_len(reader)?;
+        let num_instance_columns = read_len(reader)?;
+        let rows = read_len(reader)?;
+
+        let gates = read_list(reader, |reader| {
+            read_list(reader, |reader| read_expression(reader, 0))
+        })?;
+        let lookups = read_list(reader, |reader| {
+            read_list(reader, |reader| {
+                Ok((read_expression(reader, 0)?, read_expression(reader, 0)?))
+            })
+        })?;
+        let permutations = read_list(reader, |reader| read_list(reader, read_column))?;
+        let fixed = read_list(reader, |reader| read_list(reader, read_scalar))?;
+        let copies = read_list(reader, |reader| {
+            let permutation = read_len(reader)?;
+            let left = (read_column(reader)?, read_len(reader)?);
+            let right = (read_column(reader)?, read_len(reader)?);
+            Ok(IrCopy {
+                permutation,
+                left,
+                right,
+            })
+        })?;
+
+        let ir = CircuitIr {
+            num_advice_columns,
+            num_fixed_columns,
+            num_instance_columns,
+            rows,
+            gates,
+            lookups,
+            permutations,
+            fixed,
+            copies,
+        };
+        ir.validate()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(ir)
+    }
+}
+
+fn write_len<W: io::Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    writer.write_all(&(len as u64).to_le_bytes())
+}
+
+fn read_len<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    let mut data = [0u8; 8];
+    reader.read_exact(&mut data)?;
+    usize::try_from(u64::from_le_bytes(data))
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "IR length is too large"))
+}
+
+fn read_scalar<F: FieldExt, R: io::Read>(reader: &mut R) -> io::Result<F> {
+    let mut data = [0u8; SCALAR_LEN];
+    reader.read_exact(&mut data)?;
+    Option::from(F::from_bytes(&data)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid field element encoding in IR",
+        )
+    })
+}
+
+fn invalid_tag() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid tag in IR")
+}
+
+fn write_list<W: io::Write, T>(
+    writer: &mut W,
+    items: &[T],
+    mut write_item: impl FnMut(&mut W, &T) -> io::Result<()>,
+) -> io::Result<()> {
+    write_len(writer, items.len())?;
+    for item in items {
+        write_item(writer, item)?;
+    }
+    Ok(())
+}
+
+fn read_list<R: io::Read, T>(
+    reader: &mut R,
+    mut read_item: impl FnMut(&mut R) -> io::Result<T>,
+) -> io::Result<Vec<T>> {
+    let len = read_len(reader)?;
+    (0..len).map(|_| read_item(reader)).collect()
+}
+
+fn write_column<W: io::Write>(writer: &mut W, column: IrColumn) -> io::Result<()> {
+    let (tag, index) = match column {
+        IrColumn::Advice(index) => (0, index),
+        IrColumn::Fixed(index) => (1, index),
+        IrColumn::Instance(index) => (2, index),
+    };
+    writer.write_all(&[tag])?;
+    write_len(writer, index)
+}
+
+fn read_column<R: io::Read>(reader: &mut R) -> io::Result<IrColumn> {
+    let mut tag = [0u8; 1];
+    reader.read_exact(&mut tag)?;
+    let index = read_len(reader)?;
+    match tag[0] {
+        0 => Ok(IrColumn::Advice(index)),
+        1 => Ok(IrColumn::Fixed(index)),
+        2 => Ok(IrColumn::Instance(index)),
+        _ => Err(invalid_tag()),
+    }
+}
+
+fn write_expression<F: FieldExt, W: io::Write>(
+    writer: &mut W,
+    expression: &IrExpression<F>,
+) -> io::Result<()> {
+    match expression {
+        IrExpression::Constant(value) => {
+            writer.write_all(&[0])?;
+            writer.write_all(&value.to_bytes())
+        }
+        IrExpression::Query { column, rotation } => {
+            writer.write_all(&[1])?;
+            write_column(writer, *column)?;
+            writer.write_all(&rotation.to_le_bytes())
+        }
+        IrExpression::Sum(a, b) => {
+            writer.write_all(&[2])?;
+            write_expression(writer, a)?;
+            write_expression(writer, b)
+        }
+        IrExpression::Product(a, b) => {
+            writer.write_all(&[3])?;
+            write_expression(writer, a)?;
+            write_expression(writer, b)
+        }
+        IrExpression::Scaled(a, value) => {
+            writer.write_all(&[4])?;
+            write_expression(writer, a)?;
+            writer.write_all(&value.to_bytes())
+        }
+    }
+}
+
+fn read_expression<F: FieldExt, R: io::Read>(
+    reader: &mut R,
+    depth: usize,
+) -> io::Result<IrExpression<F>> {
+    if depth > MAX_EXPRESSION_DEPTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "IR expression is too deeply nested",
+        ));
+    }
+
+    let mut tag = [0u8; 1];
+    reader.read_exact(&mut tag)?;
+    let read_boxed = |reader: &mut R| read_expression(reader, depth + 1).map(Box::new);
+    match tag[0] {
+        0 => Ok(IrExpression::Constant(read_scalar(reader)?)),
+        1 => {
+            let column = read_column(reader)?;
+            let mut rotation = [0u8; 4];
+            reader.read_exact(&mut rotation)?;
+            Ok(IrExpression::Query {
+                column,
+                rotation: i32::from_le_bytes(rotation),
+            })
+        }
+        2 => Ok(IrExpression::Sum(read_boxed(reader)?, read_boxed(reader)?)),
+        3 => Ok(IrExpression::Product(
+            read_boxed(reader)?,
+            read_boxed(reader)?,
+        )),
+        4 => Ok(IrExpression::Scaled(
+            read_boxed(reader)?,
+            read_scalar(reader)?,
+        )),
+        _ => Err(invalid_tag()),
+    }
+}
+
+/// The columns and permutations of an [`IrCircuit`].
+#[derive(Clone, Debug)]
+pub struct IrConfig {
+    advice: Vec<Column<Advice>>,
+    fixed: Vec<Column<Fixed>>,
+    instance: Vec<Column<Instance>>,
+    permutations: Vec<Permutation>,
+}
+
+impl IrConfig {
+    fn column(&self, column: IrColumn) -> Column<Any> {
+        match column {
+            IrColumn::Advice(index) => self.advice[index].into(),
+            IrColumn::Fixed(index) => self.fixed[index].into(),
+            IrColumn::Instance(index) => self.instance[index].into(),
+        }
+    }
+}
+
+/// The circuit described by a [`CircuitIr`].
+#[derive(Debug)]
+pub struct IrCircuit<'a, F: FieldExt> {
+    ir: &'a CircuitIr<F>,
+    /// The values of each advice column, if known.
+    advice: Option<Vec<Vec<F>>>,
+}
+
+impl<'a, F: FieldExt> IrCircuit<'a, F> {
+    /// Creates the circuit described by `ir`, with the given values for each of its
+    /// advice columns.
+    ///
+    /// Each advice column must have exactly one value per row of the IR. This does not
+    /// check that the values satisfy the circuit; use [`MockProver`] for that.
+    ///
+    /// [`MockProver`]: crate::dev::MockProver
+    pub fn new(ir: &'a CircuitIr<F>, advice: Vec<Vec<F>>) -> Result<Self, IrError> {
+        ir.validate()?;
+        if advice.len() != ir.num_advice_columns
+            || advice.iter().any(|values| values.len() != ir.rows)
+        {
+            return Err(IrError::WitnessShape);
+        }
+        Ok(IrCircuit {
+            ir,
+            advice: Some(advice),
+        })
+    }
+
+    /// Creates the circuit described by `ir` without a witness, for generating keys.
+    pub fn without_witness(ir: &'a CircuitIr<F>) -> Result<Self, IrError> {
+        ir.validate()?;
+        Ok(IrCircuit { ir, advice: None })
+    }
+}
+
+impl<'a, F: FieldExt> Circuit<F> for IrCircuit<'a, F> {
+    type Config = IrConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        IrCircuit {
+            ir: self.ir,
+            advice: None,
+        }
+    }
+
+    /// Configures an empty circuit, without any columns.
+    ///
+    /// The configuration of an `IrCircuit` depends on its IR, so it must be configured
+    /// with [`Circuit::configure_from`]; synthesizing it with this configuration
+    /// returns [`Error::SynthesisError`].
+    fn configure(_: &mut ConstraintSystem<F>) -> IrConfig {
+        IrConfig {
+            advice: vec![],
+            fixed: vec![],
+            instance: vec![],
+            permutations: vec![],
+        }
+    }
+
+    fn configure_from(&self, meta: &mut ConstraintSystem<F>) -> IrConfig {
+        let ir = self.ir;
+        let mut config = IrConfig {
+            advice: (0..ir.num_advice_columns)
+                .map(|_| meta.advice_column())
+                .collect(),
+            fixed: (0..ir.num_fixed_columns)
+                .map(|_| meta.fixed_column())
+                .collect(),
+            instance: (0..ir.num_instance_columns)
+                .map(|_| meta.instance_column())
+                .collect(),
+            permutations: vec![],
+        };
+
+        for polys in &ir.gates {
+            let config = &config;
+            meta.create_gate("ir gate", |meta| {
+                polys
+                    .iter()
+                    .map(|poly| {
+                        to_expression(poly, &mut |column, rotation| {
+                            meta.query_any(config.column(column), Rotation(rotation))
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            });
+        }
+
+        for lookup in &ir.lookups {
+            let config = &config;
+            meta.lookup(|meta| {
+                let mut query =
+                    |column, rotation| meta.query_any(config.column(column), Rotation(rotation));
+                lookup
+                    .iter()
+                    .map(|(input, table)| {
+                        (
+                            to_expression(input, &mut query),
+                            to_expression(table, &mut query),
+                        )
+                    })
+                    .collect()
+            });
+        }
+
+        for columns in &ir.permutations {
+            let columns: Vec<_> = columns.iter().map(|c| config.column(*c)).collect();
+            let permutation = meta.permutation(&columns);
+            config.permutations.push(permutation);
+        }
+
+        config
+    }
+
+    fn synthesize(&self, config: IrConfig, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let ir = self.ir;
+        // The configuration must have been created from this IR.
+        if config.advice.len() != ir.num_advice_columns
+            || config.fixed.len() != ir.num_fixed_columns
+            || config.instance.len() != ir.num_instance_columns
+            || config.permutations.len() != ir.permutations.len()
+        {
+            return Err(Error::SynthesisError);
+        }
+        layouter.assign_region(
+            || "ir",
+            |mut region| {
+                // Any assigned cell identifies the region, so that cells of the copy
+                // constraints can be addressed by their row.
+                let mut region_index = None;
+
+                for (column, values) in config.fixed.iter().zip(ir.fixed.iter()) {
+                    let cells = region.assign_fixed_from_slice(|| "fixed", *column, 0, values)?;
+                    region_index = region_index.or(cells.first().map(|c| c.region_index));
+                }
+
+                for (index, column) in config.advice.iter().enumerate() {
+                    let values: Vec<_> = match &self.advice {
+                        Some(advice) => advice[index].iter().copied().map(Some).collect(),
+                        None => vec![None; ir.rows],
+                    };
+                    let cells =
+                        region.assign_advice_from_slice(|| "advice", *column, 0, &values)?;
+                    region_index = region_index.or(cells.first().map(|c| c.region_index));
+                }
+
+                for copy in &ir.copies {
+                    let region_index = region_index.ok_or(Error::SynthesisError)?;
+                    let cell = |(column, row_offset): (IrColumn, usize)| Cell {
+                        region_index,
+                        row_offset,
+                        column: config.column(column),
+                    };
+                    region.constrain_equal(
+                        &config.permutations[copy.permutation],
+                        cell(copy.left),
+                        cell(copy.right),
+                    )?;
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Converts an IR expression into an [`Expression`], querying columns with `query`.
+fn to_expression<F: FieldExt>(
+    expression: &IrExpression<F>,
+    query: &mut impl FnMut(IrColumn, i32) -> Expression<F>,
+) -> Expression<F> {
+    match expression {
+        IrExpression::Constant(value) => Expression::Constant(*value),
+        IrExpression::Query { column, rotation } => query(*column, *rotation),
+        IrExpression::Sum(a, b) => to_expression(a, query) + to_expression(b, query),
+        IrExpression::Product(a, b) => to_expression(a, query) * to_expression(b, query),
+        IrExpression::Scaled(a, value) => to_expression(a, query) * *value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::{EqAffine, Fp};
+
+    use super::{CircuitIr, IrCircuit, IrColumn, IrCopy, IrError, IrExpression};
+    use crate::{
+        dev::MockProver,
+        plonk::{keygen_vk, VerifyingKey},
+        poly::commitment::Params,
+    };
+
+    fn query(column: IrColumn, rotation: i32) -> Box<IrExpression<Fp>> {
+        Box::new(IrExpression::Query { column, rotation })
+    }
+
+    /// Checks that `b = a^2` on the rows selected by a fixed column, chains each `b`
+    /// into the next row's `a`, exposes the last `b`, and looks `a` up in a table.
+    fn squares() -> CircuitIr<Fp> {
+        let (a, b) = (IrColumn::Advice(0), IrColumn::Advice(1));
+        let (s, table) = (IrColumn::Fixed(0), IrColumn::Fixed(1));
+        let instance = IrColumn::Instance(0);
+
+        CircuitIr {
+            num_advice_columns: 2,
+            num_fixed_columns: 2,
+            num_instance_columns: 1,
+            rows: 2,
+            gates: vec![vec![IrExpression::Product(
+                query(s, 0),
+                Box::new(IrExpression::Sum(
+                    Box::new(IrExpression::Product(query(a, 0), query(a, 0))),
+                    Box::new(IrExpression::Scaled(query(b, 0), -Fp::one())),
+                )),
+            )]],
+            lookups: vec![vec![(
+                IrExpression::Product(query(s, 0), query(a, 0)),
+                IrExpression::Query {
+                    column: table,
+                    rotation: 0,
+                },
+            )]],
+            permutations: vec![vec![a, b, instance]],
+            fixed: vec![vec![Fp::one(), Fp::one()], vec![Fp::zero(), Fp::from(2u64)]],
+            copies: vec![
+                IrCopy {
+                    permutation: 0,
+                    left: (b, 0),
+                    right: (a, 1),
+                },
+                IrCopy {
+                    permutation: 0,
+                    left: (b, 1),
+                    right: (instance, 0),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn ir_circuit() {
+        let mut ir = squares();
+        // The table only contains 2, so the lookup fails once `a` is 4.
+        let advice = vec![
+            vec![Fp::from(2u64), Fp::from(4u64)],
+            vec![Fp::from(4u64), Fp::from(16u64)],
+        ];
+        let circuit = IrCircuit::new(&ir, advice.clone()).unwrap();
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(16u64)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        ir.fixed[1].push(Fp::from(4u64));
+        ir.rows = 3;
+        let mut advice = advice;
+        for column in advice.iter_mut() {
+            column.push(Fp::zero());
+        }
+        let circuit = IrCircuit::new(&ir, advice.clone()).unwrap();
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(16u64)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(15u64)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        advice[1][1] = Fp::from(15u64);
+        let circuit = IrCircuit::new(&ir, advice).unwrap();
+        let prover = MockProver::run(4, &circuit, vec![vec![Fp::from(15u64)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn ir_round_trip() {
+        let ir = squares();
+        let mut bytes = vec![];
+        ir.write(&mut bytes).unwrap();
+        assert_eq!(CircuitIr::<Fp>::read(&mut &bytes[..]).unwrap(), ir);
+
+        let mut invalid = ir;
+        invalid.copies[0].left = (IrColumn::Advice(2), 0);
+        assert_eq!(
+            invalid.validate(),
+            Err(IrError::UnknownColumn(IrColumn::Advice(2)))
+        );
+        bytes.clear();
+        invalid.write(&mut bytes).unwrap();
+        assert!(CircuitIr::<Fp>::read(&mut &bytes[..]).is_err());
+    }
+
+    #[test]
+    fn ir_verifying_key() {
+        let ir = squares();
+        let circuit = IrCircuit::without_witness(&ir).unwrap();
+        let params: Params<EqAffine> = Params::new(4);
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let mut bytes = vec![];
+        vk.write(&mut bytes).unwrap();
+
+        let read = VerifyingKey::read_with_circuit(&mut &bytes[..], &params, &circuit).unwrap();
+        assert_eq!(read.digest(), vk.digest());
+
+        // Configured from its type alone, the circuit is empty, so it does not match.
+        assert!(VerifyingKey::read::<_, IrCircuit<Fp>>(&mut &bytes[..], &params).is_err());
+    }
+}
@@ -0,0 +1,606 @@
+//! Importing rank-1 constraint systems.
+//!
+//! An [`R1cs`] is a list of constraints `<a, z> * <b, z> = <c, z>` over an assignment
+//! `z = (1, instance, witness)`, as used by Groth16 circuits. [`R1csCircuit`] lays it
+//! out as a halo2 circuit using the standard PLONK gate of [`StandardPlonkChip`], and
+//! translates a satisfying assignment into a witness for that circuit:
+//!
+//! - each public input is assigned in its own row, in order from the first row, and
+//!   constrained to equal the same row of the instance column;
+//! - each linear combination other than a single variable is accumulated into a cell,
+//!   adding up to two terms in its first row and one term in each later row;
+//! - each constraint is then checked by a multiplication in a single row.
+//!
+//! Every occurrence of a variable after the first is copy-constrained to the first.
+//!
+//! [`StandardPlonkChip`]: crate::gadget::standard::StandardPlonkChip
+
+use std::error;
+use std::fmt;
+
+use crate::{
+    arithmetic::FieldExt,
+    circuit::{Cell, Layouter, Region, SimpleFloorPlanner},
+    gadget::{
+        standard::{StandardPlonkChip, StandardPlonkConfig},
+        witness,
+    },
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+};
+
+/// A variable of an R1CS instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variable {
+    /// The constant one.
+    One,
+    /// The public input with the given index.
+    Instance(usize),
+    /// The private witness value with the given index.
+    Witness(usize),
+}
+
+/// A constraint `<a, z> * <b, z> = <c, z>`, where each linear combination is given as
+/// a list of variables and their coefficients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Constraint<F> {
+    /// The terms of `<a, z>`.
+    pub a: Vec<(Variable, F)>,
+    /// The terms of `<b, z>`.
+    pub b: Vec<(Variable, F)>,
+    /// The terms of `<c, z>`.
+    pub c: Vec<(Variable, F)>,
+}
+
+/// The reasons why an R1CS instance or assignment can be rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum R1csError {
+    /// The constraint with the given index refers to a variable that does not exist.
+    UnknownVariable {
+        /// The index of the constraint.
+        constraint: usize,
+    },
+    /// The constraint matrices have different numbers of rows.
+    MatrixRows,
+    /// An assignment has the wrong number of public inputs or witness values.
+    AssignmentLength {
+        /// The number of values that the instance has.
+        expected: usize,
+        /// The number of values that were given.
+        actual: usize,
+    },
+    /// The assignment does not satisfy the constraint with the given index.
+    Unsatisfied {
+        /// The index of the constraint.
+        constraint: usize,
+    },
+}
+
+impl fmt::Display for R1csError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownVariable { constraint } => {
+                write!(f, "constraint {} refers to an unknown variable", constraint)
+            }
+            Self::MatrixRows => write!(f, "constraint matrices have different numbers of rows"),
+            Self::AssignmentLength { expected, actual } => write!(
+                f,
+                "assignment has {} values, but {} were expected",
+                actual, expected
+            ),
+            Self::Unsatisfied { constraint } => {
+                write!(f, "constraint {} is not satisfied", constraint)
+            }
+        }
+    }
+}
+
+impl error::Error for R1csError {}
+
+/// A rank-1 constraint system.
+#[derive(Clone, Debug)]
+pub struct R1cs<F: FieldExt> {
+    num_instance: usize,
+    num_witness: usize,
+    constraints: Vec<Constraint<F>>,
+}
+
+impl<F: FieldExt> R1cs<F> {
+    /// Creates an instance with the given numbers of public inputs and witness values,
+    /// and no constraints.
+    pub fn new(num_instance: usize, num_witness: usize) -> Self {
+        R1cs {
+            num_instance,
+            num_witness,
+            constraints: vec![],
+        }
+    }
+
+    /// Creates an instance from its constraint matrices, in the layout used by bellman
+    /// and arkworks: each row of a matrix is a list of coefficients and the columns
+    /// they apply to, where column 0 is the constant one, columns `1..=num_instance`
+    /// are the public inputs, and the remaining `num_witness` columns are the witness.
+    ///
+    /// Note that `num_instance` does not count the constant one, unlike arkworks'
+    /// `num_instance_variables`.
+    pub fn from_matrices(
+        num_instance: usize,
+        num_witness: usize,
+        a: &[Vec<(F, usize)>],
+        b: &[Vec<(F, usize)>],
+        c: &[Vec<(F, usize)>],
+    ) -> Result<Self, R1csError> {
+        if a.len() != b.len() || a.len() != c.len() {
+            return Err(R1csError::MatrixRows);
+        }
+
+        let mut r1cs = Self::new(num_instance, num_witness);
+        let variable = |column: usize| match column {
+            0 => Variable::One,
+            column if column <= num_instance => Variable::Instance(column - 1),
+            column => Variable::Witness(column - 1 - num_instance),
+        };
+        let terms = |row: &[(F, usize)]| -> Vec<_> {
+            row.iter()
+                .map(|&(coefficient, column)| (variable(column), coefficient))
+                .collect()
+        };
+        for ((a, b), c) in a.iter().zip(b).zip(c) {
+            r1cs.enforce(Constraint {
+                a: terms(a),
+                b: terms(b),
+                c: terms(c),
+            })?;
+        }
+        Ok(r1cs)
+    }
+
+    /// Adds a constraint.
+    ///
+    /// Returns [`R1csError::UnknownVariable`] if it refers to a public input or
+    /// witness value that the instance does not have.
+    pub fn enforce(&mut self, constraint: Constraint<F>) -> Result<(), R1csError> {
+        let known = |&(variable, _): &(Variable, F)| match variable {
+            Variable::One => true,
+            Variable::Instance(index) => index < self.num_instance,
+            Variable::Witness(index) => index < self.num_witness,
+        };
+        if !(constraint.a.iter().all(known)
+            && constraint.b.iter().all(known)
+            && constraint.c.iter().all(known))
+        {
+            return Err(R1csError::UnknownVariable {
+                constraint: self.constraints.len(),
+            });
+        }
+
+        self.constraints.push(constraint);
+        Ok(())
+    }
+
+    /// Returns the number of public inputs.
+    pub fn num_instance(&self) -> usize {
+        self.num_instance
+    }
+
+    /// Returns the number of witness values.
+    pub fn num_witness(&self) -> usize {
+        self.num_witness
+    }
+
+    /// Returns the constraints.
+    pub fn constraints(&self) -> &[Constraint<F>] {
+        &self.constraints
+    }
+
+    /// Checks that the given assignment satisfies every constraint.
+    pub fn is_satisfied(&self, instance: &[F], witness: &[F]) -> Result<(), R1csError> {
+        let assignment = self.assignment(instance, witness)?;
+        let evaluate = |terms: &[(Variable, F)]| {
+            terms
+                .iter()
+                .fold(F::zero(), |sum, &(variable, coefficient)| {
+                    sum + coefficient * self.value(&assignment, variable)
+                })
+        };
+
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            if evaluate(&constraint.a) * evaluate(&constraint.b) != evaluate(&constraint.c) {
+                return Err(R1csError::Unsatisfied { constraint: index });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the number of rows that [`R1csCircuit`] uses for this instance, not
+    /// counting the rows that the prover reserves for blinding.
+    pub fn rows(&self) -> usize {
+        let rows = |terms: &[(Variable, F)]| match Term::reduce(terms) {
+            Term::Variable(_) => 0,
+            Term::Sum { terms, .. } => std::cmp::max(terms.len(), 2) - 1,
+        };
+
+        self.num_instance
+            + self
+                .constraints
+                .iter()
+                .map(|constraint| {
+                    rows(&constraint.a) + rows(&constraint.b) + rows(&constraint.c) + 1
+                })
+                .sum::<usize>()
+    }
+
+    /// Concatenates the public inputs and witness values, checking their lengths.
+    fn assignment(&self, instance: &[F], witness: &[F]) -> Result<Vec<F>, R1csError> {
+        for (values, expected) in [(instance, self.num_instance), (witness, self.num_witness)]
+            .iter()
+            .copied()
+        {
+            if values.len() != expected {
+                return Err(R1csError::AssignmentLength {
+                    expected,
+                    actual: values.len(),
+                });
+            }
+        }
+        Ok(instance.iter().chain(witness).copied().collect())
+    }
+
+    /// Returns the value of a variable in an assignment returned by
+    /// [`R1cs::assignment`].
+    fn value(&self, assignment: &[F], variable: Variable) -> F {
+        match self.index(variable) {
+            Some(index) => assignment[index],
+            None => F::one(),
+        }
+    }
+
+    /// Returns the index of a variable in an assignment returned by
+    /// [`R1cs::assignment`], or `None` for the constant one.
+    fn index(&self, variable: Variable) -> Option<usize> {
+        match variable {
+            Variable::One => None,
+            Variable::Instance(index) => Some(index),
+            Variable::Witness(index) => Some(self.num_instance + index),
+        }
+    }
+}
+
+/// A linear combination, as laid out by [`R1csCircuit`].
+enum Term<F> {
+    /// A single variable with coefficient one, which needs no rows of its own.
+    Variable(Variable),
+    /// A sum of a constant and of variables with their coefficients.
+    Sum {
+        constant: F,
+        terms: Vec<(Variable, F)>,
+    },
+}
+
+impl<F: FieldExt> Term<F> {
+    fn reduce(terms: &[(Variable, F)]) -> Self {
+        let mut constant = F::zero();
+        let mut variables = vec![];
+        for &(variable, coefficient) in terms {
+            match variable {
+                Variable::One => constant += coefficient,
+                _ => variables.push((variable, coefficient)),
+            }
+        }
+
+        if constant == F::zero() && variables.len() == 1 && variables[0].1 == F::one() {
+            return Term::Variable(variables[0].0);
+        }
+        Term::Sum {
+            constant,
+            terms: variables,
+        }
+    }
+}
+
+/// A value to be assigned in a row of the standard gate.
+#[derive(Clone, Copy)]
+enum Operand<F> {
+    /// A variable, which is copy-constrained to its first occurrence.
+    Variable(Variable),
+    /// A cell computed in an earlier row, which is copy-constrained to that row.
+    Cell(Cell, Option<F>),
+}
+
+/// The circuit checking an [`R1cs`] instance.
+///
+/// The public inputs are the first rows of the circuit's single instance column.
+#[derive(Debug)]
+pub struct R1csCircuit<'a, F: FieldExt> {
+    r1cs: &'a R1cs<F>,
+    /// The public inputs followed by the witness values, if known.
+    assignment: Option<Vec<F>>,
+}
+
+impl<'a, F: FieldExt> R1csCircuit<'a, F> {
+    /// Creates a circuit for an R1CS instance with the given assignment.
+    ///
+    /// This only checks the lengths of the assignment; use [`R1cs::is_satisfied`] to
+    /// check that it satisfies the instance.
+    pub fn new(r1cs: &'a R1cs<F>, instance: &[F], witness: &[F]) -> Result<Self, R1csError> {
+        Ok(R1csCircuit {
+            r1cs,
+            assignment: Some(r1cs.assignment(instance, witness)?),
+        })
+    }
+
+    /// Creates a circuit for an R1CS instance without an assignment, for generating
+    /// keys.
+    pub fn without_assignment(r1cs: &'a R1cs<F>) -> Self {
+        R1csCircuit {
+            r1cs,
+            assignment: None,
+        }
+    }
+}
+
+impl<'a, F: FieldExt> Circuit<F> for R1csCircuit<'a, F> {
+    type Config = StandardPlonkConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::without_assignment(self.r1cs)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> StandardPlonkConfig {
+        let advice = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let instance = meta.instance_column();
+        StandardPlonkChip::configure(meta, advice, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: StandardPlonkConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "r1cs",
+            |mut region| {
+                let mut synthesis = Synthesis {
+                    r1cs: self.r1cs,
+                    config: &config,
+                    region: &mut region,
+                    assignment: self.assignment.as_deref(),
+                    cells: vec![None; self.r1cs.num_instance + self.r1cs.num_witness],
+                    offset: 0,
+                };
+
+                for index in 0..self.r1cs.num_instance {
+                    config.s_pub.enable(synthesis.region, synthesis.offset)?;
+                    synthesis.put(
+                        Operand::Variable(Variable::Instance(index)),
+                        config.advice[0],
+                    )?;
+                    synthesis.offset += 1;
+                }
+
+                for constraint in &self.r1cs.constraints {
+                    let a = synthesis.reduce(&constraint.a)?;
+                    let b = synthesis.reduce(&constraint.b)?;
+                    let c = synthesis.reduce(&constraint.c)?;
+
+                    // a * b - c = 0
+                    synthesis.put(a, config.advice[0])?;
+                    synthesis.put(b, config.advice[1])?;
+                    synthesis.put(c, config.advice[2])?;
+                    synthesis.coefficients(F::zero(), F::zero(), F::one(), F::one(), F::zero())?;
+                    synthesis.offset += 1;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// The state of [`R1csCircuit::synthesize`] within its region.
+struct Synthesis<'r, 'a, F: FieldExt> {
+    r1cs: &'r R1cs<F>,
+    config: &'r StandardPlonkConfig,
+    region: &'r mut Region<'a, F>,
+    assignment: Option<&'r [F]>,
+    /// The cell of the first occurrence of each variable.
+    cells: Vec<Option<Cell>>,
+    offset: usize,
+}
+
+impl<'r, 'a, F: FieldExt> Synthesis<'r, 'a, F> {
+    /// Assigns an operand in the current row, and returns its value.
+    fn put(&mut self, operand: Operand<F>, column: Column<Advice>) -> Result<Option<F>, Error> {
+        let (value, first) = match operand {
+            Operand::Variable(variable) => {
+                let r1cs = self.r1cs;
+                let value = self
+                    .assignment
+                    .map(|assignment| r1cs.value(assignment, variable));
+                let index = r1cs.index(variable).expect("constants are not assigned");
+                (value, self.cells[index].ok_or(index))
+            }
+            Operand::Cell(cell, value) => (value, Ok(cell)),
+        };
+
+        let cell =
+            self.region
+                .assign_advice(|| "operand", column, self.offset, || witness(value))?;
+        match first {
+            Ok(first) => self
+                .region
+                .constrain_equal(&self.config.perm, first, cell)?,
+            Err(index) => self.cells[index] = Some(cell),
+        }
+        Ok(value)
+    }
+
+    /// Assigns the coefficients of the standard gate in the current row.
+    fn coefficients(&mut self, sa: F, sb: F, sc: F, sm: F, sconst: F) -> Result<(), Error> {
+        let config = self.config;
+        let offset = self.offset;
+        self.region
+            .assign_fixed(|| "sa", config.sa, offset, || Ok(sa))?;
+        self.region
+            .assign_fixed(|| "sb", config.sb, offset, || Ok(sb))?;
+        self.region
+            .assign_fixed(|| "sc", config.sc, offset, || Ok(sc))?;
+        self.region
+            .assign_fixed(|| "sm", config.sm, offset, || Ok(sm))?;
+        self.region
+            .assign_fixed(|| "sconst", config.sconst, offset, || Ok(sconst))?;
+        Ok(())
+    }
+
+    /// Assigns the rows accumulating a linear combination, and returns the operand
+    /// holding its value.
+    fn reduce(&mut self, terms: &[(Variable, F)]) -> Result<Operand<F>, Error> {
+        let (constant, terms) = match Term::reduce(terms) {
+            Term::Variable(variable) => return Ok(Operand::Variable(variable)),
+            Term::Sum { constant, terms } => (constant, terms),
+        };
+
+        // The first row adds up to two terms to the constant, and each later row adds
+        // one term to the sum of the previous row.
+        let mut terms = terms
+            .into_iter()
+            .map(|(variable, coefficient)| (Operand::Variable(variable), coefficient));
+        let mut sum = self.sum([terms.next(), terms.next()], constant)?;
+        for term in terms {
+            sum = self.sum([Some((sum, F::one())), Some(term)], F::zero())?;
+        }
+        Ok(sum)
+    }
+
+    /// Assigns a row computing `sa * a + sb * b + sconst` into `c`, and returns the
+    /// operand holding its value.
+    fn sum(
+        &mut self,
+        terms: [Option<(Operand<F>, F)>; 2],
+        constant: F,
+    ) -> Result<Operand<F>, Error> {
+        let mut value = Some(constant);
+        let mut coefficients = [F::zero(); 2];
+        for (index, term) in terms.iter().enumerate() {
+            if let Some((operand, coefficient)) = *term {
+                let term_value = self.put(operand, self.config.advice[index])?;
+                value = value.and_then(|sum| term_value.map(|term| sum + coefficient * term));
+                coefficients[index] = coefficient;
+            }
+        }
+
+        let cell = self.region.assign_advice(
+            || "sum",
+            self.config.advice[2],
+            self.offset,
+            || witness(value),
+        )?;
+        self.coefficients(
+            coefficients[0],
+            coefficients[1],
+            F::one(),
+            F::zero(),
+            constant,
+        )?;
+        self.offset += 1;
+        Ok(Operand::Cell(cell, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{Constraint, R1cs, R1csCircuit, R1csError, Variable};
+    use crate::dev::MockProver;
+
+    /// Proves knowledge of `x` such that `x^3 + x + 5` equals the public input.
+    fn cubic() -> R1cs<Fp> {
+        let one = Fp::one();
+        let (x, x2, x3) = (
+            Variable::Witness(0),
+            Variable::Witness(1),
+            Variable::Witness(2),
+        );
+
+        let mut r1cs = R1cs::new(1, 3);
+        for (a, b, c) in [(x, x, x2), (x2, x, x3)].iter().copied() {
+            r1cs.enforce(Constraint {
+                a: vec![(a, one)],
+                b: vec![(b, one)],
+                c: vec![(c, one)],
+            })
+            .unwrap();
+        }
+        r1cs.enforce(Constraint {
+            a: vec![(x3, one), (x, one), (Variable::One, Fp::from(5u64))],
+            b: vec![(Variable::One, one)],
+            c: vec![(Variable::Instance(0), one)],
+        })
+        .unwrap();
+        r1cs
+    }
+
+    #[test]
+    fn cubic_circuit() {
+        let r1cs = cubic();
+        assert_eq!(r1cs.rows(), 6);
+
+        let k = 4;
+        let witness = |x: u64| vec![Fp::from(x), Fp::from(x * x), Fp::from(x * x * x)];
+        let instance = |output: u64| {
+            let mut column = vec![Fp::zero(); 1 << k];
+            column[0] = Fp::from(output);
+            column
+        };
+
+        assert_eq!(r1cs.is_satisfied(&[Fp::from(35u64)], &witness(3)), Ok(()));
+        let circuit = R1csCircuit::new(&r1cs, &[Fp::from(35u64)], &witness(3)).unwrap();
+        let prover = MockProver::run(k, &circuit, vec![instance(35)]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The circuit rejects exactly the assignments that the R1CS instance rejects.
+        assert_eq!(
+            r1cs.is_satisfied(&[Fp::from(36u64)], &witness(3)),
+            Err(R1csError::Unsatisfied { constraint: 2 })
+        );
+        let circuit = R1csCircuit::new(&r1cs, &[Fp::from(36u64)], &witness(3)).unwrap();
+        let prover = MockProver::run(k, &circuit, vec![instance(36)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        assert_eq!(
+            R1csCircuit::new(&r1cs, &[], &witness(3)).unwrap_err(),
+            R1csError::AssignmentLength {
+                expected: 1,
+                actual: 0
+            }
+        );
+    }
+
+    #[test]
+    fn from_matrices() {
+        let one = Fp::one();
+        // Columns: one, output, x, x^2, x^3.
+        let a = vec![
+            vec![(one, 2)],
+            vec![(one, 3)],
+            vec![(one, 4), (one, 2), (Fp::from(5u64), 0)],
+        ];
+        let b = vec![vec![(one, 2)], vec![(one, 2)], vec![(one, 0)]];
+        let c = vec![vec![(one, 3)], vec![(one, 4)], vec![(one, 1)]];
+        let r1cs = R1cs::from_matrices(1, 3, &a, &b, &c).unwrap();
+        assert_eq!(r1cs.constraints(), cubic().constraints());
+
+        assert_eq!(
+            R1cs::from_matrices(1, 2, &a, &b, &c).unwrap_err(),
+            R1csError::UnknownVariable { constraint: 1 }
+        );
+        assert_eq!(
+            R1cs::from_matrices(1, 3, &a, &b, &c[..2]).unwrap_err(),
+            R1csError::MatrixRows
+        );
+    }
+}
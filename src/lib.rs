@@ -22,11 +22,22 @@
 #![deny(missing_docs)]
 #![deny(unsafe_code)]
 
+// This must come first, so that its macros are available to the other modules.
+#[macro_use]
+mod trace;
+
 pub mod arithmetic;
 pub mod circuit;
+pub mod curves;
+pub mod format;
+pub mod gadget;
+pub mod interop;
 pub use pasta_curves as pasta;
 pub mod plonk;
 pub mod poly;
 pub mod transcript;
 
+#[cfg(feature = "mmap")]
+mod mmap;
+
 pub mod dev;
@@ -0,0 +1,252 @@
+//! A raw encoding of large parameter and key tables, for loading them from
+//! memory-mapped files.
+//!
+//! The raw encoding stores each table in the in-memory representation of its elements,
+//! padded so that it is correctly aligned when the file is mapped at a page boundary.
+//! Loading a table is then a single copy out of the mapping, instead of reading it into
+//! an intermediate buffer and decoding (and decompressing) each element.
+//!
+//! Loading is not zero-copy: every table is copied into an owned `Vec`, and the mapping
+//! is dropped once the whole file has been loaded. The mapped pages are backed by the
+//! file rather than by anonymous memory, so the kernel can reclaim them under memory
+//! pressure, but until it does the resident memory can include both the mapped tables
+//! and their copies.
+//!
+//! Because the encoding depends on the in-memory representation, it can only be read on
+//! the same platform, with the same versions of `halo2` and the curve implementation,
+//! that wrote it.
+
+// Reinterpreting mapped bytes as tables of elements is inherently unsafe; the callers
+// document the conditions under which it is sound.
+#![allow(unsafe_code)]
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::mem;
+use std::path::Path;
+use std::slice;
+
+use memmap2::Mmap;
+
+use crate::arithmetic::Group;
+use crate::poly::{Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial};
+
+/// The magic bytes at the start of every raw file.
+const MAGIC: [u8; 8] = *b"halo2raw";
+
+/// The version of the raw encoding.
+///
+/// Version 2 omits the `l_0` polynomial from proving keys of circuits that have no
+/// lookup or permutation arguments.
+const VERSION: u32 = 2;
+
+/// The kinds of object that can be stored in a raw file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RawKind {
+    Params = 1,
+    ProvingKey = 2,
+}
+
+/// Returns the number of padding bytes needed after `offset` to align a `T`.
+fn padding_for<T>(offset: usize) -> usize {
+    let align = mem::align_of::<T>();
+    (align - offset % align) % align
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Writes a raw file.
+#[derive(Debug)]
+pub(crate) struct RawWriter<W: Write> {
+    writer: W,
+    offset: usize,
+}
+
+impl<W: Write> RawWriter<W> {
+    /// Starts a raw file containing an object of the given kind.
+    pub(crate) fn new(writer: W, kind: RawKind) -> io::Result<Self> {
+        let mut raw = RawWriter { writer, offset: 0 };
+        raw.write_bytes(&MAGIC)?;
+        raw.write_bytes(&VERSION.to_le_bytes())?;
+        raw.write_bytes(&(kind as u32).to_le_bytes())?;
+        // Tables are written in native byte order, so record it.
+        raw.write_bytes(&1u32.to_ne_bytes())?;
+        Ok(raw)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len();
+        Ok(())
+    }
+
+    /// Writes a table, preceded by its length and element size.
+    ///
+    /// `T` must not contain any padding bytes.
+    pub(crate) fn write_table<T: Copy>(&mut self, table: &[T]) -> io::Result<()> {
+        self.write_bytes(&(table.len() as u64).to_le_bytes())?;
+        self.write_bytes(&(mem::size_of::<T>() as u64).to_le_bytes())?;
+        self.write_bytes(&vec![0; padding_for::<T>(self.offset)])?;
+
+        // Safety: `table` is a valid slice, and `T` has no padding bytes, so all of the
+        // bytes in its memory are initialized.
+        let bytes =
+            unsafe { slice::from_raw_parts(table.as_ptr() as *const u8, mem::size_of_val(table)) };
+        self.write_bytes(bytes)
+    }
+
+    /// Writes the values of a polynomial.
+    pub(crate) fn write_poly<F: Copy, B>(&mut self, poly: &Polynomial<F, B>) -> io::Result<()> {
+        self.write_table(&poly[..])
+    }
+
+    /// Flushes the underlying writer.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reads a raw file from a memory mapping.
+#[derive(Debug)]
+pub(crate) struct RawReader {
+    map: Mmap,
+    offset: usize,
+}
+
+impl RawReader {
+    /// Maps the raw file at `path`, and checks that it contains an object of the given
+    /// kind.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified while it is mapped.
+    pub(crate) unsafe fn open<P: AsRef<Path>>(path: P, kind: RawKind) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let mut raw = RawReader {
+            map: Mmap::map(&file)?,
+            offset: 0,
+        };
+
+        if raw.read_bytes(MAGIC.len())? != &MAGIC[..] {
+            return Err(invalid_data("not a raw halo2 file"));
+        }
+        if raw.read_u32()? != VERSION {
+            return Err(invalid_data("unsupported raw encoding version"));
+        }
+        if raw.read_u32()? != kind as u32 {
+            return Err(invalid_data("raw file contains a different kind of object"));
+        }
+        if raw.read_bytes(4)? != &1u32.to_ne_bytes()[..] {
+            return Err(invalid_data(
+                "raw file was written with a different byte order",
+            ));
+        }
+
+        Ok(raw)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> io::Result<&[u8]> {
+        let start = self.offset;
+        let end = start
+            .checked_add(len)
+            .filter(|&end| end <= self.map.len())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        self.offset = end;
+        Ok(&self.map[start..end])
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.read_bytes(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_u64(&mut self) -> io::Result<u64> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Reads a table written by [`RawWriter::write_table`], checking that it has
+    /// `expected_len` elements if given.
+    ///
+    /// The table is copied out of the mapping into the returned `Vec`.
+    ///
+    /// # Safety
+    ///
+    /// Every bit pattern of the size of `T` must be a valid `T`, or the table must have
+    /// been written by [`RawWriter::write_table`] for the same `T`.
+    pub(crate) unsafe fn read_table<T: Copy>(
+        &mut self,
+        expected_len: Option<usize>,
+    ) -> io::Result<Vec<T>> {
+        let len = self.read_u64()? as usize;
+        if expected_len.map_or(false, |expected| expected != len) {
+            return Err(invalid_data("raw table has the wrong length"));
+        }
+        if self.read_u64()? != mem::size_of::<T>() as u64 {
+            return Err(invalid_data("raw table has the wrong element size"));
+        }
+        self.read_bytes(padding_for::<T>(self.offset))?;
+
+        let size = len
+            .checked_mul(mem::size_of::<T>())
+            .ok_or_else(|| invalid_data("raw table is too large"))?;
+        let bytes = self.read_bytes(size)?;
+        if bytes.as_ptr() as usize % mem::align_of::<T>() != 0 {
+            return Err(invalid_data("raw table is not aligned"));
+        }
+
+        Ok(slice::from_raw_parts(bytes.as_ptr() as *const T, len).to_vec())
+    }
+
+    /// Reads a polynomial in the Lagrange basis of `domain`.
+    ///
+    /// # Safety
+    ///
+    /// See [`RawReader::read_table`].
+    pub(crate) unsafe fn read_lagrange<G: Group + Copy>(
+        &mut self,
+        domain: &EvaluationDomain<G>,
+    ) -> io::Result<Polynomial<G, LagrangeCoeff>> {
+        let values = self.read_table(Some(1 << domain.k()))?;
+        Ok(domain.lagrange_from_vec(values))
+    }
+
+    /// Reads a polynomial in the coefficient basis of `domain`.
+    ///
+    /// # Safety
+    ///
+    /// See [`RawReader::read_table`].
+    pub(crate) unsafe fn read_coeff<G: Group + Copy>(
+        &mut self,
+        domain: &EvaluationDomain<G>,
+    ) -> io::Result<Polynomial<G, Coeff>> {
+        let values = self.read_table(Some(1 << domain.k()))?;
+        Ok(domain.coeff_from_vec(values))
+    }
+
+    /// Reads a polynomial in the extended Lagrange basis of `domain`.
+    ///
+    /// # Safety
+    ///
+    /// See [`RawReader::read_table`].
+    pub(crate) unsafe fn read_extended<G: Group + Copy>(
+        &mut self,
+        domain: &EvaluationDomain<G>,
+    ) -> io::Result<Polynomial<G, ExtendedLagrangeCoeff>> {
+        let values = self.read_table(Some(domain.extended_len()))?;
+        Ok(domain.extended_from_vec(values))
+    }
+
+    /// Checks that the whole file has been read.
+    pub(crate) fn finish(self) -> io::Result<()> {
+        if self.offset == self.map.len() {
+            Ok(())
+        } else {
+            Err(invalid_data("raw file has trailing data"))
+        }
+    }
+}
@@ -7,26 +7,44 @@
 
 use blake2b_simd::Params as Blake2bParams;
 
-use crate::arithmetic::{CurveAffine, FieldExt};
+use crate::arithmetic::{CanonicalEncoding, CurveAffine, CurveExt, FieldExt};
+use crate::format;
 use crate::poly::{
     commitment::Params, Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff,
     PinnedEvaluationDomain, Polynomial,
 };
 use crate::transcript::{ChallengeScalar, EncodedChallenge, Transcript};
 
+#[cfg(feature = "mmap")]
+use crate::mmap::{RawKind, RawReader, RawWriter};
+
+mod challenges;
 mod circuit;
+mod compose;
+mod config;
+pub mod folding;
 mod keygen;
+mod link;
 mod lookup;
 pub(crate) mod permutation;
 mod vanishing;
 
+mod proof;
 mod prover;
+mod shape;
 mod verifier;
+mod witness;
 
+pub use challenges::*;
 pub use circuit::*;
+pub use compose::*;
 pub use keygen::*;
+pub use link::*;
+pub use proof::*;
 pub use prover::*;
+pub use shape::*;
 pub use verifier::*;
+pub use witness::*;
 
 use std::io;
 
@@ -37,44 +55,82 @@ pub struct VerifyingKey<C: CurveAffine> {
     domain: EvaluationDomain<C::Scalar>,
     fixed_commitments: Vec<C>,
     permutations: Vec<permutation::VerifyingKey<C>>,
+    constant_instance_commitments: Vec<(Column<Instance>, C)>,
     cs: ConstraintSystem<C::Scalar>,
+    /// If the circuit enables [`ConstraintSystem::enable_fixed_column_compression`], its
+    /// own constraint system, and the column that each of its fixed columns was merged
+    /// into to obtain `cs`.
+    merged_fixed_columns: Option<(ConstraintSystem<C::Scalar>, Vec<usize>)>,
 }
 
 impl<C: CurveAffine> VerifyingKey<C> {
     /// Writes a verifying key to a buffer.
+    ///
+    /// This writes a header identifying the curve and the [`CircuitShape`] (see the
+    /// [`format`](mod@crate::format) module), followed by the [`VerifyingKeyCommitments`];
+    /// the shape itself is reconstructed from the circuit when the key is read.
     pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
-        for commitment in &self.fixed_commitments {
-            writer.write_all(commitment.to_bytes().as_ref())?;
-        }
-        for permutation in &self.permutations {
-            permutation.write(writer)?;
-        }
-
-        Ok(())
+        format::write_header(
+            writer,
+            format::Kind::VerifyingKey,
+            C::CurveExt::CURVE_ID,
+            Some(&shape::digest(self.circuit_cs())),
+        )?;
+        shape::write_commitments(
+            &self.fixed_commitments,
+            self.fixed_column_map(),
+            &self.permutations,
+            writer,
+        )
     }
 
     /// Reads a verification key from a buffer.
+    ///
+    /// Returns a [`FormatError`] if the key was written by an incompatible version of
+    /// `halo2`, is over a different curve, or is for a circuit with a different
+    /// constraint system than `ConcreteCircuit`.
+    ///
+    /// [`FormatError`]: crate::format::FormatError
     pub fn read<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
         reader: &mut R,
         params: &Params<C>,
     ) -> io::Result<Self> {
-        let (domain, cs, _) = keygen::create_domain::<C, ConcreteCircuit>(params);
+        Self::read_with_shape(reader, params, CircuitShape::new::<ConcreteCircuit>())
+    }
 
-        let fixed_commitments: Vec<_> = (0..cs.num_fixed_columns)
-            .map(|_| C::read(reader))
-            .collect::<Result<_, _>>()?;
+    /// Reads a verification key for `circuit` from a buffer.
+    ///
+    /// This is [`VerifyingKey::read`] for circuits whose configuration depends on their
+    /// value (such as [`IrCircuit`]): the circuit is configured with
+    /// [`Circuit::configure_from`].
+    ///
+    /// [`IrCircuit`]: crate::interop::ir::IrCircuit
+    pub fn read_with_circuit<R: io::Read, ConcreteCircuit: Circuit<C::Scalar>>(
+        reader: &mut R,
+        params: &Params<C>,
+        circuit: &ConcreteCircuit,
+    ) -> io::Result<Self> {
+        Self::read_with_shape(reader, params, CircuitShape::from_circuit(circuit))
+    }
 
-        let permutations: Vec<_> = cs
-            .permutations
-            .iter()
-            .map(|argument| permutation::VerifyingKey::read(reader, argument))
-            .collect::<Result<_, _>>()?;
+    fn read_with_shape<R: io::Read>(
+        reader: &mut R,
+        params: &Params<C>,
+        shape: CircuitShape<C::Scalar>,
+    ) -> io::Result<Self> {
+        format::read_header(
+            reader,
+            format::Kind::VerifyingKey,
+            C::CurveExt::CURVE_ID,
+            Some(&shape.digest()),
+        )?;
+        let commitments = VerifyingKeyCommitments::read(reader, &shape)?;
 
-        Ok(VerifyingKey {
-            domain,
-            fixed_commitments,
-            permutations,
-            cs,
+        Self::from_parts(params, shape, commitments).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "circuit does not fit in the parameters",
+            )
         })
     }
 
@@ -83,6 +139,61 @@ impl<C: CurveAffine> VerifyingKey<C> {
         &self,
         transcript: &mut T,
     ) -> io::Result<()> {
+        // Hash in final Blake2bState
+        transcript.common_scalar("vk", C::Scalar::from_bytes_wide(&self.digest()))?;
+
+        Ok(())
+    }
+
+    /// Hashes the values of the instance columns of a proof into a transcript.
+    ///
+    /// Each column is absorbed as its length, excluding trailing zeros, followed by its
+    /// values up to that length. Columns that differ only in trailing zeros (which
+    /// have the same commitment) are therefore absorbed identically, and the verifier
+    /// does not need to pad the instance values it is given.
+    ///
+    /// The prover and verifier call this after absorbing the instance commitments of
+    /// each proof, for circuits that call [`ConstraintSystem::bind_instance_values`].
+    /// Constant instance columns (see [`ConstraintSystem::constant_instance_column`])
+    /// are already bound by the verifying key, and are skipped.
+    pub fn hash_instance_values<E: EncodedChallenge<C>, T: Transcript<C, E>>(
+        &self,
+        transcript: &mut T,
+        instance: &[&[C::Scalar]],
+    ) -> io::Result<()> {
+        for (index, values) in instance.iter().enumerate() {
+            if self.constant_instance_commitment(index).is_some() {
+                continue;
+            }
+            let len = values
+                .iter()
+                .rposition(|value| !bool::from(value.ct_is_zero()))
+                .map_or(0, |last| last + 1);
+            transcript.common_scalar("instance length", C::Scalar::from(len as u64))?;
+            for value in &values[..len] {
+                transcript.common_scalar("instance value", *value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the commitment to the instance column with the given index, if it was
+    /// allocated with [`ConstraintSystem::constant_instance_column`].
+    ///
+    /// The prover and verifier use this commitment in place of one computed for each
+    /// proof, and do not absorb it into the transcript; it is bound to proofs through
+    /// the verifying key instead.
+    pub fn constant_instance_commitment(&self, column: usize) -> Option<C> {
+        self.constant_instance_commitments
+            .iter()
+            .find(|(existing, _)| existing.index() == column)
+            .map(|(_, commitment)| *commitment)
+    }
+
+    /// Returns the BLAKE2b digest of the pinned representation of this verifying key,
+    /// from which the value hashed into the transcript is derived.
+    pub(crate) fn digest(&self) -> [u8; 64] {
         let mut hasher = Blake2bParams::new()
             .hash_length(64)
             .personal(b"Halo2-Verify-Key")
@@ -93,21 +204,19 @@ impl<C: CurveAffine> VerifyingKey<C> {
         hasher.update(&(s.len() as u64).to_le_bytes());
         hasher.update(s.as_bytes());
 
-        // Hash in final Blake2bState
-        transcript.common_scalar(C::Scalar::from_bytes_wide(hasher.finalize().as_array()))?;
-
-        Ok(())
+        *hasher.finalize().as_array()
     }
 
     /// Obtains a pinned representation of this verification key that contains
     /// the minimal information necessary to reconstruct the verification key.
     pub fn pinned(&self) -> PinnedVerificationKey<'_, C> {
         PinnedVerificationKey {
-            base_modulus: C::Base::MODULUS,
-            scalar_modulus: C::Scalar::MODULUS,
+            base_modulus: C::Base::modulus(),
+            scalar_modulus: C::Scalar::modulus(),
             domain: self.domain.pinned(),
             fixed_commitments: &self.fixed_commitments,
             permutations: &self.permutations,
+            constant_instance_commitments: &self.constant_instance_commitments,
             cs: self.cs.pinned(),
         }
     }
@@ -115,7 +224,6 @@ impl<C: CurveAffine> VerifyingKey<C> {
 
 /// Minimal representation of a verification key that can be used to identify
 /// its active contents.
-#[derive(Debug)]
 pub struct PinnedVerificationKey<'a, C: CurveAffine> {
     base_modulus: &'static str,
     scalar_modulus: &'static str,
@@ -123,21 +231,179 @@ pub struct PinnedVerificationKey<'a, C: CurveAffine> {
     cs: PinnedConstraintSystem<'a, C::Scalar>,
     fixed_commitments: &'a Vec<C>,
     permutations: &'a Vec<permutation::VerifyingKey<C>>,
+    constant_instance_commitments: &'a Vec<(Column<Instance>, C)>,
+}
+
+impl<'a, C: CurveAffine> std::fmt::Debug for PinnedVerificationKey<'a, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug_struct = f.debug_struct("PinnedVerificationKey");
+        debug_struct
+            .field("base_modulus", &self.base_modulus)
+            .field("scalar_modulus", &self.scalar_modulus)
+            .field("domain", &self.domain)
+            .field("cs", &self.cs)
+            .field("fixed_commitments", &self.fixed_commitments)
+            .field("permutations", &self.permutations);
+        // Only include the constant instance commitments if there are any, so that the
+        // pinned representations of existing circuits are unchanged.
+        if !self.constant_instance_commitments.is_empty() {
+            debug_struct.field(
+                "constant_instance_commitments",
+                &self.constant_instance_commitments,
+            );
+        }
+        debug_struct.finish()
+    }
+}
+
+impl<'a, C: CurveAffine> PinnedVerificationKey<'a, C> {
+    /// Computes a structured diff between this pinned verification key and `other`.
+    ///
+    /// This is intended for tests that guard against accidental circuit changes: the
+    /// returned [`PinnedDiff`] pinpoints the gates, queries, arguments or commitments
+    /// that changed, instead of comparing the entire `Debug` output as a single string.
+    pub fn diff(&self, other: &Self) -> PinnedDiff {
+        let mut diff = PinnedDiff::default();
+        diff.item("base_modulus", self.base_modulus, other.base_modulus);
+        diff.item("scalar_modulus", self.scalar_modulus, other.scalar_modulus);
+        diff.item("domain", &self.domain, &other.domain);
+        diff.extend("cs", self.cs.diff(&other.cs));
+        diff.list(
+            "fixed_commitments",
+            self.fixed_commitments,
+            other.fixed_commitments,
+        );
+        diff.list("permutations", self.permutations, other.permutations);
+        diff.list(
+            "constant_instance_commitments",
+            self.constant_instance_commitments,
+            other.constant_instance_commitments,
+        );
+        diff
+    }
 }
+
 /// This is a proving key which allows for the creation of proofs for a
 /// particular circuit.
 #[derive(Debug)]
 pub struct ProvingKey<C: CurveAffine> {
     vk: VerifyingKey<C>,
-    // TODO: get rid of this?
-    l0: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
+    /// The coset of `l_0(X)`, which is only used by lookup and permutation arguments,
+    /// and so is `None` for circuits without them.
+    l0: Option<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
     fixed_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
     fixed_polys: Vec<Polynomial<C::Scalar, Coeff>>,
     fixed_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
     permutations: Vec<permutation::ProvingKey<C>>,
 }
 
+#[cfg(feature = "mmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+#[allow(unsafe_code)]
+impl<C: CurveAffine> ProvingKey<C> {
+    /// Writes a proving key in a raw encoding that can be loaded with
+    /// [`ProvingKey::read_mmap`].
+    ///
+    /// The verifying key is written in its usual encoding, and the fixed and
+    /// permutation polynomials in the in-memory representation of their values, so the
+    /// result can only be read on the same platform, using the same versions of `halo2`
+    /// and of the curve implementation.
+    pub fn write_raw<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        let mut vk = vec![];
+        self.vk.write(&mut vk)?;
+
+        let mut raw = RawWriter::new(writer, RawKind::ProvingKey)?;
+        raw.write_table(&vk)?;
+        if let Some(l0) = &self.l0 {
+            raw.write_poly(l0)?;
+        }
+        for poly in &self.fixed_values {
+            raw.write_poly(poly)?;
+        }
+        for poly in &self.fixed_polys {
+            raw.write_poly(poly)?;
+        }
+        for poly in &self.fixed_cosets {
+            raw.write_poly(poly)?;
+        }
+        for permutation in &self.permutations {
+            permutation.write_raw(&mut raw)?;
+        }
+        raw.finish()
+    }
+
+    /// Loads a proving key for `circuit`, written by [`ProvingKey::write_raw`], from a
+    /// memory-mapped file.
+    ///
+    /// The polynomials are copied directly out of the mapping, without decoding them or
+    /// reading them into an intermediate buffer. Loading is not zero-copy: the proving
+    /// key owns a copy of every polynomial, and until the kernel reclaims the mapped
+    /// pages, both may be resident in memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must have been written by [`ProvingKey::write_raw`] for the same curve,
+    /// on the same platform, using the same versions of `halo2` and of the curve
+    /// implementation. It must not be modified while it is being loaded.
+    pub unsafe fn read_mmap<P: AsRef<std::path::Path>, ConcreteCircuit: Circuit<C::Scalar>>(
+        path: P,
+        params: &Params<C>,
+        circuit: &ConcreteCircuit,
+    ) -> io::Result<Self> {
+        let mut raw = RawReader::open(path, RawKind::ProvingKey)?;
+        let vk: Vec<u8> = raw.read_table(None)?;
+        let vk = VerifyingKey::read_with_circuit(&mut &vk[..], params, circuit)?;
+
+        let domain = &vk.domain;
+        let l0 = if vk.cs.has_lookups_or_permutations() {
+            Some(raw.read_extended(domain)?)
+        } else {
+            None
+        };
+        let fixed_values = (0..vk.cs.num_fixed_columns)
+            .map(|_| raw.read_lagrange(domain))
+            .collect::<Result<_, _>>()?;
+        let fixed_polys = (0..vk.cs.num_fixed_columns)
+            .map(|_| raw.read_coeff(domain))
+            .collect::<Result<_, _>>()?;
+        let fixed_cosets = (0..vk.cs.fixed_queries.len())
+            .map(|_| raw.read_extended(domain))
+            .collect::<Result<_, _>>()?;
+        let permutations = vk
+            .cs
+            .permutations
+            .iter()
+            .map(|argument| permutation::ProvingKey::read_raw(&mut raw, argument, domain))
+            .collect::<Result<_, _>>()?;
+        raw.finish()?;
+
+        Ok(ProvingKey {
+            vk,
+            l0,
+            fixed_values,
+            fixed_polys,
+            fixed_cosets,
+            permutations,
+        })
+    }
+}
+
 /// This is an error that could occur during proving or circuit synthesis.
+///
+/// Misuse of the circuit APIs that can be detected at run time, such as a gate without
+/// constraints or an invalid copy constraint, is reported through this type rather
+/// than by panicking. The APIs that may still panic document this in a `# Panics`
+/// section; such panics indicate a bug in the calling code, such as configuring a
+/// circuit inconsistently, rather than a condition that a caller is expected to handle.
+/// They are:
+///
+/// - [`ConstraintSystem::lookup_tagged`], for invalid tags or too many inputs;
+/// - [`ConstraintSystem::interchangeable_lanes`], for lanes of different shapes;
+/// - [`LinearCombinationChip::configure`], for an empty set of value columns;
+/// - [`ColumnMatrix::from_columns`], for columns of the wrong length.
+///
+/// [`LinearCombinationChip::configure`]: crate::gadget::linear_combination::LinearCombinationChip::configure
+/// [`ColumnMatrix::from_columns`]: crate::poly::ColumnMatrix::from_columns
 // TODO: these errors need to be cleaned up
 #[derive(Debug)]
 pub enum Error {
@@ -155,38 +421,146 @@ pub enum Error {
     OpeningError,
     /// Transcript error
     TranscriptError,
+    /// Constants were requested, but the circuit has not enabled any fixed columns to
+    /// hold them. See [`ConstraintSystem::enable_constant`].
+    NotEnoughColumnsForConstants,
+    /// A copy constraint was requested between cells that its permutation argument
+    /// cannot constrain, because a column is not part of the argument or a row is
+    /// outside the circuit.
+    InvalidCopy {
+        /// The name of the region in which the copy constraint was requested, if the
+        /// backend keeps track of regions.
+        region: Option<String>,
+        /// The path of namespaces in which the copy constraint was requested, outermost
+        /// first.
+        namespace: Vec<String>,
+        /// The index of the permutation argument.
+        permutation: usize,
+        /// The left cell, as `(column, row)`.
+        left: (Column<Any>, usize),
+        /// The right cell, as `(column, row)`.
+        right: (Column<Any>, usize),
+    },
+    /// A lookup argument has a higher degree than the constraint system allows. See
+    /// [`ConstraintSystem::set_max_lookup_degree`] and
+    /// [`ConstraintSystem::enable_lookup_splitting`].
+    LookupDegreeTooHigh {
+        /// The index of the lookup argument, in the order in which
+        /// `ConstraintSystem::lookup` was called during `Circuit::configure`.
+        lookup: usize,
+        /// The degree of the lookup argument.
+        degree: usize,
+        /// The maximum degree of a lookup argument.
+        max_degree: usize,
+    },
+    /// A gate was created without any constraints. See
+    /// [`ConstraintSystem::create_gate`].
+    EmptyGate {
+        /// The name of the gate.
+        name: &'static str,
+    },
+    /// The prover would need more memory than the budget in its [`ProverOptions`].
+    MemoryBudgetExceeded {
+        /// The estimated number of bytes needed, from [`estimate_prover_memory`].
+        estimate: usize,
+        /// The budget, in bytes.
+        budget: usize,
+    },
 }
 
 impl<C: CurveAffine> ProvingKey<C> {
+    /// Returns the coset of `l_0(X)`.
+    ///
+    /// `l_0(X)` is only computed for circuits with lookup or permutation arguments, which
+    /// are its only users. Returns [`Error::IncompatibleParams`] if this key does not
+    /// contain it.
+    pub(crate) fn l0(&self) -> Result<&Polynomial<C::Scalar, ExtendedLagrangeCoeff>, Error> {
+        self.l0.as_ref().ok_or(Error::IncompatibleParams)
+    }
+
     /// Get the underlying [`VerifyingKey`].
     pub fn get_vk(&self) -> &VerifyingKey<C> {
         &self.vk
     }
 }
 
+impl<C: CurveAffine> AsRef<VerifyingKey<C>> for ProvingKey<C> {
+    fn as_ref(&self) -> &VerifyingKey<C> {
+        &self.vk
+    }
+}
+
+impl<C: CurveAffine> AsRef<VerifyingKey<C>> for VerifyingKey<C> {
+    fn as_ref(&self) -> &VerifyingKey<C> {
+        self
+    }
+}
+
 impl<C: CurveAffine> VerifyingKey<C> {
     /// Get the underlying [`EvaluationDomain`].
     pub fn get_domain(&self) -> &EvaluationDomain<C::Scalar> {
         &self.domain
     }
+
+    /// Returns the constraint system of the circuit.
+    pub(crate) fn cs(&self) -> &ConstraintSystem<C::Scalar> {
+        &self.cs
+    }
+
+    /// Returns the constraint system that the circuit configures, before any of its
+    /// fixed columns were merged.
+    pub(crate) fn circuit_cs(&self) -> &ConstraintSystem<C::Scalar> {
+        self.merged_fixed_columns
+            .as_ref()
+            .map_or(&self.cs, |(cs, _)| cs)
+    }
+
+    /// Returns the column that each fixed column was merged into, if the circuit
+    /// enables fixed-column compression.
+    pub(crate) fn fixed_column_map(&self) -> Option<&[usize]> {
+        self.merged_fixed_columns.as_ref().map(|(_, map)| &map[..])
+    }
+
+    /// Returns the commitments to the fixed columns.
+    pub(crate) fn fixed_commitments(&self) -> &[C] {
+        &self.fixed_commitments
+    }
+
+    /// Returns the commitments to the permutation polynomials of the given
+    /// permutation argument.
+    pub(crate) fn permutation_commitments(&self, argument: usize) -> &[C] {
+        self.permutations[argument].commitments()
+    }
 }
 
+/// Marks the challenge $\theta$, which compresses the expressions of each lookup
+/// argument into a single expression.
 #[derive(Clone, Copy, Debug)]
-struct Theta;
-type ChallengeTheta<F> = ChallengeScalar<F, Theta>;
+pub struct Theta;
+/// The challenge $\theta$ of a proof.
+pub type ChallengeTheta<C> = ChallengeScalar<C, Theta>;
 
+/// Marks the challenge $\beta$ of the permutation and lookup arguments.
 #[derive(Clone, Copy, Debug)]
-struct Beta;
-type ChallengeBeta<F> = ChallengeScalar<F, Beta>;
+pub struct Beta;
+/// The challenge $\beta$ of a proof.
+pub type ChallengeBeta<C> = ChallengeScalar<C, Beta>;
 
+/// Marks the challenge $\gamma$ of the permutation and lookup arguments.
 #[derive(Clone, Copy, Debug)]
-struct Gamma;
-type ChallengeGamma<F> = ChallengeScalar<F, Gamma>;
+pub struct Gamma;
+/// The challenge $\gamma$ of a proof.
+pub type ChallengeGamma<C> = ChallengeScalar<C, Gamma>;
 
+/// Marks the challenge $y$, which combines the constraints into the vanishing
+/// argument.
 #[derive(Clone, Copy, Debug)]
-struct Y;
-type ChallengeY<F> = ChallengeScalar<F, Y>;
+pub struct Y;
+/// The challenge $y$ of a proof.
+pub type ChallengeY<C> = ChallengeScalar<C, Y>;
 
+/// Marks the challenge $x$, at which the polynomials of a proof are evaluated.
 #[derive(Clone, Copy, Debug)]
-struct X;
-type ChallengeX<F> = ChallengeScalar<F, X>;
+pub struct X;
+/// The challenge $x$ of a proof.
+pub type ChallengeX<C> = ChallengeScalar<C, X>;
@@ -0,0 +1,155 @@
+use std::io;
+use std::marker::PhantomData;
+
+use super::{
+    verifier::verify_proof_inner, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
+    ChallengeY, VerifyError, VerifyingKey,
+};
+use crate::arithmetic::CurveAffine;
+use crate::poly::commitment::Params;
+use crate::transcript::{ChallengeScalar, EncodedChallenge, Transcript, TranscriptRead};
+
+/// The verifier challenges of a proof, as recomputed by [`proof_challenges`].
+#[derive(Clone, Debug)]
+pub struct ProofChallenges<C: CurveAffine> {
+    theta: ChallengeTheta<C>,
+    beta: ChallengeBeta<C>,
+    gamma: ChallengeGamma<C>,
+    y: ChallengeY<C>,
+    x: ChallengeX<C>,
+    all: Vec<(&'static str, C::Scalar)>,
+}
+
+impl<C: CurveAffine> ProofChallenges<C> {
+    /// Returns the challenge $\theta$.
+    pub fn theta(&self) -> ChallengeTheta<C> {
+        self.theta
+    }
+
+    /// Returns the challenge $\beta$.
+    pub fn beta(&self) -> ChallengeBeta<C> {
+        self.beta
+    }
+
+    /// Returns the challenge $\gamma$.
+    pub fn gamma(&self) -> ChallengeGamma<C> {
+        self.gamma
+    }
+
+    /// Returns the challenge $y$.
+    pub fn y(&self) -> ChallengeY<C> {
+        self.y
+    }
+
+    /// Returns the challenge $x$.
+    pub fn x(&self) -> ChallengeX<C> {
+        self.x
+    }
+
+    /// Returns every challenge squeezed from the transcript, in order, along with its
+    /// domain-separation label.
+    ///
+    /// This includes the challenges of the multiopen argument and of the inner product
+    /// argument, which follow $x$.
+    pub fn all(&self) -> &[(&'static str, C::Scalar)] {
+        &self.all
+    }
+}
+
+/// Forwards to a transcript, recording each challenge squeezed from it.
+struct RecordingTranscript<'t, C: CurveAffine, T> {
+    inner: &'t mut T,
+    challenges: Vec<(&'static str, C::Scalar)>,
+    _marker: PhantomData<C>,
+}
+
+impl<'t, C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for RecordingTranscript<'t, C, T>
+{
+    fn squeeze_challenge(&mut self, label: &'static str) -> E {
+        let challenge = self.inner.squeeze_challenge(label);
+        self.challenges.push((label, challenge.get_scalar()));
+        challenge
+    }
+
+    fn common_point(&mut self, label: &'static str, point: C) -> io::Result<()> {
+        self.inner.common_point(label, point)
+    }
+
+    fn common_scalar(&mut self, label: &'static str, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(label, scalar)
+    }
+}
+
+impl<'t, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+    for RecordingTranscript<'t, C, T>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        self.inner.read_point()
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        self.inner.read_scalar()
+    }
+}
+
+/// Recomputes the verifier challenges of a proof.
+///
+/// This reads the proof from `transcript` exactly as [`verify_proof`] does, and
+/// returns the challenges that it squeezes. It is intended for auditors, and for
+/// alternative verifier implementations that need to check their transcripts against
+/// this one.
+///
+/// The proof is checked up to the final multiexponentiation, which is not evaluated;
+/// use [`verify_proof`] to fully verify it. Like [`verify_proof`], this returns
+/// [`VerifyError::InstanceValuesRequired`] for circuits that bind proofs to their
+/// instance values.
+///
+/// [`verify_proof`]: super::verify_proof
+pub fn proof_challenges<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    instance_commitments: &[&[C]],
+    transcript: &mut T,
+) -> Result<ProofChallenges<C>, VerifyError> {
+    if vk.cs.binds_instance_values {
+        return Err(VerifyError::InstanceValuesRequired);
+    }
+
+    let mut recording = RecordingTranscript {
+        inner: transcript,
+        challenges: vec![],
+        _marker: PhantomData,
+    };
+    verify_proof_inner::<C, E, _>(
+        params,
+        vk,
+        params.empty_msm(),
+        instance_commitments,
+        None,
+        &mut recording,
+    )?;
+
+    // The verifier squeezes each of these exactly once before succeeding.
+    let all = recording.challenges;
+    let find = |label| {
+        all.iter()
+            .find(|(l, _)| *l == label)
+            .map(|(_, value)| *value)
+            .unwrap()
+    };
+    let theta = ChallengeScalar::new(find("theta"));
+    let beta = ChallengeScalar::new(find("beta"));
+    let gamma = ChallengeScalar::new(find("gamma"));
+    let y = ChallengeScalar::new(find("y"));
+    let x = ChallengeScalar::new(find("x"));
+
+    Ok(ProofChallenges {
+        theta,
+        beta,
+        gamma,
+        y,
+        x,
+        all,
+    })
+}
@@ -3,6 +3,7 @@ use core::ops::{Add, Mul};
 use ff::Field;
 use std::{
     convert::TryFrom,
+    fmt, iter,
     ops::{Neg, Sub},
 };
 
@@ -24,7 +25,6 @@ pub struct Column<C: ColumnType> {
 }
 
 impl<C: ColumnType> Column<C> {
-    #[cfg(test)]
     pub(crate) fn new(index: usize, column_type: C) -> Self {
         Column { index, column_type }
     }
@@ -237,6 +237,33 @@ impl Selector {
     }
 }
 
+/// A sequence of columns (and selectors) within which a region may be laid out.
+///
+/// See [`ConstraintSystem::interchangeable_lanes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lane {
+    columns: Vec<Column<Any>>,
+}
+
+impl Lane {
+    /// Creates a lane from the given columns, followed by the fixed columns of the
+    /// given selectors.
+    pub fn new(columns: &[Column<Any>], selectors: &[Selector]) -> Self {
+        Lane {
+            columns: columns
+                .iter()
+                .cloned()
+                .chain(selectors.iter().map(|selector| selector.0.into()))
+                .collect(),
+        }
+    }
+
+    /// Returns the columns of this lane, in order.
+    pub fn columns(&self) -> &[Column<Any>] {
+        &self.columns
+    }
+}
+
 /// A permutation.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Permutation {
@@ -252,6 +279,12 @@ impl Permutation {
         meta.permutation(columns)
     }
 
+    /// Reconstructs a permutation from its index and mapping, as returned by
+    /// [`Permutation::index`] and [`Permutation::mapping`].
+    pub(crate) fn from_parts(index: usize, mapping: Vec<Column<Any>>) -> Self {
+        Permutation { index, mapping }
+    }
+
     /// Returns index of permutation
     pub fn index(&self) -> usize {
         self.index
@@ -261,6 +294,39 @@ impl Permutation {
     pub fn mapping(&self) -> &[Column<Any>] {
         &self.mapping
     }
+
+    /// Returns the indices, within this permutation, of the columns of a copy
+    /// constraint between two cells of a circuit with `n` rows.
+    ///
+    /// Returns [`Error::InvalidCopy`] if either column is not part of this permutation,
+    /// or either row is out of bounds. The error records the region and namespace path
+    /// in which the copy constraint was requested.
+    pub(crate) fn copy_indices(
+        &self,
+        n: usize,
+        region: Option<&str>,
+        namespace: &[String],
+        left: (Column<Any>, usize),
+        right: (Column<Any>, usize),
+    ) -> Result<(usize, usize), Error> {
+        let index = |(column, row): (Column<Any>, usize)| {
+            self.mapping
+                .iter()
+                .position(|c| *c == column)
+                .filter(|_| row < n)
+        };
+
+        match (index(left), index(right)) {
+            (Some(left), Some(right)) => Ok((left, right)),
+            _ => Err(Error::InvalidCopy {
+                region: region.map(String::from),
+                namespace: namespace.to_vec(),
+                permutation: self.index,
+                left,
+                right,
+            }),
+        }
+    }
 }
 
 /// A value assigned to a cell within a circuit.
@@ -443,6 +509,13 @@ pub trait Assignment<F: Field> {
     /// [`Layouter::assign_region`]: crate::circuit::Layouter#method.assign_region
     fn exit_region(&mut self);
 
+    /// Records the rows that the floor planner reserved for the current region: `rows`
+    /// rows, starting at row `start`.
+    ///
+    /// Floor planners call this after [`Assignment::enter_region`]. The default
+    /// implementation does nothing.
+    fn place_region(&mut self, _start: usize, _rows: usize) {}
+
     /// Enables a selector at the given row.
     fn enable_selector<A, AR>(
         &mut self,
@@ -482,6 +555,82 @@ pub trait Assignment<F: Field> {
         A: FnOnce() -> AR,
         AR: Into<String>;
 
+    /// Assign a contiguous run of advice values (witnesses), starting at the given row.
+    ///
+    /// `None` values are unknown; they should be treated in the same way as an
+    /// assignment whose value closure returns [`Error::SynthesisError`].
+    ///
+    /// The default implementation calls [`Assignment::assign_advice`] for each value.
+    fn assign_advice_slice<A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let annotation: String = annotation().into();
+        for (offset, value) in values.iter().enumerate() {
+            self.assign_advice(
+                || annotation.as_str(),
+                column,
+                row + offset,
+                || value.ok_or(Error::SynthesisError),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Assign a contiguous run of fixed values, starting at the given row.
+    ///
+    /// The default implementation calls [`Assignment::assign_fixed`] for each value.
+    fn assign_fixed_slice<A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        values: &[Assigned<F>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let annotation: String = annotation().into();
+        for (offset, value) in values.iter().enumerate() {
+            self.assign_fixed(|| annotation.as_str(), column, row + offset, || Ok(*value))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the value that the circuit assigned to the given fixed cell.
+    ///
+    /// Returns `Ok(None)` if the value is not known to this backend; the default
+    /// implementation always does so.
+    fn fixed_value(&self, _column: Column<Fixed>, _row: usize) -> Result<Option<F>, Error> {
+        Ok(None)
+    }
+
+    /// Returns the value of the given instance cell.
+    ///
+    /// Returns `Ok(None)` if the value is not known to this backend, as is the case
+    /// during key generation; the default implementation always does so.
+    fn query_instance(&self, _column: Column<Instance>, _row: usize) -> Result<Option<F>, Error> {
+        Ok(None)
+    }
+
+    /// Marks the current region as instance-dependent.
+    ///
+    /// Not intended for downstream consumption; use [`Region::mark_instance_dependent`]
+    /// instead. The default implementation does nothing.
+    ///
+    /// [`Region::mark_instance_dependent`]: crate::circuit::Region::mark_instance_dependent
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Assign two cells to have the same value
     fn copy(
         &mut self,
@@ -547,6 +696,21 @@ pub trait Circuit<F: Field> {
     /// arrangement, column arrangement, etc.
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config;
 
+    /// Configures the circuit from this value of it, rather than from its type alone.
+    ///
+    /// Key generation, proving and the development tools call this when they are
+    /// given a value of the circuit, so circuits whose configuration is only known at
+    /// runtime (such as [`IrCircuit`]) can override it. Functions that are only given
+    /// the type of the circuit, such as [`CircuitShape::new`], call
+    /// [`Circuit::configure`] instead. The default implementation calls
+    /// [`Circuit::configure`].
+    ///
+    /// [`IrCircuit`]: crate::interop::ir::IrCircuit
+    /// [`CircuitShape::new`]: crate::plonk::CircuitShape::new
+    fn configure_from(&self, meta: &mut ConstraintSystem<F>) -> Self::Config {
+        Self::configure(meta)
+    }
+
     /// Given the provided `cs`, synthesize the circuit. The concrete type of
     /// the caller will be different depending on the context, and they may or
     /// may not expect to have a witness present.
@@ -575,6 +739,10 @@ pub enum Expression<F> {
 impl<F: Field> Expression<F> {
     /// Evaluate the polynomial using the provided closures to perform the
     /// operations.
+    ///
+    /// The expression is traversed with an explicit stack rather than by recursion, so
+    /// deeply nested expressions (such as long chains of sums) do not overflow the call
+    /// stack. Subexpressions are evaluated from left to right.
     pub fn evaluate<T>(
         &self,
         constant: &impl Fn(F) -> T,
@@ -585,85 +753,368 @@ impl<F: Field> Expression<F> {
         product: &impl Fn(T, T) -> T,
         scaled: &impl Fn(T, F) -> T,
     ) -> T {
-        match self {
-            Expression::Constant(scalar) => constant(*scalar),
-            Expression::Fixed(index) => fixed_column(*index),
-            Expression::Advice(index) => advice_column(*index),
-            Expression::Instance(index) => instance_column(*index),
-            Expression::Sum(a, b) => {
-                let a = a.evaluate(
-                    constant,
-                    fixed_column,
-                    advice_column,
-                    instance_column,
-                    sum,
-                    product,
-                    scaled,
-                );
-                let b = b.evaluate(
-                    constant,
-                    fixed_column,
-                    advice_column,
-                    instance_column,
-                    sum,
-                    product,
-                    scaled,
-                );
-                sum(a, b)
+        /// A step of the traversal: either an expression to evaluate, or an operation
+        /// to apply to the values of the subexpressions that were evaluated last.
+        enum Step<'a, F> {
+            Evaluate(&'a Expression<F>),
+            Sum,
+            Product,
+            Scaled(F),
+        }
+
+        let mut steps = vec![Step::Evaluate(self)];
+        let mut values = vec![];
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Evaluate(expression) => match expression {
+                    Expression::Constant(scalar) => values.push(constant(*scalar)),
+                    Expression::Fixed(index) => values.push(fixed_column(*index)),
+                    Expression::Advice(index) => values.push(advice_column(*index)),
+                    Expression::Instance(index) => values.push(instance_column(*index)),
+                    Expression::Sum(a, b) => {
+                        steps.push(Step::Sum);
+                        steps.push(Step::Evaluate(b));
+                        steps.push(Step::Evaluate(a));
+                    }
+                    Expression::Product(a, b) => {
+                        steps.push(Step::Product);
+                        steps.push(Step::Evaluate(b));
+                        steps.push(Step::Evaluate(a));
+                    }
+                    Expression::Scaled(a, f) => {
+                        steps.push(Step::Scaled(*f));
+                        steps.push(Step::Evaluate(a));
+                    }
+                },
+                Step::Sum => {
+                    let b = values.pop().unwrap();
+                    let a = values.pop().unwrap();
+                    values.push(sum(a, b));
+                }
+                Step::Product => {
+                    let b = values.pop().unwrap();
+                    let a = values.pop().unwrap();
+                    values.push(product(a, b));
+                }
+                Step::Scaled(f) => {
+                    let a = values.pop().unwrap();
+                    values.push(scaled(a, f));
+                }
             }
-            Expression::Product(a, b) => {
-                let a = a.evaluate(
-                    constant,
-                    fixed_column,
-                    advice_column,
-                    instance_column,
-                    sum,
-                    product,
-                    scaled,
-                );
-                let b = b.evaluate(
-                    constant,
-                    fixed_column,
-                    advice_column,
-                    instance_column,
-                    sum,
-                    product,
-                    scaled,
-                );
-                product(a, b)
+        }
+
+        values
+            .pop()
+            .expect("evaluating an expression produces a value")
+    }
+
+    /// Returns the linear combination $c_0 \cdot e_0 + c_1 \cdot e_1 + \cdots$ of the
+    /// given `(coefficient, expression)` terms.
+    ///
+    /// The terms are combined with [`Expression::sum_balanced`], so the depth of the
+    /// result grows logarithmically with the number of terms. Coefficients of one are
+    /// omitted, and no terms give the constant zero.
+    pub fn from_terms(terms: impl IntoIterator<Item = (F, Expression<F>)>) -> Self {
+        Self::sum_balanced(terms.into_iter().map(|(coefficient, expression)| {
+            if coefficient == F::one() {
+                expression
+            } else {
+                expression * coefficient
             }
-            Expression::Scaled(a, f) => {
-                let a = a.evaluate(
-                    constant,
-                    fixed_column,
-                    advice_column,
-                    instance_column,
-                    sum,
-                    product,
-                    scaled,
-                );
-                scaled(a, *f)
+        }))
+    }
+
+    /// Returns the sum of `expressions`, as a balanced tree of [`Expression::Sum`]
+    /// nodes.
+    ///
+    /// Adding expressions one at a time with `+` builds a chain as deep as the number
+    /// of expressions; this builds a tree whose depth is logarithmic in it instead. No
+    /// expressions give the constant zero.
+    pub fn sum_balanced(expressions: impl IntoIterator<Item = Expression<F>>) -> Self {
+        let mut level: Vec<_> = expressions.into_iter().collect();
+        if level.is_empty() {
+            return Expression::Constant(F::zero());
+        }
+
+        // Sum adjacent pairs until a single expression is left.
+        while level.len() > 1 {
+            let mut next = Vec::with_capacity((level.len() + 1) / 2);
+            let mut expressions = level.into_iter();
+            while let Some(a) = expressions.next() {
+                next.push(match expressions.next() {
+                    Some(b) => a + b,
+                    None => a,
+                });
             }
+            level = next;
         }
+        level.pop().unwrap()
     }
 
     /// Compute the degree of this polynomial
     pub fn degree(&self) -> usize {
-        match self {
-            Expression::Constant(_) => 0,
-            Expression::Fixed(_) => 1,
-            Expression::Advice(_) => 1,
-            Expression::Instance(_) => 1,
-            Expression::Sum(a, b) => max(a.degree(), b.degree()),
-            Expression::Product(a, b) => a.degree() + b.degree(),
-            Expression::Scaled(poly, _) => poly.degree(),
-        }
+        self.evaluate(
+            &|_| 0,
+            &|_| 1,
+            &|_| 1,
+            &|_| 1,
+            &|a, b| max(a, b),
+            &|a, b| a + b,
+            &|a, _| a,
+        )
     }
 
     /// Square this expression.
     pub fn square(self) -> Self {
         self.clone() * self
     }
+
+    /// Raises this expression to the power `exponent`.
+    ///
+    /// The result is a balanced tree of products, so its depth is logarithmic in the
+    /// exponent. Raising to the power zero gives the constant one.
+    pub fn pow(&self, exponent: u32) -> Self {
+        match exponent {
+            0 => Expression::Constant(F::one()),
+            1 => self.clone(),
+            _ => {
+                let half = exponent / 2;
+                self.pow(half) * self.pow(exponent - half)
+            }
+        }
+    }
+
+    /// Returns a canonical string identifying the structure of this expression.
+    ///
+    /// Queries are identified by their index, and each query of a `ConstraintSystem`
+    /// is of a distinct column and rotation. Two expressions created for the same
+    /// `ConstraintSystem` therefore have the same identifier exactly when they are
+    /// structurally identical.
+    pub fn identifier(&self) -> String {
+        let mut identifier = String::new();
+        self.write_identifier(&mut identifier);
+        identifier
+    }
+
+    fn write_identifier(&self, out: &mut String) {
+        // Like `evaluate`, this uses an explicit stack so that deeply nested expressions
+        // do not overflow the call stack.
+        enum Step<'a, F> {
+            Write(&'a Expression<F>),
+            Text(&'static str),
+            Scale(F),
+        }
+
+        let mut steps = vec![Step::Write(self)];
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Text(text) => out.push_str(text),
+                Step::Scale(scalar) => out.push_str(&format!("*{:?}", scalar)),
+                Step::Write(expression) => match expression {
+                    Expression::Constant(scalar) => out.push_str(&format!("{:?}", scalar)),
+                    Expression::Fixed(index) => out.push_str(&format!("fixed_query[{}]", index)),
+                    Expression::Advice(index) => out.push_str(&format!("advice_query[{}]", index)),
+                    Expression::Instance(index) => {
+                        out.push_str(&format!("instance_query[{}]", index))
+                    }
+                    Expression::Sum(a, b) => {
+                        out.push('(');
+                        steps.push(Step::Text(")"));
+                        steps.push(Step::Write(b));
+                        steps.push(Step::Text("+"));
+                        steps.push(Step::Write(a));
+                    }
+                    Expression::Product(a, b) => {
+                        out.push('(');
+                        steps.push(Step::Text(")"));
+                        steps.push(Step::Write(b));
+                        steps.push(Step::Text("*"));
+                        steps.push(Step::Write(a));
+                    }
+                    Expression::Scaled(a, scalar) => {
+                        out.push('(');
+                        steps.push(Step::Text(")"));
+                        steps.push(Step::Scale(*scalar));
+                        steps.push(Step::Write(a));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Returns a value that renders this expression as a human-readable polynomial,
+    /// naming the column and rotation of each query in `cs`.
+    ///
+    /// For example, a gate checking that an advice cell is boolean is rendered as
+    /// `advice[0][0] * (advice[0][0] - 1)`.
+    pub fn display<'a>(&'a self, cs: &'a ConstraintSystem<F>) -> DisplayExpression<'a, F> {
+        DisplayExpression {
+            expression: self,
+            cs,
+        }
+    }
+}
+
+/// A use of one of the logical tables of a tagged lookup. See
+/// [`ConstraintSystem::lookup_tagged`].
+#[derive(Clone, Debug)]
+pub struct TaggedInput<F: Field> {
+    /// The selector that enables this use of the table.
+    pub selector: Selector,
+    /// The tag of the logical table. This must not be zero.
+    pub tag: F,
+    /// The expressions to look up in the table columns, in order.
+    pub inputs: Vec<Expression<F>>,
+}
+
+/// Combines `expressions` into a single expression using powers of `factor`, in Horner
+/// form.
+///
+/// For expressions $e_0, \ldots, e_{n-1}$ this returns
+/// $e_0 \cdot f^{n-1} + e_1 \cdot f^{n-2} + \cdots + e_{n-1}$, which can be used to
+/// compress several values into one lookup input (with the same `factor` applied to
+/// the table). An empty slice gives the constant zero.
+pub fn horner<F: Field>(expressions: &[Expression<F>], factor: F) -> Expression<F> {
+    let mut expressions = expressions.iter().cloned();
+    match expressions.next() {
+        Some(first) => expressions.fold(first, |acc, expression| acc * factor + expression),
+        None => Expression::Constant(F::zero()),
+    }
+}
+
+/// Renders an [`Expression`] as a human-readable polynomial. Created by
+/// [`Expression::display`].
+#[derive(Debug)]
+pub struct DisplayExpression<'a, F: Field> {
+    expression: &'a Expression<F>,
+    cs: &'a ConstraintSystem<F>,
+}
+
+impl<'a, F: FieldExt> DisplayExpression<'a, F> {
+    /// Writes `expression`, parenthesizing it if it binds less tightly than
+    /// `precedence` (0 for sums, 1 for products, 2 for atoms).
+    fn write(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        expression: &Expression<F>,
+        precedence: u8,
+    ) -> fmt::Result {
+        fn query<C: ColumnType>(
+            f: &mut fmt::Formatter<'_>,
+            kind: &str,
+            queries: &[(Column<C>, Rotation)],
+            index: usize,
+        ) -> fmt::Result {
+            match queries.get(index) {
+                Some((column, rotation)) => {
+                    write!(f, "{}[{}][{}]", kind, column.index(), rotation.0)
+                }
+                // The expression was not created for this constraint system.
+                None => write!(f, "{}_query[{}]", kind, index),
+            }
+        }
+
+        // Subexpressions are written from an explicit stack, so that deeply nested
+        // expressions do not overflow the call stack.
+        enum Step<'e, F> {
+            Write(&'e Expression<F>, u8),
+            Text(&'static str),
+        }
+
+        let is_negation = |scalar: &F| *scalar == -F::one();
+        let mut steps = vec![Step::Write(expression, precedence)];
+        while let Some(step) = steps.pop() {
+            let (expression, precedence) = match step {
+                Step::Write(expression, precedence) => (expression, precedence),
+                Step::Text(text) => {
+                    f.write_str(text)?;
+                    continue;
+                }
+            };
+
+            let binding = match expression {
+                Expression::Sum(_, _) => 0,
+                Expression::Product(_, _) => 1,
+                Expression::Scaled(_, scalar) if !is_negation(scalar) => 1,
+                _ => 2,
+            };
+            if binding < precedence {
+                write!(f, "(")?;
+                steps.push(Step::Text(")"));
+            }
+
+            match expression {
+                Expression::Constant(scalar) => write_constant(f, *scalar)?,
+                Expression::Fixed(index) => query(f, "fixed", &self.cs.fixed_queries, *index)?,
+                Expression::Advice(index) => query(f, "advice", &self.cs.advice_queries, *index)?,
+                Expression::Instance(index) => {
+                    query(f, "instance", &self.cs.instance_queries, *index)?
+                }
+                Expression::Sum(a, b) => {
+                    match &**b {
+                        // Render `a + (-1 * b)` as a subtraction.
+                        Expression::Scaled(b, scalar) if is_negation(scalar) => {
+                            steps.push(Step::Write(b, 1));
+                            steps.push(Step::Text(" - "));
+                        }
+                        b => {
+                            steps.push(Step::Write(b, 0));
+                            steps.push(Step::Text(" + "));
+                        }
+                    }
+                    steps.push(Step::Write(a, 0));
+                }
+                Expression::Product(a, b) => {
+                    steps.push(Step::Write(b, 1));
+                    steps.push(Step::Text(" * "));
+                    steps.push(Step::Write(a, 1));
+                }
+                Expression::Scaled(a, scalar) if is_negation(scalar) => {
+                    write!(f, "-")?;
+                    steps.push(Step::Write(a, 2));
+                }
+                Expression::Scaled(a, scalar) => {
+                    write_constant(f, *scalar)?;
+                    write!(f, " * ")?;
+                    steps.push(Step::Write(a, 1));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, F: FieldExt> fmt::Display for DisplayExpression<'a, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, self.expression, 0)
+    }
+}
+
+/// Writes a field element as a (possibly negative) integer if it is small, and in
+/// hexadecimal otherwise.
+fn write_constant<F: FieldExt>(f: &mut fmt::Formatter<'_>, scalar: F) -> fmt::Result {
+    fn small<F: FieldExt>(scalar: F) -> Option<u64> {
+        let bytes = scalar.to_bytes();
+        if bytes[8..].iter().all(|b| *b == 0) {
+            let mut low = [0; 8];
+            low.copy_from_slice(&bytes[..8]);
+            Some(u64::from_le_bytes(low))
+        } else {
+            None
+        }
+    }
+
+    if let Some(value) = small(scalar) {
+        write!(f, "{}", value)
+    } else if let Some(value) = small(-scalar) {
+        write!(f, "-{}", value)
+    } else {
+        write!(f, "0x")?;
+        for byte in scalar.to_bytes().iter().rev() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
 }
 
 impl<F: Field> Neg for Expression<F> {
@@ -708,7 +1159,7 @@ pub(crate) struct PointIndex(pub usize);
 
 /// A "virtual cell" is a PLONK cell that has been queried at a particular relative offset
 /// within a custom gate.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct VirtualCell {
     pub(crate) column: Column<Any>,
     pub(crate) rotation: Rotation,
@@ -759,6 +1210,10 @@ pub(crate) struct Gate<F: Field> {
     /// trigger debug checks on gates.
     queried_selectors: Vec<Selector>,
     queried_cells: Vec<VirtualCell>,
+    /// The maximum degree of the constraints, computed when the gate is created.
+    degree: usize,
+    /// The identifiers of the constraints, used to detect duplicate gates.
+    identifier: String,
 }
 
 impl<F: Field> Gate<F> {
@@ -774,6 +1229,10 @@ impl<F: Field> Gate<F> {
         &self.polys
     }
 
+    pub(crate) fn degree(&self) -> usize {
+        self.degree
+    }
+
     pub(crate) fn queried_selectors(&self) -> &[Selector] {
         &self.queried_selectors
     }
@@ -802,6 +1261,89 @@ pub struct ConstraintSystem<F: Field> {
     // Vector of lookup arguments, where each corresponds to a sequence of
     // input expressions and a sequence of table expressions involved in the lookup.
     pub(crate) lookups: Vec<lookup::Argument<F>>,
+
+    // Groups of lanes between which floor planners may move regions. These only affect
+    // the layout of the circuit, and so are not part of the pinned constraint system.
+    pub(crate) lanes: Vec<Vec<Lane>>,
+
+    // Fixed columns into which floor planners may assign constants. Like lanes, these
+    // only affect the layout of the circuit.
+    pub(crate) constants: Vec<Column<Fixed>>,
+
+    // The minimum degree of the constraint system, which may be larger than the degree
+    // required by the gates and arguments. It determines the size of the extended
+    // domain, which is already part of the pinned verification key.
+    pub(crate) minimum_degree: Option<usize>,
+
+    // The maximum degree of each lookup argument, if it has been bounded, and whether
+    // lookup inputs that would exceed it are split through intermediate advice columns.
+    pub(crate) max_lookup_degree: Option<usize>,
+    pub(crate) split_lookup_inputs: bool,
+
+    // Advice columns holding split lookup inputs, with the input expressions whose
+    // values they hold. The backend assigns them after synthesizing the circuit.
+    pub(crate) lookup_intermediates: Vec<(Column<Advice>, Expression<F>)>,
+
+    // Whether key generation merges fixed columns with identical values. The encoding
+    // of verifying keys depends on this, so it is covered by the digest of the shape.
+    pub(crate) compress_fixed_columns: bool,
+
+    // Existing columns that are handed out in place of newly-allocated ones, while the
+    // second half of a composed circuit is being configured.
+    pub(crate) sharing: Option<ColumnSharing>,
+
+    // Whether the instance values themselves, rather than only their commitments, are
+    // absorbed into the transcript. The prover and verifier absorb different data if
+    // they disagree, so this does not need to be part of the pinned constraint system.
+    pub(crate) binds_instance_values: bool,
+
+    // Advice columns that are committed to without blinding. Blinding only affects the
+    // prover, so these are not part of the pinned constraint system either.
+    pub(crate) unblinded_advice: Vec<Column<Advice>>,
+
+    // Instance columns whose values are fixed at configure time. Their commitments are
+    // part of the verifying key, which pins them separately.
+    pub(crate) constant_instances: Vec<(Column<Instance>, Vec<F>)>,
+
+    // Gates that were created without any constraints. Configuration cannot return
+    // errors, so these are reported when the constraint system is checked against the
+    // parameters of the circuit.
+    pub(crate) empty_gates: Vec<&'static str>,
+}
+
+/// Redirects column allocations to existing columns of a constraint system.
+#[derive(Debug, Clone)]
+pub(crate) struct ColumnSharing {
+    /// The number of (fixed, advice, instance) columns that have been requested so far.
+    requested: (usize, usize, usize),
+    /// Pairs of (requested column, existing column).
+    shared: Vec<(Column<Any>, Column<Any>)>,
+}
+
+impl ColumnSharing {
+    pub(crate) fn new(shared: Vec<(Column<Any>, Column<Any>)>) -> Self {
+        ColumnSharing {
+            requested: (0, 0, 0),
+            shared,
+        }
+    }
+
+    /// Records a request for a new column of the given type, and returns the index of
+    /// the existing column that should be used instead, if any.
+    fn request(&mut self, column_type: Any) -> Option<usize> {
+        let requested = match column_type {
+            Any::Fixed => &mut self.requested.0,
+            Any::Advice => &mut self.requested.1,
+            Any::Instance => &mut self.requested.2,
+        };
+        let column = Column::new(*requested, column_type);
+        *requested += 1;
+
+        self.shared
+            .iter()
+            .find(|(requested, _)| *requested == column)
+            .map(|(_, existing)| existing.index())
+    }
 }
 
 /// Represents the minimal parameters that determine a `ConstraintSystem`.
@@ -828,6 +1370,153 @@ impl<'a, F: Field> std::fmt::Debug for PinnedGates<'a, F> {
     }
 }
 
+/// A single difference between two pinned representations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PinnedDifference {
+    /// The location of the differing item, such as `gates[1] ('Public input')[0]`.
+    pub path: String,
+    /// The item on the left-hand side, or `None` if it only exists on the right.
+    pub left: Option<String>,
+    /// The item on the right-hand side, or `None` if it only exists on the left.
+    pub right: Option<String>,
+}
+
+/// A structured diff between two pinned representations, as returned by
+/// [`PinnedConstraintSystem::diff`] and [`PinnedVerificationKey::diff`].
+///
+/// Items are compared by their `Debug` representations, which are exactly what gets
+/// hashed into the transcript when a verification key is pinned.
+///
+/// [`PinnedVerificationKey::diff`]: crate::plonk::PinnedVerificationKey::diff
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PinnedDiff {
+    differences: Vec<PinnedDifference>,
+}
+
+impl PinnedDiff {
+    /// Returns `true` if the two pinned representations are identical.
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Returns the differences, in the order in which they were found.
+    pub fn differences(&self) -> &[PinnedDifference] {
+        &self.differences
+    }
+
+    fn push(&mut self, path: String, left: Option<String>, right: Option<String>) {
+        self.differences
+            .push(PinnedDifference { path, left, right });
+    }
+
+    /// Compares a single item.
+    pub(crate) fn item<T: fmt::Debug + ?Sized>(&mut self, path: &str, left: &T, right: &T) {
+        let left = format!("{:?}", left);
+        let right = format!("{:?}", right);
+        if left != right {
+            self.push(path.to_owned(), Some(left), Some(right));
+        }
+    }
+
+    /// Compares two lists element-wise, recording any trailing elements that only
+    /// exist on one side.
+    pub(crate) fn list<T: fmt::Debug>(&mut self, path: &str, left: &[T], right: &[T]) {
+        for i in 0..max(left.len(), right.len()) {
+            let path = format!("{}[{}]", path, i);
+            match (left.get(i), right.get(i)) {
+                (Some(left), Some(right)) => self.item(&path, left, right),
+                (left, right) => self.push(
+                    path,
+                    left.map(|item| format!("{:?}", item)),
+                    right.map(|item| format!("{:?}", item)),
+                ),
+            }
+        }
+    }
+
+    /// Appends the differences from `other`, nesting their paths under `prefix`.
+    pub(crate) fn extend(&mut self, prefix: &str, other: PinnedDiff) {
+        for difference in other.differences {
+            self.push(
+                format!("{}.{}", prefix, difference.path),
+                difference.left,
+                difference.right,
+            );
+        }
+    }
+}
+
+impl fmt::Display for PinnedDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.differences.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        for difference in &self.differences {
+            writeln!(f, "{}:", difference.path)?;
+            if let Some(left) = &difference.left {
+                writeln!(f, "  - {}", left)?;
+            }
+            if let Some(right) = &difference.right {
+                writeln!(f, "  + {}", right)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, F: Field> PinnedConstraintSystem<'a, F> {
+    /// Computes a structured diff between this pinned constraint system and `other`.
+    ///
+    /// Gate names are not part of the pinned representation, so they are only used to
+    /// annotate the paths of differing gates.
+    pub fn diff(&self, other: &Self) -> PinnedDiff {
+        let mut diff = PinnedDiff::default();
+
+        diff.item(
+            "num_fixed_columns",
+            self.num_fixed_columns,
+            other.num_fixed_columns,
+        );
+        diff.item(
+            "num_advice_columns",
+            self.num_advice_columns,
+            other.num_advice_columns,
+        );
+        diff.item(
+            "num_instance_columns",
+            self.num_instance_columns,
+            other.num_instance_columns,
+        );
+
+        for i in 0..max(self.gates.0.len(), other.gates.0.len()) {
+            match (self.gates.0.get(i), other.gates.0.get(i)) {
+                (Some(left), Some(right)) => diff.list(
+                    &format!("gates[{}] ('{}')", i, left.name()),
+                    left.polynomials(),
+                    right.polynomials(),
+                ),
+                (left, right) => diff.push(
+                    format!("gates[{}]", i),
+                    left.map(|gate| format!("{:?}", gate.polynomials())),
+                    right.map(|gate| format!("{:?}", gate.polynomials())),
+                ),
+            }
+        }
+
+        diff.list("advice_queries", self.advice_queries, other.advice_queries);
+        diff.list(
+            "instance_queries",
+            self.instance_queries,
+            other.instance_queries,
+        );
+        diff.list("fixed_queries", self.fixed_queries, other.fixed_queries);
+        diff.list("permutations", self.permutations, other.permutations);
+        diff.list("lookups", self.lookups, other.lookups);
+
+        diff
+    }
+}
+
 impl<F: Field> Default for ConstraintSystem<F> {
     fn default() -> ConstraintSystem<F> {
         ConstraintSystem {
@@ -840,6 +1529,18 @@ impl<F: Field> Default for ConstraintSystem<F> {
             instance_queries: Vec::new(),
             permutations: Vec::new(),
             lookups: Vec::new(),
+            lanes: Vec::new(),
+            constants: Vec::new(),
+            minimum_degree: None,
+            max_lookup_degree: None,
+            split_lookup_inputs: false,
+            lookup_intermediates: Vec::new(),
+            compress_fixed_columns: false,
+            sharing: None,
+            binds_instance_values: false,
+            unblinded_advice: Vec::new(),
+            constant_instances: Vec::new(),
+            empty_gates: Vec::new(),
         }
     }
 }
@@ -863,6 +1564,12 @@ impl<F: Field> ConstraintSystem<F> {
     }
 
     /// Add a permutation argument for some columns
+    ///
+    /// If the argument's constraints would have a higher degree than the gates and
+    /// lookup arguments of the circuit can accommodate without a larger extended
+    /// domain, the columns are split into chunks, each with its own product polynomial.
+    /// Wide permutations therefore cost additional commitments rather than a larger
+    /// extended domain.
     pub fn permutation(&mut self, columns: &[Column<Any>]) -> Permutation {
         let index = self.permutations.len();
 
@@ -882,20 +1589,301 @@ impl<F: Field> ConstraintSystem<F> {
     ///
     /// `table_map` returns a map between input expressions and the table expressions
     /// they need to match.
+    ///
+    /// Table expressions may query instance columns, so that small public tables (such
+    /// as a whitelist) can be provided as instance values rather than being assigned to
+    /// fixed columns. Every row of the column is part of the table, including the rows
+    /// beyond the provided values, which are zero.
+    ///
+    /// Selectors may be queried with [`VirtualCells::query_selector`] to enable the lookup
+    /// on some rows, typically by multiplying the input expressions by the selector.
+    /// They should be queried this way rather than through their underlying fixed
+    /// column, so that [`MockProver`] can check that the cells used by the inputs are
+    /// assigned wherever the selector is enabled.
+    ///
+    /// [`MockProver`]: crate::dev::MockProver
     pub fn lookup(
         &mut self,
         table_map: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<(Expression<F>, Expression<F>)>,
     ) -> usize {
         let mut cells = VirtualCells::new(self);
         let table_map = table_map(&mut cells);
+        let queried_selectors = cells.queried_selectors;
+
+        // Expand the input expressions into sums of terms, each recording the selectors
+        // and cells that it multiplies together. A cell is then only required to be
+        // assigned where a selector of one of its terms is enabled (or any selector, for
+        // terms without one), so that each use of a tagged lookup only requires its own
+        // inputs.
+        type Term = (Vec<Selector>, Vec<VirtualCell>);
+        let mut terms: Vec<Term> = vec![];
+        for (input, _) in &table_map {
+            terms.extend(input.evaluate(
+                &|_| vec![(vec![], vec![])],
+                &|index| {
+                    let cell = VirtualCell::from(self.fixed_queries[index]);
+                    match queried_selectors
+                        .iter()
+                        .find(|selector| Column::<Any>::from(selector.0) == cell.column)
+                    {
+                        Some(selector) => vec![(vec![*selector], vec![])],
+                        None => vec![(vec![], vec![cell])],
+                    }
+                },
+                &|index| vec![(vec![], vec![VirtualCell::from(self.advice_queries[index])])],
+                // Instance cells are not assigned during synthesis.
+                &|_| vec![(vec![], vec![])],
+                &|a, b| a.into_iter().chain(b).collect(),
+                &|a: Vec<Term>, b: Vec<Term>| {
+                    a.iter()
+                        .flat_map(|(a_selectors, a_cells)| {
+                            b.iter().map(move |(b_selectors, b_cells)| {
+                                (
+                                    a_selectors.iter().chain(b_selectors).cloned().collect(),
+                                    a_cells.iter().chain(b_cells).cloned().collect(),
+                                )
+                            })
+                        })
+                        .collect()
+                },
+                &|a, _| a,
+            ));
+        }
+        let selector_cells = queried_selectors
+            .iter()
+            .map(|selector| {
+                let mut cells: Vec<VirtualCell> = vec![];
+                for (selectors, term_cells) in &terms {
+                    if selectors.is_empty() || selectors.contains(selector) {
+                        for cell in term_cells {
+                            if !cells.contains(cell) {
+                                cells.push(cell.clone());
+                            }
+                        }
+                    }
+                }
+                (*selector, cells)
+            })
+            .collect();
+
+        // The cells that must be assigned are those of the original inputs, so inputs
+        // are only split once they have been determined.
+        let table_map = match self.max_lookup_degree {
+            Some(max_degree) if self.split_lookup_inputs => {
+                self.split_lookup_table_map(table_map, max_degree)
+            }
+            _ => table_map,
+        };
 
         let index = self.lookups.len();
 
-        self.lookups.push(lookup::Argument::new(table_map));
+        self.lookups
+            .push(lookup::Argument::new(table_map, selector_cells));
 
         index
     }
 
+    /// Replaces each input expression that would raise the degree of a lookup argument
+    /// above `max_degree` with a query of a new advice column, and constrains the column
+    /// to equal the input.
+    fn split_lookup_table_map(
+        &mut self,
+        table_map: Vec<(Expression<F>, Expression<F>)>,
+        max_degree: usize,
+    ) -> Vec<(Expression<F>, Expression<F>)> {
+        let table_degree = table_map
+            .iter()
+            .map(|(_, table)| table.degree())
+            .fold(1, max);
+
+        table_map
+            .into_iter()
+            .map(|(input, table)| {
+                if input.degree() <= 1 || 1 + input.degree() + table_degree <= max_degree {
+                    return (input, table);
+                }
+
+                let column = self.advice_column();
+                let query = Expression::Advice(self.query_advice_index(column, Rotation::cur()));
+                self.create_gate("split lookup input", |_| {
+                    vec![input.clone() - query.clone()]
+                });
+                self.lookup_intermediates.push((column, input));
+                (query, table)
+            })
+            .collect()
+    }
+
+    /// Bounds the degree of each lookup argument by `degree`.
+    ///
+    /// Key generation (and [`MockProver::run`]) returns [`Error::LookupDegreeTooHigh`]
+    /// for the first lookup argument whose degree is higher, as reported by
+    /// [`ConstraintSystem::lookup_degrees`]. Lookup arguments are also bounded by the
+    /// largest degree for which the field has an extended domain, even if this is not
+    /// called.
+    ///
+    /// [`MockProver::run`]: crate::dev::MockProver::run
+    pub fn set_max_lookup_degree(&mut self, degree: usize) {
+        self.max_lookup_degree = Some(degree);
+    }
+
+    /// Splits the inputs of lookup arguments that would exceed the degree set with
+    /// [`ConstraintSystem::set_max_lookup_degree`].
+    ///
+    /// Each such input expression (in lookup arguments added after this is called) is
+    /// replaced by a query of a new advice column, which a gate constrains to equal the
+    /// expression on every row. The backend assigns these columns after synthesizing
+    /// the circuit, so the circuit does not assign them itself. Each split input costs
+    /// an advice column and a gate of the input's degree; inputs that would still
+    /// exceed the bound after splitting (because the table expressions alone have too
+    /// high a degree) are reported by key generation as usual.
+    pub fn enable_lookup_splitting(&mut self) {
+        self.split_lookup_inputs = true;
+    }
+
+    /// Merges fixed columns with identical values during key generation.
+    ///
+    /// Composing chips often produces several fixed columns (such as selectors) that
+    /// are assigned exactly the same values. With this enabled, [`keygen_vk`] queries
+    /// the first of each set of identical columns in place of the others, so that the
+    /// verifier reads and opens one commitment for all of them, and the encoding of the
+    /// verifying key records each distinct commitment once.
+    ///
+    /// Columns are merged according to the values assigned to them, so the verifying
+    /// key of a circuit with this enabled can only be read back (with
+    /// [`VerifyingKey::read`]) for the same circuit, which must also enable it.
+    ///
+    /// [`keygen_vk`]: crate::plonk::keygen_vk
+    /// [`VerifyingKey::read`]: crate::plonk::VerifyingKey::read
+    pub fn enable_fixed_column_compression(&mut self) {
+        self.compress_fixed_columns = true;
+    }
+
+    /// Returns the degree required by each lookup argument, in the order in which they
+    /// were added.
+    ///
+    /// The degree of a lookup argument is one more than the sum of the highest degrees
+    /// of its input and table expressions.
+    pub fn lookup_degrees(&self) -> Vec<usize> {
+        self.lookups
+            .iter()
+            .map(|lookup| lookup.required_degree())
+            .collect()
+    }
+
+    /// Computes the values of the advice columns that hold split lookup inputs, in a
+    /// circuit of `n` rows.
+    ///
+    /// `fixed`, `advice` and `instance` return the value of the cell in the given
+    /// column and row. Queries wrap around the circuit, as they do in the prover.
+    pub(crate) fn lookup_intermediate_values(
+        &self,
+        n: usize,
+        fixed: impl Fn(usize, usize) -> F,
+        advice: impl Fn(usize, usize) -> F,
+        instance: impl Fn(usize, usize) -> F,
+    ) -> Vec<(Column<Advice>, Vec<F>)> {
+        let at = |row: usize, rotation: Rotation| {
+            (row as i64 + rotation.0 as i64).rem_euclid(n as i64) as usize
+        };
+
+        self.lookup_intermediates
+            .iter()
+            .map(|(column, input)| {
+                let values = (0..n)
+                    .map(|row| {
+                        input.evaluate(
+                            &|scalar| scalar,
+                            &|index| {
+                                let (column, rotation) = self.fixed_queries[index];
+                                fixed(column.index(), at(row, rotation))
+                            },
+                            &|index| {
+                                let (column, rotation) = self.advice_queries[index];
+                                advice(column.index(), at(row, rotation))
+                            },
+                            &|index| {
+                                let (column, rotation) = self.instance_queries[index];
+                                instance(column.index(), at(row, rotation))
+                            },
+                            &|a, b| a + b,
+                            &|a, b| a * b,
+                            &|a, scalar| a * scalar,
+                        )
+                    })
+                    .collect();
+                (*column, values)
+            })
+            .collect()
+    }
+
+    /// Adds a single lookup argument over several logical tables, which share the same
+    /// table columns and are distinguished by a tag column.
+    ///
+    /// Each row of the table is `(tag, table[0], table[1], ...)`. `inputs` returns the
+    /// uses of the logical tables: on rows where the selector of a [`TaggedInput`] is
+    /// enabled, its input expressions (followed by zeroes, if there are fewer inputs than
+    /// table columns) must appear in a row of the table with its tag.
+    ///
+    /// The selectors must not be enabled on the same rows as each other. On rows where
+    /// none of them is enabled, the lookup input is all zeroes, so the table must
+    /// contain a row in which the tag and every table column are zero; unassigned rows
+    /// of fixed columns are zero.
+    ///
+    /// Returns the index of the lookup argument.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any tag is zero, if two inputs use the same tag with different
+    /// selectors, or if an input has more expressions than there are table columns.
+    pub fn lookup_tagged(
+        &mut self,
+        tag: Column<Fixed>,
+        table: &[Column<Fixed>],
+        inputs: impl FnOnce(&mut VirtualCells<'_, F>) -> Vec<TaggedInput<F>>,
+    ) -> usize {
+        self.lookup(|meta| {
+            let inputs = inputs(meta);
+            for (i, input) in inputs.iter().enumerate() {
+                assert!(
+                    input.tag != F::zero(),
+                    "Tag zero is reserved for rows on which no table is used."
+                );
+                assert!(
+                    input.inputs.len() <= table.len(),
+                    "Tagged lookup has more inputs than table columns."
+                );
+                assert!(
+                    inputs[..i]
+                        .iter()
+                        .all(|other| other.tag != input.tag || other.selector == input.selector),
+                    "Tags must identify a single logical table."
+                );
+            }
+
+            // Each input column is the sum of the inputs of every use of the tables, of
+            // which at most one is enabled on any row.
+            let mut tag_input = Expression::Constant(F::zero());
+            let mut table_inputs = vec![Expression::Constant(F::zero()); table.len()];
+            for input in inputs {
+                let selector = meta.query_selector(input.selector);
+                tag_input = tag_input + selector.clone() * input.tag;
+                for (table_input, expression) in table_inputs.iter_mut().zip(input.inputs) {
+                    *table_input = table_input.clone() + selector.clone() * expression;
+                }
+            }
+
+            iter::once((tag_input, meta.query_fixed(tag, Rotation::cur())))
+                .chain(
+                    table_inputs
+                        .into_iter()
+                        .zip(table.iter())
+                        .map(|(input, column)| (input, meta.query_fixed(*column, Rotation::cur()))),
+                )
+                .collect()
+        })
+    }
+
     fn query_fixed_index(&mut self, column: Column<Fixed>, at: Rotation) -> usize {
         // Return existing query, if it exists
         for (index, fixed_query) in self.fixed_queries.iter().enumerate() {
@@ -951,37 +1939,37 @@ impl<F: Field> ConstraintSystem<F> {
         }
     }
 
-    pub(crate) fn get_advice_query_index(&self, column: Column<Advice>, at: Rotation) -> usize {
-        for (index, advice_query) in self.advice_queries.iter().enumerate() {
-            if advice_query == &(column, at) {
-                return index;
-            }
-        }
-
-        panic!("get_advice_query_index called for non-existent query");
+    pub(crate) fn get_advice_query_index(
+        &self,
+        column: Column<Advice>,
+        at: Rotation,
+    ) -> Option<usize> {
+        self.advice_queries
+            .iter()
+            .position(|query| query == &(column, at))
     }
 
-    pub(crate) fn get_fixed_query_index(&self, column: Column<Fixed>, at: Rotation) -> usize {
-        for (index, fixed_query) in self.fixed_queries.iter().enumerate() {
-            if fixed_query == &(column, at) {
-                return index;
-            }
-        }
-
-        panic!("get_fixed_query_index called for non-existent query");
+    pub(crate) fn get_fixed_query_index(
+        &self,
+        column: Column<Fixed>,
+        at: Rotation,
+    ) -> Option<usize> {
+        self.fixed_queries
+            .iter()
+            .position(|query| query == &(column, at))
     }
 
-    pub(crate) fn get_instance_query_index(&self, column: Column<Instance>, at: Rotation) -> usize {
-        for (index, instance_query) in self.instance_queries.iter().enumerate() {
-            if instance_query == &(column, at) {
-                return index;
-            }
-        }
-
-        panic!("get_instance_query_index called for non-existent query");
+    pub(crate) fn get_instance_query_index(
+        &self,
+        column: Column<Instance>,
+        at: Rotation,
+    ) -> Option<usize> {
+        self.instance_queries
+            .iter()
+            .position(|query| query == &(column, at))
     }
 
-    pub(crate) fn get_any_query_index(&self, column: Column<Any>, at: Rotation) -> usize {
+    pub(crate) fn get_any_query_index(&self, column: Column<Any>, at: Rotation) -> Option<usize> {
         match column.column_type() {
             Any::Advice => {
                 self.get_advice_query_index(Column::<Advice>::try_from(column).unwrap(), at)
@@ -995,12 +1983,34 @@ impl<F: Field> ConstraintSystem<F> {
         }
     }
 
-    /// Creates a new gate.
+    /// Returns the index of the query of a permutation column at the current row.
     ///
     /// # Panics
     ///
-    /// A gate is required to contain polynomial constraints. This method will panic if
-    /// `constraints` returns an empty iterator.
+    /// Panics if `column` is not part of a permutation argument of this constraint
+    /// system, which is a bug in the caller: [`ConstraintSystem::permutation`] queries
+    /// every column of the argument at the current row.
+    pub(crate) fn permutation_query_index(&self, column: Column<Any>) -> usize {
+        self.get_any_query_index(column, Rotation::cur())
+            .expect("permutation columns are queried at the current row")
+    }
+
+    /// Creates a new gate.
+    ///
+    /// If the constraints of the gate are structurally identical to those of a gate that
+    /// has already been created (the same polynomials, in the same order, over the same
+    /// queries), the new gate is merged into the existing one instead of being added
+    /// again. This avoids evaluating the same constraints twice when several chips
+    /// configure the same gate; [`MockProver`] reports failures of the merged gate under
+    /// the name of the gate that was created first.
+    ///
+    /// [`MockProver`]: crate::dev::MockProver
+    ///
+    /// A gate is required to contain polynomial constraints. If `constraints` returns
+    /// an empty iterator, no gate is created, and key generation (or
+    /// [`MockProver::run`]) returns [`Error::EmptyGate`].
+    ///
+    /// [`MockProver::run`]: crate::dev::MockProver::run
     pub fn create_gate<C: Into<Constraint<F>>, Iter: IntoIterator<Item = C>>(
         &mut self,
         name: &'static str,
@@ -1017,10 +2027,21 @@ impl<F: Field> ConstraintSystem<F> {
             .map(|c| (c.name, c.poly))
             .unzip();
 
-        assert!(
-            !polys.is_empty(),
-            "Gates must contain at least one constraint."
-        );
+        if polys.is_empty() {
+            self.empty_gates.push(name);
+            return;
+        }
+
+        let identifier = polys
+            .iter()
+            .map(|poly| poly.identifier())
+            .collect::<Vec<_>>()
+            .join(";");
+        if self.gates.iter().any(|gate| gate.identifier == identifier) {
+            return;
+        }
+
+        let degree = polys.iter().map(|poly| poly.degree()).max().unwrap_or(0);
 
         self.gates.push(Gate {
             name,
@@ -1028,9 +2049,66 @@ impl<F: Field> ConstraintSystem<F> {
             polys,
             queried_selectors,
             queried_cells,
+            degree,
+            identifier,
         });
     }
 
+    /// Declares that regions may be moved between the given lanes by floor planners
+    /// that support column packing, such as [`V1`].
+    ///
+    /// A region whose cells (and enabled selectors) all lie within one of the lanes may
+    /// be laid out within any of the other lanes instead, with each column replaced by
+    /// the column at the same position in the new lane. The caller is responsible for
+    /// ensuring that this is sound: every gate, lookup and permutation must treat the
+    /// lanes identically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lanes do not all have the same sequence of column types.
+    ///
+    /// [`V1`]: crate::circuit::floor_planner::V1
+    pub fn interchangeable_lanes(&mut self, lanes: Vec<Lane>) {
+        if let Some((first, rest)) = lanes.split_first() {
+            for lane in rest {
+                assert!(
+                    lane.columns.len() == first.columns.len()
+                        && lane
+                            .columns
+                            .iter()
+                            .zip(first.columns.iter())
+                            .all(|(a, b)| a.column_type() == b.column_type()),
+                    "interchangeable lanes must have the same column types"
+                );
+            }
+        }
+        self.lanes.push(lanes);
+    }
+
+    /// Enables this fixed column to be used by floor planners to store constants that
+    /// are requested with [`Region::constrain_constant`].
+    ///
+    /// The column must be included in every [`Permutation`] that constants are
+    /// constrained through, and the floor planner will assign constants to any of its
+    /// rows that are not used by regions.
+    ///
+    /// [`Region::constrain_constant`]: crate::circuit::Region::constrain_constant
+    pub fn enable_constant(&mut self, column: Column<Fixed>) {
+        if !self.constants.contains(&column) {
+            self.constants.push(column);
+        }
+    }
+
+    /// Returns the groups of interchangeable lanes declared by the circuit.
+    pub fn lanes(&self) -> &[Vec<Lane>] {
+        &self.lanes
+    }
+
+    /// Returns the fixed columns that have been enabled for storing constants.
+    pub fn constants(&self) -> &[Column<Fixed>] {
+        &self.constants
+    }
+
     /// Allocate a new selector.
     pub fn selector(&mut self) -> Selector {
         // TODO: Track selectors separately, and combine selectors where possible.
@@ -1038,8 +2116,20 @@ impl<F: Field> ConstraintSystem<F> {
         Selector(self.fixed_column())
     }
 
+    /// Returns the index of the existing column to use in place of a new column of the
+    /// given type, if that column is shared.
+    fn shared_column(&mut self, column_type: Any) -> Option<usize> {
+        self.sharing
+            .as_mut()
+            .and_then(|sharing| sharing.request(column_type))
+    }
+
     /// Allocate a new fixed column
     pub fn fixed_column(&mut self) -> Column<Fixed> {
+        if let Some(index) = self.shared_column(Any::Fixed) {
+            return Column::new(index, Fixed);
+        }
+
         let tmp = Column {
             index: self.num_fixed_columns,
             column_type: Fixed,
@@ -1050,6 +2140,10 @@ impl<F: Field> ConstraintSystem<F> {
 
     /// Allocate a new advice column
     pub fn advice_column(&mut self) -> Column<Advice> {
+        if let Some(index) = self.shared_column(Any::Advice) {
+            return Column::new(index, Advice);
+        }
+
         let tmp = Column {
             index: self.num_advice_columns,
             column_type: Advice,
@@ -1058,8 +2152,33 @@ impl<F: Field> ConstraintSystem<F> {
         tmp
     }
 
+    /// Allocate a new advice column that is committed to without a blinding factor.
+    ///
+    /// The commitment to such a column is a deterministic function of its values, so
+    /// it hides nothing about them. This is suitable for columns that only contain
+    /// data that the verifier could derive itself, and allows the commitment to be
+    /// compared across proofs (for example, to link proofs that share the column's
+    /// values). Columns that contain secrets must use [`ConstraintSystem::advice_column`].
+    pub fn unblinded_advice_column(&mut self) -> Column<Advice> {
+        let column = self.advice_column();
+        if !self.unblinded_advice.contains(&column) {
+            self.unblinded_advice.push(column);
+        }
+        column
+    }
+
+    /// Returns `true` if the given advice column is committed to without a blinding
+    /// factor; see [`ConstraintSystem::unblinded_advice_column`].
+    pub fn is_unblinded(&self, column: Column<Advice>) -> bool {
+        self.unblinded_advice.contains(&column)
+    }
+
     /// Allocate a new instance column
     pub fn instance_column(&mut self) -> Column<Instance> {
+        if let Some(index) = self.shared_column(Any::Instance) {
+            return Column::new(index, Instance);
+        }
+
         let tmp = Column {
             index: self.num_instance_columns,
             column_type: Instance,
@@ -1068,28 +2187,113 @@ impl<F: Field> ConstraintSystem<F> {
         tmp
     }
 
+    /// Allocate a new instance column whose contents are the same in every proof.
+    ///
+    /// The column holds `values`, padded with zeroes. Its commitment is computed once
+    /// by [`keygen_vk`] and stored in the verifying key, so it is not sent or absorbed
+    /// in each proof. The prover and verifier ignore any values or commitment given for
+    /// this column, and use these values instead.
+    ///
+    /// [`keygen_vk`]: crate::plonk::keygen_vk
+    pub fn constant_instance_column(&mut self, values: Vec<F>) -> Column<Instance> {
+        let column = self.instance_column();
+        match self
+            .constant_instances
+            .iter_mut()
+            .find(|(existing, _)| *existing == column)
+        {
+            Some((_, existing)) => *existing = values,
+            None => self.constant_instances.push((column, values)),
+        }
+        column
+    }
+
+    /// Returns the values of the given instance column if it was allocated with
+    /// [`ConstraintSystem::constant_instance_column`].
+    pub fn constant_instance_values(&self, column: Column<Instance>) -> Option<&[F]> {
+        self.constant_instances
+            .iter()
+            .find(|(existing, _)| *existing == column)
+            .map(|(_, values)| &values[..])
+    }
+
+    /// Sets the minimum degree of the constraint system.
+    ///
+    /// [`ConstraintSystem::degree`] will return at least `degree`, even if the gates and
+    /// arguments require a smaller one. This fixes the size of the extended domain and
+    /// the number of pieces of the quotient polynomial, so that keys and proofs keep
+    /// the same shape while the gates stay below this degree.
+    pub fn set_minimum_degree(&mut self, degree: usize) {
+        self.minimum_degree = Some(degree);
+    }
+
+    /// Binds proofs to the values of the instance columns, rather than only to their
+    /// commitments.
+    ///
+    /// By default, the prover and verifier absorb the commitments to the instance
+    /// columns into the transcript. Once this is called, they also absorb every
+    /// instance value (see [`VerifyingKey::hash_instance_values`]) before any challenge
+    /// is squeezed, which some protocols require. Proofs for such circuits must be
+    /// verified with [`verify_proof_with_instance_values`].
+    ///
+    /// [`VerifyingKey::hash_instance_values`]: crate::plonk::VerifyingKey::hash_instance_values
+    /// [`verify_proof_with_instance_values`]: crate::plonk::verify_proof_with_instance_values
+    pub fn bind_instance_values(&mut self) {
+        self.binds_instance_values = true;
+    }
+
+    /// Returns `true` if proofs for this constraint system are bound to the values of
+    /// the instance columns; see [`ConstraintSystem::bind_instance_values`].
+    pub fn binds_instance_values(&self) -> bool {
+        self.binds_instance_values
+    }
+
     /// Compute the degree of the constraint system (the maximum degree of all
-    /// constraints).
+    /// constraints, or the minimum degree set with
+    /// [`ConstraintSystem::set_minimum_degree`] if that is larger).
+    ///
+    /// Permutation arguments over many columns are split into chunks so that they do
+    /// not increase the size of the extended domain beyond what the gates and lookup
+    /// arguments require; see [`ConstraintSystem::permutation`]. Arguments that are not
+    /// used do not contribute: without lookup or permutation arguments, the degree is
+    /// that of the gates, or 1 if there are none.
     pub fn degree(&self) -> usize {
         // The permutation argument will serve alongside the gates, so must be
         // accounted for.
-        let mut degree = self
+        let degree_bound = self.permutation_degree_bound();
+        let degree = self
             .permutations
             .iter()
-            .map(|p| p.required_degree())
+            .map(|p| p.required_degree(degree_bound))
             .max()
             .unwrap_or(1);
 
+        std::cmp::max(degree, self.gate_and_lookup_degree())
+    }
+
+    /// Returns the maximum degree of the constraints of each chunk of a permutation
+    /// argument.
+    ///
+    /// This is the largest degree that fits in the extended domain required by the
+    /// gates and lookup arguments (and the minimum degree), and is at least 3 so that
+    /// every chunk can contain at least one column.
+    pub(crate) fn permutation_degree_bound(&self) -> usize {
+        let degree = std::cmp::max(self.gate_and_lookup_degree(), 3);
+
+        // A constraint of degree d requires an extended domain of n * (d - 1) points,
+        // rounded up to a power of two.
+        (degree - 1).next_power_of_two() + 1
+    }
+
+    fn gate_and_lookup_degree(&self) -> usize {
         // The lookup argument also serves alongside the gates and must be accounted
         // for.
-        degree = std::cmp::max(
-            degree,
-            self.lookups
-                .iter()
-                .map(|l| l.required_degree())
-                .max()
-                .unwrap_or(1),
-        );
+        let mut degree = self
+            .lookups
+            .iter()
+            .map(|l| l.required_degree())
+            .max()
+            .unwrap_or(1);
 
         // Account for each gate to ensure our quotient polynomial is the
         // correct degree and that our extended domain is the right size.
@@ -1097,12 +2301,161 @@ impl<F: Field> ConstraintSystem<F> {
             degree,
             self.gates
                 .iter()
-                .flat_map(|gate| gate.polynomials().iter().map(|poly| poly.degree()))
+                .map(|gate| gate.degree())
                 .max()
                 .unwrap_or(0),
         );
 
-        degree
+        std::cmp::max(degree, self.minimum_degree.unwrap_or(1))
+    }
+
+    /// Returns whether the constraint system has any lookup or permutation arguments.
+    ///
+    /// Circuits without them (such as minimal recursion leaves) skip the work that
+    /// only these arguments need, such as computing `l_0(X)`.
+    pub(crate) fn has_lookups_or_permutations(&self) -> bool {
+        !self.lookups.is_empty() || !self.permutations.is_empty()
+    }
+
+    /// Returns a copy of this constraint system in which every use of fixed column `i`
+    /// refers to fixed column `map[i]` instead, where `map[i] <= i` and `map[map[i]] ==
+    /// map[i]`.
+    ///
+    /// This is used to merge fixed columns with identical values, so the number of
+    /// fixed columns (and their indices) stay the same, but merged columns are no
+    /// longer queried. Fields that only affect the layout of the circuit are copied
+    /// unchanged.
+    pub(crate) fn merge_fixed_columns(&self, map: &[usize]) -> Self {
+        assert_eq!(map.len(), self.num_fixed_columns);
+
+        let fixed = |column: Column<Fixed>| Column::new(map[column.index()], Fixed);
+        let any = |column: Column<Any>| match column.column_type() {
+            Any::Fixed => Column::new(map[column.index()], Any::Fixed),
+            _ => column,
+        };
+        let cell = |cell: &VirtualCell| VirtualCell {
+            column: any(cell.column),
+            rotation: cell.rotation,
+        };
+
+        let mut merged = self.clone();
+        merged.fixed_queries = Vec::new();
+        let queries: Vec<_> = self
+            .fixed_queries
+            .iter()
+            .map(|&(column, at)| merged.query_fixed_index(fixed(column), at))
+            .collect();
+        let expression = |expression: &Expression<F>| {
+            expression.evaluate(
+                &Expression::Constant,
+                &|index| Expression::Fixed(queries[index]),
+                &Expression::Advice,
+                &Expression::Instance,
+                &|a, b| Expression::Sum(Box::new(a), Box::new(b)),
+                &|a, b| Expression::Product(Box::new(a), Box::new(b)),
+                &|a, scalar| Expression::Scaled(Box::new(a), scalar),
+            )
+        };
+
+        for gate in merged.gates.iter_mut() {
+            gate.polys = gate.polys.iter().map(expression).collect();
+            gate.queried_selectors = gate
+                .queried_selectors
+                .iter()
+                .map(|selector| Selector(fixed(selector.0)))
+                .collect();
+            gate.queried_cells = gate.queried_cells.iter().map(cell).collect();
+            gate.identifier = gate
+                .polys
+                .iter()
+                .map(|poly| poly.identifier())
+                .collect::<Vec<_>>()
+                .join(";");
+        }
+        for argument in merged.lookups.iter_mut() {
+            argument.input_expressions =
+                argument.input_expressions.iter().map(expression).collect();
+            argument.table_expressions =
+                argument.table_expressions.iter().map(expression).collect();
+            for (selector, cells) in argument.selector_cells.iter_mut() {
+                *selector = Selector(fixed(selector.0));
+                *cells = cells.iter().map(cell).collect();
+            }
+        }
+        for (_, input) in merged.lookup_intermediates.iter_mut() {
+            *input = expression(input);
+        }
+        merged.permutations = self
+            .permutations
+            .iter()
+            .map(|argument| {
+                permutation::Argument::new(argument.get_columns().into_iter().map(any).collect())
+            })
+            .collect();
+
+        merged
+    }
+
+    /// Returns the largest backward and forward rotations, as `(backward, forward)`, at
+    /// which any column is queried by the gates and arguments of the constraint system.
+    ///
+    /// A circuit can only be laid out in a domain with more rows than either of these
+    /// rotations; otherwise queries would wrap all the way around the domain.
+    pub fn max_rotation(&self) -> (usize, usize) {
+        self.advice_queries
+            .iter()
+            .map(|(_, rotation)| rotation.0)
+            .chain(self.fixed_queries.iter().map(|(_, rotation)| rotation.0))
+            .chain(self.instance_queries.iter().map(|(_, rotation)| rotation.0))
+            .fold((0, 0), |(backward, forward), rotation| {
+                if rotation < 0 {
+                    (max(backward, -rotation as usize), forward)
+                } else {
+                    (backward, max(forward, rotation as usize))
+                }
+            })
+    }
+}
+
+impl<F: FieldExt> ConstraintSystem<F> {
+    /// Checks that the constraint system fits in an extended domain for a circuit of
+    /// `2^k` rows.
+    ///
+    /// Returns [`Error::LookupDegreeTooHigh`] if a lookup argument exceeds the bound set
+    /// with [`ConstraintSystem::set_max_lookup_degree`], or the largest degree that the
+    /// field supports, and [`Error::IncompatibleParams`] if anything else exceeds the
+    /// latter.
+    pub(crate) fn check_degree(&self, k: u32) -> Result<(), Error> {
+        if let Some(&name) = self.empty_gates.first() {
+            return Err(Error::EmptyGate { name });
+        }
+
+        // A constraint of degree d requires an extended domain of n * (d - 1) points,
+        // and the field only has roots of unity of order up to 2^S.
+        if k > F::S {
+            return Err(Error::IncompatibleParams);
+        }
+        let field_bound = 1usize
+            .checked_shl(F::S - k)
+            .map_or(usize::MAX, |points| points.saturating_add(1));
+
+        let max_degree = self
+            .max_lookup_degree
+            .map_or(field_bound, |degree| std::cmp::min(degree, field_bound));
+        for (lookup, degree) in self.lookup_degrees().into_iter().enumerate() {
+            if degree > max_degree {
+                return Err(Error::LookupDegreeTooHigh {
+                    lookup,
+                    degree,
+                    max_degree,
+                });
+            }
+        }
+
+        if self.degree() > field_bound {
+            return Err(Error::IncompatibleParams);
+        }
+        Ok(())
     }
 }
 
@@ -1168,3 +2521,287 @@ impl<'a, F: Field> VirtualCells<'a, F> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{ConstraintSystem, Error, Expression};
+    use crate::poly::Rotation;
+
+    #[test]
+    fn pinned_diff() {
+        let configure = |negate: bool| {
+            let mut meta = ConstraintSystem::<Fp>::default();
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            meta.create_gate("a equals b", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                if negate {
+                    vec![a + b]
+                } else {
+                    vec![a - b]
+                }
+            });
+            meta
+        };
+
+        let left = configure(false);
+        let right = configure(true);
+
+        assert!(left.pinned().diff(&configure(false).pinned()).is_empty());
+
+        let diff = left.pinned().diff(&right.pinned());
+        assert_eq!(diff.differences().len(), 1);
+        assert_eq!(diff.differences()[0].path, "gates[0] ('a equals b')[0]");
+    }
+
+    #[test]
+    fn minimum_degree() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        meta.create_gate("a is boolean", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            vec![a.clone() * (a - Expression::Constant(Fp::one()))]
+        });
+        assert_eq!(meta.degree(), 2);
+
+        // A larger minimum degree takes precedence over the gates.
+        meta.set_minimum_degree(5);
+        assert_eq!(meta.degree(), 5);
+
+        // A smaller minimum degree has no effect.
+        meta.set_minimum_degree(1);
+        assert_eq!(meta.degree(), 2);
+    }
+
+    #[test]
+    fn degree_without_arguments() {
+        // Without gates or arguments, the constraint system has degree 1, so the
+        // extended domain is no larger than the circuit and the quotient is empty.
+        let mut meta = ConstraintSystem::<Fp>::default();
+        assert!(!meta.has_lookups_or_permutations());
+        assert_eq!(meta.degree(), 1);
+
+        // Without lookup or permutation arguments, the degree is that of the gates.
+        let a = meta.advice_column();
+        meta.create_gate("a is boolean", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            vec![a.clone() * (a - Expression::Constant(Fp::one()))]
+        });
+        assert_eq!(meta.degree(), 2);
+
+        meta.permutation(&[a.into()]);
+        assert!(meta.has_lookups_or_permutations());
+    }
+
+    #[test]
+    fn max_rotation() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        assert_eq!(meta.max_rotation(), (0, 0));
+
+        let a = meta.advice_column();
+        let f = meta.fixed_column();
+        meta.create_gate("rotations", |meta| {
+            let a_prev = meta.query_advice(a, Rotation(-3));
+            let a_next = meta.query_advice(a, Rotation::next());
+            let f_next = meta.query_fixed(f, Rotation(2));
+            vec![a_prev * a_next * f_next]
+        });
+        assert_eq!(meta.max_rotation(), (3, 2));
+        assert_eq!(meta.degree(), 3);
+
+        // Queries of a window of rows.
+        let b = meta.advice_column();
+        meta.create_gate("window", |meta| {
+            Rotation::range(-4..4)
+                .map(|rotation| meta.query_advice(b, rotation + Rotation::next()))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(meta.max_rotation(), (3, 4));
+        assert!((-Rotation::next() + Rotation::next()).is_cur());
+    }
+
+    #[test]
+    fn display_expression() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        let f = meta.fixed_column();
+        meta.create_gate("boolean", |meta| {
+            let a = meta.query_advice(a, Rotation::cur());
+            vec![a.clone() * (a - Expression::Constant(Fp::one()))]
+        });
+        meta.create_gate("scaled", |meta| {
+            let a_prev = meta.query_advice(a, Rotation::prev());
+            let f = meta.query_fixed(f, Rotation::cur());
+            vec![f * Fp::from(3) - a_prev.square()]
+        });
+
+        let gates = &meta.gates;
+        assert_eq!(
+            gates[0].polynomials()[0].display(&meta).to_string(),
+            "advice[0][0] * (advice[0][0] - 1)"
+        );
+        assert_eq!(
+            gates[1].polynomials()[0].display(&meta).to_string(),
+            "3 * fixed[0][0] - advice[0][-1] * advice[0][-1]"
+        );
+
+        // Identifiers only match for structurally identical expressions.
+        let mut other = ConstraintSystem::<Fp>::default();
+        let b = other.advice_column();
+        other.create_gate("boolean", |meta| {
+            let b = meta.query_advice(b, Rotation::cur());
+            vec![b.clone() * (b - Expression::Constant(Fp::one()))]
+        });
+        assert_eq!(
+            gates[0].polynomials()[0].identifier(),
+            other.gates[0].polynomials()[0].identifier()
+        );
+        assert_ne!(
+            gates[0].polynomials()[0].identifier(),
+            gates[1].polynomials()[0].identifier()
+        );
+    }
+
+    #[test]
+    fn pow_and_horner() {
+        // Evaluates an expression in which advice query `i` has the value `i + 2`.
+        let evaluate = |expression: &Expression<Fp>| {
+            expression.evaluate(
+                &|scalar| scalar,
+                &|_| unreachable!(),
+                &|index| Fp::from(index as u64 + 2),
+                &|_| unreachable!(),
+                &|a, b| a + b,
+                &|a, b| a * b,
+                &|a, scalar| a * scalar,
+            )
+        };
+
+        let a = Expression::<Fp>::Advice(1);
+        assert_eq!(evaluate(&a.pow(0)), Fp::one());
+        assert_eq!(evaluate(&a.pow(5)), Fp::from(243));
+        assert_eq!(a.pow(5).degree(), 5);
+
+        let values: Vec<_> = (0..3).map(Expression::Advice).collect();
+        assert_eq!(
+            evaluate(&super::horner(&values, Fp::from(10))),
+            Fp::from(234)
+        );
+        assert_eq!(evaluate(&super::horner(&[], Fp::from(10))), Fp::zero());
+    }
+
+    #[test]
+    fn from_terms() {
+        // Evaluates an expression in which advice query `i` has the value `i + 2`.
+        let evaluate = |expression: &Expression<Fp>| {
+            expression.evaluate(
+                &|scalar| scalar,
+                &|_| unreachable!(),
+                &|index| Fp::from(index as u64 + 2),
+                &|_| unreachable!(),
+                &|a, b| a + b,
+                &|a, b| a * b,
+                &|a, scalar| a * scalar,
+            )
+        };
+
+        let terms = (0..1000).map(|i| (Fp::from(i as u64), Expression::Advice(i)));
+        let expected = (0..1000u64).map(|i| i * (i + 2)).sum::<u64>();
+        let combination = Expression::from_terms(terms);
+        assert_eq!(evaluate(&combination), Fp::from(expected));
+        assert_eq!(combination.degree(), 1);
+
+        // Adjacent terms are summed first, and coefficients of one are omitted.
+        let terms = (0..4).map(|i| (Fp::one(), Expression::Advice(i)));
+        assert_eq!(
+            Expression::from_terms(terms).identifier(),
+            "((advice_query[0]+advice_query[1])+(advice_query[2]+advice_query[3]))"
+        );
+        assert_eq!(
+            evaluate(&Expression::from_terms(std::iter::empty())),
+            Fp::zero()
+        );
+    }
+
+    #[test]
+    fn deeply_nested_expressions() {
+        const DEPTH: usize = 5000;
+
+        // A left-leaning chain of sums and products, as produced by folding generated terms.
+        let chain = (1..DEPTH).fold(Expression::Advice(0), |acc, i| {
+            if i % 2 == 0 {
+                acc + Expression::Advice(0)
+            } else {
+                acc * Expression::Constant(Fp::one())
+            }
+        });
+
+        // Traversals must not depend on the call stack, so run them on a small one. The
+        // chain is handed back so that it is dropped on this thread.
+        let chain = std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(move || {
+                let value = chain.evaluate(
+                    &|scalar| scalar,
+                    &|_| unreachable!(),
+                    &|_| Fp::one(),
+                    &|_| unreachable!(),
+                    &|a, b| a + b,
+                    &|a, b| a * b,
+                    &|a, scalar| a * scalar,
+                );
+                assert_eq!(value, Fp::from(DEPTH as u64 / 2));
+                assert_eq!(chain.degree(), 1);
+                assert!(chain.identifier().starts_with("((((("));
+
+                let meta = ConstraintSystem::<Fp>::default();
+                let rendered = chain.display(&meta).to_string();
+                assert!(rendered
+                    .trim_start_matches('(')
+                    .starts_with("advice_query[0] * 1 + advice_query[0]) * 1"));
+                assert!(rendered.ends_with(") * 1 + advice_query[0]) * 1"));
+                chain
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(chain.degree(), 1);
+    }
+
+    #[test]
+    fn duplicate_gates() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let boolean = |meta: &mut ConstraintSystem<Fp>, column| {
+            meta.create_gate("boolean", |meta| {
+                let value = meta.query_advice(column, Rotation::cur());
+                vec![value.clone() * (value - Expression::Constant(Fp::one()))]
+            });
+        };
+
+        boolean(&mut meta, a);
+        boolean(&mut meta, b);
+        assert_eq!(meta.gates.len(), 2);
+
+        // A structurally identical gate is merged into the existing one.
+        boolean(&mut meta, a);
+        assert_eq!(meta.gates.len(), 2);
+    }
+
+    #[test]
+    fn empty_gate() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        assert!(meta.check_degree(4).is_ok());
+
+        meta.create_gate("empty", |_| Vec::<Expression<Fp>>::new());
+        assert!(meta.gates.is_empty());
+        assert!(matches!(
+            meta.check_degree(4),
+            Err(Error::EmptyGate { name: "empty" })
+        ));
+    }
+}
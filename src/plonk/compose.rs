@@ -0,0 +1,280 @@
+//! Combinators for building a circuit out of two smaller circuits.
+
+use ff::Field;
+use std::marker::PhantomData;
+
+use super::{circuit::ColumnSharing, Any, Circuit, Column, ConstraintSystem, Error};
+use crate::circuit::Layouter;
+
+/// Describes the columns that the second half of a [`Composed`] circuit shares with
+/// the first half, for example a lookup table that both halves use.
+pub trait Sharing<F: Field, A: Circuit<F>> {
+    /// Returns pairs of `(requested, existing)` columns, given the configuration of
+    /// the first circuit.
+    ///
+    /// `requested` identifies a column that the second circuit allocates in its
+    /// `configure` method, by the index it would have if the second circuit were
+    /// configured on its own (so the first advice column that it allocates is advice
+    /// column 0). `existing` is the column of the first circuit to use instead; it must
+    /// have the same column type.
+    fn shared_columns(config: &A::Config) -> Vec<(Column<Any>, Column<Any>)>;
+}
+
+/// The two halves of the circuit share no columns.
+impl<F: Field, A: Circuit<F>> Sharing<F, A> for () {
+    fn shared_columns(_: &A::Config) -> Vec<(Column<Any>, Column<Any>)> {
+        vec![]
+    }
+}
+
+/// A circuit made of two circuits, created by [`compose`].
+///
+/// The constraint system of the composed circuit contains the columns, gates and
+/// arguments of both circuits. The columns of the second circuit are allocated after
+/// those of the first, except for the columns that are shared according to `S`.
+///
+/// The composed circuit is laid out with the floor planner of the first circuit. Each
+/// circuit is synthesized within its own namespace, so that the names of their regions
+/// do not collide.
+#[derive(Debug)]
+pub struct Composed<A, B, S = ()> {
+    first: A,
+    second: B,
+    _marker: PhantomData<S>,
+}
+
+/// Composes two circuits into a single circuit.
+///
+/// By default the two circuits share no columns; use [`Composed::sharing`] to share
+/// columns between them.
+pub fn compose<A, B>(first: A, second: B) -> Composed<A, B> {
+    Composed {
+        first,
+        second,
+        _marker: PhantomData,
+    }
+}
+
+impl<A, B, S> Composed<A, B, S> {
+    /// Shares the columns described by `T` between the two circuits.
+    pub fn sharing<T>(self) -> Composed<A, B, T> {
+        Composed {
+            first: self.first,
+            second: self.second,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the first circuit.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Returns the second circuit.
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+}
+
+impl<A: Clone, B: Clone, S> Clone for Composed<A, B, S> {
+    fn clone(&self) -> Self {
+        Composed {
+            first: self.first.clone(),
+            second: self.second.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field, A: Circuit<F>, B: Circuit<F>, S: Sharing<F, A>> Circuit<F> for Composed<A, B, S> {
+    type Config = (A::Config, B::Config);
+    type FloorPlanner = A::FloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Composed {
+            first: self.first.without_witnesses(),
+            second: self.second.without_witnesses(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure_composed::<F, A, B, S>(meta, A::configure, B::configure)
+    }
+
+    fn configure_from(&self, meta: &mut ConstraintSystem<F>) -> Self::Config {
+        configure_composed::<F, A, B, S>(
+            meta,
+            |meta| self.first.configure_from(meta),
+            |meta| self.second.configure_from(meta),
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let (first, second) = config;
+        self.first
+            .synthesize(first, layouter.namespace(|| "first"))?;
+        self.second
+            .synthesize(second, layouter.namespace(|| "second"))
+    }
+}
+
+/// Configures the two halves of a [`Composed`] circuit with the given functions.
+fn configure_composed<F: Field, A: Circuit<F>, B: Circuit<F>, S: Sharing<F, A>>(
+    meta: &mut ConstraintSystem<F>,
+    configure_first: impl FnOnce(&mut ConstraintSystem<F>) -> A::Config,
+    configure_second: impl FnOnce(&mut ConstraintSystem<F>) -> B::Config,
+) -> (A::Config, B::Config) {
+    let first = configure_first(meta);
+
+    let shared = S::shared_columns(&first);
+    for (requested, existing) in shared.iter() {
+        assert_eq!(
+            requested.column_type(),
+            existing.column_type(),
+            "shared columns must have the same type"
+        );
+    }
+
+    meta.sharing = Some(ColumnSharing::new(shared));
+    let second = configure_second(meta);
+    meta.sharing = None;
+
+    (first, second)
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{compose, Sharing};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Advice, Any, Circuit, Column, ConstraintSystem, Error, Fixed, Selector},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct RangeConfig {
+        value: Column<Advice>,
+        table: Column<Fixed>,
+        s: Selector,
+    }
+
+    /// Checks that a value is smaller than 8, using a lookup table that it loads itself.
+    #[derive(Default)]
+    struct RangeCircuit {
+        value: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for RangeCircuit {
+        type Config = RangeConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> RangeConfig {
+            let value = meta.advice_column();
+            let table = meta.fixed_column();
+            let s = meta.selector();
+
+            meta.lookup(|meta| {
+                let value = meta.query_advice(value, Rotation::cur());
+                let table = meta.query_fixed(table, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![(s * value, table)]
+            });
+
+            RangeConfig { value, table, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: RangeConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "table",
+                |mut region| {
+                    for i in 0..8 {
+                        region.assign_fixed(
+                            || "table",
+                            config.table,
+                            i,
+                            || Ok(Fp::from(i as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+
+            layouter.assign_region(
+                || "value",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "value",
+                        config.value,
+                        0,
+                        || self.value.ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    /// Shares the lookup table of the first circuit with the second circuit.
+    struct SharedTable;
+
+    impl Sharing<Fp, RangeCircuit> for SharedTable {
+        fn shared_columns(config: &RangeConfig) -> Vec<(Column<Any>, Column<Any>)> {
+            vec![(Column::new(0, Any::Fixed), config.table.into())]
+        }
+    }
+
+    #[test]
+    fn composed_circuit() {
+        let circuit = |a: u64, b: u64| {
+            compose(
+                RangeCircuit {
+                    value: Some(Fp::from(a)),
+                },
+                RangeCircuit {
+                    value: Some(Fp::from(b)),
+                },
+            )
+        };
+
+        // Without sharing, each circuit has its own columns.
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let (first, second) = <super::Composed<RangeCircuit, RangeCircuit>>::configure(&mut cs);
+        assert_eq!(cs.num_advice_columns, 2);
+        assert_eq!(cs.num_fixed_columns, 4);
+        assert_eq!(second.value.index(), first.value.index() + 1);
+
+        let prover = MockProver::run(4, &circuit(3, 7), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        let prover = MockProver::run(4, &circuit(3, 8), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // With sharing, the second circuit uses the table of the first.
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let (first, second) =
+            <super::Composed<RangeCircuit, RangeCircuit, SharedTable>>::configure(&mut cs);
+        assert_eq!(cs.num_advice_columns, 2);
+        assert_eq!(cs.num_fixed_columns, 3);
+        assert_eq!(second.table, first.table);
+        assert_ne!(second.s.0, first.s.0);
+
+        let prover = MockProver::run(4, &circuit(5, 2).sharing::<SharedTable>(), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
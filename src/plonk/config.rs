@@ -0,0 +1,291 @@
+//! Declarative circuit configurations.
+
+/// Declares a circuit configuration struct, along with an `allocate` method that
+/// allocates its columns, and optionally the struct holding the circuit's witness.
+///
+/// Each field is declared with the kind of column that it holds:
+///
+/// - `advice`, `fixed` and `instance` fields hold a [`Column`] of that type.
+/// - `selector` fields hold a [`Selector`].
+/// - `permutation(a, b, ...)` fields hold a [`Permutation`] over the columns of the
+///   previously-declared fields `a`, `b`, ...
+///
+/// The struct derives `Clone` and `Debug`. `allocate` allocates the columns in the
+/// order in which the fields are declared, so that the resulting constraint system
+/// does not change when fields are only renamed. Gates and lookups are then created
+/// as usual:
+///
+/// ```ignore
+/// halo2::circuit_config! {
+///     /// The columns of a circuit that multiplies two values.
+///     pub struct MulConfig {
+///         a: advice,
+///         b: advice,
+///         c: advice,
+///         s_mul: selector,
+///         perm: permutation(a, b, c),
+///     }
+/// }
+///
+/// fn configure(meta: &mut ConstraintSystem<F>) -> MulConfig {
+///     let config = MulConfig::allocate(meta);
+///     meta.create_gate("mul", |meta| {
+///         let a = meta.query_advice(config.a, Rotation::cur());
+///         let b = meta.query_advice(config.b, Rotation::cur());
+///         let c = meta.query_advice(config.c, Rotation::cur());
+///         let s_mul = meta.query_selector(config.s_mul);
+///         vec![s_mul * (a * b - c)]
+///     });
+///     config
+/// }
+/// ```
+///
+/// The configuration may be followed by a `witness` struct, whose fields are each
+/// wrapped in an `Option`. It derives `Clone` and `Debug`, and implements `Default`
+/// with every field set to `None`, which is the value that
+/// [`Circuit::without_witnesses`] returns:
+///
+/// ```ignore
+/// halo2::circuit_config! {
+///     pub struct MulConfig {
+///         // ...
+///     }
+///
+///     /// The witness of a circuit that multiplies two values.
+///     witness pub struct MulCircuit<F> {
+///         a: F,
+///         b: F,
+///     }
+/// }
+///
+/// impl<F: FieldExt> Circuit<F> for MulCircuit<F> {
+///     type Config = MulConfig;
+///     type FloorPlanner = SimpleFloorPlanner;
+///
+///     fn without_witnesses(&self) -> Self {
+///         Self::default()
+///     }
+///
+///     // ...
+/// }
+/// ```
+///
+/// The `Circuit` implementation itself is not generated, because its gates and
+/// `synthesize` are specific to the circuit.
+///
+/// [`Column`]: crate::plonk::Column
+/// [`Selector`]: crate::plonk::Selector
+/// [`Permutation`]: crate::plonk::Permutation
+/// [`Circuit::without_witnesses`]: crate::plonk::Circuit::without_witnesses
+#[macro_export]
+macro_rules! circuit_config {
+    (@type advice) => { $crate::plonk::Column<$crate::plonk::Advice> };
+    (@type fixed) => { $crate::plonk::Column<$crate::plonk::Fixed> };
+    (@type instance) => { $crate::plonk::Column<$crate::plonk::Instance> };
+    (@type selector) => { $crate::plonk::Selector };
+    (@type permutation) => { $crate::plonk::Permutation };
+
+    (@allocate $meta:ident, advice) => { $meta.advice_column() };
+    (@allocate $meta:ident, fixed) => { $meta.fixed_column() };
+    (@allocate $meta:ident, instance) => { $meta.instance_column() };
+    (@allocate $meta:ident, selector) => { $meta.selector() };
+    (@allocate $meta:ident, permutation($($column:ident),*)) => {
+        $meta.permutation(&[
+            $($crate::plonk::Column::<$crate::plonk::Any>::from($column)),*
+        ])
+    };
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident { $($config:tt)* }
+
+        $(#[$witness_attr:meta])*
+        witness $witness_vis:vis struct $witness:ident $(< $($param:ident),* >)? {
+            $(
+                $(#[$witness_field_attr:meta])*
+                $witness_field_vis:vis $witness_field:ident : $ty:ty
+            ),* $(,)?
+        }
+    ) => {
+        $crate::circuit_config! {
+            $(#[$attr])*
+            $vis struct $name { $($config)* }
+        }
+
+        $(#[$witness_attr])*
+        #[derive(Clone, Debug)]
+        $witness_vis struct $witness $(< $($param),* >)? {
+            $(
+                $(#[$witness_field_attr])*
+                $witness_field_vis $witness_field: Option<$ty>,
+            )*
+        }
+
+        impl $(< $($param),* >)? Default for $witness $(< $($param),* >)? {
+            fn default() -> Self {
+                $witness { $($witness_field: None,)* }
+            }
+        }
+    };
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                $field_vis:vis $field:ident : $kind:ident $(( $($column:ident),* $(,)? ))?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug)]
+        $vis struct $name {
+            $(
+                $(#[$field_attr])*
+                $field_vis $field: $crate::circuit_config!(@type $kind),
+            )*
+        }
+
+        impl $name {
+            /// Allocates the columns of this configuration, in the order in which its
+            /// fields are declared.
+            $vis fn allocate<F: $crate::arithmetic::FieldExt>(
+                meta: &mut $crate::plonk::ConstraintSystem<F>,
+            ) -> Self {
+                $(
+                    let $field = $crate::circuit_config!(
+                        @allocate meta, $kind $(( $($column),* ))?
+                    );
+                )*
+
+                $name { $($field,)* }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use crate::{
+        arithmetic::FieldExt,
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        plonk::{Any, Circuit, Column, ConstraintSystem, Error},
+        poly::Rotation,
+    };
+
+    crate::circuit_config! {
+        /// A configuration with one field of each kind.
+        struct TestConfig {
+            a: advice,
+            /// A doc comment on a field.
+            pub b: advice,
+            f: fixed,
+            p: instance,
+            s: selector,
+            perm: permutation(a, b, p),
+        }
+    }
+
+    #[test]
+    fn allocate() {
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let config = TestConfig::allocate(&mut cs);
+
+        assert_eq!(cs.num_advice_columns, 2);
+        assert_eq!(cs.num_fixed_columns, 2);
+        assert_eq!(cs.num_instance_columns, 1);
+        assert_eq!((config.a.index(), config.b.index()), (0, 1));
+        assert_eq!((config.f.index(), config.s.0.index()), (0, 1));
+        assert_eq!(
+            config.perm.mapping(),
+            &[
+                Column::<Any>::from(config.a),
+                config.b.into(),
+                config.p.into()
+            ][..]
+        );
+    }
+
+    crate::circuit_config! {
+        /// The columns of a circuit that multiplies two values.
+        struct MulConfig {
+            a: advice,
+            b: advice,
+            product: instance,
+            s_mul: selector,
+        }
+
+        /// The witness of a circuit that multiplies two values.
+        witness struct MulCircuit<F> {
+            a: F,
+            b: F,
+        }
+    }
+
+    impl<F: FieldExt> Circuit<F> for MulCircuit<F> {
+        type Config = MulConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> MulConfig {
+            let config = MulConfig::allocate(meta);
+            meta.create_gate("mul", |meta| {
+                let a = meta.query_advice(config.a, Rotation::cur());
+                let b = meta.query_advice(config.b, Rotation::cur());
+                let product = meta.query_instance(config.product, Rotation::cur());
+                let s_mul = meta.query_selector(config.s_mul);
+                vec![s_mul * (a * b - product)]
+            });
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: MulConfig,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "mul",
+                |mut region| {
+                    config.s_mul.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.a.ok_or(Error::SynthesisError),
+                    )?;
+                    region.assign_advice(
+                        || "b",
+                        config.b,
+                        0,
+                        || self.b.ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn witness() {
+        let circuit = MulCircuit {
+            a: Some(Fp::from(3u64)),
+            b: Some(Fp::from(5u64)),
+        };
+
+        let empty = circuit.without_witnesses();
+        assert!(empty.a.is_none() && empty.b.is_none());
+
+        let prover = MockProver::run(3, &circuit, vec![vec![Fp::from(15u64)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(3, &circuit, vec![vec![Fp::from(16u64)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
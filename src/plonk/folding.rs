@@ -0,0 +1,903 @@
+//! Experimental folding of circuit instances, in the style of Nova and Sangria.
+//!
+//! Instead of proving each instance of a circuit separately, folding combines two
+//! instances into a single instance that is satisfied only if both of them are, so
+//! that a long sequence of instances (such as the steps of an incrementally verifiable
+//! computation) can be checked with a single proof at the end.
+//!
+//! Folding operates on a relaxation of the circuit's relation. Each gate constraint
+//! `G` of degree `d` in the advice and instance columns (fixed columns are part of the
+//! circuit, and so have degree zero) is homogenized to the degree `D` of the
+//! constraint system with a scalar `u`, by multiplying each of its terms of degree `k`
+//! by `u^{D - k}`. A relaxed instance holds `u`, the instance values, and commitments
+//! to the advice columns and to an error vector `E` for each constraint, and is
+//! satisfied by its witness if `G_hom(w, u) = E` on every row. An instance of the
+//! circuit itself is the relaxed instance with `u = 1` and `E = 0`.
+//!
+//! Given a challenge `r`, two relaxed instances fold into one whose witness and
+//! instance values are `w_1 + r w_2`, and whose error vector is
+//! `E_1 + r T_1 + ... + r^{D - 1} T_{D - 1} + r^D E_2`, where the cross terms `T_k`
+//! are the coefficients of `G_hom(w_1 + X w_2, u_1 + X u_2)`. The prover commits to
+//! the cross terms before `r` is squeezed from the transcript, and the verifier folds
+//! the commitments without seeing either witness. Copy constraints are linear, and so
+//! are preserved by folding; copies of fixed cells are scaled by `u`.
+//!
+//! This module is experimental. It supports circuits with custom gates and
+//! permutation arguments, but not lookup arguments. The commitments to advice columns
+//! and cross terms use random blinds, which fold along with the witness; the error of
+//! a fresh instance is zero, so its commitments use the default blind. The caller is
+//! responsible for binding the transcript to the circuit (for example, with
+//! [`VerifyingKey::hash_into`]) before folding. Folding is only implemented natively;
+//! there is no gadget that verifies a folding step inside a circuit.
+//!
+//! [`VerifyingKey::hash_into`]: super::VerifyingKey::hash_into
+
+use std::cmp;
+use std::iter;
+
+use ff::Field;
+use group::{Curve, Group as _};
+
+use super::{
+    circuit::{Any, Circuit, Column, ConstraintSystem, Expression},
+    keygen::{assemble, create_domain},
+    prover::synthesize_advice,
+    Error,
+};
+use crate::{
+    arithmetic::{lagrange_interpolate, CurveAffine, FieldExt},
+    poly::{
+        commitment::{Blind, Params},
+        EvaluationDomain, LagrangeCoeff, Polynomial, Rotation,
+    },
+    transcript::{EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite},
+};
+
+/// The reasons why folding, or checking a folded instance, can fail.
+#[derive(Debug)]
+pub enum FoldingError {
+    /// The circuit uses lookup arguments, which cannot be folded.
+    Lookups,
+    /// The instance values or witness do not have the dimensions of the circuit.
+    InvalidShape,
+    /// An error occurred while synthesizing the circuit or using the transcript.
+    Synthesis(Error),
+    /// A commitment of a relaxed instance does not match its witness.
+    Commitment,
+    /// A relaxed constraint is not satisfied.
+    Constraint {
+        /// The index of the constraint, counting the constraints of every gate in the
+        /// order in which the gates were created.
+        constraint: usize,
+        /// The row on which the constraint is not satisfied.
+        row: usize,
+    },
+    /// The cells of a copy constraint do not have equal (relaxed) values.
+    Copy {
+        /// The index of the permutation argument.
+        permutation: usize,
+        /// The cell whose value differs from that of the first cell of its cycle, as
+        /// `(column, row)`.
+        cell: (Column<Any>, usize),
+    },
+}
+
+impl From<Error> for FoldingError {
+    fn from(error: Error) -> Self {
+        FoldingError::Synthesis(error)
+    }
+}
+
+/// Marks the challenge with which two relaxed instances are folded.
+#[derive(Clone, Copy, Debug)]
+struct Fold;
+
+/// A relaxed instance of a circuit, which the folding verifier holds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedInstance<C: CurveAffine> {
+    /// The scalar with which the constraints are homogenized.
+    pub u: C::Scalar,
+    /// The values of the instance columns.
+    pub instance: Vec<Vec<C::Scalar>>,
+    /// The commitments to the advice columns.
+    pub advice_commitments: Vec<C>,
+    /// The commitments to the error vector of each constraint.
+    pub error_commitments: Vec<C>,
+}
+
+/// The witness of a [`RelaxedInstance`], which the folding prover holds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedWitness<F: FieldExt> {
+    advice: Vec<Vec<F>>,
+    advice_blinds: Vec<F>,
+    error: Vec<Vec<F>>,
+    error_blinds: Vec<F>,
+}
+
+impl<F: FieldExt> RelaxedWitness<F> {
+    /// Returns the values of the advice columns.
+    pub fn advice(&self) -> &[Vec<F>] {
+        &self.advice
+    }
+
+    /// Returns the error vector of each constraint.
+    pub fn error(&self) -> &[Vec<F>] {
+        &self.error
+    }
+}
+
+/// The parts of a circuit that are needed to fold its instances: its constraints, the
+/// values of its fixed columns, and its copy constraints.
+#[derive(Debug)]
+pub struct FoldingKey<C: CurveAffine> {
+    n: usize,
+    domain: EvaluationDomain<C::Scalar>,
+    cs: ConstraintSystem<C::Scalar>,
+    constraints: Vec<Expression<C::Scalar>>,
+    /// The degree of the homogenized constraints.
+    degree: usize,
+    fixed: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+    /// The cycles of cells of each permutation argument that are constrained to be
+    /// equal.
+    copies: Vec<Vec<Vec<(Column<Any>, usize)>>>,
+}
+
+impl<C: CurveAffine> FoldingKey<C> {
+    /// Creates the folding key of a circuit.
+    ///
+    /// Returns [`FoldingError::Lookups`] if the circuit uses lookup arguments.
+    pub fn new<ConcreteCircuit: Circuit<C::Scalar>>(
+        params: &Params<C>,
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, FoldingError> {
+        let (domain, cs, config) = create_domain(params, circuit)?;
+        if !cs.lookups.is_empty() {
+            return Err(FoldingError::Lookups);
+        }
+
+        let (fixed, permutations) = assemble(params, &domain, &cs, circuit, config)?;
+        let copies = cs
+            .permutations
+            .iter()
+            .zip(permutations.iter())
+            .map(|(argument, assembly)| {
+                let columns = argument.get_columns();
+                assembly
+                    .cycles()
+                    .into_iter()
+                    .map(|cycle| {
+                        cycle
+                            .into_iter()
+                            .map(|(column, row)| (columns[column], row))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let constraints: Vec<_> = cs
+            .gates
+            .iter()
+            .flat_map(|gate| gate.polynomials().iter().cloned())
+            .collect();
+        let degree = constraints
+            .iter()
+            .map(witness_degree)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        Ok(FoldingKey {
+            n: params.n as usize,
+            domain,
+            cs,
+            constraints,
+            degree,
+            fixed,
+            copies,
+        })
+    }
+
+    /// Returns the number of advice columns of the circuit, which is the number of
+    /// advice commitments in each relaxed instance.
+    pub fn num_advice_columns(&self) -> usize {
+        self.cs.num_advice_columns
+    }
+
+    /// Returns the degree of the homogenized constraints, which is one more than the
+    /// number of cross terms that are committed to for each constraint when folding.
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    /// Synthesizes `circuit` with the given instance values, returning the relaxed
+    /// instance (with `u = 1` and no error) and its witness.
+    pub fn instance<ConcreteCircuit: Circuit<C::Scalar>>(
+        &self,
+        params: &Params<C>,
+        circuit: &ConcreteCircuit,
+        instance: Vec<Vec<C::Scalar>>,
+    ) -> Result<(RelaxedInstance<C>, RelaxedWitness<C::Scalar>), FoldingError> {
+        let instance = self.pad_instance(instance)?;
+        let instance_polys: Vec<_> = instance
+            .iter()
+            .map(|column| self.domain.lagrange_from_vec(column.clone()))
+            .collect();
+
+        let mut meta = ConstraintSystem::default();
+        let config = circuit.configure_from(&mut meta);
+        let advice: Vec<Vec<_>> = synthesize_advice(
+            self.n,
+            self.cs.num_advice_columns,
+            &self.fixed,
+            &instance_polys,
+            circuit,
+            config,
+            None,
+        )?
+        .columns()
+        .map(|column| column.to_vec())
+        .collect();
+
+        let witness = RelaxedWitness {
+            advice_blinds: advice.iter().map(|_| C::Scalar::rand()).collect(),
+            advice,
+            error: vec![vec![C::Scalar::zero(); self.n]; self.constraints.len()],
+            error_blinds: self.fresh_error_blinds(),
+        };
+        let advice_commitments = commit(params, &witness.advice, &witness.advice_blinds);
+        let instance = self.relax(params, instance, advice_commitments)?;
+
+        Ok((instance, witness))
+    }
+
+    /// Returns the relaxed instance (with `u = 1` and no error) of an instance of the
+    /// circuit, given its instance values and the commitments to its advice columns.
+    ///
+    /// This is the verifier's view of the instance that [`FoldingKey::instance`]
+    /// returns, for a verifier that receives the advice commitments from the prover.
+    pub fn relax(
+        &self,
+        params: &Params<C>,
+        instance: Vec<Vec<C::Scalar>>,
+        advice_commitments: Vec<C>,
+    ) -> Result<RelaxedInstance<C>, FoldingError> {
+        let instance = self.pad_instance(instance)?;
+        if advice_commitments.len() != self.cs.num_advice_columns {
+            return Err(FoldingError::InvalidShape);
+        }
+        let error = vec![vec![C::Scalar::zero(); self.n]; self.constraints.len()];
+
+        Ok(RelaxedInstance {
+            u: C::Scalar::one(),
+            instance,
+            advice_commitments,
+            error_commitments: commit(params, &error, &self.fresh_error_blinds()),
+        })
+    }
+
+    /// Returns the blinds of the error commitments of an instance of the circuit.
+    ///
+    /// The error of such an instance is zero, so the verifier must be able to compute
+    /// its commitments; they use the default blind rather than a random one.
+    fn fresh_error_blinds(&self) -> Vec<C::Scalar> {
+        vec![Blind::<C::Scalar>::default().0; self.constraints.len()]
+    }
+
+    /// Pads each instance column with zeroes to the number of rows of the circuit.
+    fn pad_instance(
+        &self,
+        instance: Vec<Vec<C::Scalar>>,
+    ) -> Result<Vec<Vec<C::Scalar>>, FoldingError> {
+        if instance.len() != self.cs.num_instance_columns {
+            return Err(FoldingError::InvalidShape);
+        }
+        instance
+            .into_iter()
+            .map(|mut column| {
+                if column.len() > self.n {
+                    return Err(FoldingError::InvalidShape);
+                }
+                column.resize(self.n, C::Scalar::zero());
+                Ok(column)
+            })
+            .collect()
+    }
+
+    /// Folds two relaxed instances and their witnesses into one, writing the
+    /// commitments to the cross terms to the transcript.
+    ///
+    /// The verifier obtains the same folded instance from the transcript with
+    /// [`FoldingKey::fold_instances`].
+    pub fn fold<E: EncodedChallenge<C>, T: TranscriptWrite<C, E>>(
+        &self,
+        params: &Params<C>,
+        left: (&RelaxedInstance<C>, &RelaxedWitness<C::Scalar>),
+        right: (&RelaxedInstance<C>, &RelaxedWitness<C::Scalar>),
+        transcript: &mut T,
+    ) -> Result<(RelaxedInstance<C>, RelaxedWitness<C::Scalar>), FoldingError> {
+        for (instance, witness) in iter::once(left).chain(iter::once(right)) {
+            self.check_shape(instance, Some(witness))?;
+        }
+        absorb(transcript, left.0)?;
+        absorb(transcript, right.0)?;
+
+        let cross_terms = self.cross_terms(left, right);
+        let cross_term_blinds: Vec<_> = cross_terms.iter().map(|_| C::Scalar::rand()).collect();
+        let cross_term_commitments = commit(params, &cross_terms, &cross_term_blinds);
+        for commitment in &cross_term_commitments {
+            transcript
+                .write_point(*commitment)
+                .map_err(|_| Error::TranscriptError)?;
+        }
+        let r = *transcript.squeeze_challenge_scalar::<Fold>("fold");
+
+        let (left_witness, right_witness) = (left.1, right.1);
+        let witness = RelaxedWitness {
+            advice: fold_columns(&left_witness.advice, &right_witness.advice, r),
+            advice_blinds: fold_values(
+                &left_witness.advice_blinds,
+                &right_witness.advice_blinds,
+                r,
+            ),
+            error: self.fold_error(&left_witness.error, &cross_terms, &right_witness.error, r),
+            error_blinds: self.fold_error_values(
+                &left_witness.error_blinds,
+                &cross_term_blinds,
+                &right_witness.error_blinds,
+                r,
+            ),
+        };
+        let instance = self.fold_instance(left.0, right.0, &cross_term_commitments, r);
+
+        Ok((instance, witness))
+    }
+
+    /// Folds two relaxed instances with the commitments to the cross terms that are
+    /// read from the transcript. This is the verifier's side of [`FoldingKey::fold`].
+    pub fn fold_instances<E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
+        &self,
+        left: &RelaxedInstance<C>,
+        right: &RelaxedInstance<C>,
+        transcript: &mut T,
+    ) -> Result<RelaxedInstance<C>, FoldingError> {
+        self.check_shape(left, None)?;
+        self.check_shape(right, None)?;
+        absorb(transcript, left)?;
+        absorb(transcript, right)?;
+
+        let cross_term_commitments = (0..self.constraints.len() * (self.degree - 1))
+            .map(|_| transcript.read_point())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Error::TranscriptError)?;
+        let r = *transcript.squeeze_challenge_scalar::<Fold>("fold");
+
+        Ok(self.fold_instance(left, right, &cross_term_commitments, r))
+    }
+
+    /// Checks that a relaxed instance is satisfied by its witness: the commitments
+    /// match the witness, the relaxed constraints hold on every row, and the copy
+    /// constraints hold.
+    pub fn check(
+        &self,
+        params: &Params<C>,
+        instance: &RelaxedInstance<C>,
+        witness: &RelaxedWitness<C::Scalar>,
+    ) -> Result<(), FoldingError> {
+        self.check_shape(instance, Some(witness))?;
+
+        if commit(params, &witness.advice, &witness.advice_blinds) != instance.advice_commitments
+            || commit(params, &witness.error, &witness.error_blinds) != instance.error_commitments
+        {
+            return Err(FoldingError::Commitment);
+        }
+
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            for row in 0..self.n {
+                let value = self.evaluate(
+                    constraint,
+                    row,
+                    instance.u,
+                    |column, row| witness.advice[column][row],
+                    |column, row| instance.instance[column][row],
+                );
+                if value != witness.error[index][row] {
+                    return Err(FoldingError::Constraint {
+                        constraint: index,
+                        row,
+                    });
+                }
+            }
+        }
+
+        let value = |&(column, row): &(Column<Any>, usize)| match column.column_type() {
+            Any::Advice => witness.advice[column.index()][row],
+            Any::Fixed => self.fixed[column.index()][row] * instance.u,
+            Any::Instance => instance.instance[column.index()][row],
+        };
+        for (permutation, cycles) in self.copies.iter().enumerate() {
+            for cycle in cycles {
+                let first = value(&cycle[0]);
+                if let Some(cell) = cycle[1..].iter().find(|cell| value(*cell) != first) {
+                    return Err(FoldingError::Copy {
+                        permutation,
+                        cell: *cell,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_shape(
+        &self,
+        instance: &RelaxedInstance<C>,
+        witness: Option<&RelaxedWitness<C::Scalar>>,
+    ) -> Result<(), FoldingError> {
+        let columns = |columns: &[Vec<C::Scalar>], count: usize| {
+            columns.len() == count && columns.iter().all(|column| column.len() == self.n)
+        };
+        let valid = columns(&instance.instance, self.cs.num_instance_columns)
+            && instance.advice_commitments.len() == self.cs.num_advice_columns
+            && instance.error_commitments.len() == self.constraints.len()
+            && witness.map_or(true, |witness| {
+                columns(&witness.advice, self.cs.num_advice_columns)
+                    && columns(&witness.error, self.constraints.len())
+                    && witness.advice_blinds.len() == self.cs.num_advice_columns
+                    && witness.error_blinds.len() == self.constraints.len()
+            });
+        if valid {
+            Ok(())
+        } else {
+            Err(FoldingError::InvalidShape)
+        }
+    }
+
+    /// Evaluates the homogenized constraint on the given row.
+    fn evaluate(
+        &self,
+        constraint: &Expression<C::Scalar>,
+        row: usize,
+        u: C::Scalar,
+        advice: impl Fn(usize, usize) -> C::Scalar,
+        instance: impl Fn(usize, usize) -> C::Scalar,
+    ) -> C::Scalar {
+        let rotate = |at: Rotation| (row as i64 + at.0 as i64).rem_euclid(self.n as i64) as usize;
+        let pad =
+            |value: C::Scalar, from: usize, to: usize| value * u.pow_vartime(&[(to - from) as u64]);
+
+        let (value, degree) = constraint.evaluate(
+            &|scalar| (scalar, 0),
+            &|index| {
+                let (column, at) = self.cs.fixed_queries[index];
+                (self.fixed[column.index()][rotate(at)], 0)
+            },
+            &|index| {
+                let (column, at) = self.cs.advice_queries[index];
+                (advice(column.index(), rotate(at)), 1)
+            },
+            &|index| {
+                let (column, at) = self.cs.instance_queries[index];
+                (instance(column.index(), rotate(at)), 1)
+            },
+            &|(a, a_degree), (b, b_degree)| {
+                let degree = cmp::max(a_degree, b_degree);
+                (pad(a, a_degree, degree) + pad(b, b_degree, degree), degree)
+            },
+            &|(a, a_degree), (b, b_degree)| (a * b, a_degree + b_degree),
+            &|(a, degree), scalar| (a * scalar, degree),
+        );
+        pad(value, degree, self.degree)
+    }
+
+    /// Computes the cross terms of folding two relaxed instances, as the vectors
+    /// `T_1, ..., T_{D - 1}` of each constraint in turn.
+    fn cross_terms(
+        &self,
+        left: (&RelaxedInstance<C>, &RelaxedWitness<C::Scalar>),
+        right: (&RelaxedInstance<C>, &RelaxedWitness<C::Scalar>),
+    ) -> Vec<Vec<C::Scalar>> {
+        let points: Vec<_> = (0..=self.degree)
+            .map(|point| C::Scalar::from_u64(point as u64))
+            .collect();
+
+        let mut cross_terms =
+            vec![vec![C::Scalar::zero(); self.n]; self.constraints.len() * (self.degree - 1)];
+        for (index, constraint) in self.constraints.iter().enumerate() {
+            for row in 0..self.n {
+                // Interpolate G_hom(w_1 + X w_2, u_1 + X u_2) from its values at
+                // X = 0, ..., D.
+                let evals: Vec<_> = points
+                    .iter()
+                    .map(|&x| {
+                        self.evaluate(
+                            constraint,
+                            row,
+                            left.0.u + x * right.0.u,
+                            |column, row| {
+                                left.1.advice[column][row] + x * right.1.advice[column][row]
+                            },
+                            |column, row| {
+                                left.0.instance[column][row] + x * right.0.instance[column][row]
+                            },
+                        )
+                    })
+                    .collect();
+                let coeffs = lagrange_interpolate(&points, &evals);
+                for (k, coeff) in coeffs[1..self.degree].iter().enumerate() {
+                    cross_terms[index * (self.degree - 1) + k][row] = *coeff;
+                }
+            }
+        }
+        cross_terms
+    }
+
+    /// Folds the error vectors `E_1 + r T_1 + ... + r^{D - 1} T_{D - 1} + r^D E_2` of
+    /// each constraint.
+    fn fold_error(
+        &self,
+        left: &[Vec<C::Scalar>],
+        cross_terms: &[Vec<C::Scalar>],
+        right: &[Vec<C::Scalar>],
+        r: C::Scalar,
+    ) -> Vec<Vec<C::Scalar>> {
+        (0..self.constraints.len())
+            .map(|index| {
+                (0..left[index].len())
+                    .map(|row| {
+                        let terms: Vec<_> = iter::once(left[index][row])
+                            .chain(
+                                cross_terms[index * (self.degree - 1)..][..self.degree - 1]
+                                    .iter()
+                                    .map(|term| term[row]),
+                            )
+                            .chain(iter::once(right[index][row]))
+                            .collect();
+                        eval_powers(&terms, r)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Folds a scalar (such as a blind) for each constraint in the same way as
+    /// [`FoldingKey::fold_error`].
+    fn fold_error_values(
+        &self,
+        left: &[C::Scalar],
+        cross_terms: &[C::Scalar],
+        right: &[C::Scalar],
+        r: C::Scalar,
+    ) -> Vec<C::Scalar> {
+        let left: Vec<_> = left.iter().map(|value| vec![*value]).collect();
+        let cross_terms: Vec<_> = cross_terms.iter().map(|value| vec![*value]).collect();
+        let right: Vec<_> = right.iter().map(|value| vec![*value]).collect();
+        self.fold_error(&left, &cross_terms, &right, r)
+            .into_iter()
+            .map(|values| values[0])
+            .collect()
+    }
+
+    fn fold_instance(
+        &self,
+        left: &RelaxedInstance<C>,
+        right: &RelaxedInstance<C>,
+        cross_term_commitments: &[C],
+        r: C::Scalar,
+    ) -> RelaxedInstance<C> {
+        let error_commitments = (0..self.constraints.len())
+            .map(|index| {
+                let terms: Vec<_> = iter::once(left.error_commitments[index])
+                    .chain(
+                        cross_term_commitments[index * (self.degree - 1)..][..self.degree - 1]
+                            .iter()
+                            .cloned(),
+                    )
+                    .chain(iter::once(right.error_commitments[index]))
+                    .collect();
+                eval_powers_points(&terms, r)
+            })
+            .collect::<Vec<_>>();
+
+        RelaxedInstance {
+            u: left.u + r * right.u,
+            instance: fold_columns(&left.instance, &right.instance, r),
+            advice_commitments: fold_points(&left.advice_commitments, &right.advice_commitments, r),
+            error_commitments: normalize(&error_commitments),
+        }
+    }
+}
+
+/// Returns the degree of a constraint in the advice and instance columns.
+fn witness_degree<F: FieldExt>(constraint: &Expression<F>) -> usize {
+    constraint.evaluate(
+        &|_| 0,
+        &|_| 0,
+        &|_| 1,
+        &|_| 1,
+        &cmp::max,
+        &|a, b| a + b,
+        &|a, _| a,
+    )
+}
+
+fn commit<C: CurveAffine>(
+    params: &Params<C>,
+    columns: &[Vec<C::Scalar>],
+    blinds: &[C::Scalar],
+) -> Vec<C> {
+    let commitments: Vec<_> = columns
+        .iter()
+        .zip(blinds.iter())
+        .map(|(column, blind)| params.commit_lagrange_values(column, Blind(*blind)))
+        .collect();
+    normalize(&commitments)
+}
+
+fn normalize<C: CurveAffine>(points: &[C::Curve]) -> Vec<C> {
+    let mut affine = vec![C::identity(); points.len()];
+    C::Curve::batch_normalize(points, &mut affine);
+    affine
+}
+
+/// Absorbs a relaxed instance into the transcript.
+fn absorb<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>>(
+    transcript: &mut T,
+    instance: &RelaxedInstance<C>,
+) -> Result<(), Error> {
+    transcript
+        .common_scalar("u", instance.u)
+        .map_err(|_| Error::TranscriptError)?;
+    for value in instance.instance.iter().flatten() {
+        transcript
+            .common_scalar("instance", *value)
+            .map_err(|_| Error::TranscriptError)?;
+    }
+    for commitment in instance
+        .advice_commitments
+        .iter()
+        .chain(instance.error_commitments.iter())
+    {
+        transcript
+            .common_point("commitment", *commitment)
+            .map_err(|_| Error::TranscriptError)?;
+    }
+    Ok(())
+}
+
+fn fold_values<F: FieldExt>(left: &[F], right: &[F], r: F) -> Vec<F> {
+    left.iter()
+        .zip(right.iter())
+        .map(|(left, right)| *left + r * *right)
+        .collect()
+}
+
+fn fold_columns<F: FieldExt>(left: &[Vec<F>], right: &[Vec<F>], r: F) -> Vec<Vec<F>> {
+    left.iter()
+        .zip(right.iter())
+        .map(|(left, right)| fold_values(left, right, r))
+        .collect()
+}
+
+fn fold_points<C: CurveAffine>(left: &[C], right: &[C], r: C::Scalar) -> Vec<C> {
+    let points: Vec<_> = left
+        .iter()
+        .zip(right.iter())
+        .map(|(left, right)| left.to_curve() + *right * r)
+        .collect();
+    normalize(&points)
+}
+
+/// Evaluates `terms[0] + terms[1] r + terms[2] r^2 + ...`.
+fn eval_powers<F: FieldExt>(terms: &[F], r: F) -> F {
+    terms
+        .iter()
+        .rev()
+        .fold(F::zero(), |acc, term| acc * r + *term)
+}
+
+/// Evaluates `terms[0] + [r] terms[1] + [r^2] terms[2] + ...`.
+fn eval_powers_points<C: CurveAffine>(terms: &[C], r: C::Scalar) -> C::Curve {
+    terms
+        .iter()
+        .rev()
+        .fold(C::Curve::identity(), |acc, term| acc * r + term.to_curve())
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::{EqAffine, Fp};
+
+    use super::{FoldingError, FoldingKey};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        plonk::{
+            Advice, Any, Circuit, Column, ConstraintSystem, Error, Fixed, Instance, Permutation,
+            Selector,
+        },
+        poly::{commitment::Params, Rotation},
+        transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+    };
+
+    #[derive(Clone, Debug)]
+    struct SquareConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        constant: Column<Fixed>,
+        s_mul: Selector,
+        s_pub: Selector,
+        perm: Permutation,
+    }
+
+    /// Computes `x^2` (which must equal the instance value) and `2x`, where the `2` is
+    /// copied from a fixed cell.
+    #[derive(Default)]
+    struct SquareCircuit {
+        x: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for SquareCircuit {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.advice_column();
+            let constant = meta.fixed_column();
+            let x: Column<Instance> = meta.instance_column();
+            let s_mul = meta.selector();
+            let s_pub = meta.selector();
+            let perm =
+                meta.permutation(&[Column::<Any>::from(a), b.into(), c.into(), constant.into()]);
+
+            meta.create_gate("mul", |meta| {
+                let s_mul = meta.query_selector(s_mul);
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let c = meta.query_advice(c, Rotation::cur());
+                vec![s_mul * (a * b - c)]
+            });
+            meta.create_gate("public", |meta| {
+                let s_pub = meta.query_selector(s_pub);
+                let c = meta.query_advice(c, Rotation::cur());
+                let x = meta.query_instance(x, Rotation::cur());
+                vec![s_pub * (c - x)]
+            });
+
+            SquareConfig {
+                a,
+                b,
+                c,
+                constant,
+                s_mul,
+                s_pub,
+                perm,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: SquareConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let x = self.x;
+            let two = Fp::from(2u64);
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    config.s_mul.enable(&mut region, 0)?;
+                    config.s_pub.enable(&mut region, 0)?;
+                    region.assign_advice(|| "a", config.a, 0, || x.ok_or(Error::SynthesisError))?;
+                    let b = region.assign_advice(
+                        || "b",
+                        config.b,
+                        0,
+                        || x.ok_or(Error::SynthesisError),
+                    )?;
+                    region.assign_advice(
+                        || "c",
+                        config.c,
+                        0,
+                        || x.map(|x| x.square()).ok_or(Error::SynthesisError),
+                    )?;
+
+                    config.s_mul.enable(&mut region, 1)?;
+                    let constant = region.assign_fixed(|| "two", config.constant, 1, || Ok(two))?;
+                    let a = region.assign_advice(|| "a", config.a, 1, || Ok(two))?;
+                    region.constrain_equal(&config.perm, constant, a)?;
+                    let b_next = region.assign_advice(
+                        || "b",
+                        config.b,
+                        1,
+                        || x.ok_or(Error::SynthesisError),
+                    )?;
+                    region.constrain_equal(&config.perm, b, b_next)?;
+                    region.assign_advice(
+                        || "c",
+                        config.c,
+                        1,
+                        || x.map(|x| two * x).ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn fold() {
+        const K: u32 = 4;
+        let params: Params<EqAffine> = Params::new(K);
+        let key = FoldingKey::new(&params, &SquareCircuit::default()).unwrap();
+        assert_eq!(key.degree(), 2);
+
+        let instance = |x: u64, public: u64| {
+            key.instance(
+                &params,
+                &SquareCircuit {
+                    x: Some(Fp::from(x)),
+                },
+                vec![vec![Fp::from(public)]],
+            )
+            .unwrap()
+        };
+        let (left, left_witness) = instance(3, 9);
+        let (right, right_witness) = instance(5, 25);
+        key.check(&params, &left, &left_witness).unwrap();
+        key.check(&params, &right, &right_witness).unwrap();
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        let (folded, folded_witness) = key
+            .fold(
+                &params,
+                (&left, &left_witness),
+                (&right, &right_witness),
+                &mut transcript,
+            )
+            .unwrap();
+        key.check(&params, &folded, &folded_witness).unwrap();
+        assert_ne!(folded.u, Fp::one());
+
+        // The verifier folds the instances to the same result.
+        let proof = transcript.finalize();
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        let verified = key.fold_instances(&left, &right, &mut transcript).unwrap();
+        assert_eq!(verified, folded);
+
+        // Folded instances can be folded again.
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        let (folded, folded_witness) = key
+            .fold(
+                &params,
+                (&folded, &folded_witness),
+                (&left, &left_witness),
+                &mut transcript,
+            )
+            .unwrap();
+        key.check(&params, &folded, &folded_witness).unwrap();
+
+        // Folding an unsatisfied instance gives an unsatisfied instance.
+        let (bad, bad_witness) = instance(3, 10);
+        assert!(matches!(
+            key.check(&params, &bad, &bad_witness),
+            Err(FoldingError::Constraint { row: 0, .. })
+        ));
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        let (folded, folded_witness) = key
+            .fold(
+                &params,
+                (&left, &left_witness),
+                (&bad, &bad_witness),
+                &mut transcript,
+            )
+            .unwrap();
+        assert!(key.check(&params, &folded, &folded_witness).is_err());
+    }
+}
@@ -1,9 +1,10 @@
-use ff::Field;
+use ff::{Field, PrimeField};
 use group::Curve;
 
 use super::{
     circuit::{
-        Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Selector,
+        Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Instance,
+        Selector,
     },
     permutation, Assigned, Error, LagrangeCoeff, Permutation, Polynomial, ProvingKey, VerifyingKey,
 };
@@ -15,44 +16,82 @@ use crate::{arithmetic::CurveAffine, poly::batch_invert_assigned};
 
 pub(crate) fn create_domain<C, ConcreteCircuit>(
     params: &Params<C>,
-) -> (
-    EvaluationDomain<C::Scalar>,
-    ConstraintSystem<C::Scalar>,
-    ConcreteCircuit::Config,
-)
+    circuit: &ConcreteCircuit,
+) -> Result<
+    (
+        EvaluationDomain<C::Scalar>,
+        ConstraintSystem<C::Scalar>,
+        ConcreteCircuit::Config,
+    ),
+    Error,
+>
 where
     C: CurveAffine,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
     let mut cs = ConstraintSystem::default();
-    let config = ConcreteCircuit::configure(&mut cs);
+    let config = circuit.configure_from(&mut cs);
+    cs.check_degree(params.k)?;
 
     let degree = cs.degree();
 
+    // The extended domain must be a subgroup of the multiplicative group of the
+    // scalar field.
+    if EvaluationDomain::<C::Scalar>::extended_k_for(degree as u32, params.k) > C::Scalar::S {
+        return Err(Error::IncompatibleParams);
+    }
+
     let domain = EvaluationDomain::new(degree as u32, params.k);
 
-    (domain, cs, config)
+    Ok((domain, cs, config))
+}
+
+/// Commits to the constant instance columns of a constraint system, returning
+/// [`Error::IncompatibleParams`] if one of them has more values than the circuit has
+/// rows.
+pub(crate) fn commit_constant_instances<C: CurveAffine>(
+    params: &Params<C>,
+    domain: &EvaluationDomain<C::Scalar>,
+    cs: &ConstraintSystem<C::Scalar>,
+) -> Result<Vec<(Column<Instance>, C)>, Error> {
+    cs.constant_instances
+        .iter()
+        .map(|(column, values)| {
+            if values.len() > params.n as usize {
+                return Err(Error::IncompatibleParams);
+            }
+            let mut poly = domain.empty_lagrange();
+            poly[..][..values.len()].copy_from_slice(values);
+            let commitment = params.commit_lagrange(&poly, Blind::default()).to_affine();
+            Ok((*column, commitment))
+        })
+        .collect()
 }
 
 /// Assembly to be used in circuit synthesis.
 #[derive(Debug)]
 struct Assembly<F: Field> {
+    n: usize,
+    /// The name of the current region, for reporting invalid copy constraints.
+    region: Option<String>,
+    /// The current namespace path, for reporting invalid copy constraints.
+    namespace: Vec<String>,
     fixed: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
     permutations: Vec<permutation::keygen::Assembly>,
     _marker: std::marker::PhantomData<F>,
 }
 
 impl<F: Field> Assignment<F> for Assembly<F> {
-    fn enter_region<NR, N>(&mut self, _: N)
+    fn enter_region<NR, N>(&mut self, name: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        // Do nothing; we don't care about regions in this context.
+        self.region = Some(name().into());
     }
 
     fn exit_region(&mut self) {
-        // Do nothing; we don't care about regions in this context.
+        self.region = None;
     }
 
     fn enable_selector<A, AR>(
@@ -90,6 +129,21 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         Ok(())
     }
 
+    fn assign_advice_slice<A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        _: usize,
+        _: &[Option<Assigned<F>>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about fixed columns here
+        Ok(())
+    }
+
     fn assign_fixed<V, VR, A, AR>(
         &mut self,
         _: A,
@@ -112,6 +166,34 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         Ok(())
     }
 
+    fn assign_fixed_slice<A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        values: &[Assigned<F>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.fixed
+            .get_mut(column.index())
+            .and_then(|v| v.get_mut(row..row + values.len()))
+            .ok_or(Error::BoundsFailure)?
+            .copy_from_slice(values);
+
+        Ok(())
+    }
+
+    fn fixed_value(&self, column: Column<Fixed>, row: usize) -> Result<Option<F>, Error> {
+        self.fixed
+            .get(column.index())
+            .and_then(|v| v.get(row))
+            .map(|v| Some(v.evaluate()))
+            .ok_or(Error::BoundsFailure)
+    }
+
     fn copy(
         &mut self,
         permutation: &Permutation,
@@ -125,16 +207,13 @@ impl<F: Field> Assignment<F> for Assembly<F> {
             return Err(Error::BoundsFailure);
         }
 
-        let left_column_index = permutation
-            .mapping()
-            .iter()
-            .position(|c| c == &left_column)
-            .ok_or(Error::SynthesisError)?;
-        let right_column_index = permutation
-            .mapping()
-            .iter()
-            .position(|c| c == &right_column)
-            .ok_or(Error::SynthesisError)?;
+        let (left_column_index, right_column_index) = permutation.copy_indices(
+            self.n,
+            self.region.as_deref(),
+            &self.namespace,
+            (left_column, left_row),
+            (right_column, right_row),
+        )?;
 
         self.permutations[permutation.index()].copy(
             left_column_index,
@@ -144,6 +223,134 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         )
     }
 
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespace.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        self.namespace.pop();
+    }
+}
+
+/// Assembly that records the number of rows used by a circuit's layout.
+#[derive(Debug, Default)]
+struct RowCounter {
+    rows: usize,
+}
+
+impl RowCounter {
+    fn use_row(&mut self, row: usize) {
+        self.rows = std::cmp::max(self.rows, row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn exit_region(&mut self) {
+        // Do nothing; we don't care about regions in this context.
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_advice_slice<A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if !values.is_empty() {
+            self.use_row(row + values.len() - 1);
+        }
+        Ok(())
+    }
+
+    fn assign_fixed_slice<A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        values: &[Assigned<F>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if !values.is_empty() {
+            self.use_row(row + values.len() - 1);
+        }
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: &Permutation,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.use_row(left_row);
+        self.use_row(right_row);
+        Ok(())
+    }
+
     fn push_namespace<NR, N>(&mut self, _: N)
     where
         NR: Into<String>,
@@ -157,18 +364,60 @@ impl<F: Field> Assignment<F> for Assembly<F> {
     }
 }
 
-/// Generate a `VerifyingKey` from an instance of `Circuit`.
-pub fn keygen_vk<C, ConcreteCircuit>(
+/// Returns the smallest `k` such that the given circuit fits within $2^k$ rows.
+///
+/// The circuit is laid out (without witnesses) by its floor planner, and the rows used
+/// by its regions (including any lookup tables assigned within them) are measured.
+/// Enough rows are then added that queries at the largest rotation used by the circuit
+/// read unassigned cells, rather than wrapping around onto assigned ones.
+///
+/// Instance columns are not assigned during synthesis, so their lengths are not taken
+/// into account. Use [`Params::downsize`] to obtain parameters for the returned `k`.
+pub fn k_for_circuit<F, ConcreteCircuit>(circuit: &ConcreteCircuit) -> Result<u32, Error>
+where
+    F: Field,
+    ConcreteCircuit: Circuit<F>,
+{
+    let mut cs = ConstraintSystem::default();
+    let config = circuit.configure_from(&mut cs);
+
+    let mut counter = RowCounter::default();
+    ConcreteCircuit::FloorPlanner::synthesize(&mut counter, &circuit.without_witnesses(), config)?;
+
+    let (backward, forward) = cs.max_rotation();
+
+    let rows = counter.rows + std::cmp::max(backward, forward);
+    let mut k = 0;
+    while (1 << k) < rows {
+        k += 1;
+    }
+
+    Ok(k)
+}
+
+/// Synthesizes `circuit` to obtain the values of its fixed columns and the copy
+/// constraints of each of its permutation arguments.
+pub(crate) fn assemble<C, ConcreteCircuit>(
     params: &Params<C>,
+    domain: &EvaluationDomain<C::Scalar>,
+    cs: &ConstraintSystem<C::Scalar>,
     circuit: &ConcreteCircuit,
-) -> Result<VerifyingKey<C>, Error>
+    config: ConcreteCircuit::Config,
+) -> Result<
+    (
+        Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
+        Vec<permutation::keygen::Assembly>,
+    ),
+    Error,
+>
 where
     C: CurveAffine,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params);
-
     let mut assembly: Assembly<C::Scalar> = Assembly {
+        n: params.n as usize,
+        region: None,
+        namespace: vec![],
         fixed: vec![domain.empty_lagrange_assigned(); cs.num_fixed_columns],
         permutations: cs
             .permutations
@@ -179,32 +428,101 @@ where
     };
 
     // Synthesize the circuit to obtain URS
-    ConcreteCircuit::FloorPlanner::synthesize(&mut assembly, circuit, config)?;
+    {
+        trace_span!("synthesize");
+        ConcreteCircuit::FloorPlanner::synthesize(&mut assembly, circuit, config)?;
+    }
 
-    let fixed = batch_invert_assigned(&assembly.fixed);
+    Ok((
+        batch_invert_assigned(&assembly.fixed),
+        assembly.permutations,
+    ))
+}
 
-    let permutation_helper = permutation::keygen::Assembly::build_helper(params, &cs, &domain);
+/// Generate a `VerifyingKey` from an instance of `Circuit`.
+pub fn keygen_vk<C, ConcreteCircuit>(
+    params: &Params<C>,
+    circuit: &ConcreteCircuit,
+) -> Result<VerifyingKey<C>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let (domain, cs, config) = create_domain(params, circuit)?;
+    trace_span!(
+        "keygen_vk",
+        k = params.k,
+        fixed_columns = cs.num_fixed_columns,
+        permutations = cs.permutations.len(),
+    );
+
+    // Queries must not wrap all the way around the domain.
+    let (backward, forward) = cs.max_rotation();
+    if std::cmp::max(backward, forward) >= params.n as usize {
+        return Err(Error::IncompatibleParams);
+    }
 
-    let permutation_vks = cs
-        .permutations
-        .iter()
-        .zip(assembly.permutations.into_iter())
-        .map(|(p, assembly)| assembly.build_vk(params, &domain, &permutation_helper, p))
-        .collect();
+    let (fixed, permutations) = assemble(params, &domain, &cs, circuit, config)?;
 
-    let fixed_commitments = fixed
-        .iter()
-        .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
-        .collect();
+    let permutation_vks = {
+        trace_span!("permutation_commitments", arguments = cs.permutations.len());
+        let permutation_helper = permutation::keygen::Assembly::build_helper(params, &cs, &domain);
+
+        cs.permutations
+            .iter()
+            .zip(permutations.into_iter())
+            .map(|(p, assembly)| assembly.build_vk(params, &domain, &permutation_helper, p))
+            .collect()
+    };
+
+    let fixed_commitments = {
+        trace_span!("fixed_commitments", columns = fixed.len());
+        fixed
+            .iter()
+            .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
+            .collect()
+    };
+
+    let constant_instance_commitments = commit_constant_instances(params, &domain, &cs)?;
+
+    let (cs, merged_fixed_columns) = if cs.compress_fixed_columns {
+        let map = fixed_column_map(&fixed);
+        trace_event!(
+            merged_columns = map
+                .iter()
+                .enumerate()
+                .filter(|(column, merged)| column != *merged)
+                .count(),
+            "merged fixed columns"
+        );
+        (cs.merge_fixed_columns(&map), Some((cs, map)))
+    } else {
+        (cs, None)
+    };
 
     Ok(VerifyingKey {
         domain,
         fixed_commitments,
         permutations: permutation_vks,
+        constant_instance_commitments,
         cs,
+        merged_fixed_columns,
     })
 }
 
+/// Returns, for each of the given fixed columns, the index of the first column with
+/// the same values.
+fn fixed_column_map<F: Field>(fixed: &[Polynomial<F, LagrangeCoeff>]) -> Vec<usize> {
+    let mut map: Vec<usize> = Vec::with_capacity(fixed.len());
+    for (column, values) in fixed.iter().enumerate() {
+        let merged = (0..column)
+            .find(|&other| map[other] == other && fixed[other][..] == values[..])
+            .unwrap_or(column);
+        map.push(merged);
+    }
+    map
+}
+
 /// Generate a `ProvingKey` from a `VerifyingKey` and an instance of `Circuit`.
 pub fn keygen_pk<C, ConcreteCircuit>(
     params: &Params<C>,
@@ -216,9 +534,19 @@ where
     ConcreteCircuit: Circuit<C::Scalar>,
 {
     let mut cs = ConstraintSystem::default();
-    let config = ConcreteCircuit::configure(&mut cs);
+    let config = circuit.configure_from(&mut cs);
+    trace_span!(
+        "keygen_pk",
+        k = params.k,
+        fixed_columns = vk.cs.num_fixed_columns,
+        fixed_queries = vk.cs.fixed_queries.len(),
+        permutations = vk.cs.permutations.len(),
+    );
 
     let mut assembly: Assembly<C::Scalar> = Assembly {
+        n: params.n as usize,
+        region: None,
+        namespace: vec![],
         fixed: vec![vk.domain.empty_lagrange_assigned(); vk.cs.num_fixed_columns],
         permutations: vk
             .cs
@@ -230,42 +558,56 @@ where
     };
 
     // Synthesize the circuit to obtain URS
-    ConcreteCircuit::FloorPlanner::synthesize(&mut assembly, circuit, config)?;
+    {
+        trace_span!("synthesize");
+        ConcreteCircuit::FloorPlanner::synthesize(&mut assembly, circuit, config)?;
+    }
 
     let fixed = batch_invert_assigned(&assembly.fixed);
 
-    let fixed_polys: Vec<_> = fixed
-        .iter()
-        .map(|poly| vk.domain.lagrange_to_coeff(poly.clone()))
-        .collect();
+    let (fixed_polys, fixed_cosets) = {
+        trace_span!("fixed_polys", columns = fixed.len());
+        let fixed_polys: Vec<_> = fixed
+            .iter()
+            .map(|poly| vk.domain.lagrange_to_coeff(poly.clone()))
+            .collect();
 
-    let fixed_cosets = vk
-        .cs
-        .fixed_queries
-        .iter()
-        .map(|&(column, at)| {
-            let poly = fixed_polys[column.index()].clone();
-            vk.domain.coeff_to_extended(poly, at)
-        })
-        .collect();
+        let fixed_cosets = vk
+            .cs
+            .fixed_queries
+            .iter()
+            .map(|&(column, at)| {
+                let poly = fixed_polys[column.index()].clone();
+                vk.domain.coeff_to_extended(poly, at)
+            })
+            .collect();
+
+        (fixed_polys, fixed_cosets)
+    };
 
-    let permutation_helper =
-        permutation::keygen::Assembly::build_helper(params, &vk.cs, &vk.domain);
+    let permutation_pks = {
+        trace_span!("permutation_polys", arguments = vk.cs.permutations.len());
+        let permutation_helper =
+            permutation::keygen::Assembly::build_helper(params, &vk.cs, &vk.domain);
 
-    let permutation_pks = vk
-        .cs
-        .permutations
-        .iter()
-        .zip(assembly.permutations.into_iter())
-        .map(|(p, assembly)| assembly.build_pk(&vk.domain, &permutation_helper, p))
-        .collect();
+        vk.cs
+            .permutations
+            .iter()
+            .zip(assembly.permutations.into_iter())
+            .map(|(p, assembly)| assembly.build_pk(&vk.domain, &permutation_helper, p))
+            .collect()
+    };
 
-    // Compute l_0(X)
+    // Compute l_0(X), which is only used by lookup and permutation arguments.
     // TODO: this can be done more efficiently
-    let mut l0 = vk.domain.empty_lagrange();
-    l0[0] = C::Scalar::one();
-    let l0 = vk.domain.lagrange_to_coeff(l0);
-    let l0 = vk.domain.coeff_to_extended(l0, Rotation::cur());
+    let l0 = if vk.cs.has_lookups_or_permutations() {
+        let mut l0 = vk.domain.empty_lagrange();
+        l0[0] = C::Scalar::one();
+        let l0 = vk.domain.lagrange_to_coeff(l0);
+        Some(vk.domain.coeff_to_extended(l0, Rotation::cur()))
+    } else {
+        None
+    };
 
     Ok(ProvingKey {
         vk,
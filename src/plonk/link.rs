@@ -0,0 +1,84 @@
+use super::{Advice, Column, Proof, VerifyingKey};
+use crate::arithmetic::CurveAffine;
+
+/// An advice column of a circuit instance within a proof, which can be linked to a
+/// column of another proof with [`check_link`].
+#[derive(Clone, Copy, Debug)]
+pub struct LinkedColumn<'a, C: CurveAffine> {
+    /// The verifying key that the proof was created with.
+    pub vk: &'a VerifyingKey<C>,
+    /// The proof, which must have been verified.
+    pub proof: &'a Proof<C>,
+    /// The index of the circuit instance within the proof.
+    pub instance: usize,
+    /// The advice column, which must be unblinded.
+    pub column: Column<Advice>,
+}
+
+/// The reasons why [`check_link`] can reject a link between two columns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkError {
+    /// The proof does not contain the given circuit instance or advice column.
+    NoSuchColumn {
+        /// The index of the circuit instance.
+        instance: usize,
+        /// The index of the advice column.
+        column: usize,
+    },
+    /// The advice column is blinded, so its commitment cannot be compared with the
+    /// commitments in other proofs. See [`ConstraintSystem::unblinded_advice_column`].
+    ///
+    /// [`ConstraintSystem::unblinded_advice_column`]: super::ConstraintSystem::unblinded_advice_column
+    Blinded {
+        /// The index of the advice column.
+        column: usize,
+    },
+    /// The commitments to the two columns differ, so the columns contain different
+    /// values.
+    Mismatch,
+}
+
+impl<'a, C: CurveAffine> LinkedColumn<'a, C> {
+    /// Returns the commitment to this column.
+    pub fn commitment(&self) -> Result<C, LinkError> {
+        if !self.vk.cs.is_unblinded(self.column) {
+            return Err(LinkError::Blinded {
+                column: self.column.index(),
+            });
+        }
+
+        self.proof
+            .instances()
+            .get(self.instance)
+            .and_then(|instance| instance.advice_commitments.get(self.column.index()))
+            .copied()
+            .ok_or(LinkError::NoSuchColumn {
+                instance: self.instance,
+                column: self.column.index(),
+            })
+    }
+}
+
+/// Checks that two unblinded advice columns, in the same or different proofs, contain
+/// the same values.
+///
+/// The commitment to an unblinded column is a deterministic function of its values,
+/// so the columns are equal exactly when their commitments are. This allows a large
+/// state to be passed from one proof to another (for example, between the steps of a
+/// continuation) without hashing it: the first proof writes the state into an
+/// unblinded column, and the second proof reads it from one.
+///
+/// Both proofs must also be verified with [`verify_proof`]; this only compares the
+/// commitments that they contain.
+///
+/// [`verify_proof`]: super::verify_proof
+pub fn check_link<C: CurveAffine>(
+    a: &LinkedColumn<'_, C>,
+    b: &LinkedColumn<'_, C>,
+) -> Result<(), LinkError> {
+    if a.commitment()? == b.commitment()? {
+        Ok(())
+    } else {
+        Err(LinkError::Mismatch)
+    }
+}
@@ -1,24 +1,44 @@
-use super::circuit::Expression;
+use super::circuit::{Expression, Selector, VirtualCell};
 use ff::Field;
+use std::fmt;
 
 pub(crate) mod prover;
 pub(crate) mod verifier;
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub(crate) struct Argument<F: Field> {
     pub input_expressions: Vec<Expression<F>>,
     pub table_expressions: Vec<Expression<F>>,
+    /// The selectors queried by the lookup, each with the cells used by the input
+    /// expressions (excluding selectors and instance cells) on the rows where it is
+    /// enabled.
+    pub selector_cells: Vec<(Selector, Vec<VirtualCell>)>,
+}
+
+// The selectors and cells are derived from the expressions, and are not part of the
+// pinned verification key.
+impl<F: Field> fmt::Debug for Argument<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Argument")
+            .field("input_expressions", &self.input_expressions)
+            .field("table_expressions", &self.table_expressions)
+            .finish()
+    }
 }
 
 impl<F: Field> Argument<F> {
     /// Constructs a new lookup argument.
     ///
     /// `table_map` is a sequence of `(input, table)` tuples.
-    pub fn new(table_map: Vec<(Expression<F>, Expression<F>)>) -> Self {
+    pub fn new(
+        table_map: Vec<(Expression<F>, Expression<F>)>,
+        selector_cells: Vec<(Selector, Vec<VirtualCell>)>,
+    ) -> Self {
         let (input_expressions, table_expressions) = table_map.into_iter().unzip();
         Argument {
             input_expressions,
             table_expressions,
+            selector_cells,
         }
     }
 
@@ -402,23 +402,27 @@ impl<'a, C: CurveAffine> Committed<C> {
     /// method constructs constraints that must hold between these values.
     /// This method returns the constraints as a vector of polynomials in
     /// the extended evaluation domain.
+    ///
+    /// Returns [`Error::IncompatibleParams`] if the proving key lacks `l_0(X)`.
     pub(in crate::plonk) fn construct(
         self,
         pk: &'a ProvingKey<C>,
         theta: ChallengeTheta<C>,
         beta: ChallengeBeta<C>,
         gamma: ChallengeGamma<C>,
-    ) -> (
-        Constructed<C>,
-        impl Iterator<Item = Polynomial<C::Scalar, ExtendedLagrangeCoeff>> + 'a,
-    ) {
+    ) -> Result<
+        (
+            Constructed<C>,
+            impl Iterator<Item = Polynomial<C::Scalar, ExtendedLagrangeCoeff>> + 'a,
+        ),
+        Error,
+    > {
+        let l0 = pk.l0()?;
         let permuted = self.permuted;
 
         let expressions = iter::empty()
             // l_0(X) * (1 - z'(X)) = 0
-            .chain(Some(
-                Polynomial::one_minus(self.product_coset.clone()) * &pk.l0,
-            ))
+            .chain(Some(Polynomial::one_minus(self.product_coset.clone()) * l0))
             // z'(X) (a'(X) + \beta) (s'(X) + \gamma)
             // - z'(\omega^{-1} X) (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \beta) (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \gamma)
             .chain({
@@ -467,7 +471,7 @@ impl<'a, C: CurveAffine> Committed<C> {
             // fixed expression are the same.
             // l_0(X) * (a'(X) - s'(X)) = 0
             .chain(Some(
-                (permuted.permuted_input_coset.clone() - &permuted.permuted_table_coset) * &pk.l0,
+                (permuted.permuted_input_coset.clone() - &permuted.permuted_table_coset) * l0,
             ))
             // Check that each value in the permuted lookup input expression is either
             // equal to the value above it, or the value at the same index in the
@@ -478,7 +482,7 @@ impl<'a, C: CurveAffine> Committed<C> {
                     * &(permuted.permuted_input_coset.clone() - &permuted.permuted_input_inv_coset),
             ));
 
-        (
+        Ok((
             Constructed {
                 permuted_input_poly: permuted.permuted_input_poly,
                 permuted_input_blind: permuted.permuted_input_blind,
@@ -488,7 +492,7 @@ impl<'a, C: CurveAffine> Committed<C> {
                 product_blind: self.product_blind,
             },
             expressions,
-        )
+        ))
     }
 }
 
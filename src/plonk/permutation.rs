@@ -12,6 +12,12 @@ pub(crate) mod verifier;
 
 use std::io;
 
+#[cfg(feature = "mmap")]
+use crate::{
+    mmap::{RawReader, RawWriter},
+    poly::EvaluationDomain,
+};
+
 /// A permutation argument.
 #[derive(Debug, Clone)]
 pub(crate) struct Argument {
@@ -24,7 +30,7 @@ impl Argument {
         Argument { columns }
     }
 
-    pub(crate) fn required_degree(&self) -> usize {
+    pub(crate) fn required_degree(&self, degree_bound: usize) -> usize {
         // The permutation argument will serve alongside the gates, so must be
         // accounted for. There are constraints of degree 2 regardless of the
         // number of columns involved. (It doesn't make sense to make a
@@ -32,12 +38,40 @@ impl Argument {
         // for it here.)
 
         // degree 2:
-        // l_0(X) * (1 - z(X)) = 0
+        // l_0(X) * (1 - z_0(X)) = 0
         //
-        // degree columns + 1
-        // z(X) \prod (p(X) + \beta s_i(X) + \gamma)
-        // - z(omega^{-1} X) \prod (p(X) + \delta^i \beta X + \gamma)
-        std::cmp::max(self.columns.len() + 1, 2)
+        // With a single chunk, of degree columns + 1:
+        // z_0(X) \prod (p(X) + \beta s_i(X) + \gamma)
+        // - z_0(omega^{-1} X) \prod (p(X) + \delta^i \beta X + \gamma)
+        //
+        // With several chunks, for each chunk j, of degree chunk_len + 2:
+        // z_j(X) \prod (p(X) + \beta s_i(X) + \gamma)
+        // - ((1 - l_0(X)) z_j(omega^{-1} X) + l_0(X) z_{j-1}(omega^{-1} X))
+        //   \prod (p(X) + \delta^i \beta X + \gamma)
+        if self.num_chunks(degree_bound) <= 1 {
+            std::cmp::max(self.columns.len() + 1, 2)
+        } else {
+            self.chunk_len(degree_bound) + 2
+        }
+    }
+
+    /// Returns the number of columns in each chunk of the argument (the last chunk may
+    /// have fewer), such that the constraints of each chunk have a degree of at most
+    /// `degree_bound`.
+    pub(crate) fn chunk_len(&self, degree_bound: usize) -> usize {
+        assert!(degree_bound >= 3);
+        if self.columns.len() < degree_bound {
+            std::cmp::max(self.columns.len(), 1)
+        } else {
+            degree_bound - 2
+        }
+    }
+
+    /// Returns the number of chunks, each with its own product polynomial, that the
+    /// argument is split into.
+    pub(crate) fn num_chunks(&self, degree_bound: usize) -> usize {
+        let chunk_len = self.chunk_len(degree_bound);
+        (self.columns.len() + chunk_len - 1) / chunk_len
     }
 
     pub(crate) fn get_columns(&self) -> Vec<Column<Any>> {
@@ -52,6 +86,10 @@ pub(crate) struct VerifyingKey<C: CurveAffine> {
 }
 
 impl<C: CurveAffine> VerifyingKey<C> {
+    pub(crate) fn commitments(&self) -> &[C] {
+        &self.commitments
+    }
+
     pub(crate) fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
         for commitment in &self.commitments {
             commitment.write(writer)?;
@@ -75,3 +113,45 @@ pub(crate) struct ProvingKey<C: CurveAffine> {
     polys: Vec<Polynomial<C::Scalar, Coeff>>,
     cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
 }
+
+#[cfg(feature = "mmap")]
+#[allow(unsafe_code)]
+impl<C: CurveAffine> ProvingKey<C> {
+    pub(crate) fn write_raw<W: io::Write>(&self, raw: &mut RawWriter<W>) -> io::Result<()> {
+        for poly in &self.permutations {
+            raw.write_poly(poly)?;
+        }
+        for poly in &self.polys {
+            raw.write_poly(poly)?;
+        }
+        for poly in &self.cosets {
+            raw.write_poly(poly)?;
+        }
+        Ok(())
+    }
+
+    /// # Safety
+    ///
+    /// See [`RawReader::read_table`].
+    pub(crate) unsafe fn read_raw(
+        raw: &mut RawReader,
+        argument: &Argument,
+        domain: &EvaluationDomain<C::Scalar>,
+    ) -> io::Result<Self> {
+        let columns = argument.columns.len();
+        let permutations = (0..columns)
+            .map(|_| raw.read_lagrange(domain))
+            .collect::<Result<_, _>>()?;
+        let polys = (0..columns)
+            .map(|_| raw.read_coeff(domain))
+            .collect::<Result<_, _>>()?;
+        let cosets = (0..columns)
+            .map(|_| raw.read_extended(domain))
+            .collect::<Result<_, _>>()?;
+        Ok(ProvingKey {
+            permutations,
+            polys,
+            cosets,
+        })
+    }
+}
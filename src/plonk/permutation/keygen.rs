@@ -3,7 +3,7 @@ use group::Curve;
 
 use super::{Argument, ProvingKey, VerifyingKey};
 use crate::{
-    arithmetic::{CurveAffine, FieldExt},
+    arithmetic::{CurveAffine, TwoAdicField},
     plonk::{circuit::ConstraintSystem, Error},
     poly::{
         commitment::{Blind, Params},
@@ -90,6 +90,32 @@ impl Assembly {
         Ok(())
     }
 
+    /// Returns the cycles of the permutation that contain more than one cell, as sorted
+    /// lists of `(column, row)` pairs where `column` is an index into the argument's
+    /// columns.
+    pub(crate) fn cycles(&self) -> Vec<Vec<(usize, usize)>> {
+        let mut cycles = vec![];
+        for (i, column) in self.aux.iter().enumerate() {
+            for (j, distinguished) in column.iter().enumerate() {
+                // Visit each cycle once, from its distinguished element.
+                if *distinguished != (i, j) || self.sizes[i][j] == 1 {
+                    continue;
+                }
+
+                let mut cycle = vec![(i, j)];
+                let mut cell = self.mapping[i][j];
+                while cell != (i, j) {
+                    cycle.push(cell);
+                    cell = self.mapping[cell.0][cell.1];
+                }
+                cycle.sort_unstable();
+                cycles.push(cycle);
+            }
+        }
+        cycles.sort_unstable();
+        cycles
+    }
+
     pub(crate) fn build_helper<C: CurveAffine>(
         params: &Params<C>,
         cs: &ConstraintSystem<C::Scalar>,
@@ -104,6 +130,11 @@ impl Assembly {
             .max()
             .unwrap_or_default();
 
+        // Without any permutation arguments, there is nothing to look up.
+        if largest_permutation_length == 0 {
+            return AssemblyHelper { deltaomega: vec![] };
+        }
+
         // Compute [omega^0, omega^1, ..., omega^{params.n - 1}]
         let mut omega_powers = Vec::with_capacity(params.n as usize);
         {
@@ -126,7 +157,7 @@ impl Assembly {
 
                 deltaomega.push(omega_powers);
 
-                cur *= &C::Scalar::DELTA;
+                cur *= &C::Scalar::delta();
             }
         }
 
@@ -2,10 +2,16 @@ use ff::Field;
 use group::Curve;
 use std::iter;
 
-use super::super::{circuit::Any, ChallengeBeta, ChallengeGamma, ChallengeX};
+use super::super::{
+    circuit::{Any, Column},
+    ChallengeBeta, ChallengeGamma, ChallengeX,
+};
 use super::{Argument, ProvingKey};
 use crate::{
-    arithmetic::{eval_polynomial, parallelize, BatchInvert, CurveAffine, FieldExt},
+    arithmetic::{
+        eval_polynomial, parallelize, BatchInvert, CubeRootOfUnity, CurveAffine, FieldExt,
+        TwoAdicField,
+    },
     plonk::{self, Error},
     poly::{
         commitment::{Blind, Params},
@@ -15,18 +21,26 @@ use crate::{
     transcript::{EncodedChallenge, TranscriptWrite},
 };
 
-pub(crate) struct Committed<C: CurveAffine> {
+pub(crate) struct CommittedSet<C: CurveAffine> {
     permutation_product_poly: Polynomial<C::Scalar, Coeff>,
     permutation_product_coset: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
     permutation_product_coset_inv: Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
     permutation_product_blind: Blind<C::Scalar>,
 }
 
-pub(crate) struct Constructed<C: CurveAffine> {
+pub(crate) struct Committed<C: CurveAffine> {
+    sets: Vec<CommittedSet<C>>,
+}
+
+pub(crate) struct ConstructedSet<C: CurveAffine> {
     permutation_product_poly: Polynomial<C::Scalar, Coeff>,
     permutation_product_blind: Blind<C::Scalar>,
 }
 
+pub(crate) struct Constructed<C: CurveAffine> {
+    sets: Vec<ConstructedSet<C>>,
+}
+
 pub(crate) struct Evaluated<C: CurveAffine> {
     constructed: Constructed<C>,
 }
@@ -49,104 +63,123 @@ impl Argument {
         transcript: &mut T,
     ) -> Result<Committed<C>, Error> {
         let domain = &pk.vk.domain;
+        let chunk_len = self.chunk_len(pk.vk.cs.permutation_degree_bound());
 
-        // Goal is to compute the products of fractions
-        //
-        // (p_j(\omega^i) + \delta^j \omega^i \beta + \gamma) /
-        // (p_j(\omega^i) + \beta s_j(\omega^i) + \gamma)
-        //
-        // where p_j(X) is the jth column in this permutation,
-        // and i is the ith row of the column.
-
-        let mut modified_values = vec![C::Scalar::one(); params.n as usize];
-
-        // Iterate over each column of the permutation
-        for (&column, permuted_column_values) in self.columns.iter().zip(pkey.permutations.iter()) {
-            let values = match column.column_type() {
-                Any::Advice => advice,
-                Any::Fixed => fixed,
-                Any::Instance => instance,
-            };
-            parallelize(&mut modified_values, |modified_values, start| {
-                for ((modified_values, value), permuted_value) in modified_values
-                    .iter_mut()
-                    .zip(values[column.index()][start..].iter())
-                    .zip(permuted_column_values[start..].iter())
-                {
-                    *modified_values *= &(*beta * permuted_value + &*gamma + value);
-                }
-            });
-        }
-
-        // Invert to obtain the denominator for the permutation product polynomial
-        modified_values.batch_invert();
-
-        // Iterate over each column again, this time finishing the computation
-        // of the entire fraction by computing the numerators
+        // Each chunk of columns has its own product polynomial. The first chunk's
+        // product starts at 1, and each subsequent chunk's product continues from
+        // the last value of the previous one.
+        let mut sets = vec![];
+        let mut last_z: Option<C::Scalar> = None;
         let mut deltaomega = C::Scalar::one();
-        for &column in self.columns.iter() {
-            let omega = domain.get_omega();
-            let values = match column.column_type() {
-                Any::Advice => advice,
-                Any::Fixed => fixed,
-                Any::Instance => instance,
-            };
-            parallelize(&mut modified_values, |modified_values, start| {
-                let mut deltaomega = deltaomega * &omega.pow_vartime(&[start as u64, 0, 0, 0]);
-                for (modified_values, value) in modified_values
-                    .iter_mut()
-                    .zip(values[column.index()][start..].iter())
-                {
-                    // Multiply by p_j(\omega^i) + \delta^j \omega^i \beta
-                    *modified_values *= &(deltaomega * &*beta + &*gamma + value);
-                    deltaomega *= &omega;
-                }
-            });
-            deltaomega *= &C::Scalar::DELTA;
-        }
-
-        // The modified_values vector is a vector of products of fractions
-        // of the form
-        //
-        // (p_j(\omega^i) + \delta^j \omega^i \beta + \gamma) /
-        // (p_j(\omega^i) + \beta s_j(\omega^i) + \gamma)
-        //
-        // where i is the index into modified_values, for the jth column in
-        // the permutation
-
-        // Compute the evaluations of the permutation product polynomial
-        // over our domain, starting with z[0] = 1
-        let mut z = vec![C::Scalar::one()];
-        for row in 1..(params.n as usize) {
-            let mut tmp = z[row - 1];
+        for (columns, permutations) in self
+            .columns
+            .chunks(chunk_len)
+            .zip(pkey.permutations.chunks(chunk_len))
+        {
+            // Goal is to compute the products of fractions
+            //
+            // (p_j(\omega^i) + \delta^j \omega^i \beta + \gamma) /
+            // (p_j(\omega^i) + \beta s_j(\omega^i) + \gamma)
+            //
+            // where p_j(X) is the jth column in this permutation,
+            // and i is the ith row of the column.
+
+            let mut modified_values = vec![C::Scalar::one(); params.n as usize];
+
+            // Iterate over each column of the chunk
+            for (&column, permuted_column_values) in columns.iter().zip(permutations.iter()) {
+                let values = match column.column_type() {
+                    Any::Advice => advice,
+                    Any::Fixed => fixed,
+                    Any::Instance => instance,
+                };
+                parallelize(&mut modified_values, |modified_values, start| {
+                    for ((modified_values, value), permuted_value) in modified_values
+                        .iter_mut()
+                        .zip(values[column.index()][start..].iter())
+                        .zip(permuted_column_values[start..].iter())
+                    {
+                        *modified_values *= &(*beta * permuted_value + &*gamma + value);
+                    }
+                });
+            }
+
+            // Invert to obtain the denominator for the permutation product polynomial
+            modified_values.batch_invert();
+
+            // Iterate over each column again, this time finishing the computation
+            // of the entire fraction by computing the numerators
+            for &column in columns.iter() {
+                let omega = domain.get_omega();
+                let values = match column.column_type() {
+                    Any::Advice => advice,
+                    Any::Fixed => fixed,
+                    Any::Instance => instance,
+                };
+                parallelize(&mut modified_values, |modified_values, start| {
+                    let mut deltaomega = deltaomega * &omega.pow_vartime(&[start as u64, 0, 0, 0]);
+                    for (modified_values, value) in modified_values
+                        .iter_mut()
+                        .zip(values[column.index()][start..].iter())
+                    {
+                        // Multiply by p_j(\omega^i) + \delta^j \omega^i \beta
+                        *modified_values *= &(deltaomega * &*beta + &*gamma + value);
+                        deltaomega *= &omega;
+                    }
+                });
+                deltaomega *= &C::Scalar::delta();
+            }
+
+            // The modified_values vector is a vector of products of fractions
+            // of the form
+            //
+            // (p_j(\omega^i) + \delta^j \omega^i \beta + \gamma) /
+            // (p_j(\omega^i) + \beta s_j(\omega^i) + \gamma)
+            //
+            // where i is the index into modified_values, for the jth column in
+            // the chunk
+
+            // Compute the evaluations of the chunk's product polynomial over our
+            // domain, starting with z[0] = 1 for the first chunk
+            let mut z = vec![match last_z {
+                Some(last_z) => last_z * &modified_values[0],
+                None => C::Scalar::one(),
+            }];
+            for row in 1..(params.n as usize) {
+                let mut tmp = z[row - 1];
+
+                tmp *= &modified_values[row];
+                z.push(tmp);
+            }
+            last_z = Some(z[params.n as usize - 1]);
+            let z = domain.lagrange_from_vec(z);
+
+            let blind = Blind(C::Scalar::rand());
+
+            let permutation_product_commitment_projective = params.commit_lagrange(&z, blind);
+            let permutation_product_blind = blind;
+            let z = domain.lagrange_to_coeff(z);
+            let permutation_product_poly = z.clone();
+            let permutation_product_coset = domain.coeff_to_extended(z.clone(), Rotation::cur());
+            let permutation_product_coset_inv = domain.coeff_to_extended(z, Rotation::prev());
+
+            let permutation_product_commitment =
+                permutation_product_commitment_projective.to_affine();
+
+            // Hash the permutation product commitment
+            transcript
+                .write_point(permutation_product_commitment)
+                .map_err(|_| Error::TranscriptError)?;
 
-            tmp *= &modified_values[row];
-            z.push(tmp);
+            sets.push(CommittedSet {
+                permutation_product_poly,
+                permutation_product_coset,
+                permutation_product_coset_inv,
+                permutation_product_blind,
+            });
         }
-        let z = domain.lagrange_from_vec(z);
-
-        let blind = Blind(C::Scalar::rand());
-
-        let permutation_product_commitment_projective = params.commit_lagrange(&z, blind);
-        let permutation_product_blind = blind;
-        let z = domain.lagrange_to_coeff(z);
-        let permutation_product_poly = z.clone();
-        let permutation_product_coset = domain.coeff_to_extended(z.clone(), Rotation::cur());
-        let permutation_product_coset_inv = domain.coeff_to_extended(z, Rotation::prev());
-
-        let permutation_product_commitment = permutation_product_commitment_projective.to_affine();
-
-        // Hash the permutation product commitment
-        transcript
-            .write_point(permutation_product_commitment)
-            .map_err(|_| Error::TranscriptError)?;
 
-        Ok(Committed {
-            permutation_product_poly,
-            permutation_product_coset,
-            permutation_product_coset_inv,
-            permutation_product_blind,
-        })
+        Ok(Committed { sets })
     }
 }
 
@@ -161,81 +194,94 @@ impl<C: CurveAffine> Committed<C> {
         instance_cosets: &'a [Polynomial<C::Scalar, ExtendedLagrangeCoeff>],
         beta: ChallengeBeta<C>,
         gamma: ChallengeGamma<C>,
-    ) -> (
-        Constructed<C>,
-        impl Iterator<Item = Polynomial<C::Scalar, ExtendedLagrangeCoeff>> + 'a,
-    ) {
+    ) -> Result<
+        (
+            Constructed<C>,
+            impl Iterator<Item = Polynomial<C::Scalar, ExtendedLagrangeCoeff>> + 'a,
+        ),
+        Error,
+    > {
+        let l0 = pk.l0()?;
         let domain = &pk.vk.domain;
-        let expressions = iter::empty()
-            // l_0(X) * (1 - z(X)) = 0
-            .chain(Some(
-                Polynomial::one_minus(self.permutation_product_coset.clone()) * &pk.l0,
-            ))
-            // z(X) \prod (p(X) + \beta s_i(X) + \gamma) - z(omega^{-1} X) \prod (p(X) + \delta^i \beta X + \gamma)
-            .chain(Some({
-                let mut left = self.permutation_product_coset.clone();
-                for (values, permutation) in p
-                    .columns
-                    .iter()
-                    .map(|&column| match column.column_type() {
-                        Any::Advice => {
-                            &advice_cosets[pk.vk.cs.get_any_query_index(column, Rotation::cur())]
-                        }
-                        Any::Fixed => {
-                            &fixed_cosets[pk.vk.cs.get_any_query_index(column, Rotation::cur())]
-                        }
-                        Any::Instance => {
-                            &instance_cosets[pk.vk.cs.get_any_query_index(column, Rotation::cur())]
-                        }
-                    })
-                    .zip(pkey.cosets.iter())
-                {
-                    parallelize(&mut left, |left, start| {
-                        for ((left, value), permutation) in left
-                            .iter_mut()
-                            .zip(values[start..].iter())
-                            .zip(permutation[start..].iter())
-                        {
-                            *left *= &(*value + &(*beta * permutation) + &*gamma);
-                        }
-                    });
-                }
-
-                let mut right = self.permutation_product_coset_inv.clone();
-                let mut current_delta = *beta * &C::Scalar::ZETA;
-                let step = domain.get_extended_omega();
-                for values in p.columns.iter().map(|&column| match column.column_type() {
-                    Any::Advice => {
-                        &advice_cosets[pk.vk.cs.get_any_query_index(column, Rotation::cur())]
-                    }
-                    Any::Fixed => {
-                        &fixed_cosets[pk.vk.cs.get_any_query_index(column, Rotation::cur())]
+        let chunk_len = p.chunk_len(pk.vk.cs.permutation_degree_bound());
+        let column_cosets = move |column: &Column<Any>| match column.column_type() {
+            Any::Advice => &advice_cosets[pk.vk.cs.permutation_query_index(*column)],
+            Any::Fixed => &fixed_cosets[pk.vk.cs.permutation_query_index(*column)],
+            Any::Instance => &instance_cosets[pk.vk.cs.permutation_query_index(*column)],
+        };
+
+        // l_0(X) * (1 - z_0(X)) = 0
+        let mut expressions: Vec<_> = self
+            .sets
+            .first()
+            .map(|set| Polynomial::one_minus(set.permutation_product_coset.clone()) * l0)
+            .into_iter()
+            .collect();
+
+        // z_j(X) \prod (p(X) + \beta s_i(X) + \gamma)
+        // - ((1 - l_0(X)) z_j(omega^{-1} X) + l_0(X) z_{j-1}(omega^{-1} X))
+        //   \prod (p(X) + \delta^i \beta X + \gamma)
+        //
+        // where z_{-1} is the last chunk's product. With a single chunk, the product
+        // on the right is just z_0(omega^{-1} X).
+        let mut current_delta = *beta * &C::Scalar::zeta();
+        for (((index, set), columns), permutations) in self
+            .sets
+            .iter()
+            .enumerate()
+            .zip(p.columns.chunks(chunk_len))
+            .zip(pkey.cosets.chunks(chunk_len))
+        {
+            let mut left = set.permutation_product_coset.clone();
+            for (values, permutation) in columns.iter().map(column_cosets).zip(permutations.iter())
+            {
+                parallelize(&mut left, |left, start| {
+                    for ((left, value), permutation) in left
+                        .iter_mut()
+                        .zip(values[start..].iter())
+                        .zip(permutation[start..].iter())
+                    {
+                        *left *= &(*value + &(*beta * permutation) + &*gamma);
                     }
-                    Any::Instance => {
-                        &instance_cosets[pk.vk.cs.get_any_query_index(column, Rotation::cur())]
+                });
+            }
+
+            let mut right = set.permutation_product_coset_inv.clone();
+            if self.sets.len() > 1 {
+                let prev_set = &self.sets[(index + self.sets.len() - 1) % self.sets.len()];
+                right = right
+                    + &((prev_set.permutation_product_coset_inv.clone()
+                        - &set.permutation_product_coset_inv)
+                        * l0);
+            }
+            let step = domain.get_extended_omega();
+            for values in columns.iter().map(column_cosets) {
+                parallelize(&mut right, move |right, start| {
+                    let mut beta_term = current_delta * &step.pow_vartime(&[start as u64, 0, 0, 0]);
+                    for (right, value) in right.iter_mut().zip(values[start..].iter()) {
+                        *right *= &(*value + &beta_term + &*gamma);
+                        beta_term *= &step;
                     }
-                }) {
-                    parallelize(&mut right, move |right, start| {
-                        let mut beta_term =
-                            current_delta * &step.pow_vartime(&[start as u64, 0, 0, 0]);
-                        for (right, value) in right.iter_mut().zip(values[start..].iter()) {
-                            *right *= &(*value + &beta_term + &*gamma);
-                            beta_term *= &step;
-                        }
-                    });
-                    current_delta *= &C::Scalar::DELTA;
-                }
-
-                left - &right
-            }));
+                });
+                current_delta *= &C::Scalar::delta();
+            }
 
-        (
+            expressions.push(left - &right);
+        }
+
+        Ok((
             Constructed {
-                permutation_product_poly: self.permutation_product_poly,
-                permutation_product_blind: self.permutation_product_blind,
+                sets: self
+                    .sets
+                    .into_iter()
+                    .map(|set| ConstructedSet {
+                        permutation_product_poly: set.permutation_product_poly,
+                        permutation_product_blind: set.permutation_product_blind,
+                    })
+                    .collect(),
             },
-            expressions,
-        )
+            expressions.into_iter(),
+        ))
     }
 }
 
@@ -265,24 +311,28 @@ impl<C: CurveAffine> Constructed<C> {
         transcript: &mut T,
     ) -> Result<Evaluated<C>, Error> {
         let domain = &pk.vk.domain;
-
-        let permutation_product_eval = eval_polynomial(&self.permutation_product_poly, *x);
-
-        let permutation_product_inv_eval = eval_polynomial(
-            &self.permutation_product_poly,
-            domain.rotate_omega(*x, Rotation(-1)),
-        );
-
-        let permutation_evals = pkey.evaluate(x);
+        let x_inv = domain.rotate_omega(*x, Rotation(-1));
 
         // Hash permutation product evals
-        for eval in iter::empty()
-            .chain(Some(&permutation_product_eval))
-            .chain(Some(&permutation_product_inv_eval))
-            .chain(permutation_evals.iter())
-        {
+        for set in self.sets.iter() {
+            let permutation_product_eval = eval_polynomial(&set.permutation_product_poly, *x);
+            let permutation_product_inv_eval =
+                eval_polynomial(&set.permutation_product_poly, x_inv);
+
+            for eval in iter::empty()
+                .chain(Some(permutation_product_eval))
+                .chain(Some(permutation_product_inv_eval))
+            {
+                transcript
+                    .write_scalar(eval)
+                    .map_err(|_| Error::TranscriptError)?;
+            }
+        }
+
+        // Hash permutation evals
+        for eval in pkey.evaluate(x) {
             transcript
-                .write_scalar(*eval)
+                .write_scalar(eval)
                 .map_err(|_| Error::TranscriptError)?;
         }
 
@@ -299,18 +349,23 @@ impl<C: CurveAffine> Evaluated<C> {
     ) -> impl Iterator<Item = ProverQuery<'a, C>> + Clone {
         let x_inv = pk.vk.domain.rotate_omega(*x, Rotation(-1));
 
-        iter::empty()
-            // Open permutation product commitments at x and \omega^{-1} x
-            .chain(Some(ProverQuery {
-                point: *x,
-                poly: &self.constructed.permutation_product_poly,
-                blind: self.constructed.permutation_product_blind,
-            }))
-            .chain(Some(ProverQuery {
-                point: x_inv,
-                poly: &self.constructed.permutation_product_poly,
-                blind: self.constructed.permutation_product_blind,
-            }))
+        self.constructed
+            .sets
+            .iter()
+            .flat_map(move |set| {
+                iter::empty()
+                    // Open permutation product commitments at x and \omega^{-1} x
+                    .chain(Some(ProverQuery {
+                        point: *x,
+                        poly: &set.permutation_product_poly,
+                        blind: set.permutation_product_blind,
+                    }))
+                    .chain(Some(ProverQuery {
+                        point: x_inv,
+                        poly: &set.permutation_product_poly,
+                        blind: set.permutation_product_blind,
+                    }))
+            })
             // Open permutation polynomial commitments at x
             .chain(pkey.open(x))
     }
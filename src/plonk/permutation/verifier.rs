@@ -1,41 +1,52 @@
 use ff::Field;
 use std::iter;
 
-use super::super::{circuit::Any, ChallengeBeta, ChallengeGamma, ChallengeX};
+use super::super::{
+    circuit::{Any, Column},
+    ChallengeBeta, ChallengeGamma, ChallengeX,
+};
 use super::{Argument, VerifyingKey};
 use crate::{
-    arithmetic::{CurveAffine, FieldExt},
+    arithmetic::{CurveAffine, TwoAdicField},
     plonk::{self, Error},
     poly::{multiopen::VerifierQuery, Rotation},
     transcript::{EncodedChallenge, TranscriptRead},
 };
 
 pub struct Committed<C: CurveAffine> {
-    permutation_product_commitment: C,
+    permutation_product_commitments: Vec<C>,
 }
 
-pub struct Evaluated<C: CurveAffine> {
+pub struct EvaluatedSet<C: CurveAffine> {
     permutation_product_commitment: C,
     permutation_product_eval: C::Scalar,
     permutation_product_inv_eval: C::Scalar,
+}
+
+pub struct Evaluated<C: CurveAffine> {
+    sets: Vec<EvaluatedSet<C>>,
     permutation_evals: Vec<C::Scalar>,
 }
 
 impl Argument {
-    pub(crate) fn read_product_commitment<
+    pub(crate) fn read_product_commitments<
         C: CurveAffine,
         E: EncodedChallenge<C>,
         T: TranscriptRead<C, E>,
     >(
         &self,
+        vk: &plonk::VerifyingKey<C>,
         transcript: &mut T,
     ) -> Result<Committed<C>, Error> {
-        let permutation_product_commitment = transcript
-            .read_point()
+        let num_chunks = self.num_chunks(vk.cs.permutation_degree_bound());
+
+        let permutation_product_commitments = (0..num_chunks)
+            .map(|_| transcript.read_point())
+            .collect::<Result<Vec<_>, _>>()
             .map_err(|_| Error::TranscriptError)?;
 
         Ok(Committed {
-            permutation_product_commitment,
+            permutation_product_commitments,
         })
     }
 }
@@ -46,12 +57,22 @@ impl<C: CurveAffine> Committed<C> {
         vkey: &VerifyingKey<C>,
         transcript: &mut T,
     ) -> Result<Evaluated<C>, Error> {
-        let permutation_product_eval = transcript
-            .read_scalar()
-            .map_err(|_| Error::TranscriptError)?;
-        let permutation_product_inv_eval = transcript
-            .read_scalar()
-            .map_err(|_| Error::TranscriptError)?;
+        let mut sets = Vec::with_capacity(self.permutation_product_commitments.len());
+        for permutation_product_commitment in self.permutation_product_commitments {
+            let permutation_product_eval = transcript
+                .read_scalar()
+                .map_err(|_| Error::TranscriptError)?;
+            let permutation_product_inv_eval = transcript
+                .read_scalar()
+                .map_err(|_| Error::TranscriptError)?;
+
+            sets.push(EvaluatedSet {
+                permutation_product_commitment,
+                permutation_product_eval,
+                permutation_product_inv_eval,
+            });
+        }
+
         let mut permutation_evals = Vec::with_capacity(vkey.commitments.len());
         for _ in 0..vkey.commitments.len() {
             permutation_evals.push(
@@ -62,9 +83,7 @@ impl<C: CurveAffine> Committed<C> {
         }
 
         Ok(Evaluated {
-            permutation_product_commitment: self.permutation_product_commitment,
-            permutation_product_eval,
-            permutation_product_inv_eval,
+            sets,
             permutation_evals,
         })
     }
@@ -83,49 +102,53 @@ impl<C: CurveAffine> Evaluated<C> {
         gamma: ChallengeGamma<C>,
         x: ChallengeX<C>,
     ) -> impl Iterator<Item = C::Scalar> + 'a {
+        let chunk_len = p.chunk_len(vk.cs.permutation_degree_bound());
+        let column_eval = |column: &Column<Any>| match column.column_type() {
+            Any::Advice => advice_evals[vk.cs.permutation_query_index(*column)],
+            Any::Fixed => fixed_evals[vk.cs.permutation_query_index(*column)],
+            Any::Instance => instance_evals[vk.cs.permutation_query_index(*column)],
+        };
+
+        // z_j(X) \prod (p(X) + \beta s_i(X) + \gamma)
+        // - ((1 - l_0(X)) z_j(omega^{-1} X) + l_0(X) z_{j-1}(omega^{-1} X))
+        //   \prod (p(X) + \delta^i \beta X + \gamma)
+        //
+        // where z_{-1} is the last chunk's product. With a single chunk, the product
+        // on the right is just z_0(omega^{-1} X).
+        let mut chunk_expressions = Vec::with_capacity(self.sets.len());
+        let mut current_delta = *beta * &*x;
+        for (((index, set), columns), permutation_evals) in self
+            .sets
+            .iter()
+            .enumerate()
+            .zip(p.columns.chunks(chunk_len))
+            .zip(self.permutation_evals.chunks(chunk_len))
+        {
+            let mut left = set.permutation_product_eval;
+            for (eval, permutation_eval) in columns.iter().map(column_eval).zip(permutation_evals) {
+                left *= &(eval + &(*beta * permutation_eval) + &*gamma);
+            }
+
+            let prev_set = &self.sets[(index + self.sets.len() - 1) % self.sets.len()];
+            let mut right = set.permutation_product_inv_eval
+                + &(l_0
+                    * &(prev_set.permutation_product_inv_eval - &set.permutation_product_inv_eval));
+            for eval in columns.iter().map(column_eval) {
+                right *= &(eval + &current_delta + &*gamma);
+                current_delta *= &C::Scalar::delta();
+            }
+
+            chunk_expressions.push(left - &right);
+        }
+
         iter::empty()
-            // l_0(X) * (1 - z(X)) = 0
-            .chain(Some(
-                l_0 * &(C::Scalar::one() - &self.permutation_product_eval),
-            ))
-            // z(X) \prod (p(X) + \beta s_i(X) + \gamma)
-            // - z(omega^{-1} X) \prod (p(X) + \delta^i \beta X + \gamma)
-            .chain(Some({
-                let mut left = self.permutation_product_eval;
-                for (eval, permutation_eval) in p
-                    .columns
-                    .iter()
-                    .map(|&column| match column.column_type() {
-                        Any::Advice => {
-                            advice_evals[vk.cs.get_any_query_index(column, Rotation::cur())]
-                        }
-                        Any::Fixed => {
-                            fixed_evals[vk.cs.get_any_query_index(column, Rotation::cur())]
-                        }
-                        Any::Instance => {
-                            instance_evals[vk.cs.get_any_query_index(column, Rotation::cur())]
-                        }
-                    })
-                    .zip(self.permutation_evals.iter())
-                {
-                    left *= &(eval + &(*beta * permutation_eval) + &*gamma);
-                }
-
-                let mut right = self.permutation_product_inv_eval;
-                let mut current_delta = *beta * &*x;
-                for eval in p.columns.iter().map(|&column| match column.column_type() {
-                    Any::Advice => advice_evals[vk.cs.get_any_query_index(column, Rotation::cur())],
-                    Any::Fixed => fixed_evals[vk.cs.get_any_query_index(column, Rotation::cur())],
-                    Any::Instance => {
-                        instance_evals[vk.cs.get_any_query_index(column, Rotation::cur())]
-                    }
-                }) {
-                    right *= &(eval + &current_delta + &*gamma);
-                    current_delta *= &C::Scalar::DELTA;
-                }
-
-                left - &right
-            }))
+            // l_0(X) * (1 - z_0(X)) = 0
+            .chain(
+                self.sets
+                    .first()
+                    .map(|set| l_0 * &(C::Scalar::one() - &set.permutation_product_eval)),
+            )
+            .chain(chunk_expressions)
     }
 
     pub(in crate::plonk) fn queries<'a>(
@@ -136,18 +159,22 @@ impl<C: CurveAffine> Evaluated<C> {
     ) -> impl Iterator<Item = VerifierQuery<'a, C>> + Clone {
         let x_inv = vk.domain.rotate_omega(*x, Rotation(-1));
 
-        iter::empty()
-            // Open permutation product commitments at x and \omega^{-1} x
-            .chain(Some(VerifierQuery {
-                point: *x,
-                commitment: &self.permutation_product_commitment,
-                eval: self.permutation_product_eval,
-            }))
-            .chain(Some(VerifierQuery {
-                point: x_inv,
-                commitment: &self.permutation_product_commitment,
-                eval: self.permutation_product_inv_eval,
-            }))
+        self.sets
+            .iter()
+            .flat_map(move |set| {
+                iter::empty()
+                    // Open permutation product commitments at x and \omega^{-1} x
+                    .chain(Some(VerifierQuery {
+                        point: *x,
+                        commitment: &set.permutation_product_commitment,
+                        eval: set.permutation_product_eval,
+                    }))
+                    .chain(Some(VerifierQuery {
+                        point: x_inv,
+                        commitment: &set.permutation_product_commitment,
+                        eval: set.permutation_product_inv_eval,
+                    }))
+            })
             // Open permutation commitments for each permutation argument at x
             .chain(
                 vkey.commitments
@@ -0,0 +1,732 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Read, Write};
+
+use super::{Column, ColumnType, VerifyingKey};
+use crate::arithmetic::{CurveAffine, FieldExt};
+use crate::poly::Rotation;
+
+/// The length of an encoded scalar.
+const SCALAR_LEN: usize = 32;
+
+/// The evaluations of a permutation argument that are contained in a proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermutationEvals<F> {
+    /// The evaluations of the product polynomial of each chunk of the argument at $x$
+    /// and $\omega^{-1} x$.
+    pub products: Vec<(F, F)>,
+    /// The evaluations of the permutation polynomials at $x$, one per column in the
+    /// argument.
+    pub permutations: Vec<F>,
+}
+
+/// The evaluations of a lookup argument that are contained in a proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupEvals<F> {
+    /// The evaluation of the lookup product polynomial at $x$.
+    pub product: F,
+    /// The evaluation of the lookup product polynomial at $\omega^{-1} x$.
+    pub product_inv: F,
+    /// The evaluation of the permuted input polynomial at $x$.
+    pub permuted_input: F,
+    /// The evaluation of the permuted input polynomial at $\omega^{-1} x$.
+    pub permuted_input_inv: F,
+    /// The evaluation of the permuted table polynomial at $x$.
+    pub permuted_table: F,
+}
+
+/// The parts of a [`Proof`] that are specific to a single circuit instance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstanceProof<C: CurveAffine> {
+    /// The commitments to the advice columns.
+    pub advice_commitments: Vec<C>,
+    /// The commitments to the permuted input and permuted table of each lookup.
+    pub lookup_permuted_commitments: Vec<(C, C)>,
+    /// The commitments to the product polynomials of each permutation argument, one
+    /// per chunk of each argument.
+    pub permutation_product_commitments: Vec<C>,
+    /// The commitments to the product polynomial of each lookup.
+    pub lookup_product_commitments: Vec<C>,
+    /// The evaluations of the instance columns, one per instance query.
+    pub instance_evals: Vec<C::Scalar>,
+    /// The evaluations of the advice columns, one per advice query.
+    pub advice_evals: Vec<C::Scalar>,
+    /// The evaluations of each permutation argument.
+    pub permutation_evals: Vec<PermutationEvals<C::Scalar>>,
+    /// The evaluations of each lookup.
+    pub lookup_evals: Vec<LookupEvals<C::Scalar>>,
+}
+
+/// The multi-point opening argument at the end of a [`Proof`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpeningProof<C: CurveAffine> {
+    /// The commitment to the multiopen quotient polynomial $f$.
+    pub f_commitment: C,
+    /// The evaluations of the multiopen $q$ polynomials, one per set of query points.
+    pub q_evals: Vec<C::Scalar>,
+    /// The commitment to the random polynomial $s$ of the inner product argument.
+    pub s_poly_commitment: C,
+    /// The $(L, R)$ commitments from each round of the inner product argument.
+    pub rounds: Vec<(C, C)>,
+    /// The final folded coefficient of the inner product argument.
+    pub a: C::Scalar,
+    /// The final synthetic blinding factor of the inner product argument.
+    pub xi: C::Scalar,
+}
+
+/// The parts of a [`Proof`] that are shared by all circuit instances.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SharedProof<C: CurveAffine> {
+    /// The commitments to the pieces of the vanishing argument's quotient polynomial.
+    pub vanishing_commitments: Vec<C>,
+    /// The evaluations of the fixed columns, one per fixed query.
+    pub fixed_evals: Vec<C::Scalar>,
+    /// The evaluations of the pieces of the vanishing argument's quotient polynomial.
+    pub vanishing_evals: Vec<C::Scalar>,
+    /// The multi-point opening argument.
+    pub opening: OpeningProof<C>,
+}
+
+impl<C: CurveAffine> InstanceProof<C> {
+    /// Returns the number of bytes that this circuit instance contributes to the
+    /// encoded proof.
+    pub fn encoded_len(&self) -> usize {
+        let points = self.advice_commitments.len()
+            + 2 * self.lookup_permuted_commitments.len()
+            + self.permutation_product_commitments.len()
+            + self.lookup_product_commitments.len();
+        let scalars = self.instance_evals.len()
+            + self.advice_evals.len()
+            + self
+                .permutation_evals
+                .iter()
+                .map(|evals| 2 * evals.products.len() + evals.permutations.len())
+                .sum::<usize>()
+            + 5 * self.lookup_evals.len();
+        points * point_len::<C>() + scalars * SCALAR_LEN
+    }
+}
+
+impl<C: CurveAffine> SharedProof<C> {
+    /// Returns the number of bytes that the shared sections occupy in the encoded
+    /// proof. This does not depend on the number of circuit instances.
+    pub fn encoded_len(&self) -> usize {
+        let points = self.vanishing_commitments.len() + 2 + 2 * self.opening.rounds.len();
+        let scalars =
+            self.fixed_evals.len() + self.vanishing_evals.len() + self.opening.q_evals.len() + 2;
+        points * point_len::<C>() + scalars * SCALAR_LEN
+    }
+}
+
+/// A proof, parsed into its component sections.
+///
+/// Proofs created by [`create_proof`] are opaque byte strings; this type splits them
+/// into the commitments and evaluations that they contain, in the order in which they
+/// are written to the transcript. [`Proof::to_bytes`] re-encodes the proof exactly.
+///
+/// A proof of `n` circuit instances is laid out as follows, where sections marked
+/// "per instance" are repeated `n` times (once for each instance, in order) before the
+/// next section begins:
+///
+/// 1. Advice commitments (per instance).
+/// 2. Permuted input and permuted table commitments of each lookup (per instance).
+/// 3. Product commitments of each chunk of each permutation argument (per instance).
+/// 4. Product commitments of each lookup (per instance).
+/// 5. Vanishing argument commitments.
+/// 6. Instance evaluations (per instance).
+/// 7. Advice evaluations (per instance).
+/// 8. Fixed evaluations.
+/// 9. Vanishing argument evaluations.
+/// 10. Permutation argument evaluations (per instance).
+/// 11. Lookup evaluations (per instance).
+/// 12. The multi-point opening argument.
+///
+/// Sections 5, 8, 9 and 12 are shared by all instances, so the marginal cost of an
+/// additional instance is [`InstanceProof::encoded_len`]. Every per-instance section
+/// depends on that instance's witness (via its advice commitments and the resulting
+/// permutation and lookup products), so there is no per-instance data that could be
+/// deduplicated across instances.
+///
+/// [`create_proof`]: super::create_proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof<C: CurveAffine> {
+    instances: Vec<InstanceProof<C>>,
+    vanishing_commitments: Vec<C>,
+    fixed_evals: Vec<C::Scalar>,
+    vanishing_evals: Vec<C::Scalar>,
+    opening: OpeningProof<C>,
+}
+
+impl<C: CurveAffine> Proof<C> {
+    /// Parses a proof of `num_instances` circuit instances created with the given
+    /// verifying key.
+    ///
+    /// Returns an error if any point or scalar is not canonically encoded, or if the
+    /// length of `bytes` does not match the shape of the circuit.
+    pub fn from_bytes(
+        vk: &VerifyingKey<C>,
+        num_instances: usize,
+        bytes: &[u8],
+    ) -> io::Result<Self> {
+        let cs = &vk.cs;
+        let reader = &mut &bytes[..];
+        let degree_bound = cs.permutation_degree_bound();
+        let permutation_chunks = cs
+            .permutations
+            .iter()
+            .map(|argument| argument.num_chunks(degree_bound))
+            .sum::<usize>();
+
+        let advice_commitments = (0..num_instances)
+            .map(|_| read_points(reader, cs.num_advice_columns))
+            .collect::<io::Result<Vec<_>>>()?;
+        let lookup_permuted_commitments = (0..num_instances)
+            .map(|_| {
+                (0..cs.lookups.len())
+                    .map(|_| -> io::Result<_> { Ok((C::read(reader)?, C::read(reader)?)) })
+                    .collect::<io::Result<Vec<_>>>()
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let permutation_product_commitments = (0..num_instances)
+            .map(|_| read_points(reader, permutation_chunks))
+            .collect::<io::Result<Vec<_>>>()?;
+        let lookup_product_commitments = (0..num_instances)
+            .map(|_| read_points(reader, cs.lookups.len()))
+            .collect::<io::Result<Vec<_>>>()?;
+        let vanishing_commitments = read_points(reader, vk.domain.get_quotient_poly_degree())?;
+
+        let instance_evals = (0..num_instances)
+            .map(|_| read_scalars(reader, cs.instance_queries.len()))
+            .collect::<io::Result<Vec<_>>>()?;
+        let advice_evals = (0..num_instances)
+            .map(|_| read_scalars(reader, cs.advice_queries.len()))
+            .collect::<io::Result<Vec<_>>>()?;
+        let fixed_evals = read_scalars(reader, cs.fixed_queries.len())?;
+        let vanishing_evals = read_scalars(reader, vanishing_commitments.len())?;
+        let permutation_evals = (0..num_instances)
+            .map(|_| {
+                cs.permutations
+                    .iter()
+                    .map(|argument| -> io::Result<_> {
+                        Ok(PermutationEvals {
+                            products: (0..argument.num_chunks(degree_bound))
+                                .map(|_| -> io::Result<_> {
+                                    Ok((read_scalar(reader)?, read_scalar(reader)?))
+                                })
+                                .collect::<io::Result<Vec<_>>>()?,
+                            permutations: read_scalars(reader, argument.get_columns().len())?,
+                        })
+                    })
+                    .collect::<io::Result<Vec<_>>>()
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        let lookup_evals = (0..num_instances)
+            .map(|_| {
+                (0..cs.lookups.len())
+                    .map(|_| -> io::Result<_> {
+                        Ok(LookupEvals {
+                            product: read_scalar(reader)?,
+                            product_inv: read_scalar(reader)?,
+                            permuted_input: read_scalar(reader)?,
+                            permuted_input_inv: read_scalar(reader)?,
+                            permuted_table: read_scalar(reader)?,
+                        })
+                    })
+                    .collect::<io::Result<Vec<_>>>()
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let opening = read_opening(reader, vk.domain.k() as usize)?;
+
+        let instances = advice_commitments
+            .into_iter()
+            .zip(lookup_permuted_commitments.into_iter())
+            .zip(permutation_product_commitments.into_iter())
+            .zip(lookup_product_commitments.into_iter())
+            .zip(instance_evals.into_iter())
+            .zip(advice_evals.into_iter())
+            .zip(permutation_evals.into_iter())
+            .zip(lookup_evals.into_iter())
+            .map(
+                |(
+                    (
+                        (
+                            (
+                                (
+                                    (
+                                        (advice_commitments, lookup_permuted_commitments),
+                                        permutation_product_commitments,
+                                    ),
+                                    lookup_product_commitments,
+                                ),
+                                instance_evals,
+                            ),
+                            advice_evals,
+                        ),
+                        permutation_evals,
+                    ),
+                    lookup_evals,
+                )| InstanceProof {
+                    advice_commitments,
+                    lookup_permuted_commitments,
+                    permutation_product_commitments,
+                    lookup_product_commitments,
+                    instance_evals,
+                    advice_evals,
+                    permutation_evals,
+                    lookup_evals,
+                },
+            )
+            .collect();
+
+        Ok(Proof {
+            instances,
+            vanishing_commitments,
+            fixed_evals,
+            vanishing_evals,
+            opening,
+        })
+    }
+
+    /// Encodes this proof in the format produced by [`create_proof`].
+    ///
+    /// [`create_proof`]: super::create_proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    /// Writes this proof to a buffer in the format produced by [`create_proof`].
+    ///
+    /// [`create_proof`]: super::create_proof
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for instance in &self.instances {
+            write_points(writer, &instance.advice_commitments)?;
+        }
+        for instance in &self.instances {
+            for (permuted_input, permuted_table) in &instance.lookup_permuted_commitments {
+                permuted_input.write(writer)?;
+                permuted_table.write(writer)?;
+            }
+        }
+        for instance in &self.instances {
+            write_points(writer, &instance.permutation_product_commitments)?;
+        }
+        for instance in &self.instances {
+            write_points(writer, &instance.lookup_product_commitments)?;
+        }
+        write_points(writer, &self.vanishing_commitments)?;
+
+        for instance in &self.instances {
+            write_scalars(writer, &instance.instance_evals)?;
+        }
+        for instance in &self.instances {
+            write_scalars(writer, &instance.advice_evals)?;
+        }
+        write_scalars(writer, &self.fixed_evals)?;
+        write_scalars(writer, &self.vanishing_evals)?;
+        for instance in &self.instances {
+            for evals in &instance.permutation_evals {
+                for (product, product_inv) in &evals.products {
+                    write_scalars(writer, &[*product, *product_inv])?;
+                }
+                write_scalars(writer, &evals.permutations)?;
+            }
+        }
+        for instance in &self.instances {
+            for evals in &instance.lookup_evals {
+                write_scalars(
+                    writer,
+                    &[
+                        evals.product,
+                        evals.product_inv,
+                        evals.permuted_input,
+                        evals.permuted_input_inv,
+                        evals.permuted_table,
+                    ],
+                )?;
+            }
+        }
+
+        let opening = &self.opening;
+        opening.f_commitment.write(writer)?;
+        write_scalars(writer, &opening.q_evals)?;
+        opening.s_poly_commitment.write(writer)?;
+        for (l, r) in &opening.rounds {
+            l.write(writer)?;
+            r.write(writer)?;
+        }
+        write_scalars(writer, &[opening.a, opening.xi])
+    }
+
+    /// Splits this proof into the sections that are specific to each circuit instance,
+    /// and the sections that are shared by all of them.
+    ///
+    /// The per-instance sections are not independently verifiable, as every challenge
+    /// depends on all instances; use [`Proof::from_parts`] to reassemble the proof.
+    pub fn split_instances(self) -> (Vec<InstanceProof<C>>, SharedProof<C>) {
+        (
+            self.instances,
+            SharedProof {
+                vanishing_commitments: self.vanishing_commitments,
+                fixed_evals: self.fixed_evals,
+                vanishing_evals: self.vanishing_evals,
+                opening: self.opening,
+            },
+        )
+    }
+
+    /// Reassembles a proof from the parts returned by [`Proof::split_instances`].
+    pub fn from_parts(instances: Vec<InstanceProof<C>>, shared: SharedProof<C>) -> Self {
+        Proof {
+            instances,
+            vanishing_commitments: shared.vanishing_commitments,
+            fixed_evals: shared.fixed_evals,
+            vanishing_evals: shared.vanishing_evals,
+            opening: shared.opening,
+        }
+    }
+
+    /// Returns the sections of this proof that are specific to each circuit instance.
+    pub fn instances(&self) -> &[InstanceProof<C>] {
+        &self.instances
+    }
+
+    /// Returns the commitments to the pieces of the vanishing argument's quotient
+    /// polynomial.
+    pub fn vanishing_commitments(&self) -> &[C] {
+        &self.vanishing_commitments
+    }
+
+    /// Returns the evaluations of the fixed columns, one per fixed query.
+    pub fn fixed_evals(&self) -> &[C::Scalar] {
+        &self.fixed_evals
+    }
+
+    /// Returns the evaluations of the pieces of the vanishing argument's quotient
+    /// polynomial.
+    pub fn vanishing_evals(&self) -> &[C::Scalar] {
+        &self.vanishing_evals
+    }
+
+    /// Returns the multi-point opening argument.
+    pub fn opening(&self) -> &OpeningProof<C> {
+        &self.opening
+    }
+}
+
+/// The cost of verifying proofs for a circuit, as computed by
+/// [`VerifyingKey::verifier_cost`].
+///
+/// The multi-point opening argument reduces every commitment opened by a proof to a
+/// single multiscalar multiplication (MSM). The counts here describe that MSM, as
+/// returned by [`Guard::use_challenges`] for a proof verified with an empty MSM.
+///
+/// [`Guard::use_challenges`]: crate::poly::commitment::Guard::use_challenges
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifierCost {
+    /// The number of commitments opened once per proof, regardless of the number of
+    /// circuit instances: the queried fixed columns, the permutation commitments and
+    /// the pieces of the vanishing argument's quotient polynomial.
+    pub shared_commitments: usize,
+    /// The number of commitments opened for each circuit instance: the queried
+    /// instance and advice columns, the permutation products, and the product,
+    /// permuted input and permuted table of each lookup.
+    pub commitments_per_instance: usize,
+    /// The number of distinct sets of points at which commitments are opened. This is
+    /// the number of $q$ evaluations in the opening argument.
+    pub point_sets: usize,
+    /// The number of terms contributed by the inner product argument: one per
+    /// generator, the blinding and inner product generators, the $f$ and $s$
+    /// commitments, and the $(L, R)$ commitments of each round.
+    pub opening_terms: usize,
+}
+
+impl VerifierCost {
+    /// Returns the number of terms in the MSM for a proof of `num_instances` circuit
+    /// instances.
+    pub fn msm_len(&self, num_instances: usize) -> usize {
+        self.shared_commitments + num_instances * self.commitments_per_instance + self.opening_terms
+    }
+}
+
+/// The cost of a gate, as computed by [`VerifyingKey::gate_costs`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GateCost {
+    /// The index of the gate, in the order in which it was created.
+    pub index: usize,
+    /// The name of the gate.
+    pub name: &'static str,
+    /// The number of constraints in the gate.
+    pub constraints: usize,
+    /// The maximum degree of the constraints of the gate.
+    pub degree: usize,
+    /// The number of cells queried by the gate, including its selectors.
+    pub queried_cells: usize,
+    /// The degree of the gate's contribution to the quotient polynomial, as a multiple
+    /// of $n$. This is one less than the degree of the gate.
+    pub quotient_degree: usize,
+    /// The smallest `extended_k` that can hold the gate's contribution to the quotient
+    /// polynomial. The circuit's extended domain is at least this large.
+    pub extended_k: u32,
+    /// An estimate of the number of field operations needed to evaluate the gate over
+    /// the extended domain of the circuit: the additions, multiplications and scalings
+    /// in its constraints, and the two operations that combine each constraint into
+    /// the quotient polynomial, on every point of the extended domain.
+    pub evaluation_ops: usize,
+}
+
+impl<C: CurveAffine> VerifyingKey<C> {
+    /// Returns the cost of each gate of the circuit.
+    ///
+    /// The gates are sorted by their `extended_k`, and then by their `evaluation_ops`,
+    /// in decreasing order. If the first gate's `extended_k` equals that of the
+    /// circuit's domain, it is one of the gates forcing the size of the extended
+    /// domain; otherwise, the extended domain is determined by a lookup or permutation
+    /// argument, or by [`ConstraintSystem::set_minimum_degree`].
+    ///
+    /// [`ConstraintSystem::set_minimum_degree`]: super::ConstraintSystem::set_minimum_degree
+    pub fn gate_costs(&self) -> Vec<GateCost> {
+        let k = self.domain.k();
+        let extended_len = self.domain.extended_len();
+
+        let mut costs: Vec<_> = self
+            .cs
+            .gates
+            .iter()
+            .enumerate()
+            .map(|(index, gate)| {
+                let degree = gate.degree();
+                let quotient_degree = degree.saturating_sub(1);
+
+                // Size the extended domain as `EvaluationDomain::new` does.
+                let mut extended_k = k;
+                while (1u64 << extended_k) < (1u64 << k) * quotient_degree as u64 {
+                    extended_k += 1;
+                }
+
+                let ops_per_point = gate
+                    .polynomials()
+                    .iter()
+                    .map(|poly| {
+                        poly.evaluate(
+                            &|_| 0,
+                            &|_| 0,
+                            &|_| 0,
+                            &|_| 0,
+                            &|a, b| a + b + 1,
+                            &|a, b| a + b + 1,
+                            &|a, _| a + 1,
+                        ) + 2
+                    })
+                    .sum::<usize>();
+
+                GateCost {
+                    index,
+                    name: gate.name(),
+                    constraints: gate.polynomials().len(),
+                    degree,
+                    queried_cells: gate.queried_cells().len() + gate.queried_selectors().len(),
+                    quotient_degree,
+                    extended_k,
+                    evaluation_ops: ops_per_point * extended_len,
+                }
+            })
+            .collect();
+
+        costs.sort_by(|a, b| {
+            b.extended_k
+                .cmp(&a.extended_k)
+                .then(b.evaluation_ops.cmp(&a.evaluation_ops))
+        });
+        costs
+    }
+
+    /// Returns the exact length in bytes of a proof of `num_instances` circuit
+    /// instances created with this verifying key.
+    ///
+    /// See [`Proof`] for the sections that make up a proof.
+    pub fn proof_size(&self, num_instances: usize) -> usize {
+        let cs = &self.cs;
+        let quotient_pieces = self.domain.get_quotient_poly_degree();
+        let k = self.domain.k() as usize;
+
+        let permutation_chunks = self.permutation_chunks();
+
+        let instance_points = cs.num_advice_columns + 3 * cs.lookups.len() + permutation_chunks;
+        let instance_scalars = cs.instance_queries.len()
+            + cs.advice_queries.len()
+            + 2 * permutation_chunks
+            + cs.permutations
+                .iter()
+                .map(|argument| argument.get_columns().len())
+                .sum::<usize>()
+            + 5 * cs.lookups.len();
+
+        // The vanishing commitments, f, s and the (L, R) commitments of each round.
+        let shared_points = quotient_pieces + 2 + 2 * k;
+        // The fixed and vanishing evaluations, the q evaluations, a and xi.
+        let shared_scalars =
+            cs.fixed_queries.len() + quotient_pieces + self.point_sets(num_instances) + 2;
+
+        num_instances * (instance_points * point_len::<C>() + instance_scalars * SCALAR_LEN)
+            + shared_points * point_len::<C>()
+            + shared_scalars * SCALAR_LEN
+    }
+
+    /// Returns the cost of verifying proofs created with this verifying key.
+    ///
+    /// The counts assume that each proof contains at least one circuit instance.
+    pub fn verifier_cost(&self) -> VerifierCost {
+        let cs = &self.cs;
+        let permutation_columns = cs
+            .permutations
+            .iter()
+            .map(|argument| argument.get_columns().len())
+            .sum::<usize>();
+
+        VerifierCost {
+            shared_commitments: queried_columns(&cs.fixed_queries).len()
+                + permutation_columns
+                + self.domain.get_quotient_poly_degree(),
+            commitments_per_instance: queried_columns(&cs.instance_queries).len()
+                + queried_columns(&cs.advice_queries).len()
+                + self.permutation_chunks()
+                + 3 * cs.lookups.len(),
+            point_sets: self.point_sets(1),
+            opening_terms: (1 << self.domain.k()) + 2 + 2 + 2 * self.domain.k() as usize,
+        }
+    }
+
+    /// Returns the total number of product polynomials of the permutation arguments,
+    /// each of which is split into chunks of columns.
+    fn permutation_chunks(&self) -> usize {
+        let degree_bound = self.cs.permutation_degree_bound();
+        self.cs
+            .permutations
+            .iter()
+            .map(|argument| argument.num_chunks(degree_bound))
+            .sum()
+    }
+
+    /// Returns the number of distinct sets of rotations at which the polynomials of a
+    /// proof of `num_instances` circuit instances are opened.
+    fn point_sets(&self, num_instances: usize) -> usize {
+        let cs = &self.cs;
+        let mut sets: BTreeSet<BTreeSet<i32>> = BTreeSet::new();
+        let with_previous: BTreeSet<i32> = vec![0, -1].into_iter().collect();
+
+        sets.extend(
+            queried_columns(&cs.fixed_queries)
+                .into_iter()
+                .map(|(_, r)| r),
+        );
+        // The quotient polynomial pieces are opened at x.
+        sets.insert(Some(0).into_iter().collect());
+
+        if num_instances > 0 {
+            sets.extend(
+                queried_columns(&cs.instance_queries)
+                    .into_iter()
+                    .chain(queried_columns(&cs.advice_queries))
+                    .map(|(_, rotations)| rotations),
+            );
+            if self.permutation_chunks() > 0 {
+                // Products are opened at x and \omega^{-1} x, and the permutation
+                // commitments at x.
+                sets.insert(with_previous.clone());
+            }
+            if !cs.lookups.is_empty() {
+                // Products and permuted inputs are opened at x and \omega^{-1} x, and
+                // permuted tables at x.
+                sets.insert(with_previous);
+            }
+        }
+
+        sets.len()
+    }
+}
+
+/// Groups the queries of a kind of column by column, returning the set of rotations
+/// at which each queried column is opened.
+fn queried_columns<T: ColumnType>(
+    queries: &[(Column<T>, Rotation)],
+) -> BTreeMap<usize, BTreeSet<i32>> {
+    let mut columns: BTreeMap<usize, BTreeSet<i32>> = BTreeMap::new();
+    for (column, rotation) in queries {
+        columns
+            .entry(column.index())
+            .or_default()
+            .insert(rotation.0);
+    }
+    columns
+}
+
+/// Reads the opening argument, which must exactly fill the rest of `reader`.
+fn read_opening<C: CurveAffine>(reader: &mut &[u8], k: usize) -> io::Result<OpeningProof<C>> {
+    let f_commitment = C::read(reader)?;
+
+    // The number of q evaluations depends on how the queries are grouped into sets of
+    // points, so we derive it from the length of the remainder of the proof.
+    let point_len = point_len::<C>();
+    let scalar_len = SCALAR_LEN;
+    let tail_len = point_len * (1 + 2 * k) + scalar_len * 2;
+    if reader.len() < tail_len || (reader.len() - tail_len) % scalar_len != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "proof length does not match the verifying key",
+        ));
+    }
+    let q_evals = read_scalars(reader, (reader.len() - tail_len) / scalar_len)?;
+
+    let s_poly_commitment = C::read(reader)?;
+    let rounds = (0..k)
+        .map(|_| -> io::Result<_> { Ok((C::read(reader)?, C::read(reader)?)) })
+        .collect::<io::Result<Vec<_>>>()?;
+    let a = read_scalar(reader)?;
+    let xi = read_scalar(reader)?;
+
+    Ok(OpeningProof {
+        f_commitment,
+        q_evals,
+        s_poly_commitment,
+        rounds,
+        a,
+        xi,
+    })
+}
+
+/// Returns the length of an encoded curve point.
+fn point_len<C: CurveAffine>() -> usize {
+    C::Repr::default().as_ref().len()
+}
+
+fn read_points<C: CurveAffine>(reader: &mut &[u8], n: usize) -> io::Result<Vec<C>> {
+    (0..n).map(|_| C::read(reader)).collect()
+}
+
+fn read_scalar<F: FieldExt>(reader: &mut &[u8]) -> io::Result<F> {
+    let mut data = [0u8; SCALAR_LEN];
+    reader.read_exact(&mut data)?;
+    Option::from(F::from_bytes(&data)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid field element encoding in proof",
+        )
+    })
+}
+
+fn read_scalars<F: FieldExt>(reader: &mut &[u8], n: usize) -> io::Result<Vec<F>> {
+    (0..n).map(|_| read_scalar(reader)).collect()
+}
+
+fn write_points<C: CurveAffine, W: Write>(writer: &mut W, points: &[C]) -> io::Result<()> {
+    points.iter().try_for_each(|point| point.write(writer))
+}
+
+fn write_scalars<F: FieldExt, W: Write>(writer: &mut W, scalars: &[F]) -> io::Result<()> {
+    scalars
+        .iter()
+        .try_for_each(|scalar| writer.write_all(&scalar.to_bytes()))
+}
@@ -1,27 +1,284 @@
 use ff::Field;
 use group::Curve;
+use std::collections::BTreeSet;
 use std::iter;
+use std::time::{Duration, Instant};
 
 use super::{
     circuit::{
-        Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Selector,
+        Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Instance,
+        Selector,
     },
     lookup, permutation, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
-    ChallengeY, Error, Permutation, ProvingKey,
+    ChallengeY, Error, Permutation, ProvingKey, Witness,
 };
 use crate::poly::{
     commitment::{Blind, Params},
     multiopen::{self, ProverQuery},
-    Coeff, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial,
+    Coeff, ColumnMatrix, ExtendedLagrangeCoeff, LagrangeCoeff, Polynomial, Secret, Wipe,
 };
+use crate::transcript::{EncodedChallenge, TranscriptWrite};
 use crate::{
     arithmetic::{eval_polynomial, CurveAffine, FieldExt},
     plonk::Assigned,
 };
-use crate::{
-    poly::batch_invert_assigned,
-    transcript::{EncodedChallenge, TranscriptWrite},
-};
+
+/// A phase of proof creation, reported to a [`ProverObserver`].
+///
+/// Phases are reported in the order they are listed here. The phases that are
+/// performed separately for each circuit record the index of the circuit in the
+/// `circuits` passed to [`create_proof_with_observer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverPhase {
+    /// Committing to the instance columns. The size is the number of commitments.
+    InstanceCommitment,
+    /// Synthesizing the witness for a circuit. The size is the number of advice columns.
+    WitnessSynthesis(usize),
+    /// Committing to the advice columns of a circuit. The size is the number of
+    /// commitments.
+    AdviceCommitment(usize),
+    /// Permuting the inputs and tables of the lookups, and committing to them. The size
+    /// is the number of lookup arguments, across all circuits.
+    LookupPermutation,
+    /// Committing to the permutation product polynomials. The size is the number of
+    /// permutation arguments, across all circuits.
+    PermutationProduct,
+    /// Committing to the lookup product polynomials. The size is the number of lookup
+    /// arguments, across all circuits.
+    LookupProduct,
+    /// Evaluating the constraints over the extended domain, and committing to the
+    /// pieces of the quotient polynomial. The size is the number of pieces.
+    Vanishing,
+    /// Evaluating the committed polynomials at the challenge point. The size is the
+    /// number of evaluations written to the proof.
+    Evaluation,
+    /// Creating the multiopen argument. The size is the number of openings.
+    Multiopen,
+}
+
+/// The outcome of a phase of proof creation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PhaseReport {
+    /// The time spent in the phase.
+    pub elapsed: Duration,
+    /// The size of the phase; see [`ProverPhase`] for what is counted by each phase.
+    pub size: usize,
+}
+
+/// Receives progress events from [`create_proof_with_observer`].
+///
+/// Both methods have empty default implementations, so observers only need to
+/// implement the events they are interested in. This can be used to drive progress
+/// bars, or to record how long each phase of a long-running proof takes:
+///
+/// ```ignore
+/// struct Timings(Vec<(ProverPhase, Duration)>);
+///
+/// impl ProverObserver for Timings {
+///     fn phase_finished(&mut self, phase: ProverPhase, report: &PhaseReport) {
+///         self.0.push((phase, report.elapsed));
+///     }
+/// }
+/// ```
+pub trait ProverObserver {
+    /// Called when the prover starts a phase.
+    fn phase_started(&mut self, _phase: ProverPhase) {}
+
+    /// Called when the prover finishes a phase.
+    fn phase_finished(&mut self, _phase: ProverPhase, _report: &PhaseReport) {}
+}
+
+/// An observer that ignores all events.
+impl ProverObserver for () {}
+
+/// A phase of proof creation that is being timed.
+struct Phase {
+    phase: ProverPhase,
+    start: Instant,
+    /// The span of the phase, which is entered until the phase finishes.
+    #[cfg(feature = "tracing")]
+    span: tracing::span::EnteredSpan,
+}
+
+impl Phase {
+    fn start<O: ProverObserver + ?Sized>(observer: &mut O, phase: ProverPhase) -> Self {
+        observer.phase_started(phase);
+        Phase {
+            phase,
+            start: Instant::now(),
+            #[cfg(feature = "tracing")]
+            span: tracing::debug_span!("phase", ?phase, size = tracing::field::Empty).entered(),
+        }
+    }
+
+    fn finish<O: ProverObserver + ?Sized>(self, observer: &mut O, size: usize) {
+        let report = PhaseReport {
+            elapsed: self.start.elapsed(),
+            size,
+        };
+        #[cfg(feature = "tracing")]
+        self.span.record("size", &size);
+        observer.phase_finished(self.phase, &report);
+    }
+}
+
+/// The witness of a circuit, recorded while creating one proof so that it can be
+/// reused when creating later proofs of the same circuit.
+///
+/// Passing an empty cache (from [`CachedWitness::new`]) to [`create_proof_with_cache`]
+/// records the advice values of the circuit, along with the regions that were marked
+/// with [`Region::mark_instance_dependent`]. When the cache is passed again, only
+/// those regions compute their advice values; the advice values of every other region
+/// are reused from the cache. The cache is then updated with the new witness.
+///
+/// The circuit must lay out the same regions, in the same order, each time it is
+/// synthesized.
+///
+/// [`Region::mark_instance_dependent`]: crate::circuit::Region::mark_instance_dependent
+#[derive(Debug)]
+pub struct CachedWitness<F: Field> {
+    advice: ColumnMatrix<Assigned<F>>,
+    /// The number of regions in the recorded witness, or `None` if no witness has
+    /// been recorded.
+    regions: Option<usize>,
+    /// The indices of the recorded regions that were marked as instance-dependent.
+    instance_dependent: BTreeSet<usize>,
+}
+
+impl<F: Field> Default for CachedWitness<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Field> CachedWitness<F> {
+    /// Creates an empty cache, which will record the witness of the next proof that it
+    /// is used to create.
+    pub fn new() -> Self {
+        CachedWitness {
+            advice: ColumnMatrix::default(),
+            regions: None,
+            instance_dependent: BTreeSet::new(),
+        }
+    }
+
+    /// Returns `true` if a witness has been recorded in this cache.
+    pub fn is_recorded(&self) -> bool {
+        self.regions.is_some()
+    }
+
+    /// Returns the number of regions that were marked as instance-dependent in the
+    /// recorded witness.
+    pub fn instance_dependent_regions(&self) -> usize {
+        self.instance_dependent.len()
+    }
+
+    /// Discards the recorded witness, so that the next proof created with this cache
+    /// computes its witness in full.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Options that control how [`create_proof_with_options`] creates a proof.
+///
+/// New options may be added in later releases, so options should be created with
+/// `..Default::default()`:
+///
+/// ```
+/// use halo2::plonk::ProverOptions;
+///
+/// let options = ProverOptions {
+///     max_memory_bytes: Some(8 << 30),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ProverOptions {
+    /// The largest number of bytes that the prover may allocate for the polynomials of
+    /// a proof, or `None` for no limit.
+    ///
+    /// The prover keeps every polynomial of the proof in memory. If the estimate from
+    /// [`estimate_prover_memory`] exceeds this budget, proof creation fails with
+    /// [`Error::MemoryBudgetExceeded`] before the circuits are synthesized.
+    pub max_memory_bytes: Option<usize>,
+}
+
+/// Estimates the number of bytes that the prover allocates for the polynomials of a
+/// proof of `num_circuits` circuits with the proving key `pk`.
+///
+/// This counts the advice, instance, lookup, permutation and vanishing polynomials
+/// that are held at the same time, in each of the bases in which they are kept. The
+/// proving key, which is already in memory, is not counted, and neither are the
+/// temporary buffers used by FFTs and multiexponentiations; the estimate is therefore
+/// a lower bound on the memory that proof creation requires.
+pub fn estimate_prover_memory<C: CurveAffine>(pk: &ProvingKey<C>, num_circuits: usize) -> usize {
+    let cs = &pk.vk.cs;
+    let n = 1usize << pk.vk.domain.k();
+    let extended = pk.vk.domain.extended_len();
+
+    // The advice values are held in their assigned and Lagrange forms while the
+    // circuit is synthesized, and then as Lagrange and coefficient polynomials along
+    // with a coset for each query.
+    let advice = 3 * n * cs.num_advice_columns + extended * cs.advice_queries.len();
+    let instance = n * cs.num_instance_columns + extended * cs.instance_queries.len();
+
+    // Each lookup holds its unpermuted expressions and their cosets, the permuted input
+    // and table in three bases (and the inverse coset of the input), and the product
+    // polynomial with its coset and inverse coset.
+    let lookups = cs
+        .lookups
+        .iter()
+        .map(|lookup| {
+            let expressions = lookup.input_expressions.len() + lookup.table_expressions.len();
+            expressions * (n + extended) + 5 * n + 6 * extended
+        })
+        .sum::<usize>();
+
+    // Each chunk of a permutation argument holds its product polynomial, with its coset
+    // and inverse coset.
+    let permutations = cs
+        .permutations
+        .iter()
+        .map(|argument| argument.num_chunks(cs.permutation_degree_bound()) * (n + 2 * extended))
+        .sum::<usize>();
+
+    // The vanishing argument holds the random polynomial, the evaluations of the
+    // constraints on the extended domain, and the quotient polynomial.
+    let vanishing = n + 2 * extended;
+
+    let elements = num_circuits
+        .saturating_mul(advice + instance + lookups + permutations)
+        .saturating_add(vanishing);
+    elements.saturating_mul(std::mem::size_of::<C::Scalar>())
+}
+
+/// Creates a proof in the same way as [`create_proof`], with the given `options`.
+///
+/// Returns [`Error::MemoryBudgetExceeded`] without synthesizing the circuits if the
+/// proof cannot be created within `options.max_memory_bytes`.
+pub fn create_proof_with_options<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptWrite<C, E>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[Polynomial<C::Scalar, LagrangeCoeff>]],
+    transcript: &mut T,
+    options: &ProverOptions,
+) -> Result<(), Error> {
+    if let Some(budget) = options.max_memory_bytes {
+        let estimate = estimate_prover_memory(pk, circuits.len());
+        if estimate > budget {
+            return Err(Error::MemoryBudgetExceeded { estimate, budget });
+        }
+    }
+
+    create_proof_inner(params, pk, circuits, instances, transcript, &mut (), None)
+}
 
 /// This creates a proof for the provided `circuit` when given the public
 /// parameters `params` and the proving key [`ProvingKey`] that was
@@ -38,6 +295,527 @@ pub fn create_proof<
     instances: &[&[Polynomial<C::Scalar, LagrangeCoeff>]],
     transcript: &mut T,
 ) -> Result<(), Error> {
+    create_proof_with_observer(params, pk, circuits, instances, transcript, &mut ())
+}
+
+/// Creates a proof in the same way as [`create_proof`], reporting the progress of each
+/// phase of proof creation to `observer`.
+pub fn create_proof_with_observer<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptWrite<C, E>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+    O: ProverObserver + ?Sized,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[Polynomial<C::Scalar, LagrangeCoeff>]],
+    transcript: &mut T,
+    observer: &mut O,
+) -> Result<(), Error> {
+    create_proof_inner(params, pk, circuits, instances, transcript, observer, None)
+}
+
+/// Creates a proof in the same way as [`create_proof`], reusing the witness of each
+/// circuit from a previous proof where possible.
+///
+/// `caches` must contain one [`CachedWitness`] per circuit. Each circuit is synthesized
+/// using its cache (if it has been recorded), and the cache is then updated with the
+/// circuit's witness. Returns [`Error::IncompatibleParams`] if a recorded cache does not
+/// match the shape of the circuit, and [`Error::SynthesisError`] if the circuit lays out
+/// different regions than it did when the cache was recorded.
+pub fn create_proof_with_cache<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptWrite<C, E>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[Polynomial<C::Scalar, LagrangeCoeff>]],
+    transcript: &mut T,
+    caches: &mut [CachedWitness<C::Scalar>],
+) -> Result<(), Error> {
+    if caches.len() != circuits.len() {
+        return Err(Error::IncompatibleParams);
+    }
+
+    create_proof_inner(
+        params,
+        pk,
+        circuits,
+        instances,
+        transcript,
+        &mut (),
+        Some(caches),
+    )
+}
+
+fn create_proof_inner<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptWrite<C, E>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+    O: ProverObserver + ?Sized,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[ConcreteCircuit],
+    instances: &[&[Polynomial<C::Scalar, LagrangeCoeff>]],
+    transcript: &mut T,
+    observer: &mut O,
+    mut caches: Option<&mut [CachedWitness<C::Scalar>]>,
+) -> Result<(), Error> {
+    if circuits.len() != instances.len() {
+        return Err(Error::IncompatibleParams);
+    }
+
+    create_proof_with_advice(params, pk, instances, transcript, observer, |index| {
+        let mut meta = ConstraintSystem::default();
+        let config = circuits[index].configure_from(&mut meta);
+        synthesize_advice(
+            params.n as usize,
+            pk.vk.cs.num_advice_columns,
+            &pk.fixed_values,
+            instances[index],
+            &circuits[index],
+            config,
+            caches.as_mut().map(|caches| &mut caches[index]),
+        )
+    })
+}
+
+/// Synthesizes `circuit` to obtain its witness, which can be proven later (possibly
+/// on another machine) with [`create_proof_from_witness`].
+///
+/// `instance` holds the values of the instance columns, which the circuit may query
+/// while it is synthesized. The values of the fixed columns are taken from the proving
+/// key.
+pub fn generate_witness<C: CurveAffine, ConcreteCircuit: Circuit<C::Scalar>>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuit: &ConcreteCircuit,
+    instance: &[Polynomial<C::Scalar, LagrangeCoeff>],
+) -> Result<Witness<C::Scalar>, Error> {
+    if instance.len() != pk.vk.cs.num_instance_columns {
+        return Err(Error::IncompatibleParams);
+    }
+
+    let mut meta = ConstraintSystem::default();
+    let config = circuit.configure_from(&mut meta);
+    let advice = synthesize_advice(
+        params.n as usize,
+        meta.num_advice_columns,
+        &pk.fixed_values,
+        instance,
+        circuit,
+        config,
+        None,
+    )?;
+
+    Ok(Witness::new(
+        pk.vk.digest(),
+        advice.columns().map(|column| column.to_vec()).collect(),
+    ))
+}
+
+/// Creates a proof in the same way as [`create_proof`], from witnesses that were
+/// produced by [`generate_witness`] instead of by synthesizing the circuits.
+///
+/// `witnesses` must contain one witness per circuit, each generated with the
+/// corresponding entry of `instances`. Returns [`Error::IncompatibleParams`] if a
+/// witness was generated for a different circuit, or with parameters of a different
+/// size.
+pub fn create_proof_from_witness<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptWrite<C, E>,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    witnesses: &[Witness<C::Scalar>],
+    instances: &[&[Polynomial<C::Scalar, LagrangeCoeff>]],
+    transcript: &mut T,
+) -> Result<(), Error> {
+    if witnesses.len() != instances.len() {
+        return Err(Error::IncompatibleParams);
+    }
+
+    let circuit_hash = pk.vk.digest();
+    for witness in witnesses {
+        if *witness.circuit_hash() != circuit_hash
+            || witness.advice().len() != pk.vk.cs.num_advice_columns
+            || witness
+                .advice()
+                .iter()
+                .any(|column| column.len() != params.n as usize)
+        {
+            return Err(Error::IncompatibleParams);
+        }
+    }
+
+    create_proof_with_advice(params, pk, instances, transcript, &mut (), |index| {
+        Ok(ColumnMatrix::from_columns(
+            params.n as usize,
+            witnesses[index].advice(),
+        ))
+    })
+}
+
+/// A circuit whose witness can be synthesized through a trait object.
+///
+/// This is implemented for every [`Circuit`], and allows circuits of different types to
+/// be proven together with [`create_proof_from_circuits`], as long as they configure
+/// the same constraint system.
+pub trait SynthesizableWitness<F: FieldExt> {
+    /// Returns the constraint system that the circuit configures.
+    fn constraint_system(&self) -> ConstraintSystem<F>;
+
+    /// Synthesizes the circuit in a domain of `n` rows, and returns the values of its
+    /// advice columns.
+    ///
+    /// `fixed` and `instance` hold the values of the fixed and instance columns, which
+    /// the circuit may query while it is synthesized.
+    fn synthesize_witness(
+        &self,
+        n: usize,
+        fixed: &[Polynomial<F, LagrangeCoeff>],
+        instance: &[Polynomial<F, LagrangeCoeff>],
+    ) -> Result<ColumnMatrix<F>, Error>;
+}
+
+impl<F: FieldExt, ConcreteCircuit: Circuit<F>> SynthesizableWitness<F> for ConcreteCircuit {
+    fn constraint_system(&self) -> ConstraintSystem<F> {
+        let mut meta = ConstraintSystem::default();
+        self.configure_from(&mut meta);
+        meta
+    }
+
+    fn synthesize_witness(
+        &self,
+        n: usize,
+        fixed: &[Polynomial<F, LagrangeCoeff>],
+        instance: &[Polynomial<F, LagrangeCoeff>],
+    ) -> Result<ColumnMatrix<F>, Error> {
+        let mut meta = ConstraintSystem::default();
+        let config = self.configure_from(&mut meta);
+        synthesize_advice(
+            n,
+            meta.num_advice_columns,
+            fixed,
+            instance,
+            self,
+            config,
+            None,
+        )
+    }
+}
+
+/// Creates a proof in the same way as [`create_proof`], for circuits that may have
+/// different types.
+///
+/// Each circuit must configure the same constraint system as the circuit that the
+/// proving key was generated for; otherwise, [`Error::IncompatibleParams`] is returned.
+/// This allows an application with several ways of generating the witness for a key
+/// to prove them together, without combining them into a single circuit type.
+pub fn create_proof_from_circuits<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptWrite<C, E>,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    circuits: &[&dyn SynthesizableWitness<C::Scalar>],
+    instances: &[&[Polynomial<C::Scalar, LagrangeCoeff>]],
+    transcript: &mut T,
+) -> Result<(), Error> {
+    if circuits.len() != instances.len() {
+        return Err(Error::IncompatibleParams);
+    }
+
+    let pinned = pk.vk.circuit_cs().pinned();
+    for circuit in circuits {
+        if !circuit
+            .constraint_system()
+            .pinned()
+            .diff(&pinned)
+            .is_empty()
+        {
+            return Err(Error::IncompatibleParams);
+        }
+    }
+
+    create_proof_with_advice(params, pk, instances, transcript, &mut (), |index| {
+        circuits[index].synthesize_witness(params.n as usize, &pk.fixed_values, instances[index])
+    })
+}
+
+struct WitnessCollection<'a, F: Field> {
+    pub n: usize,
+    /// The name of the current region, for reporting invalid copy constraints.
+    pub region: Option<String>,
+    /// The current namespace path, for reporting invalid copy constraints.
+    pub namespace: Vec<String>,
+    pub advice: ColumnMatrix<Assigned<F>>,
+    pub fixed: &'a [Polynomial<F, LagrangeCoeff>],
+    pub instance: &'a [Polynomial<F, LagrangeCoeff>],
+    /// The number of regions entered so far.
+    pub regions: usize,
+    /// The regions that have been marked as instance-dependent.
+    pub instance_dependent: BTreeSet<usize>,
+    /// The instance-dependent regions of the cached witness that `advice`
+    /// was initialized from, if any. Advice assignments in other regions
+    /// are skipped.
+    pub cached: Option<BTreeSet<usize>>,
+    /// Whether advice assignments in the current region are skipped.
+    pub skip: bool,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<'a, F: Field> Assignment<F> for WitnessCollection<'a, F> {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let index = self.regions;
+        self.regions += 1;
+        self.skip = self
+            .cached
+            .as_ref()
+            .map_or(false, |cached| !cached.contains(&index));
+        self.region = Some(name().into());
+    }
+
+    fn exit_region(&mut self) {
+        self.skip = false;
+        self.region = None;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, _: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about advice columns here
+
+        Ok(())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if self.skip {
+            // The value is taken from the cached witness.
+            return Ok(());
+        }
+
+        *self
+            .advice
+            .column_mut(column.index())
+            .and_then(|v| v.get_mut(row))
+            .ok_or(Error::BoundsFailure)? = to()?.into();
+
+        Ok(())
+    }
+
+    fn assign_advice_slice<A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        values: &[Option<Assigned<F>>],
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        if self.skip {
+            // The values are taken from the cached witness.
+            return Ok(());
+        }
+
+        let cells = self
+            .advice
+            .column_mut(column.index())
+            .and_then(|v| v.get_mut(row..row + values.len()))
+            .ok_or(Error::BoundsFailure)?;
+        for (cell, value) in cells.iter_mut().zip(values.iter()) {
+            *cell = value.ok_or(Error::SynthesisError)?;
+        }
+
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Result<VR, Error>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // We only care about advice columns here
+
+        Ok(())
+    }
+
+    fn fixed_value(&self, column: Column<Fixed>, row: usize) -> Result<Option<F>, Error> {
+        self.fixed
+            .get(column.index())
+            .and_then(|v| v.get(row))
+            .map(|v| Some(*v))
+            .ok_or(Error::BoundsFailure)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Option<F>, Error> {
+        self.instance
+            .get(column.index())
+            .and_then(|v| v.get(row))
+            .map(|v| Some(*v))
+            .ok_or(Error::BoundsFailure)
+    }
+
+    fn mark_instance_dependent(&mut self) -> Result<(), Error> {
+        if self.skip {
+            // The region was not instance-dependent when the cached witness
+            // was recorded, so its earlier assignments have been skipped.
+            return Err(Error::SynthesisError);
+        }
+        if self.region.is_some() {
+            self.instance_dependent.insert(self.regions - 1);
+        }
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        permutation: &Permutation,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        // The permutation itself is fixed by the proving key, so we only
+        // check that the copy constraint is one that it can enforce.
+        permutation
+            .copy_indices(
+                self.n,
+                self.region.as_deref(),
+                &self.namespace,
+                (left_column, left_row),
+                (right_column, right_row),
+            )
+            .map(|_| ())
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespace.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        self.namespace.pop();
+    }
+}
+
+/// Synthesizes `circuit` to obtain its advice values, reusing the witness recorded in
+/// `cache` (if any) and then recording the new witness in it.
+///
+/// The circuit is synthesized in a domain of `n` rows, with the given values of its
+/// fixed and instance columns.
+pub(crate) fn synthesize_advice<F: FieldExt, ConcreteCircuit: Circuit<F>>(
+    n: usize,
+    num_advice_columns: usize,
+    fixed: &[Polynomial<F, LagrangeCoeff>],
+    instance: &[Polynomial<F, LagrangeCoeff>],
+    circuit: &ConcreteCircuit,
+    config: ConcreteCircuit::Config,
+    mut cache: Option<&mut CachedWitness<F>>,
+) -> Result<ColumnMatrix<F>, Error> {
+    let cached = cache.as_ref().filter(|cache| cache.is_recorded());
+    let advice = match cached {
+        Some(cache) => {
+            if cache.advice.num_columns() != num_advice_columns || cache.advice.num_rows() != n {
+                return Err(Error::IncompatibleParams);
+            }
+            cache.advice.clone()
+        }
+        None => ColumnMatrix::new(num_advice_columns, n, Assigned::Zero),
+    };
+
+    let mut witness = WitnessCollection {
+        n,
+        region: None,
+        namespace: vec![],
+        advice,
+        fixed,
+        instance,
+        regions: 0,
+        instance_dependent: BTreeSet::new(),
+        cached: cached.map(|cache| cache.instance_dependent.clone()),
+        skip: false,
+        _marker: std::marker::PhantomData,
+    };
+
+    // Synthesize the circuit to obtain the witness and other information.
+    ConcreteCircuit::FloorPlanner::synthesize(&mut witness, circuit, config)?;
+
+    if let Some(cache) = cache.as_mut() {
+        if cache
+            .regions
+            .map_or(false, |regions| regions != witness.regions)
+        {
+            return Err(Error::SynthesisError);
+        }
+        cache.advice = witness.advice.clone();
+        cache.regions = Some(witness.regions);
+        cache.instance_dependent = witness.instance_dependent.clone();
+    }
+
+    let advice = witness.advice.batch_invert();
+    witness.advice.wipe();
+    Ok(advice)
+}
+
+/// Creates a proof of the circuits whose advice values are returned by `synthesize`,
+/// which is called once for each entry of `instances`.
+fn create_proof_with_advice<
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptWrite<C, E>,
+    O: ProverObserver + ?Sized,
+    W: FnMut(usize) -> Result<ColumnMatrix<C::Scalar>, Error>,
+>(
+    params: &Params<C>,
+    pk: &ProvingKey<C>,
+    instances: &[&[Polynomial<C::Scalar, LagrangeCoeff>]],
+    transcript: &mut T,
+    observer: &mut O,
+    mut synthesize: W,
+) -> Result<(), Error>
+where
+{
     for instance in instances.iter() {
         if instance.len() != pk.vk.cs.num_instance_columns {
             return Err(Error::IncompatibleParams);
@@ -50,8 +828,15 @@ pub fn create_proof<
         .map_err(|_| Error::TranscriptError)?;
 
     let domain = &pk.vk.domain;
-    let mut meta = ConstraintSystem::default();
-    let config = ConcreteCircuit::configure(&mut meta);
+    let meta = &pk.vk.cs;
+    trace_span!(
+        "create_proof",
+        k = domain.k(),
+        circuits = instances.len(),
+        advice_columns = meta.num_advice_columns,
+        lookups = meta.lookups.len(),
+        permutations = meta.permutations.len(),
+    );
 
     struct InstanceSingle<'a, C: CurveAffine> {
         pub instance_values: &'a [Polynomial<C::Scalar, LagrangeCoeff>],
@@ -59,12 +844,28 @@ pub fn create_proof<
         pub instance_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
     }
 
+    let phase = Phase::start(observer, ProverPhase::InstanceCommitment);
     let instance: Vec<InstanceSingle<C>> = instances
         .iter()
         .map(|instance| -> Result<InstanceSingle<C>, Error> {
+            // The constant instance columns must hold their configured values, and are
+            // already committed to in the verifying key.
+            for (column, values) in &meta.constant_instances {
+                let poly = &instance[column.index()];
+                let padded = values
+                    .iter()
+                    .cloned()
+                    .chain(iter::repeat(C::Scalar::zero()));
+                if values.len() > poly.len() || poly.iter().zip(padded).any(|(a, b)| *a != b) {
+                    return Err(Error::IncompatibleParams);
+                }
+            }
+
             let instance_commitments_projective: Vec<_> = instance
                 .iter()
-                .map(|poly| params.commit_lagrange(poly, Blind::default()))
+                .enumerate()
+                .filter(|(index, _)| pk.vk.constant_instance_commitment(*index).is_none())
+                .map(|(_, poly)| params.commit_lagrange(poly, Blind::default()))
                 .collect();
             let mut instance_commitments =
                 vec![C::identity(); instance_commitments_projective.len()];
@@ -74,7 +875,13 @@ pub fn create_proof<
 
             for commitment in &instance_commitments {
                 transcript
-                    .common_point(*commitment)
+                    .common_point("instance", *commitment)
+                    .map_err(|_| Error::TranscriptError)?;
+            }
+            if meta.binds_instance_values {
+                let values: Vec<&[_]> = instance.iter().map(|poly| &poly[..]).collect();
+                pk.vk
+                    .hash_instance_values(transcript, &values)
                     .map_err(|_| Error::TranscriptError)?;
             }
 
@@ -102,132 +909,53 @@ pub fn create_proof<
             })
         })
         .collect::<Result<Vec<_>, _>>()?;
+    phase.finish(observer, instances.len() * meta.num_instance_columns);
 
     struct AdviceSingle<C: CurveAffine> {
-        pub advice_values: Vec<Polynomial<C::Scalar, LagrangeCoeff>>,
-        pub advice_polys: Vec<Polynomial<C::Scalar, Coeff>>,
-        pub advice_cosets: Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
-        pub advice_blinds: Vec<Blind<C::Scalar>>,
+        pub advice_values: Secret<Vec<Polynomial<C::Scalar, LagrangeCoeff>>>,
+        pub advice_polys: Secret<Vec<Polynomial<C::Scalar, Coeff>>>,
+        pub advice_cosets: Secret<Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>>,
+        pub advice_blinds: Secret<Vec<Blind<C::Scalar>>>,
     }
 
-    let advice: Vec<AdviceSingle<C>> = circuits
-        .iter()
-        .map(|circuit| -> Result<AdviceSingle<C>, Error> {
-            struct WitnessCollection<F: Field> {
-                pub advice: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
-                _marker: std::marker::PhantomData<F>,
-            }
-
-            impl<F: Field> Assignment<F> for WitnessCollection<F> {
-                fn enter_region<NR, N>(&mut self, _: N)
-                where
-                    NR: Into<String>,
-                    N: FnOnce() -> NR,
-                {
-                    // Do nothing; we don't care about regions in this context.
-                }
-
-                fn exit_region(&mut self) {
-                    // Do nothing; we don't care about regions in this context.
-                }
-
-                fn enable_selector<A, AR>(
-                    &mut self,
-                    _: A,
-                    _: &Selector,
-                    _: usize,
-                ) -> Result<(), Error>
-                where
-                    A: FnOnce() -> AR,
-                    AR: Into<String>,
-                {
-                    // We only care about advice columns here
-
-                    Ok(())
-                }
-
-                fn assign_advice<V, VR, A, AR>(
-                    &mut self,
-                    _: A,
-                    column: Column<Advice>,
-                    row: usize,
-                    to: V,
-                ) -> Result<(), Error>
-                where
-                    V: FnOnce() -> Result<VR, Error>,
-                    VR: Into<Assigned<F>>,
-                    A: FnOnce() -> AR,
-                    AR: Into<String>,
-                {
-                    *self
-                        .advice
-                        .get_mut(column.index())
-                        .and_then(|v| v.get_mut(row))
-                        .ok_or(Error::BoundsFailure)? = to()?.into();
-
-                    Ok(())
-                }
-
-                fn assign_fixed<V, VR, A, AR>(
-                    &mut self,
-                    _: A,
-                    _: Column<Fixed>,
-                    _: usize,
-                    _: V,
-                ) -> Result<(), Error>
-                where
-                    V: FnOnce() -> Result<VR, Error>,
-                    VR: Into<Assigned<F>>,
-                    A: FnOnce() -> AR,
-                    AR: Into<String>,
-                {
-                    // We only care about advice columns here
-
-                    Ok(())
-                }
-
-                fn copy(
-                    &mut self,
-                    _: &Permutation,
-                    _: Column<Any>,
-                    _: usize,
-                    _: Column<Any>,
-                    _: usize,
-                ) -> Result<(), Error> {
-                    // We only care about advice columns here
-
-                    Ok(())
-                }
-
-                fn push_namespace<NR, N>(&mut self, _: N)
-                where
-                    NR: Into<String>,
-                    N: FnOnce() -> NR,
-                {
-                    // Do nothing; we don't care about namespaces in this context.
-                }
-
-                fn pop_namespace(&mut self, _: Option<String>) {
-                    // Do nothing; we don't care about namespaces in this context.
-                }
-            }
-
-            let mut witness = WitnessCollection {
-                advice: vec![domain.empty_lagrange_assigned(); meta.num_advice_columns],
-                _marker: std::marker::PhantomData,
-            };
-
+    let advice: Vec<AdviceSingle<C>> = (0..instances.len())
+        .map(|index| -> Result<AdviceSingle<C>, Error> {
             // Synthesize the circuit to obtain the witness and other information.
-            ConcreteCircuit::FloorPlanner::synthesize(&mut witness, circuit, config.clone())?;
+            let phase = Phase::start(observer, ProverPhase::WitnessSynthesis(index));
+            let mut advice = Secret::new(synthesize(index)?);
 
-            let advice = batch_invert_assigned(&witness.advice);
+            // The circuit does not assign the columns holding split lookup inputs.
+            let intermediates = meta.lookup_intermediate_values(
+                params.n as usize,
+                |column, row| pk.fixed_values[column][row],
+                |column, row| advice.column(column).unwrap()[row],
+                |column, row| instances[index][column][row],
+            );
+            for (column, values) in intermediates {
+                advice
+                    .column_mut(column.index())
+                    .unwrap()
+                    .copy_from_slice(&values);
+            }
+            phase.finish(observer, advice.num_columns());
 
             // Compute commitments to advice column polynomials
-            let advice_blinds: Vec<_> = advice.iter().map(|_| Blind(C::Scalar::rand())).collect();
+            let phase = Phase::start(observer, ProverPhase::AdviceCommitment(index));
+            let advice_blinds = Secret::new(
+                (0..advice.num_columns())
+                    .map(|index| {
+                        if meta.is_unblinded(Column::new(index, Advice)) {
+                            Blind::default()
+                        } else {
+                            Blind(C::Scalar::rand())
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            );
             let advice_commitments_projective: Vec<_> = advice
-                .iter()
+                .columns()
                 .zip(advice_blinds.iter())
-                .map(|(poly, blind)| params.commit_lagrange(poly, *blind))
+                .map(|(column, blind)| params.commit_lagrange_values(column, *blind))
                 .collect();
             let mut advice_commitments = vec![C::identity(); advice_commitments_projective.len()];
             C::Curve::batch_normalize(&advice_commitments_projective, &mut advice_commitments);
@@ -239,21 +967,25 @@ pub fn create_proof<
                     .write_point(*commitment)
                     .map_err(|_| Error::TranscriptError)?;
             }
+            phase.finish(observer, advice_commitments.len());
 
-            let advice_polys: Vec<_> = advice
-                .clone()
-                .into_iter()
-                .map(|poly| domain.lagrange_to_coeff(poly))
-                .collect();
+            let advice = Secret::new(advice.to_lagrange_polys());
+            let advice_polys = Secret::new(
+                advice
+                    .iter()
+                    .map(|poly| domain.lagrange_to_coeff(poly.clone()))
+                    .collect::<Vec<_>>(),
+            );
 
-            let advice_cosets: Vec<_> = meta
-                .advice_queries
-                .iter()
-                .map(|&(column, at)| {
-                    let poly = advice_polys[column.index()].clone();
-                    domain.coeff_to_extended(poly, at)
-                })
-                .collect();
+            let advice_cosets = Secret::new(
+                meta.advice_queries
+                    .iter()
+                    .map(|&(column, at)| {
+                        let poly = advice_polys[column.index()].clone();
+                        domain.coeff_to_extended(poly, at)
+                    })
+                    .collect::<Vec<_>>(),
+            );
 
             Ok(AdviceSingle {
                 advice_values: advice,
@@ -265,8 +997,10 @@ pub fn create_proof<
         .collect::<Result<Vec<_>, _>>()?;
 
     // Sample theta challenge for keeping lookup columns linearly independent
-    let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
+    let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar("theta");
 
+    let num_lookups = instances.len() * pk.vk.cs.lookups.len();
+    let phase = Phase::start(observer, ProverPhase::LookupPermutation);
     let lookups: Vec<Vec<lookup::prover::Permuted<C>>> = instance
         .iter()
         .zip(advice.iter())
@@ -294,13 +1028,15 @@ pub fn create_proof<
                 .collect()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    phase.finish(observer, num_lookups);
 
     // Sample beta challenge
-    let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
+    let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar("beta");
 
     // Sample gamma challenge
-    let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
+    let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar("gamma");
 
+    let phase = Phase::start(observer, ProverPhase::PermutationProduct);
     let permutations: Vec<Vec<permutation::prover::Committed<C>>> = instance
         .iter()
         .zip(advice.iter())
@@ -327,7 +1063,9 @@ pub fn create_proof<
                 .collect()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    phase.finish(observer, instances.len() * pk.vk.cs.permutations.len());
 
+    let phase = Phase::start(observer, ProverPhase::LookupProduct);
     let lookups: Vec<Vec<lookup::prover::Committed<C>>> = lookups
         .into_iter()
         .map(|lookups| -> Result<Vec<_>, _> {
@@ -338,9 +1076,12 @@ pub fn create_proof<
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    phase.finish(observer, num_lookups);
 
     // Obtain challenge for keeping all separate gates linearly independent
-    let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
+    let y: ChallengeY<_> = transcript.squeeze_challenge_scalar("y");
+
+    let phase = Phase::start(observer, ProverPhase::Vanishing);
 
     let (permutations, permutation_expressions): (Vec<Vec<_>>, Vec<Vec<_>>) = permutations
         .into_iter()
@@ -364,8 +1105,11 @@ pub fn create_proof<
                         gamma,
                     )
                 })
-                .unzip()
+                .collect::<Result<Vec<_>, _>>()
+                .map(|constructed| constructed.into_iter().unzip())
         })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
         .unzip();
 
     let (lookups, lookup_expressions): (Vec<Vec<_>>, Vec<Vec<_>>) = lookups
@@ -375,8 +1119,11 @@ pub fn create_proof<
             lookups
                 .into_iter()
                 .map(|p| p.construct(pk, theta, beta, gamma))
-                .unzip()
+                .collect::<Result<Vec<_>, _>>()
+                .map(|constructed| constructed.into_iter().unzip())
         })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
         .unzip();
 
     let expressions = advice
@@ -410,8 +1157,12 @@ pub fn create_proof<
 
     // Construct the vanishing argument
     let vanishing = vanishing::Argument::construct(params, domain, expressions, y, transcript)?;
+    phase.finish(observer, domain.get_quotient_poly_degree());
 
-    let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
+    let x: ChallengeX<_> = transcript.squeeze_challenge_scalar("x");
+
+    let phase = Phase::start(observer, ProverPhase::Evaluation);
+    let mut num_evals = 0;
 
     // Compute and hash instance evals for each circuit instance
     for instance in instance.iter() {
@@ -428,6 +1179,7 @@ pub fn create_proof<
             .collect();
 
         // Hash each instance column evaluation
+        num_evals += instance_evals.len();
         for eval in instance_evals.iter() {
             transcript
                 .write_scalar(*eval)
@@ -450,6 +1202,7 @@ pub fn create_proof<
             .collect();
 
         // Hash each advice column evaluation
+        num_evals += advice_evals.len();
         for eval in advice_evals.iter() {
             transcript
                 .write_scalar(*eval)
@@ -467,6 +1220,7 @@ pub fn create_proof<
         .collect();
 
     // Hash each fixed column evaluation
+    num_evals += fixed_evals.len();
     for eval in fixed_evals.iter() {
         transcript
             .write_scalar(*eval)
@@ -474,6 +1228,7 @@ pub fn create_proof<
     }
 
     let vanishing = vanishing.evaluate(x, transcript)?;
+    num_evals += domain.get_quotient_poly_degree();
 
     // Evaluate the permutations, if any, at omega^i x.
     let permutations: Vec<Vec<permutation::prover::Evaluated<C>>> = permutations
@@ -497,6 +1252,7 @@ pub fn create_proof<
                 .collect::<Result<Vec<_>, _>>()
         })
         .collect::<Result<Vec<_>, _>>()?;
+    phase.finish(observer, num_evals);
 
     let instances = instance
         .iter()
@@ -550,5 +1306,10 @@ pub fn create_proof<
         // We query the h(X) polynomial at x
         .chain(vanishing.open(x));
 
-    multiopen::create_proof(params, transcript, instances).map_err(|_| Error::OpeningError)
+    let phase = Phase::start(observer, ProverPhase::Multiopen);
+    let num_openings = instances.clone().into_iter().count();
+    multiopen::create_proof(params, transcript, instances).map_err(|_| Error::OpeningError)?;
+    phase.finish(observer, num_openings);
+
+    Ok(())
 }
@@ -0,0 +1,308 @@
+use blake2b_simd::Params as Blake2bParams;
+use std::io;
+
+use super::{keygen, permutation, Circuit, ConstraintSystem, Error, VerifyingKey};
+use crate::arithmetic::{CurveAffine, FieldExt};
+use crate::format;
+use crate::poly::{commitment::Params, EvaluationDomain};
+
+/// The shape of a circuit: its constraint system, independent of the parameters that
+/// keys for the circuit are generated with.
+///
+/// A [`VerifyingKey`] is made of a shape and the [`VerifyingKeyCommitments`] for a
+/// particular size of parameters. Keys for the same circuit over different parameter
+/// sizes have the same shape, which can be shared between them, and compared or
+/// hashed without the commitments.
+#[derive(Clone, Debug)]
+pub struct CircuitShape<F: FieldExt> {
+    cs: ConstraintSystem<F>,
+}
+
+impl<F: FieldExt> CircuitShape<F> {
+    /// Returns the shape of the given circuit, configured from its type with
+    /// [`Circuit::configure`].
+    ///
+    /// Use [`CircuitShape::from_circuit`] for circuits whose configuration depends on
+    /// their value.
+    pub fn new<ConcreteCircuit: Circuit<F>>() -> Self {
+        let mut cs = ConstraintSystem::default();
+        ConcreteCircuit::configure(&mut cs);
+        CircuitShape { cs }
+    }
+
+    /// Returns the shape of the given circuit, configured from its value with
+    /// [`Circuit::configure_from`].
+    pub fn from_circuit<ConcreteCircuit: Circuit<F>>(circuit: &ConcreteCircuit) -> Self {
+        let mut cs = ConstraintSystem::default();
+        circuit.configure_from(&mut cs);
+        CircuitShape { cs }
+    }
+
+    /// Returns the constraint system of the circuit.
+    pub fn cs(&self) -> &ConstraintSystem<F> {
+        &self.cs
+    }
+
+    /// Returns the evaluation domain of the circuit for parameters with `2^k` rows.
+    pub fn domain(&self, k: u32) -> EvaluationDomain<F> {
+        EvaluationDomain::new(self.cs.degree() as u32, k)
+    }
+
+    /// Returns the BLAKE2b digest of this shape.
+    ///
+    /// This covers the pinned constraint system (see [`ConstraintSystem::pinned`]), the
+    /// values of any constant instance columns, and whether fixed columns are merged
+    /// (see [`ConstraintSystem::enable_fixed_column_compression`]), so two shapes have
+    /// the same digest
+    /// exactly when keys generated from them are interchangeable for the same
+    /// parameters.
+    pub fn digest(&self) -> [u8; 64] {
+        digest(&self.cs)
+    }
+
+    /// Writes this shape to a buffer.
+    ///
+    /// A constraint system cannot be deserialized on its own, so this only writes a
+    /// header containing the [digest](CircuitShape::digest) of the shape (see the
+    /// [`format`](crate::format) module). [`CircuitShape::read`] configures the
+    /// circuit again, and checks it against the digest.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        format::write_header(
+            writer,
+            format::Kind::CircuitShape,
+            F::modulus(),
+            Some(&self.digest()),
+        )
+    }
+
+    /// Reads the shape of the given circuit from a buffer.
+    ///
+    /// Returns [`FormatError::CircuitMismatch`] if the circuit's shape differs from
+    /// the one that was written.
+    ///
+    /// [`FormatError::CircuitMismatch`]: crate::format::FormatError::CircuitMismatch
+    pub fn read<R: io::Read, ConcreteCircuit: Circuit<F>>(reader: &mut R) -> io::Result<Self> {
+        let shape = Self::new::<ConcreteCircuit>();
+        format::read_header(
+            reader,
+            format::Kind::CircuitShape,
+            F::modulus(),
+            Some(&shape.digest()),
+        )?;
+        Ok(shape)
+    }
+}
+
+/// Returns the digest of the shape of a circuit with the given constraint system.
+pub(crate) fn digest<F: FieldExt>(cs: &ConstraintSystem<F>) -> [u8; 64] {
+    let mut hasher = Blake2bParams::new()
+        .hash_length(64)
+        .personal(b"Halo2-Circ-Shape")
+        .to_state();
+
+    let s = format!("{:?}", cs.pinned());
+    hasher.update(&(s.len() as u64).to_le_bytes());
+    hasher.update(s.as_bytes());
+
+    hasher.update(&(cs.constant_instances.len() as u64).to_le_bytes());
+    for (column, values) in &cs.constant_instances {
+        hasher.update(&(column.index() as u64).to_le_bytes());
+        hasher.update(&(values.len() as u64).to_le_bytes());
+        for value in values {
+            hasher.update(&value.to_bytes());
+        }
+    }
+
+    // Only hash the flag if it is set, so that the digests of existing circuits are
+    // unchanged.
+    if cs.compress_fixed_columns {
+        hasher.update(b"compress_fixed_columns");
+    }
+
+    *hasher.finalize().as_array()
+}
+
+impl<F: FieldExt> PartialEq for CircuitShape<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.digest()[..] == other.digest()[..]
+    }
+}
+
+impl<F: FieldExt> Eq for CircuitShape<F> {}
+
+/// The commitments within a [`VerifyingKey`], which depend on the parameters that
+/// the key was generated with as well as on its [`CircuitShape`].
+#[derive(Debug)]
+pub struct VerifyingKeyCommitments<C: CurveAffine> {
+    fixed_commitments: Vec<C>,
+    fixed_column_map: Option<Vec<usize>>,
+    permutations: Vec<permutation::VerifyingKey<C>>,
+}
+
+impl<C: CurveAffine> VerifyingKeyCommitments<C> {
+    /// Returns the commitments to the fixed columns.
+    ///
+    /// Fixed columns that were merged into another column have the same commitment as
+    /// that column.
+    pub fn fixed_commitments(&self) -> &[C] {
+        &self.fixed_commitments
+    }
+
+    /// Writes the commitments to a buffer.
+    ///
+    /// This is the encoding that [`VerifyingKey::write`] writes after its header.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_commitments(
+            &self.fixed_commitments,
+            self.fixed_column_map.as_deref(),
+            &self.permutations,
+            writer,
+        )
+    }
+
+    /// Reads the commitments for a circuit of the given shape from a buffer.
+    pub fn read<R: io::Read>(reader: &mut R, shape: &CircuitShape<C::Scalar>) -> io::Result<Self> {
+        let num_fixed_columns = shape.cs.num_fixed_columns;
+        let fixed_column_map = if shape.cs.compress_fixed_columns {
+            Some(read_fixed_column_map(reader, num_fixed_columns)?)
+        } else {
+            None
+        };
+
+        // Only the commitments to columns that were not merged are written.
+        let mut fixed_commitments: Vec<C> = Vec::with_capacity(num_fixed_columns);
+        for column in 0..num_fixed_columns {
+            let commitment = match &fixed_column_map {
+                Some(map) if map[column] != column => fixed_commitments[map[column]],
+                _ => C::read(reader)?,
+            };
+            fixed_commitments.push(commitment);
+        }
+
+        let permutations: Vec<_> = shape
+            .cs
+            .permutations
+            .iter()
+            .map(|argument| permutation::VerifyingKey::read(reader, argument))
+            .collect::<Result<_, _>>()?;
+
+        Ok(VerifyingKeyCommitments {
+            fixed_commitments,
+            fixed_column_map,
+            permutations,
+        })
+    }
+}
+
+/// Reads the column that each of `num_fixed_columns` fixed columns was merged into,
+/// checking that every column is merged into an earlier column that was not merged
+/// itself.
+fn read_fixed_column_map<R: io::Read>(
+    reader: &mut R,
+    num_fixed_columns: usize,
+) -> io::Result<Vec<usize>> {
+    let mut map = Vec::with_capacity(num_fixed_columns);
+    for column in 0..num_fixed_columns {
+        let mut bytes = [0u8; 4];
+        reader.read_exact(&mut bytes)?;
+        let merged = u32::from_le_bytes(bytes) as usize;
+        if merged > column || (merged < column && map[merged] != merged) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid fixed column map",
+            ));
+        }
+        map.push(merged);
+    }
+    Ok(map)
+}
+
+/// Writes the commitments within a verifying key to a buffer.
+///
+/// If the fixed columns were merged, the column that each was merged into is written
+/// first, followed by the commitments to the columns that were not merged.
+pub(crate) fn write_commitments<C: CurveAffine, W: io::Write>(
+    fixed_commitments: &[C],
+    fixed_column_map: Option<&[usize]>,
+    permutations: &[permutation::VerifyingKey<C>],
+    writer: &mut W,
+) -> io::Result<()> {
+    if let Some(map) = fixed_column_map {
+        for merged in map {
+            writer.write_all(&(*merged as u32).to_le_bytes())?;
+        }
+    }
+    for (column, commitment) in fixed_commitments.iter().enumerate() {
+        if fixed_column_map.map_or(false, |map| map[column] != column) {
+            continue;
+        }
+        writer.write_all(commitment.to_bytes().as_ref())?;
+    }
+    for permutation in permutations {
+        permutation.write(writer)?;
+    }
+
+    Ok(())
+}
+
+impl<C: CurveAffine> VerifyingKey<C> {
+    /// Assembles a verifying key from its shape and the commitments for `params`.
+    ///
+    /// Returns [`Error::IncompatibleParams`] if the commitments were not generated
+    /// for a circuit of this shape, or if the circuit does not fit in `params`.
+    pub fn from_parts(
+        params: &Params<C>,
+        shape: CircuitShape<C::Scalar>,
+        commitments: VerifyingKeyCommitments<C>,
+    ) -> Result<Self, Error> {
+        let cs = shape.cs;
+        if commitments.fixed_commitments.len() != cs.num_fixed_columns
+            || commitments.permutations.len() != cs.permutations.len()
+            || commitments.fixed_column_map.is_some() != cs.compress_fixed_columns
+        {
+            return Err(Error::IncompatibleParams);
+        }
+        cs.check_degree(params.k)?;
+
+        let domain = EvaluationDomain::new(cs.degree() as u32, params.k);
+        let constant_instance_commitments =
+            keygen::commit_constant_instances(params, &domain, &cs)?;
+
+        let (cs, merged_fixed_columns) = match commitments.fixed_column_map {
+            Some(map) => (cs.merge_fixed_columns(&map), Some((cs, map))),
+            None => (cs, None),
+        };
+
+        Ok(VerifyingKey {
+            domain,
+            fixed_commitments: commitments.fixed_commitments,
+            permutations: commitments.permutations,
+            constant_instance_commitments,
+            cs,
+            merged_fixed_columns,
+        })
+    }
+
+    /// Splits this verifying key into its shape and its commitments.
+    pub fn into_parts(self) -> (CircuitShape<C::Scalar>, VerifyingKeyCommitments<C>) {
+        let (cs, fixed_column_map) = match self.merged_fixed_columns {
+            Some((cs, map)) => (cs, Some(map)),
+            None => (self.cs, None),
+        };
+        (
+            CircuitShape { cs },
+            VerifyingKeyCommitments {
+                fixed_commitments: self.fixed_commitments,
+                fixed_column_map,
+                permutations: self.permutations,
+            },
+        )
+    }
+
+    /// Returns the shape of the circuit that this verifying key is for.
+    pub fn shape(&self) -> CircuitShape<C::Scalar> {
+        CircuitShape {
+            cs: self.circuit_cs().clone(),
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use ff::Field;
+use group::Curve;
 use std::iter;
 
 use super::{
@@ -7,148 +8,448 @@ use super::{
 };
 use crate::arithmetic::{CurveAffine, FieldExt};
 use crate::poly::{
-    commitment::{Guard, Params, MSM},
+    commitment::{Blind, Guard, Params, MSM},
     multiopen::{self, VerifierQuery},
 };
-use crate::transcript::{read_n_points, read_n_scalars, EncodedChallenge, TranscriptRead};
+use crate::transcript::{
+    read_n_points, read_n_scalars, Blake2bRead, Challenge255, EncodedChallenge, TranscriptRead,
+};
+
+/// Verifies a single proof against the commitments to its instance columns.
+///
+/// The proof must have been created by [`create_proof`] for a single circuit, using a
+/// [`Blake2bWrite`] transcript with [`Challenge255`] challenges. This reads the proof,
+/// checks it with [`verify_proof`], and then evaluates the resulting [`Guard`] using the
+/// inner product argument's challenges. Returns [`VerifyError::Opening`] if this final
+/// check fails.
+///
+/// Verifiers that batch several proofs together, or that defer the final
+/// multiexponentiation, should use [`verify_proof`] directly.
+///
+/// [`create_proof`]: super::create_proof
+/// [`Blake2bWrite`]: crate::transcript::Blake2bWrite
+pub fn verify_single<C: CurveAffine>(
+    params: &Params<C>,
+    vk: &VerifyingKey<C>,
+    proof: &[u8],
+    instance_commitments: &[C],
+) -> Result<(), VerifyError> {
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+    let guard = verify_proof(
+        params,
+        vk,
+        params.empty_msm(),
+        &[instance_commitments],
+        &mut transcript,
+    )?;
+    if guard.use_challenges().eval() {
+        Ok(())
+    } else {
+        Err(VerifyError::Opening)
+    }
+}
+
+/// A part of a proof that is read by [`verify_proof`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofSection {
+    /// The verifying key and instance commitments, which are hashed into the
+    /// transcript before the proof is read.
+    CommonInputs,
+    /// The commitments to the advice columns.
+    AdviceCommitments,
+    /// The commitments to the permuted input and table columns of the lookups.
+    LookupPermutedCommitments,
+    /// The commitments to the permutation product polynomials.
+    PermutationProductCommitments,
+    /// The commitments to the lookup product polynomials.
+    LookupProductCommitments,
+    /// The commitments to the pieces of the vanishing argument's quotient polynomial.
+    VanishingCommitments,
+    /// The evaluations of the instance columns.
+    InstanceEvals,
+    /// The evaluations of the advice columns.
+    AdviceEvals,
+    /// The evaluations of the fixed columns.
+    FixedEvals,
+    /// The evaluations of the pieces of the quotient polynomial.
+    VanishingEvals,
+    /// The evaluations of the permutation arguments.
+    PermutationEvals,
+    /// The evaluations of the lookup arguments.
+    LookupEvals,
+}
+
+/// The reasons why [`verify_proof`] or [`verify_single`] can reject a proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The number of instance commitments given for a proof does not match the number
+    /// of instance columns in the verifying key.
+    InstanceColumns {
+        /// The index of the proof, in the order the proofs were created.
+        proof: usize,
+        /// The number of instance columns in the verifying key.
+        expected: usize,
+        /// The number of instance commitments that were given.
+        actual: usize,
+    },
+    /// A section of the proof could not be read from the transcript; it is either
+    /// truncated or contains an invalid encoding.
+    Transcript {
+        /// The section that could not be read.
+        section: ProofSection,
+        /// The index of the proof the section belongs to, or `None` for sections that
+        /// are shared by all proofs.
+        proof: Option<usize>,
+    },
+    /// The evaluations of the gates, permutations and lookups at $x$ are inconsistent
+    /// with the evaluation of the vanishing argument's quotient polynomial.
+    ///
+    /// This is the check that the circuit is satisfied; it fails if the prover used a
+    /// different constraint system, or if any constraint does not hold. It also fails,
+    /// with negligible probability, if the challenge $x$ is $1$.
+    VanishingIdentity,
+    /// The circuit binds proofs to the values of its instance columns, so the proof
+    /// must be verified with [`verify_proof_with_instance_values`].
+    InstanceValuesRequired,
+    /// More instance values were given for a column than the circuit has rows.
+    InstanceRows {
+        /// The index of the proof, in the order the proofs were created.
+        proof: usize,
+        /// The index of the instance column.
+        column: usize,
+        /// The number of values that were given.
+        actual: usize,
+    },
+    /// The multiopen argument could not be read from the transcript.
+    Multiopen,
+    /// The final multiexponentiation, which checks that the committed polynomials open
+    /// to the evaluations in the proof, did not evaluate to zero.
+    ///
+    /// This is only returned by [`verify_single`]; [`verify_proof`] leaves this check
+    /// to its caller.
+    Opening,
+}
+
+impl From<VerifyError> for Error {
+    fn from(error: VerifyError) -> Self {
+        match error {
+            VerifyError::InstanceColumns { .. }
+            | VerifyError::InstanceValuesRequired
+            | VerifyError::InstanceRows { .. } => Error::IncompatibleParams,
+            VerifyError::Transcript { .. } => Error::TranscriptError,
+            VerifyError::VanishingIdentity | VerifyError::Opening => Error::ConstraintSystemFailure,
+            VerifyError::Multiopen => Error::OpeningError,
+        }
+    }
+}
+
+/// Returns a function that maps any error to a [`VerifyError::Transcript`] error.
+fn transcript_error<E>(section: ProofSection, proof: Option<usize>) -> impl Fn(E) -> VerifyError {
+    move |_| VerifyError::Transcript { section, proof }
+}
 
 /// Returns a boolean indicating whether or not the proof is valid
+///
+/// Returns [`VerifyError::InstanceValuesRequired`] for circuits that bind proofs to
+/// their instance values (see [`ConstraintSystem::bind_instance_values`]); use
+/// [`verify_proof_with_instance_values`] for those.
+///
+/// The commitments given for constant instance columns (see
+/// [`ConstraintSystem::constant_instance_column`]) are ignored in favour of the ones in
+/// the verifying key, so any placeholder may be passed for them.
+///
+/// [`ConstraintSystem::bind_instance_values`]: super::ConstraintSystem::bind_instance_values
+/// [`ConstraintSystem::constant_instance_column`]: super::ConstraintSystem::constant_instance_column
 pub fn verify_proof<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
     params: &'a Params<C>,
     vk: &VerifyingKey<C>,
     msm: MSM<'a, C>,
     instance_commitments: &[&[C]],
     transcript: &mut T,
-) -> Result<Guard<'a, C, E>, Error> {
+) -> Result<Guard<'a, C, E>, VerifyError> {
+    if vk.cs.binds_instance_values {
+        return Err(VerifyError::InstanceValuesRequired);
+    }
+    verify_proof_inner(params, vk, msm, instance_commitments, None, transcript)
+}
+
+/// Verifies proofs against the values of their instance columns.
+///
+/// `instances` holds the values of each instance column of each proof, which may omit
+/// trailing zeros. The verifier commits to the values itself, and also absorbs them
+/// into the transcript if the circuit binds proofs to their instance values (see
+/// [`ConstraintSystem::bind_instance_values`]). Otherwise, this is equivalent to
+/// calling [`verify_proof`] with the commitments to the values.
+///
+/// [`ConstraintSystem::bind_instance_values`]: super::ConstraintSystem::bind_instance_values
+pub fn verify_proof_with_instance_values<
+    'a,
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptRead<C, E>,
+>(
+    params: &'a Params<C>,
+    vk: &VerifyingKey<C>,
+    msm: MSM<'a, C>,
+    instances: &[&[&[C::Scalar]]],
+    transcript: &mut T,
+) -> Result<Guard<'a, C, E>, VerifyError> {
+    let n = params.n as usize;
+    let instance_commitments = instances
+        .iter()
+        .enumerate()
+        .map(|(proof, instance)| -> Result<Vec<C>, VerifyError> {
+            let projective = instance
+                .iter()
+                .enumerate()
+                .map(|(column, values)| {
+                    if values.len() > n {
+                        return Err(VerifyError::InstanceRows {
+                            proof,
+                            column,
+                            actual: values.len(),
+                        });
+                    }
+                    if vk.constant_instance_commitment(column).is_some() {
+                        // This is replaced by the commitment in the verifying key.
+                        return Ok(C::identity().to_curve());
+                    }
+                    let mut padded = values.to_vec();
+                    padded.resize(n, C::Scalar::zero());
+                    Ok(params.commit_lagrange_values(&padded, Blind::default()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut commitments = vec![C::identity(); projective.len()];
+            C::Curve::batch_normalize(&projective, &mut commitments);
+            Ok(commitments)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let instance_commitments: Vec<&[C]> = instance_commitments.iter().map(|c| &c[..]).collect();
+
+    verify_proof_inner(
+        params,
+        vk,
+        msm,
+        &instance_commitments,
+        Some(instances),
+        transcript,
+    )
+}
+
+pub(crate) fn verify_proof_inner<
+    'a,
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptRead<C, E>,
+>(
+    params: &'a Params<C>,
+    vk: &VerifyingKey<C>,
+    msm: MSM<'a, C>,
+    instance_commitments: &[&[C]],
+    instance_values: Option<&[&[&[C::Scalar]]]>,
+    transcript: &mut T,
+) -> Result<Guard<'a, C, E>, VerifyError> {
     // Check that instance_commitments matches the expected number of instance columns
-    for instance_commitments in instance_commitments.iter() {
+    for (proof, instance_commitments) in instance_commitments.iter().enumerate() {
         if instance_commitments.len() != vk.cs.num_instance_columns {
-            return Err(Error::IncompatibleParams);
+            return Err(VerifyError::InstanceColumns {
+                proof,
+                expected: vk.cs.num_instance_columns,
+                actual: instance_commitments.len(),
+            });
         }
     }
 
+    // Use the commitments in the verifying key for the constant instance columns
+    let instance_commitments: Vec<Vec<C>> = instance_commitments
+        .iter()
+        .map(|commitments| {
+            commitments
+                .iter()
+                .enumerate()
+                .map(|(column, commitment)| {
+                    vk.constant_instance_commitment(column)
+                        .unwrap_or(*commitment)
+                })
+                .collect()
+        })
+        .collect();
+    let instance_commitments: Vec<&[C]> = instance_commitments.iter().map(|c| &c[..]).collect();
+
     let num_proofs = instance_commitments.len();
+    trace_span!(
+        "verify_proof",
+        k = vk.domain.k(),
+        proofs = num_proofs,
+        advice_columns = vk.cs.num_advice_columns,
+        lookups = vk.cs.lookups.len(),
+        permutations = vk.cs.permutations.len(),
+    );
 
     // Hash verification key into transcript
     vk.hash_into(transcript)
-        .map_err(|_| Error::TranscriptError)?;
+        .map_err(transcript_error(ProofSection::CommonInputs, None))?;
 
-    for instance_commitments in instance_commitments.iter() {
-        // Hash the instance (external) commitments into the transcript
-        for commitment in *instance_commitments {
+    for (proof, instance_commitments) in instance_commitments.iter().enumerate() {
+        // Hash the instance (external) commitments into the transcript, except for the
+        // constant instance columns, which are bound by the verifying key
+        for (column, commitment) in instance_commitments.iter().enumerate() {
+            if vk.constant_instance_commitment(column).is_some() {
+                continue;
+            }
             transcript
-                .common_point(*commitment)
-                .map_err(|_| Error::TranscriptError)?
+                .common_point("instance", *commitment)
+                .map_err(transcript_error(ProofSection::CommonInputs, None))?
+        }
+
+        if vk.cs.binds_instance_values {
+            let instance = instance_values.map_or(&[][..], |instances| instances[proof]);
+            vk.hash_instance_values(transcript, instance)
+                .map_err(transcript_error(ProofSection::CommonInputs, None))?;
         }
     }
 
     let advice_commitments = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> {
+        .map(|proof| -> Result<Vec<_>, _> {
             // Hash the prover's advice commitments into the transcript
-            read_n_points(transcript, vk.cs.num_advice_columns).map_err(|_| Error::TranscriptError)
+            read_n_points(transcript, vk.cs.num_advice_columns).map_err(transcript_error(
+                ProofSection::AdviceCommitments,
+                Some(proof),
+            ))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     // Sample theta challenge for keeping lookup columns linearly independent
-    let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar();
+    let theta: ChallengeTheta<_> = transcript.squeeze_challenge_scalar("theta");
 
     let lookups_permuted = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> {
+        .map(|proof| -> Result<Vec<_>, _> {
             // Hash each lookup permuted commitment
             vk.cs
                 .lookups
                 .iter()
                 .map(|argument| argument.read_permuted_commitments(transcript))
                 .collect::<Result<Vec<_>, _>>()
+                .map_err(transcript_error(
+                    ProofSection::LookupPermutedCommitments,
+                    Some(proof),
+                ))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     // Sample beta challenge
-    let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar();
+    let beta: ChallengeBeta<_> = transcript.squeeze_challenge_scalar("beta");
 
     // Sample gamma challenge
-    let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar();
+    let gamma: ChallengeGamma<_> = transcript.squeeze_challenge_scalar("gamma");
 
     let permutations_committed = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> {
+        .map(|proof| -> Result<Vec<_>, _> {
             // Hash each permutation product commitment
             vk.cs
                 .permutations
                 .iter()
-                .map(|argument| argument.read_product_commitment(transcript))
+                .map(|argument| argument.read_product_commitments(vk, transcript))
                 .collect::<Result<Vec<_>, _>>()
+                .map_err(transcript_error(
+                    ProofSection::PermutationProductCommitments,
+                    Some(proof),
+                ))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     let lookups_committed = lookups_permuted
         .into_iter()
-        .map(|lookups| {
+        .enumerate()
+        .map(|(proof, lookups)| {
             // Hash each lookup product commitment
             lookups
                 .into_iter()
                 .map(|lookup| lookup.read_product_commitment(transcript))
                 .collect::<Result<Vec<_>, _>>()
+                .map_err(transcript_error(
+                    ProofSection::LookupProductCommitments,
+                    Some(proof),
+                ))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     // Sample y challenge, which keeps the gates linearly independent.
-    let y: ChallengeY<_> = transcript.squeeze_challenge_scalar();
-    let vanishing = vanishing::Argument::read_commitments(vk, transcript)?;
+    let y: ChallengeY<_> = transcript.squeeze_challenge_scalar("y");
+    let vanishing = vanishing::Argument::read_commitments(vk, transcript)
+        .map_err(transcript_error(ProofSection::VanishingCommitments, None))?;
 
     // Sample x challenge, which is used to ensure the circuit is
     // satisfied with high probability.
-    let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
+    let x: ChallengeX<_> = transcript.squeeze_challenge_scalar("x");
     let instance_evals = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> {
+        .map(|proof| -> Result<Vec<_>, _> {
             read_n_scalars(transcript, vk.cs.instance_queries.len())
-                .map_err(|_| Error::TranscriptError)
+                .map_err(transcript_error(ProofSection::InstanceEvals, Some(proof)))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     let advice_evals = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> {
+        .map(|proof| -> Result<Vec<_>, _> {
             read_n_scalars(transcript, vk.cs.advice_queries.len())
-                .map_err(|_| Error::TranscriptError)
+                .map_err(transcript_error(ProofSection::AdviceEvals, Some(proof)))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     let fixed_evals = read_n_scalars(transcript, vk.cs.fixed_queries.len())
-        .map_err(|_| Error::TranscriptError)?;
+        .map_err(transcript_error(ProofSection::FixedEvals, None))?;
 
-    let vanishing = vanishing.evaluate(transcript)?;
+    let vanishing = vanishing
+        .evaluate(transcript)
+        .map_err(transcript_error(ProofSection::VanishingEvals, None))?;
 
     let permutations_evaluated = permutations_committed
         .into_iter()
-        .map(|permutations| -> Result<Vec<_>, _> {
+        .enumerate()
+        .map(|(proof, permutations)| -> Result<Vec<_>, _> {
             permutations
                 .into_iter()
                 .zip(vk.permutations.iter())
                 .map(|(permutation, vkey)| permutation.evaluate(vkey, transcript))
                 .collect::<Result<Vec<_>, _>>()
+                .map_err(transcript_error(
+                    ProofSection::PermutationEvals,
+                    Some(proof),
+                ))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     let lookups_evaluated = lookups_committed
         .into_iter()
-        .map(|lookups| -> Result<Vec<_>, _> {
+        .enumerate()
+        .map(|(proof, lookups)| -> Result<Vec<_>, _> {
             lookups
                 .into_iter()
                 .map(|lookup| lookup.evaluate(transcript))
                 .collect::<Result<Vec<_>, _>>()
+                .map_err(transcript_error(ProofSection::LookupEvals, Some(proof)))
         })
         .collect::<Result<Vec<_>, _>>()?;
 
     // This check ensures the circuit is satisfied so long as the polynomial
     // commitments open to the correct values.
     {
+        trace_span!("vanishing_check", gates = vk.cs.gates.len());
+
         // x^n
         let xn = x.pow(&[params.n as u64, 0, 0, 0]);
 
-        // TODO: bubble this error up
-        // l_0(x)
-        let l_0 = (*x - &C::Scalar::one()).invert().unwrap() // 1 / (x - 1)
-            * &(xn - &C::Scalar::one()) // (x^n - 1) / (x - 1)
-            * &vk.domain.get_barycentric_weight(); // l_0(x)
+        // l_0(x) is only used by lookup and permutation arguments, so circuits without
+        // them skip the inversion. The identity cannot be checked at x = 1, where l_0 is
+        // undefined.
+        let l_0 = if vk.cs.has_lookups_or_permutations() {
+            Option::<C::Scalar>::from((*x - &C::Scalar::one()).invert())
+                .ok_or(VerifyError::VanishingIdentity)? // 1 / (x - 1)
+                * &(xn - &C::Scalar::one()) // (x^n - 1) / (x - 1)
+                * &vk.domain.get_barycentric_weight() // l_0(x)
+        } else {
+            C::Scalar::zero()
+        };
 
         // Compute the expected value of h(x)
         let expressions = advice_evals
@@ -219,7 +520,9 @@ pub fn verify_proof<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRea
                 },
             );
 
-        vanishing.verify(expressions, y, xn)?;
+        vanishing
+            .verify(expressions, y, xn)
+            .map_err(|_| VerifyError::VanishingIdentity)?;
     }
 
     let queries = instance_commitments
@@ -282,5 +585,6 @@ pub fn verify_proof<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRea
 
     // We are now convinced the circuit is satisfied so long as the
     // polynomial commitments open to the correct values.
-    multiopen::verify_proof(params, transcript, queries, msm).map_err(|_| Error::OpeningError)
+    trace_span!("multiopen", queries = queries.clone().count());
+    multiopen::verify_proof(params, transcript, queries, msm).map_err(|_| VerifyError::Multiopen)
 }
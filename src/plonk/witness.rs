@@ -0,0 +1,110 @@
+use std::convert::TryFrom;
+use std::io;
+
+use crate::arithmetic::FieldExt;
+
+/// The length of the circuit hash in the header of an encoded witness.
+const CIRCUIT_HASH_LEN: usize = 64;
+
+/// The length of an encoded field element.
+const SCALAR_LEN: usize = 32;
+
+/// The advice values of a circuit, produced by [`generate_witness`] so that a proof can
+/// be created from them later with [`create_proof_from_witness`].
+///
+/// This allows witness generation (which needs the private inputs of a circuit) to be
+/// separated from proof creation (which needs most of the computation). A witness can
+/// be generated on one machine, written with [`Witness::write`], and proven by another
+/// machine that never synthesizes the circuit.
+///
+/// The encoding starts with a hash of the verifying key of the circuit, so that a
+/// witness is rejected by proving keys for other circuits, followed by the number of
+/// advice columns and rows. The advice values follow in column-major order.
+///
+/// [`generate_witness`]: crate::plonk::generate_witness
+/// [`create_proof_from_witness`]: crate::plonk::create_proof_from_witness
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Witness<F> {
+    circuit_hash: [u8; CIRCUIT_HASH_LEN],
+    advice: Vec<Vec<F>>,
+}
+
+impl<F: FieldExt> Witness<F> {
+    pub(crate) fn new(circuit_hash: [u8; CIRCUIT_HASH_LEN], advice: Vec<Vec<F>>) -> Self {
+        Witness {
+            circuit_hash,
+            advice,
+        }
+    }
+
+    /// Returns the hash of the verifying key of the circuit that this witness is for.
+    pub fn circuit_hash(&self) -> &[u8; CIRCUIT_HASH_LEN] {
+        &self.circuit_hash
+    }
+
+    /// Returns the values of each advice column.
+    pub fn advice(&self) -> &[Vec<F>] {
+        &self.advice
+    }
+
+    /// Writes this witness to a buffer.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let rows = self.advice.first().map_or(0, |column| column.len());
+        if self.advice.iter().any(|column| column.len() != rows) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "advice columns have different lengths",
+            ));
+        }
+
+        writer.write_all(&self.circuit_hash)?;
+        writer.write_all(&(self.advice.len() as u64).to_le_bytes())?;
+        writer.write_all(&(rows as u64).to_le_bytes())?;
+        for column in &self.advice {
+            for value in column {
+                writer.write_all(&value.to_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a witness from a buffer.
+    pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let mut circuit_hash = [0u8; CIRCUIT_HASH_LEN];
+        reader.read_exact(&mut circuit_hash)?;
+        let columns = read_len(reader)?;
+        let rows = read_len(reader)?;
+
+        let advice = (0..columns)
+            .map(|_| (0..rows).map(|_| read_scalar(reader)).collect())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Witness {
+            circuit_hash,
+            advice,
+        })
+    }
+}
+
+fn read_len<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+    let mut data = [0u8; 8];
+    reader.read_exact(&mut data)?;
+    usize::try_from(u64::from_le_bytes(data)).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "witness dimensions are too large",
+        )
+    })
+}
+
+fn read_scalar<F: FieldExt, R: io::Read>(reader: &mut R) -> io::Result<F> {
+    let mut data = [0u8; SCALAR_LEN];
+    reader.read_exact(&mut data)?;
+    Option::from(F::from_bytes(&data)).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid field element encoding in witness",
+        )
+    })
+}
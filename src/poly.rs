@@ -6,17 +6,21 @@ use crate::arithmetic::parallelize;
 use crate::arithmetic::BatchInvert;
 use crate::plonk::Assigned;
 
+use crate::arithmetic::FieldExt;
 use ff::Field;
-use pasta_curves::arithmetic::FieldExt;
 use std::fmt::Debug;
 use std::marker::PhantomData;
-use std::ops::{Add, Deref, DerefMut, Index, IndexMut, Mul, RangeFrom, RangeFull, Sub};
+use std::ops::{Add, Deref, DerefMut, Index, IndexMut, Mul, Neg, Range, RangeFrom, RangeFull, Sub};
 
 pub mod commitment;
 mod domain;
+mod matrix;
 pub mod multiopen;
+mod secret;
 
 pub use domain::*;
+pub use matrix::ColumnMatrix;
+pub(crate) use secret::{Secret, Wipe};
 
 /// This is an error that could occur during proving or circuit synthesis.
 // TODO: these errors need to be cleaned up
@@ -272,7 +276,10 @@ impl<'a, F: Field, B: Basis> Mul<F> for Polynomial<F, B> {
 /// Describes the relative rotation of a vector. Negative numbers represent
 /// reverse (leftmost) rotations and positive numbers represent forward (rightmost)
 /// rotations. Zero represents no rotation.
-#[derive(Copy, Clone, Debug, PartialEq)]
+///
+/// Rotations must be smaller in magnitude than the number of rows in the domain; this is
+/// checked during key generation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Rotation(pub i32);
 
 impl Default for Rotation {
@@ -296,4 +303,37 @@ impl Rotation {
     pub fn next() -> Rotation {
         Rotation(1)
     }
+
+    /// Returns `true` if this is the current location in the evaluation domain.
+    pub fn is_cur(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the rotations in `range`, in order. This is useful for gates that query
+    /// a window of rows:
+    ///
+    /// ```ignore
+    /// let window: Vec<_> = Rotation::range(-1..2)
+    ///     .map(|rotation| meta.query_advice(a, rotation))
+    ///     .collect();
+    /// ```
+    pub fn range(range: Range<i32>) -> impl Iterator<Item = Rotation> + Clone {
+        range.map(Rotation)
+    }
+}
+
+impl Add for Rotation {
+    type Output = Rotation;
+
+    fn add(self, rhs: Rotation) -> Rotation {
+        Rotation(self.0 + rhs.0)
+    }
+}
+
+impl Neg for Rotation {
+    type Output = Rotation;
+
+    fn neg(self) -> Rotation {
+        Rotation(-self.0)
+    }
 }
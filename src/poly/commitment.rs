@@ -5,8 +5,9 @@
 
 use super::{Coeff, LagrangeCoeff, Polynomial};
 use crate::arithmetic::{
-    best_fft, best_multiexp, parallelize, CurveAffine, CurveExt, FieldExt, Group,
+    best_fft, best_multiexp, parallelize, CurveAffine, CurveExt, FieldExt, Group, TwoAdicField,
 };
+use crate::format;
 
 use ff::{Field, PrimeField};
 use group::{prime::PrimeCurveAffine, Curve, Group as _};
@@ -20,10 +21,34 @@ pub use msm::MSM;
 pub use prover::create_proof;
 pub use verifier::{verify_proof, Accumulator, Guard};
 
+use std::collections::HashSet;
 use std::io;
+#[cfg(feature = "mmap")]
+use std::path::Path;
+
+#[cfg(feature = "mmap")]
+use crate::mmap::{RawKind, RawReader, RawWriter};
+
+/// The domain separator used by [`Params::new`] to derive generators.
+///
+/// Generator $G_i$ is the hash to the curve (in this domain) of the five-byte message
+/// `[0, i_0, i_1, i_2, i_3]`, where $i_0 \dots i_3$ are the little-endian bytes of $i$.
+/// $H$ and $U$ are the hashes of the one-byte messages `[1]` and `[2]` respectively.
+pub const GENERATORS_DOMAIN: &str = "Halo2-Parameters";
+
+/// The reasons why [`Params::from_generators`] can reject a set of generators.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GeneratorsError {
+    /// The number of $G$ generators is not a power of two $2^k$ with $k < 32$.
+    InvalidLength(usize),
+    /// A generator is the identity, or is not on the curve.
+    InvalidGenerator,
+    /// A generator appears more than once among $G$, $H$ and $U$.
+    DuplicateGenerator,
+}
 
 /// These are the public parameters for the polynomial commitment scheme.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Params<C: CurveAffine> {
     pub(crate) k: u32,
     pub(crate) n: u64,
@@ -50,7 +75,7 @@ impl<C: CurveAffine> Params<C> {
             g.resize(n as usize, C::Curve::identity());
 
             parallelize(&mut g, move |g, start| {
-                let hasher = C::CurveExt::hash_to_curve("Halo2-Parameters");
+                let hasher = C::CurveExt::hash_to_curve(GENERATORS_DOMAIN);
 
                 for (i, g) in g.iter_mut().enumerate() {
                     let i = (i + start) as u32;
@@ -73,34 +98,9 @@ impl<C: CurveAffine> Params<C> {
             g
         };
 
-        // Let's evaluate all of the Lagrange basis polynomials
-        // using an inverse FFT.
-        let mut alpha_inv = <<C as PrimeCurveAffine>::Curve as Group>::Scalar::ROOT_OF_UNITY_INV;
-        for _ in k..C::Scalar::S {
-            alpha_inv = alpha_inv.square();
-        }
-        let mut g_lagrange_projective = g_projective;
-        best_fft(&mut g_lagrange_projective, alpha_inv, k);
-        let minv = C::Scalar::TWO_INV.pow_vartime(&[k as u64, 0, 0, 0]);
-        parallelize(&mut g_lagrange_projective, |g, _| {
-            for g in g.iter_mut() {
-                *g *= minv;
-            }
-        });
-
-        let g_lagrange = {
-            let mut g_lagrange = vec![C::identity(); n as usize];
-            parallelize(&mut g_lagrange, |g_lagrange, starts| {
-                C::Curve::batch_normalize(
-                    &g_lagrange_projective[starts..(starts + g_lagrange.len())],
-                    g_lagrange,
-                );
-            });
-            drop(g_lagrange_projective);
-            g_lagrange
-        };
+        let g_lagrange = g_to_lagrange(g_projective, k);
 
-        let hasher = C::CurveExt::hash_to_curve("Halo2-Parameters");
+        let hasher = C::CurveExt::hash_to_curve(GENERATORS_DOMAIN);
         let h = hasher(&[1]).to_affine();
         let u = hasher(&[2]).to_affine();
 
@@ -114,6 +114,64 @@ impl<C: CurveAffine> Params<C> {
         }
     }
 
+    /// Creates parameters from an externally generated set of generators, such as a
+    /// community-standard generator set, instead of deriving them as in [`Params::new`].
+    ///
+    /// `g` must contain $2^k$ generators for some $k < 32$. All of the generators must be
+    /// distinct points on the curve other than the identity. The security of the
+    /// commitment scheme also relies on no discrete log relation between the generators
+    /// being known, which cannot be checked here; it is up to the caller to audit how
+    /// they were generated.
+    pub fn from_generators(g: Vec<C>, h: C, u: C) -> Result<Self, GeneratorsError> {
+        let n = g.len();
+        if !n.is_power_of_two() || n.trailing_zeros() >= 32 {
+            return Err(GeneratorsError::InvalidLength(n));
+        }
+        let k = n.trailing_zeros();
+
+        let mut seen = HashSet::with_capacity(n + 2);
+        for point in g.iter().chain(Some(&h)).chain(Some(&u)) {
+            if bool::from(point.is_identity()) || !bool::from(point.is_on_curve()) {
+                return Err(GeneratorsError::InvalidGenerator);
+            }
+            if !seen.insert(point.to_bytes().as_ref().to_vec()) {
+                return Err(GeneratorsError::DuplicateGenerator);
+            }
+        }
+
+        let g_projective: Vec<_> = g.iter().map(|g| g.to_curve()).collect();
+        let g_lagrange = g_to_lagrange(g_projective, k);
+
+        Ok(Params {
+            k,
+            n: n as u64,
+            g,
+            g_lagrange,
+            h,
+            u,
+        })
+    }
+
+    /// Downsizes the parameters to support polynomials of degree up to $2^k - 1$,
+    /// where `k` is at most the current `k`.
+    ///
+    /// The resulting parameters are identical to those returned by [`Params::new`] for
+    /// the smaller `k`, so a single large set of parameters can be generated (or loaded)
+    /// once and then sized to fit each circuit, for example using the `k` returned by
+    /// [`k_for_circuit`].
+    ///
+    /// [`k_for_circuit`]: crate::plonk::k_for_circuit
+    pub fn downsize(&mut self, k: u32) {
+        assert!(k <= self.k);
+
+        let n = 1 << k;
+        self.k = k;
+        self.n = n;
+        self.g.truncate(n as usize);
+        let g_projective: Vec<_> = self.g.iter().map(|g| g.to_curve()).collect();
+        self.g_lagrange = g_to_lagrange(g_projective, k);
+    }
+
     /// This computes a commitment to a polynomial described by the provided
     /// slice of coefficients. The commitment will be blinded by the blinding
     /// factor `r`.
@@ -133,15 +191,38 @@ impl<C: CurveAffine> Params<C> {
     /// This commits to a polynomial using its evaluations over the $2^k$ size
     /// evaluation domain. The commitment will be blinded by the blinding factor
     /// `r`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scalar field has no multiplicative subgroup of order $2^k$.
     pub fn commit_lagrange(
         &self,
         poly: &Polynomial<C::Scalar, LagrangeCoeff>,
         r: Blind<C::Scalar>,
     ) -> C::Curve {
-        let mut tmp_scalars = Vec::with_capacity(poly.len() + 1);
-        let mut tmp_bases = Vec::with_capacity(poly.len() + 1);
+        self.commit_lagrange_values(poly, r)
+    }
 
-        tmp_scalars.extend(poly.iter());
+    /// This commits to the evaluations of a polynomial over the $2^k$ size evaluation
+    /// domain, given as a slice (such as a column of a [`ColumnMatrix`]). The
+    /// commitment will be blinded by the blinding factor `r`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scalar field has no multiplicative subgroup of order $2^k$.
+    ///
+    /// [`ColumnMatrix`]: crate::poly::ColumnMatrix
+    pub fn commit_lagrange_values(&self, values: &[C::Scalar], r: Blind<C::Scalar>) -> C::Curve {
+        assert!(
+            self.k <= C::Scalar::S,
+            "the scalar field has no evaluation domain of size 2^{}",
+            self.k
+        );
+
+        let mut tmp_scalars = Vec::with_capacity(values.len() + 1);
+        let mut tmp_bases = Vec::with_capacity(values.len() + 1);
+
+        tmp_scalars.extend(values.iter());
         tmp_scalars.push(r.0);
 
         tmp_bases.extend(self.g_lagrange.iter());
@@ -161,8 +242,22 @@ impl<C: CurveAffine> Params<C> {
         self.g.clone()
     }
 
+    /// Getter for the blinding generator h
+    pub fn get_h(&self) -> C {
+        self.h
+    }
+
+    /// Getter for the generator u used by the inner product argument
+    pub fn get_u(&self) -> C {
+        self.u
+    }
+
     /// Writes params to a buffer.
+    ///
+    /// The params are preceded by a header identifying the encoding and the curve;
+    /// see the [`format`](mod@crate::format) module.
     pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        format::write_header(writer, format::Kind::Params, C::CurveExt::CURVE_ID, None)?;
         writer.write_all(&self.k.to_le_bytes())?;
         for g_element in &self.g {
             writer.write_all(g_element.to_bytes().as_ref())?;
@@ -177,10 +272,21 @@ impl<C: CurveAffine> Params<C> {
     }
 
     /// Reads params from a buffer.
+    ///
+    /// Returns a [`FormatError`](crate::format::FormatError) if the params were
+    /// written by an incompatible version of `halo2`, or are over a different curve.
     pub fn read<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        format::read_header(reader, format::Kind::Params, C::CurveExt::CURVE_ID, None)?;
+
         let mut k = [0u8; 4];
         reader.read_exact(&mut k[..])?;
         let k = u32::from_le_bytes(k);
+        if k >= 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "params have too many generators",
+            ));
+        }
 
         let n: u64 = 1 << k;
 
@@ -201,6 +307,99 @@ impl<C: CurveAffine> Params<C> {
     }
 }
 
+#[cfg(feature = "mmap")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+#[allow(unsafe_code)]
+impl<C: CurveAffine> Params<C> {
+    /// Writes params in a raw encoding that can be loaded with [`Params::read_mmap`].
+    ///
+    /// The raw encoding stores the in-memory representation of the generators, so it
+    /// can only be read on the same platform, using the same versions of `halo2` and
+    /// of the curve implementation.
+    pub fn write_raw<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        let mut raw = RawWriter::new(writer, RawKind::Params)?;
+        raw.write_table(&[self.k])?;
+        raw.write_table(&self.g)?;
+        raw.write_table(&self.g_lagrange)?;
+        raw.write_table(&[self.h, self.u])?;
+        raw.finish()
+    }
+
+    /// Loads params written by [`Params::write_raw`] from a memory-mapped file.
+    ///
+    /// Unlike [`Params::read`], this does not decode (or decompress) the generators;
+    /// each table of generators is copied directly out of the mapping. For large `k`,
+    /// this is much faster. Loading is not zero-copy: the params own a copy of every
+    /// table, and until the kernel reclaims the mapped pages, both may be resident in
+    /// memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must have been written by [`Params::write_raw`] for the same curve, on
+    /// the same platform, using the same versions of `halo2` and of the curve
+    /// implementation. It must not be modified while it is being loaded.
+    pub unsafe fn read_mmap<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut raw = RawReader::open(path, RawKind::Params)?;
+        let k = raw.read_table::<u32>(Some(1))?[0];
+        if k >= 32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "params have too many generators",
+            ));
+        }
+        let n: u64 = 1 << k;
+        let g = raw.read_table(Some(n as usize))?;
+        let g_lagrange = raw.read_table(Some(n as usize))?;
+        let hu: Vec<C> = raw.read_table(Some(2))?;
+        raw.finish()?;
+
+        Ok(Params {
+            k,
+            n,
+            g,
+            g_lagrange,
+            h: hu[0],
+            u: hu[1],
+        })
+    }
+}
+
+/// Computes the Lagrange basis generators for a domain of size $2^k$ from the
+/// generators `g`, using an inverse FFT.
+///
+/// If the scalar field has no multiplicative subgroup of order $2^k$, there is no
+/// such domain, and the generators are all the identity; commitments in Lagrange
+/// form are not available for such parameters.
+fn g_to_lagrange<C: CurveAffine>(g_projective: Vec<C::Curve>, k: u32) -> Vec<C> {
+    if k > C::Scalar::S {
+        return vec![C::identity(); g_projective.len()];
+    }
+
+    // Let's evaluate all of the Lagrange basis polynomials
+    // using an inverse FFT.
+    let mut alpha_inv = <<C as PrimeCurveAffine>::Curve as Group>::Scalar::root_of_unity_inv();
+    for _ in k..C::Scalar::S {
+        alpha_inv = alpha_inv.square();
+    }
+    let mut g_lagrange_projective = g_projective;
+    best_fft(&mut g_lagrange_projective, alpha_inv, k);
+    let minv = C::Scalar::two_inv().pow_vartime(&[k as u64, 0, 0, 0]);
+    parallelize(&mut g_lagrange_projective, |g, _| {
+        for g in g.iter_mut() {
+            *g *= minv;
+        }
+    });
+
+    let mut g_lagrange = vec![C::identity(); g_lagrange_projective.len()];
+    parallelize(&mut g_lagrange, |g_lagrange, starts| {
+        C::Curve::batch_normalize(
+            &g_lagrange_projective[starts..(starts + g_lagrange.len())],
+            g_lagrange,
+        );
+    });
+    g_lagrange
+}
+
 /// Wrapper type around a blinding factor.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Blind<F>(pub F);
@@ -293,6 +492,91 @@ fn test_commit_lagrange_eqaffine() {
     assert_eq!(params.commit(&b, alpha), params.commit_lagrange(&a, alpha));
 }
 
+#[test]
+fn test_downsize() {
+    use crate::pasta::EqAffine;
+
+    let mut params = Params::<EqAffine>::new(5);
+    params.downsize(3);
+    let expected = Params::<EqAffine>::new(3);
+
+    assert_eq!(params.k, expected.k);
+    assert_eq!(params.n, expected.n);
+    assert_eq!(params.g, expected.g);
+    assert_eq!(params.g_lagrange, expected.g_lagrange);
+}
+
+#[test]
+fn test_from_generators() {
+    use crate::pasta::EqAffine;
+
+    let params = Params::<EqAffine>::new(3);
+    let imported = Params::from_generators(params.get_g(), params.get_h(), params.get_u()).unwrap();
+    assert_eq!(imported.k, params.k);
+    assert_eq!(imported.n, params.n);
+    assert_eq!(imported.g_lagrange, params.g_lagrange);
+
+    let mut g = params.get_g();
+    g.pop();
+    assert_eq!(
+        Params::from_generators(g, params.get_h(), params.get_u()).unwrap_err(),
+        GeneratorsError::InvalidLength(7)
+    );
+
+    let mut g = params.get_g();
+    g[5] = EqAffine::identity();
+    assert_eq!(
+        Params::from_generators(g, params.get_h(), params.get_u()).unwrap_err(),
+        GeneratorsError::InvalidGenerator
+    );
+
+    assert_eq!(
+        Params::from_generators(params.get_g(), params.get_h(), params.get_h()).unwrap_err(),
+        GeneratorsError::DuplicateGenerator
+    );
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+#[allow(unsafe_code)]
+fn test_params_mmap() {
+    use crate::pasta::EqAffine;
+
+    let params = Params::<EqAffine>::new(4);
+    let path = std::env::temp_dir().join("halo2-test-params-mmap.raw");
+    params
+        .write_raw(std::fs::File::create(&path).unwrap())
+        .unwrap();
+    let loaded = unsafe { Params::<EqAffine>::read_mmap(&path) }.unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.k, params.k);
+    assert_eq!(loaded.n, params.n);
+    assert_eq!(loaded.g, params.g);
+    assert_eq!(loaded.g_lagrange, params.g_lagrange);
+    assert_eq!(loaded.h, params.h);
+    assert_eq!(loaded.u, params.u);
+}
+
+#[test]
+fn test_read_other_curve() {
+    use crate::format::FormatError;
+    use crate::pasta::{EpAffine, EqAffine};
+
+    let params = Params::<EqAffine>::new(1);
+    let mut params_buffer = vec![];
+    params.write(&mut params_buffer).unwrap();
+
+    let err = Params::<EpAffine>::read(&mut &params_buffer[..]).unwrap_err();
+    assert_eq!(
+        FormatError::from_io(&err),
+        Some(&FormatError::CurveMismatch {
+            expected: "pallas".to_owned(),
+            found: "vesta".to_owned(),
+        })
+    );
+}
+
 #[test]
 fn test_opening_proof() {
     const K: u32 = 6;
@@ -328,14 +612,14 @@ fn test_opening_proof() {
 
     let mut transcript = Blake2bWrite::<Vec<u8>, EpAffine, Challenge255<EpAffine>>::init(vec![]);
     transcript.write_point(p).unwrap();
-    let x = transcript.squeeze_challenge_scalar::<()>();
+    let x = transcript.squeeze_challenge_scalar::<()>("x");
     // Evaluate the polynomial
     let v = eval_polynomial(&px, *x);
     transcript.write_scalar(v).unwrap();
 
     let (proof, ch_prover) = {
         create_proof(&params, &mut transcript, &px, blind, *x).unwrap();
-        let ch_prover = transcript.squeeze_challenge();
+        let ch_prover = transcript.squeeze_challenge("test");
         (transcript.finalize(), ch_prover)
     };
 
@@ -343,7 +627,7 @@ fn test_opening_proof() {
     let mut transcript = Blake2bRead::<&[u8], EpAffine, Challenge255<EpAffine>>::init(&proof[..]);
     let p_prime = transcript.read_point().unwrap();
     assert_eq!(p, p_prime);
-    let x_prime = transcript.squeeze_challenge_scalar::<()>();
+    let x_prime = transcript.squeeze_challenge_scalar::<()>("x");
     assert_eq!(*x, *x_prime);
     let v_prime = transcript.read_scalar().unwrap();
     assert_eq!(v, v_prime);
@@ -351,7 +635,7 @@ fn test_opening_proof() {
     let mut commitment_msm = params.empty_msm();
     commitment_msm.append_term(Field::one(), p);
     let guard = verify_proof(&params, commitment_msm, &mut transcript, *x, v).unwrap();
-    let ch_verifier = transcript.squeeze_challenge();
+    let ch_verifier = transcript.squeeze_challenge("test");
     assert_eq!(*ch_prover, *ch_verifier);
 
     // Test guard behavior prior to checking another proof
@@ -364,5 +648,18 @@ fn test_opening_proof() {
         let g = guard.compute_g();
         let (msm_g, _accumulator) = guard.clone().use_g(g);
         assert!(msm_g.eval());
+
+        // Test that caching the s-poly doesn't change the results
+        let mut cached = guard.clone();
+        cached.cache_s_poly();
+        assert_eq!(cached.compute_g(), g);
+        assert!(cached.use_challenges().eval());
+
+        // Test that a failing check reports the non-identity result
+        let mut msm_bad = guard.clone().use_challenges();
+        let len = msm_bad.len();
+        msm_bad.append_term(Field::one(), p);
+        assert_eq!(msm_bad.len(), len + 1);
+        assert_eq!(msm_bad.check(), Err(p));
     }
 }
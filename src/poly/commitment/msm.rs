@@ -1,7 +1,7 @@
 use super::Params;
 use crate::arithmetic::{best_multiexp, parallelize, CurveAffine};
 use ff::Field;
-use group::Group;
+use group::{Curve, Group};
 
 /// A multiscalar multiplication in the polynomial commitment scheme
 #[derive(Debug, Clone)]
@@ -115,12 +115,43 @@ impl<'a, C: CurveAffine> MSM<'a, C> {
         self.u_scalar = self.u_scalar.map(|a| a * &factor);
     }
 
-    /// Perform multiexp and check that it results in zero
-    pub fn eval(self) -> bool {
-        let len = self.g_scalars.as_ref().map(|v| v.len()).unwrap_or(0)
+    /// Returns the parameters that this MSM is defined over.
+    pub fn params(&self) -> &'a Params<C> {
+        self.params
+    }
+
+    /// Returns the number of terms in the multiexp.
+    pub fn len(&self) -> usize {
+        self.g_scalars.as_ref().map(|v| v.len()).unwrap_or(0)
             + self.h_scalar.map(|_| 1).unwrap_or(0)
             + self.u_scalar.map(|_| 1).unwrap_or(0)
-            + self.other_scalars.len();
+            + self.other_scalars.len()
+    }
+
+    /// Returns `true` if the multiexp has no terms.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Perform multiexp and check that it results in zero
+    pub fn eval(self) -> bool {
+        self.check().is_ok()
+    }
+
+    /// Perform multiexp and check that it results in zero, returning the resulting
+    /// point if it does not.
+    pub fn check(self) -> Result<(), C> {
+        let result = self.multiexp();
+        if bool::from(result.is_identity()) {
+            Ok(())
+        } else {
+            Err(result.to_affine())
+        }
+    }
+
+    /// Performs the multiexp.
+    fn multiexp(self) -> C::Curve {
+        let len = self.len();
         let mut scalars: Vec<C::Scalar> = Vec::with_capacity(len);
         let mut bases: Vec<C> = Vec::with_capacity(len);
 
@@ -144,6 +175,6 @@ impl<'a, C: CurveAffine> MSM<'a, C> {
 
         assert_eq!(scalars.len(), len);
 
-        bool::from(best_multiexp(&scalars, &bases).is_identity())
+        best_multiexp(&scalars, &bases)
     }
 }
@@ -5,7 +5,7 @@ use super::super::Error;
 use super::{Params, MSM};
 use crate::transcript::{EncodedChallenge, TranscriptRead};
 
-use crate::arithmetic::{best_multiexp, BatchInvert, CurveAffine};
+use crate::arithmetic::{best_multiexp, parallelize, BatchInvert, CurveAffine};
 
 /// A guard returned by the verifier
 #[derive(Debug, Clone)]
@@ -14,6 +14,8 @@ pub struct Guard<'a, C: CurveAffine, E: EncodedChallenge<C>> {
     neg_a: C::Scalar,
     challenges: Vec<C::Scalar>,
     challenges_packed: Vec<E>,
+    /// The coefficients of $g(X)$, if they have been cached.
+    s_poly: Option<Vec<C::Scalar>>,
 }
 
 /// An accumulator instance consisting of an evaluation claim and a proof.
@@ -31,7 +33,18 @@ impl<'a, C: CurveAffine, E: EncodedChallenge<C>> Guard<'a, C, E> {
     /// Lets caller supply the challenges and obtain an MSM with updated
     /// scalars and points.
     pub fn use_challenges(mut self) -> MSM<'a, C> {
-        let s = compute_s(&self.challenges, self.neg_a);
+        let s = match self.s_poly.take() {
+            Some(mut s) => {
+                let neg_a = self.neg_a;
+                parallelize(&mut s, |s, _| {
+                    for s in s {
+                        *s *= &neg_a;
+                    }
+                });
+                s
+            }
+            None => compute_s(&self.challenges, self.neg_a),
+        };
         self.msm.add_to_g_scalars(&s);
         self.msm.add_to_h_scalar(self.neg_a);
 
@@ -51,11 +64,29 @@ impl<'a, C: CurveAffine, E: EncodedChallenge<C>> Guard<'a, C, E> {
         (self.msm, accumulator)
     }
 
+    /// Computes and caches the coefficients of $g(X)$, so that they are not recomputed
+    /// by later calls to [`Guard::compute_g`] and [`Guard::use_challenges`].
+    ///
+    /// This is useful when both are needed for the same proof, as in recursive
+    /// verifiers that compute G before deciding how to accumulate it.
+    pub fn cache_s_poly(&mut self) {
+        if self.s_poly.is_none() {
+            self.s_poly = Some(compute_s(&self.challenges, C::Scalar::one()));
+        }
+    }
+
     /// Computes G + H, where G = ⟨s, params.g⟩ and H is used for blinding
     pub fn compute_g(&self) -> C {
-        let s = compute_s(&self.challenges, C::Scalar::one());
+        let computed;
+        let s = match &self.s_poly {
+            Some(s) => s,
+            None => {
+                computed = compute_s(&self.challenges, C::Scalar::one());
+                &computed
+            }
+        };
 
-        let mut tmp = best_multiexp(&s, &self.msm.params.g);
+        let mut tmp = best_multiexp(s, &self.msm.params.g);
         tmp += self.msm.params.h;
         tmp.to_affine()
     }
@@ -78,11 +109,11 @@ pub fn verify_proof<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRea
     msm.add_constant_term(-v);
     let s_poly_commitment = transcript.read_point().map_err(|_| Error::OpeningError)?;
 
-    let iota = *transcript.squeeze_challenge_scalar::<()>();
+    let iota = *transcript.squeeze_challenge_scalar::<()>("iota");
 
     msm.append_term(iota, s_poly_commitment);
 
-    let z = *transcript.squeeze_challenge_scalar::<()>();
+    let z = *transcript.squeeze_challenge_scalar::<()>("z");
 
     let mut rounds = vec![];
     for _ in 0..k {
@@ -90,7 +121,7 @@ pub fn verify_proof<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRea
         let l = transcript.read_point().map_err(|_| Error::OpeningError)?;
         let r = transcript.read_point().map_err(|_| Error::OpeningError)?;
 
-        let challenge_packed = transcript.squeeze_challenge();
+        let challenge_packed = transcript.squeeze_challenge("u");
         let challenge = *challenge_packed.as_challenge_scalar::<()>();
 
         rounds.push((
@@ -143,6 +174,7 @@ pub fn verify_proof<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRea
         neg_a,
         challenges,
         challenges_packed,
+        s_poly: None,
     };
 
     Ok(guard)
@@ -160,6 +192,9 @@ fn compute_b<F: Field>(x: F, challenges: &[F]) -> F {
 }
 
 /// Computes the coefficients of $g(X) = \prod\limits_{i=0}^{k-1} (1 + u_i X^{2^i})$.
+///
+/// Each challenge doubles the number of computed coefficients; the new half is scaled
+/// in parallel.
 fn compute_s<F: Field>(challenges: &[F], init: F) -> Vec<F> {
     assert!(!challenges.is_empty());
     let mut v = vec![F::zero(); 1 << challenges.len()];
@@ -174,9 +209,11 @@ fn compute_s<F: Field>(challenges: &[F], init: F) -> Vec<F> {
         let (left, right) = v.split_at_mut(len);
         let right = &mut right[0..len];
         right.copy_from_slice(left);
-        for v in right {
-            *v *= challenge;
-        }
+        parallelize(right, |right, _| {
+            for v in right {
+                *v *= challenge;
+            }
+        });
     }
 
     v
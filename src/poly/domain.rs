@@ -2,7 +2,7 @@
 //! domain that is of a suitable size for the application.
 
 use crate::{
-    arithmetic::{best_fft, parallelize, BatchInvert, FieldExt, Group},
+    arithmetic::{best_fft, parallelize, BatchInvert, CubeRootOfUnity, FieldExt, Group},
     plonk::Assigned,
 };
 
@@ -33,9 +33,10 @@ pub struct EvaluationDomain<G: Group> {
 }
 
 impl<G: Group> EvaluationDomain<G> {
-    /// This constructs a new evaluation domain object based on the provided
-    /// values $j, k$.
-    pub fn new(j: u32, k: u32) -> Self {
+    /// Returns $\log_2$ of the size of the extended domain, over which a circuit
+    /// with $2^k$ rows whose constraints have degree at most $j$ evaluates its
+    /// quotient polynomial.
+    pub fn extended_k_for(j: u32, k: u32) -> u32 {
         // quotient_poly_degree * params.n - 1 is the degree of the quotient polynomial
         let quotient_poly_degree = (j - 1) as u64;
 
@@ -49,8 +50,31 @@ impl<G: Group> EvaluationDomain<G> {
         while (1 << extended_k) < (n * quotient_poly_degree) {
             extended_k += 1;
         }
+        extended_k
+    }
+
+    /// This constructs a new evaluation domain object based on the provided
+    /// values $j, k$.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the extended domain (see [`EvaluationDomain::extended_k_for`]) is
+    /// larger than the multiplicative subgroup of order $2^S$ of the scalar field.
+    pub fn new(j: u32, k: u32) -> Self {
+        // quotient_poly_degree * params.n - 1 is the degree of the quotient polynomial
+        let quotient_poly_degree = (j - 1) as u64;
+
+        // n = 2^k
+        let n = 1u64 << k;
+
+        let extended_k = Self::extended_k_for(j, k);
+        assert!(
+            extended_k <= G::Scalar::S,
+            "the extended domain of size 2^{} is larger than the 2-adic subgroup of the field",
+            extended_k
+        );
 
-        let mut extended_omega = G::Scalar::ROOT_OF_UNITY;
+        let mut extended_omega = G::Scalar::root_of_unity();
 
         // Get extended_omega, the 2^{extended_k}'th root of unity
         // The loop computes extended_omega = omega^{2 ^ (S - extended_k)}
@@ -77,14 +101,14 @@ impl<G: Group> EvaluationDomain<G> {
         // already.
         // The coset evaluation domain is:
         // zeta {1, extended_omega, extended_omega^2, ..., extended_omega^{(2^extended_k) - 1}}
-        let g_coset = G::Scalar::ZETA;
+        let g_coset = G::Scalar::zeta();
         let g_coset_inv = g_coset.square();
 
         let mut t_evaluations = Vec::with_capacity(1 << (extended_k - k));
         {
             // Compute the evaluations of t(X) = X^n - 1 in the coset evaluation domain.
             // We don't have to compute all of them, because it will repeat.
-            let orig = G::Scalar::ZETA.pow_vartime(&[n as u64, 0, 0, 0]);
+            let orig = G::Scalar::zeta().pow_vartime(&[n as u64, 0, 0, 0]);
             let step = extended_omega.pow_vartime(&[n as u64, 0, 0, 0]);
             let mut cur = orig;
             loop {
@@ -164,6 +188,18 @@ impl<G: Group> EvaluationDomain<G> {
         }
     }
 
+    /// Obtains a polynomial in the extended Lagrange basis when given a vector of
+    /// evaluations over the extended domain; panics if the provided vector is the
+    /// wrong length.
+    pub fn extended_from_vec(&self, values: Vec<G>) -> Polynomial<G, ExtendedLagrangeCoeff> {
+        assert_eq!(values.len(), self.extended_len());
+
+        Polynomial {
+            values,
+            _marker: PhantomData,
+        }
+    }
+
     /// Returns an empty (zero) polynomial in the coefficient basis
     pub fn empty_coeff(&self) -> Polynomial<G, Coeff> {
         Polynomial {
@@ -326,7 +362,7 @@ impl<G: Group> EvaluationDomain<G> {
     // where zeta is a cube root of unity in the multiplicative subgroup with
     // order (p - 1), i.e. zeta^3 = 1.
     fn distribute_powers_zeta(mut a: &mut [G]) {
-        let coset_powers = [G::Scalar::ZETA, G::Scalar::ZETA.square()];
+        let coset_powers = [G::Scalar::zeta(), G::Scalar::zeta().square()];
         parallelize(&mut a, |a, mut index| {
             for a in a {
                 // Distribute powers to move into coset
@@ -366,6 +402,16 @@ impl<G: Group> EvaluationDomain<G> {
         1 << self.extended_k
     }
 
+    /// Get $k$, the base-2 logarithm of the size of the domain.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Get the base-2 logarithm of the size of the extended domain.
+    pub fn extended_k(&self) -> u32 {
+        self.extended_k
+    }
+
     /// Get $\omega$, the generator of the $2^k$ order multiplicative subgroup.
     pub fn get_omega(&self) -> G::Scalar {
         self.omega
@@ -0,0 +1,174 @@
+use std::marker::PhantomData;
+
+use super::{LagrangeCoeff, Polynomial};
+use crate::arithmetic::{BatchInvert, FieldExt};
+use crate::plonk::Assigned;
+
+/// The values of several columns of the same length, held in a single contiguous
+/// allocation.
+///
+/// The values are stored in column-major order: column `i` occupies the `rows` values
+/// starting at `i * rows`. Each column can therefore be accessed as a slice, and the
+/// entire matrix (for example, to transfer it to an accelerator) with
+/// [`ColumnMatrix::as_slice`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnMatrix<F> {
+    values: Vec<F>,
+    columns: usize,
+    rows: usize,
+}
+
+impl<F> Default for ColumnMatrix<F> {
+    fn default() -> Self {
+        ColumnMatrix {
+            values: vec![],
+            columns: 0,
+            rows: 0,
+        }
+    }
+}
+
+impl<F: Clone> ColumnMatrix<F> {
+    /// Creates a matrix of `columns` columns of `rows` rows, with every value set to
+    /// `value`.
+    pub fn new(columns: usize, rows: usize, value: F) -> Self {
+        ColumnMatrix {
+            values: vec![value; columns * rows],
+            columns,
+            rows,
+        }
+    }
+
+    /// Creates a matrix from the values of each column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any column does not have `rows` values.
+    pub fn from_columns<C: AsRef<[F]>>(rows: usize, columns: &[C]) -> Self {
+        let mut values = Vec::with_capacity(columns.len() * rows);
+        for column in columns {
+            assert_eq!(column.as_ref().len(), rows);
+            values.extend_from_slice(column.as_ref());
+        }
+        ColumnMatrix {
+            values,
+            columns: columns.len(),
+            rows,
+        }
+    }
+}
+
+impl<F> ColumnMatrix<F> {
+    /// Returns the number of columns in this matrix.
+    pub fn num_columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Returns the number of rows in each column of this matrix.
+    pub fn num_rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the values of the given column, or `None` if it is out of bounds.
+    pub fn column(&self, column: usize) -> Option<&[F]> {
+        if column < self.columns {
+            Some(&self.values[column * self.rows..(column + 1) * self.rows])
+        } else {
+            None
+        }
+    }
+
+    /// Returns the values of the given column mutably, or `None` if it is out of
+    /// bounds.
+    pub fn column_mut(&mut self, column: usize) -> Option<&mut [F]> {
+        if column < self.columns {
+            Some(&mut self.values[column * self.rows..(column + 1) * self.rows])
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the values of each column, in order.
+    pub fn columns(&self) -> impl Iterator<Item = &[F]> {
+        (0..self.columns).map(move |column| &self.values[column * self.rows..][..self.rows])
+    }
+
+    /// Returns every value of this matrix, in column-major order.
+    pub fn as_slice(&self) -> &[F] {
+        &self.values
+    }
+
+    pub(crate) fn values_mut(&mut self) -> &mut [F] {
+        &mut self.values
+    }
+}
+
+impl<F: Clone> ColumnMatrix<F> {
+    /// Copies each column of this matrix into its own polynomial.
+    pub(crate) fn to_lagrange_polys(&self) -> Vec<Polynomial<F, LagrangeCoeff>> {
+        self.columns()
+            .map(|column| Polynomial {
+                values: column.to_vec(),
+                _marker: PhantomData,
+            })
+            .collect()
+    }
+}
+
+impl<F: FieldExt> ColumnMatrix<Assigned<F>> {
+    /// Evaluates every value of this matrix, inverting all of the denominators in a
+    /// single batch.
+    pub fn batch_invert(&self) -> ColumnMatrix<F> {
+        let mut denominators: Vec<_> = self.values.iter().map(|v| v.denominator()).collect();
+
+        // If the denominator is trivial, we can skip it, reducing the size of the
+        // batch inversion.
+        denominators
+            .iter_mut()
+            .filter_map(|d| d.as_mut())
+            .batch_invert();
+
+        ColumnMatrix {
+            values: self
+                .values
+                .iter()
+                .zip(denominators.into_iter())
+                .map(|(value, inv_den)| value.numerator() * inv_den.unwrap_or_else(F::one))
+                .collect(),
+            columns: self.columns,
+            rows: self.rows,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ff::Field;
+    use pasta_curves::Fp;
+
+    use super::ColumnMatrix;
+    use crate::plonk::Assigned;
+
+    #[test]
+    fn column_matrix() {
+        let mut matrix = ColumnMatrix::new(3, 2, Assigned::Zero);
+        *matrix.column_mut(1).unwrap().get_mut(0).unwrap() = Fp::from(5u64).into();
+        matrix.column_mut(2).unwrap()[1] = Assigned::Rational(Fp::from(3u64), Fp::from(2u64));
+        assert!(matrix.column(3).is_none());
+        assert!(matrix.column_mut(3).is_none());
+
+        let inverted = matrix.batch_invert();
+        let expected = [
+            vec![Fp::zero(), Fp::zero()],
+            vec![Fp::from(5u64), Fp::zero()],
+            vec![
+                Fp::zero(),
+                Fp::from(3u64) * Fp::from(2u64).invert().unwrap(),
+            ],
+        ];
+        assert_eq!(inverted, ColumnMatrix::from_columns(2, &expected));
+        assert_eq!(inverted.columns().count(), 3);
+        assert_eq!(inverted.column(1), Some(&expected[1][..]));
+        assert_eq!(inverted.as_slice().len(), 6);
+    }
+}
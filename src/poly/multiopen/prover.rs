@@ -32,8 +32,8 @@ pub fn create_proof<'a, I, C: CurveAffine, E: EncodedChallenge<C>, T: Transcript
 where
     I: IntoIterator<Item = ProverQuery<'a, C>> + Clone,
 {
-    let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar();
-    let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar();
+    let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar("x_1");
+    let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar("x_2");
 
     let (poly_map, point_sets) = construct_intermediate_sets(queries);
 
@@ -91,7 +91,7 @@ where
 
     transcript.write_point(f_commitment)?;
 
-    let x_3: ChallengeX3<_> = transcript.squeeze_challenge_scalar();
+    let x_3: ChallengeX3<_> = transcript.squeeze_challenge_scalar("x_3");
 
     let q_evals: Vec<C::Scalar> = q_polys
         .iter()
@@ -102,7 +102,7 @@ where
         transcript.write_scalar(*eval)?;
     }
 
-    let x_4: ChallengeX4<_> = transcript.squeeze_challenge_scalar();
+    let x_4: ChallengeX4<_> = transcript.squeeze_challenge_scalar("x_4");
 
     let (f_poly, f_blind_try) = q_polys.iter().zip(q_blinds.iter()).fold(
         (f_poly, f_blind),
@@ -40,11 +40,11 @@ where
     msm.scale(C::Scalar::rand());
 
     // Sample x_1 for compressing openings at the same point sets together
-    let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar();
+    let x_1: ChallengeX1<_> = transcript.squeeze_challenge_scalar("x_1");
 
     // Sample a challenge x_2 for keeping the multi-point quotient
     // polynomial terms linearly independent.
-    let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar();
+    let x_2: ChallengeX2<_> = transcript.squeeze_challenge_scalar("x_2");
 
     let (commitment_map, point_sets) = construct_intermediate_sets(queries);
 
@@ -84,7 +84,7 @@ where
 
     // Sample a challenge x_3 for checking that f(X) was committed to
     // correctly.
-    let x_3: ChallengeX3<_> = transcript.squeeze_challenge_scalar();
+    let x_3: ChallengeX3<_> = transcript.squeeze_challenge_scalar("x_3");
 
     let mut q_evals = Vec::with_capacity(q_eval_sets.len());
     for _ in 0..q_eval_sets.len() {
@@ -111,7 +111,7 @@ where
 
     // Sample a challenge x_4 that we will use to collapse the openings of
     // the various remaining polynomials at x_3 together.
-    let x_4: ChallengeX4<_> = transcript.squeeze_challenge_scalar();
+    let x_4: ChallengeX4<_> = transcript.squeeze_challenge_scalar("x_4");
 
     // Compute the final commitment that has to be opened
     msm.append_term(C::Scalar::one(), f_commitment);
@@ -125,6 +125,11 @@ where
     );
 
     // Verify the opening proof
+    trace_event!(
+        point_sets = q_evals.len(),
+        msm_len = msm.len(),
+        "multiopen commitment"
+    );
     super::commitment::verify_proof(params, msm, transcript, *x_3, msm_eval)
 }
 
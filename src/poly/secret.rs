@@ -0,0 +1,121 @@
+//! Wiping secret values from memory.
+//!
+//! The advice values of a circuit, the polynomials and cosets derived from them, and
+//! the factors that blind their commitments reveal the witness. The prover holds them
+//! in [`Secret`] values, which overwrite them with zeroes when they are dropped if the
+//! `zeroize` feature is enabled. Without the feature, wiping does nothing.
+//!
+//! Wiping is best-effort: values that are copied into temporary buffers (for example,
+//! during FFTs and multiexponentiations) are freed without being wiped. The arithmetic
+//! on secret values is constant-time as far as the field implementation is. Synthesis
+//! branches on whether an assigned value is a fraction, which is determined by the
+//! circuit rather than the witness.
+
+use std::ops::{Deref, DerefMut};
+
+use ff::Field;
+
+use super::{commitment::Blind, ColumnMatrix, Polynomial};
+use crate::plonk::Assigned;
+
+/// Overwrites `values` with `zero`.
+///
+/// With the `zeroize` feature the writes are volatile, so that the compiler does not
+/// elide them even though the values are about to be freed.
+#[cfg(feature = "zeroize")]
+#[allow(unsafe_code)]
+fn wipe_with<T: Copy>(values: &mut [T], zero: T) {
+    for value in values.iter_mut() {
+        // Safety: `value` is valid for writes and aligned, because it is a mutable
+        // reference, and `T: Copy` has no destructor that the write would skip.
+        unsafe { std::ptr::write_volatile(value, zero) };
+    }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+#[cfg(not(feature = "zeroize"))]
+fn wipe_with<T: Copy>(_: &mut [T], _: T) {}
+
+/// Values that can be overwritten with zeroes.
+pub(crate) trait Wipe {
+    /// Overwrites every secret value held by `self`.
+    fn wipe(&mut self);
+}
+
+impl<F: Field, B> Wipe for Polynomial<F, B> {
+    fn wipe(&mut self) {
+        wipe_with(&mut self.values, F::zero());
+    }
+}
+
+impl<F: Field> Wipe for ColumnMatrix<F> {
+    fn wipe(&mut self) {
+        wipe_with(self.values_mut(), F::zero());
+    }
+}
+
+impl<F: Field> Wipe for ColumnMatrix<Assigned<F>> {
+    fn wipe(&mut self) {
+        wipe_with(self.values_mut(), Assigned::Zero);
+    }
+}
+
+impl<F: Field> Wipe for Blind<F> {
+    fn wipe(&mut self) {
+        wipe_with(std::slice::from_mut(&mut self.0), F::zero());
+    }
+}
+
+impl<T: Wipe> Wipe for Vec<T> {
+    fn wipe(&mut self) {
+        for value in self.iter_mut() {
+            value.wipe();
+        }
+    }
+}
+
+/// A value holding secret data, which is wiped when it is dropped.
+#[derive(Debug)]
+pub(crate) struct Secret<T: Wipe>(T);
+
+impl<T: Wipe> Secret<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T: Wipe> Deref for Secret<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Wipe> DerefMut for Secret<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Wipe> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+#[cfg(all(test, feature = "zeroize"))]
+mod tests {
+    use ff::Field;
+    use pasta_curves::Fp;
+
+    use super::Wipe;
+    use crate::poly::ColumnMatrix;
+
+    #[test]
+    fn wipe() {
+        let mut matrix = ColumnMatrix::new(2, 4, Fp::one());
+        matrix.wipe();
+        assert_eq!(matrix, ColumnMatrix::new(2, 4, Fp::zero()));
+    }
+}
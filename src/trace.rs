@@ -0,0 +1,32 @@
+//! Instrumentation of key generation, proving and verification.
+//!
+//! With the `tracing` feature enabled, the backend records `tracing` spans (at the
+//! debug level) for the phases of key generation, each round of the prover and the
+//! steps of the verifier, with the sizes involved as fields. Without it, these macros
+//! expand to nothing, and their arguments are not evaluated.
+
+/// Enters a span that lasts until the end of the enclosing block.
+#[cfg(feature = "tracing")]
+macro_rules! trace_span {
+    ($($arg:tt)*) => {
+        let _span = ::tracing::debug_span!($($arg)*).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_span {
+    ($($arg:tt)*) => {};
+}
+
+/// Records an event within the current span.
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {
+        ::tracing::debug!($($arg)*);
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+    ($($arg:tt)*) => {};
+}
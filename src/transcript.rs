@@ -4,8 +4,11 @@
 use blake2b_simd::{Params as Blake2bParams, State as Blake2bState};
 use std::convert::TryInto;
 
-use crate::arithmetic::{Coordinates, CurveAffine, FieldExt};
+use crate::arithmetic::{
+    CanonicalEncoding, Coordinates, CubeRootOfUnity, CurveAffine, Field, FieldExt,
+};
 
+use std::fmt;
 use std::io::{self, Read, Write};
 use std::marker::PhantomData;
 
@@ -18,26 +21,62 @@ const BLAKE2B_PREFIX_POINT: u8 = 1;
 /// Prefix to a prover's message containing a scalar
 const BLAKE2B_PREFIX_SCALAR: u8 = 2;
 
+/// Prefix to a domain-separation label
+const BLAKE2B_PREFIX_LABEL: u8 = 3;
+
 /// Generic transcript view (from either the prover or verifier's perspective)
+///
+/// This trait and its [`TranscriptRead`] and [`TranscriptWrite`] extensions are object
+/// safe, so that applications can choose a transcript at runtime and pass a
+/// `Box<dyn TranscriptWrite<C, E>>` (or `Box<dyn TranscriptRead<C, E>>`) to
+/// [`create_proof`] or [`verify_proof`]. The generic helper methods are not available
+/// through a trait object itself, but are available on the box.
+///
+/// [`create_proof`]: crate::plonk::create_proof
+/// [`verify_proof`]: crate::plonk::verify_proof
 pub trait Transcript<C: CurveAffine, E: EncodedChallenge<C>> {
-    /// Squeeze an encoded verifier challenge from the transcript.
-    fn squeeze_challenge(&mut self) -> E;
+    /// Squeeze an encoded verifier challenge from the transcript, after absorbing the
+    /// given domain-separation label.
+    fn squeeze_challenge(&mut self, label: &'static str) -> E;
+
+    /// Squeeze a typed challenge (in the scalar field) from the transcript, after
+    /// absorbing the given domain-separation label.
+    fn squeeze_challenge_scalar<T>(&mut self, label: &'static str) -> ChallengeScalar<C, T>
+    where
+        Self: Sized,
+    {
+        ChallengeScalar::new(self.squeeze_challenge(label).get_scalar())
+    }
 
-    /// Squeeze a typed challenge (in the scalar field) from the transcript.
-    fn squeeze_challenge_scalar<T>(&mut self) -> ChallengeScalar<C, T> {
-        ChallengeScalar {
-            inner: self.squeeze_challenge().get_scalar(),
-            _marker: PhantomData,
-        }
+    /// Squeeze `n` typed challenges (in the scalar field) from the transcript.
+    ///
+    /// Before each challenge is squeezed, its index is absorbed as a common scalar
+    /// under the given domain-separation label, so that every challenge is bound to its
+    /// position in the vector. Provers and verifiers derive the same challenges as long
+    /// as they call this at the same point in the transcript.
+    fn squeeze_challenge_scalars<T>(
+        &mut self,
+        label: &'static str,
+        n: usize,
+    ) -> io::Result<Vec<ChallengeScalar<C, T>>>
+    where
+        Self: Sized,
+    {
+        (0..n)
+            .map(|index| {
+                self.common_scalar(label, C::Scalar::from_u64(index as u64))?;
+                Ok(self.squeeze_challenge_scalar(label))
+            })
+            .collect()
     }
 
     /// Writing the point to the transcript without writing it to the proof,
-    /// treating it as a common input.
-    fn common_point(&mut self, point: C) -> io::Result<()>;
+    /// treating it as a common input. The label is absorbed before the point.
+    fn common_point(&mut self, label: &'static str, point: C) -> io::Result<()>;
 
     /// Writing the scalar to the transcript without writing it to the proof,
-    /// treating it as a common input.
-    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()>;
+    /// treating it as a common input. The label is absorbed before the scalar.
+    fn common_scalar(&mut self, label: &'static str, scalar: C::Scalar) -> io::Result<()>;
 }
 
 /// Transcript view from the perspective of a verifier that has access to an
@@ -60,10 +99,130 @@ pub trait TranscriptWrite<C: CurveAffine, E: EncodedChallenge<C>>: Transcript<C,
     fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()>;
 }
 
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E> + ?Sized> Transcript<C, E>
+    for Box<T>
+{
+    fn squeeze_challenge(&mut self, label: &'static str) -> E {
+        (**self).squeeze_challenge(label)
+    }
+
+    fn common_point(&mut self, label: &'static str, point: C) -> io::Result<()> {
+        (**self).common_point(label, point)
+    }
+
+    fn common_scalar(&mut self, label: &'static str, scalar: C::Scalar) -> io::Result<()> {
+        (**self).common_scalar(label, scalar)
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E> + ?Sized> TranscriptRead<C, E>
+    for Box<T>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        (**self).read_point()
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        (**self).read_scalar()
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E> + ?Sized>
+    TranscriptWrite<C, E> for Box<T>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        (**self).write_point(point)
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        (**self).write_scalar(scalar)
+    }
+}
+
+/// The exported state of a [`Blake2bRead`] or [`Blake2bWrite`] transcript.
+///
+/// BLAKE2b does not expose its internal state, so this holds every byte that has been
+/// absorbed by the transcript; resuming the transcript absorbs them again. The state is
+/// therefore somewhat larger than the part of the proof that has been written (points
+/// are absorbed uncompressed).
+///
+/// Only transcripts that are created with `init_resumable` or `from_state` record what
+/// they absorb, so only they can export their state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptState {
+    absorbed: Vec<u8>,
+}
+
+impl TranscriptState {
+    /// Writes this state to a buffer.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.absorbed.len() as u64).to_le_bytes())?;
+        writer.write_all(&self.absorbed)
+    }
+
+    /// Reads a state from a buffer.
+    pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut len = [0u8; 8];
+        reader.read_exact(&mut len)?;
+        let len = u64::from_le_bytes(len);
+
+        let mut absorbed = vec![];
+        reader.by_ref().take(len).read_to_end(&mut absorbed)?;
+        if absorbed.len() as u64 != len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "truncated transcript state",
+            ));
+        }
+
+        Ok(TranscriptState { absorbed })
+    }
+}
+
+/// A BLAKE2b state that can optionally remember what it has absorbed, so that it can be
+/// exported.
+#[derive(Debug, Clone)]
+struct Blake2bSponge {
+    hasher: Blake2bState,
+    /// Every byte absorbed so far, if this sponge records them.
+    absorbed: Option<Vec<u8>>,
+}
+
+impl Blake2bSponge {
+    fn new(record: bool) -> Self {
+        Blake2bSponge {
+            hasher: Blake2bParams::new()
+                .hash_length(64)
+                .personal(b"Halo2-Transcript")
+                .to_state(),
+            absorbed: if record { Some(vec![]) } else { None },
+        }
+    }
+
+    fn from_absorbed(absorbed: &[u8]) -> Self {
+        let mut sponge = Self::new(true);
+        sponge.update(absorbed);
+        sponge
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+        if let Some(absorbed) = &mut self.absorbed {
+            absorbed.extend_from_slice(data);
+        }
+    }
+
+    fn export(&self) -> Option<TranscriptState> {
+        self.absorbed.as_ref().map(|absorbed| TranscriptState {
+            absorbed: absorbed.clone(),
+        })
+    }
+}
+
 /// We will replace BLAKE2b with an algebraic hash function in a later version.
 #[derive(Debug, Clone)]
 pub struct Blake2bRead<R: Read, C: CurveAffine, E: EncodedChallenge<C>> {
-    state: Blake2bState,
+    state: Blake2bSponge,
     reader: R,
     _marker: PhantomData<(C, E)>,
 }
@@ -72,18 +231,46 @@ impl<R: Read, C: CurveAffine, E: EncodedChallenge<C>> Blake2bRead<R, C, E> {
     /// Initialize a transcript given an input buffer.
     pub fn init(reader: R) -> Self {
         Blake2bRead {
-            state: Blake2bParams::new()
-                .hash_length(64)
-                .personal(b"Halo2-Transcript")
-                .to_state(),
+            state: Blake2bSponge::new(false),
+            reader,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Initialize a transcript given an input buffer, recording what it absorbs so
+    /// that its state can be exported with [`Blake2bRead::state`].
+    pub fn init_resumable(reader: R) -> Self {
+        Blake2bRead {
+            state: Blake2bSponge::new(true),
+            reader,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Resume a transcript from an exported state, given the remaining input buffer.
+    ///
+    /// `reader` should be positioned just after the data that was read before the
+    /// state was exported.
+    pub fn from_state(state: &TranscriptState, reader: R) -> Self {
+        Blake2bRead {
+            state: Blake2bSponge::from_absorbed(&state.absorbed),
             reader,
             _marker: PhantomData,
         }
     }
+
+    /// Exports the current state of this transcript, so that it can be resumed with
+    /// [`Blake2bRead::from_state`].
+    ///
+    /// Returns `None` if the transcript was created with [`Blake2bRead::init`], which
+    /// does not record what it absorbs.
+    pub fn state(&self) -> Option<TranscriptState> {
+        self.state.export()
+    }
 }
 
-impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
-    for Blake2bRead<R, C, Challenge255<C>>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> TranscriptRead<C, E>
+    for Blake2bRead<R, C, E>
 {
     fn read_point(&mut self) -> io::Result<C> {
         let mut compressed = C::Repr::default();
@@ -91,7 +278,7 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
         let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
             io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
         })?;
-        self.common_point(point)?;
+        blake2b_absorb_point(&mut self.state, point)?;
 
         Ok(point)
     }
@@ -105,39 +292,28 @@ impl<R: Read, C: CurveAffine> TranscriptRead<C, Challenge255<C>>
                 "invalid field element encoding in proof",
             )
         })?;
-        self.common_scalar(scalar)?;
+        blake2b_absorb_scalar(&mut self.state, scalar);
 
         Ok(scalar)
     }
 }
 
-impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
-    for Blake2bRead<R, C, Challenge255<C>>
+impl<R: Read, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> Transcript<C, E>
+    for Blake2bRead<R, C, E>
 {
-    fn squeeze_challenge(&mut self) -> Challenge255<C> {
-        self.state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
-        let hasher = self.state.clone();
-        let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
-        Challenge255::<C>::new(&result)
+    fn squeeze_challenge(&mut self, label: &'static str) -> E {
+        blake2b_absorb_label(&mut self.state, label);
+        blake2b_squeeze_challenge(&mut self.state)
     }
 
-    fn common_point(&mut self, point: C) -> io::Result<()> {
-        self.state.update(&[BLAKE2B_PREFIX_POINT]);
-        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "cannot write points at infinity to the transcript",
-            )
-        })?;
-        self.state.update(&coords.x().to_bytes());
-        self.state.update(&coords.y().to_bytes());
-
-        Ok(())
+    fn common_point(&mut self, label: &'static str, point: C) -> io::Result<()> {
+        blake2b_absorb_label(&mut self.state, label);
+        blake2b_absorb_point(&mut self.state, point)
     }
 
-    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
-        self.state.update(&[BLAKE2B_PREFIX_SCALAR]);
-        self.state.update(&scalar.to_bytes());
+    fn common_scalar(&mut self, label: &'static str, scalar: C::Scalar) -> io::Result<()> {
+        blake2b_absorb_label(&mut self.state, label);
+        blake2b_absorb_scalar(&mut self.state, scalar);
 
         Ok(())
     }
@@ -146,7 +322,7 @@ impl<R: Read, C: CurveAffine> Transcript<C, Challenge255<C>>
 /// We will replace BLAKE2b with an algebraic hash function in a later version.
 #[derive(Debug, Clone)]
 pub struct Blake2bWrite<W: Write, C: CurveAffine, E: EncodedChallenge<C>> {
-    state: Blake2bState,
+    state: Blake2bSponge,
     writer: W,
     _marker: PhantomData<(C, E)>,
 }
@@ -155,69 +331,147 @@ impl<W: Write, C: CurveAffine, E: EncodedChallenge<C>> Blake2bWrite<W, C, E> {
     /// Initialize a transcript given an output buffer.
     pub fn init(writer: W) -> Self {
         Blake2bWrite {
-            state: Blake2bParams::new()
-                .hash_length(64)
-                .personal(b"Halo2-Transcript")
-                .to_state(),
+            state: Blake2bSponge::new(false),
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Initialize a transcript given an output buffer, recording what it absorbs so
+    /// that its state can be exported with [`Blake2bWrite::state`].
+    pub fn init_resumable(writer: W) -> Self {
+        Blake2bWrite {
+            state: Blake2bSponge::new(true),
             writer,
             _marker: PhantomData,
         }
     }
 
+    /// Resume a transcript from an exported state, given an output buffer for the
+    /// remainder of the proof.
+    ///
+    /// This allows a proof to be created across several processes, or a transcript to
+    /// be bound to application data (such as a block hash) absorbed by another party.
+    /// The proof is the concatenation of the data written before the state was
+    /// exported and the data written to `writer`.
+    pub fn from_state(state: &TranscriptState, writer: W) -> Self {
+        Blake2bWrite {
+            state: Blake2bSponge::from_absorbed(&state.absorbed),
+            writer,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Exports the current state of this transcript, so that it can be resumed with
+    /// [`Blake2bWrite::from_state`].
+    ///
+    /// Returns `None` if the transcript was created with [`Blake2bWrite::init`], which
+    /// does not record what it absorbs.
+    pub fn state(&self) -> Option<TranscriptState> {
+        self.state.export()
+    }
+
     /// Conclude the interaction and return the output buffer (writer).
+    ///
+    /// The writer is not flushed; use [`Blake2bWrite::finish`] when streaming the proof
+    /// to a buffered sink such as a file or socket.
     pub fn finalize(self) -> W {
         // TODO: handle outstanding scalars? see issue #138
         self.writer
     }
+
+    /// Conclude the interaction, flushing the writer before returning it.
+    ///
+    /// Proof data is written to the writer as it is produced, so streaming a proof
+    /// directly to its destination avoids holding the whole proof in memory:
+    ///
+    /// ```ignore
+    /// let file = std::io::BufWriter::new(std::fs::File::create("proof.bin")?);
+    /// let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(file);
+    /// create_proof(&params, &pk, &[circuit], &[&[&instance]], &mut transcript)?;
+    /// transcript.finish()?;
+    /// ```
+    ///
+    /// The proof can then be verified by passing any [`Read`] source (such as a
+    /// `BufReader<File>`) to [`Blake2bRead::init`].
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
 }
 
-impl<W: Write, C: CurveAffine> TranscriptWrite<C, Challenge255<C>>
-    for Blake2bWrite<W, C, Challenge255<C>>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> TranscriptWrite<C, E>
+    for Blake2bWrite<W, C, E>
 {
     fn write_point(&mut self, point: C) -> io::Result<()> {
-        self.common_point(point)?;
+        blake2b_absorb_point(&mut self.state, point)?;
         let compressed = point.to_bytes();
         self.writer.write_all(compressed.as_ref())
     }
     fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
-        self.common_scalar(scalar)?;
+        blake2b_absorb_scalar(&mut self.state, scalar);
         let data = scalar.to_bytes();
         self.writer.write_all(&data[..])
     }
 }
 
-impl<W: Write, C: CurveAffine> Transcript<C, Challenge255<C>>
-    for Blake2bWrite<W, C, Challenge255<C>>
+impl<W: Write, C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>> Transcript<C, E>
+    for Blake2bWrite<W, C, E>
 {
-    fn squeeze_challenge(&mut self) -> Challenge255<C> {
-        self.state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
-        let hasher = self.state.clone();
-        let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
-        Challenge255::<C>::new(&result)
+    fn squeeze_challenge(&mut self, label: &'static str) -> E {
+        blake2b_absorb_label(&mut self.state, label);
+        blake2b_squeeze_challenge(&mut self.state)
     }
 
-    fn common_point(&mut self, point: C) -> io::Result<()> {
-        self.state.update(&[BLAKE2B_PREFIX_POINT]);
-        let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "cannot write points at infinity to the transcript",
-            )
-        })?;
-        self.state.update(&coords.x().to_bytes());
-        self.state.update(&coords.y().to_bytes());
-
-        Ok(())
+    fn common_point(&mut self, label: &'static str, point: C) -> io::Result<()> {
+        blake2b_absorb_label(&mut self.state, label);
+        blake2b_absorb_point(&mut self.state, point)
     }
 
-    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
-        self.state.update(&[BLAKE2B_PREFIX_SCALAR]);
-        self.state.update(&scalar.to_bytes());
+    fn common_scalar(&mut self, label: &'static str, scalar: C::Scalar) -> io::Result<()> {
+        blake2b_absorb_label(&mut self.state, label);
+        blake2b_absorb_scalar(&mut self.state, scalar);
 
         Ok(())
     }
 }
 
+/// Absorbs a domain-separation label, prefixed by its length so that consecutive labels
+/// cannot collide.
+fn blake2b_absorb_label(state: &mut Blake2bSponge, label: &'static str) {
+    state.update(&[BLAKE2B_PREFIX_LABEL]);
+    state.update(&(label.len() as u64).to_le_bytes());
+    state.update(label.as_bytes());
+}
+
+fn blake2b_absorb_point<C: CurveAffine>(state: &mut Blake2bSponge, point: C) -> io::Result<()> {
+    state.update(&[BLAKE2B_PREFIX_POINT]);
+    let coords: Coordinates<C> = Option::from(point.coordinates()).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "cannot write points at infinity to the transcript",
+        )
+    })?;
+    state.update(&coords.x().to_bytes());
+    state.update(&coords.y().to_bytes());
+
+    Ok(())
+}
+
+fn blake2b_absorb_scalar<F: FieldExt>(state: &mut Blake2bSponge, scalar: F) {
+    state.update(&[BLAKE2B_PREFIX_SCALAR]);
+    state.update(&scalar.to_bytes());
+}
+
+fn blake2b_squeeze_challenge<C: CurveAffine, E: EncodedChallenge<C, Input = [u8; 64]>>(
+    state: &mut Blake2bSponge,
+) -> E {
+    state.update(&[BLAKE2B_PREFIX_CHALLENGE]);
+    let hasher = state.hasher.clone();
+    let result: [u8; 64] = hasher.finalize().as_bytes().try_into().unwrap();
+    E::new(&result)
+}
+
 /// The scalar representation of a verifier challenge.
 ///
 /// The `Type` type can be used to scope the challenge to a specific context, or
@@ -228,6 +482,20 @@ pub struct ChallengeScalar<C: CurveAffine, T> {
     _marker: PhantomData<T>,
 }
 
+impl<C: CurveAffine, T> ChallengeScalar<C, T> {
+    pub(crate) fn new(inner: C::Scalar) -> Self {
+        ChallengeScalar {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the value of this challenge.
+    pub fn value(&self) -> C::Scalar {
+        self.inner
+    }
+}
+
 impl<C: CurveAffine, T> std::ops::Deref for ChallengeScalar<C, T> {
     type Target = C::Scalar;
 
@@ -286,6 +554,72 @@ impl<C: CurveAffine> EncodedChallenge<C> for Challenge255<C> {
     }
 }
 
+/// A 128-bit challenge, which is mapped to a scalar with the endoscaling algorithm.
+///
+/// The 128 bits of the challenge are taken from the first 16 bytes of the transcript
+/// output, in little-endian order. They are converted into a scalar by processing them
+/// in pairs, from the most significant pair to the least significant pair, starting
+/// from `acc = 2 * (ZETA + 1)`:
+///
+/// ```text
+/// q = (if bit[2i + 1] { -1 } else { 1 }) * (if bit[2i] { ZETA } else { 1 })
+/// acc = 2 * acc + q
+/// ```
+///
+/// where `ZETA` is [`CubeRootOfUnity::zeta`] of the scalar field. On curves with an efficient
+/// endomorphism, this is the scalar by which a point can be multiplied using only 64
+/// doublings and additions, so circuits that verify proofs recursively can reproduce the
+/// challenge (and its effect on commitments) exactly from its bits.
+///
+/// Use it by choosing it as the challenge type of a transcript, such as
+/// `Blake2bWrite<_, _, Challenge128<_>>`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Challenge128<C: CurveAffine>(u128, PhantomData<C>);
+
+impl<C: CurveAffine> Challenge128<C> {
+    /// Returns the 128 bits of this challenge.
+    pub fn bits(&self) -> u128 {
+        self.0
+    }
+
+    /// Returns the scalar that the given challenge bits represent, computed with the
+    /// endoscaling algorithm.
+    pub fn endoscale(bits: u128) -> C::Scalar {
+        let mut acc = (C::Scalar::zeta() + C::Scalar::one()).double();
+        for i in (0..64).rev() {
+            let should_negate = (bits >> ((i << 1) + 1)) & 1 == 1;
+            let should_endo = (bits >> (i << 1)) & 1 == 1;
+
+            let q = if should_negate {
+                -C::Scalar::one()
+            } else {
+                C::Scalar::one()
+            };
+            let q = if should_endo {
+                q * C::Scalar::zeta()
+            } else {
+                q
+            };
+            acc = acc + q + acc;
+        }
+        acc
+    }
+}
+
+impl<C: CurveAffine> EncodedChallenge<C> for Challenge128<C> {
+    type Input = [u8; 64];
+
+    fn new(challenge_input: &[u8; 64]) -> Self {
+        Challenge128(
+            u128::from_le_bytes(challenge_input[..16].try_into().unwrap()),
+            PhantomData,
+        )
+    }
+    fn get_scalar(&self) -> C::Scalar {
+        Self::endoscale(self.0)
+    }
+}
+
 pub(crate) fn read_n_points<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>>(
     transcript: &mut T,
     n: usize,
@@ -299,3 +633,339 @@ pub(crate) fn read_n_scalars<C: CurveAffine, E: EncodedChallenge<C>, T: Transcri
 ) -> io::Result<Vec<C::Scalar>> {
     (0..n).map(|_| transcript.read_scalar()).collect()
 }
+
+/// A single operation on a transcript, as captured by a [`Recorder`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptEntry<C: CurveAffine> {
+    /// A labelled curve point absorbed as a common input.
+    CommonPoint(&'static str, C),
+    /// A labelled scalar absorbed as a common input.
+    CommonScalar(&'static str, C::Scalar),
+    /// A curve point written to (or read from) the proof.
+    Point(C),
+    /// A scalar written to (or read from) the proof.
+    Scalar(C::Scalar),
+    /// A labelled challenge squeezed from the transcript.
+    Challenge(&'static str, C::Scalar),
+}
+
+impl<C: CurveAffine> TranscriptEntry<C> {
+    /// Returns a short description of the kind of this entry.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TranscriptEntry::CommonPoint(..) => "common point",
+            TranscriptEntry::CommonScalar(..) => "common scalar",
+            TranscriptEntry::Point(_) => "point",
+            TranscriptEntry::Scalar(_) => "scalar",
+            TranscriptEntry::Challenge(..) => "challenge",
+        }
+    }
+
+    /// Returns the domain-separation label of this entry, if it has one.
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            TranscriptEntry::CommonPoint(label, _)
+            | TranscriptEntry::CommonScalar(label, _)
+            | TranscriptEntry::Challenge(label, _) => Some(*label),
+            TranscriptEntry::Point(_) | TranscriptEntry::Scalar(_) => None,
+        }
+    }
+}
+
+impl<C: CurveAffine> fmt::Display for TranscriptEntry<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind())?;
+        if let Some(label) = self.label() {
+            write!(f, " '{}'", label)?;
+        }
+        match self {
+            TranscriptEntry::CommonPoint(_, point) | TranscriptEntry::Point(point) => {
+                write!(f, " {:?}", point)
+            }
+            TranscriptEntry::CommonScalar(_, scalar)
+            | TranscriptEntry::Scalar(scalar)
+            | TranscriptEntry::Challenge(_, scalar) => write!(f, " {:?}", scalar),
+        }
+    }
+}
+
+/// A transcript wrapper that records every absorbed point and scalar, and every
+/// squeezed challenge, for debugging.
+///
+/// Wrap both the prover's and the verifier's transcripts in a `Recorder`, and then use
+/// [`diff_entries`] to find the first operation at which they diverge.
+#[derive(Clone, Debug)]
+pub struct Recorder<C: CurveAffine, T> {
+    inner: T,
+    entries: Vec<TranscriptEntry<C>>,
+}
+
+impl<C: CurveAffine, T> Recorder<C, T> {
+    /// Wraps the given transcript.
+    pub fn new(inner: T) -> Self {
+        Recorder {
+            inner,
+            entries: vec![],
+        }
+    }
+
+    /// Returns the entries recorded so far, in order.
+    pub fn entries(&self) -> &[TranscriptEntry<C>] {
+        &self.entries
+    }
+
+    /// Returns the wrapped transcript and the recorded entries.
+    pub fn into_parts(self) -> (T, Vec<TranscriptEntry<C>>) {
+        (self.inner, self.entries)
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for Recorder<C, T>
+{
+    fn squeeze_challenge(&mut self, label: &'static str) -> E {
+        let challenge = self.inner.squeeze_challenge(label);
+        self.entries
+            .push(TranscriptEntry::Challenge(label, challenge.get_scalar()));
+        challenge
+    }
+
+    fn common_point(&mut self, label: &'static str, point: C) -> io::Result<()> {
+        self.inner.common_point(label, point)?;
+        self.entries
+            .push(TranscriptEntry::CommonPoint(label, point));
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, label: &'static str, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.common_scalar(label, scalar)?;
+        self.entries
+            .push(TranscriptEntry::CommonScalar(label, scalar));
+        Ok(())
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+    for Recorder<C, T>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let point = self.inner.read_point()?;
+        self.entries.push(TranscriptEntry::Point(point));
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let scalar = self.inner.read_scalar()?;
+        self.entries.push(TranscriptEntry::Scalar(scalar));
+        Ok(scalar)
+    }
+}
+
+impl<C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptWrite<C, E>> TranscriptWrite<C, E>
+    for Recorder<C, T>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.inner.write_point(point)?;
+        self.entries.push(TranscriptEntry::Point(point));
+        Ok(())
+    }
+
+    fn write_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.inner.write_scalar(scalar)?;
+        self.entries.push(TranscriptEntry::Scalar(scalar));
+        Ok(())
+    }
+}
+
+/// The first point at which two recorded transcripts diverge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptMismatch<C: CurveAffine> {
+    /// The index of the first differing entry.
+    pub index: usize,
+    /// The entry in the first transcript, or `None` if it ended early.
+    pub left: Option<TranscriptEntry<C>>,
+    /// The entry in the second transcript, or `None` if it ended early.
+    pub right: Option<TranscriptEntry<C>>,
+}
+
+impl<C: CurveAffine> fmt::Display for TranscriptMismatch<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transcripts diverge at entry {}:", self.index)?;
+        for (sign, entry) in [("-", &self.left), ("+", &self.right)].iter() {
+            match entry {
+                Some(entry) => writeln!(f, "  {} {}", sign, entry)?,
+                None => writeln!(f, "  {} (end of transcript)", sign)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares two sequences of recorded transcript entries (typically from a prover and
+/// a verifier), returning the first point at which they diverge.
+pub fn diff_entries<C: CurveAffine>(
+    left: &[TranscriptEntry<C>],
+    right: &[TranscriptEntry<C>],
+) -> Option<TranscriptMismatch<C>> {
+    (0..std::cmp::max(left.len(), right.len()))
+        .map(|index| (index, left.get(index), right.get(index)))
+        .find(|(_, left, right)| left != right)
+        .map(|(index, left, right)| TranscriptMismatch {
+            index,
+            left: left.cloned(),
+            right: right.cloned(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, BufWriter};
+
+    use group::prime::PrimeCurveAffine;
+    use pasta_curves::{EqAffine, Fp};
+
+    use super::{
+        diff_entries, Blake2bRead, Blake2bWrite, Challenge128, Challenge255, EncodedChallenge,
+        Recorder, Transcript, TranscriptEntry, TranscriptRead, TranscriptState, TranscriptWrite,
+    };
+
+    #[test]
+    fn resumed_transcript() {
+        let point = EqAffine::generator();
+        let scalar = Fp::from(7u64);
+
+        let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+        transcript.write_point(point).unwrap();
+        transcript.write_scalar(scalar).unwrap();
+        let expected = transcript.squeeze_challenge("x").get_scalar();
+        let proof = transcript.finalize();
+
+        // Write the proof in two parts, moving the state between them.
+        let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init_resumable(vec![]);
+        transcript.write_point(point).unwrap();
+        let mut encoded = vec![];
+        transcript.state().unwrap().write(&mut encoded).unwrap();
+        let mut first = transcript.finalize();
+
+        let state = TranscriptState::read(&mut &encoded[..]).unwrap();
+        assert!(TranscriptState::read(&mut &encoded[..encoded.len() - 1]).is_err());
+        let mut transcript =
+            Blake2bWrite::<_, EqAffine, Challenge255<_>>::from_state(&state, vec![]);
+        transcript.write_scalar(scalar).unwrap();
+        assert_eq!(transcript.squeeze_challenge("x").get_scalar(), expected);
+        first.extend(transcript.finalize());
+        assert_eq!(first, proof);
+
+        // Verify it in two parts as well.
+        let transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+        assert!(transcript.state().is_none());
+        let mut transcript =
+            Blake2bRead::<_, EqAffine, Challenge255<_>>::init_resumable(&proof[..]);
+        assert_eq!(transcript.read_point().unwrap(), point);
+        let state = transcript.state().unwrap();
+        let mut transcript =
+            Blake2bRead::<_, EqAffine, Challenge255<_>>::from_state(&state, &proof[32..]);
+        assert_eq!(transcript.read_scalar().unwrap(), scalar);
+        assert_eq!(transcript.squeeze_challenge("x").get_scalar(), expected);
+    }
+
+    #[test]
+    fn streamed_proof() {
+        let point = EqAffine::generator();
+        let scalar = Fp::from(7u64);
+
+        let sink = BufWriter::new(vec![]);
+        let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(sink);
+        transcript.write_point(point).unwrap();
+        transcript.write_scalar(scalar).unwrap();
+        let proof = transcript.finish().unwrap().into_inner().unwrap();
+
+        let mut transcript =
+            Blake2bRead::<_, EqAffine, Challenge255<_>>::init(BufReader::new(&proof[..]));
+        assert_eq!(transcript.read_point().unwrap(), point);
+        assert_eq!(transcript.read_scalar().unwrap(), scalar);
+    }
+
+    #[test]
+    fn recorder_diff() {
+        let point = EqAffine::generator();
+        let scalar = Fp::from(7u64);
+
+        let mut prover = Recorder::new(Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]));
+        prover.write_point(point).unwrap();
+        prover.write_scalar(scalar).unwrap();
+        let _: Challenge255<_> = prover.squeeze_challenge("test");
+        let (transcript, prover_entries) = prover.into_parts();
+        let proof = transcript.finalize();
+
+        let mut verifier = Recorder::new(Blake2bRead::<_, EqAffine, Challenge255<_>>::init(
+            &proof[..],
+        ));
+        verifier.read_point().unwrap();
+        verifier.read_scalar().unwrap();
+        let _: Challenge255<_> = verifier.squeeze_challenge("test");
+        assert_eq!(diff_entries(&prover_entries, verifier.entries()), None);
+
+        // A verifier that forgets to read the scalar squeezes a different challenge.
+        let mut verifier = Recorder::new(Blake2bRead::<_, EqAffine, Challenge255<_>>::init(
+            &proof[..],
+        ));
+        verifier.read_point().unwrap();
+        let _: Challenge255<_> = verifier.squeeze_challenge("test");
+        let mismatch = diff_entries(&prover_entries, verifier.entries()).unwrap();
+        assert_eq!(mismatch.index, 1);
+        assert_eq!(mismatch.left, Some(TranscriptEntry::Scalar(scalar)));
+        assert!(matches!(
+            mismatch.right,
+            Some(TranscriptEntry::Challenge("test", _))
+        ));
+    }
+
+    #[test]
+    fn squeeze_challenge_scalars() {
+        let mut prover = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+        prover.write_scalar(Fp::from(7u64)).unwrap();
+        let challenges = prover.squeeze_challenge_scalars::<()>("batch", 3).unwrap();
+        let proof = prover.finalize();
+
+        // The challenges are distinct.
+        assert_ne!(*challenges[0], *challenges[1]);
+        assert_ne!(*challenges[1], *challenges[2]);
+
+        // The verifier derives the same challenges.
+        let mut verifier = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(&proof[..]);
+        verifier.read_scalar().unwrap();
+        let expected = verifier
+            .squeeze_challenge_scalars::<()>("batch", 3)
+            .unwrap();
+        for (challenge, expected) in challenges.iter().zip(expected.iter()) {
+            assert_eq!(**challenge, **expected);
+        }
+    }
+
+    #[test]
+    fn challenge_128() {
+        let mut prover = Blake2bWrite::<_, EqAffine, Challenge128<_>>::init(vec![]);
+        prover.write_scalar(Fp::from(7u64)).unwrap();
+        let challenge = prover.squeeze_challenge("c");
+        let proof = prover.finalize();
+
+        let mut verifier = Blake2bRead::<_, EqAffine, Challenge128<_>>::init(&proof[..]);
+        verifier.read_scalar().unwrap();
+        assert_eq!(verifier.squeeze_challenge("c"), challenge);
+
+        // The scalar is determined by the bits of the challenge.
+        assert_eq!(
+            challenge.get_scalar(),
+            Challenge128::<EqAffine>::endoscale(challenge.bits())
+        );
+        assert_ne!(
+            Challenge128::<EqAffine>::endoscale(0),
+            Challenge128::<EqAffine>::endoscale(1)
+        );
+        assert_ne!(
+            Challenge128::<EqAffine>::endoscale(1),
+            Challenge128::<EqAffine>::endoscale(2)
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Runs the full proving pipeline over each curve of the Pasta cycle, and of the
+//! BN254 and grumpkin cycle. With the `secp256k1` feature, it also proves a circuit
+//! over secp256k1.
+//!
+//! The scalar field of grumpkin has no evaluation domain large enough for a circuit,
+//! so over grumpkin we only check that keygen rejects the circuit, and open a
+//! polynomial commitment instead.
+
+use halo2::arithmetic::{eval_polynomial, CurveAffine, FieldExt};
+use halo2::curves::{bn256, grumpkin};
+use halo2::dev::prove_and_verify;
+use halo2::interop::r1cs::{Constraint, R1cs, R1csCircuit, Variable};
+use halo2::pasta::{EpAffine, EqAffine};
+use halo2::plonk::{keygen_vk, Error};
+use halo2::poly::{
+    commitment::{create_proof, verify_proof, Blind, Params},
+    EvaluationDomain,
+};
+use halo2::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, Transcript, TranscriptRead, TranscriptWrite,
+};
+
+/// Proves knowledge of `x` such that `x^3 + x + 5` equals the public input.
+fn cubic<F: FieldExt>() -> R1cs<F> {
+    let one = F::one();
+    let (x, x2, x3) = (
+        Variable::Witness(0),
+        Variable::Witness(1),
+        Variable::Witness(2),
+    );
+
+    let mut r1cs = R1cs::new(1, 3);
+    for (a, b, c) in [(x, x, x2), (x2, x, x3)].iter().copied() {
+        r1cs.enforce(Constraint {
+            a: vec![(a, one)],
+            b: vec![(b, one)],
+            c: vec![(c, one)],
+        })
+        .unwrap();
+    }
+    r1cs.enforce(Constraint {
+        a: vec![(x3, one), (x, one), (Variable::One, F::from_u64(5))],
+        b: vec![(Variable::One, one)],
+        c: vec![(Variable::Instance(0), one)],
+    })
+    .unwrap();
+    r1cs
+}
+
+fn prove_cubic<C: CurveAffine>() {
+    let r1cs = cubic::<C::Scalar>();
+    let output = C::Scalar::from_u64(35);
+    let witness = [3, 9, 27]
+        .iter()
+        .map(|value| C::Scalar::from_u64(*value))
+        .collect::<Vec<_>>();
+
+    let circuit = R1csCircuit::new(&r1cs, &[output], &witness).unwrap();
+    prove_and_verify::<C, _>(4, &circuit, vec![vec![output]]).unwrap();
+}
+
+#[test]
+fn pallas() {
+    prove_cubic::<EpAffine>();
+}
+
+#[test]
+fn vesta() {
+    prove_cubic::<EqAffine>();
+}
+
+#[test]
+fn bn254() {
+    prove_cubic::<bn256::G1Affine>();
+}
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn secp256k1() {
+    prove_cubic::<halo2::curves::secp256k1::Secp256k1Affine>();
+}
+
+#[test]
+fn grumpkin() {
+    type C = grumpkin::G1Affine;
+
+    let r1cs = cubic::<grumpkin::Fr>();
+    let output = grumpkin::Fr::from_u64(35);
+    let witness = [3, 9, 27]
+        .iter()
+        .map(|value| grumpkin::Fr::from_u64(*value))
+        .collect::<Vec<_>>();
+    let circuit = R1csCircuit::new(&r1cs, &[output], &witness).unwrap();
+    assert!(matches!(
+        keygen_vk(&Params::<C>::new(4), &circuit),
+        Err(Error::IncompatibleParams)
+    ));
+
+    // The field only has an evaluation domain of size 2.
+    const K: u32 = 1;
+    let params = Params::<C>::new(K);
+    let domain = EvaluationDomain::new(1, K);
+    let mut px = domain.empty_coeff();
+    for (i, a) in px.iter_mut().enumerate() {
+        *a = grumpkin::Fr::from_u64(i as u64 + 1);
+    }
+    let blind = Blind(grumpkin::Fr::rand());
+    let p = params.commit(&px, blind).to_affine();
+
+    let mut transcript = Blake2bWrite::<_, C, Challenge255<C>>::init(vec![]);
+    transcript.write_point(p).unwrap();
+    let x = transcript.squeeze_challenge_scalar::<()>("x");
+    let v = eval_polynomial(&px, *x);
+    transcript.write_scalar(v).unwrap();
+    create_proof(&params, &mut transcript, &px, blind, *x).unwrap();
+    let proof = transcript.finalize();
+
+    let mut transcript = Blake2bRead::<_, C, Challenge255<C>>::init(&proof[..]);
+    let p = transcript.read_point().unwrap();
+    let x = transcript.squeeze_challenge_scalar::<()>("x");
+    let v = transcript.read_scalar().unwrap();
+    let mut msm = params.empty_msm();
+    msm.append_term(grumpkin::Fr::one(), p);
+    let guard = verify_proof(&params, msm, &mut transcript, *x, v).unwrap();
+    assert!(guard.use_challenges().eval());
+}
@@ -0,0 +1,308 @@
+//! Uses a field that is defined outside of this crate, and implements only the
+//! capability traits of [`FieldExt`], with the field-generic parts of the proving
+//! system.
+
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use ff::{Field, PrimeField};
+use halo2::arithmetic::{
+    check_field_constants, eval_polynomial, CanonicalEncoding, CubeRootOfUnity, FieldExt,
+    TwoAdicField,
+};
+use halo2::dev::MockProver;
+use halo2::interop::r1cs::{Constraint, R1cs, R1csCircuit, Variable};
+use halo2::poly::{EvaluationDomain, Rotation};
+use rand::RngCore;
+use subtle::{Choice, ConditionallySelectable, CtOption};
+
+/// The modulus of the Goldilocks field, `2^64 - 2^32 + 1`.
+const MODULUS: u64 = 0xffff_ffff_0000_0001;
+
+/// An element of the Goldilocks field, in canonical form.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct Goldilocks(u64);
+
+impl Goldilocks {
+    fn reduce(value: u128) -> Self {
+        Goldilocks((value % u128::from(MODULUS)) as u64)
+    }
+
+    fn sum(self, rhs: Self) -> Self {
+        Goldilocks::reduce(u128::from(self.0) + u128::from(rhs.0))
+    }
+
+    fn difference(self, rhs: Self) -> Self {
+        Goldilocks::reduce(u128::from(self.0) + u128::from(MODULUS - rhs.0))
+    }
+
+    fn product(self, rhs: Self) -> Self {
+        Goldilocks::reduce(u128::from(self.0) * u128::from(rhs.0))
+    }
+}
+
+impl ConditionallySelectable for Goldilocks {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Goldilocks(u64::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl From<u64> for Goldilocks {
+    fn from(value: u64) -> Self {
+        Goldilocks::reduce(u128::from(value))
+    }
+}
+
+impl Neg for Goldilocks {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Goldilocks::reduce(u128::from(MODULUS - self.0))
+    }
+}
+
+macro_rules! impl_binop {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:ident) => {
+        impl $trait for Goldilocks {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                self.$op(rhs)
+            }
+        }
+
+        impl<'a> $trait<&'a Goldilocks> for Goldilocks {
+            type Output = Self;
+
+            fn $method(self, rhs: &'a Self) -> Self {
+                self.$op(*rhs)
+            }
+        }
+
+        impl $assign_trait for Goldilocks {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = self.$op(rhs);
+            }
+        }
+
+        impl<'a> $assign_trait<&'a Goldilocks> for Goldilocks {
+            fn $assign_method(&mut self, rhs: &'a Self) {
+                *self = self.$op(*rhs);
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, AddAssign, add_assign, sum);
+impl_binop!(Sub, sub, SubAssign, sub_assign, difference);
+impl_binop!(Mul, mul, MulAssign, mul_assign, product);
+
+impl Field for Goldilocks {
+    fn random(mut rng: impl RngCore) -> Self {
+        Goldilocks::reduce(u128::from(rng.next_u64()))
+    }
+
+    fn zero() -> Self {
+        Goldilocks(0)
+    }
+
+    fn one() -> Self {
+        Goldilocks(1)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    fn square(&self) -> Self {
+        *self * self
+    }
+
+    fn double(&self) -> Self {
+        *self + self
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        CtOption::new(
+            self.pow_vartime(&[MODULUS - 2]),
+            Choice::from(!self.is_zero() as u8),
+        )
+    }
+
+    fn sqrt(&self) -> CtOption<Self> {
+        // Tonelli-Shanks, with c = ROOT_OF_UNITY of order 2^S.
+        let t_plus1_over2 = ((MODULUS >> Self::S) + 1) / 2;
+        let mut m = Self::S;
+        let mut c = Self::root_of_unity();
+        let mut t = self.pow_vartime(&[MODULUS >> Self::S]);
+        let mut r = self.pow_vartime(&[t_plus1_over2]);
+        while t != Self::one() && !t.is_zero() {
+            let mut i = 0;
+            let mut t2i = t;
+            while t2i != Self::one() {
+                t2i = t2i.square();
+                i += 1;
+                if i == m {
+                    return CtOption::new(Self::zero(), Choice::from(0));
+                }
+            }
+            let b = c.pow_vartime(&[1u64 << (m - i - 1)]);
+            m = i;
+            c = b.square();
+            t *= c;
+            r *= b;
+        }
+        CtOption::new(r, Choice::from((r.square() == *self) as u8))
+    }
+}
+
+impl PrimeField for Goldilocks {
+    type Repr = [u8; 8];
+
+    fn from_repr(repr: Self::Repr) -> Option<Self> {
+        let value = u64::from_le_bytes(repr);
+        if value < MODULUS {
+            Some(Goldilocks(value))
+        } else {
+            None
+        }
+    }
+
+    fn to_repr(&self) -> Self::Repr {
+        self.0.to_le_bytes()
+    }
+
+    fn is_odd(&self) -> bool {
+        self.0 & 1 == 1
+    }
+
+    const NUM_BITS: u32 = 64;
+    const CAPACITY: u32 = 63;
+
+    fn multiplicative_generator() -> Self {
+        Goldilocks(7)
+    }
+
+    const S: u32 = 32;
+
+    fn root_of_unity() -> Self {
+        Goldilocks(0x1856_29dc_da58_878c)
+    }
+}
+
+impl TwoAdicField for Goldilocks {
+    fn root_of_unity_inv() -> Self {
+        Goldilocks(0x76b6_b635_b6fc_8719)
+    }
+
+    fn delta() -> Self {
+        // GENERATOR^(2^S)
+        Goldilocks(0xaa5b_2509_f86b_b4d4)
+    }
+
+    fn two_inv() -> Self {
+        Goldilocks(0x7fff_ffff_8000_0001)
+    }
+}
+
+impl CubeRootOfUnity for Goldilocks {
+    fn zeta() -> Self {
+        // GENERATOR^((p - 1) / 3)
+        Goldilocks(0xffff_fffe_0000_0001)
+    }
+}
+
+impl CanonicalEncoding for Goldilocks {
+    fn modulus() -> &'static str {
+        "0x000000000000000000000000000000000000000000000000ffffffff00000001"
+    }
+
+    fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes[..8].copy_from_slice(&self.to_repr());
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8; 32]) -> CtOption<Self> {
+        let mut repr = [0; 8];
+        repr.copy_from_slice(&bytes[..8]);
+        let value = Self::from_repr(repr).filter(|_| bytes[8..].iter().all(|b| *b == 0));
+        CtOption::new(
+            value.unwrap_or_default(),
+            Choice::from(value.is_some() as u8),
+        )
+    }
+
+    fn from_bytes_wide(bytes: &[u8; 64]) -> Self {
+        bytes.rchunks(8).fold(Self::zero(), |acc, limb| {
+            let mut repr = [0; 8];
+            repr.copy_from_slice(limb);
+            Goldilocks::reduce((u128::from(acc.0) << 64) | u128::from(u64::from_le_bytes(repr)))
+        })
+    }
+}
+
+#[test]
+fn field_constants() {
+    assert_eq!(check_field_constants::<Goldilocks>(), Ok(()));
+}
+
+#[test]
+fn fft() {
+    let k = 4;
+    let domain = EvaluationDomain::<Goldilocks>::new(3, k);
+    let values: Vec<_> = (0..1 << k).map(|_| Goldilocks::rand()).collect();
+
+    let coeffs = domain.lagrange_to_coeff(domain.lagrange_from_vec(values.clone()));
+    let mut point = Goldilocks::one();
+    for value in values {
+        assert_eq!(eval_polynomial(&coeffs, point), value);
+        point *= domain.get_omega();
+    }
+
+    // The extended domain is a coset, so the round trip recovers the coefficients.
+    let extended = domain.coeff_to_extended(coeffs.clone(), Rotation::cur());
+    assert_eq!(domain.extended_to_coeff(extended)[..1 << k], coeffs[..]);
+}
+
+#[test]
+fn mock_prover() {
+    let one = Goldilocks::one();
+    let (x, x2, x3) = (
+        Variable::Witness(0),
+        Variable::Witness(1),
+        Variable::Witness(2),
+    );
+
+    // x^3 + x + 5 = 35
+    let mut r1cs = R1cs::new(1, 3);
+    for (a, b, c) in [(x, x, x2), (x2, x, x3)].iter().copied() {
+        r1cs.enforce(Constraint {
+            a: vec![(a, one)],
+            b: vec![(b, one)],
+            c: vec![(c, one)],
+        })
+        .unwrap();
+    }
+    r1cs.enforce(Constraint {
+        a: vec![
+            (x3, one),
+            (x, one),
+            (Variable::One, Goldilocks::from_u64(5)),
+        ],
+        b: vec![(Variable::One, one)],
+        c: vec![(Variable::Instance(0), one)],
+    })
+    .unwrap();
+
+    let output = Goldilocks::from_u64(35);
+    let witness: Vec<_> = [3, 9, 27]
+        .iter()
+        .map(|value| Goldilocks::from_u64(*value))
+        .collect();
+    let circuit = R1csCircuit::new(&r1cs, &[output], &witness).unwrap();
+    let prover = MockProver::run(4, &circuit, vec![vec![output]]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let prover = MockProver::run(4, &circuit, vec![vec![output + one]]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -1,21 +1,29 @@
 #![allow(clippy::many_single_char_names)]
 #![allow(clippy::op_ref)]
 
-use group::Curve;
-use halo2::arithmetic::FieldExt;
+use group::{Curve, GroupEncoding};
+use halo2::arithmetic::{CubeRootOfUnity, FieldExt};
 use halo2::circuit::{Cell, Layouter, SimpleFloorPlanner};
 use halo2::dev::MockProver;
+use halo2::gadget::table::TableBuilder;
 use halo2::pasta::{EqAffine, Fp};
 use halo2::plonk::{
-    create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column, ConstraintSystem,
-    Error, Fixed, Permutation, VerifyingKey,
+    check_link, create_proof, create_proof_from_circuits, create_proof_from_witness,
+    create_proof_with_cache, create_proof_with_observer, create_proof_with_options,
+    estimate_prover_memory, generate_witness, k_for_circuit, keygen_pk, keygen_vk,
+    proof_challenges, verify_proof, verify_proof_with_instance_values, verify_single, Advice,
+    CachedWitness, Circuit, CircuitShape, Column, ConstraintSystem, Error, Expression, Fixed,
+    LinkError, LinkedColumn, Permutation, PhaseReport, Proof, ProofSection, ProverObserver,
+    ProverOptions, ProverPhase, Selector, VerifyError, VerifyingKey, VerifyingKeyCommitments,
+    Witness,
 };
 use halo2::poly::{
     commitment::{Blind, Params},
     Rotation,
 };
-use halo2::transcript::{Blake2bRead, Blake2bWrite, Challenge255};
+use halo2::transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptRead, TranscriptWrite};
 use std::marker::PhantomData;
+use std::slice;
 
 #[test]
 fn plonk_api() {
@@ -236,28 +244,9 @@ fn plonk_api() {
             layouter: &mut impl Layouter<FF>,
             values: &[Vec<FF>],
         ) -> Result<(), Error> {
-            layouter.assign_region(
-                || "",
-                |mut region| {
-                    for (index, (&value_0, &value_1)) in
-                        values[0].iter().zip(values[1].iter()).enumerate()
-                    {
-                        region.assign_fixed(
-                            || "table col 1",
-                            self.config.sl,
-                            index,
-                            || Ok(value_0),
-                        )?;
-                        region.assign_fixed(
-                            || "table col 2",
-                            self.config.sl2,
-                            index,
-                            || Ok(value_1),
-                        )?;
-                    }
-                    Ok(())
-                },
-            )?;
+            TableBuilder::new(&[self.config.sl.into(), self.config.sl2.into()])
+                .rows(values[0].iter().cloned().zip(values[1].iter().cloned()))
+                .assign(layouter.namespace(|| "lookup table"))?;
             Ok(())
         }
     }
@@ -406,7 +395,7 @@ fn plonk_api() {
         }
     }
 
-    let a = Fp::from_u64(2834758237) * Fp::ZETA;
+    let a = Fp::from_u64(2834758237) * Fp::zeta();
     let a_squared = a * &a;
     let instance = Fp::one() + Fp::one();
     let lookup_table = vec![instance, a, a, Fp::zero()];
@@ -422,6 +411,10 @@ fn plonk_api() {
         lookup_tables: vec![lookup_table, lookup_table_2],
     };
 
+    // The circuit should fit within the parameters we generated.
+    let min_k = k_for_circuit(&empty_circuit).expect("k_for_circuit should not fail");
+    assert!(min_k <= K);
+
     // Initialize the proving key
     let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
     let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
@@ -451,6 +444,18 @@ fn plonk_api() {
         )
         .expect("proof generation should not fail");
         let proof: Vec<u8> = transcript.finalize();
+        assert_eq!(pk.get_vk().proof_size(2), proof.len());
+
+        // Check that the structured proof round-trips.
+        let parsed = Proof::from_bytes(pk.get_vk(), 2, &proof).unwrap();
+        assert_eq!(parsed.instances().len(), 2);
+        assert_eq!(parsed.to_bytes(), proof);
+        let (instances, shared) = parsed.clone().split_instances();
+        assert_eq!(
+            instances.iter().map(|i| i.encoded_len()).sum::<usize>() + shared.encoded_len(),
+            proof.len()
+        );
+        assert_eq!(Proof::from_parts(instances, shared), parsed);
 
         let pubinput_slice = &[pubinput];
         let pubinput_slice_copy = &[pubinput];
@@ -474,12 +479,77 @@ fn plonk_api() {
             assert!(msm.eval());
         }
         let msm = guard.clone().use_challenges();
+        assert_eq!(msm.len(), pk.get_vk().verifier_cost().msm_len(2));
+
+        // The gate costs are sorted, and no gate needs a larger domain than the circuit.
+        let gate_costs = pk.get_vk().gate_costs();
+        assert!(!gate_costs.is_empty());
+        assert!(gate_costs
+            .windows(2)
+            .all(|pair| pair[0].extended_k >= pair[1].extended_k));
+        assert!(gate_costs[0].extended_k <= pk.get_vk().get_domain().extended_k());
         assert!(msm.clone().eval());
+
+        // A truncated proof is rejected, reporting the section that could not be read.
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..proof.len() - 1]);
+        assert_eq!(
+            verify_proof(
+                &params,
+                pk.get_vk(),
+                params.empty_msm(),
+                &[pubinput_slice, pubinput_slice_copy],
+                &mut transcript,
+            )
+            .err(),
+            Some(VerifyError::Multiopen)
+        );
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..64]);
+        assert_eq!(
+            verify_proof(
+                &params,
+                pk.get_vk(),
+                params.empty_msm(),
+                &[pubinput_slice, pubinput_slice_copy],
+                &mut transcript,
+            )
+            .err(),
+            Some(VerifyError::Transcript {
+                section: ProofSection::AdviceCommitments,
+                proof: Some(0),
+            })
+        );
+
         let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
         let mut vk_buffer = vec![];
         pk.get_vk().write(&mut vk_buffer).unwrap();
         let vk = VerifyingKey::<EqAffine>::read::<_, MyCircuit<Fp>>(&mut &vk_buffer[..], &params)
             .unwrap();
+
+        // The verifying key can be split into its shape and commitments, which are
+        // encoded separately and reassembled.
+        {
+            let (shape, commitments) =
+                VerifyingKey::<EqAffine>::read::<_, MyCircuit<Fp>>(&mut &vk_buffer[..], &params)
+                    .unwrap()
+                    .into_parts();
+            assert_eq!(shape, CircuitShape::<Fp>::new::<MyCircuit<Fp>>());
+            let mut shape_buffer = vec![];
+            shape.write(&mut shape_buffer).unwrap();
+            let mut commitments_buffer = vec![];
+            commitments.write(&mut commitments_buffer).unwrap();
+            assert!(vk_buffer.ends_with(&commitments_buffer));
+
+            let shape =
+                CircuitShape::<Fp>::read::<_, MyCircuit<Fp>>(&mut &shape_buffer[..]).unwrap();
+            let commitments =
+                VerifyingKeyCommitments::<EqAffine>::read(&mut &commitments_buffer[..], &shape)
+                    .unwrap();
+            let rebuilt = VerifyingKey::from_parts(&params, shape, commitments).unwrap();
+            assert_eq!(
+                format!("{:?}", rebuilt.pinned()),
+                format!("{:?}", vk.pinned())
+            );
+        }
         let guard = verify_proof(
             &params,
             &vk,
@@ -499,331 +569,964 @@ fn plonk_api() {
         }
     }
 
-    // Check that the verification key has not changed unexpectedly
+    // Check that an observer sees every phase of proof creation, in order.
+    #[derive(Default)]
+    struct Recorder(Vec<(ProverPhase, usize)>);
+
+    impl ProverObserver for Recorder {
+        fn phase_finished(&mut self, phase: ProverPhase, report: &PhaseReport) {
+            self.0.push((phase, report.size));
+        }
+    }
+
+    let mut recorder = Recorder::default();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof_with_observer(
+        &params,
+        &pk,
+        &[circuit.clone(), circuit.clone()],
+        &[&[pubinputs.clone()], &[pubinputs.clone()]],
+        &mut transcript,
+        &mut recorder,
+    )
+    .expect("proof generation should not fail");
+    assert_eq!(
+        recorder
+            .0
+            .iter()
+            .map(|(phase, _)| *phase)
+            .collect::<Vec<_>>(),
+        vec![
+            ProverPhase::InstanceCommitment,
+            ProverPhase::WitnessSynthesis(0),
+            ProverPhase::AdviceCommitment(0),
+            ProverPhase::WitnessSynthesis(1),
+            ProverPhase::AdviceCommitment(1),
+            ProverPhase::LookupPermutation,
+            ProverPhase::PermutationProduct,
+            ProverPhase::LookupProduct,
+            ProverPhase::Vanishing,
+            ProverPhase::Evaluation,
+            ProverPhase::Multiopen,
+        ]
+    );
+    assert_eq!(recorder.0[0].1, 2);
+
+    // Check that a memory budget is enforced before the circuits are synthesized.
+    let estimate = estimate_prover_memory(&pk, 1);
+    for (budget, fits) in [(estimate, true), (estimate - 1, false)].iter().copied() {
+        let options = ProverOptions {
+            max_memory_bytes: Some(budget),
+        };
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        let result = create_proof_with_options(
+            &params,
+            &pk,
+            &[circuit.clone()],
+            &[&[pubinputs.clone()]],
+            &mut transcript,
+            &options,
+        );
+        match result {
+            Ok(()) => assert!(fits),
+            Err(Error::MemoryBudgetExceeded {
+                estimate: reported,
+                budget: limit,
+            }) => assert!(!fits && reported == estimate && limit == budget),
+            Err(e) => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    // Check that a serialized witness can be proven without the circuit.
+    let witness = generate_witness(&params, &pk, &circuit, &[pubinputs.clone()])
+        .expect("witness generation should not fail");
+    let mut encoded = vec![];
+    witness.write(&mut encoded).unwrap();
+    let witness = Witness::read(&mut &encoded[..]).unwrap();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof_from_witness(
+        &params,
+        &pk,
+        slice::from_ref(&witness),
+        &[&[pubinputs.clone()]],
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof: Vec<u8> = transcript.finalize();
+    let msm = params.empty_msm();
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let guard = verify_proof(&params, pk.get_vk(), msm, &[&[pubinput]], &mut transcript).unwrap();
+    assert!(guard.use_challenges().eval());
+
+    // Check that a transcript chosen at runtime can be used through a trait object.
+    let mut proof = vec![];
     {
-        assert_eq!(
-            format!("{:#?}", pk.get_vk().pinned()),
-            r#####"PinnedVerificationKey {
-    base_modulus: "0x40000000000000000000000000000000224698fc0994a8dd8c46eb2100000001",
-    scalar_modulus: "0x40000000000000000000000000000000224698fc094cf91b992d30ed00000001",
-    domain: PinnedEvaluationDomain {
-        k: 5,
-        extended_k: 7,
-        omega: 0x0cc3380dc616f2e1daf29ad1560833ed3baea3393eceb7bc8fa36376929b78cc,
-    },
-    cs: PinnedConstraintSystem {
-        num_fixed_columns: 8,
-        num_advice_columns: 5,
-        num_instance_columns: 1,
-        gates: [
-            Sum(
-                Sum(
-                    Sum(
-                        Sum(
-                            Product(
-                                Advice(
-                                    0,
-                                ),
-                                Fixed(
-                                    3,
-                                ),
-                            ),
-                            Product(
-                                Advice(
-                                    1,
-                                ),
-                                Fixed(
-                                    4,
-                                ),
-                            ),
-                        ),
-                        Product(
-                            Product(
-                                Advice(
-                                    0,
-                                ),
-                                Advice(
-                                    1,
-                                ),
-                            ),
-                            Fixed(
-                                6,
-                            ),
-                        ),
-                    ),
-                    Scaled(
-                        Product(
-                            Advice(
-                                2,
-                            ),
-                            Fixed(
-                                5,
-                            ),
-                        ),
-                        0x40000000000000000000000000000000224698fc094cf91b992d30ed00000000,
-                    ),
-                ),
-                Product(
-                    Fixed(
-                        2,
-                    ),
-                    Product(
-                        Advice(
-                            3,
-                        ),
-                        Advice(
-                            4,
-                        ),
-                    ),
-                ),
-            ),
-            Product(
-                Fixed(
-                    7,
-                ),
-                Sum(
-                    Advice(
+        let mut transcript: Box<dyn TranscriptWrite<EqAffine, Challenge255<EqAffine>> + '_> =
+            Box::new(Blake2bWrite::<_, _, Challenge255<_>>::init(&mut proof));
+        create_proof(
+            &params,
+            &pk,
+            &[circuit.clone()],
+            &[&[pubinputs.clone()]],
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+    }
+    let msm = params.empty_msm();
+    let mut transcript: Box<dyn TranscriptRead<EqAffine, Challenge255<EqAffine>> + '_> =
+        Box::new(Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]));
+    let guard = verify_proof(&params, pk.get_vk(), msm, &[&[pubinput]], &mut transcript).unwrap();
+    assert!(guard.use_challenges().eval());
+
+    // Check that circuits of different types can be proven together, as long as they
+    // configure the same constraint system.
+    struct Wrapped(MyCircuit<Fp>);
+
+    impl Circuit<Fp> for Wrapped {
+        type Config = PlonkConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Wrapped(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> PlonkConfig {
+            MyCircuit::<Fp>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: PlonkConfig,
+            layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            self.0.synthesize(config, layouter)
+        }
+    }
+
+    struct Unrelated;
+
+    impl Circuit<Fp> for Unrelated {
+        type Config = ();
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Unrelated
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) {
+            meta.advice_column();
+        }
+
+        fn synthesize(&self, _: (), _: impl Layouter<Fp>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    let wrapped = Wrapped(circuit.clone());
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof_from_circuits(
+        &params,
+        &pk,
+        &[&circuit, &wrapped],
+        &[&[pubinputs.clone()], &[pubinputs.clone()]],
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof: Vec<u8> = transcript.finalize();
+    let msm = params.empty_msm();
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let guard = verify_proof(
+        &params,
+        pk.get_vk(),
+        msm,
+        &[&[pubinput], &[pubinput]],
+        &mut transcript,
+    )
+    .unwrap();
+    assert!(guard.use_challenges().eval());
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    assert!(matches!(
+        create_proof_from_circuits(
+            &params,
+            &pk,
+            &[&circuit, &Unrelated],
+            &[&[pubinputs.clone()], &[pubinputs.clone()]],
+            &mut transcript,
+        ),
+        Err(Error::IncompatibleParams)
+    ));
+
+    // Check that the verification key has not changed unexpectedly
+    halo2::assert_vk_unchanged!(
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/snapshots/plonk_api.vk.txt"
+        ),
+        pk.get_vk()
+    );
+}
+
+#[test]
+fn plonk_witness_cache() {
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct CacheConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        s_square: Selector,
+        s_public: Selector,
+    }
+
+    /// Squares a secret in one region, and exposes a public input in another.
+    #[derive(Clone, Default)]
+    struct CacheCircuit {
+        secret: Option<Fp>,
+        public: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for CacheCircuit {
+        type Config = CacheConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> CacheConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let p = meta.instance_column();
+            let s_square = meta.selector();
+            let s_public = meta.selector();
+
+            meta.create_gate("square", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let s = meta.query_selector(s_square);
+                vec![s * (a.clone() * a - b)]
+            });
+
+            meta.create_gate("public", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let p = meta.query_instance(p, Rotation::cur());
+                let s = meta.query_selector(s_public);
+                vec![s * (a - p)]
+            });
+
+            CacheConfig {
+                a,
+                b,
+                s_square,
+                s_public,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: CacheConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    config.s_square.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
                         0,
-                    ),
-                    Scaled(
-                        Instance(
-                            0,
-                        ),
-                        0x40000000000000000000000000000000224698fc094cf91b992d30ed00000000,
-                    ),
-                ),
-            ),
-        ],
-        advice_queries: [
-            (
-                Column {
-                    index: 1,
-                    column_type: Advice,
-                },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 2,
-                    column_type: Advice,
-                },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 3,
-                    column_type: Advice,
-                },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 4,
-                    column_type: Advice,
-                },
-                Rotation(
-                    1,
-                ),
-            ),
-            (
-                Column {
-                    index: 0,
-                    column_type: Advice,
-                },
-                Rotation(
-                    -1,
-                ),
-            ),
-        ],
-        instance_queries: [
-            (
-                Column {
-                    index: 0,
-                    column_type: Instance,
-                },
-                Rotation(
-                    0,
-                ),
-            ),
-        ],
-        fixed_queries: [
-            (
-                Column {
-                    index: 6,
-                    column_type: Fixed,
-                },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 7,
-                    column_type: Fixed,
-                },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 0,
-                    column_type: Fixed,
-                },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 2,
-                    column_type: Fixed,
+                        || self.secret.ok_or(Error::SynthesisError),
+                    )?;
+                    region.assign_advice(
+                        || "b",
+                        config.b,
+                        0,
+                        || self.secret.map(|a| a * a).ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
                 },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 3,
-                    column_type: Fixed,
+            )?;
+
+            layouter.assign_region(
+                || "public",
+                |mut region| {
+                    region.mark_instance_dependent()?;
+                    config.s_public.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.public.ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
                 },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 4,
-                    column_type: Fixed,
+            )
+        }
+    }
+
+    let params: Params<EqAffine> = Params::new(K);
+    let empty_circuit = CacheCircuit::default();
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let prove = |circuit: &CacheCircuit,
+                 public: u64,
+                 cache: &mut CachedWitness<Fp>|
+     -> Result<bool, Error> {
+        // The "public" region is laid out after the "square" region, on row 1.
+        let mut pubinputs = pk.get_vk().get_domain().empty_lagrange();
+        pubinputs[1] = Fp::from(public);
+        let pubinput = params
+            .commit_lagrange(&pubinputs, Blind::default())
+            .to_affine();
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof_with_cache(
+            &params,
+            &pk,
+            slice::from_ref(circuit),
+            &[&[pubinputs]],
+            &mut transcript,
+            slice::from_mut(cache),
+        )?;
+        let proof = transcript.finalize();
+        Ok(verify_single(&params, pk.get_vk(), &proof, &[pubinput]).is_ok())
+    };
+
+    // The first proof records the full witness.
+    let mut cache = CachedWitness::new();
+    assert!(!cache.is_recorded());
+    let circuit = CacheCircuit {
+        secret: Some(Fp::from(3u64)),
+        public: Some(Fp::from(5u64)),
+    };
+    assert!(prove(&circuit, 5, &mut cache).unwrap());
+    assert!(cache.is_recorded());
+    assert_eq!(cache.instance_dependent_regions(), 1);
+
+    // Later proofs only need the witness of the instance-dependent region.
+    let circuit = CacheCircuit {
+        secret: None,
+        public: Some(Fp::from(7u64)),
+    };
+    assert!(prove(&circuit, 7, &mut cache).unwrap());
+
+    // Without a recorded witness, the missing secret cannot be computed.
+    cache.clear();
+    assert!(matches!(
+        prove(&circuit, 7, &mut cache),
+        Err(Error::SynthesisError)
+    ));
+}
+
+#[test]
+fn plonk_instance_value_binding() {
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct PublicConfig {
+        a: Column<Advice>,
+        s: Selector,
+    }
+
+    /// Exposes a value as a public input, binding proofs to the instance values.
+    #[derive(Clone, Default)]
+    struct PublicCircuit {
+        value: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for PublicCircuit {
+        type Config = PublicConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> PublicConfig {
+            let a = meta.advice_column();
+            let p = meta.instance_column();
+            let s = meta.selector();
+            meta.bind_instance_values();
+
+            meta.create_gate("public", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let p = meta.query_instance(p, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a - p)]
+            });
+
+            PublicConfig { a, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: PublicConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "public",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.value.ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
                 },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 1,
-                    column_type: Fixed,
+            )
+        }
+    }
+
+    let params: Params<EqAffine> = Params::new(K);
+    let empty_circuit = PublicCircuit::default();
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let value = Fp::from(5u64);
+    let mut pubinputs = pk.get_vk().get_domain().empty_lagrange();
+    pubinputs[0] = value;
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        &[PublicCircuit { value: Some(value) }],
+        &[&[pubinputs.clone()]],
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    let verify = |values: &[Fp]| {
+        let msm = params.empty_msm();
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        verify_proof_with_instance_values(&params, pk.get_vk(), msm, &[&[values]], &mut transcript)
+            .map(|guard| guard.use_challenges().eval())
+    };
+
+    // The values may omit trailing zeros, but must match those the proof was made for.
+    assert_eq!(verify(&[value]), Ok(true));
+    assert_eq!(verify(&[value, Fp::zero()]), Ok(true));
+    assert!(!matches!(verify(&[Fp::one()]), Ok(true)));
+
+    // The commitments alone are not enough to verify the proof.
+    let pubinput = params
+        .commit_lagrange(&pubinputs, Blind::default())
+        .to_affine();
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    assert!(matches!(
+        verify_proof(
+            &params,
+            pk.get_vk(),
+            params.empty_msm(),
+            &[&[pubinput]],
+            &mut transcript
+        ),
+        Err(VerifyError::InstanceValuesRequired)
+    ));
+}
+
+#[test]
+fn plonk_unblinded_advice() {
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct CopyConfig {
+        public: Column<Advice>,
+        secret: Column<Advice>,
+        s: Selector,
+    }
+
+    /// Constrains a blinded column to equal an unblinded one.
+    #[derive(Clone, Default)]
+    struct CopyCircuit {
+        value: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for CopyCircuit {
+        type Config = CopyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> CopyConfig {
+            let public = meta.unblinded_advice_column();
+            let secret = meta.advice_column();
+            let s = meta.selector();
+            assert!(meta.is_unblinded(public));
+            assert!(!meta.is_unblinded(secret));
+
+            meta.create_gate("copy", |meta| {
+                let public = meta.query_advice(public, Rotation::cur());
+                let secret = meta.query_advice(secret, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (public - secret)]
+            });
+
+            CopyConfig { public, secret, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: CopyConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "copy",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    let value = || self.value.ok_or(Error::SynthesisError);
+                    region.assign_advice(|| "public", config.public, 0, value)?;
+                    region.assign_advice(|| "secret", config.secret, 0, value)?;
+                    Ok(())
                 },
-                Rotation(
-                    0,
-                ),
-            ),
-            (
-                Column {
-                    index: 5,
-                    column_type: Fixed,
+            )
+        }
+    }
+
+    let params: Params<EqAffine> = Params::new(K);
+    let empty_circuit = CopyCircuit::default();
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let value = Fp::from(5u64);
+    let prove = || {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[CopyCircuit { value: Some(value) }],
+            &[&[]],
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        transcript.finalize()
+    };
+    let (first, second) = (prove(), prove());
+
+    // The advice commitments are the first points of the proof. Only the commitment to
+    // the unblinded column is the same in both proofs.
+    let mut values = pk.get_vk().get_domain().empty_lagrange();
+    values[0] = value;
+    let commitment = params
+        .commit_lagrange(&values, Blind::default())
+        .to_affine();
+    assert_eq!(&first[..32], commitment.to_bytes().as_ref());
+    assert_eq!(first[..32], second[..32]);
+    assert_ne!(first[32..64], second[32..64]);
+
+    for proof in &[&first, &second] {
+        assert!(verify_single(&params, pk.get_vk(), proof, &[]).is_ok());
+    }
+
+    // The unblinded columns of the two proofs can be linked.
+    let mut cs = ConstraintSystem::default();
+    let config = CopyCircuit::configure(&mut cs);
+    let first = Proof::from_bytes(pk.get_vk(), 1, &first).unwrap();
+    let second = Proof::from_bytes(pk.get_vk(), 1, &second).unwrap();
+    let linked = |proof, column| LinkedColumn {
+        vk: pk.get_vk(),
+        proof,
+        instance: 0,
+        column,
+    };
+    assert_eq!(
+        check_link(
+            &linked(&first, config.public),
+            &linked(&second, config.public)
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_link(
+            &linked(&first, config.secret),
+            &linked(&second, config.secret)
+        ),
+        Err(LinkError::Blinded { column: 1 })
+    );
+    let other = {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[CopyCircuit {
+                value: Some(Fp::from(6u64)),
+            }],
+            &[&[]],
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        Proof::from_bytes(pk.get_vk(), 1, &transcript.finalize()).unwrap()
+    };
+    assert_eq!(
+        check_link(
+            &linked(&first, config.public),
+            &linked(&other, config.public)
+        ),
+        Err(LinkError::Mismatch)
+    );
+}
+
+#[test]
+fn plonk_constant_instance() {
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct ConstantConfig {
+        a: Column<Advice>,
+        s: Selector,
+    }
+
+    /// Checks a witness value against a parameter that is the same in every proof.
+    #[derive(Clone, Default)]
+    struct ConstantCircuit {
+        value: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for ConstantCircuit {
+        type Config = ConstantConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> ConstantConfig {
+            let a = meta.advice_column();
+            let p = meta.constant_instance_column(vec![Fp::from(7u64)]);
+            let s = meta.selector();
+
+            meta.create_gate("parameter", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let p = meta.query_instance(p, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a - p)]
+            });
+
+            ConstantConfig { a, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: ConstantConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "parameter",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
+                        0,
+                        || self.value.ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
                 },
-                Rotation(
-                    0,
-                ),
-            ),
-        ],
-        permutations: [
-            Argument {
-                columns: [
-                    Column {
-                        index: 1,
-                        column_type: Advice,
-                    },
-                    Column {
-                        index: 2,
-                        column_type: Advice,
-                    },
-                    Column {
-                        index: 3,
-                        column_type: Advice,
-                    },
-                ],
-            },
-            Argument {
-                columns: [
-                    Column {
-                        index: 1,
-                        column_type: Advice,
-                    },
-                    Column {
-                        index: 2,
-                        column_type: Advice,
-                    },
-                    Column {
-                        index: 3,
-                        column_type: Advice,
-                    },
-                ],
-            },
-        ],
-        lookups: [
-            Argument {
-                input_expressions: [
-                    Advice(
+            )
+        }
+    }
+
+    let params: Params<EqAffine> = Params::new(K);
+    let empty_circuit = ConstantCircuit::default();
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+
+    // The verifying key holds the commitment to the parameter, and reading it back
+    // recomputes the same commitment.
+    let mut parameter = vk.get_domain().empty_lagrange();
+    parameter[0] = Fp::from(7u64);
+    let commitment = params
+        .commit_lagrange(&parameter, Blind::default())
+        .to_affine();
+    assert_eq!(vk.constant_instance_commitment(0), Some(commitment));
+    let mut vk_bytes = vec![];
+    vk.write(&mut vk_bytes).unwrap();
+    let read_vk =
+        VerifyingKey::<EqAffine>::read::<_, ConstantCircuit>(&mut &vk_bytes[..], &params).unwrap();
+    assert_eq!(read_vk.constant_instance_commitment(0), Some(commitment));
+
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+    let circuit = ConstantCircuit {
+        value: Some(Fp::from(7u64)),
+    };
+
+    // The instance values must match the configured parameter.
+    let mut wrong = parameter.clone();
+    wrong[1] = Fp::one();
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    assert!(matches!(
+        create_proof(
+            &params,
+            &pk,
+            &[circuit.clone()],
+            &[&[wrong]],
+            &mut transcript
+        ),
+        Err(Error::IncompatibleParams)
+    ));
+
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(&params, &pk, &[circuit], &[&[parameter]], &mut transcript)
+        .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    // The verifier uses the commitment in the verifying key, whatever it is given.
+    let placeholder = params
+        .commit_lagrange(&pk.get_vk().get_domain().empty_lagrange(), Blind::default())
+        .to_affine();
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let guard = verify_proof(
+        &params,
+        pk.get_vk(),
+        params.empty_msm(),
+        &[&[placeholder]],
+        &mut transcript,
+    )
+    .expect("verification should not fail");
+    assert!(guard.use_challenges().eval());
+
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let guard = verify_proof_with_instance_values(
+        &params,
+        pk.get_vk(),
+        params.empty_msm(),
+        &[&[&[]]],
+        &mut transcript,
+    )
+    .expect("verification should not fail");
+    assert!(guard.use_challenges().eval());
+
+    // The challenges can be recomputed from the proof, in the order that they are
+    // squeezed.
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    let challenges = proof_challenges(&params, pk.get_vk(), &[&[placeholder]], &mut transcript)
+        .expect("challenges should be recomputed");
+    let labels: Vec<_> = challenges.all().iter().map(|(label, _)| *label).collect();
+    assert_eq!(&labels[..5], &["theta", "beta", "gamma", "y", "x"]);
+    assert_eq!(challenges.x().value(), challenges.all()[4].1);
+}
+
+#[test]
+fn plonk_without_arguments() {
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct SquareConfig {
+        a: Column<Advice>,
+        s: Selector,
+    }
+
+    /// Proves knowledge of a square root of a public input, with a single gate and no
+    /// lookup or permutation arguments.
+    #[derive(Clone, Default)]
+    struct SquareCircuit {
+        root: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for SquareCircuit {
+        type Config = SquareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> SquareConfig {
+            let a = meta.advice_column();
+            let p = meta.instance_column();
+            let s = meta.selector();
+
+            meta.create_gate("square", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let p = meta.query_instance(p, Rotation::cur());
+                let s = meta.query_selector(s);
+                vec![s * (a.clone() * a - p)]
+            });
+
+            SquareConfig { a, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: SquareConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "square",
+                |mut region| {
+                    config.s.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
                         0,
-                    ),
-                ],
-                table_expressions: [
-                    Fixed(
+                        || self.root.ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    let params: Params<EqAffine> = Params::new(K);
+    let empty_circuit = SquareCircuit::default();
+    let vk = keygen_vk(&params, &empty_circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let root = Fp::from(3u64);
+    let mut pubinputs = pk.get_vk().get_domain().empty_lagrange();
+    pubinputs[0] = root * root;
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        &[SquareCircuit { root: Some(root) }],
+        &[&[pubinputs.clone()]],
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    let commit = |pubinputs: &_| {
+        params
+            .commit_lagrange(pubinputs, Blind::default())
+            .to_affine()
+    };
+    assert!(verify_single(&params, pk.get_vk(), &proof, &[commit(&pubinputs)]).is_ok());
+
+    pubinputs[0] = Fp::from(10u64);
+    assert!(verify_single(&params, pk.get_vk(), &proof, &[commit(&pubinputs)]).is_err());
+}
+
+#[test]
+fn plonk_fixed_column_compression() {
+    const K: u32 = 4;
+
+    #[derive(Clone)]
+    struct TwinConfig {
+        a: Column<Advice>,
+        square: Selector,
+        root: Selector,
+    }
+
+    /// Proves that the square of a public input is 9, with two selectors that are
+    /// enabled on the same rows.
+    #[derive(Clone, Default)]
+    struct TwinCircuit<const COMPRESS: bool> {
+        root: Option<Fp>,
+    }
+
+    impl<const COMPRESS: bool> Circuit<Fp> for TwinCircuit<COMPRESS> {
+        type Config = TwinConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> TwinConfig {
+            if COMPRESS {
+                meta.enable_fixed_column_compression();
+            }
+
+            let a = meta.advice_column();
+            let p = meta.instance_column();
+            let square = meta.selector();
+            let root = meta.selector();
+
+            meta.create_gate("square", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let square = meta.query_selector(square);
+                vec![square * (a.clone() * a - Expression::Constant(Fp::from(9u64)))]
+            });
+            meta.create_gate("root", |meta| {
+                let a = meta.query_advice(a, Rotation::cur());
+                let p = meta.query_instance(p, Rotation::cur());
+                let root = meta.query_selector(root);
+                vec![root * (a - p)]
+            });
+
+            TwinConfig { a, square, root }
+        }
+
+        fn synthesize(
+            &self,
+            config: TwinConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "root",
+                |mut region| {
+                    config.square.enable(&mut region, 0)?;
+                    config.root.enable(&mut region, 0)?;
+                    region.assign_advice(
+                        || "a",
+                        config.a,
                         0,
-                    ),
-                ],
-            },
-            Argument {
-                input_expressions: [
-                    Product(
-                        Advice(
-                            0,
-                        ),
-                        Advice(
-                            1,
-                        ),
-                    ),
-                ],
-                table_expressions: [
-                    Product(
-                        Fixed(
-                            0,
-                        ),
-                        Fixed(
-                            1,
-                        ),
-                    ),
-                ],
-            },
-        ],
-    },
-    fixed_commitments: [
-        (0x2bbc94ef7b22aebef24f9a4b0cc1831882548b605171366017d45c3e6fd92075, 0x082b801a6e176239943bfb759fb02138f47a5c8cc4aa7fa0af559fde4e3abd97),
-        (0x2bf5082b105b2156ed0e9c5b8e42bf2a240b058f74a464d080e9585274dd1e84, 0x222ad83cee7777e7a160585e212140e5e770dd8d1df788d869b5ee483a5864fb),
-        (0x374a656456a0aae7429b23336f825752b575dd5a44290ff614946ee59d6a20c0, 0x054491e187e6e3460e7601fb54ae10836d34d420026f96316f0c5c62f86db9b8),
-        (0x374a656456a0aae7429b23336f825752b575dd5a44290ff614946ee59d6a20c0, 0x054491e187e6e3460e7601fb54ae10836d34d420026f96316f0c5c62f86db9b8),
-        (0x02e62cd68370b13711139a08cbcdd889e800a272b9ea10acc90880fff9d89199, 0x1a96c468cb0ce77065d3a58f1e55fea9b72d15e44c01bba1e110bd0cbc6e9bc6),
-        (0x224ef42758215157d3ee48fb8d769da5bddd35e5929a90a4a89736f5c4b5ae9b, 0x11bc3a1e08eb320cde764f1492ecef956d71e996e2165f7a9a30ad2febb511c1),
-        (0x3c145eb1e4f1e49d9eed351a4e2d9f3deed13bc5ba028d3b425084d606418cc8, 0x045d846e7df4e563ce57cd5483d17bad87f0345e18409bf15abc3d71953ae71c),
-        (0x27b1cd6c0408a2fe7a764e6ac7abda4f6c7e7a4b3f7375532fe11f3af579de64, 0x19dcda088f6c8ad67408650554cfdd5c8c2e5385cf59c662554c837cf3f42c2d),
-    ],
-    permutations: [
-        VerifyingKey {
-            commitments: [
-                (0x1347b4b385837977a96b87f199c6a9a81520015539d1e8fa79429bb4ca229a00, 0x2168e404cabef513654d6ff516cde73f0ba87e3dc84e4b940ed675b5f66f3884),
-                (0x0e6d69cd2455ec43be640f6397ed65c9e51b1d8c0fd2216339314ff37ade122a, 0x222ed6dc8cfc9ea26dcc10b9d4add791ada60f2b5a63ee1e4635f88aa0c96654),
-                (0x13c447846f48c41a5e0675ccf88ebc0cdef2c96c51446d037acb866d24255785, 0x1f0b5414fc5e8219dbfab996eed6129d831488b2386a8b1a63663938903bd63a),
-            ],
-        },
-        VerifyingKey {
-            commitments: [
-                (0x1347b4b385837977a96b87f199c6a9a81520015539d1e8fa79429bb4ca229a00, 0x2168e404cabef513654d6ff516cde73f0ba87e3dc84e4b940ed675b5f66f3884),
-                (0x0e6d69cd2455ec43be640f6397ed65c9e51b1d8c0fd2216339314ff37ade122a, 0x222ed6dc8cfc9ea26dcc10b9d4add791ada60f2b5a63ee1e4635f88aa0c96654),
-                (0x13c447846f48c41a5e0675ccf88ebc0cdef2c96c51446d037acb866d24255785, 0x1f0b5414fc5e8219dbfab996eed6129d831488b2386a8b1a63663938903bd63a),
-            ],
-        },
-    ],
-}"#####
-        );
+                        || self.root.ok_or(Error::SynthesisError),
+                    )?;
+                    Ok(())
+                },
+            )
+        }
     }
+
+    let params: Params<EqAffine> = Params::new(K);
+    let write_vk = |vk: &VerifyingKey<EqAffine>| {
+        let mut bytes = vec![];
+        vk.write(&mut bytes).unwrap();
+        bytes
+    };
+
+    // The selectors are merged, so the encoding of the verifying key records a single
+    // commitment after the column map.
+    let vk = keygen_vk(&params, &TwinCircuit::<false>::default()).unwrap();
+    let uncompressed = write_vk(&vk);
+    let vk = keygen_vk(&params, &TwinCircuit::<true>::default()).unwrap();
+    let compressed = write_vk(&vk);
+    assert_eq!(compressed.len() + 32, uncompressed.len() + 2 * 4);
+
+    // Reading the verifying key merges the columns again.
+    let read_vk =
+        VerifyingKey::<EqAffine>::read::<_, TwinCircuit<true>>(&mut &compressed[..], &params)
+            .unwrap();
+    assert_eq!(
+        format!("{:?}", read_vk.pinned()),
+        format!("{:?}", vk.pinned())
+    );
+    assert!(
+        VerifyingKey::<EqAffine>::read::<_, TwinCircuit<false>>(&mut &compressed[..], &params)
+            .is_err()
+    );
+
+    let empty_circuit = TwinCircuit::<true>::default();
+    let pk = keygen_pk(&params, vk, &empty_circuit).expect("keygen_pk should not fail");
+
+    let root = Fp::from(3u64);
+    let mut pubinputs = pk.get_vk().get_domain().empty_lagrange();
+    pubinputs[0] = root;
+    let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+    create_proof(
+        &params,
+        &pk,
+        &[TwinCircuit::<true> { root: Some(root) }],
+        &[&[pubinputs.clone()]],
+        &mut transcript,
+    )
+    .expect("proof generation should not fail");
+    let proof = transcript.finalize();
+
+    let commit = |pubinputs: &_| {
+        params
+            .commit_lagrange(pubinputs, Blind::default())
+            .to_affine()
+    };
+    assert!(verify_single(&params, &read_vk, &proof, &[commit(&pubinputs)]).is_ok());
+
+    pubinputs[0] = -root;
+    assert!(verify_single(&params, &read_vk, &proof, &[commit(&pubinputs)]).is_err());
 }